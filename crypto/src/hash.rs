@@ -988,6 +988,37 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn test_ed25519_signatures_verification() {
+        use sodiumoxide::crypto::sign;
+
+        let items = (0..4u8)
+            .map(|i| {
+                let (pk, sk) = sign::gen_keypair();
+                let msg = vec![i; 32];
+                let sig = sign::sign_detached(&msg, &sk);
+                (
+                    PublicKeyEd25519::try_from_bytes(&pk.0).unwrap(),
+                    Signature(sig.0.to_vec()),
+                    msg,
+                )
+            })
+            .collect::<Vec<_>>();
+        let verify = |items: &[(PublicKeyEd25519, Signature, Vec<u8>)]| {
+            items
+                .iter()
+                .map(|(pk, sig, msg)| pk.verify_signature(sig, msg).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(verify(&items), vec![true; 4]);
+
+        // signature of the other message
+        let mut items = items;
+        items[2].2 = vec![0xff; 32];
+        assert_eq!(verify(&items), vec![true, true, false, true]);
+    }
+
     #[test]
     fn test_secp256k1_signature_verification() {
         let pk = PublicKeySecp256k1::from_base58_check(
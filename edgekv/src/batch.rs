@@ -0,0 +1,41 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchOperation {
+    Put(Vec<u8>, Vec<u8>),
+    Delete(Vec<u8>),
+}
+
+/// Set of puts and deletes applied atomically by [`crate::edgekv::EdgeKV::write_batch`],
+/// after a crash, either all the operations of the batch are recovered, or none of them.
+#[derive(Debug, Clone, Default)]
+pub struct WriteBatch {
+    operations: Vec<BatchOperation>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.operations.push(BatchOperation::Put(key, value));
+    }
+
+    pub fn delete(&mut self, key: Vec<u8>) {
+        self.operations.push(BatchOperation::Delete(key));
+    }
+
+    pub fn operations(&self) -> &[BatchOperation] {
+        &self.operations
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
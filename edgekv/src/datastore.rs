@@ -3,19 +3,21 @@
 
 #![allow(clippy::ptr_arg)]
 
+use crate::batch::{BatchOperation, WriteBatch};
 use crate::datastore::DataIndex::Persisted;
 use crate::errors::EdgeKVError;
 use crate::file_ops::{
-    create_new_file_pair, fetch_file_pairs, get_lock_file, ActiveFilePair, FilePair, Index,
+    create_compaction_file_pair, create_new_file_pair, fetch_file_pairs, get_lock_file,
+    recover_file_pairs, ActiveFilePair, FilePair, Index,
 };
-use crate::schema::{DataEntry, Decoder, Encoder};
+use crate::schema::{DataEntry, Decoder, Encoder, DATA_ENTRY_HEADER_SIZE};
 use fs2::FileExt;
 
 use std::collections::{BTreeMap, HashMap};
 use std::fs::{File, OpenOptions};
-use std::ops::{Add, RangeBounds};
+use std::ops::{Add, Bound, RangeBounds};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::Result;
 use std::io::{BufReader, Write};
@@ -58,63 +60,138 @@ impl KeyDirEntry {
             + std::mem::size_of_val(&self.data_entry_position)
             + self.file_id.len()
     }
+
+    /// Size of the data entry in the data file
+    pub fn data_entry_size(&self) -> u64 {
+        DATA_ENTRY_HEADER_SIZE + self.key_size + self.value_size
+    }
 }
 
 pub type IVec = Arc<Vec<u8>>;
 
+type Keys = BTreeMap<IVec, DataIndex>;
+
 pub struct KeysDir {
-    keys: RwLock<BTreeMap<IVec, DataIndex>>,
+    /// Shared with the snapshots, copied on the first write after a snapshot
+    keys: RwLock<Arc<Keys>>,
+    /// Bytes of the data entries referenced by the keys dir, per file
+    live_bytes: RwLock<HashMap<String, u64>>,
+}
+
+/// Moves the size of the replaced entry from the live bytes of its file to the new entry file
+fn update_live_bytes(
+    live_bytes: &mut HashMap<String, u64>,
+    old: Option<DataIndex>,
+    new: Option<&KeyDirEntry>,
+) {
+    if let Some(Persisted(old)) = old {
+        if let Some(bytes) = live_bytes.get_mut(&old.file_id) {
+            *bytes = bytes.saturating_sub(old.data_entry_size());
+        }
+    }
+    if let Some(new) = new {
+        *live_bytes.entry(new.file_id.clone()).or_default() += new.data_entry_size();
+    }
 }
 
 impl KeysDir {
-    pub fn insert(&self, key: IVec, value: KeyDirEntry) -> Result<()> {
-        let mut keys_dir_writer = self
-            .keys
+    fn keys_writer(&self) -> Result<RwLockWriteGuard<Arc<Keys>>> {
+        self.keys
             .write()
-            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))
+    }
+
+    fn live_bytes_writer(&self) -> Result<RwLockWriteGuard<HashMap<String, u64>>> {
+        self.live_bytes
+            .write()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))
+    }
+
+    pub fn insert(&self, key: IVec, value: KeyDirEntry) -> Result<()> {
+        let mut keys_dir_writer = self.keys_writer()?;
+        let mut live_bytes = self.live_bytes_writer()?;
 
+        update_live_bytes(&mut live_bytes, None, Some(&value));
         let index = DataIndex::Persisted(value);
-        keys_dir_writer.insert(key, index);
+        let old = Arc::make_mut(&mut keys_dir_writer).insert(key, index);
+        update_live_bytes(&mut live_bytes, old, None);
         Ok(())
     }
 
+    /// Replaces the entry of the key, only if it's still `expected`.
+    /// Returns false when the key has been modified or removed.
+    pub fn compare_and_insert(
+        &self,
+        key: IVec,
+        expected: &KeyDirEntry,
+        value: KeyDirEntry,
+    ) -> Result<bool> {
+        let mut keys_dir_writer = self.keys_writer()?;
+
+        match keys_dir_writer.get(&key) {
+            Some(Persisted(current))
+                if current.file_id == expected.file_id
+                    && current.data_entry_position == expected.data_entry_position => {}
+            _ => return Ok(false),
+        }
+
+        let mut live_bytes = self.live_bytes_writer()?;
+        update_live_bytes(&mut live_bytes, None, Some(&value));
+        let old = Arc::make_mut(&mut keys_dir_writer).insert(key, DataIndex::Persisted(value));
+        update_live_bytes(&mut live_bytes, old, None);
+        Ok(true)
+    }
+
     pub fn insert_bulk(&self, bulk: BTreeMap<Vec<u8>, KeyDirEntry>) -> Result<()> {
-        let mut keys_dir_writer = self
-            .keys
-            .write()
-            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
-        keys_dir_writer.extend(
-            bulk.iter()
-                .map(|(k, v)| (Arc::new(k.clone()), DataIndex::Persisted(v.clone()))),
-        );
+        let mut keys_dir_writer = self.keys_writer()?;
+        let keys = Arc::make_mut(&mut keys_dir_writer);
+        let mut live_bytes = self.live_bytes_writer()?;
+        for (key, value) in bulk {
+            update_live_bytes(&mut live_bytes, None, Some(&value));
+            let old = keys.insert(Arc::new(key), DataIndex::Persisted(value));
+            update_live_bytes(&mut live_bytes, old, None);
+        }
         Ok(())
     }
 
     pub fn partial_insert(&self, key: IVec) -> Result<()> {
-        let mut keys_dir_writer = self
-            .keys
-            .write()
-            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+        let mut keys_dir_writer = self.keys_writer()?;
         let index = DataIndex::InBuffer;
-        keys_dir_writer.insert(key, index);
+        let old = Arc::make_mut(&mut keys_dir_writer).insert(key, index);
+        update_live_bytes(&mut *self.live_bytes_writer()?, old, None);
         Ok(())
     }
 
     pub fn remove(&self, key: &Vec<u8>) -> Result<()> {
-        let mut keys_dir_writer = self
-            .keys
-            .write()
-            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
-        keys_dir_writer.remove(key);
+        let mut keys_dir_writer = self.keys_writer()?;
+        if !keys_dir_writer.contains_key(key) {
+            return Ok(());
+        }
+        let old = Arc::make_mut(&mut keys_dir_writer).remove(key);
+        update_live_bytes(&mut *self.live_bytes_writer()?, old, None);
         Ok(())
     }
 
     pub fn clear(&self) -> Result<()> {
-        let mut keys_dir_writer = self
-            .keys
-            .write()
+        let mut keys_dir_writer = self.keys_writer()?;
+        // The snapshots keep the previous keys
+        *keys_dir_writer = Default::default();
+        self.live_bytes_writer()?.clear();
+        Ok(())
+    }
+
+    /// Bytes of the data entries referenced by the keys dir, per file
+    pub fn live_bytes(&self) -> Result<HashMap<String, u64>> {
+        let live_bytes = self
+            .live_bytes
+            .read()
             .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
-        keys_dir_writer.clear();
+        Ok(live_bytes.clone())
+    }
+
+    /// Forgets the live bytes of a file removed by the compaction
+    pub fn remove_file(&self, file_id: &str) -> Result<()> {
+        self.live_bytes_writer()?.remove(file_id);
         Ok(())
     }
 
@@ -188,6 +265,15 @@ impl KeysDir {
         keys_dir_reader.len()
     }
 
+    /// Keys dir shared with the snapshot, it is not copied until the next write
+    fn snapshot(&self) -> Result<Arc<Keys>> {
+        let keys_dir_reader = self
+            .keys
+            .read()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+        Ok(Arc::clone(&keys_dir_reader))
+    }
+
     pub fn contains(&self, key: &Vec<u8>) -> Result<bool> {
         let keys_dir_reader = self
             .keys
@@ -201,6 +287,7 @@ impl KeysDir {
     pub fn new(file_pairs: &BTreeMap<String, FilePair>) -> Result<Self> {
         let keys_dir = Self {
             keys: Default::default(),
+            live_bytes: Default::default(),
         };
         for fp in file_pairs.values() {
             fp.fetch_hint_entries(&keys_dir)?;
@@ -244,6 +331,160 @@ impl IndexDir {
         indexes.insert(file_pair.file_id(), file_pair.to_index()?);
         Ok(())
     }
+
+    pub fn remove(&self, file_id: &str) -> Result<()> {
+        let mut indexes = self
+            .indexes
+            .write()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+        indexes.remove(file_id);
+        Ok(())
+    }
+}
+
+/// Files referenced by the living snapshots, their removal by the compaction is deferred
+/// until the last snapshot referencing them is dropped.
+#[derive(Default)]
+pub struct FilePins {
+    pins: HashMap<String, usize>,
+    pending_removals: HashMap<String, Vec<PathBuf>>,
+}
+
+impl FilePins {
+    fn pin(&mut self, file_ids: &[String]) {
+        for file_id in file_ids {
+            *self.pins.entry(file_id.clone()).or_default() += 1;
+        }
+    }
+
+    fn unpin(&mut self, file_ids: &[String]) -> Result<()> {
+        let mut removals = Vec::new();
+        for file_id in file_ids {
+            if let Some(count) = self.pins.get_mut(file_id) {
+                *count -= 1;
+                if *count == 0 {
+                    self.pins.remove(file_id);
+                    if let Some(paths) = self.pending_removals.remove(file_id) {
+                        removals.extend(paths);
+                    }
+                }
+            }
+        }
+        fs_extra::remove_items(&removals)?;
+        Ok(())
+    }
+
+    /// Removes the files, or defers their removal if a snapshot references them
+    fn remove(&mut self, file_id: String, paths: Vec<PathBuf>) -> Result<()> {
+        if self.pins.contains_key(&file_id) {
+            self.pending_removals.insert(file_id, paths);
+        } else {
+            fs_extra::remove_items(&paths)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStats {
+    pub file_id: String,
+    pub total_bytes: u64,
+    pub live_bytes: u64,
+}
+
+impl FileStats {
+    pub fn dead_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.live_bytes)
+    }
+
+    pub fn dead_ratio(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.dead_bytes() as f64 / self.total_bytes as f64
+    }
+}
+
+type Buffer = HashMap<IVec, IVec>;
+
+/// Point-in-time view of the store, see [`DataStore::snapshot`]
+pub struct DataStoreSnapshot {
+    keys: Arc<Keys>,
+    buffer: Arc<Buffer>,
+    indexes: BTreeMap<String, Index>,
+    file_ids: Vec<String>,
+    file_pins: Arc<Mutex<FilePins>>,
+}
+
+/// `BTreeMap::range` panics on such ranges
+fn is_empty_range(start: Bound<&Vec<u8>>, end: Bound<&Vec<u8>>) -> bool {
+    match (start, end) {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end))
+        | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        _ => false,
+    }
+}
+
+impl DataStoreSnapshot {
+    pub fn get(&self, key: &Vec<u8>) -> Result<Option<Vec<u8>>> {
+        match self.keys.get(key) {
+            None => Ok(None),
+            // The buffer and the keys dir are captured together, a buffered key has its value
+            Some(DataIndex::InBuffer) => match self.buffer.get(key) {
+                Some(value) => Ok(Some(value.to_vec())),
+                None => Err(EdgeKVError::CorruptData),
+            },
+            Some(DataIndex::Persisted(entry)) => {
+                let index = self
+                    .indexes
+                    .get(&entry.file_id)
+                    .ok_or(EdgeKVError::CorruptData)?;
+                let data_entry = index.read(entry.data_entry_position, entry.size())?;
+                Ok(Some(data_entry.value()))
+            }
+        }
+    }
+
+    pub fn contains(&self, key: &Vec<u8>) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    /// First key of the range, in ascending order
+    pub fn first_key(&self, start: Bound<&Vec<u8>>, end: Bound<&Vec<u8>>) -> Option<IVec> {
+        if is_empty_range(start, end) {
+            return None;
+        }
+        self.keys
+            .range::<Vec<u8>, _>((start, end))
+            .next()
+            .map(|(k, _)| k.clone())
+    }
+
+    /// Last key of the range, in ascending order
+    pub fn last_key(&self, start: Bound<&Vec<u8>>, end: Bound<&Vec<u8>>) -> Option<IVec> {
+        if is_empty_range(start, end) {
+            return None;
+        }
+        self.keys
+            .range::<Vec<u8>, _>((start, end))
+            .next_back()
+            .map(|(k, _)| k.clone())
+    }
+
+    pub fn size(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl Drop for DataStoreSnapshot {
+    fn drop(&mut self) {
+        if let Ok(mut file_pins) = self.file_pins.lock() {
+            // TODO - TE-721: handle this error
+            let _ = file_pins.unpin(&self.file_ids);
+        }
+    }
 }
 
 pub struct DataStore {
@@ -252,10 +493,12 @@ pub struct DataStore {
     active_file: RwLock<ActiveFilePair>,
     keys_dir: KeysDir,
     index_dir: IndexDir,
-    buffer: RwLock<HashMap<Arc<Vec<u8>>, Arc<Vec<u8>>>>,
+    /// Shared with the snapshots, copied on the first write after a snapshot
+    buffer: RwLock<Arc<Buffer>>,
     double_buffer: HashMap<Vec<u8>, DataEntry>,
     buffer_size: RwLock<usize>,
     cache: RwLock<LruCache<Arc<Vec<u8>>, Arc<Vec<u8>>>>,
+    file_pins: Arc<Mutex<FilePins>>,
 }
 
 pub fn fetch_double_buffer_file(
@@ -278,12 +521,17 @@ pub fn fetch_double_buffer_file(
 impl DataStore {
     pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
         let lock_file = get_lock_file(dir.as_ref())?;
+        lock_file
+            .lock_exclusive()
+            .map_err(|_| EdgeKVError::LockFailed(String::from(dir.as_ref().to_string_lossy())))?;
+        // Entries torn by a crash are removed before loading the hints
+        recover_file_pairs(dir.as_ref())?;
         let active_file_pair = create_new_file_pair(dir.as_ref())?;
         let (files_dir, buffer_files) = fetch_file_pairs(dir.as_ref())?;
         let double_buffer = fetch_double_buffer_file(&buffer_files)?;
         let keys_dir = KeysDir::new(&files_dir)?;
         let index_dir = IndexDir::new(files_dir)?;
-        let mut instance = Self {
+        let instance = Self {
             lock_file,
             dir: dir.as_ref().to_path_buf(),
             active_file: RwLock::new(ActiveFilePair::from(active_file_pair)?),
@@ -293,18 +541,11 @@ impl DataStore {
             double_buffer,
             buffer_size: RwLock::new(0),
             cache: RwLock::new(LruCache::new(24_000)),
+            file_pins: Arc::new(Mutex::new(FilePins::default())),
         };
-        instance.lock()?;
         Ok(instance)
     }
 
-    fn lock(&mut self) -> Result<()> {
-        self.lock_file
-            .lock_exclusive()
-            .map_err(|_| EdgeKVError::LockFailed(String::from(self.dir.to_string_lossy())))?;
-        Ok(())
-    }
-
     pub fn keys_dir(&self) -> &KeysDir {
         &self.keys_dir
     }
@@ -338,7 +579,7 @@ impl DataStore {
             .write()
             .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
 
-        buffer.insert(key.clone(), value.clone());
+        Arc::make_mut(&mut buffer).insert(key.clone(), value.clone());
         self.keys_dir.partial_insert(key.clone())?;
         cache.insert(key, value);
         Ok(())
@@ -399,13 +640,59 @@ impl DataStore {
             .read()
             .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
 
-        buffer.remove(key);
+        if buffer.contains_key(key) {
+            Arc::make_mut(&mut buffer).remove(key);
+        }
         active_file.remove(key.to_vec())?;
         self.keys_dir.remove(key)?;
         cache.remove(key);
         Ok(())
     }
 
+    /// Writes the batch directly into the active file pair, bypassing the buffer
+    pub fn write_batch(&self, batch: &WriteBatch) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let active_file = self
+            .active_file
+            .write()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+        let mut buffer = self
+            .buffer
+            .write()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+        let mut cache = self
+            .cache
+            .write()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+
+        let key_dir_entries = active_file.write_batch(batch)?;
+
+        for (operation, key_dir_entry) in batch.operations().iter().zip(key_dir_entries) {
+            match (operation, key_dir_entry) {
+                (BatchOperation::Put(key, value), Some(key_dir_entry)) => {
+                    // The buffered value is older than the batch
+                    if buffer.contains_key(key) {
+                        Arc::make_mut(&mut buffer).remove(key);
+                    }
+                    let key = Arc::new(key.clone());
+                    self.keys_dir.insert(key.clone(), key_dir_entry)?;
+                    cache.insert(key, Arc::new(value.clone()));
+                }
+                (BatchOperation::Delete(key), _) | (BatchOperation::Put(key, _), None) => {
+                    if buffer.contains_key(key) {
+                        Arc::make_mut(&mut buffer).remove(key);
+                    }
+                    self.keys_dir.remove(key)?;
+                    cache.remove(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn contains(&self, key: &Vec<u8>) -> Result<bool> {
         let buffer = self
             .buffer
@@ -434,7 +721,7 @@ impl DataStore {
             .buffer
             .write()
             .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
-        buffer.clear();
+        *buffer = Default::default();
         Ok(())
     }
 
@@ -442,6 +729,41 @@ impl DataStore {
         self.keys_dir.keys()
     }
 
+    fn file_pins(&self) -> Result<MutexGuard<FilePins>> {
+        self.file_pins
+            .lock()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))
+    }
+
+    /// Captures the keys and the location of their values, they are shared with the store
+    /// until its next write. The data files are pinned so the compaction doesn't remove them
+    /// while the snapshot is alive.
+    pub fn snapshot(&self) -> Result<DataStoreSnapshot> {
+        // The compaction needs the pins to remove files, holding them
+        // makes sure that all the captured entries are readable
+        let mut file_pins = self.file_pins()?;
+
+        // Captured together, the buffered keys are moved to the keys dir under the buffer lock
+        let buffer = self
+            .buffer
+            .read()
+            .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
+        let keys = self.keys_dir.snapshot()?;
+        let buffer = Arc::clone(&buffer);
+
+        let indexes = self.index_dir.indexes()?.clone();
+        let file_ids: Vec<String> = indexes.keys().cloned().collect();
+        file_pins.pin(&file_ids);
+
+        Ok(DataStoreSnapshot {
+            keys,
+            buffer,
+            indexes,
+            file_ids,
+            file_pins: self.file_pins.clone(),
+        })
+    }
+
     pub fn try_split(&self, active_file: &mut ActiveFilePair) -> Result<()> {
         let active_file_pair = create_new_file_pair(self.dir.as_path())?;
         *active_file = ActiveFilePair::from(active_file_pair)?;
@@ -485,33 +807,142 @@ impl DataStore {
         self.keys_dir.prefix(prefix)
     }
 
-    pub fn merge(&self) -> Result<()> {
+    fn active_file_id(&self) -> Result<String> {
         let active_file = self
             .active_file
             .read()
             .map_err(|e| EdgeKVError::RWLockPoisonError(format!("{}", e)))?;
-        let merged_file_pair = ActiveFilePair::from(create_new_file_pair(self.dir.as_path())?)?;
-        let mut mark_for_removal = Vec::new();
+        Ok(active_file.file_id())
+    }
 
-        for (_, index) in self.index_dir.indexes()?.iter() {
-            if index.file_id() == active_file.file_id() {
-                continue;
-            }
-            let hints = index.get_hints()?;
-            for hint in hints {
+    /// Live and dead bytes of the data files
+    pub fn file_stats(&self) -> Result<Vec<FileStats>> {
+        let live_bytes = self.keys_dir.live_bytes()?;
+        let indexes = self.index_dir.indexes()?;
+        Ok(indexes
+            .values()
+            .map(|index| FileStats {
+                file_id: index.file_id(),
+                total_bytes: std::fs::metadata(index.data_file_path()).map_or(0, |m| m.len()),
+                live_bytes: live_bytes.get(&index.file_id()).copied().unwrap_or(0),
+            })
+            .collect())
+    }
+
+    /// Files with the highest ratio of dead bytes, at least `min_dead_ratio`,
+    /// the active file is never selected
+    pub fn compaction_candidates(
+        &self,
+        min_dead_ratio: f64,
+        max_files: usize,
+    ) -> Result<Vec<String>> {
+        let active_file_id = self.active_file_id()?;
+        let mut candidates: Vec<(f64, String)> = self
+            .file_stats()?
+            .into_iter()
+            .filter(|stats| stats.file_id != active_file_id && stats.dead_bytes() > 0)
+            .map(|stats| (stats.dead_ratio(), stats.file_id))
+            .filter(|(dead_ratio, _)| *dead_ratio >= min_dead_ratio)
+            .collect();
+        candidates.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(candidates
+            .into_iter()
+            .take(max_files)
+            .map(|(_, file_id)| file_id)
+            .collect())
+    }
+
+    /// Compacts at most `max_files` files whose ratio of dead bytes is at least `min_dead_ratio`.
+    /// Returns the number of compacted files.
+    pub fn compact(&self, min_dead_ratio: f64, max_files: usize) -> Result<usize> {
+        let file_ids = self.compaction_candidates(min_dead_ratio, max_files)?;
+        self.compact_files(&file_ids)?;
+        Ok(file_ids.len())
+    }
+
+    /// Compacts all the files except the active one
+    pub fn merge(&self) -> Result<()> {
+        let active_file_id = self.active_file_id()?;
+        let file_ids: Vec<String> = self
+            .index_dir
+            .indexes()?
+            .keys()
+            .filter(|file_id| **file_id != active_file_id)
+            .cloned()
+            .collect();
+        self.compact_files(&file_ids)
+    }
+
+    /// Copies the live entries of the files into a new file, then removes them.
+    ///
+    /// The new file is ordered right after the most recent compacted file, so the entries
+    /// written meanwhile into the active file still override it when the hints are reloaded.
+    /// The writes are not blocked, an entry modified during the compaction stays in the
+    /// active file and its copy is dead.
+    fn compact_files(&self, file_ids: &[String]) -> Result<()> {
+        let indexes: Vec<Index> = {
+            let indexes = self.index_dir.indexes()?;
+            file_ids
+                .iter()
+                .filter_map(|file_id| indexes.get(file_id).cloned())
+                .collect()
+        };
+        let last_file_id = match indexes.iter().map(|index| index.file_id()).max() {
+            Some(file_id) => file_id,
+            None => return Ok(()),
+        };
+
+        // Tombstones are only needed while an older file, not compacted, can contain the key
+        let keep_tombstones = self
+            .index_dir
+            .indexes()?
+            .keys()
+            .any(|file_id| *file_id < last_file_id && !file_ids.contains(file_id));
+
+        let compacted_file_pair = ActiveFilePair::from(create_compaction_file_pair(
+            self.dir.as_path(),
+            &last_file_id,
+        )?)?;
+        let mut moved_entries = Vec::new();
+
+        for index in indexes.iter() {
+            for hint in index.get_hints()? {
+                if hint.is_deleted() {
+                    if keep_tombstones && !self.keys_dir.contains(&hint.key())? {
+                        compacted_file_pair.remove(hint.key())?;
+                    }
+                    continue;
+                }
                 if let Some(keys_dir_entry) = self.keys_dir.get(&hint.key()) {
-                    if keys_dir_entry.file_id == index.file_id() {
+                    if keys_dir_entry.file_id == index.file_id()
+                        && keys_dir_entry.data_entry_position == hint.data_entry_position()
+                    {
                         let data_entry = index.read(hint.data_entry_position(), hint.size())?;
-                        let key_entry = merged_file_pair.write(&data_entry, &self.keys_dir)?;
-                        self.keys_dir.insert(Arc::new(hint.key()), key_entry)?;
+                        let key_entry = compacted_file_pair.write(&data_entry, &self.keys_dir)?;
+                        moved_entries.push((hint.key(), keys_dir_entry, key_entry));
                     }
                 }
             }
-            mark_for_removal.push(index.data_file_path());
-            mark_for_removal.push(index.hint_file_path());
+        }
+        compacted_file_pair.sync()?;
+        let compacted_file_pair = compacted_file_pair.as_file_pair().commit_compaction()?;
+
+        // Registered before the keys point to it, so snapshots can read it
+        self.index_dir.insert(compacted_file_pair)?;
+        for (key, expected, key_entry) in moved_entries {
+            self.keys_dir
+                .compare_and_insert(Arc::new(key), &expected, key_entry)?;
         }
 
-        fs_extra::remove_items(&mark_for_removal)?;
+        let mut file_pins = self.file_pins()?;
+        for index in indexes {
+            self.index_dir.remove(&index.file_id())?;
+            self.keys_dir.remove_file(&index.file_id())?;
+            file_pins.remove(
+                index.file_id(),
+                vec![index.data_file_path(), index.hint_file_path()],
+            )?;
+        }
 
         Ok(())
     }
@@ -548,7 +979,8 @@ impl DataStore {
         buffer_file.sync_all()?;
 
         let mut key_entries: BTreeMap<Vec<u8>, KeyDirEntry> = BTreeMap::new();
-        for (key, value) in buffer.drain() {
+        // The snapshots keep the buffered values
+        for (key, value) in std::mem::take(&mut *buffer).iter() {
             let data_entry = DataEntry::new(key.to_vec(), value.to_vec());
             let key_dir_entry = active_file.write(&data_entry, &self.keys_dir)?;
             key_entries.insert(key.to_vec(), key_dir_entry);
@@ -578,9 +1010,13 @@ impl Drop for DataStore {
 
 #[cfg(test)]
 mod tests {
+    use crate::batch::WriteBatch;
     use crate::datastore::DataStore;
-    use crate::edgekv::DBIterator;
+    use crate::edgekv::{DBIterator, EdgeKV};
+    use crate::file_ops::fetch_file_pairs;
     use serial_test::serial;
+    use std::fs::OpenOptions;
+    use std::ops::Bound;
     use std::sync::Arc;
 
     #[ignore] // TODO: re-enable again if we start using this crate
@@ -779,6 +1215,232 @@ mod tests {
         clean_up();
     }
 
+    #[test]
+    #[serial]
+    fn test_write_batch_recovery() {
+        let dir = "./testdir/_test_write_batch_recovery";
+        fs_extra::dir::remove(dir).ok();
+        {
+            let ds = DataStore::open(dir).unwrap();
+            let mut batch = WriteBatch::new();
+            batch.put(vec![1], vec![1, 1]);
+            batch.put(vec![2], vec![2, 2]);
+            ds.write_batch(&batch).unwrap();
+
+            let mut batch = WriteBatch::new();
+            batch.put(vec![3], vec![3, 3]);
+            batch.delete(vec![1]);
+            ds.write_batch(&batch).unwrap();
+
+            assert_eq!(ds.get(&vec![1]).unwrap(), None);
+            assert_eq!(ds.get(&vec![3]).unwrap(), Some(vec![3, 3]));
+        }
+
+        // Tear the last hint of the second batch
+        let (file_pairs, _) = fetch_file_pairs(dir).unwrap();
+        let hint_file_path = file_pairs
+            .values()
+            .map(|file_pair| file_pair.hint_file_path())
+            .find(|path| std::fs::metadata(path).unwrap().len() > 0)
+            .unwrap();
+        let hint_file = OpenOptions::new()
+            .write(true)
+            .open(&hint_file_path)
+            .unwrap();
+        let hint_file_size = hint_file.metadata().unwrap().len();
+        hint_file.set_len(hint_file_size - 3).unwrap();
+        drop(hint_file);
+
+        {
+            let ds = DataStore::open(dir).unwrap();
+            assert_eq!(ds.get(&vec![1]).unwrap(), Some(vec![1, 1]));
+            assert_eq!(ds.get(&vec![2]).unwrap(), Some(vec![2, 2]));
+            assert_eq!(ds.get(&vec![3]).unwrap(), None);
+            assert_eq!(ds.size(), 2);
+        }
+        fs_extra::dir::remove(dir).ok();
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot_pins_files() {
+        let dir = "./testdir/_test_snapshot_pins_files";
+        fs_extra::dir::remove(dir).ok();
+        {
+            let ds = DataStore::open(dir).unwrap();
+            ds.put(vec![1], vec![1, 1]).unwrap();
+            ds.put(vec![2], vec![2, 2]).unwrap();
+            ds.sync_all(true).unwrap();
+            ds.put(vec![3], vec![3, 3]).unwrap();
+
+            let snapshot = ds.snapshot().unwrap();
+
+            ds.put(vec![1], vec![9, 9]).unwrap();
+            ds.delete(&vec![2]).unwrap();
+            ds.put(vec![4], vec![4, 4]).unwrap();
+            ds.sync_all(true).unwrap();
+            ds.merge().unwrap();
+
+            assert_eq!(snapshot.get(&vec![1]).unwrap(), Some(vec![1, 1]));
+            assert_eq!(snapshot.get(&vec![2]).unwrap(), Some(vec![2, 2]));
+            assert_eq!(snapshot.get(&vec![3]).unwrap(), Some(vec![3, 3]));
+            assert_eq!(snapshot.get(&vec![4]).unwrap(), None);
+            assert_eq!(snapshot.size(), 3);
+            assert_eq!(
+                snapshot.first_key(Bound::Excluded(&vec![1]), Bound::Unbounded),
+                Some(Arc::new(vec![2]))
+            );
+            assert_eq!(
+                snapshot.last_key(Bound::Unbounded, Bound::Excluded(&vec![3])),
+                Some(Arc::new(vec![2]))
+            );
+            assert_eq!(
+                snapshot.first_key(Bound::Excluded(&vec![2]), Bound::Excluded(&vec![2])),
+                None
+            );
+
+            assert_eq!(ds.get(&vec![1]).unwrap(), Some(vec![9, 9]));
+            assert_eq!(ds.get(&vec![2]).unwrap(), None);
+
+            let (file_pairs_before, _) = fetch_file_pairs(dir).unwrap();
+            drop(snapshot);
+            let (file_pairs_after, _) = fetch_file_pairs(dir).unwrap();
+            assert!(file_pairs_after.len() < file_pairs_before.len());
+
+            assert_eq!(ds.get(&vec![1]).unwrap(), Some(vec![9, 9]));
+            assert_eq!(ds.get(&vec![3]).unwrap(), Some(vec![3, 3]));
+        }
+        fs_extra::dir::remove(dir).ok();
+    }
+
+    #[test]
+    #[serial]
+    fn test_compaction() {
+        let dir = "./testdir/_test_compaction";
+        fs_extra::dir::remove(dir).ok();
+        {
+            let ds = DataStore::open(dir).unwrap();
+            for i in 0..10 {
+                ds.put(vec![i], vec![i; 100]).unwrap();
+            }
+            ds.sync_all(true).unwrap();
+            for i in 0..8 {
+                ds.put(vec![i], vec![i + 1; 100]).unwrap();
+            }
+            ds.delete(&vec![9]).unwrap();
+            ds.sync_all(true).unwrap();
+
+            let stats = ds.file_stats().unwrap();
+            let fragmented = stats.iter().find(|stats| stats.dead_ratio() > 0.8).unwrap();
+            assert_eq!(fragmented.live_bytes, 129);
+
+            assert_eq!(ds.compact(0.5, 4).unwrap(), 1);
+            assert_eq!(ds.compact(0.5, 4).unwrap(), 0);
+            assert!(ds
+                .file_stats()
+                .unwrap()
+                .iter()
+                .all(|stats| stats.dead_bytes() == 0));
+
+            assert_eq!(ds.get(&vec![0]).unwrap(), Some(vec![1; 100]));
+            assert_eq!(ds.get(&vec![8]).unwrap(), Some(vec![8; 100]));
+            assert_eq!(ds.get(&vec![9]).unwrap(), None);
+        }
+        {
+            let ds = DataStore::open(dir).unwrap();
+            assert_eq!(ds.size(), 9);
+            assert_eq!(ds.get(&vec![0]).unwrap(), Some(vec![1; 100]));
+            assert_eq!(ds.get(&vec![8]).unwrap(), Some(vec![8; 100]));
+            assert_eq!(ds.get(&vec![9]).unwrap(), None);
+        }
+        fs_extra::dir::remove(dir).ok();
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot_shares_keys_until_write() {
+        let dir = "./testdir/_test_snapshot_shares_keys_until_write";
+        fs_extra::dir::remove(dir).ok();
+        {
+            let ds = DataStore::open(dir).unwrap();
+            ds.put(vec![1], vec![1, 1]).unwrap();
+            ds.sync_all(true).unwrap();
+            ds.put(vec![2], vec![2, 2]).unwrap();
+
+            let first = ds.snapshot().unwrap();
+            let second = ds.snapshot().unwrap();
+            assert!(Arc::ptr_eq(&first.keys, &second.keys));
+            assert!(Arc::ptr_eq(&first.buffer, &second.buffer));
+
+            ds.put(vec![3], vec![3, 3]).unwrap();
+            ds.sync_all(true).unwrap();
+            let third = ds.snapshot().unwrap();
+            assert!(!Arc::ptr_eq(&first.keys, &third.keys));
+
+            assert_eq!(first.size(), 2);
+            assert_eq!(first.get(&vec![2]).unwrap(), Some(vec![2, 2]));
+            assert_eq!(first.get(&vec![3]).unwrap(), None);
+            assert_eq!(third.size(), 3);
+            assert_eq!(third.get(&vec![2]).unwrap(), Some(vec![2, 2]));
+        }
+        fs_extra::dir::remove(dir).ok();
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot_iterator() {
+        let dir = "./testdir/_test_snapshot_iterator";
+        fs_extra::dir::remove(dir).ok();
+        {
+            let db = EdgeKV::open(dir).unwrap();
+            for key in [vec![1], vec![1, 255], vec![2], vec![2, 0], vec![3]] {
+                db.put(key.clone(), key).unwrap();
+            }
+            let snapshot = db.snapshot().unwrap();
+            db.put(vec![1, 1], vec![1, 1]).unwrap();
+            db.delete(&vec![3]).unwrap();
+
+            let keys = |iter: Vec<crate::Result<(Vec<u8>, Vec<u8>)>>| -> Vec<Vec<u8>> {
+                iter.into_iter().map(|item| item.unwrap().0).collect()
+            };
+
+            assert_eq!(
+                keys(snapshot.iter().collect()),
+                vec![vec![1], vec![1, 255], vec![2], vec![2, 0], vec![3]]
+            );
+            assert_eq!(
+                keys(snapshot.iter().rev().collect()),
+                vec![vec![3], vec![2, 0], vec![2], vec![1, 255], vec![1]]
+            );
+            assert_eq!(
+                keys(snapshot.range(vec![2]..).collect()),
+                vec![vec![2], vec![2, 0], vec![3]]
+            );
+            assert_eq!(
+                keys(snapshot.range(..=vec![2]).rev().collect()),
+                vec![vec![2], vec![1, 255], vec![1]]
+            );
+            assert_eq!(
+                keys(snapshot.prefix(&vec![1]).collect()),
+                vec![vec![1], vec![1, 255]]
+            );
+            assert_eq!(
+                keys(snapshot.prefix(&vec![2]).rev().collect()),
+                vec![vec![2, 0], vec![2]]
+            );
+
+            // Both ends meet
+            let mut iter = snapshot.iter();
+            assert_eq!(iter.next().unwrap().unwrap().0, vec![1]);
+            assert_eq!(iter.next_back().unwrap().unwrap().0, vec![3]);
+            assert_eq!(
+                keys(iter.collect()),
+                vec![vec![1, 255], vec![2], vec![2, 0]]
+            );
+        }
+        fs_extra::dir::remove(dir).ok();
+    }
+
     fn clean_up() {
         fs_extra::dir::remove("./testdir/_test_data_store").ok();
     }
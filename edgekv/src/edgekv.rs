@@ -3,23 +3,27 @@
 
 #![allow(clippy::ptr_arg)]
 
-use crate::datastore::{DataStore, MergeOperator};
+use crate::batch::WriteBatch;
+use crate::datastore::{DataStore, DataStoreSnapshot, FileStats, MergeOperator};
 
+use crate::errors::EdgeKVError;
 use crate::Result;
 
+use slog::{error, o, Discard, Logger};
 use std::fmt::{Display, Formatter};
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // TODO - TE-721: dir and config are not used
 pub struct EdgeKV {
     store: Arc<DataStore>,
     dropped: Arc<AtomicBool>,
     config: EdgeKVConfiguration,
+    log: Logger,
 }
 
 impl Display for EdgeKV {
@@ -34,46 +38,52 @@ impl Display for EdgeKV {
 #[derive(Copy, Clone)]
 pub struct EdgeKVConfiguration {
     pub write_threshold: usize,
+    /// Interval between two compactions by the background worker
+    pub compaction_interval: Duration,
+    /// Minimal ratio of dead bytes of a file to be compacted
+    pub compaction_dead_ratio: f64,
+    /// Maximal number of files compacted at once
+    pub compaction_max_files: usize,
 }
 
 const DEFAULT_WRITE_THRESHOLD: usize = 1000;
+const DEFAULT_COMPACTION_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_COMPACTION_DEAD_RATIO: f64 = 0.5;
+const DEFAULT_COMPACTION_MAX_FILES: usize = 8;
 
 impl Default for EdgeKVConfiguration {
     fn default() -> Self {
         Self {
             write_threshold: DEFAULT_WRITE_THRESHOLD,
+            compaction_interval: DEFAULT_COMPACTION_INTERVAL,
+            compaction_dead_ratio: DEFAULT_COMPACTION_DEAD_RATIO,
+            compaction_max_files: DEFAULT_COMPACTION_MAX_FILES,
         }
     }
 }
 
 impl EdgeKV {
+    /// Opens the store with the default configuration, the errors of the background
+    /// workers are not logged
     pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
-        let store = Arc::new(DataStore::open(dir.as_ref())?);
-        let config = EdgeKVConfiguration::default();
-        let instance = Self {
-            store,
-            dropped: Arc::new(AtomicBool::new(false)),
-            config,
-        };
-        //Only for testing
-        let mut p = PathBuf::new();
-        p.push(dir);
-        let worker_name = p
-            .file_name()
-            .map_or("".to_string(), |name| name.to_string_lossy().to_string());
-        instance.start_background_workers(worker_name)?;
-        Ok(instance)
+        Self::open_with_configuration(
+            dir,
+            EdgeKVConfiguration::default(),
+            Logger::root(Discard, o!()),
+        )
     }
 
     pub fn open_with_configuration<P: AsRef<Path>>(
         dir: P,
         config: EdgeKVConfiguration,
+        log: Logger,
     ) -> Result<Self> {
         let store = Arc::new(DataStore::open(dir.as_ref())?);
         let instance = Self {
             store,
             dropped: Arc::new(AtomicBool::new(false)),
             config,
+            log,
         };
         //Only for testing
         let mut p = PathBuf::new();
@@ -89,10 +99,12 @@ impl EdgeKV {
         let is_dropped = self.dropped.clone();
         let store = self.store.clone();
         let config = self.config;
+        let log = self.log.new(o!("worker" => worker_name.clone()));
         //let max_hint_file_size = self.config.max_hint_file_size;
         thread::Builder::new()
             .name(format!("edgekv-{}", worker_name))
             .spawn(move || {
+                let mut last_compaction = Instant::now();
                 loop {
                     thread::sleep(Duration::from_millis(1));
                     let is_dropped = is_dropped.load(Ordering::Acquire);
@@ -102,8 +114,16 @@ impl EdgeKV {
                     match store.sync_all(store.buffer_size() >= config.write_threshold) {
                         Ok(_) => {}
                         Err(e) => {
-                            //Todo log Error
-                            println!("Error {:?}", e)
+                            error!(log, "Failed to sync the store"; "reason" => format!("{:?}", e))
+                        }
+                    }
+                    // Compacts a few files at a time, the most fragmented first
+                    if last_compaction.elapsed() >= config.compaction_interval {
+                        last_compaction = Instant::now();
+                        if let Err(e) =
+                            store.compact(config.compaction_dead_ratio, config.compaction_max_files)
+                        {
+                            error!(log, "Failed to compact the store"; "reason" => format!("{:?}", e))
                         }
                     }
                 }
@@ -136,6 +156,11 @@ impl EdgeKV {
         self.store.delete(key)
     }
 
+    /// Applies all the operations of the batch, or none of them if the process crashes
+    pub fn write_batch(&self, batch: WriteBatch) -> Result<()> {
+        self.store.write_batch(&batch)
+    }
+
     pub fn compact(&self) -> Result<()> {
         self.store.merge()
     }
@@ -177,10 +202,22 @@ impl EdgeKV {
         DBIterator::prefix(self.store.clone(), prefix)
     }
 
+    /// Point-in-time view of the store, unaffected by the following writes and compactions
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        Ok(Snapshot {
+            inner: Arc::new(self.store.snapshot()?),
+        })
+    }
+
     pub fn size(&self) -> usize {
         self.store.size()
     }
 
+    /// Live and dead bytes of each data file
+    pub fn file_stats(&self) -> Result<Vec<FileStats>> {
+        self.store.file_stats()
+    }
+
     pub fn sync_all(&self) -> Result<()> {
         self.store.sync_all(true)
     }
@@ -282,3 +319,116 @@ impl DoubleEndedIterator for DBIterator {
         }
     }
 }
+
+#[derive(Clone)]
+pub struct Snapshot {
+    inner: Arc<DataStoreSnapshot>,
+}
+
+impl Snapshot {
+    pub fn get(&self, key: &Vec<u8>) -> Result<Option<Vec<u8>>> {
+        if key.is_empty() {
+            return Ok(None);
+        }
+        self.inner.get(key)
+    }
+
+    pub fn contains(&self, key: &Vec<u8>) -> bool {
+        !key.is_empty() && self.inner.contains(key)
+    }
+
+    pub fn iter(&self) -> SnapshotIterator {
+        SnapshotIterator::new(self.inner.clone(), Bound::Unbounded, Bound::Unbounded)
+    }
+
+    pub fn range<R>(&self, range: R) -> SnapshotIterator
+    where
+        R: RangeBounds<Vec<u8>>,
+    {
+        SnapshotIterator::new(
+            self.inner.clone(),
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+        )
+    }
+
+    pub fn prefix(&self, prefix: &Vec<u8>) -> SnapshotIterator {
+        SnapshotIterator::new(
+            self.inner.clone(),
+            Bound::Included(prefix.clone()),
+            prefix_end(prefix),
+        )
+    }
+
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+}
+
+/// First key after all the keys starting with `prefix`
+fn prefix_end(prefix: &[u8]) -> Bound<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Bound::Excluded(end);
+        }
+    }
+    Bound::Unbounded
+}
+
+fn as_ref_bound(bound: &Bound<Vec<u8>>) -> Bound<&Vec<u8>> {
+    match bound {
+        Bound::Included(key) => Bound::Included(key),
+        Bound::Excluded(key) => Bound::Excluded(key),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Iterates over the keys of the snapshot lazily, the remaining keys are those between
+/// `start` and `end`
+pub struct SnapshotIterator {
+    snapshot: Arc<DataStoreSnapshot>,
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+}
+
+impl SnapshotIterator {
+    fn new(snapshot: Arc<DataStoreSnapshot>, start: Bound<Vec<u8>>, end: Bound<Vec<u8>>) -> Self {
+        Self {
+            snapshot,
+            start,
+            end,
+        }
+    }
+
+    fn read(&self, key: Arc<Vec<u8>>) -> Result<(Vec<u8>, Vec<u8>)> {
+        match self.snapshot.get(&key)? {
+            Some(value) => Ok((key.to_vec(), value)),
+            // The snapshot is immutable, its keys always have a value
+            None => Err(EdgeKVError::CorruptData),
+        }
+    }
+}
+
+impl Iterator for SnapshotIterator {
+    type Item = Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self
+            .snapshot
+            .first_key(as_ref_bound(&self.start), as_ref_bound(&self.end))?;
+        self.start = Bound::Excluded(key.to_vec());
+        Some(self.read(key))
+    }
+}
+
+impl DoubleEndedIterator for SnapshotIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let key = self
+            .snapshot
+            .last_key(as_ref_bound(&self.start), as_ref_bound(&self.end))?;
+        self.end = Bound::Excluded(key.to_vec());
+        Some(self.read(key))
+    }
+}
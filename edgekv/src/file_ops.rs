@@ -1,6 +1,7 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
+use crate::batch::{BatchOperation, WriteBatch};
 use crate::datastore::{KeyDirEntry, KeysDir};
 use crate::errors::EdgeKVError;
 use crate::schema::{DataEntry, Decoder, Encoder, HintEntry};
@@ -17,6 +18,8 @@ use time::OffsetDateTime;
 const DATA_FILE_EXTENSION: &str = "data";
 const HINT_FILE_EXTENSION: &str = "hint";
 const BUFFER_FILE_EXTENSION: &str = "buff";
+/// Extension of the files being written by the compaction
+const COMPACTION_FILE_EXTENSION: &str = "tmp";
 
 #[derive(Debug, Clone)]
 pub struct FilePair {
@@ -42,7 +45,7 @@ impl FilePair {
         String::from(self.hint_file_path.to_string_lossy())
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Index {
     file_id: String,
     data_file_path: PathBuf,
@@ -70,6 +73,9 @@ impl Index {
         let mut rdr = BufReader::new(hint);
         let mut hints = Vec::new();
         while let Ok(hint_entry) = HintEntry::decode(&mut rdr) {
+            if hint_entry.is_batch() {
+                continue;
+            }
             hints.push(hint_entry)
         }
         Ok(hints)
@@ -80,6 +86,9 @@ impl Index {
         let hint = File::open(self.hint_file_path.as_path())?;
         let mut rdr = BufReader::new(hint);
         while let Ok(hint_entry) = HintEntry::decode(&mut rdr) {
+            if hint_entry.is_batch() {
+                continue;
+            }
             if hint_entry.is_deleted() {
                 entries.remove(&hint_entry.key());
             } else {
@@ -120,6 +129,9 @@ impl FilePair {
             if !hint_entry.check_crc() {
                 return Err(EdgeKVError::CorruptData);
             }
+            if hint_entry.is_batch() {
+                continue;
+            }
             if hint_entry.is_deleted() {
                 keys_dir.remove(&hint_entry.key())?;
             } else {
@@ -135,6 +147,88 @@ impl FilePair {
         Ok(())
     }
 
+    fn is_empty(&self) -> bool {
+        let file_size = |path: &Path| std::fs::metadata(path).map_or(0, |m| m.len());
+        file_size(&self.data_file_path) == 0 && file_size(&self.hint_file_path) == 0
+    }
+
+    /// Truncates the torn entries at the end of the data and hint files, and the hints
+    /// of an incomplete batch, with its data entries.
+    /// Returns true when the files have been truncated.
+    pub fn recover(&self) -> Result<bool> {
+        // Length of the data file until the first invalid entry
+        let mut data_file_size = 0;
+        if let Ok(data_file) = File::open(self.data_file_path.as_path()) {
+            let mut rdr = BufReader::new(data_file);
+            while let Ok(data_entry) = DataEntry::decode(&mut rdr) {
+                if !data_entry.check_crc() {
+                    break;
+                }
+                data_file_size += data_entry.size();
+            }
+        }
+
+        // Length of the hint file until the first invalid hint, or the incomplete batch
+        let mut hint_file_size = 0;
+        if let Ok(hint_file) = File::open(self.hint_file_path.as_path()) {
+            let mut rdr = BufReader::new(hint_file);
+            let mut position = 0;
+            // Position of the data entries and number of remaining hints of the current batch
+            let mut batch: Option<(u64, u64)> = None;
+            while let Ok(hint_entry) = HintEntry::decode(&mut rdr) {
+                if !hint_entry.check_crc() {
+                    break;
+                }
+                position += hint_entry.size() as u64;
+
+                if hint_entry.is_batch() {
+                    if batch.is_some() {
+                        break;
+                    }
+                    batch = Some((hint_entry.data_entry_position(), hint_entry.batch_size()));
+                } else if !hint_entry.is_deleted()
+                    && hint_entry.data_entry_position() + hint_entry.data_entry_size()
+                        > data_file_size
+                {
+                    break;
+                } else if let Some((_, remaining)) = batch.as_mut() {
+                    *remaining -= 1;
+                }
+
+                match batch {
+                    Some((_, 0)) => {
+                        batch = None;
+                        hint_file_size = position;
+                    }
+                    Some(_) => {}
+                    None => hint_file_size = position,
+                }
+            }
+
+            if let Some((batch_data_position, _)) = batch {
+                data_file_size = data_file_size.min(batch_data_position);
+            }
+        }
+
+        let data_truncated = truncate_file(&self.data_file_path, data_file_size)?;
+        let hint_truncated = truncate_file(&self.hint_file_path, hint_file_size)?;
+        Ok(data_truncated || hint_truncated)
+    }
+
+    /// Renames the files written by the compaction, the hint file last: a data file
+    /// without its hint file is removed by [`recover_file_pairs`]
+    pub fn commit_compaction(&self) -> Result<FilePair> {
+        let data_file_path = self.data_file_path.with_extension("");
+        let hint_file_path = self.hint_file_path.with_extension("");
+        std::fs::rename(&self.data_file_path, &data_file_path)?;
+        std::fs::rename(&self.hint_file_path, &hint_file_path)?;
+        Ok(FilePair {
+            file_id: self.file_id.clone(),
+            data_file_path,
+            hint_file_path,
+        })
+    }
+
     pub fn to_index(&self) -> Result<Index> {
         let data_file_path = self.data_file_path.clone();
         let hint_file_path = self.hint_file_path.clone();
@@ -238,6 +332,65 @@ impl ActiveFilePair {
         ))
     }
 
+    /// Appends the entries of the batch to the data file, then the batch hint followed by
+    /// the hints of the entries to the hint file. Both files are synced, the data file first,
+    /// so the batch is complete once its last hint is on disk (see [`FilePair::recover`]).
+    /// Returns the key dir entries of the puts, `None` for the deletes.
+    pub fn write_batch(&self, batch: &WriteBatch) -> Result<Vec<Option<KeyDirEntry>>> {
+        self.data_file.try_lock_exclusive()?;
+        self.hint_file.try_lock_exclusive()?;
+
+        let mut dfw = BufWriter::new(&self.data_file);
+        let batch_data_position = dfw.seek(SeekFrom::End(0))?;
+        let mut data_entry_position = batch_data_position;
+
+        let mut hints = HintEntry::batch(batch.len() as u64, batch_data_position).encode();
+        let mut key_dir_entries = Vec::with_capacity(batch.len());
+
+        for operation in batch.operations() {
+            match operation {
+                BatchOperation::Put(key, value) => {
+                    let data_entry = DataEntry::new(key.clone(), value.clone());
+                    dfw.write_all(&data_entry.encode())?;
+
+                    let hint_entry = HintEntry::from(&data_entry, data_entry_position);
+                    hints.extend_from_slice(&hint_entry.encode());
+                    key_dir_entries.push(Some(KeyDirEntry::new(
+                        self.file_pair.file_id.to_string(),
+                        hint_entry.key_size(),
+                        hint_entry.value_size(),
+                        data_entry_position,
+                    )));
+
+                    data_entry_position += data_entry.size();
+                }
+                BatchOperation::Delete(key) => {
+                    hints.extend_from_slice(&HintEntry::tombstone(key.clone()).encode());
+                    key_dir_entries.push(None);
+                }
+            }
+        }
+        dfw.flush()?;
+        drop(dfw);
+        // Both syncs are needed, the first one is an ordering barrier: the hints must never
+        // reach the disk before the data entries they point to. Otherwise a crash could leave
+        // complete batch hints pointing at a range of the data file that was never written,
+        // and its content (zeros or stale blocks) is not guaranteed to fail the crc check of
+        // `FilePair::recover`. The second one makes the batch durable when this returns.
+        self.data_file.sync_all()?;
+
+        let mut hfw = BufWriter::new(&self.hint_file);
+        hfw.seek(SeekFrom::End(0))?;
+        hfw.write_all(&hints)?;
+        hfw.flush()?;
+        drop(hfw);
+        self.hint_file.sync_all()?;
+
+        self.data_file.unlock()?;
+        self.hint_file.unlock()?;
+        Ok(key_dir_entries)
+    }
+
     pub fn remove(&self, key: Vec<u8>) -> Result<()> {
         self.hint_file.try_lock_exclusive()?;
         //Append hint to hint file
@@ -282,6 +435,96 @@ pub fn create_new_file_pair<P: AsRef<Path>>(dir: P) -> Result<FilePair> {
     })
 }
 
+fn truncate_file(path: &Path, size: u64) -> Result<bool> {
+    let file = match OpenOptions::new().write(true).open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(false),
+    };
+    if file.metadata()?.len() <= size {
+        return Ok(false);
+    }
+    file.set_len(size)?;
+    file.sync_all()?;
+    Ok(true)
+}
+
+/// Removes the files of an interrupted compaction, then recovers the file pair that was
+/// active when the store was last closed, it's the most recent non empty file pair,
+/// see [`FilePair::recover`]
+pub fn recover_file_pairs<P: AsRef<Path>>(dir: P) -> Result<bool> {
+    let mut option = DirOptions::new();
+    option.depth = 1;
+    let dir_content = fs_extra::dir::get_dir_content2(dir.as_ref(), &option)?;
+    let compaction_files: Vec<&String> = dir_content
+        .files
+        .iter()
+        .filter(|file| Path::new(file).extension().unwrap_or_default() == COMPACTION_FILE_EXTENSION)
+        .collect();
+    fs_extra::remove_items(&compaction_files)?;
+
+    let (mut file_pairs, _) = fetch_file_pairs(dir)?;
+    let incomplete_file_pairs: Vec<String> = file_pairs
+        .iter()
+        .filter(|(_, file_pair)| {
+            file_pair.data_file_path.as_os_str().is_empty()
+                || file_pair.hint_file_path.as_os_str().is_empty()
+        })
+        .map(|(file_id, _)| file_id.clone())
+        .collect();
+    for file_id in incomplete_file_pairs {
+        if let Some(file_pair) = file_pairs.remove(&file_id) {
+            let paths: Vec<&PathBuf> = [&file_pair.data_file_path, &file_pair.hint_file_path]
+                .into_iter()
+                .filter(|path| !path.as_os_str().is_empty())
+                .collect();
+            fs_extra::remove_items(&paths)?;
+        }
+    }
+
+    match file_pairs
+        .values()
+        .rev()
+        .find(|file_pair| !file_pair.is_empty())
+    {
+        Some(file_pair) => file_pair.recover(),
+        None => Ok(false),
+    }
+}
+
+/// Creates the file pair written by the compaction of files up to `last_file_id`.
+/// Its id is ordered right after `last_file_id`, and before the more recent files.
+pub fn create_compaction_file_pair<P: AsRef<Path>>(dir: P, last_file_id: &str) -> Result<FilePair> {
+    let file_name = format!("{}_c", last_file_id);
+    let mut data_file_path = PathBuf::new();
+    data_file_path.push(dir.as_ref());
+    data_file_path.push(format!(
+        "{}.{}.{}",
+        file_name, DATA_FILE_EXTENSION, COMPACTION_FILE_EXTENSION
+    ));
+
+    let mut hint_file_path = PathBuf::new();
+    hint_file_path.push(dir.as_ref());
+    hint_file_path.push(format!(
+        "{}.{}.{}",
+        file_name, HINT_FILE_EXTENSION, COMPACTION_FILE_EXTENSION
+    ));
+
+    OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .open(data_file_path.as_path())?;
+    OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .open(hint_file_path.as_path())?;
+
+    Ok(FilePair {
+        data_file_path,
+        hint_file_path,
+        file_id: file_name,
+    })
+}
+
 pub fn get_lock_file<P: AsRef<Path>>(dir: P) -> Result<File> {
     let mut lock_file_path = PathBuf::new();
     lock_file_path.push(dir.as_ref());
@@ -3,6 +3,7 @@
 
 use crate::errors::EdgeKVError;
 
+pub mod batch;
 pub mod datastore;
 pub mod edgekv;
 pub mod errors;
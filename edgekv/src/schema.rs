@@ -5,6 +5,10 @@ use crc::{Crc, CRC_32_CKSUM};
 use std::io::Read;
 use time::OffsetDateTime;
 pub const CRC_CKSUM: Crc<u32> = Crc::<u32>::new(&CRC_32_CKSUM);
+/// Size of the crc, timestamp, key size and value size of an encoded [`DataEntry`]
+pub const DATA_ENTRY_HEADER_SIZE: u64 = 4 + 8 + 8 + 8;
+/// Timestamp of the hint entry starting a batch, see [`HintEntry::batch`]
+const BATCH_TIMESTAMP: i64 = -2;
 use crate::Result;
 
 #[derive(Debug, Clone, PartialOrd, PartialEq)]
@@ -100,6 +104,11 @@ impl DataEntry {
         self.crc == CRC_CKSUM.checksum(&self.encode_content())
     }
 
+    /// Size of the encoded entry
+    pub fn size(&self) -> u64 {
+        DATA_ENTRY_HEADER_SIZE + self.key_size + self.value_size
+    }
+
     fn encode_content(&self) -> Vec<u8> {
         let mut buf = vec![];
         buf.extend_from_slice(&self.timestamp.to_be_bytes());
@@ -148,12 +157,42 @@ impl HintEntry {
             key,
         }
     }
+    /// Hint written before the hints of a batch, `size` is the number of hints
+    /// in the batch and `data_entry_position` the position of its first data entry.
+    pub fn batch(size: u64, data_entry_position: u64) -> Self {
+        Self {
+            crc: 0,
+            timestamp: BATCH_TIMESTAMP,
+            key_size: 0,
+            value_size: size,
+            data_entry_position,
+            key: vec![],
+        }
+    }
+
     pub fn data_entry_position(&self) -> u64 {
         self.data_entry_position
     }
 
     pub fn is_deleted(&self) -> bool {
-        self.timestamp <= 0 && self.value_size == 0 && self.data_entry_position == 0
+        self.timestamp <= 0
+            && self.value_size == 0
+            && self.data_entry_position == 0
+            && !self.is_batch()
+    }
+
+    pub fn is_batch(&self) -> bool {
+        self.timestamp == BATCH_TIMESTAMP && self.key_size == 0
+    }
+
+    /// Number of hints in the batch started by this hint
+    pub fn batch_size(&self) -> u64 {
+        self.value_size
+    }
+
+    /// Size of the data entry referenced by this hint
+    pub fn data_entry_size(&self) -> u64 {
+        DATA_ENTRY_HEADER_SIZE + self.key_size + self.value_size
     }
 
     fn encode_content(&self) -> Vec<u8> {
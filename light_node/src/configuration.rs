@@ -6,6 +6,7 @@ use std::ffi::OsString;
 use std::fs;
 use std::io::{self, BufRead};
 use std::net::SocketAddr;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
@@ -17,11 +18,13 @@ use slog::Logger;
 
 use crypto::hash::BlockHash;
 use logging::config::{FileLoggerConfig, LogFormat, LoggerType, NoDrainError, SlogConfig};
-use shell::shell_automaton_manager::P2p;
+use shell::shell_automaton_manager::{
+    BandwidthLimit, IpCidr, MempoolLimits, P2p, P2pRecorderConfig, PeersScoreThresholds,
+};
 use shell::PeerConnectionThreshold;
 use storage::database::tezedge_database::TezedgeDatabaseBackendConfiguration;
 use storage::initializer::{DbsRocksDbTableInitializer, RocksDbConfig};
-use storage::{BlockReference, Replay, StorageSnapshot};
+use storage::{BlockReference, HistoryMode, Replay, SnapshotFormat, StorageSnapshot};
 use tezos_api::environment::{self, TezosEnvironmentConfiguration};
 use tezos_api::environment::{TezosEnvironment, ZcashParams};
 use tezos_context_api::{
@@ -86,10 +89,15 @@ pub struct Storage {
     pub db_path: PathBuf,
     pub context_stats_db_path: Option<PathBuf>,
     pub context_storage_configuration: TezosContextStorageConfiguration,
+    pub context_gc_preserve_cycles: Option<NonZeroU64>,
     pub compute_context_action_tree_hashes: bool,
     pub patch_context: Option<PatchContext>,
     pub main_db: TezedgeDatabaseBackendConfiguration,
     pub initialize_context_timeout: Duration,
+    pub history_mode: HistoryMode,
+    pub index_account_operations: bool,
+    pub db_migration_dry_run: bool,
+    pub protocol_sources_dir: Option<PathBuf>,
 }
 
 impl Storage {
@@ -104,7 +112,20 @@ impl Storage {
 
     const DEFAULT_MAINDB: &'static str = "rocksdb";
 
+    const DEFAULT_HISTORY_MODE: &'static str = "archive";
+
     const DEFAULT_INITIALIZE_CONTEXT_TIMEOUT_IN_SECONDS: u64 = 15;
+
+    /// Number of cycles preserved by the garbage collector of the context, an explicit
+    /// `--context-gc-preserve-cycles` has priority over the cycles retained by the history mode
+    /// with the `preserved_cycles` constant of the active protocol.
+    pub fn context_gc_retained_cycles(&self, preserved_cycles: u8) -> Option<NonZeroU64> {
+        self.context_gc_preserve_cycles.or_else(|| {
+            self.history_mode
+                .retained_cycles(preserved_cycles)
+                .and_then(|retained_cycles| NonZeroU64::new(retained_cycles.into()))
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -136,6 +157,7 @@ pub struct Environment {
     pub ffi: Ffi,
     pub replay: Option<Replay>,
     pub snapshot: Option<StorageSnapshot>,
+    pub import_octez_snapshot: Option<PathBuf>,
 
     pub tezos_network: TezosEnvironment,
     pub tezos_network_config: TezosEnvironmentConfiguration,
@@ -203,6 +225,13 @@ macro_rules! parse_validator_fn {
     };
 }
 
+fn parse_cidr_list(value: &str) -> Result<Vec<IpCidr>, String> {
+    value
+        .split(',')
+        .map(|cidr| cidr.trim().parse::<IpCidr>().map_err(|e| e.to_string()))
+        .collect()
+}
+
 // Creates tezos app
 pub fn tezos_app() -> App<'static, 'static> {
     // Default values for arguments are specidied in default configuration file
@@ -398,10 +427,103 @@ pub fn tezos_app() -> App<'static, 'static> {
             .takes_value(true)
             .value_name("BOOL")
             .help("Enable or disable prechecking of endorsements"))
+        .arg(Arg::with_name("disable-manager-operations-precheck")
+            .long("disable-manager-operations-precheck")
+            .global(true)
+            .takes_value(true)
+            .value_name("BOOL")
+            .help("Enable or disable prechecking of manager operations"))
         .arg(Arg::with_name("disable-peer-graylist")
             .long("disable-peer-graylist")
             .global(true)
             .help("Disable peer graylisting"))
+        .arg(Arg::with_name("peer-score-disconnect-threshold")
+            .long("peer-score-disconnect-threshold")
+            .global(true)
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .value_name("NUM")
+            .help("Peer is disconnected when its score drops to this (negative) value, default: -50")
+            .validator(parse_validator_fn!(i32, "Value must be a valid number")))
+        .arg(Arg::with_name("peer-score-graylist-threshold")
+            .long("peer-score-graylist-threshold")
+            .global(true)
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .value_name("NUM")
+            .help("Peer is graylisted when its score drops to this (negative) value, default: -100")
+            .validator(parse_validator_fn!(i32, "Value must be a valid number")))
+        .arg(Arg::with_name("peer-score-ban-threshold")
+            .long("peer-score-ban-threshold")
+            .global(true)
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .value_name("NUM")
+            .help("Peer is permanently banned when its score drops to this (negative) value, default: -500")
+            .validator(parse_validator_fn!(i32, "Value must be a valid number")))
+        .arg(Arg::with_name("peer-score-recovery-interval")
+            .long("peer-score-recovery-interval")
+            .global(true)
+            .takes_value(true)
+            .value_name("SECONDS")
+            .help("Negative score of the peer recovers by one point every <SECONDS>, default: 60")
+            .validator(parse_validator_fn!(u64, "Value must be a valid number")))
+        .arg(Arg::with_name("max-download-speed")
+            .long("max-download-speed")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum number of bytes read per second from all peers together, unlimited if not set")
+            .validator(parse_validator_fn!(NonZeroU64, "Value must be a positive number")))
+        .arg(Arg::with_name("max-upload-speed")
+            .long("max-upload-speed")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum number of bytes written per second to all peers together, unlimited if not set")
+            .validator(parse_validator_fn!(NonZeroU64, "Value must be a positive number")))
+        .arg(Arg::with_name("peer-max-download-speed")
+            .long("peer-max-download-speed")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum number of bytes read per second from a single peer, unlimited if not set")
+            .validator(parse_validator_fn!(NonZeroU64, "Value must be a positive number")))
+        .arg(Arg::with_name("peer-max-upload-speed")
+            .long("peer-max-upload-speed")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum number of bytes written per second to a single peer, unlimited if not set")
+            .validator(parse_validator_fn!(NonZeroU64, "Value must be a positive number")))
+        .arg(Arg::with_name("mempool-max-pending-operations")
+            .long("mempool-max-pending-operations")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum number of operations waiting for the validation in mempool, operations with the lowest fee are evicted")
+            .validator(parse_validator_fn!(usize, "Value must be a valid number")))
+        .arg(Arg::with_name("mempool-max-pending-bytes")
+            .long("mempool-max-pending-bytes")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum total size in bytes of operations waiting for the validation in mempool, operations with the lowest fee are evicted")
+            .validator(parse_validator_fn!(usize, "Value must be a valid number")))
+        .arg(Arg::with_name("mempool-max-validated-operations")
+            .long("mempool-max-validated-operations")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum number of applied operations in mempool, operations with the lowest fee are evicted")
+            .validator(parse_validator_fn!(usize, "Value must be a valid number")))
+        .arg(Arg::with_name("mempool-max-validated-bytes")
+            .long("mempool-max-validated-bytes")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Maximum total size in bytes of applied operations in mempool, operations with the lowest fee are evicted")
+            .validator(parse_validator_fn!(usize, "Value must be a valid number")))
         .arg(Arg::with_name("mempool-downloaded-operation-max-ttl-in-secs")
             .long("mempool-downloaded-operation-max-ttl-in-secs")
             .takes_value(true)
@@ -454,6 +576,25 @@ pub fn tezos_app() -> App<'static, 'static> {
             .value_name("PORT")
             .help("Socket listening port for p2p for communication with tezos world")
             .validator(parse_validator_fn!(u16, "Value must be a valid port number")))
+        .arg(Arg::with_name("p2p-listen-address")
+            .long("p2p-listen-address")
+            .global(true)
+            .takes_value(true)
+            .value_name("IP:PORT")
+            .help("Socket addresses (IPv4 or IPv6) where the node listens for p2p connections, overrides p2p-port. Format: 0.0.0.0:9732,[::]:9732")
+            .validator(|v| {
+                match v.split(',').map(|ip_port| ip_port.parse::<SocketAddr>()).find(|v| v.is_err()) {
+                    None => Ok(()),
+                    Some(_) => Err(format!("Value '{}' is not valid. Expected format is: IP1:PORT1,IP2:PORT2", v)),
+                }
+            }))
+        .arg(Arg::with_name("p2p-public-address")
+            .long("p2p-public-address")
+            .global(true)
+            .takes_value(true)
+            .value_name("IP:PORT")
+            .help("Public address advertised to the peers, if it differs from the listening one (e.g. node behind NAT)")
+            .validator(parse_validator_fn!(SocketAddr, "Value must be a valid IP:PORT")))
         .arg(Arg::with_name("rpc-port")
             .long("rpc-port")
             .global(true)
@@ -502,6 +643,32 @@ pub fn tezos_app() -> App<'static, 'static> {
                     Err(format!("Value '{}' is not valid. Expected format is: IP1:PORT1,IP2:PORT2,IP3:PORT3", v))
                 }
             }))
+        .arg(Arg::with_name("trusted-peers")
+            .long("trusted-peers")
+            .global(true)
+            .takes_value(true)
+            .value_name("IP:PORT")
+            .help("Trusted peers, which are always reconnected, never graylisted and not limited by peer-thresh-high. Format: IP1:PORT1,IP2:PORT2,IP3:PORT3")
+            .validator(|v| {
+                match v.split(',').map(|ip_port| ip_port.parse::<SocketAddr>()).find(|v| v.is_err()) {
+                    None => Ok(()),
+                    Some(_) => Err(format!("Value '{}' is not valid. Expected format is: IP1:PORT1,IP2:PORT2,IP3:PORT3", v)),
+                }
+            }))
+        .arg(Arg::with_name("incoming-allow")
+            .long("incoming-allow")
+            .global(true)
+            .takes_value(true)
+            .value_name("CIDR")
+            .help("Accept incoming connections only from these ip ranges, trusted peers are always accepted. Format: 10.0.0.0/8,fd00::/8")
+            .validator(|v| parse_cidr_list(&v).map(|_| ())))
+        .arg(Arg::with_name("incoming-deny")
+            .long("incoming-deny")
+            .global(true)
+            .takes_value(true)
+            .value_name("CIDR")
+            .help("Reject incoming connections from these ip ranges, trusted peers are always accepted. Format: 10.0.0.0/8,fd00::/8")
+            .validator(|v| parse_cidr_list(&v).map(|_| ())))
         .arg(Arg::with_name("peer-thresh-low")
             .long("peer-thresh-low")
             .global(true)
@@ -564,6 +731,27 @@ pub fn tezos_app() -> App<'static, 'static> {
             .possible_values(&TezedgeDatabaseBackendConfiguration::possible_values())
             .default_value(Storage::DEFAULT_MAINDB)
             .help("Options fo main database backend"))
+        .arg(Arg::with_name("history-mode")
+            .long("history-mode")
+            .takes_value(true)
+            .value_name("STRING")
+            .default_value(Storage::DEFAULT_HISTORY_MODE)
+            .help("History mode of the main database - 'archive' keeps everything, 'full[:<additional_cycles>]' removes blocks metadata below the savepoint, 'rolling[:<additional_cycles>]' also removes blocks below the caboose")
+            .validator(parse_validator_fn!(HistoryMode, "Value must be one of 'archive', 'full[:<additional_cycles>]', 'rolling[:<additional_cycles>]'")))
+        .arg(Arg::with_name("index-account-operations")
+            .long("index-account-operations")
+            .takes_value(false)
+            .help("Index the operations of the applied blocks by the accounts they touch (source, destination, delegate, originated contract), the history of an account is served by the rpc '/dev/chains/main/accounts/:address/operations?role=<source|destination|delegate|originated>', failed and backtracked operations are included"))
+        .arg(Arg::with_name("db-migration-dry-run")
+            .long("db-migration-dry-run")
+            .takes_value(false)
+            .help("Only print the steps needed to migrate the main database to the current version and stop, without changing the database"))
+        .arg(Arg::with_name("protocol-sources-dir")
+            .long("protocol-sources-dir")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Directory with the sources of the protocols (like the 'src' directory of Octez, with a 'TEZOS_PROTOCOL' manifest per protocol), stored on startup so they can be served to peers.
+                       In case it starts with ./ or ../, it is relative path to the current dir, otherwise to the --tezos-data-dir"))
         .arg(Arg::with_name("context-kv-store")
             .long("context-kv-store")
             .global(true)
@@ -571,6 +759,13 @@ pub fn tezos_app() -> App<'static, 'static> {
             .value_name("STRING")
             .possible_values(&SupportedContextKeyValueStore::possible_values())
             .help("Choose the TezEdge context storage backend - supported backends: 'inmem', 'ondisk'"))
+        .arg(Arg::with_name("context-gc-preserve-cycles")
+            .long("context-gc-preserve-cycles")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Enable the garbage collector of the 'ondisk' TezEdge context, keeping the contexts of the last <NUM> cycles (enabled by default for the 'full' and 'rolling' history modes), <NUM> must be greater than 0")
+            .validator(parse_validator_fn!(NonZeroU64, "Value must be a number greater than 0")))
         // TODO - TE-261: right now this is obsolete, either reintegrate with the timings database or remove
         .arg(Arg::with_name("compute-context-action-tree-hashes")
             .long("compute-context-action-tree-hashes")
@@ -590,6 +785,38 @@ pub fn tezos_app() -> App<'static, 'static> {
             .takes_value(false)
             .help("Enable recording/persisting shell automaton actions.")
         )
+        .arg(Arg::with_name("record-p2p-traffic-dir")
+            .long("record-p2p-traffic-dir")
+            .global(true)
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Enable recording of the decrypted p2p messages into the rotating files in this directory.
+                   Path can be absolute or relative to --tezos-data-dir.")
+        )
+        .arg(Arg::with_name("record-p2p-traffic-max-file-size")
+            .long("record-p2p-traffic-max-file-size")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Size in megabytes after which the p2p recording file is rotated, default: 100")
+            .validator(parse_validator_fn!(NonZeroU64, "Value must be a positive number"))
+        )
+        .arg(Arg::with_name("record-p2p-traffic-max-files")
+            .long("record-p2p-traffic-max-files")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("How many p2p recording files are kept, default: 10")
+            .validator(parse_validator_fn!(NonZeroUsize, "Value must be a positive number"))
+        )
+        .arg(Arg::with_name("signature-verifier-threads")
+            .long("signature-verifier-threads")
+            .global(true)
+            .takes_value(true)
+            .value_name("NUM")
+            .help("Number of threads verifying signatures of the operations, default: number of CPUs")
+            .validator(parse_validator_fn!(NonZeroUsize, "Value must be a positive number"))
+        )
         .arg(Arg::with_name("sandbox-patch-context-json-file")
             .long("sandbox-patch-context-json-file")
             .global(true)
@@ -695,6 +922,31 @@ pub fn tezos_app() -> App<'static, 'static> {
                              }
                          }
                      }))
+                .arg(Arg::with_name("format")
+                     .long("format")
+                     .takes_value(true)
+                     .value_name("FORMAT")
+                     .display_order(2)
+                     .required(false)
+                     .possible_values(&["tezedge", "octez"])
+                     .default_value("tezedge")
+                     .help("Format of the snapshot, 'tezedge' produces a trimmed copy of the storage, 'octez' produces a snapshot compatible with Octez"))
+        ).subcommand(
+            clap::SubCommand::with_name("import-octez-snapshot")
+                .arg(Arg::with_name("from")
+                     .long("from")
+                     .takes_value(true)
+                     .value_name("PATH")
+                     .display_order(0)
+                     .required(true)
+                     .help("Path to the Octez snapshot file (rolling)")
+                     .validator(|v| {
+                         if Path::new(&v).is_file() {
+                             Ok(())
+                         } else {
+                             Err(format!("Snapshot file '{}' does not exist", v))
+                         }
+                     }))
         );
     app
 }
@@ -1051,9 +1303,27 @@ impl Environment {
                 );
             });
 
-            StorageSnapshot { block, target_path }
+            let format = match args.value_of("format") {
+                Some("octez") => SnapshotFormat::Octez,
+                _ => SnapshotFormat::Tezedge,
+            };
+
+            StorageSnapshot {
+                block,
+                target_path,
+                format,
+            }
         });
 
+        let import_octez_snapshot = args
+            .subcommand_matches("import-octez-snapshot")
+            .map(|args| {
+                args.value_of("from")
+                    .unwrap()
+                    .parse::<PathBuf>()
+                    .expect("Provided value cannot be converted to path")
+            });
+
         let log_targets: HashSet<String> = match args.values_of("log") {
             Some(v) => v.map(String::from).collect(),
             None => std::iter::once("terminal".to_string()).collect(),
@@ -1125,12 +1395,50 @@ impl Environment {
 
         Environment {
             p2p: crate::configuration::P2p {
-                listener_port,
-                listener_address: format!("0.0.0.0:{}", listener_port)
-                    .parse::<SocketAddr>()
-                    .expect("Failed to parse listener address"),
+                listener_addresses: args
+                    .value_of("p2p-listen-address")
+                    .map(|addresses_str| {
+                        addresses_str
+                            .split(',')
+                            .map(|ip_port| ip_port.parse().expect("Was expecting IP:PORT"))
+                            .collect()
+                    })
+                    .unwrap_or_else(|| vec![SocketAddr::from(([0, 0, 0, 0], listener_port))]),
+                public_address: args
+                    .value_of("p2p-public-address")
+                    .map(|ip_port| ip_port.parse().expect("Was expecting IP:PORT")),
                 disable_bootstrap_lookup: args.is_present("disable-bootstrap-lookup"),
                 disable_peer_graylist: args.is_present("disable-peer-graylist"),
+                peers_score_thresholds: {
+                    let threshold = |name: &str, default: i32| {
+                        args.value_of(name).map_or(default, |v| {
+                            v.parse::<i32>()
+                                .expect("Provided value cannot be converted to number")
+                        })
+                    };
+                    PeersScoreThresholds {
+                        disconnect: threshold(
+                            "peer-score-disconnect-threshold",
+                            PeersScoreThresholds::DEFAULT_DISCONNECT,
+                        ),
+                        graylist: threshold(
+                            "peer-score-graylist-threshold",
+                            PeersScoreThresholds::DEFAULT_GRAYLIST,
+                        ),
+                        ban: threshold(
+                            "peer-score-ban-threshold",
+                            PeersScoreThresholds::DEFAULT_BAN,
+                        ),
+                        recovery_interval: args
+                            .value_of("peer-score-recovery-interval")
+                            .map_or(PeersScoreThresholds::DEFAULT_RECOVERY_INTERVAL, |v| {
+                                Duration::from_secs(
+                                    v.parse::<u64>()
+                                        .expect("Provided value cannot be converted to number"),
+                                )
+                            }),
+                    }
+                },
                 bootstrap_lookup_addresses: args
                     .value_of("bootstrap-lookup-address")
                     .map(|addresses_str| {
@@ -1173,6 +1481,23 @@ impl Environment {
                         }
                     })
                     .unwrap_or_default(),
+                trusted_peers: args
+                    .value_of("trusted-peers")
+                    .map(|peers_str| {
+                        peers_str
+                            .split(',')
+                            .map(|ip_port| ip_port.parse().expect("Was expecting IP:PORT"))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                incoming_allow: args
+                    .value_of("incoming-allow")
+                    .map(|v| parse_cidr_list(v).expect("Was expecting CIDR list"))
+                    .unwrap_or_default(),
+                incoming_deny: args
+                    .value_of("incoming-deny")
+                    .map(|v| parse_cidr_list(v).expect("Was expecting CIDR list"))
+                    .unwrap_or_default(),
                 current_head_level_override: args
                     .value_of("current-head-level-override")
                     .and_then(|level| level.parse().ok()),
@@ -1191,6 +1516,47 @@ impl Environment {
                     }),
                 )
                 .expect("Invalid threashold range"),
+                bandwidth_limit: {
+                    let speed = |name: &str| {
+                        args.value_of(name).map(|v| {
+                            v.parse::<NonZeroU64>()
+                                .expect("Provided value cannot be converted to positive number")
+                                .get()
+                        })
+                    };
+                    BandwidthLimit {
+                        download: speed("max-download-speed"),
+                        upload: speed("max-upload-speed"),
+                        peer_download: speed("peer-max-download-speed"),
+                        peer_upload: speed("peer-max-upload-speed"),
+                    }
+                },
+                mempool_limits: {
+                    let limit = |name: &str, default: usize| {
+                        args.value_of(name).map_or(default, |v| {
+                            v.parse::<usize>()
+                                .expect("Provided value cannot be converted to number")
+                        })
+                    };
+                    MempoolLimits {
+                        pending_max_count: limit(
+                            "mempool-max-pending-operations",
+                            MempoolLimits::DEFAULT_PENDING_MAX_COUNT,
+                        ),
+                        pending_max_bytes: limit(
+                            "mempool-max-pending-bytes",
+                            MempoolLimits::DEFAULT_PENDING_MAX_BYTES,
+                        ),
+                        validated_max_count: limit(
+                            "mempool-max-validated-operations",
+                            MempoolLimits::DEFAULT_VALIDATED_MAX_COUNT,
+                        ),
+                        validated_max_bytes: limit(
+                            "mempool-max-validated-bytes",
+                            MempoolLimits::DEFAULT_VALIDATED_MAX_BYTES,
+                        ),
+                    }
+                },
                 private_node: args
                     .value_of("private-node")
                     .unwrap_or("false")
@@ -1207,6 +1573,13 @@ impl Environment {
                         s.parse()
                             .expect("Boolean value expected for disable-endorsements-precheck")
                     }),
+                disable_manager_operations_precheck: args
+                    .value_of("disable-manager-operations-precheck")
+                    .map_or(false, |s| {
+                        s.parse().expect(
+                            "Boolean value expected for disable-manager-operations-precheck",
+                        )
+                    }),
                 randomness_seed: args.value_of("randomness-seed").map(|s| {
                     s.parse::<u64>()
                         .expect("Provided value cannot be converted to u64")
@@ -1214,6 +1587,36 @@ impl Environment {
                 record_shell_automaton_state_snapshots: args
                     .is_present("record-shell-automaton-state-snapshots"),
                 record_shell_automaton_actions: args.is_present("record-shell-automaton-actions"),
+                p2p_recorder: args.value_of("record-p2p-traffic-dir").map(|path| {
+                    P2pRecorderConfig {
+                        dir: get_final_path(&tezos_data_dir, PathBuf::from(path)),
+                        max_file_size: args.value_of("record-p2p-traffic-max-file-size").map_or(
+                            P2pRecorderConfig::DEFAULT_MAX_FILE_SIZE,
+                            |v| {
+                                v.parse::<NonZeroU64>()
+                                    .expect("Provided value cannot be converted to positive number")
+                                    .get()
+                                    * 1024
+                                    * 1024
+                            },
+                        ),
+                        max_files: args.value_of("record-p2p-traffic-max-files").map_or(
+                            P2pRecorderConfig::DEFAULT_MAX_FILES,
+                            |v| {
+                                v.parse::<NonZeroUsize>()
+                                    .expect("Provided value cannot be converted to positive number")
+                                    .get()
+                            },
+                        ),
+                    }
+                }),
+                signature_verifier_threads: args
+                    .value_of("signature-verifier-threads")
+                    .map_or_else(num_cpus::get, |v| {
+                        v.parse::<NonZeroUsize>()
+                            .expect("Provided value cannot be converted to positive number")
+                            .get()
+                    }),
             },
             rpc: crate::configuration::Rpc {
                 listener_port: args
@@ -1382,9 +1785,22 @@ impl Environment {
                     ),
                 };
 
+                let history_mode = args
+                    .value_of("history-mode")
+                    .unwrap_or(Storage::DEFAULT_HISTORY_MODE)
+                    .parse::<HistoryMode>()
+                    .unwrap_or_else(|e| panic!("{}", e));
+
                 crate::configuration::Storage {
                     db,
                     context_storage_configuration,
+                    context_gc_preserve_cycles: args.value_of("context-gc-preserve-cycles").map(
+                        |value| {
+                            value
+                                .parse::<NonZeroU64>()
+                                .expect("Provided value must be a number greater than 0")
+                        },
+                    ),
                     main_db: maindb_backend,
                     db_path,
                     context_stats_db_path,
@@ -1435,6 +1851,15 @@ impl Environment {
                             .parse::<u64>()
                             .expect("Provided value cannot be converted to number"),
                     ),
+                    history_mode,
+                    index_account_operations: args.is_present("index-account-operations"),
+                    db_migration_dry_run: args.is_present("db-migration-dry-run"),
+                    protocol_sources_dir: args.value_of("protocol-sources-dir").map(|value| {
+                        let path = value
+                            .parse::<PathBuf>()
+                            .expect("Provided value cannot be converted to path");
+                        get_final_path(&tezos_data_dir, path)
+                    }),
                 }
             },
             identity: crate::configuration::Identity {
@@ -1469,6 +1894,7 @@ impl Environment {
             },
             replay,
             snapshot,
+            import_octez_snapshot,
             tokio_threads: args
                 .value_of("tokio-threads")
                 .unwrap_or("0")
@@ -25,7 +25,7 @@ use storage::persistent::sequence::Sequences;
 use storage::persistent::{open_cl, CommitLogSchema};
 use storage::{
     hydrate_current_head, resolve_storage_init_chain_data, BlockHeaderWithHash, BlockStorage,
-    PersistentStorage, StorageInitInfo,
+    PersistentStorage, ProtocolStorage, SnapshotFormat, StorageInitInfo, SystemStorage,
 };
 use storage::{
     initializer::{initialize_rocksdb, GlobalRocksDbCacheHolder, MainChain, RocksDbCache},
@@ -42,11 +42,14 @@ use crate::configuration::Environment;
 use crate::notification_integration::RpcNotificationCallbackActor;
 use crate::snapshot_command::snapshot_storage;
 use storage::database::tezedge_database::TezedgeDatabaseBackendConfiguration;
+use storage::history_mode::{check_history_mode, head_preserved_cycles, DEFAULT_PRESERVED_CYCLES};
 use storage::initializer::initialize_maindb;
+use storage::migration::{migrate_database, migrations};
 
 mod configuration;
 mod identity;
 mod notification_integration;
+mod octez_snapshot;
 mod snapshot_command;
 mod system;
 
@@ -79,8 +82,11 @@ fn create_tokio_runtime_default() -> std::io::Result<tokio::runtime::Runtime> {
     builder.build()
 }
 
+/// `preserved_cycles` - constant of the active protocol, used to derive the number of cycles
+/// preserved by the garbage collector of the context from the history mode
 fn create_protocol_runner_configuration(
     env: &crate::configuration::Environment,
+    preserved_cycles: u8,
 ) -> ProtocolRunnerConfiguration {
     ProtocolRunnerConfiguration::new(
         TezosRuntimeConfiguration {
@@ -90,6 +96,7 @@ fn create_protocol_runner_configuration(
         env.tezos_network_config.clone(),
         env.enable_testchain,
         env.storage.context_storage_configuration.clone(),
+        env.storage.context_gc_retained_cycles(preserved_cycles),
         env.ffi.protocol_runner.clone(),
         env.logging.slog.level,
     )
@@ -112,12 +119,21 @@ fn block_on_actors(
         shell::SUPPORTED_P2P_VERSION.to_vec(),
     ));
 
+    enable_history_mode(&env, &persistent_storage, &log);
+    seed_protocol_storage(&env, &persistent_storage, &log);
+
     // create tokio runtime
     let tokio_runtime = create_tokio_runtime(&env).expect("Failed to create tokio runtime");
 
     let (context_init_status_sender, context_init_status_receiver) =
         tokio::sync::watch::channel(false);
-    let protocol_runner_configuration = create_protocol_runner_configuration(&env);
+    let preserved_cycles = head_preserved_cycles(&persistent_storage, &init_storage_data.chain_id)
+        .unwrap_or_else(|e| {
+            warn!(log, "Failed to read the preserved cycles of the active protocol"; "reason" => format!("{}", e));
+            DEFAULT_PRESERVED_CYCLES
+        });
+    let protocol_runner_configuration =
+        create_protocol_runner_configuration(&env, preserved_cycles);
     let tezos_protocol_api = ProtocolRunnerApi::new(
         protocol_runner_configuration.clone(),
         context_init_status_receiver,
@@ -167,6 +183,8 @@ fn block_on_actors(
         env.identity.expected_pow,
         init_storage_data.clone(),
         protocol_runner_configuration,
+        env.storage.history_mode,
+        env.storage.index_account_operations,
         context_init_status_sender,
     );
 
@@ -565,6 +583,10 @@ fn main() {
 
             {
                 let persistent_storage = initialize_persistent_storage(&env, &log);
+                if env.storage.db_migration_dry_run {
+                    info!(log, "Database migration dry run finished");
+                    return;
+                }
 
                 match resolve_storage_init_chain_data(
                     &env.tezos_network_config,
@@ -589,12 +611,29 @@ fn main() {
                         if let Some(snapshot) = &env.snapshot {
                             let target_block = snapshot.block.clone();
                             let target_path = snapshot.target_path.clone();
-                            snapshot_storage(
+                            match snapshot.format {
+                                SnapshotFormat::Tezedge => snapshot_storage(
+                                    env,
+                                    persistent_storage,
+                                    init_storage_data,
+                                    target_block,
+                                    target_path,
+                                    log,
+                                ),
+                                SnapshotFormat::Octez => octez_snapshot::export_snapshot(
+                                    env,
+                                    persistent_storage,
+                                    target_block,
+                                    target_path,
+                                    log,
+                                ),
+                            }
+                        } else if let Some(from) = env.import_octez_snapshot.clone() {
+                            octez_snapshot::import_snapshot(
                                 env,
                                 persistent_storage,
                                 init_storage_data,
-                                target_block,
-                                target_path,
+                                &from,
                                 log,
                             )
                         } else {
@@ -661,6 +700,43 @@ fn parse_environment() -> configuration::TezedgeEnv {
     configuration::TezedgeEnv::Normal(crate::configuration::Environment::from_args())
 }
 
+/// Checks that the database can be used with the configured history mode.
+///
+/// The garbage collector of the context is enabled for `full` and `rolling` modes
+/// (see `Storage::context_gc_retained_cycles`).
+fn enable_history_mode(env: &Environment, persistent_storage: &PersistentStorage, log: &Logger) {
+    let history_mode = env.storage.history_mode;
+    let mut system_storage = SystemStorage::new(persistent_storage.main_db());
+    if let Err(e) = check_history_mode(&mut system_storage, history_mode) {
+        error!(log, "Invalid history mode"; "reason" => format!("{}", e));
+        panic!("Invalid history mode, reason: {}", e);
+    }
+
+    info!(log, "History mode"; "history_mode" => history_mode.to_string());
+}
+
+/// Stores the sources of the protocols from `--protocol-sources-dir`,
+/// so peers asking for them with `GetProtocols` can be served.
+fn seed_protocol_storage(env: &Environment, persistent_storage: &PersistentStorage, log: &Logger) {
+    let sources_dir = match env.storage.protocol_sources_dir.as_ref() {
+        Some(sources_dir) => sources_dir,
+        None => return,
+    };
+
+    match ProtocolStorage::new(persistent_storage).seed_from_sources(sources_dir, log) {
+        Ok(protocols) => {
+            info!(log, "Protocol sources stored";
+                       "path" => sources_dir.display().to_string(),
+                       "protocols" => protocols.iter().map(|p| p.to_base58_check()).collect::<Vec<_>>().join(", "));
+        }
+        Err(e) => {
+            warn!(log, "Failed to store the protocol sources";
+                       "path" => sources_dir.display().to_string(),
+                       "reason" => format!("{}", e));
+        }
+    }
+}
+
 // TODO: needs to take a path and other stuff, not just env?
 fn initialize_persistent_storage(env: &Environment, log: &Logger) -> PersistentStorage {
     // create common RocksDB block cache to be shared among column families
@@ -721,6 +797,28 @@ fn initialize_persistent_storage(env: &Environment, log: &Logger) -> PersistentS
         .expect("Failed to open plain block_header storage"),
     );
     let sequences = Arc::new(Sequences::new(maindb.clone(), 1000));
+    let persistent_storage = PersistentStorage::new(maindb, commit_logs, sequences);
+
+    // migrate older database to the expected version
+    match migrate_database(
+        &persistent_storage,
+        migrations(),
+        env.storage.db.expected_db_version,
+        env.storage.db_migration_dry_run,
+        log,
+    ) {
+        Ok(report) => {
+            if env.storage.db_migration_dry_run {
+                info!(log, "Database migration plan";
+                           "steps" => format!("{:?}", report.steps),
+                           "records" => report.records);
+            }
+        }
+        Err(e) => {
+            error!(log, "Failed to migrate database"; "reason" => format!("{}", e));
+            panic!("Failed to migrate database, reason: {}", e);
+        }
+    }
 
-    PersistentStorage::new(maindb, commit_logs, sequences)
+    persistent_storage
 }
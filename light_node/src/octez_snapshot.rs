@@ -0,0 +1,1363 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Export and import of snapshots in the Octez format.
+//!
+//! An Octez snapshot is a tar archive with the following files, under the `snapshot/` directory:
+//! - `VERSION`: version of the snapshot format, in JSON
+//! - `METADATA`: chain name, history mode, hash and level of the snapshot block, in JSON
+//! - `block_data`: header and operations of the snapshot block, and the header and
+//!   metadata hashes of its predecessor
+//! - `rolling_floating_blocks`: the predecessors of the snapshot block, required
+//!   to validate the operations of the next blocks (`max_operations_ttl`)
+//! - `protocols/`: sources of the protocols which are not embedded in Octez
+//! - `context`: dump of the context of the predecessor of the snapshot block
+//!
+//! Like Octez, the import restores the context of the predecessor, then applies the snapshot
+//! block on it, which gives the metadata of the snapshot block (`max_operations_ttl`, protocols,
+//! metadata hashes...) and checks that the resulting context is the one of the snapshot block.
+//! The context is dumped and restored by the protocol runner
+//! (`ProtocolRunnerConnection::dump_context`/`restore_context`).
+//!
+//! Only rolling snapshots are produced. The cemented blocks of full snapshots are not imported,
+//! so both rolling and full snapshots can only be imported by a node in `rolling` history mode.
+//! The node can only run the protocols it embeds, the import fails if the snapshot requires
+//! a protocol from `protocols/` which is not supported. TezEdge embeds a subset of the protocols
+//! embedded in Octez, so no protocol is exported.
+//!
+//! Example:
+//!
+//! ```
+//! ./target/release/light-node \
+//!     --config-file ./light_node/etc/tezedge/tezedge.config \
+//!     --tezos-data-dir /path/to/source \
+//!     snapshot \
+//!     --format octez \
+//!     --target-path /path/to/target
+//!
+//! ./target/release/light-node \
+//!     --config-file ./light_node/etc/tezedge/tezedge.config \
+//!     --tezos-data-dir /path/to/empty/dir \
+//!     --history-mode rolling \
+//!     import-octez-snapshot \
+//!     --from /path/to/target/TEZOS_MAINNET-1234.rolling
+//! ```
+
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use tar::{Archive, Builder, Header};
+use tempfile::tempdir_in;
+use thiserror::Error;
+
+use crypto::hash::{
+    BlockHash, BlockMetadataHash, ChainId, ContextHash, OperationMetadataHash,
+    OperationMetadataListListHash, ProtocolHash,
+};
+use storage::history_mode::DEFAULT_PRESERVED_CYCLES;
+use storage::{
+    store_applied_block_result, BlockHeaderWithHash, BlockMetaStorage, BlockMetaStorageReader,
+    BlockReference, BlockStorage, BlockStorageReader, ChainMetaStorage, ChainMetaStorageReader,
+    ConstantsStorage, CycleErasStorage, CycleMetaStorage, HistoryMode, OperationsStorage,
+    OperationsStorageReader, PersistentStorage, StorageInitInfo, SystemStorage,
+};
+use tezos_api::ffi::{ApplyBlockRequest, ApplyBlockResponse};
+use tezos_messages::p2p::binary_message::{BinaryRead, BinaryWrite};
+use tezos_messages::p2p::encoding::prelude::{
+    BlockHeader, Operation, OperationsForBlock, OperationsForBlocksMessage, Path as OperationsPath,
+};
+use tezos_messages::protocol::SupportedProtocol;
+use tezos_messages::{ts_to_rfc3339, Head};
+use tezos_protocol_ipc_client::ProtocolRunnerApi;
+
+use crate::snapshot_command::{resolve_block_reference, terminate_or_kill};
+use crate::{create_protocol_runner_configuration, create_tokio_runtime};
+
+/// Version of the Octez snapshot format
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+const SNAPSHOT_ROOT: &str = "snapshot";
+const VERSION_FILE: &str = "VERSION";
+const METADATA_FILE: &str = "METADATA";
+const BLOCK_DATA_FILE: &str = "block_data";
+const FLOATING_BLOCKS_FILE: &str = "rolling_floating_blocks";
+const PROTOCOLS_DIR: &str = "protocols";
+const CONTEXT_FILE: &str = "context";
+
+const HISTORY_MODE_ROLLING: &str = "rolling";
+const HISTORY_MODE_FULL: &str = "full";
+
+const HASH_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum OctezSnapshotError {
+    #[error("I/O error: {reason}")]
+    IoError {
+        #[from]
+        reason: std::io::Error,
+    },
+    #[error("Invalid JSON: {reason}")]
+    JsonError {
+        #[from]
+        reason: serde_json::Error,
+    },
+    #[error("Invalid encoding of {what}")]
+    EncodingError { what: &'static str },
+    #[error(
+        "Unsupported snapshot version {version}, expected {}",
+        SNAPSHOT_VERSION
+    )]
+    UnsupportedVersion { version: u32 },
+    #[error("Unsupported snapshot history mode {history_mode}")]
+    UnsupportedHistoryMode { history_mode: String },
+    #[error(
+        "Cannot import a {snapshot} snapshot in {node} history mode, only rolling is supported"
+    )]
+    HistoryModeMismatch { snapshot: String, node: HistoryMode },
+    #[error("The snapshot requires the protocol {protocol}, which is not supported")]
+    UnsupportedProtocol { protocol: String },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SnapshotMetadata {
+    pub chain_name: String,
+    pub history_mode: String,
+    pub block_hash: String,
+    pub level: i32,
+    pub timestamp: String,
+    pub context_elements: i64,
+}
+
+/// Header and operations of a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotBlock {
+    pub header: BlockHeader,
+    pub operations: Vec<Vec<Operation>>,
+}
+
+/// Content of the `block_data` file.
+///
+/// The predecessor is required to apply the snapshot block on the restored context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotBlockData {
+    pub block: SnapshotBlock,
+    pub predecessor_header: BlockHeader,
+    pub predecessor_max_operations_ttl: i32,
+    pub predecessor_block_metadata_hash: Option<BlockMetadataHash>,
+    pub predecessor_ops_metadata_hash: Option<OperationMetadataListListHash>,
+}
+
+/// Block of the `rolling_floating_blocks` file (`Block_repr` in Octez), without its metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatingBlock {
+    pub hash: BlockHash,
+    pub block: SnapshotBlock,
+    pub block_metadata_hash: Option<BlockMetadataHash>,
+    pub ops_metadata_hashes: Option<Vec<Vec<OperationMetadataHash>>>,
+}
+
+/// Encoding of the Octez `data_encoding` library, the length of the value is written
+/// on 4 bytes before the value (`dynamic_size`)
+fn write_dynamic(output: &mut Vec<u8>, bytes: &[u8]) {
+    output.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    output.extend_from_slice(bytes);
+}
+
+/// `option` of `data_encoding`: a `0x00` or `0xff` tag before the value
+fn write_option<T, F: FnOnce(&mut Vec<u8>, &T)>(output: &mut Vec<u8>, value: Option<&T>, f: F) {
+    match value {
+        Some(value) => {
+            output.push(0xff);
+            f(output, value);
+        }
+        None => output.push(0x00),
+    }
+}
+
+fn write_hash_list_list(output: &mut Vec<u8>, hashes: &[Vec<OperationMetadataHash>]) {
+    let mut passes = Vec::new();
+    for pass in hashes {
+        let mut bytes = Vec::with_capacity(pass.len() * HASH_LEN);
+        for hash in pass {
+            bytes.extend_from_slice(hash.as_ref());
+        }
+        write_dynamic(&mut passes, &bytes);
+    }
+    write_dynamic(output, &passes);
+}
+
+struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_fixed(
+        &mut self,
+        length: usize,
+        what: &'static str,
+    ) -> Result<&'a [u8], OctezSnapshotError> {
+        if self.bytes.len() < length {
+            return Err(OctezSnapshotError::EncodingError { what });
+        }
+        let (value, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(value)
+    }
+
+    fn read_dynamic(&mut self, what: &'static str) -> Result<&'a [u8], OctezSnapshotError> {
+        let length = self.read_fixed(4, what)?;
+        let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]);
+        self.read_fixed(length as usize, what)
+    }
+
+    fn read_i32(&mut self, what: &'static str) -> Result<i32, OctezSnapshotError> {
+        let bytes = self.read_fixed(4, what)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_option_tag(&mut self, what: &'static str) -> Result<bool, OctezSnapshotError> {
+        match self.read_fixed(1, what)? {
+            [0x00] => Ok(false),
+            [0xff] => Ok(true),
+            _ => Err(OctezSnapshotError::EncodingError { what }),
+        }
+    }
+
+    fn read_hash<H>(&mut self, what: &'static str) -> Result<H, OctezSnapshotError>
+    where
+        H: for<'h> TryFrom<&'h [u8]>,
+    {
+        H::try_from(self.read_fixed(HASH_LEN, what)?)
+            .map_err(|_| OctezSnapshotError::EncodingError { what })
+    }
+
+    fn read_option_hash<H>(&mut self, what: &'static str) -> Result<Option<H>, OctezSnapshotError>
+    where
+        H: for<'h> TryFrom<&'h [u8]>,
+    {
+        if self.read_option_tag(what)? {
+            self.read_hash(what).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn read_hash_list_list(
+        &mut self,
+        what: &'static str,
+    ) -> Result<Vec<Vec<OperationMetadataHash>>, OctezSnapshotError> {
+        let mut passes = SnapshotReader::new(self.read_dynamic(what)?);
+        let mut result = Vec::with_capacity(4);
+
+        while !passes.is_empty() {
+            let mut pass = SnapshotReader::new(passes.read_dynamic(what)?);
+            let mut hashes = Vec::new();
+            while !pass.is_empty() {
+                hashes.push(pass.read_hash(what)?);
+            }
+            result.push(hashes);
+        }
+
+        Ok(result)
+    }
+}
+
+impl SnapshotBlock {
+    fn encode_operations(&self, output: &mut Vec<u8>) -> Result<(), OctezSnapshotError> {
+        // list (list (dynamic_size Operation.encoding))
+        let mut passes = Vec::new();
+        for operations in &self.operations {
+            let mut pass = Vec::new();
+            for operation in operations {
+                let bytes = operation
+                    .as_bytes()
+                    .map_err(|_| OctezSnapshotError::EncodingError { what: "operation" })?;
+                write_dynamic(&mut pass, &bytes);
+            }
+            write_dynamic(&mut passes, &pass);
+        }
+        write_dynamic(output, &passes);
+        Ok(())
+    }
+
+    fn decode_operations(
+        reader: &mut SnapshotReader,
+    ) -> Result<Vec<Vec<Operation>>, OctezSnapshotError> {
+        let mut passes = SnapshotReader::new(reader.read_dynamic("operations")?);
+        let mut result = Vec::with_capacity(4);
+
+        while !passes.is_empty() {
+            let mut pass = SnapshotReader::new(passes.read_dynamic("operations")?);
+            let mut operations = Vec::new();
+
+            while !pass.is_empty() {
+                let bytes = pass.read_dynamic("operation")?;
+                let operation = Operation::from_bytes(bytes)
+                    .map_err(|_| OctezSnapshotError::EncodingError { what: "operation" })?;
+                operations.push(operation);
+            }
+
+            result.push(operations);
+        }
+
+        Ok(result)
+    }
+
+    fn encode_header(header: &BlockHeader, output: &mut Vec<u8>) -> Result<(), OctezSnapshotError> {
+        let bytes = header
+            .as_bytes()
+            .map_err(|_| OctezSnapshotError::EncodingError {
+                what: "block header",
+            })?;
+        write_dynamic(output, &bytes);
+        Ok(())
+    }
+
+    fn decode_header(reader: &mut SnapshotReader) -> Result<BlockHeader, OctezSnapshotError> {
+        BlockHeader::from_bytes(reader.read_dynamic("block header")?).map_err(|_| {
+            OctezSnapshotError::EncodingError {
+                what: "block header",
+            }
+        })
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) -> Result<(), OctezSnapshotError> {
+        Self::encode_header(&self.header, output)?;
+        self.encode_operations(output)
+    }
+
+    fn decode(reader: &mut SnapshotReader) -> Result<Self, OctezSnapshotError> {
+        let header = Self::decode_header(reader)?;
+        let operations = Self::decode_operations(reader)?;
+        Ok(Self { header, operations })
+    }
+}
+
+impl SnapshotBlockData {
+    /// Encoding of the `block_data` file:
+    ///
+    /// | block | predecessor header | predecessor ttl (int31) | option hash | option hash |
+    pub fn encode(&self) -> Result<Vec<u8>, OctezSnapshotError> {
+        let mut output = Vec::new();
+        self.block.encode(&mut output)?;
+        SnapshotBlock::encode_header(&self.predecessor_header, &mut output)?;
+        output.extend_from_slice(&self.predecessor_max_operations_ttl.to_be_bytes());
+        write_option(
+            &mut output,
+            self.predecessor_block_metadata_hash.as_ref(),
+            |output, hash| output.extend_from_slice(hash.as_ref()),
+        );
+        write_option(
+            &mut output,
+            self.predecessor_ops_metadata_hash.as_ref(),
+            |output, hash| output.extend_from_slice(hash.as_ref()),
+        );
+        Ok(output)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, OctezSnapshotError> {
+        let mut reader = SnapshotReader::new(bytes);
+        let block = SnapshotBlock::decode(&mut reader)?;
+        let predecessor_header = SnapshotBlock::decode_header(&mut reader)?;
+        let predecessor_max_operations_ttl = reader.read_i32("predecessor max_operations_ttl")?;
+        let predecessor_block_metadata_hash =
+            reader.read_option_hash("predecessor block metadata hash")?;
+        let predecessor_ops_metadata_hash =
+            reader.read_option_hash("predecessor operations metadata hash")?;
+
+        if !reader.is_empty() {
+            return Err(OctezSnapshotError::EncodingError { what: "block data" });
+        }
+
+        Ok(Self {
+            block,
+            predecessor_header,
+            predecessor_max_operations_ttl,
+            predecessor_block_metadata_hash,
+            predecessor_ops_metadata_hash,
+        })
+    }
+}
+
+impl FloatingBlock {
+    /// Encoding of a block of the floating store (`Block_repr` in Octez), without its metadata:
+    ///
+    /// | hash | block | option block metadata hash | option operations metadata hashes |
+    fn encode(&self, output: &mut Vec<u8>) -> Result<(), OctezSnapshotError> {
+        let mut block = Vec::new();
+        block.extend_from_slice(self.hash.as_ref());
+        self.block.encode(&mut block)?;
+        write_option(
+            &mut block,
+            self.block_metadata_hash.as_ref(),
+            |output, hash| output.extend_from_slice(hash.as_ref()),
+        );
+        write_option(
+            &mut block,
+            self.ops_metadata_hashes.as_ref(),
+            |output, hashes| write_hash_list_list(output, hashes),
+        );
+        // The metadata is a `varopt`, it is absent when the block ends here
+        write_dynamic(output, &block);
+        Ok(())
+    }
+
+    pub fn encode_all(blocks: &[Self]) -> Result<Vec<u8>, OctezSnapshotError> {
+        let mut output = Vec::new();
+        for block in blocks {
+            block.encode(&mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Decodes the blocks of the `rolling_floating_blocks` file.
+    ///
+    /// The metadata of the blocks are ignored, they are not needed to validate
+    /// the operations of the next blocks.
+    pub fn decode_all(bytes: &[u8]) -> Result<Vec<Self>, OctezSnapshotError> {
+        let mut reader = SnapshotReader::new(bytes);
+        let mut blocks = Vec::new();
+
+        while !reader.is_empty() {
+            let mut block = SnapshotReader::new(reader.read_dynamic("floating block")?);
+            let hash: BlockHash = block.read_hash("block hash")?;
+            let snapshot_block = SnapshotBlock::decode(&mut block)?;
+            let block_metadata_hash = block.read_option_hash("block metadata hash")?;
+            let ops_metadata_hashes = if block.read_option_tag("operations metadata hashes")? {
+                Some(block.read_hash_list_list("operations metadata hashes")?)
+            } else {
+                None
+            };
+
+            blocks.push(Self {
+                hash,
+                block: snapshot_block,
+                block_metadata_hash,
+                ops_metadata_hashes,
+            });
+        }
+
+        Ok(blocks)
+    }
+}
+
+fn read_block(
+    block_hash: &BlockHash,
+    block_storage: &BlockStorage,
+    operations_storage: &OperationsStorage,
+) -> (BlockHeaderWithHash, SnapshotBlock) {
+    let block = block_storage
+        .get(block_hash)
+        .expect("Failed to obtain block header")
+        .unwrap_or_else(|| panic!("Block {} not found", block_hash.to_base58_check()));
+
+    let mut messages = operations_storage
+        .get_operations(block_hash)
+        .expect("Failed to obtain operations for block");
+    messages.sort_by_key(|message| message.operations_for_block().validation_pass());
+
+    let operations = messages
+        .into_iter()
+        .map(|message| message.operations().clone())
+        .collect();
+
+    let snapshot_block = SnapshotBlock {
+        header: block.header.as_ref().clone(),
+        operations,
+    };
+
+    (block, snapshot_block)
+}
+
+fn append_file(builder: &mut Builder<File>, name: &str, bytes: &[u8]) {
+    let mut header = Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+
+    builder
+        .append_data(&mut header, Path::new(SNAPSHOT_ROOT).join(name), bytes)
+        .unwrap_or_else(|e| panic!("Failed to write {} in the snapshot: {}", name, e));
+}
+
+fn append_dir(builder: &mut Builder<File>, name: &str) {
+    let mut header = Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_size(0);
+    header.set_mode(0o755);
+    header.set_cksum();
+
+    builder
+        .append_data(
+            &mut header,
+            Path::new(SNAPSHOT_ROOT).join(name),
+            std::io::empty(),
+        )
+        .unwrap_or_else(|e| panic!("Failed to write {} in the snapshot: {}", name, e));
+}
+
+/// Export the block `target_block`, its predecessors and the context of its predecessor
+/// in an Octez snapshot
+pub fn export_snapshot(
+    env: crate::configuration::Environment,
+    persistent_storage: PersistentStorage,
+    target_block: Option<BlockReference>,
+    target_path: PathBuf,
+    log: Logger,
+) {
+    let target_block = target_block.unwrap_or(BlockReference::OffsetFromHead(10));
+
+    let system_storage = SystemStorage::new(persistent_storage.main_db());
+    let block_storage = BlockStorage::new(&persistent_storage);
+    let block_meta_storage = BlockMetaStorage::new(&persistent_storage);
+    let operations_storage = OperationsStorage::new(&persistent_storage);
+    let chain_meta_storage = ChainMetaStorage::new(&persistent_storage);
+
+    let chain_id = system_storage
+        .get_chain_id()
+        .expect("Failed to obtain chain id from storage")
+        .expect("Failed to obtain chain id from storage");
+
+    let head = chain_meta_storage
+        .get_current_head(&chain_id)
+        .expect("Failed to obtain the current head from the storage")
+        .expect("Storage does not have a current head");
+
+    let target_block =
+        resolve_block_reference(target_block, &block_storage, &block_meta_storage, &head);
+
+    let (block, snapshot_block) = read_block(&target_block, &block_storage, &operations_storage);
+    let additional_data = block_meta_storage
+        .get_additional_data(&target_block)
+        .expect("Failed to obtain additional block data")
+        .expect("The snapshot block must be applied");
+
+    let predecessor_hash = block.header.predecessor().clone();
+    let predecessor = block_storage
+        .get(&predecessor_hash)
+        .expect("Failed to obtain block header")
+        .expect("The predecessor of the snapshot block is missing");
+    let predecessor_additional_data = block_meta_storage
+        .get_additional_data(&predecessor_hash)
+        .expect("Failed to obtain additional block data")
+        .expect("The predecessor of the snapshot block must be applied");
+
+    info!(log, "Exporting Octez snapshot"; "block_hash" => target_block.to_base58_check(), "level" => block.header.level());
+
+    // The predecessors required to validate the operations of the next blocks
+    let mut floating_blocks = Vec::new();
+    let mut predecessor_hash = predecessor_hash;
+    for _ in 0..additional_data.max_operations_ttl() {
+        if block_storage
+            .get(&predecessor_hash)
+            .expect("Failed to obtain block header")
+            .is_none()
+        {
+            break;
+        }
+        let (block, snapshot_block) =
+            read_block(&predecessor_hash, &block_storage, &operations_storage);
+        if block.header.level() == 0 {
+            break;
+        }
+        let block_additional_data = block_meta_storage
+            .get_additional_data(&block.hash)
+            .expect("Failed to obtain additional block data");
+
+        predecessor_hash = block.header.predecessor().clone();
+        floating_blocks.push(FloatingBlock {
+            hash: block.hash,
+            block: snapshot_block,
+            block_metadata_hash: block_additional_data
+                .as_ref()
+                .and_then(|data| data.block_metadata_hash().clone()),
+            ops_metadata_hashes: block_additional_data
+                .as_ref()
+                .and_then(|data| data.ops_metadata_hashes().clone()),
+        });
+    }
+    floating_blocks.reverse();
+
+    let tmpdir =
+        tempdir_in(&target_path).expect("Could not create a temporary directory for the snapshot");
+    let context_dump_path = tmpdir.path().join(CONTEXT_FILE);
+    let context_elements =
+        dump_context(&env, predecessor.header.context(), &context_dump_path, &log);
+
+    let metadata = SnapshotMetadata {
+        chain_name: env.tezos_network_config.version.clone(),
+        history_mode: HISTORY_MODE_ROLLING.to_string(),
+        block_hash: target_block.to_base58_check(),
+        level: block.header.level(),
+        timestamp: ts_to_rfc3339(block.header.timestamp().i64())
+            .unwrap_or_else(|_| block.header.timestamp().i64().to_string()),
+        context_elements,
+    };
+
+    let block_data = SnapshotBlockData {
+        block: snapshot_block,
+        predecessor_header: predecessor.header.as_ref().clone(),
+        predecessor_max_operations_ttl: predecessor_additional_data.max_operations_ttl() as i32,
+        predecessor_block_metadata_hash: predecessor_additional_data.block_metadata_hash().clone(),
+        predecessor_ops_metadata_hash: predecessor_additional_data.ops_metadata_hash().clone(),
+    };
+
+    let snapshot_file_path = target_path.join(format!(
+        "{}-{}.{}",
+        metadata.chain_name, metadata.level, HISTORY_MODE_ROLLING
+    ));
+
+    info!(log, "Writing snapshot"; "path" => snapshot_file_path.display().to_string());
+
+    let file = File::create(&snapshot_file_path).expect("Failed to create the snapshot file");
+    let mut builder = Builder::new(file);
+
+    let version = serde_json::to_vec(&SnapshotVersion {
+        version: SNAPSHOT_VERSION,
+    })
+    .expect("Failed to encode the snapshot version");
+    append_file(&mut builder, VERSION_FILE, &version);
+
+    let metadata_bytes =
+        serde_json::to_vec(&metadata).expect("Failed to encode the snapshot metadata");
+    append_file(&mut builder, METADATA_FILE, &metadata_bytes);
+
+    let block_data = block_data
+        .encode()
+        .expect("Failed to encode the snapshot block");
+    append_file(&mut builder, BLOCK_DATA_FILE, &block_data);
+
+    let floating_bytes =
+        FloatingBlock::encode_all(&floating_blocks).expect("Failed to encode the snapshot history");
+    append_file(&mut builder, FLOATING_BLOCKS_FILE, &floating_bytes);
+
+    // All the protocols supported by TezEdge are embedded in Octez
+    append_dir(&mut builder, PROTOCOLS_DIR);
+
+    builder
+        .append_path_with_name(
+            &context_dump_path,
+            Path::new(SNAPSHOT_ROOT).join(CONTEXT_FILE),
+        )
+        .expect("Failed to write the context in the snapshot");
+
+    builder.finish().expect("Failed to write the snapshot");
+
+    info!(log, "Octez snapshot exported"; "path" => snapshot_file_path.display().to_string(), "history_blocks" => floating_blocks.len(), "context_elements" => context_elements);
+}
+
+fn dump_context(
+    env: &crate::configuration::Environment,
+    context_hash: &ContextHash,
+    context_dump_path: &Path,
+    log: &Logger,
+) -> i64 {
+    let tokio_runtime = create_tokio_runtime(env).expect("Failed to create tokio runtime");
+
+    let (_context_init_status_sender, context_init_status_receiver) =
+        tokio::sync::watch::channel(false);
+    let mut tezos_protocol_api = ProtocolRunnerApi::new(
+        create_protocol_runner_configuration(env, DEFAULT_PRESERVED_CYCLES),
+        context_init_status_receiver,
+        tokio_runtime.handle(),
+        log.clone(),
+    );
+
+    tokio_runtime.block_on(async {
+        info!(log, "Initializing protocol runner...");
+
+        let mut child = tezos_protocol_api
+            .start(None)
+            .await
+            .expect("Failed to launch protocol runner");
+        let mut conn = tezos_protocol_api
+            .connect()
+            .await
+            .expect("Failed to connect to protocol runner");
+
+        conn.init_protocol_for_write(false, &env.storage.patch_context, None)
+            .await
+            .expect("Failed to initialize protocol runner for write");
+
+        info!(log, "Dumping context..."; "context_hash" => context_hash.to_base58_check());
+
+        let instant = Instant::now();
+        let context_elements = conn
+            .dump_context(
+                context_hash.clone(),
+                context_dump_path.to_string_lossy().to_string(),
+            )
+            .await
+            .expect("Failed to produce a context dump");
+
+        info!(log, "Done dumping context"; "dump_time" => format!("{:?}", instant.elapsed()), "context_elements" => context_elements);
+
+        terminate_or_kill(&mut child, "Done".into()).await.unwrap();
+
+        context_elements
+    })
+}
+
+/// Files of the snapshot, `context` is extracted in a temporary directory
+struct SnapshotFiles {
+    version: Option<Vec<u8>>,
+    metadata: Option<Vec<u8>>,
+    block_data: Option<Vec<u8>>,
+    floating_blocks: Option<Vec<u8>>,
+    /// Names of the files in `protocols/`
+    protocols: Vec<String>,
+    context_path: Option<PathBuf>,
+}
+
+fn read_entry<R: Read>(entry: &mut tar::Entry<R>) -> Result<Vec<u8>, OctezSnapshotError> {
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_snapshot_files(
+    snapshot_path: &Path,
+    context_dir: &Path,
+) -> Result<SnapshotFiles, OctezSnapshotError> {
+    let file = File::open(snapshot_path)?;
+    let mut archive = Archive::new(BufReader::new(file));
+
+    let mut files = SnapshotFiles {
+        version: None,
+        metadata: None,
+        block_data: None,
+        floating_blocks: None,
+        protocols: Vec::new(),
+        context_path: None,
+    };
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+
+        let name = match path.strip_prefix(SNAPSHOT_ROOT) {
+            Ok(name) => name.to_path_buf(),
+            Err(_) => continue,
+        };
+
+        if let Ok(protocol) = name.strip_prefix(PROTOCOLS_DIR) {
+            if entry.header().entry_type().is_file() {
+                files.protocols.push(protocol.to_string_lossy().to_string());
+            }
+            continue;
+        }
+
+        match name.to_string_lossy().as_ref() {
+            VERSION_FILE => files.version = Some(read_entry(&mut entry)?),
+            METADATA_FILE => files.metadata = Some(read_entry(&mut entry)?),
+            BLOCK_DATA_FILE => files.block_data = Some(read_entry(&mut entry)?),
+            FLOATING_BLOCKS_FILE => files.floating_blocks = Some(read_entry(&mut entry)?),
+            CONTEXT_FILE => {
+                let context_path = context_dir.join(CONTEXT_FILE);
+                entry.unpack(&context_path)?;
+                files.context_path = Some(context_path);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+/// The cemented blocks of full snapshots are not imported, the node keeps only
+/// the history of a rolling node
+fn check_history_mode(
+    snapshot_history_mode: &str,
+    node_history_mode: HistoryMode,
+) -> Result<(), OctezSnapshotError> {
+    match snapshot_history_mode {
+        HISTORY_MODE_ROLLING | HISTORY_MODE_FULL => (),
+        _ => {
+            return Err(OctezSnapshotError::UnsupportedHistoryMode {
+                history_mode: snapshot_history_mode.to_string(),
+            })
+        }
+    }
+
+    match node_history_mode {
+        HistoryMode::Rolling { .. } => Ok(()),
+        HistoryMode::Archive | HistoryMode::Full { .. } => {
+            Err(OctezSnapshotError::HistoryModeMismatch {
+                snapshot: snapshot_history_mode.to_string(),
+                node: node_history_mode,
+            })
+        }
+    }
+}
+
+/// Protocols are shipped as sources in `protocols/`, named by their hash,
+/// the node can only run the protocols it embeds
+fn check_protocols(protocols: &[String]) -> Result<(), OctezSnapshotError> {
+    for protocol in protocols {
+        // Other files (like tables) are not protocols
+        let protocol_hash = match ProtocolHash::from_base58_check(protocol) {
+            Ok(protocol_hash) => protocol_hash,
+            Err(_) => continue,
+        };
+
+        if SupportedProtocol::try_from(&protocol_hash).is_err() {
+            return Err(OctezSnapshotError::UnsupportedProtocol {
+                protocol: protocol.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Import an Octez snapshot: restore the context of the predecessor of the snapshot block,
+/// apply the snapshot block, then store the block and its predecessors, and set it as
+/// the current head.
+pub fn import_snapshot(
+    env: crate::configuration::Environment,
+    persistent_storage: PersistentStorage,
+    init_storage_data: StorageInitInfo,
+    snapshot_path: &Path,
+    log: Logger,
+) {
+    info!(log, "Importing Octez snapshot"; "path" => snapshot_path.display().to_string());
+
+    let tmpdir = tempdir_in(&env.storage.db_path)
+        .expect("Could not create a temporary directory for the context dump");
+
+    let files = read_snapshot_files(snapshot_path, tmpdir.path())
+        .unwrap_or_else(|e| panic!("Failed to read the snapshot: {}", e));
+
+    let version: SnapshotVersion = serde_json::from_slice(
+        &files
+            .version
+            .expect("The snapshot is missing its version file"),
+    )
+    .expect("Invalid snapshot version file");
+    if version.version != SNAPSHOT_VERSION {
+        panic!(
+            "{}",
+            OctezSnapshotError::UnsupportedVersion {
+                version: version.version
+            }
+        );
+    }
+
+    let metadata: SnapshotMetadata = serde_json::from_slice(
+        &files
+            .metadata
+            .expect("The snapshot is missing its metadata file"),
+    )
+    .expect("Invalid snapshot metadata file");
+
+    info!(log, "Snapshot metadata"; "chain_name" => &metadata.chain_name, "history_mode" => &metadata.history_mode, "block_hash" => &metadata.block_hash, "level" => metadata.level);
+
+    if metadata.chain_name != env.tezos_network_config.version {
+        panic!(
+            "The snapshot is for the chain {}, but the node is configured for {}",
+            metadata.chain_name, env.tezos_network_config.version
+        );
+    }
+
+    if let Err(e) = check_history_mode(&metadata.history_mode, env.storage.history_mode) {
+        panic!("{}", e);
+    }
+
+    if let Err(e) = check_protocols(&files.protocols) {
+        panic!("{}", e);
+    }
+
+    let block_data = SnapshotBlockData::decode(
+        &files
+            .block_data
+            .expect("The snapshot is missing its block data"),
+    )
+    .unwrap_or_else(|e| panic!("Invalid snapshot block data: {}", e));
+
+    let block_header = BlockHeaderWithHash::new(block_data.block.header.clone())
+        .expect("Failed to compute the hash of the snapshot block");
+    if block_header.hash.to_base58_check() != metadata.block_hash {
+        panic!(
+            "The snapshot block hash {} doesn't match its metadata {}",
+            block_header.hash.to_base58_check(),
+            metadata.block_hash
+        );
+    }
+
+    let predecessor_header = BlockHeaderWithHash::new(block_data.predecessor_header.clone())
+        .expect("Failed to compute the hash of the predecessor of the snapshot block");
+    if &predecessor_header.hash != block_header.header.predecessor() {
+        panic!(
+            "The snapshot predecessor {} is not the predecessor of the snapshot block",
+            predecessor_header.hash.to_base58_check(),
+        );
+    }
+
+    let floating_blocks = match files.floating_blocks.as_ref() {
+        Some(bytes) => FloatingBlock::decode_all(bytes)
+            .unwrap_or_else(|e| panic!("Invalid snapshot history: {}", e)),
+        None => Vec::new(),
+    };
+
+    let context_path = files
+        .context_path
+        .expect("The snapshot is missing its context");
+
+    let apply_result = restore_and_apply_block(
+        &env,
+        &init_storage_data.chain_id,
+        &block_data,
+        &context_path,
+        metadata.context_elements,
+        &log,
+    );
+
+    store_snapshot_blocks(
+        &persistent_storage,
+        &init_storage_data.chain_id,
+        block_header,
+        block_data,
+        predecessor_header,
+        floating_blocks,
+        apply_result,
+        &log,
+    );
+
+    info!(log, "Octez snapshot imported"; "block_hash" => metadata.block_hash, "level" => metadata.level);
+}
+
+/// Restores the context of the predecessor, and applies the snapshot block on it
+fn restore_and_apply_block(
+    env: &crate::configuration::Environment,
+    chain_id: &ChainId,
+    block_data: &SnapshotBlockData,
+    context_path: &Path,
+    context_elements: i64,
+    log: &Logger,
+) -> ApplyBlockResponse {
+    let tokio_runtime = create_tokio_runtime(env).expect("Failed to create tokio runtime");
+
+    let (_context_init_status_sender, context_init_status_receiver) =
+        tokio::sync::watch::channel(false);
+    let mut tezos_protocol_api = ProtocolRunnerApi::new(
+        create_protocol_runner_configuration(env, DEFAULT_PRESERVED_CYCLES),
+        context_init_status_receiver,
+        tokio_runtime.handle(),
+        log.clone(),
+    );
+
+    let predecessor_context_hash = block_data.predecessor_header.context();
+    let context_hash = block_data.block.header.context();
+
+    tokio_runtime.block_on(async {
+        info!(log, "Initializing protocol runner...");
+
+        let mut child = tezos_protocol_api
+            .start(None)
+            .await
+            .expect("Failed to launch protocol runner");
+        let mut conn = tezos_protocol_api
+            .connect()
+            .await
+            .expect("Failed to connect to protocol runner");
+
+        conn.init_protocol_for_write(true, &env.storage.patch_context, None)
+            .await
+            .expect("Failed to initialize protocol runner for write");
+
+        info!(log, "Restoring context from the snapshot..."; "context_hash" => predecessor_context_hash.to_base58_check());
+
+        let instant = Instant::now();
+        conn.restore_context(
+            predecessor_context_hash.clone(),
+            context_path.to_string_lossy().to_string(),
+            context_elements,
+        )
+        .await
+        .expect("Failed to restore the context from the snapshot");
+
+        info!(log, "Done restoring context"; "restore_time" => format!("{:?}", instant.elapsed()));
+
+        // Make sure that the restored commit is the one of the predecessor
+        let latest_context_hashes = conn
+            .latest_context_hashes(1)
+            .await
+            .expect("Failed to get the latest context hashes");
+        if latest_context_hashes.last() != Some(predecessor_context_hash) {
+            panic!(
+                "The restored context {:?} doesn't match the context {} of the snapshot predecessor",
+                latest_context_hashes
+                    .last()
+                    .map(|context_hash| context_hash.to_base58_check()),
+                predecessor_context_hash.to_base58_check()
+            );
+        }
+
+        info!(log, "Applying the snapshot block...");
+
+        let result = conn
+            .apply_block(ApplyBlockRequest {
+                chain_id: chain_id.clone(),
+                block_header: block_data.block.header.clone(),
+                pred_header: block_data.predecessor_header.clone(),
+                max_operations_ttl: block_data.predecessor_max_operations_ttl,
+                operations: block_data.block.operations.clone(),
+                predecessor_block_metadata_hash: block_data.predecessor_block_metadata_hash.clone(),
+                predecessor_ops_metadata_hash: block_data.predecessor_ops_metadata_hash.clone(),
+            })
+            .await
+            .expect("Failed to apply the snapshot block");
+
+        if &result.context_hash != context_hash {
+            panic!(
+                "The context {} of the applied snapshot block doesn't match its header {}",
+                result.context_hash.to_base58_check(),
+                context_hash.to_base58_check()
+            );
+        }
+
+        terminate_or_kill(&mut child, "Done".into()).await.unwrap();
+
+        result
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn store_block(
+    block_storage: &BlockStorage,
+    block_meta_storage: &BlockMetaStorage,
+    operations_storage: &OperationsStorage,
+    chain_id: &ChainId,
+    block_header: &BlockHeaderWithHash,
+    operations: Vec<Vec<Operation>>,
+    log: &Logger,
+) -> storage::block_meta_storage::Meta {
+    block_storage
+        .put_block_header(block_header)
+        .expect("Failed to store block header");
+
+    let block_meta = block_meta_storage
+        .put_block_header(block_header, chain_id, log)
+        .expect("Failed to store block header meta");
+
+    block_meta_storage
+        .store_predecessors(&block_header.hash, &block_meta)
+        .expect("Failed to store predecessors metadata");
+
+    for (validation_pass, operations) in operations.into_iter().enumerate() {
+        // The operations of the snapshot are trusted, their merkle path is not needed
+        let message = OperationsForBlocksMessage::new(
+            OperationsForBlock::new(block_header.hash.clone(), validation_pass as i8),
+            OperationsPath(Vec::new()),
+            operations,
+        );
+        operations_storage
+            .put_operations(&message)
+            .expect("Failed to store operations");
+    }
+
+    block_meta
+}
+
+#[allow(clippy::too_many_arguments)]
+fn store_snapshot_blocks(
+    persistent_storage: &PersistentStorage,
+    chain_id: &ChainId,
+    block_header: BlockHeaderWithHash,
+    block_data: SnapshotBlockData,
+    predecessor_header: BlockHeaderWithHash,
+    floating_blocks: Vec<FloatingBlock>,
+    apply_result: ApplyBlockResponse,
+    log: &Logger,
+) {
+    let block_storage = BlockStorage::new(persistent_storage);
+    let block_meta_storage = BlockMetaStorage::new(persistent_storage);
+    let operations_storage = OperationsStorage::new(persistent_storage);
+    let chain_meta_storage = ChainMetaStorage::new(persistent_storage);
+
+    info!(log, "Storing snapshot history..."; "blocks" => floating_blocks.len());
+
+    // The lowest block kept by the node
+    let mut caboose = None;
+
+    for block in floating_blocks {
+        let header = BlockHeaderWithHash::new(block.block.header)
+            .expect("Failed to compute the hash of a snapshot block");
+        if header.hash != block.hash {
+            panic!(
+                "The hash of the snapshot history block {} doesn't match its header",
+                block.hash.to_base58_check()
+            );
+        }
+
+        store_block(
+            &block_storage,
+            &block_meta_storage,
+            &operations_storage,
+            chain_id,
+            &header,
+            block.block.operations,
+            log,
+        );
+
+        if caboose.is_none() {
+            caboose = Some(Head::new(
+                header.hash.clone(),
+                header.header.level(),
+                header.header.fitness().clone(),
+            ));
+        }
+    }
+
+    // Already stored if it is part of the history
+    if block_storage
+        .get(&predecessor_header.hash)
+        .expect("Failed to obtain block header")
+        .is_none()
+    {
+        store_block(
+            &block_storage,
+            &block_meta_storage,
+            &operations_storage,
+            chain_id,
+            &predecessor_header,
+            Vec::new(),
+            log,
+        );
+    }
+
+    let mut block_meta = store_block(
+        &block_storage,
+        &block_meta_storage,
+        &operations_storage,
+        chain_id,
+        &block_header,
+        block_data.block.operations,
+        log,
+    );
+
+    // Also sets the snapshot block as the current head
+    store_applied_block_result(
+        &chain_meta_storage,
+        &block_storage,
+        &block_meta_storage,
+        &block_header.hash,
+        block_header.header.fitness().clone(),
+        apply_result,
+        &mut block_meta,
+        &CycleMetaStorage::new(persistent_storage),
+        &CycleErasStorage::new(persistent_storage),
+        &ConstantsStorage::new(persistent_storage),
+    )
+    .expect("Failed to store the result of the snapshot block");
+
+    // The metadata are only known from the snapshot block, and the node
+    // cannot reorganize below it
+    let head = Head::new(
+        block_header.hash.clone(),
+        block_header.header.level(),
+        block_header.header.fitness().clone(),
+    );
+    chain_meta_storage
+        .set_savepoint(chain_id, head.clone())
+        .expect("Failed to set savepoint");
+    chain_meta_storage
+        .set_caboose(chain_id, caboose.unwrap_or(head))
+        .expect("Failed to set caboose");
+
+    info!(log, "Snapshot block set as current head"; "block_hash" => block_header.hash.to_base58_check());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mainnet blocks at level 1 and 2
+    const BLOCK_HASH_LEVEL_1: &str =
+        "dd9fb5edc4f29e7d28f41fe56d57ad172b7686ed140ad50294488b68de29474d";
+    const BLOCK_HASH_LEVEL_2: &str =
+        "a14f19e0df37d7b71312523305d71ac79e3d989c1c1d4e8e884b6857e4ec1627";
+    const BLOCK_HEADER_LEVEL_2: &str = "0000000201dd9fb5edc4f29e7d28f41fe56d57ad172b7686ed140ad50294488b68de29474d000000005c017cd804683625c2445a4e9564bf710c5528fd99a7d150d2a2a323bc22ff9e2710da4f6d0000001100000001000000000800000000000000029bd8c75dec93c276d2d8e8febc3aa6c9471cb2cb42236b3ab4ca5f1f2a0892f6000500000003ba671eef00d6a8bea20a4677fae51268ab6be7bd8cfc373cd6ac9e0a00064efcc404e1fb39409c5df255f7651e3d1bb5d91cb2172b687e5d56ebde58cfd92e1855aaafbf05";
+    const OPERATION: &str = "10490b79070cf19175cd7e3b9c1ee66f6e85799980404b119132ea7e58a4a97e000008c387fa065a181d45d47a9b78ddc77e92a881779ff2cbabbf9646eade4bf1405a08e00b725ed849eea46953b10b5cdebc518e6fd47e69b82d2ca18c4cf6d2f312dd08";
+
+    fn block_hash(hash: &str) -> BlockHash {
+        BlockHash::try_from(hex::decode(hash).unwrap().as_slice()).unwrap()
+    }
+
+    fn header_level_2() -> BlockHeader {
+        BlockHeader::from_bytes(hex::decode(BLOCK_HEADER_LEVEL_2).unwrap()).unwrap()
+    }
+
+    fn operation() -> Operation {
+        Operation::from_bytes(hex::decode(OPERATION).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_block_data_encoding() {
+        let block_data = SnapshotBlockData {
+            block: SnapshotBlock {
+                header: header_level_2(),
+                operations: vec![
+                    vec![operation()],
+                    vec![],
+                    vec![],
+                    vec![operation(), operation()],
+                ],
+            },
+            predecessor_header: header_level_2(),
+            predecessor_max_operations_ttl: 60,
+            predecessor_block_metadata_hash: Some(
+                BlockMetadataHash::try_from([7; HASH_LEN].as_ref()).unwrap(),
+            ),
+            predecessor_ops_metadata_hash: None,
+        };
+
+        let bytes = block_data.encode().unwrap();
+        assert_eq!(SnapshotBlockData::decode(&bytes).unwrap(), block_data);
+
+        // Trailing bytes
+        let mut bytes = bytes;
+        bytes.push(0);
+        assert!(SnapshotBlockData::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_floating_blocks_encoding() {
+        let blocks = vec![
+            FloatingBlock {
+                hash: block_hash(BLOCK_HASH_LEVEL_2),
+                block: SnapshotBlock {
+                    header: header_level_2(),
+                    operations: vec![vec![], vec![], vec![], vec![]],
+                },
+                block_metadata_hash: None,
+                ops_metadata_hashes: None,
+            },
+            FloatingBlock {
+                hash: block_hash(BLOCK_HASH_LEVEL_2),
+                block: SnapshotBlock {
+                    header: header_level_2(),
+                    operations: vec![vec![operation()], vec![], vec![], vec![]],
+                },
+                block_metadata_hash: Some(
+                    BlockMetadataHash::try_from([1; HASH_LEN].as_ref()).unwrap(),
+                ),
+                ops_metadata_hashes: Some(vec![
+                    vec![OperationMetadataHash::try_from([2; HASH_LEN].as_ref()).unwrap()],
+                    vec![],
+                    vec![],
+                    vec![],
+                ]),
+            },
+        ];
+
+        let bytes = FloatingBlock::encode_all(&blocks).unwrap();
+        assert_eq!(FloatingBlock::decode_all(&bytes).unwrap(), blocks);
+    }
+
+    /// Files laid out by hand from a mainnet block, like Octez writes them
+    #[test]
+    fn test_decode_snapshot_fixture() {
+        let header_len = format!("{:08x}", BLOCK_HEADER_LEVEL_2.len() / 2);
+        // 4 empty validation passes
+        let operations = format!("00000010{}", "00000000".repeat(4));
+
+        // The predecessor metadata hashes are absent
+        let block_data = hex::decode(format!(
+            "{len}{header}{ops}{len}{header}0000003c0000",
+            len = header_len,
+            header = BLOCK_HEADER_LEVEL_2,
+            ops = operations,
+        ))
+        .unwrap();
+        let block_data = SnapshotBlockData::decode(&block_data).unwrap();
+        assert_eq!(block_data.block.header.level(), 2);
+        assert_eq!(
+            block_data.block.header.predecessor(),
+            &block_hash(BLOCK_HASH_LEVEL_1)
+        );
+        assert_eq!(
+            block_data.block.operations,
+            vec![Vec::<Operation>::new(); 4]
+        );
+        assert_eq!(block_data.predecessor_max_operations_ttl, 60);
+        assert!(block_data.predecessor_block_metadata_hash.is_none());
+        assert!(block_data.predecessor_ops_metadata_hash.is_none());
+
+        // With a block metadata hash, without operations metadata hashes,
+        // followed by its metadata, which is ignored
+        let block = format!(
+            "{hash}{len}{header}{ops}ff{metadata_hash}00{metadata}",
+            hash = BLOCK_HASH_LEVEL_2,
+            len = header_len,
+            header = BLOCK_HEADER_LEVEL_2,
+            ops = operations,
+            metadata_hash = "01".repeat(HASH_LEN),
+            metadata = "ff0000003c",
+        );
+        let floating = hex::decode(format!("{:08x}{}", block.len() / 2, block)).unwrap();
+        let blocks = FloatingBlock::decode_all(&floating).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].hash, block_hash(BLOCK_HASH_LEVEL_2));
+        assert_eq!(
+            BlockHeaderWithHash::new(blocks[0].block.header.clone())
+                .unwrap()
+                .hash,
+            blocks[0].hash
+        );
+        assert!(blocks[0].block_metadata_hash.is_some());
+        assert!(blocks[0].ops_metadata_hashes.is_none());
+    }
+
+    #[test]
+    fn test_check_history_mode() {
+        let rolling = HistoryMode::Rolling {
+            additional_cycles: 5,
+        };
+        assert!(check_history_mode("rolling", rolling).is_ok());
+        assert!(check_history_mode("full", rolling).is_ok());
+        assert!(check_history_mode("archive", rolling).is_err());
+        assert!(check_history_mode("rolling", HistoryMode::Archive).is_err());
+        assert!(check_history_mode(
+            "full",
+            HistoryMode::Full {
+                additional_cycles: 5
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_protocols() {
+        let unsupported = ProtocolHash::try_from([0; HASH_LEN].as_ref())
+            .unwrap()
+            .to_base58_check();
+
+        assert!(check_protocols(&[]).is_ok());
+        assert!(check_protocols(&[
+            SupportedProtocol::Proto012.protocol_hash(),
+            "protocol_levels".to_string(),
+        ])
+        .is_ok());
+        assert!(check_protocols(&[unsupported]).is_err());
+    }
+}
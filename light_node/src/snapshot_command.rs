@@ -33,6 +33,7 @@ use tempfile::tempdir_in;
 use slog::{info, Logger};
 
 use crypto::hash::{BlockHash, ContextHash};
+use storage::history_mode::DEFAULT_PRESERVED_CYCLES;
 use storage::{
     initialize_storage_with_genesis_block, store_commit_genesis_result, BlockMetaStorage,
     BlockMetaStorageReader, BlockReference, BlockStorage, BlockStorageReader, ChainMetaStorage,
@@ -310,7 +311,7 @@ pub fn snapshot_storage(
     std::fs::remove_file(tezedge_lock_file).ok();
 }
 
-async fn terminate_or_kill(process: &mut Child, reason: String) -> Result<(), ProtocolRunnerError> {
+pub(crate) async fn terminate_or_kill(process: &mut Child, reason: String) -> Result<(), ProtocolRunnerError> {
     // try to send SIGINT (ctrl-c)
     if let Some(pid) = process.id() {
         let pid = Pid::from_raw(pid as i32);
@@ -344,7 +345,8 @@ fn initialize_protocol_runner_and_snapshot_context(
 
     let (_context_init_status_sender, context_init_status_receiver) =
         tokio::sync::watch::channel(false);
-    let protocol_runner_configuration = create_protocol_runner_configuration(env);
+    let protocol_runner_configuration =
+        create_protocol_runner_configuration(env, DEFAULT_PRESERVED_CYCLES);
     let mut tezos_protocol_api = ProtocolRunnerApi::new(
         protocol_runner_configuration,
         context_init_status_receiver,
@@ -418,7 +420,7 @@ fn initialize_protocol_runner_and_snapshot_context(
     })
 }
 
-fn resolve_block_reference(
+pub(crate) fn resolve_block_reference(
     block_reference: BlockReference,
     block_storage: &BlockStorage,
     block_meta_storage: &BlockMetaStorage,
@@ -6,6 +6,8 @@
 //! problems, from panics to high memory usage, for better stability, we separated protocol into
 //! self-contained process communicating through Unix Socket.
 
+use std::num::NonZeroU64;
+
 use clap::{App, Arg};
 use slog::*;
 use tezos_interop::runtime::OCamlBlockPanic;
@@ -45,6 +47,14 @@ fn main() {
                 .empty_values(false)
                 .required(true),
         )
+        .arg(
+            Arg::with_name("merkle-proof-socket-path")
+                .long("merkle-proof-socket-path")
+                .value_name("path")
+                .help("Path to the socket serving the merkle proofs of the context")
+                .takes_value(true)
+                .empty_values(false),
+        )
         .arg(
             Arg::with_name("endpoint")
                 .long("endpoint")
@@ -62,6 +72,15 @@ fn main() {
                 .possible_values(&["critical", "error", "warn", "info", "debug", "trace"])
                 .help("Set log level"),
         )
+        .arg(
+            Arg::with_name("context-gc-preserve-cycles")
+                .long("context-gc-preserve-cycles")
+                .takes_value(true)
+                .value_name("NUM")
+                .help(
+                    "Number of cycles preserved by the garbage collector of the persistent context, greater than 0",
+                ),
+        )
         .get_matches();
 
     let cmd_socket_path = matches
@@ -77,8 +96,27 @@ fn main() {
         .parse::<slog::Level>()
         .expect("Was expecting one value from slog::Level");
 
+    let context_gc_preserve_cycles = matches.value_of("context-gc-preserve-cycles").map(|value| {
+        value
+            .parse::<NonZeroU64>()
+            .expect("Was expecting a number of cycles greater than 0")
+    });
+
     let log = create_logger(log_level, endpoint_name);
 
+    // Must be configured before the context is initialized
+    tezos_context::gc::persistent::set_preserve_cycles(context_gc_preserve_cycles);
+
+    if let Some(socket_path) = matches.value_of("merkle-proof-socket-path") {
+        if let Err(err) = tezos_context::merkle_proof::spawn_merkle_proof_server(
+            socket_path,
+            tezos_context::ffi::get_context_index,
+            log.clone(),
+        ) {
+            warn!(log, "Failed to start the merkle proof server"; "reason" => format!("{:?}", err));
+        }
+    }
+
     let shutdown_callback = |log: &Logger| {
         debug!(log, "Shutting down OCaml runtime");
         match std::panic::catch_unwind(|| {
@@ -9,7 +9,9 @@ use crate::{empty, make_json_response, required_param, result_to_json_response,
 use anyhow::format_err;
 use crypto::hash::{BlockHash, CryptoboxPublicKeyHash, OperationHash};
 use crypto::PublicKeyWithHash;
-use hyper::{Body, Request, Response};
+use hyper::body::Buf;
+use hyper::{Body, Method, Request, Response};
+use shell_automaton::service::rpc_service::PeersAclUpdate;
 use shell_automaton::service::{BlockApplyStats, BlockPeerStats};
 use slog::warn;
 use std::collections::BTreeSet;
@@ -81,18 +83,24 @@ pub async fn dev_block_actions(
     )
 }
 
-#[allow(dead_code)]
-pub async fn dev_contract_actions(
+pub async fn dev_account_operations(
     _: Request<Body>,
     params: Params,
     query: Query,
     env: Arc<RpcServiceEnvironment>,
 ) -> ServiceResult {
-    let contract_id = required_param!(params, "contract_address")?;
+    let address = required_param!(params, "address")?;
+    let role = query.get_str("role");
     let from_id = query.get_u64("from_id");
     let limit = query.get_usize("limit").unwrap_or(50);
     result_to_json_response(
-        dev_services::get_contract_actions(contract_id, from_id, limit, env.persistent_storage()),
+        dev_services::get_account_operations(
+            address,
+            role,
+            from_id,
+            limit,
+            env.persistent_storage(),
+        ),
         env.log(),
     )
 }
@@ -346,6 +354,22 @@ pub async fn dev_shell_automaton_storage_requests_get(
     make_json_response(&dev_services::get_shell_automaton_storage_requests(&env).await?)
 }
 
+/// GET returns the peers connection policies, PUT changes them, see [`PeersAclUpdate`].
+pub async fn dev_shell_automaton_peers_acl(
+    req: Request<Body>,
+    _: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let update = if req.method() == Method::PUT {
+        let body = hyper::body::aggregate(req).await?;
+        Some(serde_json::from_reader::<_, PeersAclUpdate>(body.reader())?)
+    } else {
+        None
+    };
+    make_json_response(&dev_services::get_shell_automaton_peers_acl(&env, update).await?)
+}
+
 pub async fn dev_shell_automaton_actions_get(
     _: Request<Body>,
     _: Params,
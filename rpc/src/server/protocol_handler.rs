@@ -7,10 +7,12 @@ use slog::warn;
 
 use crate::helpers::{create_rpc_request, parse_block_hash, parse_chain_id, RpcServiceError};
 use crate::server::{HasSingleValue, Params, Query, RpcServiceEnvironment};
-use crate::services::protocol::{ContextParamsError, RightsError, VotesError};
+use crate::services::protocol::{
+    ContextParamsError, ContractField, ContractsError, RightsError, VotesError,
+};
 use crate::{
-    handle_rpc_service_error, parse_block_hash_or_fail, required_param, result_to_json_response,
-    services, ServiceResult,
+    handle_rpc_service_error, parse_block_hash_or_fail, required_param,
+    result_option_to_json_response, result_to_json_response, services, ServiceResult,
 };
 use std::sync::Arc;
 
@@ -205,6 +207,87 @@ pub async fn votes_listings(
         }
     }
 }
+
+pub async fn contract_balance(
+    req: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    contract_field(req, params, env, ContractField::Balance).await
+}
+
+pub async fn contract_counter(
+    req: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    contract_field(req, params, env, ContractField::Counter).await
+}
+
+pub async fn contract_manager_key(
+    req: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    contract_field(req, params, env, ContractField::Manager).await
+}
+
+pub async fn contract_delegate(
+    req: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    contract_field(req, params, env, ContractField::Delegate).await
+}
+
+async fn contract_field(
+    req: Request<Body>,
+    params: Params,
+    env: Arc<RpcServiceEnvironment>,
+    field: ContractField,
+) -> ServiceResult {
+    let chain_id_param = required_param!(params, "chain_id")?;
+    let chain_id = parse_chain_id(chain_id_param, &env)?;
+    let block_hash =
+        parse_block_hash_or_fail!(&chain_id, required_param!(params, "block_id")?, &env);
+    let contract_id = required_param!(params, "contract_id")?;
+
+    // try to call our implementation
+    match services::protocol::get_contract_field(&chain_id, &block_hash, contract_id, field, &env)
+        .await
+    {
+        Ok(value) => result_option_to_json_response(Ok(value), env.log()),
+        Err(
+            ContractsError::UnsupportedProtocolError { .. } | ContractsError::NotFoundInContext,
+        ) => {
+            // fallback, if the contract's data is not available in Tezedge impl, we trigger rpc protocol router
+            let result = services::protocol::call_protocol_rpc(
+                chain_id_param,
+                chain_id,
+                block_hash,
+                create_rpc_request(req).await?,
+                &env,
+            )
+            .await?;
+            make_response_with_status_and_json_string(result.0, &result.1)
+        }
+        Err(ContractsError::ServiceError { reason }) => {
+            slog::warn!(env.log(), "Failed to execute RPC function for contract"; "reason" => format!("{:?}", &reason));
+            handle_rpc_service_error(RpcServiceError::UnexpectedError {
+                reason: format!("{}", reason),
+            })
+        }
+        Err(ContractsError::RpcServiceError { reason }) => {
+            slog::warn!(env.log(), "Failed to execute RPC function for contract"; "reason" => format!("{:?}", &reason));
+            handle_rpc_service_error(reason)
+        }
+    }
+}
+
 pub async fn call_protocol_rpc(
     req: Request<Body>,
     params: Params,
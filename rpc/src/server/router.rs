@@ -106,6 +106,31 @@ pub(crate) fn create_routes(tezedge_is_enabled: bool) -> PathTree<MethodHandler>
         "/chains/:chain_id/mempool/request_operations",
         shell_handler::mempool_request_operations,
     );
+    routes.handle(
+        hash_set![Method::GET],
+        "/chains/:chain_id/mempool/filter",
+        shell_handler::mempool_filter_get,
+    );
+    routes.handle(
+        hash_set![Method::POST],
+        "/chains/:chain_id/mempool/filter",
+        shell_handler::mempool_filter_set,
+    );
+    routes.handle(
+        hash_set![Method::POST],
+        "/chains/:chain_id/mempool/ban_operation",
+        shell_handler::mempool_ban_operation,
+    );
+    routes.handle(
+        hash_set![Method::POST],
+        "/chains/:chain_id/mempool/unban_operation",
+        shell_handler::mempool_unban_operation,
+    );
+    routes.handle(
+        hash_set![Method::POST],
+        "/chains/:chain_id/mempool/unban_all_operations",
+        shell_handler::mempool_unban_all_operations,
+    );
     routes.handle(
         hash_set![Method::GET],
         "/chains/:chain_id/blocks/:block_id/protocols",
@@ -181,6 +206,19 @@ pub(crate) fn create_routes(tezedge_is_enabled: bool) -> PathTree<MethodHandler>
             shell_handler::context_raw_bytes,
         );
     }
+    if tezedge_is_enabled {
+        // Proofs are built by the TezEdge context only
+        routes.handle(
+            hash_set![Method::GET],
+            "/chains/:chain_id/blocks/:block_id/context/merkle_tree",
+            shell_handler::context_merkle_tree,
+        );
+        routes.handle(
+            hash_set![Method::GET],
+            "/chains/:chain_id/blocks/:block_id/context/merkle_tree/*any",
+            shell_handler::context_merkle_tree,
+        );
+    }
     routes.handle(
         hash_set![Method::GET],
         "/chains/:chain_id/blocks/:block_id/metadata",
@@ -236,6 +274,30 @@ pub(crate) fn create_routes(tezedge_is_enabled: bool) -> PathTree<MethodHandler>
         protocol_handler::endorsing_rights,
     );
 
+    if tezedge_is_enabled {
+        // Contracts data read directly from the TezEdge context
+        routes.handle(
+            hash_set![Method::GET],
+            "/chains/:chain_id/blocks/:block_id/context/contracts/:contract_id/balance",
+            protocol_handler::contract_balance,
+        );
+        routes.handle(
+            hash_set![Method::GET],
+            "/chains/:chain_id/blocks/:block_id/context/contracts/:contract_id/counter",
+            protocol_handler::contract_counter,
+        );
+        routes.handle(
+            hash_set![Method::GET],
+            "/chains/:chain_id/blocks/:block_id/context/contracts/:contract_id/manager_key",
+            protocol_handler::contract_manager_key,
+        );
+        routes.handle(
+            hash_set![Method::GET],
+            "/chains/:chain_id/blocks/:block_id/context/contracts/:contract_id/delegate",
+            protocol_handler::contract_delegate,
+        );
+    }
+
     // TODO - TE-261: we are routing these to OCaml for now, even when the TezEdge
     // context is available. Once these handlers have been tested better and revised, enable again.
     if enable_tezedge_rpcs_with_context(tezedge_is_enabled, false) {
@@ -280,6 +342,11 @@ pub(crate) fn create_routes(tezedge_is_enabled: bool) -> PathTree<MethodHandler>
         "/dev/chains/main/actions/contracts/:contract_address",
         dev_handler::dev_action_cursor,
     );
+    routes.handle(
+        hash_set![Method::GET],
+        "/dev/chains/main/accounts/:address/operations",
+        dev_handler::dev_account_operations,
+    );
     routes.handle(
         hash_set![Method::GET],
         "/dev/version",
@@ -311,6 +378,12 @@ pub(crate) fn create_routes(tezedge_is_enabled: bool) -> PathTree<MethodHandler>
         dev_handler::dev_shell_automaton_storage_requests_get,
     );
 
+    routes.handle(
+        hash_set![Method::GET, Method::PUT],
+        "/dev/shell/automaton/peers/acl",
+        dev_handler::dev_shell_automaton_peers_acl,
+    );
+
     routes.handle(
         hash_set![Method::GET],
         "/dev/shell/automaton/actions",
@@ -433,6 +506,67 @@ pub(crate) fn create_routes(tezedge_is_enabled: bool) -> PathTree<MethodHandler>
         "/network/points",
         shell_handler::network_connections,
     );
+    routes.handle(
+        hash_set![Method::PUT, Method::PATCH],
+        "/network/points/:point",
+        shell_handler::network_command,
+    );
+    // aliases of the PATCH requests
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/points/:point/ban",
+        shell_handler::network_ban,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/points/:point/unban",
+        shell_handler::network_unban,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/points/:point/trust",
+        shell_handler::network_trust,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/points/:point/untrust",
+        shell_handler::network_untrust,
+    );
+    routes.handle(
+        hash_set![Method::PATCH],
+        "/network/peers/:peer_id",
+        shell_handler::network_command,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/peers/:peer_id/ban",
+        shell_handler::network_ban,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/peers/:peer_id/unban",
+        shell_handler::network_unban,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/peers/:peer_id/trust",
+        shell_handler::network_trust,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/peers/:peer_id/untrust",
+        shell_handler::network_untrust,
+    );
+    routes.handle(
+        hash_set![Method::DELETE],
+        "/network/greylist",
+        shell_handler::network_greylist_clear,
+    );
+    routes.handle(
+        hash_set![Method::GET],
+        "/network/greylist/clear",
+        shell_handler::network_greylist_clear,
+    );
 
     // DEPRECATED in ocaml but still used by python tests
     routes.handle(
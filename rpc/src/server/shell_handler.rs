@@ -6,11 +6,13 @@ use std::sync::Arc;
 
 use hyper::body::Buf;
 use hyper::{Body, Method, Request};
+use serde::Deserialize;
 
 use tokio_stream::{wrappers::UnboundedReceiverStream, StreamExt};
 
-use crypto::hash::{ChainId, ProtocolHash};
-use shell_automaton::service::rpc_service::RpcRequestStream;
+use crypto::hash::{ChainId, CryptoboxPublicKeyHash, OperationHash, ProtocolHash};
+use shell_automaton::mempool::MempoolFilter;
+use shell_automaton::service::rpc_service::{NetworkCommand, NetworkTarget, RpcRequestStream};
 
 use crate::helpers::{
     create_rpc_request, parse_async, parse_block_hash, parse_chain_id, RpcServiceError,
@@ -20,8 +22,8 @@ use crate::server::{HResult, HasSingleValue, Params, Query, RpcServiceEnvironmen
 use crate::services::{base_services, stream_services};
 use crate::{
     empty, encoding::base_types::*, error, helpers, make_json_response, make_json_stream_response,
-    not_found, parse_block_hash_or_fail, required_param, result_to_empty_json_response,
-    result_to_json_response, services, ServiceResult,
+    not_found, parse_block_hash_or_fail, required_param, result_option_to_json_response,
+    result_to_empty_json_response, result_to_json_response, services, ServiceResult,
 };
 
 pub async fn bootstrapped(
@@ -294,6 +296,23 @@ pub async fn context_raw_bytes(
     )
 }
 
+pub async fn context_merkle_tree(
+    _: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let chain_id = parse_chain_id(required_param!(params, "chain_id")?, &env)?;
+    let block_hash =
+        parse_block_hash_or_fail!(&chain_id, required_param!(params, "block_id")?, &env);
+    let path = params.get_str("any").map(|s| s.to_owned());
+
+    result_option_to_json_response(
+        base_services::get_context_merkle_proof(&chain_id, &block_hash, path, &env).await,
+        env.log(),
+    )
+}
+
 pub async fn mempool_pending_operations(
     _: Request<Body>,
     params: Params,
@@ -363,6 +382,107 @@ pub async fn mempool_request_operations(
     )
 }
 
+pub async fn mempool_filter_get(
+    _: Request<Body>,
+    _: Params,
+    query: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let include_default = query
+        .get_str("include_default")
+        .map_or(true, |value| value.eq("true"));
+
+    result_to_json_response(
+        services::mempool_services::get_mempool_filter(&env, include_default).await,
+        env.log(),
+    )
+}
+
+pub async fn mempool_filter_set(
+    req: Request<Body>,
+    _: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let body = hyper::body::aggregate(req).await?;
+    let filter = match serde_json::from_reader::<_, MempoolFilter>(body.reader()) {
+        Ok(filter) => filter,
+        Err(err) => {
+            return result_to_empty_json_response(
+                Err(RpcServiceError::InvalidParameters {
+                    reason: format!("Invalid mempool filter: {}", err),
+                }),
+                env.log(),
+            )
+        }
+    };
+
+    result_to_empty_json_response(
+        services::mempool_services::set_mempool_filter(&env, filter).await,
+        env.log(),
+    )
+}
+
+/// Parses operation hash from the json string in the request body.
+async fn parse_operation_hash_body(req: Request<Body>) -> Result<OperationHash, RpcServiceError> {
+    let body =
+        hyper::body::aggregate(req)
+            .await
+            .map_err(|err| RpcServiceError::UnexpectedError {
+                reason: err.to_string(),
+            })?;
+    let operation_hash = serde_json::from_reader::<_, String>(body.reader()).map_err(|err| {
+        RpcServiceError::InvalidParameters {
+            reason: format!("Expected operation hash: {}", err),
+        }
+    })?;
+    OperationHash::from_base58_check(&operation_hash).map_err(|_| {
+        RpcServiceError::InvalidParameters {
+            reason: format!("Invalid operation hash '{}'", operation_hash),
+        }
+    })
+}
+
+pub async fn mempool_ban_operation(
+    req: Request<Body>,
+    _: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let result = match parse_operation_hash_body(req).await {
+        Ok(operation_hash) => services::mempool_services::ban_operation(&env, operation_hash).await,
+        Err(err) => Err(err),
+    };
+    result_to_empty_json_response(result, env.log())
+}
+
+pub async fn mempool_unban_operation(
+    req: Request<Body>,
+    _: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let result = match parse_operation_hash_body(req).await {
+        Ok(operation_hash) => {
+            services::mempool_services::unban_operation(&env, operation_hash).await
+        }
+        Err(err) => Err(err),
+    };
+    result_to_empty_json_response(result, env.log())
+}
+
+pub async fn mempool_unban_all_operations(
+    _: Request<Body>,
+    _: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    result_to_empty_json_response(
+        services::mempool_services::unban_all_operations(&env).await,
+        env.log(),
+    )
+}
+
 pub async fn get_block_protocols(
     _: Request<Body>,
     params: Params,
@@ -708,14 +828,20 @@ pub async fn preapply_block(
     )
 }
 
-// TODO: properly implement this handler
-
 #[derive(serde::Serialize)]
 struct P2pStats {
     total_sent: String,
     total_recv: String,
     current_inflow: i64,
     current_outflow: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_download_speed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_upload_speed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peer_max_download_speed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peer_max_upload_speed: Option<u64>,
 }
 
 pub async fn network_stat(
@@ -724,14 +850,18 @@ pub async fn network_stat(
     _: Query,
     env: Arc<RpcServiceEnvironment>,
 ) -> ServiceResult {
-    let dummy = P2pStats {
-        total_sent: "100".into(),
-        total_recv: "100".into(),
-        current_inflow: 10,
-        current_outflow: 10,
-    };
-
-    result_to_json_response(Ok(dummy), env.log())
+    let stats = services::dev_services::get_shell_automaton_network_stats(&env).await?;
+
+    make_json_response(&P2pStats {
+        total_sent: stats.total_sent.to_string(),
+        total_recv: stats.total_recv.to_string(),
+        current_inflow: stats.current_inflow as i64,
+        current_outflow: stats.current_outflow as i64,
+        max_download_speed: stats.bandwidth_limit.download,
+        max_upload_speed: stats.bandwidth_limit.upload,
+        peer_max_download_speed: stats.bandwidth_limit.peer_download,
+        peer_max_upload_speed: stats.bandwidth_limit.peer_upload,
+    })
 }
 
 pub async fn network_connections(
@@ -744,6 +874,188 @@ pub async fn network_connections(
     result_to_json_response(Ok(connections), env.log())
 }
 
+/// Access control of the peer, the `acl` field of the body of the Octez
+/// `PATCH /network/points/<point>` and `PATCH /network/peers/<peer_id>` rpcs.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum NetworkAcl {
+    Ban,
+    Trust,
+    Open,
+}
+
+impl From<NetworkAcl> for NetworkCommand {
+    fn from(acl: NetworkAcl) -> Self {
+        match acl {
+            NetworkAcl::Ban => NetworkCommand::Ban,
+            NetworkAcl::Trust => NetworkCommand::Trust,
+            NetworkAcl::Open => NetworkCommand::Open,
+        }
+    }
+}
+
+/// Body of the `PATCH` network rpcs, `connect` is only accepted for points.
+#[derive(Deserialize, Debug, Default)]
+struct NetworkPatch {
+    acl: Option<NetworkAcl>,
+    #[serde(default)]
+    connect: bool,
+}
+
+fn network_target(
+    point: Option<&str>,
+    peer_id: Option<&str>,
+) -> Result<NetworkTarget, RpcServiceError> {
+    match (point, peer_id) {
+        (Some(point), _) => point.parse().map(NetworkTarget::Point).map_err(|_| {
+            RpcServiceError::InvalidParameters {
+                reason: format!("Invalid point '{}', expected IP:PORT", point),
+            }
+        }),
+        (None, Some(peer_id)) => CryptoboxPublicKeyHash::from_base58_check(peer_id)
+            .map(NetworkTarget::PeerId)
+            .map_err(|_| RpcServiceError::InvalidParameters {
+                reason: format!("Invalid peer id '{}'", peer_id),
+            }),
+        (None, None) => Err(RpcServiceError::InvalidParameters {
+            reason: "Missing point or peer id".to_string(),
+        }),
+    }
+}
+
+/// `PUT` connects to the point, `PATCH` applies the [`NetworkPatch`] body.
+fn network_commands(
+    method: &Method,
+    body: &[u8],
+    target: &NetworkTarget,
+) -> Result<Vec<NetworkCommand>, RpcServiceError> {
+    if *method == Method::PUT {
+        return Ok(vec![NetworkCommand::Connect]);
+    }
+
+    let patch = if body.is_empty() {
+        NetworkPatch::default()
+    } else {
+        serde_json::from_slice::<NetworkPatch>(body).map_err(|err| {
+            RpcServiceError::InvalidParameters {
+                reason: format!("Invalid body: {}", err),
+            }
+        })?
+    };
+    if patch.connect && !matches!(target, NetworkTarget::Point(_)) {
+        return Err(RpcServiceError::InvalidParameters {
+            reason: "Only points can be connected to".to_string(),
+        });
+    }
+
+    Ok(patch
+        .acl
+        .map(NetworkCommand::from)
+        .into_iter()
+        .chain(patch.connect.then(|| NetworkCommand::Connect))
+        .collect())
+}
+
+async fn network_request(
+    req: Request<Body>,
+    params: &Params,
+) -> Result<(NetworkTarget, Vec<NetworkCommand>), RpcServiceError> {
+    let target = network_target(params.get_str("point"), params.get_str("peer_id"))?;
+    let method = req.method().clone();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(|err| RpcServiceError::InvalidParameters {
+            reason: format!("Failed to read the body: {}", err),
+        })?;
+    let commands = network_commands(&method, &body, &target)?;
+    Ok((target, commands))
+}
+
+/// Handles `PUT /network/points/:point`, `PATCH /network/points/:point`
+/// and `PATCH /network/peers/:peer_id`.
+pub async fn network_command(
+    req: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let result = match network_request(req, &params).await {
+        Ok((target, commands)) => {
+            services::dev_services::shell_automaton_network_command(&env, target, commands).await
+        }
+        Err(err) => Err(err),
+    };
+    result_to_empty_json_response(result, env.log())
+}
+
+async fn network_alias(
+    params: Params,
+    command: NetworkCommand,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    let result = match network_target(params.get_str("point"), params.get_str("peer_id")) {
+        Ok(target) => {
+            services::dev_services::shell_automaton_network_command(&env, target, vec![command])
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    result_to_empty_json_response(result, env.log())
+}
+
+/// Handles `GET /network/points/:point/ban` and `GET /network/peers/:peer_id/ban`.
+pub async fn network_ban(
+    _: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    network_alias(params, NetworkCommand::Ban, env).await
+}
+
+/// Handles `GET /network/points/:point/unban` and `GET /network/peers/:peer_id/unban`.
+pub async fn network_unban(
+    _: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    network_alias(params, NetworkCommand::Unban, env).await
+}
+
+/// Handles `GET /network/points/:point/trust` and `GET /network/peers/:peer_id/trust`.
+pub async fn network_trust(
+    _: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    network_alias(params, NetworkCommand::Trust, env).await
+}
+
+/// Handles `GET /network/points/:point/untrust` and `GET /network/peers/:peer_id/untrust`.
+pub async fn network_untrust(
+    _: Request<Body>,
+    params: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    network_alias(params, NetworkCommand::Untrust, env).await
+}
+
+/// Handles `DELETE /network/greylist` and `GET /network/greylist/clear`.
+pub async fn network_greylist_clear(
+    _: Request<Body>,
+    _: Params,
+    _: Query,
+    env: Arc<RpcServiceEnvironment>,
+) -> ServiceResult {
+    result_to_empty_json_response(
+        services::dev_services::shell_automaton_network_greylist_clear(&env).await,
+        env.log(),
+    )
+}
+
 pub async fn node_version(
     _: Request<Body>,
     _: Params,
@@ -882,3 +1194,64 @@ pub async fn worker_prevalidators(
 ) -> ServiceResult {
     result_to_json_response(helpers::get_prevalidators(&env).await, env.log())
 }
+
+#[cfg(test)]
+mod network_command_test {
+    use hyper::Method;
+    use shell_automaton::service::rpc_service::{NetworkCommand, NetworkTarget};
+
+    use super::{network_commands, network_target};
+
+    const PEER_ID: &str = "idtqxHUjbjbCfaDn4jczoPGsnhacKX";
+
+    #[test]
+    fn network_target_parse() {
+        assert!(matches!(
+            network_target(Some("127.0.0.1:9732"), None),
+            Ok(NetworkTarget::Point(_))
+        ));
+        assert!(matches!(
+            network_target(None, Some(PEER_ID)),
+            Ok(NetworkTarget::PeerId(_))
+        ));
+        assert!(network_target(Some("127.0.0.1"), None).is_err());
+        assert!(network_target(None, Some("invalid")).is_err());
+        assert!(network_target(None, None).is_err());
+    }
+
+    #[test]
+    fn network_commands_parse() {
+        let point = network_target(Some("127.0.0.1:9732"), None).unwrap();
+        let peer = network_target(None, Some(PEER_ID)).unwrap();
+
+        assert_eq!(
+            network_commands(&Method::PUT, b"", &point).unwrap(),
+            vec![NetworkCommand::Connect]
+        );
+        assert_eq!(
+            network_commands(&Method::PATCH, b"", &point).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            network_commands(&Method::PATCH, br#"{"acl":"ban"}"#, &peer).unwrap(),
+            vec![NetworkCommand::Ban]
+        );
+        assert_eq!(
+            network_commands(
+                &Method::PATCH,
+                br#"{"acl":"trust","connect":true,"peer_id":"ignored"}"#,
+                &point
+            )
+            .unwrap(),
+            vec![NetworkCommand::Trust, NetworkCommand::Connect]
+        );
+        assert_eq!(
+            network_commands(&Method::PATCH, br#"{"acl":"open"}"#, &point).unwrap(),
+            vec![NetworkCommand::Open]
+        );
+
+        assert!(network_commands(&Method::PATCH, br#"{"acl":"connect"}"#, &point).is_err());
+        assert!(network_commands(&Method::PATCH, br#"{"connect":true}"#, &peer).is_err());
+        assert!(network_commands(&Method::PATCH, b"not json", &point).is_err());
+    }
+}
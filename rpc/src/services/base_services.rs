@@ -10,7 +10,8 @@ use storage::{
     BlockJsonData, BlockMetaStorage, BlockMetaStorageReader, BlockStorage, BlockStorageReader,
     OperationsStorage, OperationsStorageReader,
 };
-use tezos_context_api::{context_key_owned, StringTreeObject};
+use tezos_context_api::merkle_proof::MerkleProof;
+use tezos_context_api::{context_key_owned, ContextKeyOwned, StringTreeObject};
 use tezos_messages::p2p::encoding::version::NetworkVersion;
 
 use crate::helpers::{
@@ -280,6 +281,34 @@ pub(crate) async fn get_context_raw_bytes(
     ))
 }
 
+/// Build the proof that `path` is in the context of the block.
+///
+/// Unlike `get_context_raw_bytes`, the path starts at the root of the context (not at "/data"),
+/// so that the proof can be checked against the context hash of the block.
+pub(crate) async fn get_context_merkle_proof(
+    chain_id: &ChainId,
+    block_hash: &BlockHash,
+    path: Option<String>,
+    env: &RpcServiceEnvironment,
+) -> Result<Option<MerkleProof>, RpcServiceError> {
+    let key: ContextKeyOwned = match path {
+        Some(path) => path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let ctx_hash = get_context_hash(chain_id, block_hash, env)?;
+    env.tezedge_context()
+        .get_merkle_proof(&ctx_hash, key)
+        .await
+        .map_err(|e| RpcServiceError::UnexpectedError {
+            reason: format!("{}", e),
+        })
+}
+
 /// Extract the current_protocol and the next_protocol from the block metadata
 #[cached(
     name = "BLOCK_PROTOCOLS_CACHE",
@@ -9,7 +9,10 @@
 use crypto::hash::{BlockPayloadHash, ContractKt1Hash, OperationHash};
 use serde::{Deserialize, Serialize};
 use shell_automaton::mempool::{OperationKind, OperationValidationResult};
-use shell_automaton::service::rpc_service::RpcShellAutomatonActionsRaw;
+use shell_automaton::peers::acl::PeersAclState;
+use shell_automaton::service::rpc_service::{
+    NetworkCommand, NetworkCommandError, NetworkTarget, PeersAclUpdate, RpcShellAutomatonActionsRaw,
+};
 use shell_automaton::service::statistics_service::ActionKindStatsForBlock;
 use shell_automaton::{Action, ActionWithMeta};
 use slog::Logger;
@@ -24,6 +27,7 @@ use crypto::hash::{BlockHash, ChainId, ContractTz1Hash, ContractTz2Hash, Contrac
 use shell::stats::memory::{Memory, MemoryData, MemoryStatsResult};
 use shell_automaton::service::rpc_service::RpcRequest as RpcShellAutomatonMsg;
 use shell_automaton::ActionId;
+use storage::account_operations_storage::{AccountAddress, AccountOperation, AccountRole};
 use storage::cycle_eras_storage::CycleEra;
 use storage::database::backend::BoxedSliceKV;
 use storage::database::error::Error as DBError;
@@ -33,9 +37,9 @@ use storage::persistent::Decoder;
 //    ContextActionJson, ContextActionRecordValue, ContextActionStorageReader, ContextActionType,
 //};
 use storage::{
-    BlockMetaStorage, BlockMetaStorageReader, BlockStorage, BlockStorageReader, ConstantsStorage,
-    CycleErasStorage, Direction, IteratorMode, PersistentStorage, ShellAutomatonActionStorage,
-    ShellAutomatonStateStorage, StorageError,
+    AccountOperationsStorage, BlockMetaStorage, BlockMetaStorageReader, BlockStorage,
+    BlockStorageReader, ConstantsStorage, CycleErasStorage, Direction, IteratorMode,
+    PersistentStorage, ShellAutomatonActionStorage, ShellAutomatonStateStorage, StorageError,
 };
 //use tezos_context::channel::ContextAction;
 use tezos_messages::base::ConversionError;
@@ -143,28 +147,28 @@ pub(crate) fn get_contract_actions_cursor(
     })
 }
 
-/// Get actions for a specific contract in ascending order.
-#[allow(dead_code)]
-pub(crate) fn get_contract_actions(
-    _contract_id: &str,
-    _from_id: Option<u64>,
-    _limit: usize,
-    _persistent_storage: &PersistentStorage,
-) -> Result<PagedResult<Vec<() /*ContextActionRecordValue*/>>, RpcServiceError> {
-    //let context_action_storage = ensure_context_action_storage(persistent_storage)?;
-    //let contract_address = contract_id_to_contract_address_for_index(contract_id)?;
-    //let mut context_records =
-    //    context_action_storage.get_by_contract_address(&contract_address, from_id, limit + 1)?;
-    //let next_id = if context_records.len() > limit {
-    //    context_records.last().map(|rec| rec.id())
-    //} else {
-    //    None
-    //};
-    //context_records.truncate(std::cmp::min(context_records.len(), limit));
-    //Ok(PagedResult::new(context_records, next_id, limit))
-    Err(RpcServiceError::UnexpectedError {
-        reason: "Persistent context actions storage is not implemented!".to_string(),
-    })
+/// Get operations which touched the account (with the `role`, if any), from the newest one.
+///
+/// Operations are indexed from their contents, so failed and backtracked operations are included.
+pub(crate) fn get_account_operations(
+    address: &str,
+    role: Option<&str>,
+    from_id: Option<u64>,
+    limit: usize,
+    persistent_storage: &PersistentStorage,
+) -> Result<PagedResult<Vec<AccountOperation>>, RpcServiceError> {
+    let address = AccountAddress::from_b58_check(address).ok_or_else(|| {
+        RpcServiceError::InvalidParameters {
+            reason: format!("Invalid account address: {}", address),
+        }
+    })?;
+    let role = role
+        .map(|role| role.parse::<AccountRole>())
+        .transpose()
+        .map_err(|reason| RpcServiceError::InvalidParameters { reason })?;
+    let (operations, next_id) = AccountOperationsStorage::new(persistent_storage)
+        .get_operations(&address, role, from_id, limit)?;
+    Ok(PagedResult::new(operations, next_id, limit))
 }
 
 pub(crate) fn get_stats_memory() -> MemoryStatsResult<MemoryData> {
@@ -412,6 +416,86 @@ pub(crate) async fn get_shell_automaton_storage_requests(
     rx.await
 }
 
+pub(crate) async fn get_shell_automaton_network_stats(
+    env: &RpcServiceEnvironment,
+) -> Result<
+    shell_automaton::service::rpc_service::NetworkStats,
+    tokio::sync::oneshot::error::RecvError,
+> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let _ = env
+        .shell_automaton_sender()
+        .send(RpcShellAutomatonMsg::GetNetworkStats { channel: tx })
+        .await;
+    rx.await
+}
+
+pub(crate) async fn get_shell_automaton_peers_acl(
+    env: &RpcServiceEnvironment,
+    update: Option<PeersAclUpdate>,
+) -> Result<PeersAclState, tokio::sync::oneshot::error::RecvError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let msg = match update {
+        Some(update) => RpcShellAutomatonMsg::UpdatePeersAcl {
+            update,
+            channel: tx,
+        },
+        None => RpcShellAutomatonMsg::GetPeersAcl { channel: tx },
+    };
+    let _ = env.shell_automaton_sender().send(msg).await;
+    rx.await
+}
+
+pub(crate) async fn shell_automaton_network_command(
+    env: &RpcServiceEnvironment,
+    target: NetworkTarget,
+    commands: Vec<NetworkCommand>,
+) -> Result<(), RpcServiceError> {
+    let result = env
+        .shell_automaton_sender()
+        .send(RpcShellAutomatonMsg::NetworkCommand { target, commands })
+        .await
+        .map_err(|err| RpcServiceError::UnexpectedError {
+            reason: err.to_string(),
+        })?
+        .await
+        .map_err(|err| RpcServiceError::UnexpectedError {
+            reason: err.to_string(),
+        })?;
+    let result: Result<(), NetworkCommandError> =
+        serde_json::from_value(result).map_err(|err| RpcServiceError::UnexpectedError {
+            reason: err.to_string(),
+        })?;
+    result.map_err(|err| match err {
+        NetworkCommandError::UnknownPeer => RpcServiceError::NoDataFoundError {
+            reason: err.to_string(),
+        },
+        NetworkCommandError::NotAllowed | NetworkCommandError::Unchanged => {
+            RpcServiceError::InvalidParameters {
+                reason: err.to_string(),
+            }
+        }
+    })
+}
+
+pub(crate) async fn shell_automaton_network_greylist_clear(
+    env: &RpcServiceEnvironment,
+) -> Result<(), RpcServiceError> {
+    env.shell_automaton_sender()
+        .send(RpcShellAutomatonMsg::NetworkGreylistClear)
+        .await
+        .map_err(|err| RpcServiceError::UnexpectedError {
+            reason: err.to_string(),
+        })?
+        .await
+        .map_err(|err| RpcServiceError::UnexpectedError {
+            reason: err.to_string(),
+        })?;
+    Ok(())
+}
+
 pub(crate) async fn get_shell_automaton_state_after(
     env: &RpcServiceEnvironment,
     target_action_id: u64,
@@ -757,6 +841,7 @@ pub struct OperationStats {
     validations: Vec<OperationValidationStats>,
     nodes: HashMap<String, OperationNodeStats>,
     injected_timestamp: Option<u64>,
+    evicted: Option<(i128, shell_automaton::mempool::OperationEvictionReason)>,
 }
 
 #[derive(Serialize)]
@@ -1056,6 +1141,9 @@ fn map_operations_stats(
                         )
                     },
                 ),
+                evicted: op_stats
+                    .evicted
+                    .map(|(t, reason)| ((t as i128).checked_sub(start_time).unwrap_or(0), reason)),
                 validations: op_stats
                     .validations
                     .into_iter()
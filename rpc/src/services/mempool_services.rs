@@ -8,6 +8,7 @@ use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 use shell::validation::CanApplyStatus;
+use shell_automaton::mempool::MempoolFilter;
 use shell_automaton::service::rpc_service::RpcRequest as RpcShellAutomatonMsg;
 use slog::{info, warn};
 
@@ -545,3 +546,81 @@ pub async fn request_operations(env: &RpcServiceEnvironment) -> Result<(), RpcSe
 
     Ok(())
 }
+
+/// Sends the request with the response channel to the shell automaton and waits for the response.
+async fn shell_automaton_request<T>(
+    env: &RpcServiceEnvironment,
+    request: impl FnOnce(tokio::sync::oneshot::Sender<T>) -> RpcShellAutomatonMsg,
+) -> Result<T, RpcServiceError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    env.shell_automaton_sender()
+        .send(request(tx))
+        .await
+        .map_err(|_| RpcServiceError::UnexpectedError {
+            reason: "the channel between rpc and shell is overflown".to_string(),
+        })?;
+    rx.await.map_err(|_| RpcServiceError::UnexpectedError {
+        reason: "state machine failed to respond".to_string(),
+    })
+}
+
+pub async fn get_mempool_filter(
+    env: &RpcServiceEnvironment,
+    include_default: bool,
+) -> Result<serde_json::Value, RpcServiceError> {
+    let filter = shell_automaton_request(env, |channel| RpcShellAutomatonMsg::GetMempoolFilter {
+        channel,
+    })
+    .await?;
+    Ok(filter.to_json(include_default))
+}
+
+pub async fn set_mempool_filter(
+    env: &RpcServiceEnvironment,
+    filter: MempoolFilter,
+) -> Result<(), RpcServiceError> {
+    shell_automaton_request(env, |channel| RpcShellAutomatonMsg::SetMempoolFilter {
+        filter,
+        channel,
+    })
+    .await
+}
+
+pub async fn ban_operation(
+    env: &RpcServiceEnvironment,
+    operation_hash: OperationHash,
+) -> Result<(), RpcServiceError> {
+    shell_automaton_request(env, |channel| RpcShellAutomatonMsg::MempoolOperationBan {
+        operation_hash,
+        channel,
+    })
+    .await
+}
+
+pub async fn unban_operation(
+    env: &RpcServiceEnvironment,
+    operation_hash: OperationHash,
+) -> Result<(), RpcServiceError> {
+    let reason = format!(
+        "Operation {} is not banned",
+        operation_hash.to_base58_check()
+    );
+    let unbanned =
+        shell_automaton_request(env, |channel| RpcShellAutomatonMsg::MempoolOperationUnban {
+            operation_hash,
+            channel,
+        })
+        .await?;
+    if unbanned {
+        Ok(())
+    } else {
+        Err(RpcServiceError::InvalidParameters { reason })
+    }
+}
+
+pub async fn unban_all_operations(env: &RpcServiceEnvironment) -> Result<(), RpcServiceError> {
+    shell_automaton_request(env, |channel| {
+        RpcShellAutomatonMsg::MempoolOperationUnbanAll { channel }
+    })
+    .await
+}
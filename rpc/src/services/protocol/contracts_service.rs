@@ -0,0 +1,138 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Contract rpcs answered directly from the context.
+//!
+//! Contracts are stored under the flat path `data/contracts/index/<hex of the contract id>`
+//! in all the supported protocols, so the implementation is shared, see
+//! [tezos_messages::base::contract]. Whatever can't be answered from the context
+//! (e.g. the contract is not allocated) is left to the protocol.
+//!
+//! Contract's `storage` and `script` are not provided here, they are always left to
+//! the protocol: they are stored as binary Micheline, and there is no Micheline decoder
+//! to convert them to their json representation.
+
+use crypto::hash::ContextHash;
+use tezos_context_api::ContextValue;
+use tezos_messages::base::contract::{
+    ContractField, ContractFieldValue, ContractId, ContractManager,
+};
+
+use crate::server::RpcServiceEnvironment;
+use crate::services::protocol::ContractsError;
+
+/// Gets the `field` of the contract from the context.
+///
+/// Returns `Ok(None)` if the contract doesn't have the field (e.g. no delegate is set),
+/// or [ContractsError::NotFoundInContext] if the protocol has to be asked.
+pub(crate) async fn get_contract_field(
+    env: &RpcServiceEnvironment,
+    context_hash: &ContextHash,
+    contract_id: &str,
+    field: ContractField,
+) -> Result<Option<serde_json::Value>, ContractsError> {
+    let contract_id = ContractId::from_b58_check(contract_id)
+        .filter(|id| matches!(id, ContractId::Implicit(_)) || !field.implicit_only())
+        .ok_or(ContractsError::NotFoundInContext)?;
+    let path = contract_id
+        .context_path()
+        .ok_or(ContractsError::NotFoundInContext)?;
+
+    let value = get_contract_key(env, context_hash, &path, field).await?;
+    let value = match value {
+        Some(value) => value,
+        // allocated contract without a delegate
+        None if field == ContractField::Delegate => {
+            return match get_contract_key(env, context_hash, &path, ContractField::Balance).await? {
+                Some(_) => Ok(None),
+                None => Err(ContractsError::NotFoundInContext),
+            };
+        }
+        None => return Err(ContractsError::NotFoundInContext),
+    };
+
+    decode_contract_field(field, &value).map(Some)
+}
+
+async fn get_contract_key(
+    env: &RpcServiceEnvironment,
+    context_hash: &ContextHash,
+    path: &[String],
+    field: ContractField,
+) -> Result<Option<ContextValue>, ContractsError> {
+    let mut key = path.to_vec();
+    key.push(field.context_key().to_string());
+    Ok(env
+        .tezedge_context()
+        .get_key_from_history(context_hash, key)
+        .await?)
+}
+
+/// Converts the context value of the `field` to its json representation.
+fn decode_contract_field(
+    field: ContractField,
+    value: &[u8],
+) -> Result<serde_json::Value, ContractsError> {
+    let json = match ContractFieldValue::from_bytes(field, value)? {
+        ContractFieldValue::Balance(balance) => serde_json::Value::String(balance.0.to_string()),
+        ContractFieldValue::Counter(counter) => serde_json::Value::String(counter.0.to_string()),
+        // the public key is known only once it is revealed.
+        ContractFieldValue::Manager(ContractManager::PublicKeyHash(_)) => serde_json::Value::Null,
+        ContractFieldValue::Manager(ContractManager::PublicKey(public_key)) => {
+            serde_json::Value::String(public_key.to_string_representation())
+        }
+        ContractFieldValue::Delegate(delegate) => {
+            serde_json::Value::String(delegate.to_string_representation())
+        }
+    };
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use tezos_messages::base::signature_public_key::{SignaturePublicKey, SignaturePublicKeyHash};
+    use tezos_messages::p2p::binary_message::BinaryWrite;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_contract_field() {
+        assert_eq!(
+            decode_contract_field(ContractField::Balance, &[0x80, 0x01]).unwrap(),
+            serde_json::json!("128")
+        );
+        assert_eq!(
+            decode_contract_field(ContractField::Counter, &[0x2a]).unwrap(),
+            serde_json::json!("42")
+        );
+
+        let public_key = "edpkuBknW28nW72KG6RoHtYW7p12T6GKc7nAbwYX5m8Wd9sDVC9yav";
+        let mut manager = vec![1];
+        manager.extend(
+            SignaturePublicKey::from_b58_hash(public_key)
+                .unwrap()
+                .as_bytes()
+                .unwrap(),
+        );
+        assert_eq!(
+            decode_contract_field(ContractField::Manager, &manager).unwrap(),
+            serde_json::json!(public_key)
+        );
+
+        let delegate = "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx";
+        let pkh = SignaturePublicKeyHash::from_b58_hash(delegate)
+            .unwrap()
+            .as_bytes()
+            .unwrap();
+        let mut manager = vec![0];
+        manager.extend(&pkh);
+        assert_eq!(
+            decode_contract_field(ContractField::Manager, &manager).unwrap(),
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            decode_contract_field(ContractField::Delegate, &pkh).unwrap(),
+            serde_json::json!(delegate)
+        );
+    }
+}
@@ -33,6 +33,10 @@ use crate::services::base_services::{
 };
 use tezos_context_api::context_key_owned;
 use tezos_context_ipc_client::TezedgeContextClientError;
+use tezos_encoding::binary_reader::BinaryReaderError;
+
+mod contracts_service;
+pub use tezos_messages::base::contract::ContractField;
 
 mod proto_001;
 mod proto_002;
@@ -574,6 +578,110 @@ pub(crate) async fn get_votes_listings(
     }
 }
 
+#[derive(Debug, Error)]
+pub enum ContractsError {
+    #[error("Rpc service error, reason: {reason}")]
+    RpcServiceError { reason: RpcServiceError },
+    #[error("Contracts error, reason: {reason}")]
+    ServiceError { reason: Error },
+    #[error("Unsupported protocol {protocol}")]
+    UnsupportedProtocolError { protocol: String },
+    /// The contract's data is not in the context (e.g. the contract is not allocated),
+    /// so the request has to be answered by the protocol.
+    #[error("Contract not found in context")]
+    NotFoundInContext,
+}
+
+impl From<TezedgeContextClientError> for ContractsError {
+    fn from(error: TezedgeContextClientError) -> Self {
+        ContractsError::ServiceError {
+            reason: error.into(),
+        }
+    }
+}
+
+impl From<ConversionError> for ContractsError {
+    fn from(error: ConversionError) -> Self {
+        ContractsError::ServiceError {
+            reason: error.into(),
+        }
+    }
+}
+
+impl From<BinaryReaderError> for ContractsError {
+    fn from(error: BinaryReaderError) -> Self {
+        ContractsError::ServiceError {
+            reason: error.into(),
+        }
+    }
+}
+
+impl From<FromBytesError> for ContractsError {
+    fn from(error: FromBytesError) -> Self {
+        ContractsError::ServiceError {
+            reason: error.into(),
+        }
+    }
+}
+
+impl From<UnsupportedProtocolError> for ContractsError {
+    fn from(error: UnsupportedProtocolError) -> Self {
+        ContractsError::UnsupportedProtocolError {
+            protocol: error.protocol,
+        }
+    }
+}
+
+impl From<RpcServiceError> for ContractsError {
+    fn from(reason: RpcServiceError) -> Self {
+        ContractsError::RpcServiceError { reason }
+    }
+}
+
+/// Get the `field` of the contract directly from the context.
+///
+/// # Arguments
+///
+/// * `chain_id` - Url path parameter 'chain_id'.
+/// * `block_hash` - Url path parameter 'block_id', it contains string "head", block level or block hash.
+/// * `contract_id` - Url path parameter 'contract_id'.
+/// * `field` - Requested contract's data.
+pub(crate) async fn get_contract_field(
+    chain_id: &ChainId,
+    block_hash: &BlockHash,
+    contract_id: &str,
+    field: ContractField,
+    env: &RpcServiceEnvironment,
+) -> Result<Option<serde_json::Value>, ContractsError> {
+    let context_hash = get_context_hash(chain_id, block_hash, env)?;
+
+    // get protocol version
+    let protocol_hash = if let Some(protocol_hash) = env
+        .tezedge_context()
+        .get_key_from_history(&context_hash, context_key_owned!("protocol"))
+        .await?
+    {
+        ProtocolHash::try_from(protocol_hash)?
+    } else {
+        return Err(ContractsError::ServiceError {
+            reason: format_err!(
+                "No protocol found in context for block_hash: {}",
+                block_hash.to_base58_check()
+            ),
+        });
+    };
+
+    // only the protocols with the flat contracts index are supported
+    match SupportedProtocol::try_from(protocol_hash)? {
+        SupportedProtocol::Proto010 | SupportedProtocol::Proto011 | SupportedProtocol::Proto012 => {
+            contracts_service::get_contract_field(env, &context_hash, contract_id, field).await
+        }
+        supported_protocol => Err(ContractsError::UnsupportedProtocolError {
+            protocol: supported_protocol.protocol_hash(),
+        }),
+    }
+}
+
 /// Get protocol context constants from context list
 /// (just for RPC render use-case, do not use in processing or algorithms)
 ///
@@ -13,43 +13,60 @@ use std::time::{Duration, SystemTime};
 
 use rand::{rngs::StdRng, Rng, SeedableRng as _};
 use slog::{info, warn, Logger};
-use storage::{PersistentStorage, StorageInitInfo};
+use storage::{HistoryMode, PersistentStorage, StorageInitInfo, SystemStorage};
 
 use networking::network_channel::NetworkChannelRef;
 use tezos_identity::Identity;
 use tezos_messages::p2p::encoding::block_header::Level;
 use tezos_protocol_ipc_client::{ProtocolRunnerApi, ProtocolRunnerConfiguration};
 
+use shell_automaton::mempool::MempoolFilter;
+pub use shell_automaton::peers::acl::IpCidr;
 pub use shell_automaton::service::actors_service::{
     ActorsMessageFrom as ShellAutomatonMsg, AutomatonSyncSender as ShellAutomatonSender,
 };
 pub use shell_automaton::service::actors_service::{ApplyBlockCallback, ApplyBlockResult};
 use shell_automaton::service::mio_service::MioInternalEventsContainer;
 use shell_automaton::service::rpc_service::RpcShellAutomatonSender;
+pub use shell_automaton::service::P2pRecorderConfig;
 use shell_automaton::service::{
-    ActorsServiceDefault, DnsServiceDefault, MioServiceDefault, ProtocolRunnerServiceDefault,
-    RpcServiceDefault, ServiceDefault, StorageServiceDefault,
+    ActorsServiceDefault, DnsServiceDefault, MioServiceDefault, P2pRecorderService,
+    ProtocolRunnerServiceDefault, RpcServiceDefault, ServiceDefault,
+    SignatureVerifierServiceDefault, StorageServiceDefault,
 };
 use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+pub use shell_automaton::BandwidthLimit;
+pub use shell_automaton::MempoolLimits;
+pub use shell_automaton::PeersScoreThresholds;
 use shell_automaton::ShellAutomaton;
 
 use crate::PeerConnectionThreshold;
 
 #[derive(Debug, Clone)]
 pub struct P2p {
-    /// Node p2p port
-    pub listener_port: u16,
-    /// P2p socket address, where node listens for incoming p2p connections
-    pub listener_address: SocketAddr,
+    /// P2p socket addresses (IPv4 or IPv6), where node listens for incoming p2p connections
+    pub listener_addresses: Vec<SocketAddr>,
+    /// Public address advertised to the peers, if it differs from the listener address (e.g. NAT)
+    pub public_address: Option<SocketAddr>,
 
     pub disable_mempool: bool,
     pub disable_block_precheck: bool,
     pub disable_endorsements_precheck: bool,
+    pub disable_manager_operations_precheck: bool,
     pub disable_peer_graylist: bool,
     pub private_node: bool,
 
+    /// Scores at which the misbehaving peer is disconnected, graylisted or banned
+    pub peers_score_thresholds: PeersScoreThresholds,
+
     pub peer_threshold: PeerConnectionThreshold,
 
+    /// Upload/download rate limits, global and per peer
+    pub bandwidth_limit: BandwidthLimit,
+
+    /// Bounds of the pending and validated mempool operations
+    pub mempool_limits: MempoolLimits,
+
     /// Bootstrap lookup addresses disable/enable
     pub disable_bootstrap_lookup: bool,
     /// Used for lookup with DEFAULT_P2P_PORT_FOR_LOOKUP
@@ -58,6 +75,14 @@ pub struct P2p {
     /// Peers (IP:port) which we try to connect all the time
     pub bootstrap_peers: Vec<SocketAddr>,
 
+    /// Trusted peers (IP:port), always reconnected, never graylisted
+    /// and not limited by the peer threshold
+    pub trusted_peers: Vec<SocketAddr>,
+    /// If not empty, incoming connections are accepted only from these ip ranges
+    pub incoming_allow: Vec<IpCidr>,
+    /// Incoming connections from these ip ranges are rejected
+    pub incoming_deny: Vec<IpCidr>,
+
     pub current_head_level_override: Option<Level>,
 
     /// Randomness seed for [shell_automaton::ShellAutomaton].
@@ -65,10 +90,24 @@ pub struct P2p {
 
     pub record_shell_automaton_state_snapshots: bool,
     pub record_shell_automaton_actions: bool,
+
+    /// If set, decrypted p2p messages are recorded into the rotating files
+    pub p2p_recorder: Option<P2pRecorderConfig>,
+
+    /// Number of threads verifying signatures of the operations
+    pub signature_verifier_threads: usize,
 }
 
 impl P2p {
     pub const DEFAULT_P2P_PORT_FOR_LOOKUP: u16 = 9732;
+
+    /// Port advertised to the peers, the port of the public address
+    /// or of the first listener address.
+    pub fn advertised_port(&self) -> u16 {
+        self.public_address
+            .or_else(|| self.listener_addresses.first().copied())
+            .map_or(Self::DEFAULT_P2P_PORT_FOR_LOOKUP, |address| address.port())
+    }
 }
 
 enum ShellAutomatonThreadHandle {
@@ -96,6 +135,8 @@ impl ShellAutomatonManager {
         pow_target: f64,
         init_storage_data: StorageInitInfo,
         protocol_runner_config: ProtocolRunnerConfiguration,
+        history_mode: HistoryMode,
+        index_account_operations: bool,
         context_init_status_sender: tokio::sync::watch::Sender<bool>,
     ) -> (Self, RpcShellAutomatonSender) {
         // resolve all bootstrap addresses - init from bootstrap_peers
@@ -111,10 +152,6 @@ impl ShellAutomatonManager {
             bootstrap_addresses.extend(p2p_config.bootstrap_lookup_addresses.iter().cloned());
         };
 
-        // override port passed listener address
-        let mut listener_addr = p2p_config.listener_address;
-        listener_addr.set_port(p2p_config.listener_port);
-
         let seed = p2p_config.randomness_seed.unwrap_or_else(|| {
             let seed = rand::thread_rng().gen();
             info!(log, "Automaton's randomness seed selected"; "seed" => seed);
@@ -122,7 +159,7 @@ impl ShellAutomatonManager {
         });
 
         let mio_service = MioServiceDefault::new(
-            listener_addr,
+            p2p_config.listener_addresses.clone(),
             // Buffer size for reading. Chunk size is 2 bytes (u16) and that is
             // the max number of bytes that we will want to read from kernel at
             // any given point.
@@ -130,8 +167,22 @@ impl ShellAutomatonManager {
         );
         let (rpc_service, rpc_channel) = RpcServiceDefault::new(mio_service.waker(), 128);
 
-        let storage_service =
-            StorageServiceDefault::init(log.clone(), mio_service.waker(), persistent_storage, 4096);
+        let mempool_filter = Self::load_mempool_filter(&persistent_storage, &log);
+
+        let storage_service = StorageServiceDefault::init(
+            log.clone(),
+            mio_service.waker(),
+            persistent_storage,
+            history_mode,
+            index_account_operations,
+            4096,
+        );
+
+        let signature_verifier_service = SignatureVerifierServiceDefault::init(
+            mio_service.waker(),
+            p2p_config.signature_verifier_threads,
+            4096,
+        );
 
         let (automaton_sender, automaton_receiver) =
             shell_automaton::service::actors_service::sync_channel(
@@ -147,6 +198,24 @@ impl ShellAutomatonManager {
             log.new(slog::o!("service" => "protocol_runner")),
         );
 
+        let p2p_recorder = p2p_config.p2p_recorder.clone().and_then(|config| {
+            match P2pRecorderService::new(
+                config.clone(),
+                log.new(slog::o!("service" => "p2p_recorder")),
+            ) {
+                Ok(recorder) => {
+                    info!(log, "Recording p2p traffic"; "dir" => config.dir.display().to_string());
+                    Some(recorder)
+                }
+                Err(err) => {
+                    warn!(log, "Failed to initialize p2p recorder";
+                               "dir" => config.dir.display().to_string(),
+                               "error" => format!("{:?}", err));
+                    None
+                }
+            }
+        });
+
         let service = ServiceDefault {
             randomness: StdRng::seed_from_u64(seed),
             dns: DnsServiceDefault::default(),
@@ -155,7 +224,9 @@ impl ShellAutomatonManager {
             storage: storage_service,
             rpc: rpc_service,
             actors: ActorsServiceDefault::new(automaton_receiver, network_channel),
+            signature_verifier: signature_verifier_service,
             statistics: Some(Default::default()),
+            p2p_recorder,
         };
 
         let events = MioInternalEventsContainer::with_capacity(1024);
@@ -167,7 +238,8 @@ impl ShellAutomatonManager {
             protocol_runner: protocol_runner_config,
             init_storage_data,
 
-            port: p2p_config.listener_port,
+            port: p2p_config.advertised_port(),
+            public_address: p2p_config.public_address,
             disable_mempool: p2p_config.disable_mempool,
             private_node: p2p_config.private_node,
             identity: (*identity).clone(),
@@ -193,6 +265,15 @@ impl ShellAutomatonManager {
 
             peers_graylist_disable: p2p_config.disable_peer_graylist,
             peers_graylist_timeout: Duration::from_secs(15 * 60),
+            peers_score_thresholds: p2p_config.peers_score_thresholds.clone(),
+
+            peers_trusted: p2p_config.trusted_peers.clone(),
+            peers_trusted_reconnect_interval: Duration::from_secs(5),
+            peers_incoming_allow: p2p_config.incoming_allow.clone(),
+            peers_incoming_deny: p2p_config.incoming_deny.clone(),
+
+            peers_swap_disable: p2p_config.private_node,
+            peers_swap_linger: Duration::from_secs(30),
 
             bootstrap_block_header_get_timeout: Duration::from_millis(500),
             bootstrap_block_operations_get_timeout: Duration::from_millis(1000),
@@ -213,10 +294,14 @@ impl ShellAutomatonManager {
                 read_quota: env_variable("QUOTA_READ_BYTES").unwrap_or(3 * 1024 * 1024), // 3MB
                 write_quota: env_variable("QUOTA_WRITE_BYTES").unwrap_or(3 * 1024 * 1024), // 3MB
             },
+            bandwidth_limit: p2p_config.bandwidth_limit.clone(),
+            mempool_limits: p2p_config.mempool_limits.clone(),
             disable_block_precheck: p2p_config.disable_block_precheck,
             disable_endorsements_precheck: p2p_config.disable_endorsements_precheck,
+            disable_manager_operations_precheck: p2p_config.disable_manager_operations_precheck,
         });
 
+        initial_state.mempool.filter = mempool_filter;
         initial_state.set_logger(log.clone());
 
         let shell_automaton = ShellAutomaton::new(initial_state, service, events);
@@ -232,6 +317,28 @@ impl ShellAutomatonManager {
         (this, rpc_channel)
     }
 
+    /// Mempool filter persisted by the last `mempool/filter` rpc call, or the default one.
+    fn load_mempool_filter(persistent_storage: &PersistentStorage, log: &Logger) -> MempoolFilter {
+        let filter = match SystemStorage::new(persistent_storage.main_db()).get_mempool_filter() {
+            Ok(Some(filter)) => filter,
+            Ok(None) => return MempoolFilter::default(),
+            Err(err) => {
+                warn!(log, "Failed to load mempool filter"; "error" => format!("{:?}", err));
+                return MempoolFilter::default();
+            }
+        };
+        match serde_json::from_str(&filter) {
+            Ok(filter) => {
+                info!(log, "Mempool filter loaded"; "filter" => format!("{:?}", filter));
+                filter
+            }
+            Err(err) => {
+                warn!(log, "Failed to decode mempool filter"; "filter" => filter, "error" => format!("{:?}", err));
+                MempoolFilter::default()
+            }
+        }
+    }
+
     pub fn start(&mut self) {
         if let Some(ShellAutomatonThreadHandle::NotRunning(mut shell_automaton)) =
             self.shell_automaton_thread_handle.take()
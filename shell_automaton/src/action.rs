@@ -36,6 +36,7 @@ use crate::peer::remote_requests::block_header_get::*;
 use crate::peer::remote_requests::block_operations_get::*;
 use crate::peer::remote_requests::current_branch_get::*;
 use crate::peer::requests::potential_peers_get::*;
+use crate::peer::requests::protocols_get::*;
 use crate::peer::{
     PeerCurrentHeadUpdateAction, PeerTryReadLoopFinishAction, PeerTryReadLoopStartAction,
     PeerTryWriteLoopFinishAction, PeerTryWriteLoopStartAction,
@@ -55,22 +56,38 @@ use crate::peer::disconnection::{PeerDisconnectAction, PeerDisconnectedAction};
 
 use crate::peer::handshaking::*;
 
+use crate::mempool::storage::*;
 use crate::mempool::validator::*;
+use crate::peers::acl::{
+    PeersAclIncomingSetAction, PeersAclTrustedAddAction, PeersAclTrustedReconnectAction,
+    PeersAclTrustedRemoveAction,
+};
 use crate::peers::add::multi::PeersAddMultiAction;
 use crate::peers::add::PeersAddIncomingPeerAction;
+use crate::peers::address_book::{
+    PeersAddressBookLoadErrorAction, PeersAddressBookLoadInitAction,
+    PeersAddressBookLoadPendingAction, PeersAddressBookLoadSuccessAction,
+};
 use crate::peers::check::timeouts::{
     PeersCheckTimeoutsCleanupAction, PeersCheckTimeoutsInitAction, PeersCheckTimeoutsSuccessAction,
 };
+use crate::peers::connect::PeersConnectPointAction;
 use crate::peers::dns_lookup::{
     PeersDnsLookupCleanupAction, PeersDnsLookupErrorAction, PeersDnsLookupInitAction,
     PeersDnsLookupSuccessAction,
 };
 use crate::peers::graylist::{
-    PeersGraylistAddressAction, PeersGraylistIpAddAction, PeersGraylistIpAddedAction,
-    PeersGraylistIpRemoveAction, PeersGraylistIpRemovedAction,
+    PeersGraylistAddressAction, PeersGraylistAddressBanAction, PeersGraylistAddressUnbanAction,
+    PeersGraylistClearAction, PeersGraylistIpAddAction, PeersGraylistIpAddedAction,
+    PeersGraylistIpBanAction, PeersGraylistIpRemoveAction, PeersGraylistIpRemovedAction,
 };
 use crate::peers::init::PeersInitAction;
 use crate::peers::remove::PeersRemoveAction;
+use crate::peers::score::PeersScoreUpdateAction;
+use crate::peers::swap::{
+    PeersSwapAckReceivedAction, PeersSwapErrorAction, PeersSwapInitAction,
+    PeersSwapRequestReceivedAction, PeersSwapRequestSendAction, PeersSwapSuccessAction,
+};
 use crate::prechecker::prechecker_actions::*;
 
 use crate::rights::rights_actions::*;
@@ -303,16 +320,41 @@ pub enum Action {
     PeersDnsLookupSuccess(PeersDnsLookupSuccessAction),
     PeersDnsLookupCleanup(PeersDnsLookupCleanupAction),
 
+    PeersAddressBookLoadInit(PeersAddressBookLoadInitAction),
+    PeersAddressBookLoadPending(PeersAddressBookLoadPendingAction),
+    PeersAddressBookLoadError(PeersAddressBookLoadErrorAction),
+    PeersAddressBookLoadSuccess(PeersAddressBookLoadSuccessAction),
+
+    PeersSwapRequestSend(PeersSwapRequestSendAction),
+    PeersSwapRequestReceived(PeersSwapRequestReceivedAction),
+    PeersSwapAckReceived(PeersSwapAckReceivedAction),
+    PeersSwapInit(PeersSwapInitAction),
+    PeersSwapSuccess(PeersSwapSuccessAction),
+    PeersSwapError(PeersSwapErrorAction),
+
+    PeersAclTrustedAdd(PeersAclTrustedAddAction),
+    PeersAclTrustedRemove(PeersAclTrustedRemoveAction),
+    PeersAclTrustedReconnect(PeersAclTrustedReconnectAction),
+    PeersAclIncomingSet(PeersAclIncomingSetAction),
+
     PeersGraylistAddress(PeersGraylistAddressAction),
     PeersGraylistIpAdd(PeersGraylistIpAddAction),
     PeersGraylistIpAdded(PeersGraylistIpAddedAction),
+    PeersGraylistIpBan(PeersGraylistIpBanAction),
     PeersGraylistIpRemove(PeersGraylistIpRemoveAction),
     PeersGraylistIpRemoved(PeersGraylistIpRemovedAction),
+    PeersGraylistAddressBan(PeersGraylistAddressBanAction),
+    PeersGraylistAddressUnban(PeersGraylistAddressUnbanAction),
+    PeersGraylistClear(PeersGraylistClearAction),
+
+    PeersScoreUpdate(PeersScoreUpdateAction),
 
     PeersAddIncomingPeer(PeersAddIncomingPeerAction),
     PeersAddMulti(PeersAddMultiAction),
     PeersRemove(PeersRemoveAction),
 
+    PeersConnectPoint(PeersConnectPointAction),
+
     PeersCheckTimeoutsInit(PeersCheckTimeoutsInitAction),
     PeersCheckTimeoutsSuccess(PeersCheckTimeoutsSuccessAction),
     PeersCheckTimeoutsCleanup(PeersCheckTimeoutsCleanupAction),
@@ -416,6 +458,12 @@ pub enum Action {
     PeerRequestsPotentialPeersGetSuccess(PeerRequestsPotentialPeersGetSuccessAction),
     PeerRequestsPotentialPeersGetFinish(PeerRequestsPotentialPeersGetFinishAction),
 
+    PeerRequestsProtocolsGetInit(PeerRequestsProtocolsGetInitAction),
+    PeerRequestsProtocolsGetPending(PeerRequestsProtocolsGetPendingAction),
+    PeerRequestsProtocolsGetError(PeerRequestsProtocolsGetErrorAction),
+    PeerRequestsProtocolsGetSuccess(PeerRequestsProtocolsGetSuccessAction),
+    PeerRequestsProtocolsGetFinish(PeerRequestsProtocolsGetFinishAction),
+
     PeerRemoteRequestsBlockHeaderGetEnqueue(PeerRemoteRequestsBlockHeaderGetEnqueueAction),
     PeerRemoteRequestsBlockHeaderGetInitNext(PeerRemoteRequestsBlockHeaderGetInitNextAction),
     PeerRemoteRequestsBlockHeaderGetPending(PeerRemoteRequestsBlockHeaderGetPendingAction),
@@ -508,6 +556,10 @@ pub enum Action {
     MempoolOperationDecoded(MempoolOperationDecodedAction),
     MempoolRpcEndorsementsStatusGet(MempoolRpcEndorsementsStatusGetAction),
     MempoolOperationValidateNext(MempoolOperationValidateNextAction),
+    MempoolFilterSet(MempoolFilterSetAction),
+    MempoolOperationBan(MempoolOperationBanAction),
+    MempoolOperationUnban(MempoolOperationUnbanAction),
+    MempoolOperationUnbanAll(MempoolOperationUnbanAllAction),
 
     MempoolValidatorInit(MempoolValidatorInitAction),
     MempoolValidatorPending(MempoolValidatorPendingAction),
@@ -518,6 +570,12 @@ pub enum Action {
     MempoolValidatorValidatePending(MempoolValidatorValidatePendingAction),
     MempoolValidatorValidateSuccess(MempoolValidatorValidateSuccessAction),
 
+    MempoolStorageLoadInit(MempoolStorageLoadInitAction),
+    MempoolStorageLoadPending(MempoolStorageLoadPendingAction),
+    MempoolStorageLoadError(MempoolStorageLoadErrorAction),
+    MempoolStorageLoadSuccess(MempoolStorageLoadSuccessAction),
+    MempoolStorageSync(MempoolStorageSyncAction),
+
     BlockInject(BlockInjectAction),
 
     PrecheckerPrecheckOperationRequest(PrecheckerPrecheckOperationRequestAction),
@@ -536,6 +594,11 @@ pub enum Action {
     PrecheckerValidateEndorsement(PrecheckerValidateEndorsementAction),
     PrecheckerEndorsementValidationApplied(PrecheckerEndorsementValidationAppliedAction),
     PrecheckerEndorsementValidationRefused(PrecheckerEndorsementValidationRefusedAction),
+    PrecheckerValidateManagerOperation(PrecheckerValidateManagerOperationAction),
+    PrecheckerManagerContextPending(PrecheckerManagerContextPendingAction),
+    PrecheckerManagerContextReady(PrecheckerManagerContextReadyAction),
+    PrecheckerManagerOperationValidationRefused(PrecheckerManagerOperationValidationRefusedAction),
+    PrecheckerSignatureVerified(PrecheckerSignatureVerifiedAction),
     PrecheckerProtocolNeeded(PrecheckerProtocolNeededAction),
     PrecheckerError(PrecheckerErrorAction),
     PrecheckerPrecacheEndorsingRights(PrecheckerPrecacheEndorsingRightsAction),
@@ -568,6 +631,7 @@ pub enum Action {
 
     CurrentHeadReceived(CurrentHeadReceivedAction),
     CurrentHeadPrecheck(CurrentHeadPrecheckAction),
+    CurrentHeadPrecheckSignatureVerified(CurrentHeadPrecheckSignatureVerifiedAction),
     CurrentHeadPrecheckSuccess(CurrentHeadPrecheckSuccessAction),
     CurrentHeadPrecheckRejected(CurrentHeadPrecheckRejectedAction),
     CurrentHeadError(CurrentHeadErrorAction),
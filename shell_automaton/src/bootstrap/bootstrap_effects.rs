@@ -22,7 +22,7 @@ use crate::service::actors_service::ActorsMessageTo;
 use crate::service::storage_service::StorageRequestPayload;
 use crate::service::{ActorsService, RandomnessService};
 use crate::storage::request::{StorageRequestCreateAction, StorageRequestor};
-use crate::{Action, ActionWithMeta, Service, Store};
+use crate::{Action, ActionWithMeta, Service, State, Store};
 
 use super::{
     BootstrapError, BootstrapErrorAction, BootstrapFinishedAction,
@@ -228,6 +228,7 @@ where
                 .or_else(|| {
                     let handshaked_iter = state.peers.handshaked_iter();
                     let peers = handshaked_iter.map(|(addr, _)| addr).collect::<Vec<_>>();
+                    let peers = prefer_well_scored_peers(state, peers);
                     store.service.randomness().choose_peer(&peers)
                 }) {
                 Some(v) => v,
@@ -495,16 +496,44 @@ where
     S: Service,
 {
     let state = store.state.get();
-    let peers = state
-        .peers
-        .handshaked_iter()
-        .map(|(addr, _)| addr)
-        .collect::<BTreeSet<_>>();
+    // peers with the best score get the intervals first.
+    let peers = peers_by_score(state, state.peers.handshaked_iter().map(|(addr, _)| addr));
     for peer in peers {
         store.dispatch(BootstrapPeerBlockHeaderGetInitAction { peer });
     }
 }
 
+/// Sorts peers by their score, the best first.
+fn peers_by_score<I>(state: &State, peers: I) -> Vec<SocketAddr>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let time = state.time_as_nanos();
+    let recovery_interval = state.config.peers_score_thresholds.recovery_interval;
+    let mut peers = peers
+        .into_iter()
+        .map(|addr| (state.peers.score(&addr.ip(), time, recovery_interval), addr))
+        .collect::<Vec<_>>();
+    peers.sort_by(|(score1, addr1), (score2, addr2)| score2.cmp(score1).then(addr1.cmp(addr2)));
+    peers.into_iter().map(|(_, addr)| addr).collect()
+}
+
+/// Filters out peers with negative score, unless there are no other peers.
+fn prefer_well_scored_peers(state: &State, peers: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let time = state.time_as_nanos();
+    let recovery_interval = state.config.peers_score_thresholds.recovery_interval;
+    let well_scored = peers
+        .iter()
+        .copied()
+        .filter(|addr| state.peers.score(&addr.ip(), time, recovery_interval) >= 0)
+        .collect::<Vec<_>>();
+    if well_scored.is_empty() {
+        peers
+    } else {
+        well_scored
+    }
+}
+
 pub fn retry_block_operations_request<S>(store: &mut Store<S>, block_hash: BlockHash)
 where
     S: Service,
@@ -539,6 +568,7 @@ where
     } else {
         new_peers
     };
+    let peers = prefer_well_scored_peers(store.state(), peers);
     let peer = match store.service.randomness().choose_peer(&peers) {
         Some(v) => v,
         // TODO(zura): log.
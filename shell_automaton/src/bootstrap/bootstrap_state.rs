@@ -714,6 +714,20 @@ impl BootstrapState {
         }
     }
 
+    /// State of the block operations requested from the `peer`.
+    pub fn peer_block_operations_get(
+        &self,
+        peer: SocketAddr,
+        block_hash: &BlockHash,
+    ) -> Option<&PeerBlockOperationsGetState> {
+        match self {
+            Self::PeersBlockOperationsGetPending { pending, .. } => {
+                pending.get(block_hash).and_then(|b| b.peers.get(&peer))
+            }
+            _ => None,
+        }
+    }
+
     pub fn next_block_for_apply(&self) -> Option<&BlockHash> {
         match self {
             Self::PeersBlockOperationsGetPending { pending, .. } => pending
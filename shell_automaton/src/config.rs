@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT
 
 use std::convert::TryFrom;
+use std::net::SocketAddr;
 use std::time::{Duration, SystemTime};
 
 use hex::FromHex;
@@ -15,6 +16,7 @@ use tezos_context_api::{
 use tezos_messages::p2p::encoding::block_header::Level;
 use tezos_protocol_ipc_client::ProtocolRunnerConfiguration;
 
+use crate::peers::acl::IpCidr;
 use crate::shell_compatibility_version::ShellCompatibilityVersion;
 use crypto::{
     crypto_box::{CryptoKey, PublicKey, SecretKey},
@@ -53,7 +55,11 @@ pub struct Config {
     pub protocol_runner: ProtocolRunnerConfiguration,
     pub init_storage_data: StorageInitInfo,
 
+    /// Port advertised to the peers in the connection message.
     pub port: Port,
+    /// Public address of the node advertised to the peers, if it differs
+    /// from the listening one (e.g. node behind NAT).
+    pub public_address: Option<SocketAddr>,
     pub disable_mempool: bool,
     pub private_node: bool,
     pub pow_target: f64,
@@ -94,12 +100,36 @@ pub struct Config {
     /// for us to be considered bootstrapped (`Self::is_bootstrapped() == true`).
     pub peers_bootstrapped_min: usize,
 
-    /// Disable automatic graylisting peers when something goes wrong.
+    /// Disable automatic graylisting/banning peers when their score drops,
+    /// such peers will only be disconnected.
     pub peers_graylist_disable: bool,
 
     /// Duration after which graylisted peer will timeout and be whitelisted.
     pub peers_graylist_timeout: Duration,
 
+    /// Scores at which the peer is disconnected, graylisted or banned.
+    pub peers_score_thresholds: PeersScoreThresholds,
+
+    /// Trusted peers are always reconnected, never graylisted
+    /// and exempt from `peers_connected_max`.
+    pub peers_trusted: Vec<SocketAddr>,
+
+    /// How often to try reconnecting to the trusted peers we aren't connected to.
+    pub peers_trusted_reconnect_interval: Duration,
+
+    /// If not empty, incoming connections are accepted only from these ip ranges.
+    pub peers_incoming_allow: Vec<IpCidr>,
+
+    /// Incoming connections from these ip ranges are rejected.
+    pub peers_incoming_deny: Vec<IpCidr>,
+
+    /// Disable swapping of peers with our peers, used to rebalance
+    /// the connections in the network. Should be disabled for private node.
+    pub peers_swap_disable: bool,
+
+    /// Minimal time between two accepted swaps.
+    pub peers_swap_linger: Duration,
+
     pub bootstrap_block_header_get_timeout: Duration,
     pub bootstrap_block_operations_get_timeout: Duration,
 
@@ -118,8 +148,15 @@ pub struct Config {
 
     pub quota: Quota,
 
+    /// Limits of the transfer rate with peers.
+    pub bandwidth_limit: BandwidthLimit,
+
+    /// Bounds of the pending and validated operations in the mempool.
+    pub mempool_limits: MempoolLimits,
+
     pub disable_block_precheck: bool,
     pub disable_endorsements_precheck: bool,
+    pub disable_manager_operations_precheck: bool,
 }
 
 impl Config {
@@ -135,6 +172,82 @@ pub struct Quota {
     pub write_quota: usize,
 }
 
+/// Transfer rate limits in bytes per second, `None` means unlimited.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BandwidthLimit {
+    /// Limit of the download rate from all peers together.
+    pub download: Option<u64>,
+    /// Limit of the upload rate to all peers together.
+    pub upload: Option<u64>,
+    /// Limit of the download rate from a single peer.
+    pub peer_download: Option<u64>,
+    /// Limit of the upload rate to a single peer.
+    pub peer_upload: Option<u64>,
+}
+
+/// Actions taken when the score of the peer drops, see [`crate::peers::score::PeerScore`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersScoreThresholds {
+    /// Peer is disconnected when its score drops to this value.
+    pub disconnect: i32,
+    /// Peer's ip is graylisted for `peers_graylist_timeout` when its score drops to this value.
+    pub graylist: i32,
+    /// Peer's ip is permanently banned when its score drops to this value.
+    pub ban: i32,
+    /// Negative score of the peer recovers by one point every `recovery_interval`.
+    pub recovery_interval: Duration,
+}
+
+impl PeersScoreThresholds {
+    pub const DEFAULT_DISCONNECT: i32 = -50;
+    pub const DEFAULT_GRAYLIST: i32 = -100;
+    pub const DEFAULT_BAN: i32 = -500;
+    pub const DEFAULT_RECOVERY_INTERVAL: Duration = Duration::from_secs(60);
+}
+
+impl Default for PeersScoreThresholds {
+    fn default() -> Self {
+        Self {
+            disconnect: Self::DEFAULT_DISCONNECT,
+            graylist: Self::DEFAULT_GRAYLIST,
+            ban: Self::DEFAULT_BAN,
+            recovery_interval: Self::DEFAULT_RECOVERY_INTERVAL,
+        }
+    }
+}
+
+/// Bounds of the mempool. When exceeded, operations with the lowest
+/// priority are evicted, see [`crate::mempool::OperationPriority`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolLimits {
+    /// Maximum number of operations waiting for the validation.
+    pub pending_max_count: usize,
+    /// Maximum total size in bytes of operations waiting for the validation.
+    pub pending_max_bytes: usize,
+    /// Maximum number of applied operations.
+    pub validated_max_count: usize,
+    /// Maximum total size in bytes of applied operations.
+    pub validated_max_bytes: usize,
+}
+
+impl MempoolLimits {
+    pub const DEFAULT_PENDING_MAX_COUNT: usize = 10_000;
+    pub const DEFAULT_PENDING_MAX_BYTES: usize = 64 * 1024 * 1024;
+    pub const DEFAULT_VALIDATED_MAX_COUNT: usize = 5_000;
+    pub const DEFAULT_VALIDATED_MAX_BYTES: usize = 32 * 1024 * 1024;
+}
+
+impl Default for MempoolLimits {
+    fn default() -> Self {
+        Self {
+            pending_max_count: Self::DEFAULT_PENDING_MAX_COUNT,
+            pending_max_bytes: Self::DEFAULT_PENDING_MAX_BYTES,
+            validated_max_count: Self::DEFAULT_VALIDATED_MAX_COUNT,
+            validated_max_bytes: Self::DEFAULT_VALIDATED_MAX_BYTES,
+        }
+    }
+}
+
 pub fn default_test_config() -> Config {
     Config {
         initial_time: SystemTime::now(),
@@ -171,6 +284,7 @@ pub fn default_test_config() -> Config {
                     ipc_socket_path: None,
                 },
             ),
+            context_gc_preserve_cycles: None,
             executable_path: Default::default(),
             log_level: slog::Level::Error,
         },
@@ -186,6 +300,7 @@ pub fn default_test_config() -> Config {
         },
 
         port: 9732,
+        public_address: None,
         disable_mempool: false,
         private_node: false,
         pow_target: 0.0,
@@ -213,6 +328,15 @@ pub fn default_test_config() -> Config {
 
         peers_graylist_disable: false,
         peers_graylist_timeout: Duration::from_secs(15 * 60),
+        peers_score_thresholds: PeersScoreThresholds::default(),
+
+        peers_trusted: vec![],
+        peers_trusted_reconnect_interval: Duration::from_secs(5),
+        peers_incoming_allow: vec![],
+        peers_incoming_deny: vec![],
+
+        peers_swap_disable: false,
+        peers_swap_linger: Duration::from_secs(30),
 
         bootstrap_block_header_get_timeout: Duration::from_millis(500),
         bootstrap_block_operations_get_timeout: Duration::from_millis(1000),
@@ -227,8 +351,11 @@ pub fn default_test_config() -> Config {
             read_quota: 1024,
             write_quota: 1024,
         },
+        bandwidth_limit: BandwidthLimit::default(),
+        mempool_limits: MempoolLimits::default(),
 
         disable_endorsements_precheck: false,
+        disable_manager_operations_precheck: false,
         disable_block_precheck: true,
     }
 }
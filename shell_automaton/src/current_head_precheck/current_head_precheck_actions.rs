@@ -7,6 +7,7 @@ use tezos_messages::{
     base::signature_public_key::SignaturePublicKey, p2p::encoding::block_header::BlockHeader,
 };
 
+use crate::service::signature_verifier_service::SignatureVerificationError;
 use crate::State;
 
 use super::{CurrentHeadPrecheckError, CurrentHeadState};
@@ -59,6 +60,26 @@ impl EnablingCondition<State> for CurrentHeadPrecheckAction {
     }
 }
 
+/// Result of the block header's signature verification against the baker with the `priority`.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CurrentHeadPrecheckSignatureVerifiedAction {
+    pub block_hash: BlockHash,
+    pub priority: u16,
+    pub result: Result<bool, SignatureVerificationError>,
+}
+
+impl EnablingCondition<State> for CurrentHeadPrecheckSignatureVerifiedAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        match state.current_heads.candidates.get(&self.block_hash) {
+            Some(CurrentHeadState::PendingSignature { verified, .. }) => {
+                verified.get(self.priority as usize) == Some(&None)
+            }
+            _ => false,
+        }
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CurrentHeadPrecheckSuccessAction {
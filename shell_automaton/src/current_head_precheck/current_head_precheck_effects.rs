@@ -4,12 +4,21 @@
 use std::convert::TryInto;
 
 use crypto::hash::BlockHash;
-use tezos_messages::p2p::{binary_message::MessageHash, encoding::peer::PeerMessage};
+use tezos_messages::{
+    base::signature_public_key::SignatureWatermark,
+    p2p::{binary_message::MessageHash, encoding::peer::PeerMessage},
+};
 
 use crate::{
     mempool::mempool_actions::BlockInjectAction,
     peer::message::read::PeerMessageReadSuccessAction,
     rights::{rights_actions::RightsGetAction, RightsKey},
+    service::{
+        signature_verifier_service::{
+            SignatureVerificationRequest, SignatureVerificationTarget, SignedData,
+        },
+        SignatureVerifierService,
+    },
     stats::current_head::stats_current_head_actions::StatsCurrentHeadPrecheckInitAction,
     Action,
 };
@@ -76,40 +85,60 @@ where
                 });
             }
         }
-        Action::CurrentHeadPrecheck(CurrentHeadPrecheckAction {
-            block_hash,
-            injected,
-            ..
-        }) => {
-            match store.state.get().current_heads.candidates.get(block_hash) {
-                Some(CurrentHeadState::Prechecked {
-                    block_header: _,
-                    baker,
-                    priority,
+        Action::CurrentHeadPrecheck(CurrentHeadPrecheckAction { block_hash, .. }) => {
+            let state = store.state.get();
+            let requests = match state.current_heads.candidates.get(block_hash) {
+                Some(CurrentHeadState::PendingSignature {
+                    signature,
+                    signed_bytes,
+                    bakers,
+                    ..
                 }) => {
-                    let baker = baker.clone();
-                    let priority = *priority;
-                    store.dispatch(CurrentHeadPrecheckSuccessAction {
-                        block_hash: block_hash.clone(),
-                        baker,
-                        priority,
-                        injected: *injected,
-                    });
-                }
-                Some(CurrentHeadState::Rejected) => {
-                    store.dispatch(CurrentHeadPrecheckRejectedAction {
-                        block_hash: block_hash.clone(),
-                    });
+                    let watermark = SignatureWatermark::BlockHeader(state.config.chain_id.clone());
+                    bakers
+                        .iter()
+                        .enumerate()
+                        .map(|(priority, baker)| SignatureVerificationRequest {
+                            target: SignatureVerificationTarget::BlockHeader {
+                                block_hash: block_hash.clone(),
+                                priority: priority as u16,
+                            },
+                            signed_data: SignedData {
+                                public_key: baker.clone(),
+                                signature: signature.clone(),
+                                watermark: watermark.clone(),
+                                bytes: signed_bytes.clone(),
+                            },
+                        })
+                        .collect::<Vec<_>>()
                 }
-                Some(CurrentHeadState::Error { error }) => {
-                    let error = error.clone();
-                    store.dispatch(CurrentHeadErrorAction {
-                        block_hash: block_hash.clone(),
-                        error,
-                    });
+                _ => {
+                    dispatch_precheck_result(store, block_hash);
+                    return;
                 }
-                _ => (),
             };
+            for req in requests {
+                if let Err(err) = store.service.signature_verifier().verify(req) {
+                    // the service is not running, verify it here.
+                    let req = err.payload();
+                    if let SignatureVerificationTarget::BlockHeader {
+                        block_hash,
+                        priority,
+                    } = req.target
+                    {
+                        store.dispatch(CurrentHeadPrecheckSignatureVerifiedAction {
+                            block_hash,
+                            priority,
+                            result: req.signed_data.verify(),
+                        });
+                    }
+                }
+            }
+        }
+        Action::CurrentHeadPrecheckSignatureVerified(
+            CurrentHeadPrecheckSignatureVerifiedAction { block_hash, .. },
+        ) => {
+            dispatch_precheck_result(store, block_hash);
         }
         Action::CurrentHeadError(CurrentHeadErrorAction { block_hash, error }) => {
             slog::error!(&store.state().log, "current head error"; "block_hash" => block_hash.to_base58_check(), "error" => error.to_string());
@@ -158,6 +187,44 @@ where
     }
 }
 
+/// Dispatches the result of the prechecking, once it is finished.
+fn dispatch_precheck_result<S>(store: &mut crate::Store<S>, block_hash: &BlockHash)
+where
+    S: crate::Service,
+{
+    match store.state.get().current_heads.candidates.get(block_hash) {
+        Some(CurrentHeadState::Prechecked {
+            baker,
+            priority,
+            injected,
+            ..
+        }) => {
+            let baker = baker.clone();
+            let priority = *priority;
+            let injected = *injected;
+            store.dispatch(CurrentHeadPrecheckSuccessAction {
+                block_hash: block_hash.clone(),
+                baker,
+                priority,
+                injected,
+            });
+        }
+        Some(CurrentHeadState::Rejected) => {
+            store.dispatch(CurrentHeadPrecheckRejectedAction {
+                block_hash: block_hash.clone(),
+            });
+        }
+        Some(CurrentHeadState::Error { error }) => {
+            let error = error.clone();
+            store.dispatch(CurrentHeadErrorAction {
+                block_hash: block_hash.clone(),
+                error,
+            });
+        }
+        _ => (),
+    };
+}
+
 fn max_priority_to_precache(
     prev_timestamp: i64,
     block_times: (i64, i64),
@@ -3,17 +3,14 @@
 
 use std::convert::{TryFrom, TryInto};
 
-use crypto::hash::{ChainId, HashTrait, Signature};
-use tezos_messages::{
-    base::signature_public_key::{SignaturePublicKey, SignatureWatermark},
-    p2p::{binary_message::BinaryWrite, encoding::block_header::BlockHeader},
-};
+use crypto::hash::{HashTrait, Signature};
+use tezos_messages::p2p::{binary_message::BinaryWrite, encoding::block_header::BlockHeader};
 
 use crate::{current_head_precheck::CurrentHeadPrecheckError, Action};
 
 use super::{
-    BakingPriorityError, BakingRightsError, CurrentHeadPrecheckAction, CurrentHeadReceivedAction,
-    CurrentHeadState,
+    BakingPriorityError, BakingRightsError, CurrentHeadPrecheckAction,
+    CurrentHeadPrecheckSignatureVerifiedAction, CurrentHeadReceivedAction, CurrentHeadState,
 };
 
 pub(super) const TIME_BETWEEN_BLOCKS: (i64, i64) = (20, 15);
@@ -33,8 +30,11 @@ pub fn current_head_precheck_reducer(state: &mut crate::State, action: &crate::A
                     block_header: block_header.clone(),
                 });
         }
-        Action::CurrentHeadPrecheck(CurrentHeadPrecheckAction { block_hash, .. }) => {
-            let chain_id = &state.config.chain_id;
+        Action::CurrentHeadPrecheck(CurrentHeadPrecheckAction {
+            block_hash,
+            injected,
+            ..
+        }) => {
             let baking_cache = &state.rights.cache.baking;
             let applied_head = match state.current_head.get() {
                 Some(v) => &v.header,
@@ -77,22 +77,40 @@ pub fn current_head_precheck_reducer(state: &mut crate::State, action: &crate::A
                         return;
                     }
 
-                    *current_head_state = match precheck_block_header(
-                        block_header,
-                        chain_id,
-                        &priorities[..=(max_priority as usize)],
-                    ) {
-                        Ok(Some((delegate, priority))) => CurrentHeadState::Prechecked {
+                    *current_head_state = match split_signature(block_header) {
+                        Ok((signed_bytes, signature)) => CurrentHeadState::PendingSignature {
                             block_header: block_header.clone(),
-                            baker: delegate,
-                            priority,
+                            injected: *injected,
+                            signature,
+                            signed_bytes,
+                            bakers: priorities[..=(max_priority as usize)].to_vec(),
+                            verified: vec![None; max_priority as usize + 1],
                         },
-                        Ok(None) => CurrentHeadState::Rejected,
                         Err(err) => CurrentHeadState::Error { error: err.into() },
                     };
                 }
             }
         }
+        Action::CurrentHeadPrecheckSignatureVerified(
+            CurrentHeadPrecheckSignatureVerifiedAction {
+                block_hash,
+                priority,
+                result,
+            },
+        ) => {
+            let current_head_state = match state.current_heads.candidates.get_mut(block_hash) {
+                Some(v) => v,
+                None => return,
+            };
+            if let CurrentHeadState::PendingSignature { verified, .. } = current_head_state {
+                if let Some(verified) = verified.get_mut(*priority as usize) {
+                    *verified = Some(result.clone());
+                }
+            }
+            if let Some(next_state) = signature_verified(current_head_state) {
+                *current_head_state = next_state;
+            }
+        }
         Action::CurrentHeadUpdate(_) => {
             state.current_heads.candidates.clear();
         }
@@ -130,22 +148,45 @@ fn max_priority_for_prechecking(
     })
 }
 
-fn precheck_block_header(
-    block_header: &BlockHeader,
-    chain_id: &ChainId,
-    priorities: &[SignaturePublicKey],
-) -> Result<Option<(SignaturePublicKey, u16)>, BakingRightsError> {
-    let encoded = block_header.as_bytes()?;
-    let (unsigned, signature) =
-        encoded.split_at(encoded.len().saturating_sub(Signature::hash_size()));
+/// Splits the binary encoding of the block header into the signed bytes and the signature.
+fn split_signature(block_header: &BlockHeader) -> Result<(Vec<u8>, Signature), BakingRightsError> {
+    let mut signed_bytes = block_header.as_bytes()?;
+    let signature =
+        signed_bytes.split_off(signed_bytes.len().saturating_sub(Signature::hash_size()));
     let signature = Signature::try_from(signature)?;
-    let watermark = SignatureWatermark::BlockHeader(chain_id.clone());
-    for (priority, delegate) in priorities.iter().enumerate() {
-        if delegate.verify_signature(&signature, &watermark, unsigned)? {
-            return Ok(Some((delegate.clone(), priority as u16)));
-        }
+    Ok((signed_bytes, signature))
+}
+
+/// Next state once the baker of the block is known, i.e. the signature is verified
+/// against the baker with the lowest priority that matches, and against all the
+/// bakers with the lower priorities.
+fn signature_verified(current_head_state: &CurrentHeadState) -> Option<CurrentHeadState> {
+    let (block_header, injected, bakers, verified) = match current_head_state {
+        CurrentHeadState::PendingSignature {
+            block_header,
+            injected,
+            bakers,
+            verified,
+            ..
+        } => (block_header, *injected, bakers, verified),
+        _ => return None,
+    };
+    for (priority, (baker, result)) in bakers.iter().zip(verified).enumerate() {
+        return match result {
+            Some(Ok(false)) => continue,
+            Some(Ok(true)) => Some(CurrentHeadState::Prechecked {
+                baker: baker.clone(),
+                priority: priority as u16,
+                block_header: block_header.clone(),
+                injected,
+            }),
+            Some(Err(err)) => Some(CurrentHeadState::Error {
+                error: BakingRightsError::Crypto(err.to_string()).into(),
+            }),
+            None => None,
+        };
     }
-    Ok(None)
+    Some(CurrentHeadState::Rejected)
 }
 
 #[cfg(test)]
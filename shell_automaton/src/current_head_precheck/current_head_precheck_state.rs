@@ -4,7 +4,7 @@
 use std::{collections::BTreeMap, num::TryFromIntError};
 
 use crypto::{
-    hash::{BlockHash, FromBytesError},
+    hash::{BlockHash, FromBytesError, Signature},
     CryptoError,
 };
 use tezos_encoding::binary_writer::BinaryWriterError;
@@ -12,6 +12,8 @@ use tezos_messages::{
     base::signature_public_key::SignaturePublicKey, p2p::encoding::block_header::BlockHeader,
 };
 
+use crate::service::signature_verifier_service::SignatureVerificationError;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct CurrentHeads {
     pub candidates: BTreeMap<BlockHash, CurrentHeadState>,
@@ -25,10 +27,24 @@ pub enum CurrentHeadState {
     PendingBakingRights {
         block_header: BlockHeader,
     },
+    /// Signature is verified against the bakers of all the priorities
+    /// allowed by the block's timestamp, by the signature verifier service.
+    PendingSignature {
+        block_header: BlockHeader,
+        injected: bool,
+        signature: Signature,
+        /// Binary encoding of the block header without the signature.
+        signed_bytes: Vec<u8>,
+        /// Bakers by priority.
+        bakers: Vec<SignaturePublicKey>,
+        /// Verification results by priority.
+        verified: Vec<Option<Result<bool, SignatureVerificationError>>>,
+    },
     Prechecked {
         baker: SignaturePublicKey,
         priority: u16,
         block_header: BlockHeader,
+        injected: bool,
     },
     Rejected,
     Error {
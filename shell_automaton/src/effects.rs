@@ -40,18 +40,27 @@ use crate::peer::handshaking::peer_handshaking_effects;
 use crate::peer::message::read::peer_message_read_effects;
 use crate::peer::message::write::peer_message_write_effects;
 use crate::peer::peer_effects;
+use crate::peer::recorder::peer_recorder_effects;
 use crate::peer::remote_requests::block_header_get::peer_remote_requests_block_header_get_effects;
 use crate::peer::remote_requests::block_operations_get::peer_remote_requests_block_operations_get_effects;
 use crate::peer::remote_requests::current_branch_get::peer_remote_requests_current_branch_get_effects;
 use crate::peer::requests::potential_peers_get::peer_requests_potential_peers_get_effects;
+use crate::peer::requests::protocols_get::peer_requests_protocols_get_effects;
 
+use crate::peers::acl::peers_acl_effects;
 use crate::peers::add::multi::peers_add_multi_effects;
+use crate::peers::address_book::peers_address_book_effects;
+use crate::peers::bandwidth::peers_bandwidth_effects;
 use crate::peers::check::timeouts::{peers_check_timeouts_effects, PeersCheckTimeoutsInitAction};
+use crate::peers::connect::peers_connect_effects;
 use crate::peers::dns_lookup::peers_dns_lookup_effects;
 use crate::peers::graylist::peers_graylist_effects;
 use crate::peers::init::peers_init_effects;
+use crate::peers::score::peers_score_effects;
+use crate::peers::swap::peers_swap_effects;
 
 use crate::mempool::mempool_effects;
+use crate::mempool::storage::{mempool_storage_effects, MempoolStorageSyncAction};
 use crate::mempool::validator::mempool_validator_effects;
 
 use crate::storage::blocks::genesis::check_applied::storage_blocks_genesis_check_applied_effects;
@@ -99,6 +108,7 @@ fn applied_actions_count_effects<S: Service>(store: &mut Store<S>, action: &Acti
 pub fn check_timeouts<S: Service>(store: &mut Store<S>) {
     store.dispatch(PeersCheckTimeoutsInitAction {});
     store.dispatch(BootstrapCheckTimeoutsInitAction {});
+    store.dispatch(MempoolStorageSyncAction {});
 }
 
 pub fn effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
@@ -144,10 +154,12 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
     peer_binary_message_read_effects(store, action);
     peer_chunk_write_effects(store, action);
     peer_chunk_read_effects(store, action);
+    peer_recorder_effects(store, action);
 
     peer_handshaking_effects(store, action);
 
     peer_requests_potential_peers_get_effects(store, action);
+    peer_requests_protocols_get_effects(store, action);
 
     peer_remote_requests_block_header_get_effects(store, action);
     peer_remote_requests_block_operations_get_effects(store, action);
@@ -158,10 +170,17 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
     peers_add_multi_effects(store, action);
     peers_check_timeouts_effects(store, action);
     peers_graylist_effects(store, action);
+    peers_score_effects(store, action);
+    peers_address_book_effects(store, action);
+    peers_bandwidth_effects(store, action);
+    peers_swap_effects(store, action);
+    peers_acl_effects(store, action);
+    peers_connect_effects(store, action);
 
     bootstrap_effects(store, action);
     mempool_validator_effects(store, action);
     mempool_effects(store, action);
+    mempool_storage_effects(store, action);
 
     storage_request_effects(store, action);
 
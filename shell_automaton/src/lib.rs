@@ -14,7 +14,7 @@ pub use action::{
 };
 
 pub mod config;
-pub use config::{Config, Quota};
+pub use config::{BandwidthLimit, Config, MempoolLimits, PeersScoreThresholds, Quota};
 
 pub mod logger;
 pub use logger::Logger;
@@ -18,7 +18,7 @@ use crate::{action::EnablingCondition, state::State};
 #[cfg(feature = "fuzzing")]
 use crate::fuzzing::net::SocketAddrMutator;
 
-use super::MempoolOperation;
+use super::{MempoolFilter, MempoolOperation};
 
 /// Process the mempool received from the peer
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
@@ -260,6 +260,54 @@ impl EnablingCondition<State> for MempoolOperationDecodedAction {
     }
 }
 
+/// Set the mempool filter and persist it.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolFilterSetAction {
+    pub filter: MempoolFilter,
+}
+
+impl EnablingCondition<State> for MempoolFilterSetAction {
+    fn is_enabled(&self, _state: &State) -> bool {
+        true
+    }
+}
+
+/// Remove the operation from the mempool and don't accept it again.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolOperationBanAction {
+    pub hash: OperationHash,
+}
+
+impl EnablingCondition<State> for MempoolOperationBanAction {
+    fn is_enabled(&self, _state: &State) -> bool {
+        true
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolOperationUnbanAction {
+    pub hash: OperationHash,
+}
+
+impl EnablingCondition<State> for MempoolOperationUnbanAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.mempool.is_banned(&self.hash)
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolOperationUnbanAllAction {}
+
+impl EnablingCondition<State> for MempoolOperationUnbanAllAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.mempool.banned_operations.is_empty()
+    }
+}
+
 // RPC
 
 pub(super) trait MempoolOperationMatcher {
@@ -34,7 +34,9 @@ use crate::{
         OperationDecodedContents,
     },
     rights::Slot,
+    service::storage_service::StorageRequestPayload,
     service::RpcService,
+    storage::request::{StorageRequestCreateAction, StorageRequestor},
     Action, ActionWithMeta, Service, State,
 };
 
@@ -71,6 +73,12 @@ where
         Action::MempoolOperationValidateNext(_) => {
             let mempool_state = &store.state().mempool;
 
+            // removed applied operations are still in the prevalidator.
+            if mempool_state.validator_outdated {
+                store.dispatch(MempoolValidatorInitAction {});
+                return;
+            }
+
             // Find operation with highest priority.
             let (op_hash, op_content) = match mempool_state.next_for_prevalidation() {
                 Some(v) => (v.0.clone(), v.1.clone()),
@@ -85,8 +93,27 @@ where
         Action::MempoolValidatorReady(_) => {
             store.dispatch(MempoolOperationValidateNextAction {});
         }
+        Action::MempoolOperationBan(_) => {
+            // replay the remaining applied operations without the banned one.
+            if store.state().mempool.validator_outdated {
+                store.dispatch(MempoolValidatorInitAction {});
+            }
+        }
+        Action::MempoolFilterSet(content) => {
+            store.dispatch(StorageRequestCreateAction {
+                payload: StorageRequestPayload::MempoolFilterPut(content.filter.clone()),
+                requestor: StorageRequestor::None,
+            });
+        }
         Action::MempoolValidatorValidateSuccess(content) => {
-            if content.result.is_applied() {
+            // the applied operation might have been evicted or banned meanwhile.
+            if content.result.is_applied()
+                && store
+                    .state()
+                    .mempool
+                    .applied_operations
+                    .contains(&content.op_hash)
+            {
                 let addresses = store.state().peers.iter_addr().cloned().collect::<Vec<_>>();
 
                 for address in addresses {
@@ -353,6 +380,8 @@ where
         Action::PrecheckerPrecheckOperationResponse(
             PrecheckerPrecheckOperationResponseAction { response },
         ) => {
+            // prechecked manager operations are skipped by the validation until now.
+            store.dispatch(MempoolOperationValidateNextAction {});
             match response {
                 PrecheckerPrecheckOperationResponse::Applied(PrecheckerApplied {
                     operation_decoded_contents,
@@ -438,9 +467,6 @@ where
                         }
                     }
                 }
-                PrecheckerPrecheckOperationResponse::Prevalidate(_) => {
-                    store.dispatch(MempoolOperationValidateNextAction {});
-                }
                 _ => (),
             }
         }
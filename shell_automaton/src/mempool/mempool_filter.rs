@@ -0,0 +1,276 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Mempool filter, configured with `/chains/:chain_id/mempool/filter` rpc.
+//!
+//! Encoded the same way as the filter configuration of the octez mempool plugin,
+//! so that the tooling (bakers, wallets) can use the same requests for both nodes.
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use tezos_messages::p2p::encoding::operation::Operation;
+
+use super::{operation_size, ManagerOperationInfo};
+
+/// Non-negative rational number `numerator / denominator`.
+///
+/// Encoded as `["numerator", "denominator"]`.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MempoolFilterRatio {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl MempoolFilterRatio {
+    pub const fn new(numerator: u64, denominator: u64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+impl Serialize for MempoolFilterRatio {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        [self.numerator.to_string(), self.denominator.to_string()].serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MempoolFilterRatio {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let [numerator, denominator] = <[String; 2]>::deserialize(deserializer)?;
+        let numerator = numerator.parse().map_err(D::Error::custom)?;
+        let denominator = denominator.parse().map_err(D::Error::custom)?;
+        if denominator == 0 {
+            return Err(D::Error::custom("denominator must not be zero"));
+        }
+        Ok(Self::new(numerator, denominator))
+    }
+}
+
+/// Mutez encoded as a string.
+mod mutez_string {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(mutez: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&mutez.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Requirements on the manager operations accepted into the mempool.
+///
+/// Missing fields are set to their default values.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct MempoolFilter {
+    /// Fee in mutez every manager operation has to pay.
+    #[serde(with = "mutez_string")]
+    pub minimal_fees: u64,
+    /// Fee in nanotez required per unit of gas limit, on top of the `minimal_fees`.
+    pub minimal_nanotez_per_gas_unit: MempoolFilterRatio,
+    /// Fee in nanotez required per byte of the operation, on top of the `minimal_fees`.
+    pub minimal_nanotez_per_byte: MempoolFilterRatio,
+    /// How many times the fee (and the fee per gas unit) of the operation must
+    /// be greater to replace the conflicting manager operation in the mempool.
+    pub replace_by_fee_factor: MempoolFilterRatio,
+}
+
+impl MempoolFilter {
+    pub const DEFAULT_MINIMAL_FEES: u64 = 100;
+    pub const DEFAULT_MINIMAL_NANOTEZ_PER_GAS_UNIT: MempoolFilterRatio =
+        MempoolFilterRatio::new(100, 1);
+    pub const DEFAULT_MINIMAL_NANOTEZ_PER_BYTE: MempoolFilterRatio =
+        MempoolFilterRatio::new(1000, 1);
+    pub const DEFAULT_REPLACE_BY_FEE_FACTOR: MempoolFilterRatio = MempoolFilterRatio::new(105, 100);
+
+    /// Json of the filter, with the fields equal to the defaults omitted
+    /// unless `include_default` is set.
+    pub fn to_json(&self, include_default: bool) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        if include_default {
+            return json;
+        }
+        if let (Some(fields), Ok(serde_json::Value::Object(defaults))) =
+            (json.as_object_mut(), serde_json::to_value(Self::default()))
+        {
+            fields.retain(|key, value| defaults.get(key) != Some(value));
+        }
+        json
+    }
+
+    /// Minimal fee in mutez of the manager operation with the given size and gas limit.
+    pub fn required_fee(&self, operation_size: usize, gas_limit: u64) -> u64 {
+        let per_gas = self.minimal_nanotez_per_gas_unit;
+        let per_byte = self.minimal_nanotez_per_byte;
+        let denominator = 1000u128
+            .saturating_mul(per_gas.denominator as u128)
+            .saturating_mul(per_byte.denominator as u128);
+        // nanotez multiplied by both denominators.
+        let numerator = (self.minimal_fees as u128)
+            .saturating_mul(denominator)
+            .saturating_add(
+                (gas_limit as u128)
+                    .saturating_mul(per_gas.numerator as u128)
+                    .saturating_mul(per_byte.denominator as u128),
+            )
+            .saturating_add(
+                (operation_size as u128)
+                    .saturating_mul(per_byte.numerator as u128)
+                    .saturating_mul(per_gas.denominator as u128),
+            );
+        let fee = numerator / denominator + (numerator % denominator != 0) as u128;
+        fee.min(u64::MAX as u128) as u64
+    }
+
+    /// Checks that the manager operation pays enough fees, other operations always pass.
+    pub fn check(
+        &self,
+        operation: &Operation,
+        manager: Option<&ManagerOperationInfo>,
+    ) -> Result<(), MempoolRejectReason> {
+        let manager = match manager {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let required = self.required_fee(operation_size(operation), manager.gas_limit);
+        if manager.fee < required {
+            return Err(MempoolRejectReason::FeesTooLow {
+                fee: manager.fee,
+                required,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the `new` manager operation pays enough more than the
+    /// conflicting `old` one to replace it.
+    pub fn replaces(&self, new: &ManagerOperationInfo, old: &ManagerOperationInfo) -> bool {
+        let factor = self.replace_by_fee_factor;
+        let (num, den) = (factor.numerator as u128, factor.denominator as u128);
+        let (new_fee, new_gas) = (new.fee as u128, new.gas_limit.max(1) as u128);
+        let (old_fee, old_gas) = (old.fee as u128, old.gas_limit.max(1) as u128);
+
+        new_fee.saturating_mul(den) >= old_fee.saturating_mul(num)
+            && new_fee.saturating_mul(old_gas).saturating_mul(den)
+                >= old_fee.saturating_mul(new_gas).saturating_mul(num)
+    }
+}
+
+impl Default for MempoolFilter {
+    fn default() -> Self {
+        Self {
+            minimal_fees: Self::DEFAULT_MINIMAL_FEES,
+            minimal_nanotez_per_gas_unit: Self::DEFAULT_MINIMAL_NANOTEZ_PER_GAS_UNIT,
+            minimal_nanotez_per_byte: Self::DEFAULT_MINIMAL_NANOTEZ_PER_BYTE,
+            replace_by_fee_factor: Self::DEFAULT_REPLACE_BY_FEE_FACTOR,
+        }
+    }
+}
+
+/// Why the operation was not accepted into the mempool.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MempoolRejectReason {
+    #[error("operation is banned")]
+    Banned,
+    #[error("fees too low: operation pays {fee} mutez, at least {required} mutez required")]
+    FeesTooLow { fee: u64, required: u64 },
+    /// Conflicting manager operation (with the same source and counter)
+    /// is already in the mempool and pays about the same fees.
+    #[error("conflicting operation paying {conflicting_fee} mutez is already in the mempool, operation pays {fee} mutez")]
+    ReplacementFeesTooLow { fee: u64, conflicting_fee: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use tezos_messages::base::signature_public_key::SignaturePublicKeyHash;
+
+    use super::*;
+
+    fn manager(fee: u64, gas_limit: u64) -> ManagerOperationInfo {
+        ManagerOperationInfo {
+            source: SignaturePublicKeyHash::from_b58_hash("tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx")
+                .unwrap(),
+            counter: 1,
+            fee,
+            gas_limit,
+        }
+    }
+
+    #[test]
+    fn test_mempool_filter_json() {
+        let filter = MempoolFilter::default();
+        assert_eq!(
+            filter.to_json(true),
+            serde_json::json!({
+                "minimal_fees": "100",
+                "minimal_nanotez_per_gas_unit": ["100", "1"],
+                "minimal_nanotez_per_byte": ["1000", "1"],
+                "replace_by_fee_factor": ["105", "100"],
+            })
+        );
+        assert_eq!(filter.to_json(false), serde_json::json!({}));
+
+        let filter: MempoolFilter = serde_json::from_value(serde_json::json!({
+            "minimal_fees": "0",
+            "minimal_nanotez_per_byte": ["1", "2"],
+            "allow_script_failure": false,
+        }))
+        .unwrap();
+        assert_eq!(
+            filter,
+            MempoolFilter {
+                minimal_fees: 0,
+                minimal_nanotez_per_byte: MempoolFilterRatio::new(1, 2),
+                ..MempoolFilter::default()
+            }
+        );
+        assert_eq!(
+            filter.to_json(false),
+            serde_json::json!({
+                "minimal_fees": "0",
+                "minimal_nanotez_per_byte": ["1", "2"],
+            })
+        );
+
+        assert!(serde_json::from_value::<MempoolFilter>(serde_json::json!({
+            "replace_by_fee_factor": ["1", "0"],
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_mempool_filter_required_fee() {
+        let filter = MempoolFilter::default();
+        // 100 mutez + 1000 gas * 100 nanotez + 200 bytes * 1000 nanotez.
+        assert_eq!(filter.required_fee(200, 1000), 100 + 100 + 200);
+
+        let filter = MempoolFilter {
+            minimal_fees: 0,
+            minimal_nanotez_per_gas_unit: MempoolFilterRatio::new(1, 3),
+            minimal_nanotez_per_byte: MempoolFilterRatio::new(0, 1),
+            ..MempoolFilter::default()
+        };
+        // rounded up
+        assert_eq!(filter.required_fee(200, 3000), 1);
+        assert_eq!(filter.required_fee(200, 3001), 2);
+    }
+
+    #[test]
+    fn test_mempool_filter_replaces() {
+        let filter = MempoolFilter::default();
+        assert!(filter.replaces(&manager(1050, 1000), &manager(1000, 1000)));
+        assert!(!filter.replaces(&manager(1049, 1000), &manager(1000, 1000)));
+        // fee is higher enough, but fee per gas unit is not.
+        assert!(!filter.replaces(&manager(2000, 3000), &manager(1000, 1000)));
+    }
+}
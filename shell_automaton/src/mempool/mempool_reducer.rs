@@ -7,21 +7,25 @@ use std::net::SocketAddr;
 
 use crypto::hash::OperationHash;
 use tezos_messages::p2p::binary_message::MessageHash;
+use tezos_messages::p2p::encoding::operation::Operation;
 use tezos_messages::p2p::encoding::peer::PeerMessage;
 
 use crate::block_applier::BlockApplierApplyState;
+use crate::config::Config;
 use crate::peers::remove::PeersRemoveAction;
-use crate::prechecker::prechecking_enabled;
+use crate::prechecker::{prechecking_enabled, PrecheckerState};
 use crate::{Action, ActionWithMeta, State};
 
 use super::validator::MempoolValidatorValidateResult;
 use super::{
     mempool_actions::*,
-    mempool_state::{HeadState, MempoolOperation, OperationStream, MAX_REFUSED_OPERATIONS},
+    mempool_state::{
+        HeadState, MempoolOperation, OperationStream, MAX_REFUSED_OPERATIONS, OPERATION_TTL,
+    },
 };
 use super::{
-    OperationKind, OperationNodeCurrentHeadStats, OperationState, OperationStats,
-    OperationValidationResult,
+    OperationKind, OperationNodeCurrentHeadStats, OperationPriority, OperationState,
+    OperationStats, OperationValidationResult,
 };
 use crate::prechecker::prechecker_actions::{
     PrecheckerPrecheckOperationResponse, PrecheckerPrecheckOperationResponseAction,
@@ -47,7 +51,13 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
 
             match &content.result {
                 MempoolValidatorValidateResult::Applied(v) => {
+                    let priority = mempool_state.pending_operations.priority(&v.hash);
                     if let Some(op) = mempool_state.pending_operations.remove(&v.hash) {
+                        let priority =
+                            priority.unwrap_or_else(|| OperationPriority::from_operation(&op));
+                        mempool_state
+                            .applied_operations
+                            .insert(v.hash.clone(), priority, &op);
                         mempool_state
                             .validated_operations
                             .ops
@@ -64,6 +74,10 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                                 current_head_level,
                                 OperationValidationResult::Applied,
                             );
+                        mempool_state.evict_applied_over_limits(
+                            &state.config.mempool_limits,
+                            action.time_as_nanos(),
+                        );
                     }
                     if let Some(operation_state) = mempool_state.operations_state.get_mut(&v.hash) {
                         if let MempoolOperation {
@@ -284,6 +298,7 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                 .retain(|v| !operation_hashes.contains(&v.hash));
             for op in operation_hashes {
                 mempool_state.validated_operations.ops.remove(&op);
+                mempool_state.applied_operations.remove(&op);
             }
         }
         Action::PeerCurrentHeadUpdate(_) => {
@@ -292,9 +307,6 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
             }
         }
         Action::CurrentHeadRehydrated(_) | Action::CurrentHeadUpdate(_) => {
-            // TODO: get from protocol
-            const TTL: i32 = 120;
-
             let block = match state.current_head.get() {
                 Some(v) => v,
                 None => return,
@@ -318,7 +330,7 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
             let last_predecessor_blocks = &mut mempool_state.last_predecessor_blocks;
             last_predecessor_blocks
                 .insert(block.header.predecessor().clone(), block.header.level() - 1);
-            if last_predecessor_blocks.len() as i32 > TTL {
+            if last_predecessor_blocks.len() as i32 > OPERATION_TTL {
                 if let Some((oldest, _)) = last_predecessor_blocks
                     .iter()
                     .min_by(|(_, l0), (_, l1)| l0.cmp(l1))
@@ -328,13 +340,14 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                 }
             }
 
-            let level = block.header.level().saturating_sub(TTL);
+            let level = block.header.level().saturating_sub(OPERATION_TTL);
 
             // `drain_filter` is unstable for now
             for (_, ops) in mempool_state.level_to_operation.range(..level) {
                 for op in ops {
                     mempool_state.pending_operations.remove(op);
                     mempool_state.validated_operations.ops.remove(op);
+                    mempool_state.applied_operations.remove(op);
                     mempool_state
                         .validated_operations
                         .applied
@@ -388,7 +401,9 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
             for hash in pending.chain(known_valid) {
                 let known = mempool_state.pending_operations.contains_key(&hash)
                     || mempool_state.prechecking_operations.contains(&hash)
-                    || mempool_state.validated_operations.ops.contains_key(&hash);
+                    || mempool_state.validated_operations.ops.contains_key(&hash)
+                    || mempool_state.evicted_operations_set.contains(&hash)
+                    || mempool_state.banned_operations.contains(&hash);
 
                 if !known {
                     ops.push(hash.clone());
@@ -417,14 +432,21 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                 // We might already processed it.
                 return;
             }
+            match mempool_state.check_new_operation(hash, operation) {
+                Ok(Some(replaced)) => mempool_state.replaced(&replaced, action.time_as_nanos()),
+                Ok(None) => {}
+                Err(reason) => {
+                    mempool_state.rejected(hash, &reason, action.time_as_nanos());
+                    return;
+                }
+            }
 
-            if prechecking_enabled(&state.prechecker, operation.branch())
-                && matches!(
-                    OperationKind::from_operation_content_raw(operation.data().as_ref()),
-                    OperationKind::Preendorsement | OperationKind::Endorsement
-                )
-            {
+            let (precheck, is_consensus) =
+                precheck_operation(&state.prechecker, &state.config, operation);
+            if precheck {
                 mempool_state.prechecking_operations.insert(hash.clone());
+            }
+            if precheck && is_consensus {
                 if let Some(operation_state) = mempool_state.operations_state.get_mut(hash) {
                     if let MempoolOperation {
                         state: OperationState::ReceivedHash,
@@ -441,6 +463,8 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
             mempool_state
                 .pending_operations
                 .insert(hash.clone(), operation.clone());
+            mempool_state
+                .evict_pending_over_limits(&state.config.mempool_limits, action.time_as_nanos());
         }
         Action::MempoolOperationInject(MempoolOperationInjectAction {
             operation,
@@ -448,6 +472,12 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
             rpc_id,
             injected_timestamp,
         }) => {
+            // rejected operations are responded to and not dispatched by rpc effects.
+            match mempool_state.check_new_operation(operation_hash, operation) {
+                Ok(Some(replaced)) => mempool_state.replaced(&replaced, action.time_as_nanos()),
+                Ok(None) => {}
+                Err(_) => return,
+            }
             let level = mempool_state
                 .local_head_state
                 .as_ref()
@@ -459,15 +489,14 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                 .injecting_rpc_ids
                 .insert(operation_hash.clone(), *rpc_id);
 
-            if prechecking_enabled(&state.prechecker, operation.branch())
-                && matches!(
-                    OperationKind::from_operation_content_raw(operation.data().as_ref()),
-                    OperationKind::Preendorsement | OperationKind::Endorsement
-                )
-            {
+            let (precheck, is_consensus) =
+                precheck_operation(&state.prechecker, &state.config, operation);
+            if precheck {
                 mempool_state
                     .prechecking_operations
                     .insert(operation_hash.clone());
+            }
+            if precheck && is_consensus {
                 mempool_state.operations_state.insert(
                     operation_hash.clone(),
                     MempoolOperation::injected(level, action),
@@ -476,6 +505,8 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
             mempool_state
                 .pending_operations
                 .insert(operation_hash.clone(), operation.clone());
+            mempool_state
+                .evict_pending_over_limits(&state.config.mempool_limits, action.time_as_nanos());
 
             let (block_level, block_timestamp) = match &mempool_state.local_head_state {
                 Some(local_head_state) => (
@@ -602,6 +633,54 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                             *operation_state = next;
                         }
                     }
+                    // refused manager operations are not validated by the protocol.
+                    if errored
+                        .operation_decoded_contents
+                        .manager_source()
+                        .is_some()
+                    {
+                        if let Some(op) = mempool_state.pending_operations.remove(hash) {
+                            let validated_operations = &mut mempool_state.validated_operations;
+                            let refused = if errored.branch_refused {
+                                &mut validated_operations.branch_refused
+                            } else {
+                                &mut validated_operations.refused
+                            };
+                            while refused.len() >= MAX_REFUSED_OPERATIONS {
+                                let hash = match refused.pop_front() {
+                                    Some(v) => v.hash,
+                                    None => break,
+                                };
+                                validated_operations.ops.remove(&hash);
+                            }
+                            validated_operations.ops.insert(hash.clone(), op);
+                            refused.push_back(errored.as_errored());
+
+                            let current_head_level = mempool_state
+                                .local_head_state
+                                .as_ref()
+                                .map(|v| v.header.level());
+                            let result = if errored.branch_refused {
+                                OperationValidationResult::BranchRefused
+                            } else {
+                                OperationValidationResult::PrecheckRefused
+                            };
+                            mempool_state
+                                .operation_stats
+                                .entry(hash.clone())
+                                .or_insert_with(OperationStats::new)
+                                .validation_finished(
+                                    action.time_as_nanos(),
+                                    None,
+                                    None,
+                                    current_head_level,
+                                    result,
+                                );
+                        }
+                        if let Some(rpc_id) = mempool_state.injecting_rpc_ids.remove(hash) {
+                            mempool_state.injected_rpc_ids.push(rpc_id);
+                        }
+                    }
                     // let current_head_level = mempool_state
                     //     .local_head_state
                     //     .as_ref()
@@ -693,6 +772,9 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                 }
             }
         }
+        Action::MempoolValidatorInit(_) => {
+            mempool_state.validator_outdated = false;
+        }
         Action::MempoolValidatorReady(_) => {
             if mempool_state.branch_changed {
                 // remove all `branch_refused` results, put them into `pending_operations`
@@ -715,6 +797,22 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
                     mempool_state.pending_operations.insert(v.hash, op);
                 }
             }
+            mempool_state.applied_operations.clear();
+            mempool_state
+                .evict_pending_over_limits(&state.config.mempool_limits, action.time_as_nanos());
+        }
+        Action::MempoolFilterSet(content) => {
+            mempool_state.filter = content.filter.clone();
+            mempool_state.remove_filtered_pending(action.time_as_nanos());
+        }
+        Action::MempoolOperationBan(content) => {
+            mempool_state.ban_operation(&content.hash);
+        }
+        Action::MempoolOperationUnban(content) => {
+            mempool_state.banned_operations.remove(&content.hash);
+        }
+        Action::MempoolOperationUnbanAll(_) => {
+            mempool_state.banned_operations.clear();
         }
         Action::MempoolValidatorValidateInit(content) => {
             let current_head_level = state.current_head.get().map(|v| v.header.level());
@@ -828,6 +926,23 @@ pub fn mempool_reducer(state: &mut State, action: &ActionWithMeta) {
     }
 }
 
+/// Whether the operation should be prechecked, and whether it is a consensus operation.
+fn precheck_operation(
+    prechecker: &PrecheckerState,
+    config: &Config,
+    operation: &Operation,
+) -> (bool, bool) {
+    let kind = OperationKind::from_operation_content_raw(operation.data().as_ref());
+    let is_consensus = matches!(
+        kind,
+        OperationKind::Preendorsement | OperationKind::Endorsement
+    );
+    let is_manager = kind.is_manager_operation() && !config.disable_manager_operations_precheck;
+    let precheck =
+        (is_consensus || is_manager) && prechecking_enabled(prechecker, operation.branch());
+    (precheck, is_consensus)
+}
+
 fn update_operation_sent_stats(state: &mut State, address: SocketAddr, time: u64) {
     let peer = match state.peers.get(&address) {
         Some(v) => match v.status.as_handshaked() {
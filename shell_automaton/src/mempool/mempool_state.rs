@@ -8,18 +8,25 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crypto::hash::{BlockHash, CryptoboxPublicKeyHash, OperationHash};
+use crypto::hash::{BlockHash, CryptoboxPublicKeyHash, HashTrait, OperationHash};
 use tezos_api::ffi::{Applied, Errored};
+use tezos_encoding::types::Mutez;
+use tezos_messages::base::signature_public_key::SignaturePublicKeyHash;
+use tezos_messages::p2p::binary_message::BinaryRead;
 use tezos_messages::p2p::encoding::{
     block_header::{BlockHeader, Level},
     operation::Operation,
 };
+use tezos_messages::protocol::proto_012::operation::{Contents, OperationContents};
 
 use crate::{
-    prechecker::OperationDecodedContents, rights::Slot, service::rpc_service::RpcId, ActionWithMeta,
+    prechecker::OperationDecodedContents, rights::Slot, service::rpc_service::RpcId,
+    ActionWithMeta, MempoolLimits,
 };
 
+use super::storage::MempoolStorageState;
 use super::validator::MempoolValidatorState;
+use super::{MempoolFilter, MempoolRejectReason};
 
 /// https://gitlab.com/tezedge/tezos/-/blob/v12.2/src/lib_shell/prevalidator.ml#L219
 ///
@@ -28,6 +35,20 @@ use super::validator::MempoolValidatorState;
 /// bound is reached and we add operation, oldest one will be removed.
 pub const MAX_REFUSED_OPERATIONS: usize = 2048;
 
+/// Bound for the evicted operations remembered by the mempool, so that
+/// we don't download them again from the peers.
+pub const MAX_EVICTED_OPERATIONS: usize = 2048;
+
+/// Number of blocks after its branch the operation can be included in.
+// TODO: get from protocol
+pub const OPERATION_TTL: i32 = 120;
+
+/// Hard gas limit per block (Ithaca).
+const HARD_GAS_LIMIT_PER_BLOCK: u128 = 5_200_000;
+
+/// Maximum size of the manager operations in the block (Ithaca).
+const MAX_MANAGER_OPERATIONS_SIZE: u128 = 512 * 1024;
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct MempoolState {
     pub validator: MempoolValidatorState,
@@ -52,6 +73,20 @@ pub struct MempoolState {
     pub(super) pending_operations: MempoolPendingOperations,
     pub(super) prechecking_operations: BTreeSet<OperationHash>,
     pub validated_operations: ValidatedOperations,
+    // priorities and size of the applied operations, used for eviction
+    pub(super) applied_operations: MempoolAppliedOperations,
+    // applied operations were removed, so the prevalidator must be
+    // constructed again and the remaining applied operations revalidated
+    pub(super) validator_outdated: bool,
+    // operations evicted because of the mempool limits, oldest first
+    pub(super) evicted_operations: VecDeque<OperationHash>,
+    pub(super) evicted_operations_set: HashSet<OperationHash>,
+    /// Requirements on the manager operations accepted into the mempool.
+    pub filter: MempoolFilter,
+    /// Operations banned through rpc, never accepted into the mempool.
+    pub banned_operations: BTreeSet<OperationHash>,
+    /// Operations persisted in the storage.
+    pub storage: MempoolStorageState,
     // track ttl
     pub(super) level_to_operation: BTreeMap<i32, Vec<OperationHash>>,
 
@@ -74,19 +109,403 @@ impl MempoolState {
     pub fn next_for_prevalidation(&self) -> Option<(&OperationHash, &Operation)> {
         self.injecting_rpc_ids
             .iter()
+            .filter(|(hash, _)| !self.is_prechecking_manager_operation(hash))
             .find_map(|(hash, _)| {
                 self.pending_operations
                     .get(hash)
                     .map(|content| (hash, content))
             })
-            .or_else(|| self.pending_operations.next_for_prevalidation())
+            .or_else(|| {
+                self.pending_operations
+                    .next_for_prevalidation(&self.prechecking_operations)
+            })
+    }
+
+    /// Manager operation is validated by the protocol only after it is prechecked.
+    fn is_prechecking_manager_operation(&self, op_hash: &OperationHash) -> bool {
+        self.prechecking_operations.contains(op_hash)
+            && self.pending_operations.managers.contains_key(op_hash)
+    }
+
+    pub fn is_evicted(&self, op_hash: &OperationHash) -> bool {
+        self.evicted_operations_set.contains(op_hash)
+    }
+
+    pub fn is_banned(&self, op_hash: &OperationHash) -> bool {
+        self.banned_operations.contains(op_hash)
+    }
+
+    /// Whether the operation is already in the mempool, or it was evicted or banned.
+    pub fn is_known(&self, op_hash: &OperationHash) -> bool {
+        self.pending_operations.contains_key(op_hash)
+            || self.prechecking_operations.contains(op_hash)
+            || self.validated_operations.ops.contains_key(op_hash)
+            || self.is_evicted(op_hash)
+            || self.is_banned(op_hash)
+    }
+
+    /// Checks whether the new operation can be added to the pending operations.
+    ///
+    /// Returns the conflicting pending operation the new one replaces, if any.
+    pub fn check_new_operation(
+        &self,
+        hash: &OperationHash,
+        operation: &Operation,
+    ) -> Result<Option<OperationHash>, MempoolRejectReason> {
+        if self.is_banned(hash) {
+            return Err(MempoolRejectReason::Banned);
+        }
+        let manager = match ManagerOperationInfo::from_operation(operation) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        self.filter.check(operation, Some(&manager))?;
+
+        let (conflicting_hash, conflicting) = match self.pending_operations.conflicting(&manager) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        if conflicting_hash == hash {
+            return Ok(None);
+        }
+        if self.injecting_rpc_ids.contains_key(conflicting_hash)
+            || !self.filter.replaces(&manager, conflicting)
+        {
+            return Err(MempoolRejectReason::ReplacementFeesTooLow {
+                fee: manager.fee,
+                conflicting_fee: conflicting.fee,
+            });
+        }
+        Ok(Some(conflicting_hash.clone()))
+    }
+
+    /// Removes the operation from the mempool and bans it, so that it's
+    /// neither validated, broadcasted nor downloaded again.
+    pub(super) fn ban_operation(&mut self, hash: &OperationHash) {
+        self.banned_operations.insert(hash.clone());
+
+        self.pending_full_content.remove(hash);
+        self.pending_operations.remove(hash);
+        self.prechecking_operations.remove(hash);
+        self.operations_state.remove(hash);
+        for peer in self.peer_state.values_mut() {
+            peer.requesting_full_content.remove(hash);
+        }
+
+        // prevalidator must be constructed again without the operation.
+        if self.applied_operations.remove(hash) {
+            self.validator_outdated = true;
+        }
+
+        let validated = &mut self.validated_operations;
+        if validated.ops.remove(hash).is_some() {
+            validated.applied.retain(|v| &v.hash != hash);
+            validated.branch_delayed.retain(|v| &v.hash != hash);
+            validated.branch_refused.retain(|v| &v.hash != hash);
+            validated.refused.retain(|v| &v.hash != hash);
+            validated.outdated.retain(|v| &v.hash != hash);
+        }
+    }
+
+    /// Remembers the rejected operation, so that it's not downloaded again.
+    pub(super) fn rejected(
+        &mut self,
+        hash: &OperationHash,
+        reason: &MempoolRejectReason,
+        time: u64,
+    ) {
+        match reason {
+            MempoolRejectReason::Banned => {
+                self.operations_state.remove(hash);
+            }
+            MempoolRejectReason::FeesTooLow { .. }
+            | MempoolRejectReason::ReplacementFeesTooLow { .. } => {
+                self.evicted(hash.clone(), OperationEvictionReason::Filtered, time);
+            }
+        }
+    }
+
+    /// Removes the pending operation replaced by the conflicting one paying higher fees.
+    pub(super) fn replaced(&mut self, hash: &OperationHash, time: u64) {
+        if self.pending_operations.remove(hash).is_some() {
+            self.evicted(hash.clone(), OperationEvictionReason::Replaced, time);
+        }
+    }
+
+    /// Removes pending manager operations which don't pass the current filter.
+    pub(super) fn remove_filtered_pending(&mut self, time: u64) {
+        let filtered = self
+            .pending_operations
+            .managers
+            .iter()
+            .filter(|(hash, _)| !self.injecting_rpc_ids.contains_key(*hash))
+            .filter_map(|(hash, manager)| {
+                let operation = self.pending_operations.get(hash)?;
+                self.filter.check(operation, Some(manager)).err()?;
+                Some(hash.clone())
+            })
+            .collect::<Vec<_>>();
+        for hash in filtered {
+            self.pending_operations.remove(&hash);
+            self.evicted(hash, OperationEvictionReason::Filtered, time);
+        }
+    }
+
+    /// Evicts the lowest priority pending operations until they fit into the `limits`.
+    ///
+    /// Operations injected through rpc, which are still waiting for
+    /// the validation, are never evicted.
+    pub(super) fn evict_pending_over_limits(&mut self, limits: &MempoolLimits, time: u64) {
+        loop {
+            let reason = if self.pending_operations.len() > limits.pending_max_count {
+                OperationEvictionReason::PendingMaxCount
+            } else if self.pending_operations.bytes() > limits.pending_max_bytes {
+                OperationEvictionReason::PendingMaxBytes
+            } else {
+                return;
+            };
+            let injecting_rpc_ids = &self.injecting_rpc_ids;
+            let hash = match self
+                .pending_operations
+                .lowest_priority_iter()
+                .find(|hash| !injecting_rpc_ids.contains_key(hash))
+            {
+                Some(hash) => hash.clone(),
+                None => return,
+            };
+            self.pending_operations.remove(&hash);
+            self.evicted(hash, reason, time);
+        }
+    }
+
+    /// Evicts the lowest priority applied operations until they fit into the `limits`.
+    ///
+    /// The prevalidator still contains the evicted operations, so it's
+    /// marked as outdated.
+    pub(super) fn evict_applied_over_limits(&mut self, limits: &MempoolLimits, time: u64) {
+        let mut evicted = BTreeSet::new();
+        loop {
+            let reason = if self.applied_operations.len() > limits.validated_max_count {
+                OperationEvictionReason::ValidatedMaxCount
+            } else if self.applied_operations.bytes() > limits.validated_max_bytes {
+                OperationEvictionReason::ValidatedMaxBytes
+            } else {
+                break;
+            };
+            let hash = match self.applied_operations.lowest_priority() {
+                Some(hash) => hash.clone(),
+                None => break,
+            };
+            self.applied_operations.remove(&hash);
+            self.validated_operations.ops.remove(&hash);
+            self.evicted(hash.clone(), reason, time);
+            evicted.insert(hash);
+        }
+        if !evicted.is_empty() {
+            self.validated_operations
+                .applied
+                .retain(|v| !evicted.contains(&v.hash));
+            self.validator_outdated = true;
+        }
+    }
+
+    fn evicted(&mut self, hash: OperationHash, reason: OperationEvictionReason, time: u64) {
+        self.operations_state.remove(&hash);
+        self.operation_stats
+            .entry(hash.clone())
+            .or_insert_with(OperationStats::new)
+            .evicted(time, reason);
+        if self.evicted_operations_set.contains(&hash) {
+            return;
+        }
+        if self.evicted_operations.len() >= MAX_EVICTED_OPERATIONS {
+            if let Some(oldest) = self.evicted_operations.pop_front() {
+                self.evicted_operations_set.remove(&oldest);
+            }
+        }
+        self.evicted_operations_set.insert(hash.clone());
+        self.evicted_operations.push_back(hash);
+    }
+}
+
+/// Size of the operation in bytes, as it is included in the block.
+pub fn operation_size(operation: &Operation) -> usize {
+    BlockHash::hash_size() + operation.data().len()
+}
+
+/// Class of the operation, the more important operations are greater.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OperationPriorityClass {
+    /// Manager operations, ordered by their weight.
+    Manager,
+    /// Anonymous and voting operations.
+    Other,
+    /// Preendorsements and endorsements.
+    Consensus,
+}
+
+/// Priority of the operation in the mempool, the more important operations are greater.
+///
+/// Higher priority operations are validated first and the lowest priority
+/// ones are evicted when the mempool is full.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OperationPriority {
+    pub class: OperationPriorityClass,
+    /// Fee (in mutez) of the manager operation per whole block, that is
+    /// the fee the baker would get if the block was full of such operations.
+    ///
+    /// The block is limited by both gas and size, so the operation
+    /// consumes the larger part of the two. Zero for other operations.
+    pub weight: u64,
+}
+
+impl OperationPriority {
+    pub fn from_operation(operation: &Operation) -> Self {
+        Self::new(
+            operation,
+            ManagerOperationInfo::from_operation(operation).as_ref(),
+        )
+    }
+
+    fn new(operation: &Operation, manager: Option<&ManagerOperationInfo>) -> Self {
+        let kind = OperationKind::from_operation_content_raw(operation.data().as_ref());
+        let (class, weight) = if kind.is_consensus_operation() {
+            (OperationPriorityClass::Consensus, 0)
+        } else if kind.is_manager_operation() || matches!(kind, OperationKind::Unknown) {
+            (
+                OperationPriorityClass::Manager,
+                manager.map_or(0, |manager| {
+                    Self::manager_operation_weight(operation, manager)
+                }),
+            )
+        } else {
+            (OperationPriorityClass::Other, 0)
+        };
+        Self { class, weight }
+    }
+
+    fn manager_operation_weight(operation: &Operation, manager: &ManagerOperationInfo) -> u64 {
+        // consumed part of the block in millionths.
+        let gas = manager.gas_limit as u128 * 1_000_000 / HARD_GAS_LIMIT_PER_BLOCK;
+        let size = operation_size(operation) as u128 * 1_000_000 / MAX_MANAGER_OPERATIONS_SIZE;
+        let consumed = gas.max(size).max(1);
+
+        (manager.fee as u128 * 1_000_000 / consumed).min(u64::MAX as u128) as u64
+    }
+}
+
+/// Summary of the (batch of) manager operation(s).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManagerOperationInfo {
+    /// Source of the first operation in the batch.
+    pub source: SignaturePublicKeyHash,
+    /// Counter of the first operation in the batch.
+    pub counter: u64,
+    /// Total fee of the batch in mutez.
+    pub fee: u64,
+    /// Total gas limit of the batch.
+    pub gas_limit: u64,
+}
+
+impl ManagerOperationInfo {
+    /// Decodes the manager operation, `None` if it's not a manager operation
+    /// or it can't be decoded.
+    pub fn from_operation(operation: &Operation) -> Option<Self> {
+        let contents = OperationContents::from_bytes(operation.data())
+            .ok()?
+            .contents;
+        let mutez = |v: &Mutez| u64::try_from(&v.0).unwrap_or(u64::MAX);
+        let (source, counter) = contents.first()?.manager_source_and_counter()?;
+        let (fee, gas_limit) = contents
+            .iter()
+            .filter_map(Contents::manager_fee_and_gas_limit)
+            .fold((0u64, 0u64), |(fee, gas_limit), (op_fee, op_gas_limit)| {
+                (
+                    fee.saturating_add(mutez(op_fee)),
+                    gas_limit.saturating_add(mutez(op_gas_limit)),
+                )
+            });
+        Some(Self {
+            source: source.clone(),
+            counter: mutez(counter),
+            fee,
+            gas_limit,
+        })
+    }
+}
+
+/// Applied operations ordered by their priority, with their total size.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MempoolAppliedOperations {
+    priorities: BTreeMap<OperationHash, (OperationPriority, usize)>,
+    /// Operations ordered by their priority, the lowest first.
+    queue: BTreeSet<(OperationPriority, OperationHash)>,
+    /// Total size of the operations in bytes.
+    bytes: usize,
+}
+
+impl MempoolAppliedOperations {
+    pub fn len(&self) -> usize {
+        self.priorities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.priorities.is_empty()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn contains(&self, key: &OperationHash) -> bool {
+        self.priorities.contains_key(key)
+    }
+
+    pub fn insert(
+        &mut self,
+        key: OperationHash,
+        priority: OperationPriority,
+        operation: &Operation,
+    ) {
+        self.remove(&key);
+        let size = operation_size(operation);
+        self.bytes += size;
+        self.queue.insert((priority, key.clone()));
+        self.priorities.insert(key, (priority, size));
+    }
+
+    pub fn remove(&mut self, key: &OperationHash) -> bool {
+        match self.priorities.remove(key) {
+            Some((priority, size)) => {
+                self.queue.remove(&(priority, key.clone()));
+                self.bytes = self.bytes.saturating_sub(size);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Operation with the lowest priority, candidate for the eviction.
+    pub fn lowest_priority(&self) -> Option<&OperationHash> {
+        self.queue.iter().next().map(|(_, hash)| hash)
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct MempoolPendingOperations {
     ops: BTreeMap<OperationHash, Operation>,
-    consensus_ops: BTreeSet<OperationHash>,
+    priorities: BTreeMap<OperationHash, OperationPriority>,
+    /// Operations ordered by their priority, the lowest first.
+    queue: BTreeSet<(OperationPriority, OperationHash)>,
+    managers: BTreeMap<OperationHash, ManagerOperationInfo>,
+    /// Manager operations by their source and counter, to find the conflicting ones.
+    manager_counters: BTreeMap<SignaturePublicKeyHash, BTreeMap<u64, OperationHash>>,
+    /// Total size of the operations in bytes.
+    bytes: usize,
 }
 
 impl MempoolPendingOperations {
@@ -94,6 +513,18 @@ impl MempoolPendingOperations {
         self.ops.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn priority(&self, key: &OperationHash) -> Option<OperationPriority> {
+        self.priorities.get(key).copied()
+    }
+
     pub fn contains_key(&self, key: &OperationHash) -> bool {
         self.ops.contains_key(key)
     }
@@ -103,30 +534,75 @@ impl MempoolPendingOperations {
     }
 
     pub fn insert(&mut self, key: OperationHash, value: Operation) {
-        let op_kind = OperationKind::from_operation_content_raw(value.data().as_ref());
-        let is_new = self.ops.insert(key.clone(), value).is_none();
-        if is_new && op_kind.is_consensus_operation() {
-            self.consensus_ops.insert(key);
+        self.remove(&key);
+        let manager = ManagerOperationInfo::from_operation(&value);
+        let priority = OperationPriority::new(&value, manager.as_ref());
+        self.bytes += operation_size(&value);
+        self.queue.insert((priority, key.clone()));
+        self.priorities.insert(key.clone(), priority);
+        if let Some(manager) = manager {
+            self.manager_counters
+                .entry(manager.source.clone())
+                .or_default()
+                .insert(manager.counter, key.clone());
+            self.managers.insert(key.clone(), manager);
         }
+        self.ops.insert(key, value);
     }
 
     /// Remove an operation from pending queue.
     pub fn remove(&mut self, key: &OperationHash) -> Option<Operation> {
-        self.consensus_ops.remove(key);
-        self.ops.remove(key)
+        let op = self.ops.remove(key)?;
+        if let Some(priority) = self.priorities.remove(key) {
+            self.queue.remove(&(priority, key.clone()));
+        }
+        if let Some(manager) = self.managers.remove(key) {
+            if let Some(counters) = self.manager_counters.get_mut(&manager.source) {
+                if counters.get(&manager.counter) == Some(key) {
+                    counters.remove(&manager.counter);
+                }
+                if counters.is_empty() {
+                    self.manager_counters.remove(&manager.source);
+                }
+            }
+        }
+        self.bytes = self.bytes.saturating_sub(operation_size(&op));
+        Some(op)
+    }
+
+    /// Pending manager operation with the same source and counter.
+    pub fn conflicting(
+        &self,
+        manager: &ManagerOperationInfo,
+    ) -> Option<(&OperationHash, &ManagerOperationInfo)> {
+        let hash = self
+            .manager_counters
+            .get(&manager.source)?
+            .get(&manager.counter)?;
+        Some((hash, self.managers.get(hash)?))
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&OperationHash, &Operation)> {
         self.ops.iter()
     }
 
+    /// Operations from the lowest priority, candidates for the eviction.
+    pub fn lowest_priority_iter(&self) -> impl Iterator<Item = &OperationHash> {
+        self.queue.iter().map(|(_, hash)| hash)
+    }
+
     /// Get next operation with highest priority for prevalidation.
-    pub fn next_for_prevalidation(&self) -> Option<(&OperationHash, &Operation)> {
-        self.consensus_ops
+    /// Operation with the highest priority, skipping manager operations
+    /// that are still being prechecked.
+    pub fn next_for_prevalidation(
+        &self,
+        prechecking: &BTreeSet<OperationHash>,
+    ) -> Option<(&OperationHash, &Operation)> {
+        self.queue
             .iter()
-            .filter_map(|hash| self.ops.get(hash).map(|content| (hash, content)))
-            .chain(self.ops.iter())
-            .next()
+            .rev()
+            .filter(|(_, hash)| !(prechecking.contains(hash) && self.managers.contains_key(hash)))
+            .find_map(|(_, hash)| self.ops.get_key_value(hash))
     }
 }
 
@@ -182,6 +658,8 @@ pub struct OperationStats {
     pub validations: Vec<OperationValidationStats>,
     pub nodes: BTreeMap<CryptoboxPublicKeyHash, OperationNodeStats>,
     pub injected_timestamp: Option<u64>,
+    /// (time_evicted, reason) if the operation was evicted from the mempool.
+    pub evicted: Option<(u64, OperationEvictionReason)>,
 }
 
 impl OperationStats {
@@ -416,6 +894,27 @@ impl OperationStats {
     pub fn injected(&mut self, time: &u64) {
         self.injected_timestamp = Some(*time);
     }
+
+    pub fn evicted(&mut self, time: u64, reason: OperationEvictionReason) {
+        self.evicted = Some((time, reason));
+    }
+}
+
+/// Why the operation was evicted from the mempool.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum OperationEvictionReason {
+    /// Too many pending operations.
+    PendingMaxCount,
+    /// Pending operations are too big in total.
+    PendingMaxBytes,
+    /// Too many applied operations.
+    ValidatedMaxCount,
+    /// Applied operations are too big in total.
+    ValidatedMaxBytes,
+    /// Manager operation doesn't pay enough fees required by the mempool filter.
+    Filtered,
+    /// Replaced by the conflicting manager operation paying higher fees.
+    Replaced,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -554,6 +1053,18 @@ impl OperationKind {
             Self::Preendorsement | Self::Endorsement | Self::EndorsementWithSlot
         )
     }
+
+    pub fn is_manager_operation(&self) -> bool {
+        matches!(
+            self,
+            Self::Reveal
+                | Self::Transaction
+                | Self::Origination
+                | Self::Delegation
+                | Self::RegisterGlobalConstant
+                | Self::SetDepositsLimit
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -675,3 +1186,245 @@ impl OperationState {
         self.to_string() + "_time"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tezos_messages::p2p::binary_message::BinaryWrite;
+    use tezos_messages::protocol::proto_012::operation::Operation as ProtocolOperation;
+
+    use crate::config::default_test_config;
+    use crate::mempool::{
+        MempoolOperationBanAction, MempoolOperationRecvDoneAction, MempoolOperationUnbanAction,
+    };
+    use crate::{Action, ActionId, EnablingCondition, State};
+
+    use super::*;
+
+    fn op_hash(i: u8) -> OperationHash {
+        OperationHash::try_from_bytes(&[i; 32]).unwrap()
+    }
+
+    fn transaction(fee: u64, gas_limit: u64) -> Operation {
+        let operation: ProtocolOperation = serde_json::from_value(serde_json::json!({
+            "branch": "BKpbfCvh777DQHnXjU2sqHvVUNZ7dBAdqEfKkdw8EGSkD9LSYXb",
+            "contents": [{
+                "kind": "transaction",
+                "source": "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx",
+                "fee": fee.to_string(),
+                "counter": "732",
+                "gas_limit": gas_limit.to_string(),
+                "storage_limit": "257",
+                "amount": "407",
+                "destination": "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx"
+            }],
+            "signature": "sigbQ5ZNvkjvGssJgoAnUAfY4Wvvg3QZqawBYB1j1VDBNTMBAALnCzRHWzer34bnfmzgHg3EvwdzQKdxgSghB897cono6gbQ"
+        }))
+        .unwrap();
+        Operation::from_bytes(operation.as_bytes().unwrap()).unwrap()
+    }
+
+    fn endorsement() -> Operation {
+        let mut bytes = vec![0; 32];
+        // endorsement tag followed by slot, level, round and payload hash.
+        bytes.push(21);
+        bytes.extend_from_slice(&[0; 42]);
+        Operation::from_bytes(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_pending_operations_priority_order() {
+        let mut pending = MempoolPendingOperations::default();
+        pending.insert(op_hash(1), transaction(1_000, 10_000));
+        pending.insert(op_hash(2), transaction(100_000, 10_000));
+        pending.insert(op_hash(3), transaction(100_000, 1_000_000));
+        pending.insert(op_hash(4), endorsement());
+
+        let order = std::iter::from_fn(|| {
+            let hash = pending.next_for_prevalidation(&BTreeSet::new())?.0.clone();
+            pending.remove(&hash);
+            Some(hash)
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(order, vec![op_hash(4), op_hash(2), op_hash(3), op_hash(1)]);
+        assert_eq!(pending.bytes(), 0);
+    }
+
+    #[test]
+    fn test_next_for_prevalidation_skips_prechecking_managers() {
+        let mut state = MempoolState::default();
+        state
+            .pending_operations
+            .insert(op_hash(1), transaction(100_000, 10_000));
+        state.pending_operations.insert(op_hash(2), endorsement());
+        state
+            .pending_operations
+            .insert(op_hash(3), transaction(1_000, 10_000));
+        state.prechecking_operations.insert(op_hash(1));
+        state.prechecking_operations.insert(op_hash(2));
+
+        // prechecked endorsements are still validated by the protocol.
+        let next = |state: &MempoolState| state.next_for_prevalidation().map(|(h, _)| h.clone());
+        assert_eq!(next(&state), Some(op_hash(2)));
+        state.pending_operations.remove(&op_hash(2));
+        assert_eq!(next(&state), Some(op_hash(3)));
+
+        state.prechecking_operations.remove(&op_hash(1));
+        assert_eq!(next(&state), Some(op_hash(1)));
+    }
+
+    #[test]
+    fn test_evict_pending_over_limits() {
+        let mut state = MempoolState::default();
+        for i in 1..=3 {
+            let fee = i as u64 * 1_000;
+            state
+                .pending_operations
+                .insert(op_hash(i), transaction(fee, 10_000));
+        }
+        state.pending_operations.insert(op_hash(4), endorsement());
+
+        let limits = MempoolLimits {
+            pending_max_count: 2,
+            ..MempoolLimits::default()
+        };
+        state.evict_pending_over_limits(&limits, 7);
+
+        assert_eq!(state.pending_operations.len(), 2);
+        assert!(state.pending_operations.contains_key(&op_hash(3)));
+        assert!(state.pending_operations.contains_key(&op_hash(4)));
+        for hash in [op_hash(1), op_hash(2)] {
+            assert!(state.is_evicted(&hash));
+            assert!(matches!(
+                state.operation_stats.get(&hash).and_then(|s| s.evicted),
+                Some((7, OperationEvictionReason::PendingMaxCount))
+            ));
+        }
+    }
+
+    fn applied(state: &mut MempoolState, hash: OperationHash, operation: Operation) {
+        let priority = OperationPriority::from_operation(&operation);
+        state
+            .applied_operations
+            .insert(hash.clone(), priority, &operation);
+        state
+            .validated_operations
+            .ops
+            .insert(hash.clone(), operation);
+        state.validated_operations.applied.push(Applied {
+            hash,
+            protocol_data_json: String::new(),
+        });
+    }
+
+    #[test]
+    fn test_evict_applied_over_limits() {
+        let mut state = MempoolState::default();
+        for i in 1..=3 {
+            let fee = i as u64 * 1_000;
+            applied(&mut state, op_hash(i), transaction(fee, 10_000));
+        }
+        let size = operation_size(&transaction(1_000, 10_000));
+        assert_eq!(state.applied_operations.bytes(), 3 * size);
+
+        let limits = MempoolLimits {
+            validated_max_count: 3,
+            ..MempoolLimits::default()
+        };
+        state.evict_applied_over_limits(&limits, 7);
+        assert!(!state.validator_outdated);
+
+        applied(&mut state, op_hash(4), endorsement());
+        state.evict_applied_over_limits(&limits, 8);
+
+        assert!(state.validator_outdated);
+        assert_eq!(state.applied_operations.len(), 3);
+        assert_eq!(
+            state
+                .validated_operations
+                .applied
+                .iter()
+                .map(|v| v.hash.clone())
+                .collect::<Vec<_>>(),
+            vec![op_hash(2), op_hash(3), op_hash(4)]
+        );
+        assert!(state.is_evicted(&op_hash(1)));
+        assert!(!state.validated_operations.ops.contains_key(&op_hash(1)));
+        assert!(matches!(
+            state
+                .operation_stats
+                .get(&op_hash(1))
+                .and_then(|s| s.evicted),
+            Some((8, OperationEvictionReason::ValidatedMaxCount))
+        ));
+        assert_eq!(
+            state.applied_operations.bytes(),
+            2 * size + operation_size(&endorsement())
+        );
+    }
+
+    #[test]
+    fn test_evicted_operations_bound() {
+        let mut state = MempoolState::default();
+        for i in 0..MAX_EVICTED_OPERATIONS + 1 {
+            let mut bytes = [0; 32];
+            bytes[..8].copy_from_slice(&(i as u64).to_be_bytes());
+            let hash = OperationHash::try_from_bytes(&bytes).unwrap();
+            state.evicted(hash, OperationEvictionReason::PendingMaxCount, 0);
+        }
+        assert_eq!(state.evicted_operations.len(), MAX_EVICTED_OPERATIONS);
+        assert_eq!(state.evicted_operations_set.len(), MAX_EVICTED_OPERATIONS);
+        assert!(!state.is_evicted(&OperationHash::try_from_bytes(&[0; 32]).unwrap()));
+    }
+
+    fn reduce(state: &mut State, action: impl Into<Action>) {
+        crate::mempool::mempool_reducer(
+            state,
+            &ActionWithMeta {
+                action: action.into(),
+                id: ActionId::new_unchecked(0),
+                depth: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_banned_operation() {
+        let mut state = State::new(default_test_config());
+        let hash = op_hash(1);
+        let operation = transaction(1_000, 10_000);
+        applied(&mut state.mempool, hash.clone(), operation.clone());
+
+        reduce(&mut state, MempoolOperationBanAction { hash: hash.clone() });
+
+        // removed from the applied operations, so it's not broadcasted,
+        // and the prevalidator is constructed again without it.
+        assert!(state.mempool.validated_operations.applied.is_empty());
+        assert!(!state.mempool.validated_operations.ops.contains_key(&hash));
+        assert!(state.mempool.validator_outdated);
+
+        // not downloaded again.
+        assert!(state.mempool.is_known(&hash));
+
+        // not accepted for the prevalidation, even if requested before the ban.
+        state.mempool.pending_full_content.insert(hash.clone());
+        reduce(
+            &mut state,
+            MempoolOperationRecvDoneAction {
+                hash: hash.clone(),
+                operation,
+            },
+        );
+        assert!(!state.mempool.pending_operations.contains_key(&hash));
+        assert!(state.mempool.next_for_prevalidation().is_none());
+
+        assert!(MempoolOperationUnbanAction { hash: hash.clone() }.is_enabled(&state));
+        reduce(
+            &mut state,
+            MempoolOperationUnbanAction { hash: hash.clone() },
+        );
+        assert!(!state.mempool.is_known(&hash));
+
+        // not banned anymore.
+        assert!(!MempoolOperationUnbanAction { hash }.is_enabled(&state));
+    }
+}
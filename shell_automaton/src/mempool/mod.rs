@@ -3,9 +3,14 @@
 
 pub mod validator;
 
+pub mod storage;
+
 mod mempool_state;
 pub use self::mempool_state::*;
 
+mod mempool_filter;
+pub use self::mempool_filter::*;
+
 pub mod mempool_actions;
 pub use self::mempool_actions::*;
 
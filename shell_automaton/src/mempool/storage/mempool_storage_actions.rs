@@ -0,0 +1,87 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+use crate::request::RequestId;
+use crate::service::storage_service::{MempoolStorageLoadedItem, StorageError};
+use crate::{EnablingCondition, State};
+
+use super::{MempoolStorageLoadState, MEMPOOL_STORAGE_SYNC_INTERVAL};
+
+/// Load the operations persisted before the node was restarted.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageLoadInitAction {}
+
+impl EnablingCondition<State> for MempoolStorageLoadInitAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.config.disable_mempool
+            && state.mempool.local_head_state.is_some()
+            && matches!(&state.mempool.storage.load, MempoolStorageLoadState::Idle)
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageLoadPendingAction {
+    pub storage_req_id: RequestId,
+}
+
+impl EnablingCondition<State> for MempoolStorageLoadPendingAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        matches!(&state.mempool.storage.load, MempoolStorageLoadState::Idle)
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageLoadErrorAction {
+    pub error: StorageError,
+}
+
+impl EnablingCondition<State> for MempoolStorageLoadErrorAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        matches!(
+            &state.mempool.storage.load,
+            MempoolStorageLoadState::Pending { .. }
+        )
+    }
+}
+
+/// Persisted operations were loaded, live ones are added to the pending
+/// operations to be validated again.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageLoadSuccessAction {
+    pub operations: Vec<MempoolStorageLoadedItem>,
+}
+
+impl EnablingCondition<State> for MempoolStorageLoadSuccessAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        matches!(
+            &state.mempool.storage.load,
+            MempoolStorageLoadState::Pending { .. }
+        )
+    }
+}
+
+/// Write pending and applied operations to the storage and remove
+/// the ones which are no longer in the mempool.
+///
+/// Debounced, changes made since the last sync are written by the next one,
+/// dispatched at the latest by [`crate::check_timeouts`].
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageSyncAction {}
+
+impl EnablingCondition<State> for MempoolStorageSyncAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        let interval = MEMPOOL_STORAGE_SYNC_INTERVAL.as_nanos() as u64;
+        !state.config.disable_mempool
+            && state
+                .time_as_nanos()
+                .saturating_sub(state.mempool.storage.synced_at)
+                >= interval
+    }
+}
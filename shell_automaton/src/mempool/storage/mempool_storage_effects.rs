@@ -0,0 +1,108 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::mempool::MempoolOperationValidateNextAction;
+use crate::service::storage_service::{
+    MempoolStorageItem, StorageRequestPayload, StorageResponseError, StorageResponseSuccess,
+};
+use crate::service::Service;
+use crate::storage::request::{StorageRequestCreateAction, StorageRequestor};
+use crate::{Action, ActionWithMeta, Store};
+
+use super::{
+    MempoolStorageLoadErrorAction, MempoolStorageLoadInitAction, MempoolStorageLoadPendingAction,
+    MempoolStorageLoadState, MempoolStorageLoadSuccessAction, MempoolStorageSyncAction,
+};
+
+pub fn mempool_storage_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
+    match &action.action {
+        Action::CurrentHeadRehydrated(_) | Action::CurrentHeadUpdate(_) => {
+            store.dispatch(MempoolStorageLoadInitAction {});
+            store.dispatch(MempoolStorageSyncAction {});
+        }
+        Action::MempoolStorageLoadInit(_) => {
+            let storage_req_id = store.state().storage.requests.next_req_id();
+            store.dispatch(StorageRequestCreateAction {
+                payload: StorageRequestPayload::MempoolOperationsLoad,
+                requestor: StorageRequestor::None,
+            });
+            store.dispatch(MempoolStorageLoadPendingAction { storage_req_id });
+        }
+        Action::StorageResponseReceived(content) => {
+            let target_req_id = match &store.state().mempool.storage.load {
+                MempoolStorageLoadState::Pending { storage_req_id, .. } => *storage_req_id,
+                _ => return,
+            };
+            if content.response.req_id != Some(target_req_id) {
+                return;
+            }
+
+            match &content.response.result {
+                Ok(StorageResponseSuccess::MempoolOperationsLoadSuccess(operations)) => {
+                    store.dispatch(MempoolStorageLoadSuccessAction {
+                        operations: operations.clone(),
+                    });
+                }
+                Err(StorageResponseError::MempoolOperationsLoadError(error)) => {
+                    store.dispatch(MempoolStorageLoadErrorAction {
+                        error: error.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Action::MempoolStorageLoadSuccess(_) => {
+            if let MempoolStorageLoadState::Success { revalidated, .. } =
+                &store.state().mempool.storage.load
+            {
+                slog::info!(&store.state().log, "Loaded persisted mempool operations";
+                    "revalidated" => revalidated);
+            }
+            store.dispatch(MempoolStorageSyncAction {});
+            store.dispatch(MempoolOperationValidateNextAction {});
+        }
+        Action::MempoolStorageLoadError(content) => {
+            slog::warn!(&store.state().log, "Failed to load persisted mempool operations";
+                "error" => content.error.to_string());
+        }
+        Action::MempoolOperationRecvDone(_)
+        | Action::MempoolOperationInject(_)
+        | Action::MempoolOperationBan(_)
+        | Action::MempoolFilterSet(_)
+        | Action::MempoolValidatorReady(_)
+        | Action::MempoolValidatorValidateSuccess(_)
+        | Action::BlockApplierApplySuccess(_) => {
+            store.dispatch(MempoolStorageSyncAction {});
+        }
+        Action::MempoolStorageSync(_) => {
+            let mempool = &store.state().mempool;
+            let changes = &mempool.storage.changes;
+            if changes.is_empty() {
+                return;
+            }
+            let put = changes
+                .put
+                .iter()
+                .filter_map(|(hash, known_valid)| {
+                    let operation = if *known_valid {
+                        mempool.validated_operations.ops.get(hash)
+                    } else {
+                        mempool.pending_operations.get(hash)
+                    }?;
+                    Some(MempoolStorageItem {
+                        hash: hash.clone(),
+                        operation: operation.clone(),
+                        known_valid: *known_valid,
+                    })
+                })
+                .collect();
+            let delete = changes.delete.clone();
+
+            store.dispatch(StorageRequestCreateAction {
+                payload: StorageRequestPayload::MempoolOperationsUpdate { put, delete },
+                requestor: StorageRequestor::None,
+            });
+        }
+        _ => {}
+    }
+}
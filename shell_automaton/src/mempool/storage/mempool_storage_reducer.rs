@@ -0,0 +1,322 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+
+use crate::mempool::OPERATION_TTL;
+use crate::service::storage_service::MempoolStorageLoadedItem;
+use crate::{Action, ActionWithMeta, State};
+
+use super::{MempoolStorageChanges, MempoolStorageLoadState};
+
+pub fn mempool_storage_reducer(state: &mut State, action: &ActionWithMeta) {
+    let mempool = &mut state.mempool;
+
+    match &action.action {
+        Action::MempoolStorageLoadPending(content) => {
+            mempool.storage.load = MempoolStorageLoadState::Pending {
+                time: action.time_as_nanos(),
+                storage_req_id: content.storage_req_id,
+            };
+        }
+        Action::MempoolStorageLoadError(content) => {
+            mempool.storage.load = MempoolStorageLoadState::Error {
+                time: action.time_as_nanos(),
+                error: content.error.clone(),
+            };
+        }
+        Action::MempoolStorageLoadSuccess(content) => {
+            let (head_hash, head_level) = match &mempool.local_head_state {
+                Some(head) => (head.hash.clone(), head.header.level()),
+                None => return,
+            };
+            let min_level = head_level.saturating_sub(OPERATION_TTL);
+            let mut revalidated = 0;
+
+            for MempoolStorageLoadedItem { item, branch_level } in &content.operations {
+                // every loaded operation is in the storage, the ones which
+                // don't get into the mempool are removed by the next sync.
+                mempool
+                    .storage
+                    .persisted
+                    .insert(item.hash.clone(), item.known_valid);
+
+                let branch = item.operation.branch();
+                let branch_level = if branch == &head_hash {
+                    Some(head_level)
+                } else {
+                    mempool
+                        .last_predecessor_blocks
+                        .get(branch)
+                        .copied()
+                        .or(*branch_level)
+                };
+                let branch_level = match branch_level {
+                    Some(level) if level >= min_level => level,
+                    _ => continue,
+                };
+                if mempool.is_known(&item.hash)
+                    || !matches!(
+                        mempool.check_new_operation(&item.hash, &item.operation),
+                        Ok(None)
+                    )
+                {
+                    continue;
+                }
+
+                // expires with its branch.
+                mempool
+                    .level_to_operation
+                    .entry(branch_level)
+                    .or_default()
+                    .push(item.hash.clone());
+                mempool
+                    .pending_operations
+                    .insert(item.hash.clone(), item.operation.clone());
+                revalidated += 1;
+            }
+            mempool.evict_pending_over_limits(&state.config.mempool_limits, action.time_as_nanos());
+
+            mempool.storage.load = MempoolStorageLoadState::Success {
+                time: action.time_as_nanos(),
+                revalidated,
+            };
+        }
+        Action::MempoolStorageSync(_) => {
+            // pending operations and the ones applied by the prevalidator.
+            let live = mempool
+                .pending_operations
+                .iter()
+                .map(|(hash, _)| (hash, false))
+                .chain(
+                    mempool
+                        .validated_operations
+                        .applied
+                        .iter()
+                        .map(|v| (&v.hash, true)),
+                )
+                .collect::<BTreeMap<_, _>>();
+            let persisted = &mut mempool.storage.persisted;
+
+            let put = live
+                .iter()
+                .filter(|&(hash, known_valid)| persisted.get(*hash) != Some(known_valid))
+                .map(|(hash, known_valid)| ((*hash).clone(), *known_valid))
+                .collect::<BTreeMap<_, _>>();
+            let delete = persisted
+                .keys()
+                .filter(|hash| !live.contains_key(hash))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for hash in &delete {
+                persisted.remove(hash);
+            }
+            persisted.extend(put.clone());
+            mempool.storage.changes = MempoolStorageChanges { put, delete };
+            mempool.storage.synced_at = action.time_as_nanos();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use crypto::hash::{BlockHash, HashTrait, OperationHash};
+    use tezos_messages::p2p::binary_message::BinaryRead;
+    use tezos_messages::p2p::encoding::block_header::BlockHeaderBuilder;
+    use tezos_messages::p2p::encoding::operation::Operation;
+
+    use crate::config::default_test_config;
+    use crate::mempool::storage::{MempoolStorageLoadSuccessAction, MempoolStorageSyncAction};
+    use crate::mempool::HeadState;
+    use crate::service::storage_service::MempoolStorageItem;
+    use crate::{ActionId, State};
+
+    use super::*;
+
+    const HEAD_LEVEL: i32 = 200;
+
+    fn op_hash(i: u8) -> OperationHash {
+        OperationHash::try_from_bytes(&[i; 32]).unwrap()
+    }
+
+    fn block_hash(i: u8) -> BlockHash {
+        BlockHash::try_from_bytes(&[i; 32]).unwrap()
+    }
+
+    /// Endorsement with the `branch`.
+    fn operation(branch: &BlockHash) -> Operation {
+        let mut bytes = branch.as_ref().to_vec();
+        bytes.push(21);
+        bytes.extend_from_slice(&[0; 42]);
+        Operation::from_bytes(bytes).unwrap()
+    }
+
+    fn reduce(state: &mut State, time: u64, action: impl Into<Action>) {
+        mempool_storage_reducer(
+            state,
+            &ActionWithMeta {
+                action: action.into(),
+                id: ActionId::new_unchecked(time),
+                depth: 0,
+            },
+        );
+    }
+
+    fn state_with_head() -> State {
+        let mut state = State::new(default_test_config());
+        let header = BlockHeaderBuilder::default()
+            .level(HEAD_LEVEL)
+            .proto(1)
+            .predecessor(block_hash(2))
+            .timestamp(0i64.into())
+            .validation_pass(4)
+            .operations_hash(
+                "LLoZS2LW3rEi7KYU4ouBQtorua37aWWCtpDmv1n2x3xoKi6sVXLWp"
+                    .try_into()
+                    .unwrap(),
+            )
+            .fitness(vec![HEAD_LEVEL.to_be_bytes().to_vec()].into())
+            .context(
+                "CoV8SQumiVU9saiu3FVNeDNewJaJH8yWdsGF3WLdsRr2P9S7MzCj"
+                    .try_into()
+                    .unwrap(),
+            )
+            .protocol_data(vec![0, 1, 2, 3, 4, 5, 6, 7, 8].into())
+            .build()
+            .unwrap();
+        state.mempool.local_head_state = Some(HeadState {
+            header,
+            hash: block_hash(1),
+        });
+        state
+            .mempool
+            .last_predecessor_blocks
+            .insert(block_hash(2), HEAD_LEVEL - 1);
+        state
+    }
+
+    fn loaded(
+        hash: OperationHash,
+        branch: &BlockHash,
+        branch_level: Option<i32>,
+    ) -> MempoolStorageLoadedItem {
+        MempoolStorageLoadedItem {
+            item: MempoolStorageItem {
+                hash,
+                operation: operation(branch),
+                known_valid: false,
+            },
+            branch_level,
+        }
+    }
+
+    #[test]
+    fn test_load_success_filters_expired_operations() {
+        let mut state = state_with_head();
+        let operations = vec![
+            // branch is the head.
+            loaded(op_hash(1), &block_hash(1), None),
+            // branch is the known predecessor.
+            loaded(op_hash(2), &block_hash(2), None),
+            // branch is in the storage, still live.
+            loaded(op_hash(3), &block_hash(3), Some(HEAD_LEVEL - 100)),
+            // branch is older than TTL.
+            loaded(
+                op_hash(4),
+                &block_hash(4),
+                Some(HEAD_LEVEL - OPERATION_TTL - 1),
+            ),
+            // branch is unknown.
+            loaded(op_hash(5), &block_hash(5), None),
+        ];
+        reduce(
+            &mut state,
+            1,
+            MempoolStorageLoadSuccessAction { operations },
+        );
+
+        let mempool = &state.mempool;
+        assert!(matches!(
+            mempool.storage.load,
+            MempoolStorageLoadState::Success { revalidated: 3, .. }
+        ));
+        for i in 1..=3 {
+            assert!(mempool.pending_operations.contains_key(&op_hash(i)));
+        }
+        for i in 4..=5 {
+            assert!(!mempool.pending_operations.contains_key(&op_hash(i)));
+        }
+        // all of them are in the storage until the next sync.
+        assert_eq!(mempool.storage.persisted.len(), 5);
+
+        // operations expire with their branch.
+        assert_eq!(
+            mempool.level_to_operation.get(&HEAD_LEVEL),
+            Some(&vec![op_hash(1)])
+        );
+        assert_eq!(
+            mempool.level_to_operation.get(&(HEAD_LEVEL - 1)),
+            Some(&vec![op_hash(2)])
+        );
+        assert_eq!(
+            mempool.level_to_operation.get(&(HEAD_LEVEL - 100)),
+            Some(&vec![op_hash(3)])
+        );
+    }
+
+    #[test]
+    fn test_sync_changes() {
+        let mut state = state_with_head();
+        let branch = block_hash(1);
+        for i in 1..=2 {
+            state
+                .mempool
+                .pending_operations
+                .insert(op_hash(i), operation(&branch));
+        }
+        // stored as pending, but applied meanwhile.
+        state
+            .mempool
+            .validated_operations
+            .ops
+            .insert(op_hash(3), operation(&branch));
+        state
+            .mempool
+            .validated_operations
+            .applied
+            .push(tezos_api::ffi::Applied {
+                hash: op_hash(3),
+                protocol_data_json: String::new(),
+            });
+        let persisted = &mut state.mempool.storage.persisted;
+        persisted.insert(op_hash(1), false);
+        persisted.insert(op_hash(3), false);
+        persisted.insert(op_hash(4), false);
+
+        reduce(&mut state, 7, MempoolStorageSyncAction {});
+
+        let storage = &state.mempool.storage;
+        assert_eq!(
+            storage.changes.put,
+            vec![(op_hash(2), false), (op_hash(3), true)]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+        );
+        assert_eq!(storage.changes.delete, vec![op_hash(4)]);
+        assert_eq!(
+            storage.persisted,
+            vec![(op_hash(1), false), (op_hash(2), false), (op_hash(3), true)]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+        );
+        assert_eq!(storage.synced_at, 7);
+
+        // nothing changed since the last sync.
+        reduce(&mut state, 8, MempoolStorageSyncAction {});
+        assert!(state.mempool.storage.changes.is_empty());
+    }
+}
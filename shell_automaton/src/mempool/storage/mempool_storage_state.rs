@@ -0,0 +1,64 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crypto::hash::OperationHash;
+
+use crate::request::RequestId;
+use crate::service::storage_service::StorageError;
+
+/// Mempool is written to the storage at most once per this interval.
+pub const MEMPOOL_STORAGE_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Loading of the persisted operations, done once after the first head is applied.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum MempoolStorageLoadState {
+    Idle,
+    Pending {
+        time: u64,
+        storage_req_id: RequestId,
+    },
+    Success {
+        time: u64,
+        /// Number of the loaded operations added to the pending operations.
+        revalidated: usize,
+    },
+    Error {
+        time: u64,
+        error: StorageError,
+    },
+}
+
+impl Default for MempoolStorageLoadState {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
+
+/// Changes of the persisted operations, made by the last `MempoolStorageSyncAction`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MempoolStorageChanges {
+    /// Operations to store, `true` if the operation is known valid.
+    pub put: BTreeMap<OperationHash, bool>,
+    pub delete: Vec<OperationHash>,
+}
+
+impl MempoolStorageChanges {
+    pub fn is_empty(&self) -> bool {
+        self.put.is_empty() && self.delete.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MempoolStorageState {
+    pub load: MempoolStorageLoadState,
+    /// Operations in the storage, `true` if the operation is stored as known valid.
+    pub persisted: BTreeMap<OperationHash, bool>,
+    pub changes: MempoolStorageChanges,
+    /// Time of the last sync, see [`MEMPOOL_STORAGE_SYNC_INTERVAL`].
+    pub synced_at: u64,
+}
@@ -0,0 +1,16 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Persistence of the mempool operations, so that they survive the node's restart.
+
+mod mempool_storage_state;
+pub use mempool_storage_state::*;
+
+mod mempool_storage_actions;
+pub use mempool_storage_actions::*;
+
+mod mempool_storage_reducer;
+pub use mempool_storage_reducer::*;
+
+mod mempool_storage_effects;
+pub use mempool_storage_effects::*;
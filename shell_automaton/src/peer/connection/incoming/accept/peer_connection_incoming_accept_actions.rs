@@ -17,6 +17,8 @@ use crate::fuzzing::net::{PeerTokenMutator, SocketAddrMutator};
 pub enum PeerConnectionIncomingRejectedReason {
     PeersConnectedMaxBoundReached,
     PeerBlacklisted(PeerBlacklistState),
+    /// Peer's ip is denied by the incoming connections allow/deny lists.
+    PeerNotAllowed,
 }
 
 impl EnablingCondition<State> for PeerConnectionIncomingRejectedReason {
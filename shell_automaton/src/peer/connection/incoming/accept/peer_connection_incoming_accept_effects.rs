@@ -26,8 +26,11 @@ where
             match store.service.mio().peer_connection_incoming_accept() {
                 Ok((peer_token, peer)) => {
                     let peer_address = peer.address;
+                    let is_trusted = state.peers.acl.is_trusted_ip(&peer_address.ip());
 
-                    if state.peers.connected_len() >= state.config.peers_connected_max {
+                    if !is_trusted
+                        && state.peers.connected_len() >= state.config.peers_connected_max
+                    {
                         store.dispatch(PeerConnectionIncomingRejectedAction {
                             token: peer_token,
                             address: peer_address,
@@ -50,6 +53,15 @@ where
                         return;
                     }
 
+                    if !state.peers.acl.is_incoming_allowed(&peer_address.ip()) {
+                        store.dispatch(PeerConnectionIncomingRejectedAction {
+                            token: peer_token,
+                            address: peer_address,
+                            reason: PeerConnectionIncomingRejectedReason::PeerNotAllowed,
+                        });
+                        return;
+                    }
+
                     store.dispatch(PeerConnectionIncomingAcceptSuccessAction {
                         token: peer_token,
                         address: peer_address,
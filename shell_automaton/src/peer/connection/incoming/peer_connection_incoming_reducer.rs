@@ -29,12 +29,13 @@ pub fn peer_connection_incoming_reducer(state: &mut State, action: &ActionWithMe
         }
         Action::PeerConnectionIncomingSuccess(action) => {
             let peers_connected = state.peers.connected_len();
+            let is_trusted = state.peers.acl.is_trusted_ip(&action.address.ip());
             if let Some(peer) = state.peers.get_mut(&action.address) {
                 if let PeerStatus::Connecting(PeerConnectionState::Incoming(
                     PeerConnectionIncomingState::Pending { token, .. },
                 )) = peer.status
                 {
-                    if peers_connected <= state.config.peers_connected_max {
+                    if is_trusted || peers_connected <= state.config.peers_connected_max {
                         peer.status = PeerStatus::Connecting(
                             PeerConnectionIncomingState::Success {
                                 time: action_time,
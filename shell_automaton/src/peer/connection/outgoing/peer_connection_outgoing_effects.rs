@@ -24,7 +24,11 @@ where
                 return;
             }
 
-            if let Some(address) = store.service.randomness().choose_peer(&potential_peers) {
+            // Prefer the best-known peers from the address book.
+            let best_known = state.peers.best_known_potential_iter().next();
+            if let Some(address) =
+                best_known.or_else(|| store.service.randomness().choose_peer(&potential_peers))
+            {
                 store.dispatch(PeerConnectionOutgoingInitAction { address });
             }
         }
@@ -4,7 +4,7 @@
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
-use crate::{EnablingCondition, State};
+use crate::{EnablingCondition, Port, State};
 
 #[cfg(feature = "fuzzing")]
 use crate::fuzzing::net::SocketAddrMutator;
@@ -29,6 +29,8 @@ impl EnablingCondition<State> for PeerDisconnectAction {
 pub struct PeerDisconnectedAction {
     #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
     pub address: SocketAddr,
+    /// Port the peer listens on, if known, see [`super::PeerDisconnecting::listen_port`].
+    pub listen_port: Option<Port>,
 }
 
 impl EnablingCondition<State> for PeerDisconnectedAction {
@@ -25,11 +25,18 @@ where
             match &peer.status {
                 PeerStatus::Disconnecting(disconnection_state) => {
                     let peer_token = disconnection_state.token;
+                    let listen_port = disconnection_state.listen_port;
                     store.service().mio().peer_disconnect(peer_token);
-                    store.dispatch(PeerDisconnectedAction { address });
+                    store.dispatch(PeerDisconnectedAction {
+                        address,
+                        listen_port,
+                    });
                 }
                 PeerStatus::Disconnected => {
-                    store.dispatch(PeerDisconnectedAction { address });
+                    store.dispatch(PeerDisconnectedAction {
+                        address,
+                        listen_port: None,
+                    });
                 }
                 _ => {}
             };
@@ -1,6 +1,7 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
+use crate::peer::connection::PeerConnectionState;
 use crate::peer::disconnection::PeerDisconnecting;
 use crate::peer::PeerStatus;
 use crate::{Action, ActionWithMeta, State};
@@ -17,13 +18,29 @@ pub fn peer_disconnection_reducer(state: &mut State, action: &ActionWithMeta) {
                 PeerStatus::Potential => return,
                 PeerStatus::Connecting(state) => {
                     if let Some(token) = state.token() {
-                        PeerDisconnecting { token }.into()
+                        let listen_port = match state {
+                            PeerConnectionState::Outgoing(_) => Some(action.address.port()),
+                            PeerConnectionState::Incoming(_) => None,
+                        };
+                        PeerDisconnecting { token, listen_port }.into()
                     } else {
                         PeerStatus::Disconnected
                     }
                 }
-                PeerStatus::Handshaking(state) => PeerDisconnecting { token: state.token }.into(),
-                PeerStatus::Handshaked(state) => PeerDisconnecting { token: state.token }.into(),
+                PeerStatus::Handshaking(state) => PeerDisconnecting {
+                    token: state.token,
+                    listen_port: if state.incoming {
+                        None
+                    } else {
+                        Some(action.address.port())
+                    },
+                }
+                .into(),
+                PeerStatus::Handshaked(state) => PeerDisconnecting {
+                    token: state.token,
+                    listen_port: Some(state.port),
+                }
+                .into(),
                 PeerStatus::Disconnecting(_) => return,
                 PeerStatus::Disconnected => return,
             };
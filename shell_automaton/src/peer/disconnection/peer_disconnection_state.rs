@@ -5,10 +5,15 @@ use derive_more::From;
 use serde::{Deserialize, Serialize};
 
 use crate::peer::PeerToken;
+use crate::Port;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PeerDisconnecting {
     pub token: PeerToken,
+    /// Port the peer listens on, if known.
+    ///
+    /// It's only known for the outgoing connections and the handshaked peers.
+    pub listen_port: Option<Port>,
 }
 
 #[derive(From, Serialize, Deserialize, Debug, Clone)]
@@ -8,7 +8,7 @@ use networking::network_channel::PeerMessageReceived;
 use storage::{BlockHeaderWithHash, OperationKey};
 use tezos_messages::p2p::binary_message::{BinaryRead, MessageHash};
 use tezos_messages::p2p::encoding::peer::{PeerMessage, PeerMessageResponse};
-use tezos_messages::p2p::encoding::prelude::AdvertiseMessage;
+use tezos_messages::p2p::encoding::prelude::{AdvertiseMessage, DeactivateMessage};
 
 use crate::bootstrap::{
     BootstrapPeerBlockHeaderGetSuccessAction, BootstrapPeerBlockOperationsReceivedAction,
@@ -16,16 +16,23 @@ use crate::bootstrap::{
 };
 use crate::mempool::MempoolRecvDoneAction;
 use crate::peer::binary_message::read::PeerBinaryMessageReadInitAction;
+use crate::peer::disconnection::PeerDisconnectAction;
 use crate::peer::message::read::PeerMessageReadErrorAction;
 use crate::peer::message::write::PeerMessageWriteInitAction;
 use crate::peer::remote_requests::block_header_get::PeerRemoteRequestsBlockHeaderGetEnqueueAction;
 use crate::peer::remote_requests::block_operations_get::PeerRemoteRequestsBlockOperationsGetEnqueueAction;
 use crate::peer::remote_requests::current_branch_get::PeerRemoteRequestsCurrentBranchGetInitAction;
 use crate::peer::requests::potential_peers_get::PeerRequestsPotentialPeersGetSuccessAction;
+use crate::peer::requests::protocols_get::PeerRequestsProtocolsGetSuccessAction;
 use crate::peer::{Peer, PeerCurrentHeadUpdateAction};
 use crate::peers::graylist::{PeerGraylistReason, PeersGraylistAddressAction};
+use crate::peers::parse_point;
+use crate::peers::score::{PeerScoreEvent, PeersScoreUpdateAction};
+use crate::peers::swap::{PeersSwapAckReceivedAction, PeersSwapRequestReceivedAction};
 use crate::service::actors_service::{ActorsMessageTo, ActorsService};
+use crate::service::storage_service::StorageRequestPayload;
 use crate::service::{RandomnessService, Service, StatisticsService};
+use crate::storage::request::{StorageRequestCreateAction, StorageRequestor};
 use crate::{Action, ActionId, ActionWithMeta, State, Store};
 
 use super::{PeerMessageReadInitAction, PeerMessageReadSuccessAction};
@@ -174,12 +181,18 @@ where
 
             match &content.message.message() {
                 PeerMessage::Bootstrap => {
-                    let potential_peers =
-                        store.state.get().peers.potential_iter().collect::<Vec<_>>();
-                    let advertise_peers = store
+                    let state = store.state.get();
+                    let potential_peers = state.peers.potential_iter().collect::<Vec<_>>();
+                    let public_address = state
+                        .config
+                        .public_address
+                        .filter(|_| !state.config.private_node);
+                    let mut advertise_peers = store
                         .service
                         .randomness()
                         .choose_potential_peers_for_advertise(&potential_peers);
+                    // let the peer know where we can be reached.
+                    advertise_peers.extend(public_address);
                     store.dispatch(PeerMessageWriteInitAction {
                         address: content.address,
                         message: PeerMessageResponse::from(AdvertiseMessage::new(advertise_peers))
@@ -189,12 +202,16 @@ where
                 PeerMessage::Advertise(msg) => {
                     store.dispatch(PeerRequestsPotentialPeersGetSuccessAction {
                         address: content.address,
-                        result: msg.id().iter().filter_map(|x| x.parse().ok()).collect(),
+                        result: msg.id().iter().filter_map(|x| parse_point(x)).collect(),
                     });
                 }
                 PeerMessage::GetCurrentBranch(msg) => {
                     if msg.chain_id != store.state().config.chain_id {
-                        // TODO: log
+                        // we don't run this chain, tell the peer to stop asking.
+                        store.dispatch(PeerMessageWriteInitAction {
+                            address: content.address,
+                            message: DeactivateMessage::new(msg.chain_id.clone()).into(),
+                        });
                         return;
                     }
                     if !store.dispatch(PeerRemoteRequestsCurrentBranchGetInitAction {
@@ -210,6 +227,79 @@ where
                                     "current" => format!("{:?}", current));
                     }
                 }
+                PeerMessage::GetCurrentHead(msg) => {
+                    // requests for our chain are handled by mempool.
+                    if msg.chain_id() != &store.state().config.chain_id {
+                        store.dispatch(PeerMessageWriteInitAction {
+                            address: content.address,
+                            message: DeactivateMessage::new(msg.chain_id().clone()).into(),
+                        });
+                    }
+                }
+                PeerMessage::Deactivate(msg) => {
+                    let state = store.state.get();
+                    if msg.deactivate() != &state.config.chain_id {
+                        // test chain, which we don't run.
+                        slog::debug!(&state.log, "Peer deactivated unknown chain";
+                            "peer" => format!("{}", content.address),
+                            "chain_id" => msg.deactivate().to_base58_check());
+                        return;
+                    }
+                    slog::info!(&state.log, "Peer stopped following our chain, disconnecting";
+                        "peer" => format!("{}", content.address),
+                        "peer_pkh" => format!("{:?}", state.peer_public_key_hash_b58check(content.address)));
+                    store.dispatch(PeerDisconnectAction {
+                        address: content.address,
+                    });
+                }
+                PeerMessage::SwapRequest(msg) | PeerMessage::SwapAck(msg) => {
+                    let point = match parse_point(msg.point()) {
+                        Some(v) => v,
+                        None => {
+                            slog::debug!(&store.state().log, "Peer sent invalid swap point";
+                                "peer" => format!("{}", content.address),
+                                "point" => msg.point());
+                            return;
+                        }
+                    };
+                    if let PeerMessage::SwapRequest(_) = content.message.message() {
+                        store.dispatch(PeersSwapRequestReceivedAction {
+                            address: content.address,
+                            point,
+                            peer_id: msg.peer_id().clone(),
+                        });
+                    } else {
+                        store.dispatch(PeersSwapAckReceivedAction {
+                            address: content.address,
+                            point,
+                            peer_id: msg.peer_id().clone(),
+                        });
+                    }
+                }
+                PeerMessage::GetProtocols(msg) => {
+                    for protocol_hash in msg.get_protocols() {
+                        store.dispatch(StorageRequestCreateAction {
+                            payload: StorageRequestPayload::ProtocolGet(protocol_hash.clone()),
+                            requestor: StorageRequestor::Peer(content.address),
+                        });
+                    }
+                }
+                PeerMessage::Protocol(msg) => {
+                    let protocol_hash = match msg.protocol().message_typed_hash() {
+                        Ok(v) => v,
+                        Err(_) => return,
+                    };
+                    if !store.dispatch(PeerRequestsProtocolsGetSuccessAction {
+                        address: content.address,
+                        protocol_hash,
+                        result: msg.protocol().clone(),
+                    }) {
+                        store.dispatch(PeersScoreUpdateAction {
+                            address: content.address,
+                            event: PeerScoreEvent::UselessResponse,
+                        });
+                    }
+                }
                 PeerMessage::CurrentHead(msg) => {
                     if msg.chain_id() != &store.state().config.chain_id {
                         return;
@@ -348,9 +438,33 @@ where
                             "peer_pkh" => format!("{:?}", state.peer_public_key_hash_b58check(content.address)),
                             "block" => format!("{:?}", &block),
                             "expected" => format!("{:?}", state.bootstrap.peer_interval(content.address, |p| p.current.is_pending())));
+                        store.dispatch(PeersScoreUpdateAction {
+                            address: content.address,
+                            event: PeerScoreEvent::UselessResponse,
+                        });
                     }
                 }
                 PeerMessage::OperationsForBlocks(msg) => {
+                    let operations_for_block = msg.operations_for_block();
+                    let event = match store.state().bootstrap.peer_block_operations_get(
+                        content.address,
+                        operations_for_block.block_hash(),
+                    ) {
+                        Some(p) => {
+                            let validation_pass = operations_for_block.validation_pass().max(0);
+                            if p.is_validation_pass_pending(validation_pass as u8) {
+                                PeerScoreEvent::TimelyOperations
+                            } else {
+                                PeerScoreEvent::DuplicateResponse
+                            }
+                        }
+                        None => PeerScoreEvent::UselessResponse,
+                    };
+                    store.dispatch(PeersScoreUpdateAction {
+                        address: content.address,
+                        event,
+                    });
+
                     store.dispatch(BootstrapPeerBlockOperationsReceivedAction {
                         peer: content.address,
                         message: msg.clone(),
@@ -7,6 +7,7 @@ pub mod connection;
 pub mod disconnection;
 pub mod handshaking;
 pub mod message;
+pub mod recorder;
 pub mod remote_requests;
 pub mod requests;
 
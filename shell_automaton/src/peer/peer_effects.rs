@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: MIT
 use std::io;
 use tezos_messages::p2p::binary_message::CONTENT_LENGTH_FIELD_BYTES;
+use tezos_messages::p2p::encoding::protocol::ProtocolMessage;
 
 use crate::paused_loops::{PausedLoop, PausedLoopsAddAction};
+use crate::peers::bandwidth::{
+    peers_bandwidth_download_allowance, peers_bandwidth_upload_allowance,
+};
 use crate::request::RequestId;
 use crate::service::storage_service::{StorageResponseError, StorageResponseSuccess};
 use crate::service::{MioService, Service};
@@ -28,6 +32,7 @@ use super::connection::PeerConnectionState;
 use super::disconnection::PeerDisconnectAction;
 use super::handshaking::PeerHandshakingStatus;
 use super::message::read::PeerMessageReadState;
+use super::message::write::PeerMessageWriteInitAction;
 use super::remote_requests::block_header_get::{
     PeerRemoteRequestsBlockHeaderGetErrorAction, PeerRemoteRequestsBlockHeaderGetSuccessAction,
 };
@@ -152,7 +157,16 @@ where
                     _ => return finish(store, address, PeerIOLoopResult::NotReady),
                 };
 
-                match mio_peer.write(&chunk.raw()[*prev_written..]) {
+                let mut to_write = &chunk.raw()[*prev_written..];
+                match peers_bandwidth_upload_allowance(store.state.get(), peer) {
+                    Some(0) => return finish(store, address, PeerIOLoopResult::ByteQuotaReached),
+                    Some(allowance) => {
+                        to_write = &to_write[..to_write.len().min(allowance as usize)]
+                    }
+                    None => {}
+                }
+
+                match mio_peer.write(to_write) {
                     Ok(written) if written > 0 => {
                         store.dispatch(PeerChunkWritePartAction {
                             address: action.address,
@@ -253,6 +267,14 @@ where
                     _ => return finish(store, address, PeerIOLoopResult::NotReady),
                 };
                 // debug_assert!(bytes_to_read > 0);
+                let bytes_to_read =
+                    match peers_bandwidth_download_allowance(store.state.get(), peer) {
+                        Some(0) => {
+                            return finish(store, address, PeerIOLoopResult::ByteQuotaReached)
+                        }
+                        Some(allowance) => bytes_to_read.min(allowance as usize),
+                        None => bytes_to_read,
+                    };
 
                 match mio_peer.read(bytes_to_read) {
                     Ok(bytes) if !bytes.is_empty() => {
@@ -389,6 +411,18 @@ where
                         error: error.clone(),
                     });
                 }
+                Ok(StorageResponseSuccess::ProtocolGetSuccess(_, Some(protocol))) => {
+                    store.dispatch(PeerMessageWriteInitAction {
+                        address,
+                        message: ProtocolMessage::new(protocol.clone()).into(),
+                    });
+                }
+                Ok(StorageResponseSuccess::ProtocolGetSuccess(protocol_hash, None)) => {
+                    // same as octez, unknown protocol is not answered.
+                    slog::debug!(&state.log, "Peer requested unknown protocol";
+                        "address" => address.to_string(),
+                        "protocol_hash" => protocol_hash.to_base58_check());
+                }
                 _ => {}
             }
         }
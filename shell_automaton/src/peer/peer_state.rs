@@ -9,6 +9,7 @@ use crypto::hash::CryptoboxPublicKeyHash;
 use storage::BlockHeaderWithHash;
 use tezos_messages::p2p::encoding::version::NetworkVersion;
 
+use crate::peers::bandwidth::PeerBandwidth;
 use crate::Port;
 
 use super::connection::PeerConnectionState;
@@ -78,6 +79,7 @@ pub struct Peer {
     pub status: PeerStatus,
     pub try_read_loop: PeerIOLoopState,
     pub try_write_loop: PeerIOLoopState,
+    pub bandwidth: PeerBandwidth,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -100,6 +102,17 @@ impl PeerIOLoopState {
             },
         }
     }
+
+    /// Whether or not loop was stopped because of the bandwidth limit.
+    pub fn is_byte_quota_reached(&self) -> bool {
+        matches!(
+            self,
+            Self::Finished {
+                result: PeerIOLoopResult::ByteQuotaReached,
+                ..
+            }
+        )
+    }
 }
 
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
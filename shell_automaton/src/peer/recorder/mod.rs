@@ -0,0 +1,5 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peer_recorder_effects;
+pub use peer_recorder_effects::*;
@@ -0,0 +1,89 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use tezos_messages::p2p::binary_message::BinaryRead;
+use tezos_messages::p2p::encoding::peer::PeerMessageResponse;
+
+use crate::service::p2p_recorder_service::{P2pRecord, P2pRecordDirection, P2pRecordEvent};
+use crate::service::Service;
+use crate::{Action, ActionWithMeta, Store};
+
+fn is_handshaked<S: Service>(store: &Store<S>, address: &SocketAddr) -> bool {
+    store
+        .state()
+        .peers
+        .get(address)
+        .map_or(false, |peer| peer.status.as_handshaked().is_some())
+}
+
+fn message_event(direction: P2pRecordDirection, raw: &[u8]) -> P2pRecordEvent {
+    P2pRecordEvent::Message {
+        direction,
+        raw: raw.to_vec(),
+        message: PeerMessageResponse::from_bytes(raw)
+            .ok()
+            .map(|response| response.message().clone()),
+    }
+}
+
+/// Records decrypted messages of the handshaked peers, if the recorder is enabled.
+pub fn peer_recorder_effects<S>(store: &mut Store<S>, action: &ActionWithMeta)
+where
+    S: Service,
+{
+    if store.service.p2p_recorder().is_none() {
+        return;
+    }
+
+    let (address, event) = match &action.action {
+        Action::PeerHandshakingFinish(content) => {
+            let peer = match store
+                .state()
+                .peers
+                .get(&content.address)
+                .and_then(|peer| peer.status.as_handshaked())
+            {
+                Some(v) => v,
+                None => return,
+            };
+            let event = P2pRecordEvent::Handshaked {
+                public_key_hash: peer.public_key_hash.clone(),
+                port: peer.port,
+                disable_mempool: peer.disable_mempool,
+                private_node: peer.private_node,
+            };
+            (content.address, event)
+        }
+        Action::PeerBinaryMessageReadReady(content) => {
+            if !is_handshaked(store, &content.address) {
+                return;
+            }
+            let event = message_event(P2pRecordDirection::Incoming, &content.message);
+            (content.address, event)
+        }
+        Action::PeerBinaryMessageWriteSetContent(content) => {
+            if !is_handshaked(store, &content.address) {
+                return;
+            }
+            let event = message_event(P2pRecordDirection::Outgoing, &content.message);
+            (content.address, event)
+        }
+        Action::PeerDisconnected(content) => (content.address, P2pRecordEvent::Disconnected),
+        _ => return,
+    };
+
+    let record = P2pRecord {
+        time: action.time_as_nanos(),
+        address,
+        event,
+    };
+    if let Some(recorder) = store.service.p2p_recorder() {
+        if let Err(err) = recorder.record(record) {
+            slog::warn!(&store.state().log, "Failed to record p2p traffic";
+                "peer" => format!("{}", address),
+                "error" => format!("{:?}", err));
+        }
+    }
+}
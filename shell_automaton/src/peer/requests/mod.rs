@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT
 
 pub mod potential_peers_get;
+pub mod protocols_get;
 
 mod peer_requests_state;
 pub use peer_requests_state::*;
@@ -4,8 +4,10 @@
 use serde::{Deserialize, Serialize};
 
 use super::potential_peers_get::PeerRequestsPotentialPeersGetState;
+use super::protocols_get::PeerRequestsProtocolsGetState;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct PeerRequestsState {
     pub potential_peers_get: PeerRequestsPotentialPeersGetState,
+    pub protocols_get: PeerRequestsProtocolsGetState,
 }
@@ -0,0 +1,17 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peer_requests_protocols_get_state;
+pub use peer_requests_protocols_get_state::*;
+
+mod peer_requests_protocols_get_actions;
+pub use peer_requests_protocols_get_actions::*;
+
+mod peer_requests_protocols_get_reducer;
+pub use peer_requests_protocols_get_reducer::*;
+
+mod peer_requests_protocols_get_effects;
+pub use peer_requests_protocols_get_effects::*;
+
+/// 10 seconds.
+pub const PEER_PROTOCOLS_GET_TIMEOUT: u64 = 10 * 1_000_000_000;
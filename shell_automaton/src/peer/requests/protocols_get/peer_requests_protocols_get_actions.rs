@@ -0,0 +1,130 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crypto::hash::ProtocolHash;
+use tezos_messages::p2p::encoding::protocol::Protocol;
+
+use crate::{EnablingCondition, State};
+
+use super::PeerRequestsProtocolsGetError;
+
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::SocketAddrMutator;
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerRequestsProtocolsGetInitAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    pub protocol_hash: ProtocolHash,
+}
+
+impl PeerRequestsProtocolsGetInitAction {
+    pub fn should_request(state: &State, protocol_hash: &ProtocolHash) -> bool {
+        // only request the protocol from 1 peer at a time.
+        !state.peers.handshaked_iter().any(|(_, p)| {
+            p.requests.protocols_get.is_init()
+                || p.requests
+                    .protocols_get
+                    .is_pending_protocol_hash_eq(protocol_hash)
+        })
+    }
+
+    pub fn can_request_from_peer(
+        state: &State,
+        peer: SocketAddr,
+        protocol_hash: &ProtocolHash,
+    ) -> bool {
+        state
+            .peers
+            .get_handshaked(&peer)
+            .map(|p| &p.requests.protocols_get)
+            // don't ask the peer which already failed to send us the protocol.
+            .map_or(false, |req| {
+                !req.is_init() && !req.is_pending() && !req.is_error_protocol_hash_eq(protocol_hash)
+            })
+    }
+}
+
+impl EnablingCondition<State> for PeerRequestsProtocolsGetInitAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        Self::should_request(state, &self.protocol_hash)
+            && Self::can_request_from_peer(state, self.address, &self.protocol_hash)
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerRequestsProtocolsGetPendingAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeerRequestsProtocolsGetPendingAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .peers
+            .get_handshaked(&self.address)
+            .map_or(false, |p| p.requests.protocols_get.is_init())
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerRequestsProtocolsGetErrorAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    pub error: PeerRequestsProtocolsGetError,
+}
+
+impl EnablingCondition<State> for PeerRequestsProtocolsGetErrorAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .peers
+            .get_handshaked(&self.address)
+            .map_or(false, |p| p.requests.protocols_get.is_pending())
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerRequestsProtocolsGetSuccessAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    /// Hash of the received protocol.
+    pub protocol_hash: ProtocolHash,
+    pub result: Protocol,
+}
+
+impl EnablingCondition<State> for PeerRequestsProtocolsGetSuccessAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .peers
+            .get_handshaked(&self.address)
+            .map_or(false, |p| {
+                p.requests
+                    .protocols_get
+                    .is_pending_protocol_hash_eq(&self.protocol_hash)
+            })
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerRequestsProtocolsGetFinishAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeerRequestsProtocolsGetFinishAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .peers
+            .get_handshaked(&self.address)
+            .map_or(false, |p| p.requests.protocols_get.is_success())
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crypto::hash::ProtocolHash;
+use tezos_messages::p2p::encoding::protocol::GetProtocolsMessage;
+
+use crate::block_applier::BlockApplierApplyState;
+use crate::peer::message::write::PeerMessageWriteInitAction;
+use crate::service::storage_service::{StorageRequestPayload, StorageResponseSuccess};
+use crate::storage::request::{StorageRequestCreateAction, StorageRequestor};
+use crate::{Action, ActionWithMeta, Service, Store};
+
+use super::{
+    PeerRequestsProtocolsGetFinishAction, PeerRequestsProtocolsGetInitAction,
+    PeerRequestsProtocolsGetPendingAction, PeerRequestsProtocolsGetState,
+};
+
+pub fn peer_requests_protocols_get_effects<S>(store: &mut Store<S>, action: &ActionWithMeta)
+where
+    S: Service,
+{
+    match &action.action {
+        Action::PeerRequestsProtocolsGetInit(content) => {
+            store.dispatch(PeerMessageWriteInitAction {
+                address: content.address,
+                message: GetProtocolsMessage::new(vec![content.protocol_hash.clone()]).into(),
+            });
+            store.dispatch(PeerRequestsProtocolsGetPendingAction {
+                address: content.address,
+            });
+        }
+        Action::PeerRequestsProtocolsGetError(content) => {
+            let protocol_hash = match store
+                .state()
+                .peers
+                .get_handshaked(&content.address)
+                .and_then(|p| p.requests.protocols_get.protocol_hash())
+            {
+                Some(v) => v.clone(),
+                None => return,
+            };
+            request_protocol_from_any_peer(store, protocol_hash);
+        }
+        Action::PeerRequestsProtocolsGetSuccess(content) => {
+            let (protocol_hash, protocol) = match store
+                .state()
+                .peers
+                .get_handshaked(&content.address)
+                .map(|p| &p.requests.protocols_get)
+            {
+                Some(PeerRequestsProtocolsGetState::Success {
+                    protocol_hash,
+                    result,
+                    ..
+                }) => (protocol_hash.clone(), result.clone()),
+                _ => return,
+            };
+            store.dispatch(StorageRequestCreateAction {
+                payload: StorageRequestPayload::ProtocolPut(protocol_hash, protocol),
+                requestor: StorageRequestor::None,
+            });
+            store.dispatch(PeerRequestsProtocolsGetFinishAction {
+                address: content.address,
+            });
+        }
+        Action::BlockApplierApplySuccess(_) => {
+            // check whether we know the protocol the next block will be applied with.
+            let next_protocol_hash = match &store.state().block_applier.current {
+                BlockApplierApplyState::Success {
+                    block_additional_data,
+                    ..
+                } if block_additional_data.protocol_hash
+                    != block_additional_data.next_protocol_hash =>
+                {
+                    block_additional_data.next_protocol_hash.clone()
+                }
+                _ => return,
+            };
+            store.dispatch(StorageRequestCreateAction {
+                payload: StorageRequestPayload::ProtocolGet(next_protocol_hash),
+                requestor: StorageRequestor::None,
+            });
+        }
+        Action::StorageResponseReceived(content) => {
+            if !matches!(content.requestor, StorageRequestor::None) {
+                return;
+            }
+            if let Ok(StorageResponseSuccess::ProtocolGetSuccess(protocol_hash, None)) =
+                &content.response.result
+            {
+                request_protocol_from_any_peer(store, protocol_hash.clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Requests unknown protocol from the first peer we haven't asked for it yet.
+pub fn request_protocol_from_any_peer<S>(store: &mut Store<S>, protocol_hash: ProtocolHash)
+where
+    S: Service,
+{
+    let state = store.state.get();
+    if !PeerRequestsProtocolsGetInitAction::should_request(state, &protocol_hash) {
+        return;
+    }
+
+    let address = match state.peers.handshaked_iter().find(|(address, _)| {
+        PeerRequestsProtocolsGetInitAction::can_request_from_peer(state, *address, &protocol_hash)
+    }) {
+        Some((address, _)) => address,
+        None => {
+            slog::warn!(&state.log, "No peer to request unknown protocol from";
+                "protocol_hash" => protocol_hash.to_base58_check());
+            return;
+        }
+    };
+    store.dispatch(PeerRequestsProtocolsGetInitAction {
+        address,
+        protocol_hash,
+    });
+}
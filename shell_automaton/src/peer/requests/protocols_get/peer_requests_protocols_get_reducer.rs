@@ -0,0 +1,71 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::{Action, ActionWithMeta, State};
+
+use super::PeerRequestsProtocolsGetState;
+
+pub fn peer_requests_protocols_get_reducer(state: &mut State, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeerRequestsProtocolsGetInit(content) => {
+            let peer = match state.peers.get_handshaked_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            peer.requests.protocols_get = PeerRequestsProtocolsGetState::Init {
+                time: action.time_as_nanos(),
+                protocol_hash: content.protocol_hash.clone(),
+            };
+        }
+        Action::PeerRequestsProtocolsGetPending(content) => {
+            let peer = match state.peers.get_handshaked_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            let protocol_hash = match peer.requests.protocols_get.protocol_hash() {
+                Some(v) => v.clone(),
+                None => return,
+            };
+            peer.requests.protocols_get = PeerRequestsProtocolsGetState::Pending {
+                time: action.time_as_nanos(),
+                protocol_hash,
+            };
+        }
+        Action::PeerRequestsProtocolsGetError(content) => {
+            let peer = match state.peers.get_handshaked_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            let protocol_hash = match peer.requests.protocols_get.protocol_hash() {
+                Some(v) => v.clone(),
+                None => return,
+            };
+            peer.requests.protocols_get = PeerRequestsProtocolsGetState::Error {
+                time: action.time_as_nanos(),
+                protocol_hash,
+                error: content.error.clone(),
+            };
+        }
+        Action::PeerRequestsProtocolsGetSuccess(content) => {
+            let peer = match state.peers.get_handshaked_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            peer.requests.protocols_get = PeerRequestsProtocolsGetState::Success {
+                time: action.time_as_nanos(),
+                protocol_hash: content.protocol_hash.clone(),
+                result: content.result.clone(),
+            };
+        }
+        Action::PeerRequestsProtocolsGetFinish(content) => {
+            let peer = match state.peers.get_handshaked_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            peer.requests.protocols_get = PeerRequestsProtocolsGetState::Idle {
+                time: action.time_as_nanos(),
+            };
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+use crypto::hash::ProtocolHash;
+use tezos_messages::p2p::encoding::protocol::Protocol;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+pub enum PeerRequestsProtocolsGetError {
+    Timeout,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PeerRequestsProtocolsGetState {
+    Idle {
+        time: u64,
+    },
+    Init {
+        time: u64,
+        protocol_hash: ProtocolHash,
+    },
+    Pending {
+        time: u64,
+        protocol_hash: ProtocolHash,
+    },
+    Error {
+        time: u64,
+        protocol_hash: ProtocolHash,
+        error: PeerRequestsProtocolsGetError,
+    },
+    Success {
+        time: u64,
+        protocol_hash: ProtocolHash,
+        result: Protocol,
+    },
+}
+
+impl PeerRequestsProtocolsGetState {
+    pub fn time(&self) -> u64 {
+        match self {
+            Self::Idle { time } => *time,
+            Self::Init { time, .. } => *time,
+            Self::Pending { time, .. } => *time,
+            Self::Error { time, .. } => *time,
+            Self::Success { time, .. } => *time,
+        }
+    }
+
+    pub fn protocol_hash(&self) -> Option<&ProtocolHash> {
+        match self {
+            Self::Idle { .. } => None,
+            Self::Init { protocol_hash, .. } => Some(protocol_hash),
+            Self::Pending { protocol_hash, .. } => Some(protocol_hash),
+            Self::Error { protocol_hash, .. } => Some(protocol_hash),
+            Self::Success { protocol_hash, .. } => Some(protocol_hash),
+        }
+    }
+
+    pub fn is_init(&self) -> bool {
+        matches!(self, Self::Init { .. })
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending { .. })
+    }
+
+    pub fn is_pending_protocol_hash_eq(&self, other: &ProtocolHash) -> bool {
+        match self {
+            Self::Pending { protocol_hash, .. } => protocol_hash == other,
+            _ => false,
+        }
+    }
+
+    pub fn is_error_protocol_hash_eq(&self, other: &ProtocolHash) -> bool {
+        match self {
+            Self::Error { protocol_hash, .. } => protocol_hash == other,
+            _ => false,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success { .. })
+    }
+}
+
+impl Default for PeerRequestsProtocolsGetState {
+    fn default() -> Self {
+        Self::Idle { time: 0 }
+    }
+}
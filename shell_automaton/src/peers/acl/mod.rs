@@ -0,0 +1,14 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peers_acl_state;
+pub use peers_acl_state::*;
+
+mod peers_acl_actions;
+pub use peers_acl_actions::*;
+
+mod peers_acl_reducer;
+pub use peers_acl_reducer::*;
+
+mod peers_acl_effects;
+pub use peers_acl_effects::*;
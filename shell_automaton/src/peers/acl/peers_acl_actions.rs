@@ -0,0 +1,82 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::peer::PeerStatus;
+use crate::{EnablingCondition, State};
+
+use super::IpCidr;
+
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::SocketAddrMutator;
+
+/// Trust the peer listening on the `address`.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAclTrustedAddAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeersAclTrustedAddAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.peers.acl.is_trusted(&self.address)
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAclTrustedRemoveAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeersAclTrustedRemoveAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.peers.acl.is_trusted(&self.address)
+    }
+}
+
+/// Whether we are neither connected nor connecting to the peer listening on `address`.
+pub fn peers_acl_is_disconnected(state: &State, address: &SocketAddr) -> bool {
+    state
+        .peers
+        .get(address)
+        .map_or(true, |peer| matches!(peer.status, PeerStatus::Potential))
+}
+
+/// Connect to the trusted peers we aren't connected to.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAclTrustedReconnectAction {}
+
+impl EnablingCondition<State> for PeersAclTrustedReconnectAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        let acl = &state.peers.acl;
+        let interval = state.config.peers_trusted_reconnect_interval.as_nanos() as u64;
+        state.time_as_nanos() >= acl.trusted_reconnect_time + interval
+            && acl
+                .trusted
+                .iter()
+                .any(|address| peers_acl_is_disconnected(state, address))
+    }
+}
+
+/// Replace the allow/deny lists for incoming connections.
+///
+/// Already connected peers aren't affected.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAclIncomingSetAction {
+    pub allow: Vec<IpCidr>,
+    pub deny: Vec<IpCidr>,
+}
+
+impl EnablingCondition<State> for PeersAclIncomingSetAction {
+    fn is_enabled(&self, _: &State) -> bool {
+        true
+    }
+}
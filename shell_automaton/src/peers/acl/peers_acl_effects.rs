@@ -0,0 +1,46 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::connection::outgoing::PeerConnectionOutgoingInitAction;
+use crate::peer::PeerStatus;
+use crate::peers::graylist::PeersGraylistIpRemoveAction;
+use crate::{Action, ActionWithMeta, Service, Store};
+
+use super::PeersAclTrustedReconnectAction;
+
+pub fn peers_acl_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeersCheckTimeoutsInit(_) => {
+            store.dispatch(PeersAclTrustedReconnectAction {});
+        }
+        Action::PeersAclTrustedAdd(content) => {
+            let ip = content.address.ip();
+            if store.state().peers.is_blacklisted(&ip) {
+                store.dispatch(PeersGraylistIpRemoveAction { ip });
+            }
+            store.dispatch(PeersAclTrustedReconnectAction {});
+        }
+        Action::PeersAclTrustedReconnect(_) => {
+            let state = store.state.get();
+            let addresses = state
+                .peers
+                .acl
+                .trusted
+                .iter()
+                .filter(|address| {
+                    matches!(
+                        state.peers.get(address).map(|peer| &peer.status),
+                        Some(PeerStatus::Potential)
+                    )
+                })
+                .copied()
+                .collect::<Vec<_>>();
+
+            // connect directly, `peers_connected_max` doesn't apply to trusted peers.
+            for address in addresses {
+                store.dispatch(PeerConnectionOutgoingInitAction { address });
+            }
+        }
+        _ => {}
+    }
+}
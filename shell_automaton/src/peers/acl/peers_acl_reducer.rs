@@ -0,0 +1,40 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::{Peer, PeerIOLoopState, PeerStatus};
+use crate::{Action, ActionWithMeta, State};
+
+pub fn peers_acl_reducer(state: &mut State, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeersAclTrustedAdd(content) => {
+            state.peers.acl.trusted.insert(content.address);
+            // reconnect to the new trusted peer right away.
+            state.peers.acl.trusted_reconnect_time = 0;
+        }
+        Action::PeersAclTrustedRemove(content) => {
+            state.peers.acl.trusted.remove(&content.address);
+        }
+        Action::PeersAclTrustedReconnect(_) => {
+            let peers = &mut state.peers;
+            peers.acl.trusted_reconnect_time = action.time_as_nanos();
+
+            // trusted peers are added even if we have enough potential peers.
+            let trusted = peers.acl.trusted.iter().copied().collect::<Vec<_>>();
+            for address in trusted {
+                if let Ok(entry) = peers.entry(address) {
+                    entry.or_insert_with(|| Peer {
+                        status: PeerStatus::Potential,
+                        try_read_loop: PeerIOLoopState::Idle,
+                        try_write_loop: PeerIOLoopState::Idle,
+                        bandwidth: Default::default(),
+                    });
+                }
+            }
+        }
+        Action::PeersAclIncomingSet(content) => {
+            state.peers.acl.incoming_allow = content.allow.clone();
+            state.peers.acl.incoming_deny = content.deny.clone();
+        }
+        _ => {}
+    }
+}
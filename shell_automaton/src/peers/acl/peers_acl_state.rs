@@ -0,0 +1,200 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::peers::canonical_ip;
+use crate::Config;
+
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::IpAddrMutator;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("invalid CIDR notation `{0}`, expected `IP/PREFIX_LEN` or `IP`")]
+pub struct IpCidrParseError(String);
+
+/// Range of ip addresses in CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpCidr {
+    #[cfg_attr(feature = "fuzzing", field_mutator(IpAddrMutator))]
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Self> {
+        let max_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_len {
+            return None;
+        }
+        Some(Self { addr, prefix_len })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let (net, ip, bits) = match (&self.addr, &canonical_ip(*ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                (u32::from(*net) as u128, u32::from(*ip) as u128, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => (u128::from(*net), u128::from(*ip), 128),
+            _ => return false,
+        };
+        let shift = bits - self.prefix_len as u32;
+        shift >= bits || (net >> shift) == (ip >> shift)
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = IpCidrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || IpCidrParseError(s.to_owned());
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => {
+                let addr = addr.parse::<IpAddr>().map_err(|_| err())?;
+                (addr, prefix_len.parse::<u8>().map_err(|_| err())?)
+            }
+            None => {
+                let addr = s.parse::<IpAddr>().map_err(|_| err())?;
+                (addr, if addr.is_ipv4() { 32 } else { 128 })
+            }
+        };
+        Self::new(addr, prefix_len).ok_or_else(err)
+    }
+}
+
+impl TryFrom<String> for IpCidr {
+    type Error = IpCidrParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<IpCidr> for String {
+    fn from(cidr: IpCidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Connection policies of the peers.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PeersAclState {
+    /// Trusted peers are always reconnected, never graylisted
+    /// and exempt from `peers_connected_max`.
+    pub trusted: BTreeSet<SocketAddr>,
+
+    /// If not empty, incoming connections are accepted only from these ranges.
+    pub incoming_allow: Vec<IpCidr>,
+
+    /// Incoming connections from these ranges are rejected.
+    pub incoming_deny: Vec<IpCidr>,
+
+    /// Last time we tried to reconnect to the trusted peers.
+    pub trusted_reconnect_time: u64,
+}
+
+impl PeersAclState {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            trusted: config.peers_trusted.iter().copied().collect(),
+            incoming_allow: config.peers_incoming_allow.clone(),
+            incoming_deny: config.peers_incoming_deny.clone(),
+            trusted_reconnect_time: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_trusted(&self, address: &SocketAddr) -> bool {
+        self.trusted.contains(address)
+    }
+
+    /// Whether or not `ip` belongs to some trusted peer.
+    ///
+    /// Incoming connections of the trusted peers come from a different port
+    /// than the one they listen on, so for those only ip can be checked.
+    pub fn is_trusted_ip(&self, ip: &IpAddr) -> bool {
+        let ip = canonical_ip(*ip);
+        self.trusted
+            .iter()
+            .any(|address| canonical_ip(address.ip()) == ip)
+    }
+
+    /// Whether or not incoming connection from the `ip` is allowed.
+    pub fn is_incoming_allowed(&self, ip: &IpAddr) -> bool {
+        if self.is_trusted_ip(ip) {
+            return true;
+        }
+        if self.incoming_deny.iter().any(|cidr| cidr.contains(ip)) {
+            return false;
+        }
+        self.incoming_allow.is_empty() || self.incoming_allow.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidr(s: &str) -> IpCidr {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ip_cidr() {
+        assert!(cidr("10.0.0.0/8").contains(&ip("10.1.2.3")));
+        assert!(!cidr("10.0.0.0/8").contains(&ip("11.1.2.3")));
+        assert!(cidr("0.0.0.0/0").contains(&ip("1.2.3.4")));
+        assert!(cidr("1.2.3.4").contains(&ip("1.2.3.4")));
+        assert!(!cidr("1.2.3.4").contains(&ip("1.2.3.5")));
+        assert!(cidr("fd00::/8").contains(&ip("fd12::1")));
+        assert!(!cidr("fd00::/8").contains(&ip("fe80::1")));
+        assert!(!cidr("0.0.0.0/0").contains(&ip("::1")));
+        // IPv4-mapped address matches IPv4 range.
+        assert!(cidr("10.0.0.0/8").contains(&ip("::ffff:10.1.2.3")));
+
+        assert_eq!(cidr("10.0.0.0/8").to_string(), "10.0.0.0/8");
+        assert_eq!(cidr("::1").to_string(), "::1/128");
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("10.0.0/8".parse::<IpCidr>().is_err());
+    }
+
+    #[test]
+    fn test_incoming_allowed() {
+        let mut acl = PeersAclState::default();
+        assert!(acl.is_incoming_allowed(&ip("1.2.3.4")));
+
+        acl.incoming_deny = vec![cidr("1.2.0.0/16")];
+        assert!(!acl.is_incoming_allowed(&ip("1.2.3.4")));
+        assert!(acl.is_incoming_allowed(&ip("1.3.3.4")));
+
+        acl.incoming_allow = vec![cidr("10.0.0.0/8")];
+        assert!(!acl.is_incoming_allowed(&ip("1.3.3.4")));
+        assert!(acl.is_incoming_allowed(&ip("10.3.3.4")));
+
+        // trusted peers are always allowed.
+        acl.trusted.insert("1.2.3.4:9732".parse().unwrap());
+        assert!(acl.is_incoming_allowed(&ip("1.2.3.4")));
+    }
+}
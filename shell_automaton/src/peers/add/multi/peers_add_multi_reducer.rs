@@ -19,6 +19,7 @@ pub fn peers_add_multi_reducer(state: &mut State, action: &ActionWithMeta) {
                     status: PeerStatus::Potential,
                     try_read_loop: PeerIOLoopState::Idle,
                     try_write_loop: PeerIOLoopState::Idle,
+                    bandwidth: Default::default(),
                 });
             }
         }
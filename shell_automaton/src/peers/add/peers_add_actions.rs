@@ -22,11 +22,14 @@ pub struct PeersAddIncomingPeerAction {
 
 impl EnablingCondition<State> for PeersAddIncomingPeerAction {
     fn is_enabled(&self, state: &State) -> bool {
-        if state.peers.connected_len() >= state.config.peers_connected_max {
+        let ip = self.address.ip();
+        if !state.peers.acl.is_trusted_ip(&ip)
+            && state.peers.connected_len() >= state.config.peers_connected_max
+        {
             return false;
         }
 
-        if state.peers.is_blacklisted(&self.address.ip()) {
+        if state.peers.is_blacklisted(&ip) {
             return false;
         }
 
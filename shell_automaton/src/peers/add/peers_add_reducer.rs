@@ -22,6 +22,7 @@ pub fn peers_add_reducer(state: &mut State, action: &ActionWithMeta) {
                 ),
                 try_read_loop: PeerIOLoopState::Idle,
                 try_write_loop: PeerIOLoopState::Idle,
+                bandwidth: Default::default(),
             });
         }
     }
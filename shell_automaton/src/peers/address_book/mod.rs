@@ -0,0 +1,14 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peers_address_book_state;
+pub use peers_address_book_state::*;
+
+mod peers_address_book_actions;
+pub use peers_address_book_actions::*;
+
+mod peers_address_book_reducer;
+pub use peers_address_book_reducer::*;
+
+mod peers_address_book_effects;
+pub use peers_address_book_effects::*;
@@ -0,0 +1,70 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+use crate::request::RequestId;
+use crate::service::storage_service::{PeerAddressBookItem, StorageError};
+use crate::{EnablingCondition, State};
+
+use super::PeersAddressBookState;
+
+/// Load known peers from the persisted address book.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAddressBookLoadInitAction {}
+
+impl EnablingCondition<State> for PeersAddressBookLoadInitAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !matches!(
+            &state.peers.address_book,
+            PeersAddressBookState::LoadPending { .. }
+        )
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAddressBookLoadPendingAction {
+    pub storage_req_id: RequestId,
+}
+
+impl EnablingCondition<State> for PeersAddressBookLoadPendingAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !matches!(
+            &state.peers.address_book,
+            PeersAddressBookState::LoadPending { .. }
+        )
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAddressBookLoadErrorAction {
+    pub error: StorageError,
+}
+
+impl EnablingCondition<State> for PeersAddressBookLoadErrorAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        matches!(
+            &state.peers.address_book,
+            PeersAddressBookState::LoadPending { .. }
+        )
+    }
+}
+
+/// Known peers were loaded, best-known first.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersAddressBookLoadSuccessAction {
+    pub entries: Vec<PeerAddressBookItem>,
+}
+
+impl EnablingCondition<State> for PeersAddressBookLoadSuccessAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        matches!(
+            &state.peers.address_book,
+            PeersAddressBookState::LoadPending { .. }
+        )
+    }
+}
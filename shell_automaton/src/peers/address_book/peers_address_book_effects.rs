@@ -0,0 +1,153 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use storage::peer_address_book_storage::{PeerAddressBookUpdate, PEER_BAN_PERMANENT};
+
+use crate::peer::connection::outgoing::PeerConnectionOutgoingRandomInitAction;
+use crate::peer::connection::PeerConnectionState;
+use crate::peer::PeerStatus;
+use crate::peers::PeerBlacklistState;
+use crate::service::storage_service::{
+    StorageRequestPayload, StorageResponseError, StorageResponseSuccess,
+};
+use crate::service::Service;
+use crate::storage::request::{StorageRequestCreateAction, StorageRequestor};
+use crate::{Action, ActionWithMeta, State, Store};
+
+use super::{
+    PeersAddressBookLoadErrorAction, PeersAddressBookLoadPendingAction,
+    PeersAddressBookLoadSuccessAction, PeersAddressBookState,
+};
+
+pub fn peers_address_book_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeersAddressBookLoadInit(_) => {
+            let storage_req_id = store.state().storage.requests.next_req_id();
+            store.dispatch(StorageRequestCreateAction {
+                payload: StorageRequestPayload::PeerAddressBookLoad,
+                requestor: StorageRequestor::None,
+            });
+            store.dispatch(PeersAddressBookLoadPendingAction { storage_req_id });
+        }
+        Action::StorageResponseReceived(content) => {
+            let target_req_id = match &store.state().peers.address_book {
+                PeersAddressBookState::LoadPending { storage_req_id, .. } => *storage_req_id,
+                _ => return,
+            };
+            if content.response.req_id != Some(target_req_id) {
+                return;
+            }
+
+            match &content.response.result {
+                Ok(StorageResponseSuccess::PeerAddressBookLoadSuccess(entries)) => {
+                    store.dispatch(PeersAddressBookLoadSuccessAction {
+                        entries: entries.clone(),
+                    });
+                }
+                Err(StorageResponseError::PeerAddressBookLoadError(error)) => {
+                    store.dispatch(PeersAddressBookLoadErrorAction {
+                        error: error.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Action::PeersAddressBookLoadSuccess(_) => {
+            // Try connecting to the best-known peers.
+            store.dispatch(PeerConnectionOutgoingRandomInitAction {});
+        }
+        Action::PeersAddressBookLoadError(content) => {
+            slog::warn!(&store.state().log, "Failed to load peer address book";
+                "error" => content.error.to_string());
+        }
+        Action::PeerHandshakingFinish(content) => {
+            let address = content.address;
+            if let Some(peer) = store.state().peers.get_handshaked(&address) {
+                let point = SocketAddr::new(address.ip(), peer.port);
+                address_book_entry_update(store, point, true);
+            }
+        }
+        Action::PeerDisconnected(content) => {
+            if let Some(port) = content.listen_port {
+                let point = SocketAddr::new(content.address.ip(), port);
+                address_book_entry_update(store, point, false);
+            }
+        }
+        Action::PeersGraylistAddressBan(content) => {
+            if let Some(point) = address_book_point(store.state(), content.address) {
+                address_book_entry_update(store, point, false);
+            }
+        }
+        Action::PeersGraylistAddressUnban(content) => {
+            if let Some(point) = address_book_point(store.state(), content.address) {
+                address_book_entry_update(store, point, false);
+            }
+        }
+        Action::PeersScoreUpdate(content) => {
+            // persist the ban as soon as the score drops enough to graylist/ban the peer.
+            if store.state().peers.is_blacklisted(&content.address.ip()) {
+                if let Some(point) = address_book_point(store.state(), content.address) {
+                    address_book_entry_update(store, point, false);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Address the peer listens on, its address book entry is stored under it.
+///
+/// Connected peers are known by the address of the connection, which has an ephemeral
+/// port for the incoming connections: their listening port is only known once handshaked.
+/// Addresses without a connection are points.
+fn address_book_point(state: &State, address: SocketAddr) -> Option<SocketAddr> {
+    let peer = match state.peers.get(&address) {
+        Some(peer) => peer,
+        None => return Some(address),
+    };
+    match &peer.status {
+        PeerStatus::Potential => Some(address),
+        PeerStatus::Connecting(PeerConnectionState::Outgoing(_)) => Some(address),
+        PeerStatus::Connecting(PeerConnectionState::Incoming(_)) => None,
+        PeerStatus::Handshaking(handshaking) if handshaking.incoming => None,
+        PeerStatus::Handshaking(_) => Some(address),
+        PeerStatus::Handshaked(handshaked) => Some(SocketAddr::new(address.ip(), handshaked.port)),
+        PeerStatus::Disconnecting(disconnecting) => disconnecting
+            .listen_port
+            .map(|port| SocketAddr::new(address.ip(), port)),
+        PeerStatus::Disconnected => None,
+    }
+}
+
+fn address_book_entry_update<S: Service>(
+    store: &mut Store<S>,
+    point: SocketAddr,
+    handshaked: bool,
+) {
+    let state = store.state();
+    let time = state.time_as_nanos();
+    let ip = point.ip();
+    let graylist_timeout = state.config.peers_graylist_timeout;
+    let ban_expiry = match state.peers.get_blacklisted_ip(&ip) {
+        Some(PeerBlacklistState::Banned { .. }) => Some(PEER_BAN_PERMANENT),
+        Some(blacklist_state) => blacklist_state.timeout(graylist_timeout),
+        None => None,
+    };
+    let update = PeerAddressBookUpdate {
+        time,
+        score: state.peers.score(
+            &ip,
+            time,
+            state.config.peers_score_thresholds.recovery_interval,
+        ),
+        ban_expiry,
+        handshaked,
+    };
+
+    store.dispatch(StorageRequestCreateAction {
+        payload: StorageRequestPayload::PeerAddressBookEntryUpdate(point, update),
+        requestor: StorageRequestor::None,
+    });
+}
@@ -0,0 +1,86 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::collections::btree_map::Entry as BTreeMapEntry;
+
+use storage::peer_address_book_storage::PEER_BAN_PERMANENT;
+
+use crate::peer::{Peer, PeerIOLoopState, PeerStatus};
+use crate::peers::score::PeerScore;
+use crate::peers::PeerBlacklistState;
+use crate::service::storage_service::PeerAddressBookItem;
+use crate::{Action, ActionWithMeta, State};
+
+use super::PeersAddressBookState;
+
+pub fn peers_address_book_reducer(state: &mut State, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeersAddressBookLoadPending(content) => {
+            state.peers.address_book = PeersAddressBookState::LoadPending {
+                time: action.time_as_nanos(),
+                storage_req_id: content.storage_req_id,
+            };
+        }
+        Action::PeersAddressBookLoadError(content) => {
+            state.peers.address_book = PeersAddressBookState::LoadError {
+                time: action.time_as_nanos(),
+                error: content.error.clone(),
+            };
+        }
+        Action::PeersAddressBookLoadSuccess(content) => {
+            let time = action.time_as_nanos();
+            let graylist_timeout = state.config.peers_graylist_timeout.as_nanos() as u64;
+            let restore_bans = !state.config.peers_graylist_disable;
+            let mut max_len = state
+                .config
+                .peers_potential_max
+                .saturating_sub(state.peers.potential_len());
+            let mut best_known = Vec::new();
+
+            for PeerAddressBookItem { address, entry } in content.entries.iter() {
+                let ip = address.ip();
+                // score recovers for the time node wasn't running too.
+                state
+                    .peers
+                    .restore_score(ip, PeerScore::new(entry.score, entry.last_seen));
+
+                if entry.is_banned(time) {
+                    // trusted peers are never graylisted.
+                    if restore_bans && !state.peers.acl.is_trusted_ip(&ip) {
+                        let blacklist_state = match entry.ban_expiry {
+                            Some(PEER_BAN_PERMANENT) => PeerBlacklistState::Banned {
+                                since: entry.last_seen,
+                            },
+                            Some(expiry) => PeerBlacklistState::Graylisted {
+                                since: expiry.saturating_sub(graylist_timeout),
+                            },
+                            None => continue,
+                        };
+                        state
+                            .peers
+                            .ip_blacklist_entry(ip)
+                            .or_insert(blacklist_state);
+                    }
+                    continue;
+                }
+
+                if max_len == 0 {
+                    continue;
+                }
+                if let Ok(BTreeMapEntry::Vacant(peer_entry)) = state.peers.entry(*address) {
+                    peer_entry.insert(Peer {
+                        status: PeerStatus::Potential,
+                        try_read_loop: PeerIOLoopState::Idle,
+                        try_write_loop: PeerIOLoopState::Idle,
+                        bandwidth: Default::default(),
+                    });
+                    best_known.push(*address);
+                    max_len -= 1;
+                }
+            }
+
+            state.peers.address_book = PeersAddressBookState::LoadSuccess { time, best_known };
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::request::RequestId;
+use crate::service::storage_service::StorageError;
+
+/// State of the persisted address book of peers.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PeersAddressBookState {
+    Idle,
+    LoadPending {
+        time: u64,
+        storage_req_id: RequestId,
+    },
+    LoadSuccess {
+        time: u64,
+        /// Peers loaded from the address book, best-known first.
+        best_known: Vec<SocketAddr>,
+    },
+    LoadError {
+        time: u64,
+        error: StorageError,
+    },
+}
+
+impl PeersAddressBookState {
+    pub fn best_known(&self) -> &[SocketAddr] {
+        match self {
+            Self::LoadSuccess { best_known, .. } => best_known,
+            _ => &[],
+        }
+    }
+}
+
+impl Default for PeersAddressBookState {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
@@ -0,0 +1,11 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peers_bandwidth_state;
+pub use peers_bandwidth_state::*;
+
+mod peers_bandwidth_reducer;
+pub use peers_bandwidth_reducer::*;
+
+mod peers_bandwidth_effects;
+pub use peers_bandwidth_effects::*;
@@ -0,0 +1,43 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::{PeerTryReadLoopStartAction, PeerTryWriteLoopStartAction};
+use crate::{Action, ActionWithMeta, Service, Store};
+
+use super::{peers_bandwidth_download_allowance, peers_bandwidth_upload_allowance};
+
+pub fn peers_bandwidth_effects<S>(store: &mut Store<S>, action: &ActionWithMeta)
+where
+    S: Service,
+{
+    if let Action::MioWaitForEvents(_) = &action.action {
+        // Resume io loops which were stopped because of the bandwidth limit,
+        // once there is some bandwidth available again.
+        let state = store.state.get();
+        let resume_read = state
+            .peers
+            .iter()
+            .filter(|(_, peer)| peer.try_read_loop.is_byte_quota_reached())
+            .filter(|(_, peer)| {
+                peers_bandwidth_download_allowance(state, peer).map_or(true, |v| v > 0)
+            })
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+        let resume_write = state
+            .peers
+            .iter()
+            .filter(|(_, peer)| peer.try_write_loop.is_byte_quota_reached())
+            .filter(|(_, peer)| {
+                peers_bandwidth_upload_allowance(state, peer).map_or(true, |v| v > 0)
+            })
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+
+        for address in resume_read {
+            store.dispatch(PeerTryReadLoopStartAction { address });
+        }
+        for address in resume_write {
+            store.dispatch(PeerTryWriteLoopStartAction { address });
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::{Action, ActionWithMeta, State};
+
+pub fn peers_bandwidth_reducer(state: &mut State, action: &ActionWithMeta) {
+    let time = action.time_as_nanos();
+    let limit = &state.config.bandwidth_limit;
+
+    match &action.action {
+        Action::PeerChunkReadPart(content) => {
+            let bytes = content.bytes.len() as u64;
+            let peer = match state.peers.get_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            peer.bandwidth.total_recv = peer.bandwidth.total_recv.saturating_add(bytes);
+            if let Some(limit) = limit.peer_download {
+                peer.bandwidth.download.consume(limit, time, bytes);
+            }
+
+            let bandwidth = &mut state.peers.bandwidth;
+            bandwidth.record_recv(bytes);
+            if let Some(limit) = limit.download {
+                bandwidth.download.consume(limit, time, bytes);
+            }
+        }
+        Action::PeerChunkWritePart(content) => {
+            let bytes = content.written as u64;
+            let peer = match state.peers.get_mut(&content.address) {
+                Some(v) => v,
+                None => return,
+            };
+            peer.bandwidth.total_sent = peer.bandwidth.total_sent.saturating_add(bytes);
+            if let Some(limit) = limit.peer_upload {
+                peer.bandwidth.upload.consume(limit, time, bytes);
+            }
+
+            let bandwidth = &mut state.peers.bandwidth;
+            bandwidth.record_sent(bytes);
+            if let Some(limit) = limit.upload {
+                bandwidth.upload.consume(limit, time, bytes);
+            }
+        }
+        Action::MioWaitForEvents(_) => {
+            state.peers.bandwidth.update_flow(time);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::peer::Peer;
+use crate::State;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// How long to wait for events when some peer's io loop is throttled,
+/// so that the loop is resumed soon after the bandwidth is available again.
+pub const PEERS_BANDWIDTH_THROTTLED_WAIT: Duration = Duration::from_millis(10);
+
+/// Token bucket limiting the transfer rate.
+///
+/// Bucket is refilled with `limit` bytes per second and holds at most
+/// one second worth of bytes, so after being idle, transfer can burst
+/// up to `limit` bytes. New bucket is full.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BandwidthBucket {
+    available: u64,
+    updated_at: u64,
+}
+
+impl BandwidthBucket {
+    /// Bytes added to the bucket since `updated_at`, not capped by `limit`.
+    fn refill(&self, limit: u64, time: u64) -> u64 {
+        let elapsed = time.saturating_sub(self.updated_at) as u128;
+        (elapsed * limit as u128 / NANOS_PER_SEC as u128).min(u64::MAX as u128) as u64
+    }
+
+    /// Bytes that can be transferred at `time`.
+    pub fn available(&self, limit: u64, time: u64) -> u64 {
+        self.available
+            .saturating_add(self.refill(limit, time))
+            .min(limit)
+    }
+
+    pub fn consume(&mut self, limit: u64, time: u64, bytes: u64) {
+        let refill = self.refill(limit, time);
+        let available = self.available.saturating_add(refill);
+
+        if available >= limit {
+            self.available = limit;
+            self.updated_at = time;
+        } else if refill > 0 {
+            // only move by the time the refilled bytes correspond to,
+            // so that the remainder isn't lost with frequent small transfers.
+            self.available = available;
+            self.updated_at += (refill as u128 * NANOS_PER_SEC as u128 / limit as u128) as u64;
+        }
+        self.available = self.available.saturating_sub(bytes);
+    }
+}
+
+/// Bandwidth used by the single peer.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PeerBandwidth {
+    pub download: BandwidthBucket,
+    pub upload: BandwidthBucket,
+
+    /// Total bytes received from the peer.
+    pub total_recv: u64,
+    /// Total bytes sent to the peer.
+    pub total_sent: u64,
+}
+
+/// Bandwidth used by all peers together.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PeersBandwidthState {
+    pub download: BandwidthBucket,
+    pub upload: BandwidthBucket,
+
+    pub total_recv: u64,
+    pub total_sent: u64,
+
+    /// Bytes received per second, measured over the last finished second.
+    pub current_inflow: u64,
+    /// Bytes sent per second, measured over the last finished second.
+    pub current_outflow: u64,
+
+    window_start: u64,
+    window_recv: u64,
+    window_sent: u64,
+}
+
+impl PeersBandwidthState {
+    pub fn record_recv(&mut self, bytes: u64) {
+        self.total_recv = self.total_recv.saturating_add(bytes);
+        self.window_recv = self.window_recv.saturating_add(bytes);
+    }
+
+    pub fn record_sent(&mut self, bytes: u64) {
+        self.total_sent = self.total_sent.saturating_add(bytes);
+        self.window_sent = self.window_sent.saturating_add(bytes);
+    }
+
+    /// Updates the current inflow/outflow once the measured second is over.
+    pub fn update_flow(&mut self, time: u64) {
+        let elapsed = time.saturating_sub(self.window_start);
+        if elapsed < NANOS_PER_SEC {
+            return;
+        }
+        let per_sec = |bytes: u64| (bytes as u128 * NANOS_PER_SEC as u128 / elapsed as u128) as u64;
+        self.current_inflow = per_sec(self.window_recv);
+        self.current_outflow = per_sec(self.window_sent);
+        self.window_start = time;
+        self.window_recv = 0;
+        self.window_sent = 0;
+    }
+}
+
+fn allowance(
+    global: (&BandwidthBucket, Option<u64>),
+    peer: (&BandwidthBucket, Option<u64>),
+    time: u64,
+) -> Option<u64> {
+    let global = global.1.map(|limit| global.0.available(limit, time));
+    let peer = peer.1.map(|limit| peer.0.available(limit, time));
+    match (global, peer) {
+        (Some(global), Some(peer)) => Some(global.min(peer)),
+        (global, peer) => global.or(peer),
+    }
+}
+
+/// How many bytes can be read from the peer now, `None` if download isn't limited.
+pub fn peers_bandwidth_download_allowance(state: &State, peer: &Peer) -> Option<u64> {
+    let limit = &state.config.bandwidth_limit;
+    allowance(
+        (&state.peers.bandwidth.download, limit.download),
+        (&peer.bandwidth.download, limit.peer_download),
+        state.time_as_nanos(),
+    )
+}
+
+/// How many bytes can be written to the peer now, `None` if upload isn't limited.
+pub fn peers_bandwidth_upload_allowance(state: &State, peer: &Peer) -> Option<u64> {
+    let limit = &state.config.bandwidth_limit;
+    allowance(
+        (&state.peers.bandwidth.upload, limit.upload),
+        (&peer.bandwidth.upload, limit.peer_upload),
+        state.time_as_nanos(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = NANOS_PER_SEC;
+
+    #[test]
+    fn test_bandwidth_bucket() {
+        let limit = 1000;
+        let mut bucket = BandwidthBucket::default();
+
+        // new bucket is full, but never over the limit.
+        assert_eq!(bucket.available(limit, 10 * SECOND), limit);
+
+        bucket.consume(limit, 10 * SECOND, 1000);
+        assert_eq!(bucket.available(limit, 10 * SECOND), 0);
+        assert_eq!(bucket.available(limit, 10 * SECOND + SECOND / 2), 500);
+        assert_eq!(bucket.available(limit, 20 * SECOND), limit);
+
+        // small frequent transfers don't lose the refill to rounding.
+        let mut time = 10 * SECOND;
+        for _ in 0..1000 {
+            time += SECOND / 1000 + SECOND / 2000;
+            bucket.consume(limit, time, 1);
+        }
+        assert_eq!(bucket.available(limit, time), 500);
+    }
+
+    #[test]
+    fn test_bandwidth_flow() {
+        let mut state = PeersBandwidthState::default();
+
+        state.update_flow(SECOND);
+        state.record_recv(3000);
+        state.record_sent(1000);
+        state.update_flow(SECOND + SECOND / 2);
+        assert_eq!(state.current_inflow, 0);
+
+        state.update_flow(3 * SECOND);
+        assert_eq!(state.current_inflow, 1500);
+        assert_eq!(state.current_outflow, 500);
+        assert_eq!(state.total_recv, 3000);
+        assert_eq!(state.total_sent, 1000);
+    }
+}
@@ -16,8 +16,12 @@ use crate::peer::requests::potential_peers_get::{
     PeerRequestsPotentialPeersGetError, PeerRequestsPotentialPeersGetErrorAction,
     PEER_POTENTIAL_PEERS_GET_TIMEOUT,
 };
+use crate::peer::requests::protocols_get::{
+    PeerRequestsProtocolsGetError, PeerRequestsProtocolsGetErrorAction, PEER_PROTOCOLS_GET_TIMEOUT,
+};
 use crate::peer::{Peer, PeerStatus};
 use crate::peers::graylist::PeersGraylistIpRemoveAction;
+use crate::peers::score::{PeerScoreEvent, PeersScoreUpdateAction};
 use crate::{Action, ActionWithMeta, Service, Store};
 
 use super::{
@@ -65,6 +69,12 @@ fn check_timeout(
                 Some(PeerTimeout::RequestsPotentialPeersGet)
                     .filter(|_| peer.requests.potential_peers_get.is_pending())
                     .filter(|_| current_time >= time + PEER_POTENTIAL_PEERS_GET_TIMEOUT)
+            })
+            .or_else(|| {
+                let time = peer.requests.protocols_get.time();
+                Some(PeerTimeout::RequestsProtocolsGet)
+                    .filter(|_| peer.requests.protocols_get.is_pending())
+                    .filter(|_| current_time >= time + PEER_PROTOCOLS_GET_TIMEOUT)
             }),
         PeerStatus::Disconnecting(_) => None,
         PeerStatus::Disconnected => None,
@@ -158,6 +168,10 @@ where
                                 });
                             }
                             PeerTimeout::CurrentHeadUpdate => {
+                                store.dispatch(PeersScoreUpdateAction {
+                                    address,
+                                    event: PeerScoreEvent::Timeout,
+                                });
                                 store.dispatch(PeerDisconnectAction { address });
                             }
                             PeerTimeout::RequestsPotentialPeersGet => {
@@ -166,6 +180,12 @@ where
                                     error: PeerRequestsPotentialPeersGetError::Timeout,
                                 });
                             }
+                            PeerTimeout::RequestsProtocolsGet => {
+                                store.dispatch(PeerRequestsProtocolsGetErrorAction {
+                                    address,
+                                    error: PeerRequestsProtocolsGetError::Timeout,
+                                });
+                            }
                         }
                     }
 
@@ -28,6 +28,7 @@ pub enum PeerTimeout {
     Handshaking(PeerHandshakingPhase),
     CurrentHeadUpdate,
     RequestsPotentialPeersGet,
+    RequestsProtocolsGet,
 }
 
 #[cfg(feature = "fuzzing")]
@@ -0,0 +1,11 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peers_connect_actions;
+pub use peers_connect_actions::*;
+
+mod peers_connect_reducer;
+pub use peers_connect_reducer::*;
+
+mod peers_connect_effects;
+pub use peers_connect_effects::*;
@@ -0,0 +1,34 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::peer::PeerStatus;
+use crate::{EnablingCondition, State};
+
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::SocketAddrMutator;
+
+/// Operator's request to connect to the peer listening on the `address`.
+///
+/// Peer is connected to even if we have enough potential or connected peers.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersConnectPointAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeersConnectPointAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        if state.peers.is_blacklisted(&self.address.ip()) {
+            return false;
+        }
+        match state.peers.get(&self.address) {
+            Some(peer) => matches!(peer.status, PeerStatus::Potential),
+            None => true,
+        }
+    }
+}
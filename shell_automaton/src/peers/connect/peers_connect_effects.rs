@@ -0,0 +1,13 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::connection::outgoing::PeerConnectionOutgoingInitAction;
+use crate::{Action, ActionWithMeta, Service, Store};
+
+pub fn peers_connect_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
+    if let Action::PeersConnectPoint(content) = &action.action {
+        store.dispatch(PeerConnectionOutgoingInitAction {
+            address: content.address,
+        });
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::{Peer, PeerIOLoopState, PeerStatus};
+use crate::{Action, ActionWithMeta, State};
+
+pub fn peers_connect_reducer(state: &mut State, action: &ActionWithMeta) {
+    if let Action::PeersConnectPoint(content) = &action.action {
+        if let Ok(entry) = state.peers.entry(content.address) {
+            entry.or_insert_with(|| Peer {
+                status: PeerStatus::Potential,
+                try_read_loop: PeerIOLoopState::Idle,
+                try_write_loop: PeerIOLoopState::Idle,
+                bandwidth: Default::default(),
+            });
+        }
+    }
+}
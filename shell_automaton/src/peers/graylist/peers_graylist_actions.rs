@@ -5,9 +5,11 @@ use std::net::{IpAddr, SocketAddr};
 
 use serde::{Deserialize, Serialize};
 
+use crypto::hash::CryptoboxPublicKeyHash;
 use tezos_messages::p2p::encoding::ack::NackMotive;
 
 use crate::peer::message::write::PeerMessageWriteError;
+use crate::peers::PeerBlacklistState;
 use crate::{EnablingCondition, State};
 
 #[cfg(feature = "fuzzing")]
@@ -65,8 +67,9 @@ pub struct PeersGraylistIpAddAction {
 }
 
 impl EnablingCondition<State> for PeersGraylistIpAddAction {
-    fn is_enabled(&self, _: &State) -> bool {
-        true
+    fn is_enabled(&self, state: &State) -> bool {
+        // trusted peers are never graylisted.
+        !state.peers.acl.is_trusted_ip(&self.ip)
     }
 }
 
@@ -83,6 +86,24 @@ impl EnablingCondition<State> for PeersGraylistIpAddedAction {
     }
 }
 
+/// Permanently ban the ip, it won't be removed after `peers_graylist_timeout`.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersGraylistIpBanAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(IpAddrMutator))]
+    pub ip: IpAddr,
+}
+
+impl EnablingCondition<State> for PeersGraylistIpBanAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.peers.acl.is_trusted_ip(&self.ip)
+            && !matches!(
+                state.peers.get_blacklisted_ip(&self.ip),
+                Some(PeerBlacklistState::Banned { .. })
+            )
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PeersGraylistIpRemoveAction {
@@ -108,3 +129,52 @@ impl EnablingCondition<State> for PeersGraylistIpRemovedAction {
         true
     }
 }
+
+/// Operator's request to permanently ban the peer listening on the `address`.
+///
+/// `peer_id` of the banned peer is remembered, so that it can be unbanned
+/// by its peer id too.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersGraylistAddressBanAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    pub peer_id: Option<CryptoboxPublicKeyHash>,
+}
+
+impl EnablingCondition<State> for PeersGraylistAddressBanAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.peers.acl.is_trusted_ip(&self.address.ip())
+    }
+}
+
+/// Operator's request to unban the peer listening on the `address`.
+///
+/// Ip is removed from the graylist and its score is reset, so the peer
+/// isn't graylisted again right away.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersGraylistAddressUnbanAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeersGraylistAddressUnbanAction {
+    fn is_enabled(&self, _: &State) -> bool {
+        true
+    }
+}
+
+/// Remove all graylisted ips, permanently banned ips stay banned.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersGraylistClearAction {}
+
+impl EnablingCondition<State> for PeersGraylistClearAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .peers
+            .blacklist_ip_iter()
+            .any(|(_, blacklisted)| matches!(blacklisted, PeerBlacklistState::Graylisted { .. }))
+    }
+}
@@ -4,27 +4,34 @@
 use crate::peer::disconnection::PeerDisconnectAction;
 use crate::peer::PeerStatus;
 use crate::peers::remove::PeersRemoveAction;
+use crate::peers::score::{PeerScoreEvent, PeersScoreUpdateAction};
+use crate::peers::PeerBlacklistState;
 use crate::{Action, ActionWithMeta, Service, Store};
 
-use super::{PeersGraylistIpAddAction, PeersGraylistIpAddedAction, PeersGraylistIpRemovedAction};
+use super::{
+    PeersGraylistIpAddedAction, PeersGraylistIpBanAction, PeersGraylistIpRemoveAction,
+    PeersGraylistIpRemovedAction,
+};
 
 pub fn peers_graylist_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
     match &action.action {
         Action::PeersGraylistAddress(action) => {
-            if store.state.get().config.peers_graylist_disable {
-                store.dispatch(PeerDisconnectAction {
-                    address: action.address,
-                });
-                return;
-            }
-
-            store.dispatch(PeersGraylistIpAddAction {
-                ip: action.address.ip(),
+            // whether ip gets graylisted depends on the resulting score of the peer.
+            store.dispatch(PeersScoreUpdateAction {
+                address: action.address,
+                event: PeerScoreEvent::Misbehaved(action.reason.clone()),
+            });
+            store.dispatch(PeerDisconnectAction {
+                address: action.address,
             });
         }
         Action::PeersGraylistIpAdd(action) => {
             store.dispatch(PeersGraylistIpAddedAction { ip: action.ip });
         }
+        Action::PeersGraylistIpBan(action) => {
+            slog::warn!(&store.state().log, "Peer ip banned"; "ip" => action.ip.to_string());
+            store.dispatch(PeersGraylistIpAddedAction { ip: action.ip });
+        }
         Action::PeersGraylistIpAdded(action) => {
             let peers = &store.state.get().peers;
             // find all peers with same ip and disconnect/remove them.
@@ -56,6 +63,30 @@ pub fn peers_graylist_effects<S: Service>(store: &mut Store<S>, action: &ActionW
         Action::PeersGraylistIpRemove(action) => {
             store.dispatch(PeersGraylistIpRemovedAction { ip: action.ip });
         }
+        Action::PeersGraylistAddressBan(action) => {
+            store.dispatch(PeersGraylistIpBanAction {
+                ip: action.address.ip(),
+            });
+        }
+        Action::PeersGraylistAddressUnban(action) => {
+            store.dispatch(PeersGraylistIpRemoveAction {
+                ip: action.address.ip(),
+            });
+        }
+        Action::PeersGraylistClear(_) => {
+            let graylisted = store
+                .state()
+                .peers
+                .blacklist_ip_iter()
+                .filter(|(_, blacklisted)| {
+                    matches!(blacklisted, PeerBlacklistState::Graylisted { .. })
+                })
+                .map(|(ip, _)| *ip)
+                .collect::<Vec<_>>();
+            for ip in graylisted {
+                store.dispatch(PeersGraylistIpRemoveAction { ip });
+            }
+        }
         _ => {}
     }
 }
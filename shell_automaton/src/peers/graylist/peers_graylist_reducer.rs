@@ -1,6 +1,7 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
+use crate::peers::score::PeerScore;
 use crate::peers::PeerBlacklistState;
 use crate::{Action, ActionWithMeta, State};
 
@@ -13,9 +14,35 @@ pub fn peers_graylist_reducer(state: &mut State, action: &ActionWithMeta) {
                 },
             );
         }
+        Action::PeersGraylistIpBan(action_content) => {
+            let banned = PeerBlacklistState::Banned {
+                since: action.time_as_nanos(),
+            };
+            state
+                .peers
+                .ip_blacklist_entry(action_content.ip)
+                .and_modify(|blacklisted| *blacklisted = banned.clone())
+                .or_insert(banned);
+        }
         Action::PeersGraylistIpRemove(action_content) => {
             state.peers.remove_blacklisted_ip(&action_content.ip);
         }
+        Action::PeersGraylistAddressBan(action_content) => {
+            if let Some(peer_id) = &action_content.peer_id {
+                state
+                    .peers
+                    .banned_peer_ids
+                    .insert(peer_id.clone(), action_content.address);
+            }
+        }
+        Action::PeersGraylistAddressUnban(action_content) => {
+            let ip = action_content.address.ip();
+            state
+                .peers
+                .banned_peer_ids
+                .retain(|_, address| address.ip() != ip);
+            *state.peers.score_entry(ip) = PeerScore::default();
+        }
         _ => {}
     }
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{EnablingCondition, State};
 
-/// Do dns lookups to find and connect to initial peers.
+/// Load the address book and do dns lookups to find and connect to initial peers.
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PeersInitAction {}
@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: MIT
 
 use crate::peer::connection::outgoing::PeerConnectionOutgoingRandomInitAction;
+use crate::peers::address_book::PeersAddressBookLoadInitAction;
 use crate::peers::dns_lookup::PeersDnsLookupInitAction;
 use crate::service::Service;
 use crate::{Action, ActionWithMeta, Store};
 
 pub fn peers_init_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
     if let Action::PeersInit(_) = &action.action {
+        // Seed potential peers with the best-known peers from the previous runs.
+        store.dispatch(PeersAddressBookLoadInitAction {});
+
         let list = store.state().config.peers_dns_lookup_addresses.clone();
 
         // Do dns lookups to gather some potential peers.
@@ -1,14 +1,23 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
+pub mod acl;
+pub mod address_book;
+pub mod bandwidth;
+pub mod connect;
 pub mod dns_lookup;
 pub mod graylist;
 pub mod init;
+pub mod score;
+pub mod swap;
 
 pub mod add;
 pub mod remove;
 
 pub mod check;
 
+mod peers_address;
+pub use peers_address::*;
+
 mod peers_state;
 pub use peers_state::*;
@@ -0,0 +1,77 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Converts IPv4-mapped IPv6 address (`::ffff:a.b.c.d`) to IPv4 address.
+///
+/// Same peer can be seen with both forms, e.g. connection accepted by
+/// dual stack IPv6 listener has IPv4-mapped address, so peers, their
+/// scores and blacklist are always keyed by the canonical form.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => match ipv6.octets() {
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, a, b, c, d] => {
+                IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+            }
+            _ => ip,
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Address with the ip converted by [`canonical_ip`].
+pub fn canonical_address(address: SocketAddr) -> SocketAddr {
+    SocketAddr::new(canonical_ip(address.ip()), address.port())
+}
+
+/// Parses point (`1.2.3.4:9732` or `[::1]:9732`) received from the peer.
+pub fn parse_point(point: &str) -> Option<SocketAddr> {
+    point.parse().ok().map(canonical_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_canonical_address() {
+        assert_eq!(
+            canonical_address(address("[::ffff:1.2.3.4]:9732")),
+            address("1.2.3.4:9732")
+        );
+        assert_eq!(
+            canonical_address(address("1.2.3.4:9732")),
+            address("1.2.3.4:9732")
+        );
+        assert_eq!(
+            canonical_address(address("[::1]:9732")),
+            address("[::1]:9732")
+        );
+        assert_eq!(
+            canonical_address(address("[2001:db8::1]:9732")),
+            address("[2001:db8::1]:9732")
+        );
+    }
+
+    #[test]
+    fn test_parse_point() {
+        assert_eq!(parse_point("1.2.3.4:9732"), Some(address("1.2.3.4:9732")));
+        assert_eq!(parse_point("[::1]:9732"), Some(address("[::1]:9732")));
+        assert_eq!(
+            parse_point("[2001:db8::1]:9732"),
+            Some(address("[2001:db8::1]:9732"))
+        );
+        assert_eq!(
+            parse_point("[::ffff:1.2.3.4]:9732"),
+            Some(address("1.2.3.4:9732"))
+        );
+        assert_eq!(parse_point("1.2.3.4"), None);
+        assert_eq!(parse_point("::1:9732"), None);
+        assert_eq!(parse_point("localhost:9732"), None);
+    }
+}
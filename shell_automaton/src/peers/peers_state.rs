@@ -7,24 +7,33 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use crypto::hash::BlockHash;
+use crypto::hash::{BlockHash, CryptoboxPublicKeyHash};
 
 use crate::peer::{Peer, PeerHandshaked, PeerStatus};
 
+use super::acl::PeersAclState;
+use super::address_book::PeersAddressBookState;
+use super::bandwidth::PeersBandwidthState;
 use super::check::timeouts::PeersCheckTimeoutsState;
 use super::dns_lookup::PeersDnsLookupState;
+use super::score::PeerScore;
+use super::swap::PeersSwapState;
+use super::{canonical_address, canonical_ip};
 
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum PeerBlacklistState {
     /// Peer is temporarily graylisted.
     Graylisted { since: u64 },
+    /// Peer is permanently banned.
+    Banned { since: u64 },
 }
 
 impl PeerBlacklistState {
     pub fn timeout(&self, graylist_duration: Duration) -> Option<u64> {
         match self {
             Self::Graylisted { since } => Some(*since + graylist_duration.as_nanos() as u64),
+            Self::Banned { .. } => None,
         }
     }
 }
@@ -33,9 +42,21 @@ impl PeerBlacklistState {
 pub struct PeersState {
     pub list: BTreeMap<SocketAddr, Peer>,
     ip_blacklist: BTreeMap<IpAddr, PeerBlacklistState>,
+    /// Scores of the peers, by ip, same as the blacklist.
+    scores: BTreeMap<IpAddr, PeerScore>,
+    /// Addresses of the peers banned by the operator, by their peer id.
+    pub banned_peer_ids: BTreeMap<CryptoboxPublicKeyHash, SocketAddr>,
 
     pub dns_lookup: Option<PeersDnsLookupState>,
 
+    pub address_book: PeersAddressBookState,
+
+    pub swap: PeersSwapState,
+
+    pub bandwidth: PeersBandwidthState,
+
+    pub acl: PeersAclState,
+
     pub check_timeouts: PeersCheckTimeoutsState,
 
     // TODO(zura): implement p2p peer requests to better track each request.
@@ -48,9 +69,19 @@ impl PeersState {
         Self {
             list: BTreeMap::new(),
             ip_blacklist: BTreeMap::new(),
+            scores: BTreeMap::new(),
+            banned_peer_ids: BTreeMap::new(),
 
             dns_lookup: None,
 
+            address_book: PeersAddressBookState::Idle,
+
+            swap: PeersSwapState::default(),
+
+            bandwidth: PeersBandwidthState::default(),
+
+            acl: PeersAclState::default(),
+
             check_timeouts: PeersCheckTimeoutsState::new(),
 
             pending_block_header_requests: BTreeMap::new(),
@@ -101,6 +132,7 @@ impl PeersState {
         &mut self,
         address: SocketAddr,
     ) -> Result<BTreeMapEntry<SocketAddr, Peer>, &PeerBlacklistState> {
+        let address = canonical_address(address);
         if let Some(blacklist_state) = self.ip_blacklist.get(&address.ip()) {
             return Err(blacklist_state);
         }
@@ -112,12 +144,12 @@ impl PeersState {
         &mut self,
         ip: IpAddr,
     ) -> BTreeMapEntry<IpAddr, PeerBlacklistState> {
-        self.ip_blacklist.entry(ip)
+        self.ip_blacklist.entry(canonical_ip(ip))
     }
 
     #[inline(always)]
     pub(super) fn remove_blacklisted_ip(&mut self, ip: &IpAddr) -> Option<PeerBlacklistState> {
-        self.ip_blacklist.remove(ip)
+        self.ip_blacklist.remove(&canonical_ip(*ip))
     }
 
     #[inline(always)]
@@ -145,6 +177,20 @@ impl PeersState {
             .map(|(addr, _)| *addr)
     }
 
+    /// `Potential` peers loaded from the address book, best-known first.
+    pub fn best_known_potential_iter(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.address_book
+            .best_known()
+            .iter()
+            .filter(move |addr| {
+                matches!(
+                    self.list.get(*addr).map(|peer| &peer.status),
+                    Some(PeerStatus::Potential)
+                )
+            })
+            .copied()
+    }
+
     /// Number of potential peers.
     pub fn potential_len(&self) -> usize {
         self.potential_iter().count()
@@ -165,6 +211,14 @@ impl PeersState {
         self.get_blacklisted_ip(ip).is_some()
     }
 
+    /// Whether or not some peer's io loop is stopped because of the bandwidth limit.
+    pub fn is_bandwidth_throttled(&self) -> bool {
+        self.list.values().any(|peer| {
+            peer.try_read_loop.is_byte_quota_reached()
+                || peer.try_write_loop.is_byte_quota_reached()
+        })
+    }
+
     /// Iterator over handshaked peers.
     pub fn handshaked_iter(&self) -> impl Iterator<Item = (SocketAddr, &PeerHandshaked)> {
         self.iter()
@@ -178,7 +232,25 @@ impl PeersState {
 
     #[inline(always)]
     pub fn get_blacklisted_ip(&self, ip: &IpAddr) -> Option<&PeerBlacklistState> {
-        self.ip_blacklist.get(ip)
+        self.ip_blacklist.get(&canonical_ip(*ip))
+    }
+
+    /// Current score of the peer's ip, see [`PeerScore::value`].
+    pub fn score(&self, ip: &IpAddr, time: u64, recovery_interval: Duration) -> i32 {
+        self.scores
+            .get(&canonical_ip(*ip))
+            .map_or(0, |score| score.value(time, recovery_interval))
+    }
+
+    #[inline(always)]
+    pub(super) fn score_entry(&mut self, ip: IpAddr) -> &mut PeerScore {
+        self.scores.entry(canonical_ip(ip)).or_default()
+    }
+
+    /// Sets the score of the peer's ip, unless it is already known.
+    #[inline(always)]
+    pub(super) fn restore_score(&mut self, ip: IpAddr, score: PeerScore) {
+        self.scores.entry(canonical_ip(ip)).or_insert(score);
     }
 
     #[inline(always)]
@@ -0,0 +1,14 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peers_score_state;
+pub use peers_score_state::*;
+
+mod peers_score_actions;
+pub use peers_score_actions::*;
+
+mod peers_score_reducer;
+pub use peers_score_reducer::*;
+
+mod peers_score_effects;
+pub use peers_score_effects::*;
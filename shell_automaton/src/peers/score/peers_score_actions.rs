@@ -0,0 +1,32 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{EnablingCondition, State};
+
+use super::PeerScoreEvent;
+
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::SocketAddrMutator;
+
+/// Update score of the peer's ip.
+///
+/// Depending on the resulting score, peer might be disconnected,
+/// graylisted or banned.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersScoreUpdateAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+
+    pub event: PeerScoreEvent,
+}
+
+impl EnablingCondition<State> for PeersScoreUpdateAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.peers.is_blacklisted(&self.address.ip())
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::disconnection::PeerDisconnectAction;
+use crate::peer::requests::potential_peers_get::PeerRequestsPotentialPeersGetError;
+use crate::peers::graylist::{PeersGraylistIpAddAction, PeersGraylistIpBanAction};
+use crate::{Action, ActionWithMeta, Service, Store};
+
+use super::{PeerScoreEvent, PeersScoreUpdateAction};
+
+pub fn peers_score_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeersScoreUpdate(content) => {
+            let state = store.state.get();
+            let config = &state.config;
+            let ip = content.address.ip();
+            let thresholds = &config.peers_score_thresholds;
+            let score = state
+                .peers
+                .score(&ip, state.time_as_nanos(), thresholds.recovery_interval);
+
+            // trusted peers are only disconnected, they are never graylisted.
+            let graylist_disable =
+                config.peers_graylist_disable || state.peers.acl.is_trusted_ip(&ip);
+
+            if score <= thresholds.ban && !graylist_disable {
+                store.dispatch(PeersGraylistIpBanAction { ip });
+            } else if score <= thresholds.graylist && !graylist_disable {
+                store.dispatch(PeersGraylistIpAddAction { ip });
+            } else if score <= thresholds.disconnect {
+                store.dispatch(PeerDisconnectAction {
+                    address: content.address,
+                });
+            }
+        }
+        Action::BootstrapPeerBlockHeaderGetSuccess(content) => {
+            store.dispatch(PeersScoreUpdateAction {
+                address: content.peer,
+                event: PeerScoreEvent::TimelyBlock,
+            });
+        }
+        Action::BootstrapPeerBlockHeaderGetTimeout(content) => {
+            store.dispatch(PeersScoreUpdateAction {
+                address: content.peer,
+                event: PeerScoreEvent::Timeout,
+            });
+        }
+        Action::BootstrapPeerBlockOperationsGetTimeout(content) => {
+            store.dispatch(PeersScoreUpdateAction {
+                address: content.peer,
+                event: PeerScoreEvent::Timeout,
+            });
+        }
+        Action::PeerRequestsPotentialPeersGetError(content) => match &content.error {
+            PeerRequestsPotentialPeersGetError::Timeout => {
+                store.dispatch(PeersScoreUpdateAction {
+                    address: content.address,
+                    event: PeerScoreEvent::Timeout,
+                });
+            }
+        },
+        _ => {}
+    }
+}
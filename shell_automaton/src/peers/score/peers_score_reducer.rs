@@ -0,0 +1,15 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::{Action, ActionWithMeta, State};
+
+pub fn peers_score_reducer(state: &mut State, action: &ActionWithMeta) {
+    if let Action::PeersScoreUpdate(content) = &action.action {
+        let recovery_interval = state.config.peers_score_thresholds.recovery_interval;
+        state.peers.score_entry(content.address.ip()).update(
+            content.event.score_change(),
+            action.time_as_nanos(),
+            recovery_interval,
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::peers::graylist::PeerGraylistReason;
+
+pub const PEER_SCORE_MIN: i32 = -1000;
+pub const PEER_SCORE_MAX: i32 = 100;
+
+/// Reputation of the peer.
+///
+/// Every peer starts with zero score, which is decreased when peer misbehaves
+/// and increased when it is useful for us. Negative score slowly recovers
+/// back to zero with time.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PeerScore {
+    value: i32,
+    updated_at: u64,
+}
+
+impl PeerScore {
+    /// Score restored from the peer address book, `updated_at` is the time it was stored.
+    pub fn new(value: i32, updated_at: u64) -> Self {
+        Self {
+            value: value.clamp(PEER_SCORE_MIN, PEER_SCORE_MAX),
+            updated_at,
+        }
+    }
+
+    /// Score at `time`, negative score recovers by one point every `recovery_interval`.
+    ///
+    /// Zero `recovery_interval` disables the recovery.
+    pub fn value(&self, time: u64, recovery_interval: Duration) -> i32 {
+        let recovery_interval = recovery_interval.as_nanos() as u64;
+        if self.value >= 0 || recovery_interval == 0 {
+            return self.value;
+        }
+        let recovered = time.saturating_sub(self.updated_at) / recovery_interval;
+        (self.value as i64 + recovered as i64).min(0) as i32
+    }
+
+    pub fn update(&mut self, change: i32, time: u64, recovery_interval: Duration) {
+        let value = self.value(time, recovery_interval);
+        let interval = recovery_interval.as_nanos() as u64;
+        if self.value < 0 && value < 0 && interval > 0 {
+            // keep the time elapsed towards the next recovered point.
+            self.updated_at += (value - self.value) as u64 * interval;
+        } else {
+            self.updated_at = time;
+        }
+        self.value = value
+            .saturating_add(change)
+            .clamp(PEER_SCORE_MIN, PEER_SCORE_MAX);
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PeerScoreEvent {
+    /// Peer sent invalid message, violated the protocol or the connection failed.
+    Misbehaved(PeerGraylistReason),
+    /// Peer didn't respond to our request in time.
+    Timeout,
+    /// Peer sent us something we didn't request.
+    UselessResponse,
+    /// Peer sent us a response we already received.
+    DuplicateResponse,
+    /// Peer sent us requested block header in time.
+    TimelyBlock,
+    /// Peer sent us requested block operations in time.
+    TimelyOperations,
+}
+
+impl PeerScoreEvent {
+    pub fn score_change(&self) -> i32 {
+        match self {
+            Self::Misbehaved(reason) => match reason {
+                // most likely network issues, honest peer can cause those.
+                PeerGraylistReason::ConnectionClosed
+                | PeerGraylistReason::ChunkWriteError
+                | PeerGraylistReason::BinaryMessageWriteError
+                | PeerGraylistReason::MessageWriteError(_)
+                | PeerGraylistReason::NackReceived(_) => -25,
+                // peer sent us invalid data or we couldn't even connect/handshake with it.
+                PeerGraylistReason::ConnectionIncomingError
+                | PeerGraylistReason::ConnectionOutgoingError
+                | PeerGraylistReason::HandshakeError
+                | PeerGraylistReason::NackSent(_)
+                | PeerGraylistReason::ChunkReadError
+                | PeerGraylistReason::BinaryMessageReadError
+                | PeerGraylistReason::MessageReadError
+                | PeerGraylistReason::BootstrapCementedBlockReorg
+                | PeerGraylistReason::Unknown => -100,
+                PeerGraylistReason::RequestedBlockHeaderLevelMismatch
+                | PeerGraylistReason::BootstrapBlockHeaderInconsistentChain => -250,
+            },
+            Self::Timeout => -10,
+            Self::UselessResponse => -5,
+            Self::DuplicateResponse => -2,
+            Self::TimelyBlock => 2,
+            Self::TimelyOperations => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_peer_score_recovery() {
+        let recovery_interval = Duration::from_secs(60);
+        let mut score = PeerScore::default();
+
+        score.update(-100, 10 * SECOND, recovery_interval);
+        assert_eq!(score.value(10 * SECOND, recovery_interval), -100);
+        assert_eq!(score.value(69 * SECOND, recovery_interval), -100);
+        assert_eq!(score.value(70 * SECOND, recovery_interval), -99);
+        assert_eq!(score.value(10_000 * SECOND, recovery_interval), 0);
+        assert_eq!(score.value(10_000 * SECOND, Duration::ZERO), -100);
+
+        score.update(-10, 130 * SECOND, recovery_interval);
+        assert_eq!(score.value(130 * SECOND, recovery_interval), -108);
+    }
+
+    #[test]
+    fn test_peer_score_update_keeps_recovery_progress() {
+        let recovery_interval = Duration::from_secs(60);
+        let mut score = PeerScore::default();
+
+        score.update(-100, 10 * SECOND, recovery_interval);
+        // frequent updates must not prevent the recovery.
+        for time in (40..=190).step_by(30) {
+            score.update(0, time * SECOND, recovery_interval);
+        }
+        assert_eq!(score.value(190 * SECOND, recovery_interval), -97);
+        assert_eq!(score.value(250 * SECOND, recovery_interval), -96);
+
+        // recovery starts when the score drops below zero.
+        let mut score = PeerScore::default();
+        score.update(10, 10 * SECOND, recovery_interval);
+        score.update(-20, 100 * SECOND, recovery_interval);
+        assert_eq!(score.value(159 * SECOND, recovery_interval), -10);
+        assert_eq!(score.value(160 * SECOND, recovery_interval), -9);
+    }
+
+    #[test]
+    fn test_peer_score_bounds() {
+        let recovery_interval = Duration::from_secs(60);
+        let mut score = PeerScore::default();
+
+        for time in 0..1000 {
+            score.update(
+                PeerScoreEvent::TimelyBlock.score_change(),
+                time,
+                recovery_interval,
+            );
+        }
+        assert_eq!(score.value(1000, recovery_interval), PEER_SCORE_MAX);
+
+        for time in 1000..2000 {
+            score.update(
+                PeerScoreEvent::Misbehaved(PeerGraylistReason::MessageReadError).score_change(),
+                time,
+                recovery_interval,
+            );
+        }
+        assert_eq!(score.value(2000, recovery_interval), PEER_SCORE_MIN);
+    }
+}
@@ -0,0 +1,14 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+mod peers_swap_state;
+pub use peers_swap_state::*;
+
+mod peers_swap_actions;
+pub use peers_swap_actions::*;
+
+mod peers_swap_reducer;
+pub use peers_swap_reducer::*;
+
+mod peers_swap_effects;
+pub use peers_swap_effects::*;
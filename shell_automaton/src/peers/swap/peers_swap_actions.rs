@@ -0,0 +1,171 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crypto::hash::CryptoboxPublicKeyHash;
+
+use crate::peer::{PeerHandshaked, PeerStatus};
+use crate::{EnablingCondition, State};
+
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::SocketAddrMutator;
+
+/// Handshaked peers, which can be proposed for the swap, with their points.
+pub fn peers_swap_proposable_iter(
+    state: &State,
+) -> impl Iterator<Item = (SocketAddr, SocketAddr, &PeerHandshaked)> {
+    state
+        .peers
+        .handshaked_iter()
+        .filter(|(_, peer)| !peer.private_node)
+        .map(|(address, peer)| (address, SocketAddr::new(address.ip(), peer.port), peer))
+}
+
+/// Whether we are connected (or connecting) to the peer listening on `point`.
+pub fn peers_swap_is_connected_to_point(state: &State, point: SocketAddr) -> bool {
+    let connected = state
+        .peers
+        .get(&point)
+        .map_or(false, |peer| !matches!(peer.status, PeerStatus::Potential));
+    connected
+        || state
+            .peers
+            .handshaked_iter()
+            .any(|(address, peer)| address.ip() == point.ip() && peer.port == point.port())
+}
+
+/// Send the swap request to `recipient`, proposing it our peer `proposed`.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapRequestSendAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub recipient: SocketAddr,
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub proposed: SocketAddr,
+}
+
+impl PeersSwapRequestSendAction {
+    pub fn should_send(state: &State) -> bool {
+        let config = &state.config;
+        let swap = &state.peers.swap;
+        let time = state.time_as_nanos();
+        if config.peers_swap_disable
+            || swap.pending.is_some()
+            || swap.is_lingering(time, config.peers_swap_linger)
+        {
+            return false;
+        }
+        if let Some(req) = &swap.sent_request {
+            // still waiting for the ack.
+            if time < req.time + config.peers_swap_linger.as_nanos() as u64 {
+                return false;
+            }
+        }
+
+        // only rebalance when we have neither too few nor too many peers.
+        let handshaked_len = state.peers.handshaked_len();
+        handshaked_len >= config.peers_connected_min.max(2)
+            && handshaked_len <= config.peers_connected_max
+    }
+}
+
+impl EnablingCondition<State> for PeersSwapRequestSendAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        if !Self::should_send(state) || self.recipient == self.proposed {
+            return false;
+        }
+        let is_proposable = |address| {
+            state
+                .peers
+                .get_handshaked(address)
+                .map_or(false, |peer| !peer.private_node)
+        };
+        is_proposable(&self.recipient) && is_proposable(&self.proposed)
+    }
+}
+
+/// Peer has sent us the swap request.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapRequestReceivedAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    /// Point of the peer proposed to us.
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub point: SocketAddr,
+    pub peer_id: CryptoboxPublicKeyHash,
+}
+
+impl EnablingCondition<State> for PeersSwapRequestReceivedAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.peers.get_handshaked(&self.address).is_some()
+    }
+}
+
+/// Peer has accepted our swap request.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapAckReceivedAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    /// Point of the peer proposed to us in exchange.
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub point: SocketAddr,
+    pub peer_id: CryptoboxPublicKeyHash,
+}
+
+impl EnablingCondition<State> for PeersSwapAckReceivedAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.peers.swap.is_request_sent_to(
+            self.address,
+            state.time_as_nanos(),
+            state.config.peers_swap_linger,
+        )
+    }
+}
+
+/// Connect to `connect` and disconnect from `disconnect` once connected.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapInitAction {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub connect: SocketAddr,
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub disconnect: SocketAddr,
+}
+
+impl EnablingCondition<State> for PeersSwapInitAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        !state.config.peers_swap_disable
+            && state.peers.swap.pending.is_none()
+            && state.peers.get_handshaked(&self.disconnect).is_some()
+            && !state.peers.acl.is_trusted_ip(&self.disconnect.ip())
+            && !state.peers.is_blacklisted(&self.connect.ip())
+            && !peers_swap_is_connected_to_point(state, self.connect)
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapSuccessAction {}
+
+impl EnablingCondition<State> for PeersSwapSuccessAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.peers.swap.pending.as_ref().map_or(false, |pending| {
+            state.peers.get_handshaked(&pending.connect).is_some()
+        })
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapErrorAction {}
+
+impl EnablingCondition<State> for PeersSwapErrorAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.peers.swap.pending.is_some()
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tezos_messages::p2p::encoding::peer::PeerMessage;
+use tezos_messages::p2p::encoding::swap::SwapMessage;
+
+use crate::peer::connection::outgoing::PeerConnectionOutgoingInitAction;
+use crate::peer::disconnection::PeerDisconnectAction;
+use crate::peer::message::write::PeerMessageWriteInitAction;
+use crate::service::{RandomnessService, Service};
+use crate::{Action, ActionWithMeta, EnablingCondition, Store};
+
+use super::{
+    peers_swap_proposable_iter, PeersSwapErrorAction, PeersSwapInitAction,
+    PeersSwapRequestSendAction, PeersSwapSuccessAction,
+};
+
+pub fn peers_swap_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
+    match &action.action {
+        Action::MioTimeoutEvent(_) => {
+            let state = store.state.get();
+            if let Some(pending) = &state.peers.swap.pending {
+                let linger = state.config.peers_swap_linger.as_nanos() as u64;
+                if state.time_as_nanos() >= pending.time + linger {
+                    store.dispatch(PeersSwapErrorAction {});
+                }
+                return;
+            }
+            if !PeersSwapRequestSendAction::should_send(state) {
+                return;
+            }
+
+            let proposable = peers_swap_proposable_iter(state)
+                .map(|(address, _, _)| address)
+                .collect::<Vec<_>>();
+            let recipient = match store.service.randomness().choose_peer(&proposable) {
+                Some(v) => v,
+                None => return,
+            };
+            let proposable = proposable
+                .into_iter()
+                .filter(|address| *address != recipient)
+                .collect::<Vec<_>>();
+            let proposed = match store.service.randomness().choose_peer(&proposable) {
+                Some(v) => v,
+                None => return,
+            };
+            store.dispatch(PeersSwapRequestSendAction {
+                recipient,
+                proposed,
+            });
+        }
+        Action::PeersSwapRequestSend(content) => {
+            let message = match store.state().peers.get_handshaked(&content.proposed) {
+                Some(peer) => SwapMessage::new(
+                    SocketAddr::new(content.proposed.ip(), peer.port).to_string(),
+                    peer.public_key_hash.clone(),
+                ),
+                None => return,
+            };
+            store.dispatch(PeerMessageWriteInitAction {
+                address: content.recipient,
+                message: Arc::new(PeerMessage::SwapRequest(message).into()),
+            });
+        }
+        Action::PeersSwapRequestReceived(content) => {
+            let state = store.state.get();
+            let swap = PeersSwapInitAction {
+                connect: content.point,
+                disconnect: content.address,
+            };
+            let proposed = peers_swap_proposable_iter(state)
+                .find(|(address, point, _)| *address != content.address && *point != content.point)
+                .map(|(_, point, peer)| (point, peer.public_key_hash.clone()));
+
+            let ignore_reason = if state.config.peers_swap_disable {
+                Some("swap disabled")
+            } else if state
+                .peers
+                .swap
+                .is_lingering(state.time_as_nanos(), state.config.peers_swap_linger)
+            {
+                Some("recent swap")
+            } else if !swap.is_enabled(state) {
+                Some("swap pending, peer trusted or point already connected or blacklisted")
+            } else if proposed.is_none() {
+                Some("no peer to propose")
+            } else {
+                None
+            };
+            let (proposed_point, proposed_peer_id) = match (ignore_reason, proposed) {
+                (None, Some(v)) => v,
+                (ignore_reason, _) => {
+                    slog::debug!(&state.log, "Ignoring swap request";
+                        "peer" => content.address.to_string(),
+                        "point" => content.point.to_string(),
+                        "reason" => ignore_reason.unwrap_or_default());
+                    return;
+                }
+            };
+
+            store.dispatch(PeerMessageWriteInitAction {
+                address: content.address,
+                message: Arc::new(
+                    PeerMessage::SwapAck(SwapMessage::new(
+                        proposed_point.to_string(),
+                        proposed_peer_id,
+                    ))
+                    .into(),
+                ),
+            });
+            store.dispatch(swap);
+        }
+        Action::PeersSwapAckReceived(content) => {
+            store.dispatch(PeersSwapInitAction {
+                connect: content.point,
+                disconnect: content.address,
+            });
+        }
+        Action::PeersSwapInit(content) => {
+            store.dispatch(PeerConnectionOutgoingInitAction {
+                address: content.connect,
+            });
+        }
+        Action::PeerHandshakingFinish(content) => {
+            let disconnect = match &store.state().peers.swap.pending {
+                Some(pending) if pending.connect == content.address => pending.disconnect,
+                _ => return,
+            };
+            if store.dispatch(PeersSwapSuccessAction {}) {
+                store.dispatch(PeerDisconnectAction {
+                    address: disconnect,
+                });
+            }
+        }
+        Action::PeerDisconnected(content) => {
+            let is_swap_connection = store
+                .state()
+                .peers
+                .swap
+                .pending
+                .as_ref()
+                .map_or(false, |pending| pending.connect == content.address);
+            if is_swap_connection {
+                store.dispatch(PeersSwapErrorAction {});
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use crate::peer::{Peer, PeerIOLoopState, PeerStatus};
+use crate::{Action, ActionWithMeta, State};
+
+use super::{PeersSwapPending, PeersSwapSentRequest};
+
+pub fn peers_swap_reducer(state: &mut State, action: &ActionWithMeta) {
+    match &action.action {
+        Action::PeersSwapRequestSend(content) => {
+            state.peers.swap.sent_request = Some(PeersSwapSentRequest {
+                time: action.time_as_nanos(),
+                recipient: content.recipient,
+            });
+        }
+        Action::PeersSwapAckReceived(_) => {
+            state.peers.swap.sent_request = None;
+        }
+        Action::PeersSwapInit(content) => {
+            // swapped peer is added even if we have enough potential peers.
+            if let Ok(entry) = state.peers.entry(content.connect) {
+                entry.or_insert_with(|| Peer {
+                    status: PeerStatus::Potential,
+                    try_read_loop: PeerIOLoopState::Idle,
+                    try_write_loop: PeerIOLoopState::Idle,
+                    bandwidth: Default::default(),
+                });
+            }
+            let swap = &mut state.peers.swap;
+            swap.last_accepted = action.time_as_nanos();
+            swap.pending = Some(PeersSwapPending {
+                time: action.time_as_nanos(),
+                connect: content.connect,
+                disconnect: content.disconnect,
+            });
+        }
+        Action::PeersSwapSuccess(_) => {
+            let swap = &mut state.peers.swap;
+            swap.last_successful = action.time_as_nanos();
+            swap.pending = None;
+        }
+        Action::PeersSwapError(_) => {
+            let swap = &mut state.peers.swap;
+            // failed swap doesn't count for the linger.
+            swap.last_accepted = swap.last_successful;
+            swap.pending = None;
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Swap request sent by us, waiting for the ack.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapSentRequest {
+    pub time: u64,
+    /// Peer we have sent the swap request to.
+    pub recipient: SocketAddr,
+}
+
+/// Accepted swap, waiting for the connection with the new peer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeersSwapPending {
+    pub time: u64,
+    /// New peer we are connecting to.
+    pub connect: SocketAddr,
+    /// Peer we swapped with, disconnected once we connect to the new peer.
+    pub disconnect: SocketAddr,
+}
+
+/// State of the peer swapping (rebalancing of the connections with our peers).
+///
+/// Peer which we send the swap request to with a point of one of our peers,
+/// may accept it, reply with a point of one of its peers and connect to
+/// the proposed point. Both sides then disconnect from each other.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PeersSwapState {
+    pub sent_request: Option<PeersSwapSentRequest>,
+    pub pending: Option<PeersSwapPending>,
+    /// Time of the last accepted swap.
+    pub last_accepted: u64,
+    /// Time of the last successful swap.
+    pub last_successful: u64,
+}
+
+impl PeersSwapState {
+    /// Whether the last accepted swap is too recent for another one.
+    #[inline(always)]
+    pub fn is_lingering(&self, time: u64, linger: Duration) -> bool {
+        time < self.last_accepted + linger.as_nanos() as u64
+    }
+
+    pub fn is_request_sent_to(&self, address: SocketAddr, time: u64, linger: Duration) -> bool {
+        self.sent_request.as_ref().map_or(false, |req| {
+            req.recipient == address && time < req.time + linger.as_nanos() as u64
+        })
+    }
+}
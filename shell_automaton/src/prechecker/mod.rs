@@ -15,6 +15,9 @@ pub use prechecker_effects::prechecker_effects;
 
 mod prechecker_validator;
 pub use prechecker_validator::*;
+
+mod prechecker_manager_validator;
+pub use prechecker_manager_validator::*;
 use tezos_messages::{p2p::encoding::block_header::BlockHeader, protocol::SupportedProtocol};
 
 /// Checks if prechecking is enabled for the block that is a successor of the block with `prev_block` hash.
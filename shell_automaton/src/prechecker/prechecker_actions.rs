@@ -12,12 +12,14 @@ use tezos_messages::{
 };
 
 use crate::{
-    prechecker::PrecheckerOperationState, rights::EndorsingRights, EnablingCondition, State,
+    prechecker::PrecheckerOperationState, protocol_runner::ProtocolRunnerToken,
+    rights::EndorsingRights, service::signature_verifier_service::SignatureVerificationError,
+    EnablingCondition, State,
 };
 
 use super::{
-    EndorsementValidationError, Key, OperationDecodedContents, PrecheckerError,
-    PrecheckerResponseError,
+    EndorsementValidationError, Key, ManagerContext, ManagerOperationValidationError,
+    OperationDecodedContents, PrecheckerError, PrecheckerResponseError,
 };
 
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
@@ -92,6 +94,8 @@ pub struct PrecheckerErrored {
     pub hash: OperationHash,
     pub operation_decoded_contents: OperationDecodedContents,
     pub error: String,
+    /// The operation might be valid on another branch.
+    pub branch_refused: bool,
 }
 
 impl PrecheckerErrored {
@@ -136,11 +140,13 @@ impl PrecheckerPrecheckOperationResponseAction {
         operation_hash: &OperationHash,
         operation_decoded_contents: OperationDecodedContents,
         error: String,
+        branch_refused: bool,
     ) -> Self {
         let errored = PrecheckerErrored {
             hash: operation_hash.clone(),
             error,
             operation_decoded_contents,
+            branch_refused,
         };
         Self {
             response: PrecheckerPrecheckOperationResponse::Refused(errored),
@@ -246,6 +252,41 @@ pub struct PrecheckerEndorsementValidationRefusedAction {
     pub error: EndorsementValidationError,
 }
 
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrecheckerValidateManagerOperationAction {
+    pub key: Key,
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrecheckerManagerContextPendingAction {
+    pub key: Key,
+    pub token: ProtocolRunnerToken,
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrecheckerManagerContextReadyAction {
+    pub key: Key,
+    pub context: ManagerContext,
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrecheckerManagerOperationValidationRefusedAction {
+    pub key: Key,
+    pub error: ManagerOperationValidationError,
+}
+
+/// Result of the operation's signature verification by the signature verifier service.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrecheckerSignatureVerifiedAction {
+    pub key: Key,
+    pub result: Result<bool, SignatureVerificationError>,
+}
+
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PrecheckerProtocolNeededAction {
@@ -312,6 +353,7 @@ impl EnablingCondition<State> for PrecheckerPruneOperationAction {
                 op.state,
                 PrecheckerOperationState::Applied { .. }
                     | PrecheckerOperationState::Refused { .. }
+                    | PrecheckerOperationState::ManagerRefused { .. }
                     | PrecheckerOperationState::ProtocolNeeded
             ),
             None => false,
@@ -391,6 +433,69 @@ impl EnablingCondition<State> for PrecheckerEndorsementValidationRefusedAction {
         true
     }
 }
+impl EnablingCondition<State> for PrecheckerValidateManagerOperationAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .prechecker
+            .operations
+            .get(&self.key)
+            .map_or(false, |op| {
+                matches!(
+                    op.state,
+                    PrecheckerOperationState::DecodedContentReady { .. }
+                )
+            })
+    }
+}
+impl EnablingCondition<State> for PrecheckerManagerContextPendingAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .prechecker
+            .operations
+            .get(&self.key)
+            .map_or(false, |op| {
+                matches!(
+                    op.state,
+                    PrecheckerOperationState::DecodedContentReady { .. }
+                )
+            })
+    }
+}
+impl EnablingCondition<State> for PrecheckerManagerContextReadyAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .prechecker
+            .operations
+            .get(&self.key)
+            .map_or(false, |op| {
+                matches!(
+                    op.state,
+                    PrecheckerOperationState::PendingManagerContext { .. }
+                )
+            })
+    }
+}
+impl EnablingCondition<State> for PrecheckerManagerOperationValidationRefusedAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        state
+            .prechecker
+            .operations
+            .get(&self.key)
+            .map_or(false, |op| {
+                matches!(
+                    op.state,
+                    PrecheckerOperationState::DecodedContentReady { .. }
+                        | PrecheckerOperationState::ManagerContextReady { .. }
+                )
+            })
+    }
+}
+impl EnablingCondition<State> for PrecheckerSignatureVerifiedAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        let _ = state;
+        true
+    }
+}
 impl EnablingCondition<State> for PrecheckerProtocolNeededAction {
     fn is_enabled(&self, state: &State) -> bool {
         let _ = state;
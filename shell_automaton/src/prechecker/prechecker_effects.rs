@@ -4,12 +4,15 @@
 use std::convert::{TryFrom, TryInto};
 
 use crypto::{blake2b, hash::BlockHash};
-use slog::error;
-use tezos_messages::{p2p::binary_message::BinaryWrite, protocol::SupportedProtocol};
+use slog::{error, warn};
+use tezos_messages::{
+    base::contract::ContractId, p2p::binary_message::BinaryWrite, protocol::SupportedProtocol,
+};
 
 use crate::{
     current_head_precheck::{
-        CurrentHeadPrecheckRejectedAction, CurrentHeadPrecheckSuccessAction, CurrentHeadState,
+        CurrentHeadPrecheckRejectedAction, CurrentHeadPrecheckSignatureVerifiedAction,
+        CurrentHeadPrecheckSuccessAction, CurrentHeadState,
     },
     mempool::mempool_actions::MempoolOperationDecodedAction,
     prechecker::{
@@ -17,11 +20,20 @@ use crate::{
         PrecheckerOperationState, Refused,
     },
     rights::{rights_actions::*, RightsKey},
+    service::{
+        protocol_runner_service::ProtocolRunnerResult,
+        signature_verifier_service::{
+            SignatureVerificationError, SignatureVerificationRequest, SignatureVerificationTarget,
+            SignedData,
+        },
+        ProtocolRunnerService, SignatureVerifierService,
+    },
     Action, ActionWithMeta, Service, State, Store,
 };
 
 use super::{
-    prechecker_actions::*, protocol_for_block, EndorsementValidationError, Key,
+    prechecker_actions::*, protocol_for_block, signed_contents, EndorsementValidationError, Key,
+    ManagerContext, ManagerOperationValidationError, ManagerOperationValidator,
     OperationDecodedContents, PrecheckerError, PrecheckerOperation,
 };
 
@@ -179,12 +191,15 @@ where
             }) = prechecker_state_operations.get(key).map(|op| &op.state)
             {
                 let is_endorsement = operation_decoded_contents.is_endorsement();
+                let is_manager_operation = operation_decoded_contents.manager_source().is_some();
                 let endorsement_level = operation_decoded_contents.endorsement_level();
                 let block = operation_decoded_contents.branch().clone();
                 let operation_decoded_contents = operation_decoded_contents.clone();
                 let disable_block_precheck = store.state().config.disable_block_precheck;
                 let disable_endorsements_precheck =
                     store.state().config.disable_endorsements_precheck;
+                let disable_manager_operations_precheck =
+                    store.state().config.disable_manager_operations_precheck;
                 let ithaca_protocol = matches!(
                     operation_decoded_contents,
                     OperationDecodedContents::Proto012(_)
@@ -195,7 +210,9 @@ where
                     operation_decoded_contents,
                 });
 
-                if disable_endorsements_precheck || !is_endorsement || ithaca_protocol {
+                if is_manager_operation && !disable_manager_operations_precheck {
+                    store.dispatch(PrecheckerValidateManagerOperationAction { key: key.clone() });
+                } else if disable_endorsements_precheck || !is_endorsement || ithaca_protocol {
                     store.dispatch(PrecheckerProtocolNeededAction { key: key.clone() });
                 } else if disable_block_precheck {
                     let current_head = match store.state().current_head.get() {
@@ -430,10 +447,8 @@ where
                 );
 
                 match validation_result {
-                    Ok(Applied { .. }) => {
-                        store.dispatch(PrecheckerEndorsementValidationAppliedAction {
-                            key: key.clone(),
-                        });
+                    Ok(Applied { signed_data, .. }) => {
+                        verify_signature(store, key, signed_data);
                     }
                     Err(Refused { error, .. }) => {
                         store.dispatch(PrecheckerEndorsementValidationRefusedAction {
@@ -444,6 +459,227 @@ where
                 };
             }
         }
+        Action::PrecheckerValidateManagerOperation(PrecheckerValidateManagerOperationAction {
+            key,
+        }) => {
+            let (operation, operation_decoded_contents) = match prechecker_state_operations.get(key)
+            {
+                Some(PrecheckerOperation {
+                    operation,
+                    state:
+                        PrecheckerOperationState::DecodedContentReady {
+                            operation_decoded_contents,
+                        },
+                    ..
+                }) => (operation, operation_decoded_contents),
+                _ => return,
+            };
+            let mempool_state = &store.state.get().mempool;
+            let head = match mempool_state.local_head_state.as_ref() {
+                Some(v) => v,
+                None => {
+                    store.dispatch(PrecheckerProtocolNeededAction { key: key.clone() });
+                    return;
+                }
+            };
+            let branch_level = if &head.hash == operation.branch() {
+                Some(head.header.level())
+            } else {
+                mempool_state
+                    .last_predecessor_blocks
+                    .get(operation.branch())
+                    .cloned()
+            };
+
+            if let Err(error) = operation_decoded_contents
+                .validate_manager_operation(branch_level, head.header.level())
+            {
+                store.dispatch(PrecheckerManagerOperationValidationRefusedAction {
+                    key: key.clone(),
+                    error,
+                });
+                return;
+            }
+
+            let prefix = match operation_decoded_contents
+                .manager_source()
+                .and_then(|source| ContractId::Implicit(source.clone()).context_path())
+            {
+                Some(v) => v,
+                None => {
+                    store.dispatch(PrecheckerProtocolNeededAction { key: key.clone() });
+                    return;
+                }
+            };
+            let context_hash = head.header.context().clone();
+            let token = store
+                .service
+                .protocol_runner()
+                .get_context_key_values_by_prefix(context_hash, prefix);
+            store.dispatch(PrecheckerManagerContextPendingAction {
+                key: key.clone(),
+                token,
+            });
+        }
+        Action::ProtocolRunnerResponse(resp) => {
+            let (token, result) = match &resp.result {
+                ProtocolRunnerResult::ContextKeyValuesByPrefixGet((token, result)) => {
+                    (token, result)
+                }
+                _ => return,
+            };
+            let (key, source) =
+                match prechecker_state_operations
+                    .iter()
+                    .find_map(|(key, op)| match &op.state {
+                        PrecheckerOperationState::PendingManagerContext {
+                            operation_decoded_contents,
+                            token: op_token,
+                        } if op_token == token => {
+                            Some((key, operation_decoded_contents.manager_source()?))
+                        }
+                        _ => None,
+                    }) {
+                    Some((key, source)) => (key.clone(), source),
+                    None => return,
+                };
+            match result {
+                Ok(key_values) => {
+                    let prefix = ContractId::Implicit(source.clone())
+                        .context_path()
+                        .unwrap_or_default();
+                    let context = ManagerContext::from_key_values(
+                        &prefix,
+                        key_values.as_deref().unwrap_or_default(),
+                    );
+                    store.dispatch(PrecheckerManagerContextReadyAction { key, context });
+                }
+                Err(err) => {
+                    warn!(log, "Getting manager context failed"; "operation" => key.to_string(), "error" => err.to_string());
+                    store.dispatch(PrecheckerProtocolNeededAction { key });
+                }
+            }
+        }
+        Action::PrecheckerManagerContextReady(PrecheckerManagerContextReadyAction {
+            key, ..
+        }) => {
+            if let Some(PrecheckerOperation {
+                operation_binary_encoding,
+                state:
+                    PrecheckerOperationState::ManagerContextReady {
+                        operation_decoded_contents,
+                        context,
+                    },
+                ..
+            }) = prechecker_state_operations.get(key)
+            {
+                let signed_contents =
+                    signed_contents(operation_binary_encoding).unwrap_or_default();
+                match operation_decoded_contents
+                    .validate_manager_operation_with_context(context, signed_contents)
+                {
+                    Ok(Some(signed_data)) => {
+                        verify_signature(store, key, signed_data);
+                    }
+                    Ok(None) => {
+                        store.dispatch(PrecheckerProtocolNeededAction { key: key.clone() });
+                    }
+                    Err(error) => {
+                        store.dispatch(PrecheckerManagerOperationValidationRefusedAction {
+                            key: key.clone(),
+                            error,
+                        });
+                    }
+                }
+            }
+        }
+        Action::WakeupEvent(_) => {
+            while let Ok(response) = store.service.signature_verifier().try_recv() {
+                match response.target {
+                    SignatureVerificationTarget::Operation(operation) => {
+                        store.dispatch(PrecheckerSignatureVerifiedAction {
+                            key: (&operation).into(),
+                            result: response.result,
+                        });
+                    }
+                    SignatureVerificationTarget::BlockHeader {
+                        block_hash,
+                        priority,
+                    } => {
+                        store.dispatch(CurrentHeadPrecheckSignatureVerifiedAction {
+                            block_hash,
+                            priority,
+                            result: response.result,
+                        });
+                    }
+                }
+            }
+        }
+        Action::PrecheckerSignatureVerified(PrecheckerSignatureVerifiedAction { key, result }) => {
+            match prechecker_state_operations.get(key).map(|op| &op.state) {
+                Some(PrecheckerOperationState::PendingOperationPrechecking { .. }) => {
+                    let error = match result {
+                        Ok(true) => {
+                            store.dispatch(PrecheckerEndorsementValidationAppliedAction {
+                                key: key.clone(),
+                            });
+                            return;
+                        }
+                        Ok(false) => EndorsementValidationError::InlinedSignatureMismatch,
+                        Err(SignatureVerificationError::Unsupported) => {
+                            EndorsementValidationError::UnsupportedPublicKey
+                        }
+                        Err(SignatureVerificationError::Other(_)) => {
+                            EndorsementValidationError::SignatureError
+                        }
+                    };
+                    store.dispatch(PrecheckerEndorsementValidationRefusedAction {
+                        key: key.clone(),
+                        error,
+                    });
+                }
+                Some(PrecheckerOperationState::ManagerContextReady { .. }) => {
+                    // unsupported curve is left to the protocol.
+                    let error = match result {
+                        Ok(true) | Err(SignatureVerificationError::Unsupported) => {
+                            store.dispatch(PrecheckerProtocolNeededAction { key: key.clone() });
+                            return;
+                        }
+                        Ok(false) => ManagerOperationValidationError::SignatureMismatch,
+                        Err(SignatureVerificationError::Other(_)) => {
+                            ManagerOperationValidationError::SignatureError
+                        }
+                    };
+                    store.dispatch(PrecheckerManagerOperationValidationRefusedAction {
+                        key: key.clone(),
+                        error,
+                    });
+                }
+                _ => (),
+            }
+        }
+        Action::PrecheckerManagerOperationValidationRefused(
+            PrecheckerManagerOperationValidationRefusedAction { key, .. },
+        ) => {
+            if let Some(PrecheckerOperation {
+                state:
+                    PrecheckerOperationState::ManagerRefused {
+                        operation_decoded_contents,
+                        error,
+                    },
+                ..
+            }) = prechecker_state_operations.get(key)
+            {
+                let action = PrecheckerPrecheckOperationResponseAction::reject(
+                    &key.operation,
+                    operation_decoded_contents.clone(),
+                    serde_json::to_string(error).unwrap_or_else(|_| "<unserialized>".to_string()),
+                    error.is_branch_error(),
+                );
+                store.dispatch(action);
+            }
+            store.dispatch(PrecheckerPruneOperationAction { key: key.clone() });
+        }
         Action::PrecheckerProtocolNeeded(PrecheckerProtocolNeededAction { key, .. }) => {
             if let Some(op) = prechecker_state_operations.get(key) {
                 if matches!(op.state, PrecheckerOperationState::ProtocolNeeded {}) {
@@ -488,6 +724,7 @@ where
                     &key.operation,
                     operation_decoded_contents.clone(),
                     serde_json::to_string(error).unwrap_or_else(|_| "<unserialized>".to_string()),
+                    false,
                 );
                 store.dispatch(action);
             }
@@ -594,3 +831,23 @@ fn endorsement_branch_is_valid(state: &State, branch: &BlockHash) -> Option<bool
         }
     }
 }
+
+/// Queues the operation's signature for the verification by the signature
+/// verifier service, the result is dispatched as [PrecheckerSignatureVerifiedAction].
+fn verify_signature<S>(store: &mut Store<S>, key: &Key, signed_data: SignedData)
+where
+    S: Service,
+{
+    let req = SignatureVerificationRequest {
+        target: SignatureVerificationTarget::Operation(key.operation.clone()),
+        signed_data,
+    };
+    if let Err(err) = store.service.signature_verifier().verify(req) {
+        // the service is not running, verify it here.
+        let result = err.payload().signed_data.verify();
+        store.dispatch(PrecheckerSignatureVerifiedAction {
+            key: key.clone(),
+            result,
+        });
+    }
+}
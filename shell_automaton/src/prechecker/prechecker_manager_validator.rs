@@ -0,0 +1,295 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Prechecking of the manager operations (Ithaca).
+//!
+//! Operation is checked against the manager's public key and counter read
+//! from the context of the current head, so that obviously invalid operations
+//! are refused without the protocol. Operations passing the precheck are
+//! still validated by the protocol, as e.g. the balance is not checked here.
+
+use std::convert::TryFrom;
+
+use crypto::{hash::Signature, PublicKeyWithHash};
+use tezos_context_api::{ContextKeyOwned, ContextValue};
+use tezos_encoding::types::Mutez;
+use tezos_messages::{
+    base::{
+        contract::{ContractField, ContractFieldValue, ContractManager},
+        signature_public_key::{SignaturePublicKey, SignatureWatermark},
+    },
+    p2p::encoding::block_header::Level,
+};
+
+use crate::mempool::OPERATION_TTL;
+use crate::service::signature_verifier_service::SignedData;
+
+use super::OperationDecodedContents;
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, thiserror::Error)]
+pub enum ManagerOperationValidationError {
+    #[error("Unknown or outdated branch")]
+    InvalidBranch,
+    #[error("Non-manager operation in the batch")]
+    InvalidContents,
+    #[error("Manager operations in the batch have different sources")]
+    InconsistentSources,
+    #[error("Counters of the manager operations in the batch are not sequential")]
+    InconsistentCounters,
+    #[error("Counter {counter} already used, expected {expected}")]
+    CounterInThePast { counter: u64, expected: u64 },
+    #[error("Revealed public key does not match the source")]
+    InconsistentPublicKey,
+    #[error("Error verifying operation signature")]
+    SignatureError,
+    #[error("Failed to verify the operation's signature")]
+    SignatureMismatch,
+}
+
+impl ManagerOperationValidationError {
+    /// Whether the operation might be valid on another branch, so it is
+    /// `branch_refused` rather than `refused`.
+    pub fn is_branch_error(&self) -> bool {
+        matches!(self, Self::InvalidBranch | Self::CounterInThePast { .. })
+    }
+}
+
+/// Manager's data read from the context of the current head.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ManagerContext {
+    /// Public key of the manager, `None` if it is not revealed yet.
+    pub public_key: Option<SignaturePublicKey>,
+    /// Counter of the last operation of the manager, `None` if the account doesn't exist.
+    pub counter: Option<u64>,
+}
+
+impl ManagerContext {
+    /// Picks the manager's data from the key-values read under the context path
+    /// of the manager's contract, see [`ContractId::context_path`].
+    ///
+    /// [`ContractId::context_path`]: tezos_messages::base::contract::ContractId::context_path
+    pub fn from_key_values(
+        prefix: &[String],
+        key_values: &[(ContextKeyOwned, ContextValue)],
+    ) -> Self {
+        let mut context = Self::default();
+        let contract_id = match prefix.last() {
+            Some(v) => v,
+            None => return context,
+        };
+        for (key, value) in key_values {
+            let field = match key.as_slice() {
+                [.., id, name] if id == contract_id => {
+                    [ContractField::Counter, ContractField::Manager]
+                        .iter()
+                        .copied()
+                        .find(|field| field.context_key() == name)
+                }
+                _ => None,
+            };
+            match field.map(|field| ContractFieldValue::from_bytes(field, value)) {
+                Some(Ok(ContractFieldValue::Counter(counter))) => {
+                    context.counter = u64::try_from(&counter.0).ok();
+                }
+                // public key hash only, if the key is not revealed yet.
+                Some(Ok(ContractFieldValue::Manager(ContractManager::PublicKey(public_key)))) => {
+                    context.public_key = Some(public_key);
+                }
+                _ => (),
+            }
+        }
+        context
+    }
+}
+
+pub(super) trait ManagerOperationValidator {
+    /// Checks that don't need the manager's context: all the operations in
+    /// the batch are of the same manager with sequential counters and the
+    /// branch is not outdated.
+    ///
+    /// Fees are checked by the mempool filter before the precheck.
+    fn validate_manager_operation(
+        &self,
+        branch_level: Option<Level>,
+        head_level: Level,
+    ) -> Result<(), ManagerOperationValidationError>;
+
+    /// Checks the counter against the manager's `context`, returns the
+    /// signature to verify with the manager's public key.
+    ///
+    /// If the public key is not known (not revealed yet), `None` is returned,
+    /// the signature is left to the protocol.
+    fn validate_manager_operation_with_context(
+        &self,
+        context: &ManagerContext,
+        signed_contents: &[u8],
+    ) -> Result<Option<SignedData>, ManagerOperationValidationError>;
+}
+
+impl ManagerOperationValidator for OperationDecodedContents {
+    fn validate_manager_operation(
+        &self,
+        branch_level: Option<Level>,
+        head_level: Level,
+    ) -> Result<(), ManagerOperationValidationError> {
+        match self {
+            OperationDecodedContents::Proto012(operation) => {
+                validate_manager_operation_012(operation, branch_level, head_level)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_manager_operation_with_context(
+        &self,
+        context: &ManagerContext,
+        signed_contents: &[u8],
+    ) -> Result<Option<SignedData>, ManagerOperationValidationError> {
+        match self {
+            OperationDecodedContents::Proto012(operation) => {
+                validate_manager_operation_with_context_012(operation, context, signed_contents)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+fn mutez(value: &Mutez) -> u64 {
+    u64::try_from(&value.0).unwrap_or(u64::MAX)
+}
+
+fn validate_manager_operation_012(
+    operation: &tezos_messages::protocol::proto_012::operation::Operation,
+    branch_level: Option<Level>,
+    head_level: Level,
+) -> Result<(), ManagerOperationValidationError> {
+    match branch_level {
+        Some(level) if level >= head_level - OPERATION_TTL => (),
+        _ => return Err(ManagerOperationValidationError::InvalidBranch),
+    }
+
+    let (source, first_counter) = operation
+        .contents
+        .first()
+        .and_then(|contents| contents.manager_source_and_counter())
+        .ok_or(ManagerOperationValidationError::InvalidContents)?;
+    let first_counter = mutez(first_counter);
+
+    for (index, contents) in operation.contents.iter().enumerate() {
+        let (op_source, counter) = contents
+            .manager_source_and_counter()
+            .ok_or(ManagerOperationValidationError::InvalidContents)?;
+        if op_source != source {
+            return Err(ManagerOperationValidationError::InconsistentSources);
+        }
+        if Some(mutez(counter)) != first_counter.checked_add(index as u64) {
+            return Err(ManagerOperationValidationError::InconsistentCounters);
+        }
+    }
+    Ok(())
+}
+
+fn validate_manager_operation_with_context_012(
+    operation: &tezos_messages::protocol::proto_012::operation::Operation,
+    context: &ManagerContext,
+    signed_contents: &[u8],
+) -> Result<Option<SignedData>, ManagerOperationValidationError> {
+    use tezos_messages::protocol::proto_012::operation::*;
+
+    let (source, counter) = operation
+        .contents
+        .first()
+        .and_then(|contents| contents.manager_source_and_counter())
+        .ok_or(ManagerOperationValidationError::InvalidContents)?;
+
+    if let Some(expected) = context.counter.map(|counter| counter.saturating_add(1)) {
+        let counter = mutez(counter);
+        if counter < expected {
+            return Err(ManagerOperationValidationError::CounterInThePast { counter, expected });
+        }
+    }
+
+    // the key is revealed by the first operation of the batch
+    let revealed_key = match operation.contents.first() {
+        Some(Contents::Reveal(RevealOperation { public_key, .. })) => {
+            if public_key.pk_hash().ok().as_ref() != Some(source) {
+                return Err(ManagerOperationValidationError::InconsistentPublicKey);
+            }
+            Some(public_key)
+        }
+        _ => None,
+    };
+
+    Ok(context
+        .public_key
+        .as_ref()
+        .or(revealed_key)
+        .map(|public_key| SignedData {
+            public_key: public_key.clone(),
+            signature: operation.signature.clone(),
+            watermark: SignatureWatermark::GenericOperation,
+            bytes: signed_contents.to_vec(),
+        }))
+}
+
+/// Signed part of the operation's binary encoding, that is without the signature.
+pub(super) fn signed_contents(operation_binary_encoding: &[u8]) -> Option<&[u8]> {
+    let len = operation_binary_encoding
+        .len()
+        .checked_sub(Signature::hash_size())?;
+    Some(&operation_binary_encoding[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use tezos_messages::{
+        base::{contract::ContractId, signature_public_key::SignaturePublicKeyHash},
+        p2p::binary_message::BinaryWrite,
+    };
+
+    use super::*;
+
+    fn source() -> SignaturePublicKeyHash {
+        SignaturePublicKeyHash::from_b58_hash("tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx").unwrap()
+    }
+
+    #[test]
+    fn test_manager_context_from_key_values() {
+        let prefix = ContractId::Implicit(source()).context_path().unwrap();
+        let public_key = SignaturePublicKey::from_b58_hash(
+            "edpkuBknW28nW72KG6RoHtYW7p12T6GKc7nAbwYX5m8Wd9sDVC9yav",
+        )
+        .unwrap();
+        let mut manager = vec![1];
+        manager.extend(public_key.as_bytes().unwrap());
+        let key = |name: &str| {
+            let mut key = prefix.clone();
+            key.push(name.to_string());
+            key
+        };
+
+        let context = ManagerContext::from_key_values(
+            &prefix,
+            &[
+                (key("balance"), vec![0x80, 0x01]),
+                (key("counter"), vec![0x2a]),
+                (key("manager"), manager),
+            ],
+        );
+        assert_eq!(
+            context,
+            ManagerContext {
+                public_key: Some(public_key),
+                counter: Some(42),
+            }
+        );
+
+        // not revealed yet, only the public key hash is known.
+        let mut manager = vec![0];
+        manager.extend(source().as_bytes().unwrap());
+        let context = ManagerContext::from_key_values(&prefix, &[(key("manager"), manager)]);
+        assert_eq!(context, ManagerContext::default());
+    }
+}
@@ -228,6 +228,73 @@ pub fn prechecker_reducer(state: &mut State, action: &ActionWithMeta) {
                     }
                 });
         }
+        Action::PrecheckerManagerContextPending(PrecheckerManagerContextPendingAction {
+            key,
+            token,
+        }) => {
+            prechecker_state
+                .operations
+                .entry(key.clone())
+                .and_modify(|state| {
+                    if let PrecheckerOperationState::DecodedContentReady {
+                        operation_decoded_contents,
+                    } = &state.state
+                    {
+                        state.state = PrecheckerOperationState::PendingManagerContext {
+                            operation_decoded_contents: operation_decoded_contents.clone(),
+                            token: *token,
+                        };
+                    }
+                });
+        }
+        Action::PrecheckerManagerContextReady(PrecheckerManagerContextReadyAction {
+            key,
+            context,
+        }) => {
+            prechecker_state
+                .operations
+                .entry(key.clone())
+                .and_modify(|state| {
+                    if let PrecheckerOperationState::PendingManagerContext {
+                        operation_decoded_contents,
+                        ..
+                    } = &state.state
+                    {
+                        state.state = PrecheckerOperationState::ManagerContextReady {
+                            operation_decoded_contents: operation_decoded_contents.clone(),
+                            context: context.clone(),
+                        };
+                    }
+                });
+        }
+        Action::PrecheckerManagerOperationValidationRefused(
+            PrecheckerManagerOperationValidationRefusedAction { key, error },
+        ) => {
+            let log = &state.log;
+            prechecker_state
+                .operations
+                .entry(key.clone())
+                .and_modify(|state| match &state.state {
+                    PrecheckerOperationState::DecodedContentReady {
+                        operation_decoded_contents,
+                    }
+                    | PrecheckerOperationState::ManagerContextReady {
+                        operation_decoded_contents,
+                        ..
+                    } => {
+                        trace!(log, "Prechecking manager operation refused";
+                               "operation" => FnValue(|_| key.operation.to_string()),
+                               "error" => FnValue(|_| error.to_string()),
+                               "duration" => FnValue(|_| format!("{:?}", action.id.duration_since(state.start)))
+                        );
+                        state.state = PrecheckerOperationState::ManagerRefused {
+                            operation_decoded_contents: operation_decoded_contents.clone(),
+                            error: error.clone(),
+                        };
+                    }
+                    _ => (),
+                });
+        }
         Action::PrecheckerProtocolNeeded(PrecheckerProtocolNeededAction { key }) => {
             let log = &state.log;
             prechecker_state
@@ -249,6 +316,20 @@ pub fn prechecker_reducer(state: &mut State, action: &ActionWithMeta) {
                             );
                             state.state = PrecheckerOperationState::ProtocolNeeded;
                         }
+                        PrecheckerOperationState::PendingManagerContext { .. } => {
+                            trace!(log, "Prechecking cannot be performed (manager context unavailable)";
+                                   "operation" => FnValue(|_| key.operation.to_string()),
+                                   "duration" => FnValue(|_| format!("{:?}", action.id.duration_since(state.start)))
+                            );
+                            state.state = PrecheckerOperationState::ProtocolNeeded;
+                        }
+                        PrecheckerOperationState::ManagerContextReady { .. } => {
+                            trace!(log, "Prechecking of manager operation passed";
+                                   "operation" => FnValue(|_| key.operation.to_string()),
+                                   "duration" => FnValue(|_| format!("{:?}", action.id.duration_since(state.start)))
+                            );
+                            state.state = PrecheckerOperationState::ProtocolNeeded;
+                        }
                         _ => (),
                     }
                 });
@@ -15,6 +15,7 @@ use crypto::{
 use redux_rs::ActionId;
 use tezos_encoding::{binary_reader::BinaryReaderError, binary_writer::BinaryWriterError};
 use tezos_messages::{
+    base::signature_public_key::SignaturePublicKeyHash,
     p2p::{
         binary_message::BinaryRead,
         encoding::{
@@ -26,11 +27,15 @@ use tezos_messages::{
 };
 
 use crate::{
+    protocol_runner::ProtocolRunnerToken,
     rights::{Delegate, EndorsingRights, RightsError, Slot},
     storage::kv_block_additional_data::Error as BlockAdditionalDataStorageError,
 };
 
-use super::{EndorsementValidationError, OperationProtocolData};
+use super::{
+    EndorsementValidationError, ManagerContext, ManagerOperationValidationError,
+    OperationProtocolData,
+};
 
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash)]
@@ -184,6 +189,18 @@ pub enum PrecheckerOperationState {
         operation_decoded_contents: OperationDecodedContents,
         error: EndorsementValidationError,
     },
+    PendingManagerContext {
+        operation_decoded_contents: OperationDecodedContents,
+        token: ProtocolRunnerToken,
+    },
+    ManagerContextReady {
+        operation_decoded_contents: OperationDecodedContents,
+        context: ManagerContext,
+    },
+    ManagerRefused {
+        operation_decoded_contents: OperationDecodedContents,
+        error: ManagerOperationValidationError,
+    },
     ProtocolNeeded,
     Error {
         error: PrecheckerError,
@@ -309,6 +326,19 @@ impl OperationDecodedContents {
         }
     }
 
+    /// Source of the manager operation, `None` if it's not a manager operation
+    /// or manager operations of the protocol are not prechecked.
+    pub(crate) fn manager_source(&self) -> Option<&SignaturePublicKeyHash> {
+        match self {
+            OperationDecodedContents::Proto012(operation) => operation
+                .contents
+                .first()?
+                .manager_source_and_counter()
+                .map(|(source, _)| source),
+            _ => None,
+        }
+    }
+
     pub(crate) fn endorsement_slot(&self) -> Option<Slot> {
         match self {
             OperationDecodedContents::Proto010(operation) => {
@@ -1,12 +1,7 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-use std::time::Instant;
-
-use crypto::{
-    hash::{BlockHash, ChainId},
-    CryptoError,
-};
+use crypto::hash::{BlockHash, ChainId};
 use slog::{debug, FnValue, Logger};
 use tezos_messages::{
     base::signature_public_key::SignatureWatermark,
@@ -14,6 +9,7 @@ use tezos_messages::{
 };
 
 use crate::rights::{Delegate, EndorsingRights};
+use crate::service::signature_verifier_service::SignedData;
 
 use super::OperationDecodedContents;
 
@@ -62,9 +58,11 @@ impl From<serde_json::Error> for EndorsementValidationError {
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Endorsement passing the checks, only its signature is left to verify.
+#[derive(Debug, Clone)]
 pub struct Applied {
     pub decoded_contents: OperationDecodedContents,
+    pub signed_data: SignedData,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -179,8 +177,9 @@ impl EndorsementValidator for OperationDecodedContents {
             }
         };
         match result {
-            Ok(_) => Ok(Applied {
+            Ok(signed_data) => Ok(Applied {
                 decoded_contents: self.clone(),
+                signed_data,
             }),
             Err(error) => Err(Refused {
                 decoded_contents: self.clone(),
@@ -196,11 +195,9 @@ fn validate_endorsement_010_granada(
     block_hash: &BlockHash,
     rights: &EndorsingRights,
     log: &Logger,
-) -> Result<(), EndorsementValidationError> {
+) -> Result<SignedData, EndorsementValidationError> {
     use tezos_messages::protocol::proto_010::operation::*;
 
-    let start = Instant::now();
-
     let contents = if operation.contents.len() == 1 {
         &operation.contents[0]
     } else {
@@ -218,15 +215,7 @@ fn validate_endorsement_010_granada(
                 return Err(EndorsementValidationError::InvalidEndorsementWrapper);
             }
 
-            validate_inlined_endorsement(
-                endorsement,
-                block_hash,
-                *slot,
-                rights,
-                chain_id,
-                start,
-                log,
-            )
+            validate_inlined_endorsement(endorsement, block_hash, *slot, rights, chain_id, log)
         }
         _ => Err(EndorsementValidationError::InvalidContents),
     }
@@ -238,11 +227,9 @@ fn validate_endorsement_011_hangzhou(
     block_hash: &BlockHash,
     rights: &EndorsingRights,
     log: &Logger,
-) -> Result<(), EndorsementValidationError> {
+) -> Result<SignedData, EndorsementValidationError> {
     use tezos_messages::protocol::proto_011::operation::*;
 
-    let start = Instant::now();
-
     let contents = if operation.contents.len() == 1 {
         &operation.contents[0]
     } else {
@@ -260,15 +247,7 @@ fn validate_endorsement_011_hangzhou(
                 return Err(EndorsementValidationError::InvalidEndorsementWrapper);
             }
 
-            validate_inlined_endorsement(
-                endorsement,
-                block_hash,
-                *slot,
-                rights,
-                chain_id,
-                start,
-                log,
-            )
+            validate_inlined_endorsement(endorsement, block_hash, *slot, rights, chain_id, log)
         }
         _ => Err(EndorsementValidationError::InvalidContents),
     }
@@ -280,9 +259,8 @@ fn validate_inlined_endorsement(
     slot: u16,
     rights: &EndorsingRights,
     chain_id: &ChainId,
-    start: Instant,
     log: &Logger,
-) -> Result<(), EndorsementValidationError> {
+) -> Result<SignedData, EndorsementValidationError> {
     if &endorsement.branch != block_hash {
         return Err(EndorsementValidationError::WrongEndorsementPredecessor);
     }
@@ -306,22 +284,10 @@ fn validate_inlined_endorsement(
            "branch" => FnValue(|_| endorsement.branch.to_base58_check()),
            "contents" => FnValue(|_| format!("{:?}", endorsement.operations))
     );
-    let verifying = Instant::now();
-    match delegate.verify_signature(
-        signature,
-        &SignatureWatermark::Endorsement(chain_id.clone()),
-        encoded,
-    ) {
-        Ok(true) => (),
-        Ok(_) => return Err(EndorsementValidationError::InlinedSignatureMismatch),
-        Err(CryptoError::Unsupported(_)) => {
-            return Err(EndorsementValidationError::UnsupportedPublicKey)
-        }
-        Err(_) => return Err(EndorsementValidationError::SignatureError),
-    }
-    let done = Instant::now();
-    debug!(log, "Endorsement signature verified";
-           "total" => FnValue(|_| format!("{:?}", done - start)),
-           "crypto" => FnValue(|_| format!("{:?}", done - verifying)));
-    Ok(())
+    Ok(SignedData {
+        public_key: delegate.clone(),
+        signature: signature.clone(),
+        watermark: SignatureWatermark::Endorsement(chain_id.clone()),
+        bytes: encoded,
+    })
 }
@@ -28,15 +28,23 @@ use crate::peer::remote_requests::block_header_get::peer_remote_requests_block_h
 use crate::peer::remote_requests::block_operations_get::peer_remote_requests_block_operations_get_reducer;
 use crate::peer::remote_requests::current_branch_get::peer_remote_requests_current_branch_get_reducer;
 use crate::peer::requests::potential_peers_get::peer_requests_potential_peers_get_reducer;
+use crate::peer::requests::protocols_get::peer_requests_protocols_get_reducer;
 
+use crate::peers::acl::peers_acl_reducer;
 use crate::peers::add::multi::peers_add_multi_reducer;
 use crate::peers::add::peers_add_reducer;
+use crate::peers::address_book::peers_address_book_reducer;
+use crate::peers::bandwidth::peers_bandwidth_reducer;
 use crate::peers::check::timeouts::peers_check_timeouts_reducer;
+use crate::peers::connect::peers_connect_reducer;
 use crate::peers::dns_lookup::peers_dns_lookup_reducer;
 use crate::peers::graylist::peers_graylist_reducer;
 use crate::peers::remove::peers_remove_reducer;
+use crate::peers::score::peers_score_reducer;
+use crate::peers::swap::peers_swap_reducer;
 
 use crate::mempool::mempool_reducer;
+use crate::mempool::storage::mempool_storage_reducer;
 use crate::mempool::validator::mempool_validator_reducer;
 
 use crate::prechecker::prechecker_reducer;
@@ -112,18 +120,26 @@ pub fn reducer(state: &mut State, action: &ActionWithMeta) {
         peer_chunk_read_reducer,
         peer_disconnection_reducer,
         peer_requests_potential_peers_get_reducer,
+        peer_requests_protocols_get_reducer,
         peer_remote_requests_block_header_get_reducer,
         peer_remote_requests_block_operations_get_reducer,
         peer_remote_requests_current_branch_get_reducer,
         peers_dns_lookup_reducer,
+        peers_address_book_reducer,
+        peers_bandwidth_reducer,
+        peers_swap_reducer,
+        peers_acl_reducer,
         peers_add_multi_reducer,
         peers_add_reducer,
         peers_remove_reducer,
+        peers_connect_reducer,
         peers_check_timeouts_reducer,
         peers_graylist_reducer,
+        peers_score_reducer,
         bootstrap_reducer,
         mempool_validator_reducer,
         mempool_reducer,
+        mempool_storage_reducer,
         rights_reducer,
         current_head_precheck_reducer,
         stats_current_head_reducer,
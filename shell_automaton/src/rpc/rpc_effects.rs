@@ -1,23 +1,36 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-use crypto::hash::{BlockHash, ChainId};
+use crypto::hash::{BlockHash, ChainId, CryptoboxPublicKeyHash};
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use tezos_messages::p2p::encoding::block_header::BlockHeader;
 
 use crate::block_applier::BlockApplierApplyState;
 use crate::block_applier::BlockApplierEnqueueBlockAction;
 use crate::mempool::mempool_actions::{
-    BlockInjectAction, MempoolAskCurrentHeadAction, MempoolGetPendingOperationsAction,
-    MempoolOperationInjectAction, MempoolRegisterOperationsStreamAction,
-    MempoolRpcEndorsementsStatusGetAction,
+    BlockInjectAction, MempoolAskCurrentHeadAction, MempoolFilterSetAction,
+    MempoolGetPendingOperationsAction, MempoolOperationBanAction, MempoolOperationInjectAction,
+    MempoolOperationUnbanAction, MempoolOperationUnbanAllAction,
+    MempoolRegisterOperationsStreamAction, MempoolRpcEndorsementsStatusGetAction,
 };
 use crate::mempool::OperationKind;
+use crate::peers::acl::{
+    peers_acl_is_disconnected, PeersAclIncomingSetAction, PeersAclTrustedAddAction,
+    PeersAclTrustedRemoveAction,
+};
+use crate::peers::canonical_address;
+use crate::peers::connect::PeersConnectPointAction;
+use crate::peers::graylist::{
+    PeersGraylistAddressBanAction, PeersGraylistAddressUnbanAction, PeersGraylistClearAction,
+};
 use crate::rights::{rights_actions::RightsRpcGetAction, RightsKey};
-use crate::service::rpc_service::{RpcRequest, RpcRequestStream};
+use crate::service::rpc_service::{
+    NetworkCommand, NetworkCommandError, NetworkStats, NetworkTarget, RpcRequest, RpcRequestStream,
+};
 use crate::service::{RpcService, Service};
 use crate::storage::request::StorageRequestStatus;
-use crate::{Action, ActionWithMeta, Store};
+use crate::{Action, ActionWithMeta, EnablingCondition, State, Store};
 
 use super::rpc_actions::RpcInjectBlockAction;
 use super::rpc_actions::RpcRejectOutdatedInjectedBlockAction;
@@ -27,6 +40,123 @@ use super::rpc_actions::{
 };
 use super::BootstrapState;
 
+fn network_command_check(
+    state: &State,
+    address: SocketAddr,
+    peer_id: &Option<CryptoboxPublicKeyHash>,
+    command: NetworkCommand,
+    previous: &[NetworkCommand],
+) -> Result<(), NetworkCommandError> {
+    let unbanned = previous
+        .iter()
+        .any(|command| matches!(command, NetworkCommand::Unban | NetworkCommand::Open));
+    let enabled = match command {
+        NetworkCommand::Connect => {
+            let banned = previous.contains(&NetworkCommand::Ban)
+                || (!unbanned && state.peers.is_blacklisted(&address.ip()));
+            if banned {
+                return Err(NetworkCommandError::NotAllowed);
+            }
+            // the blacklist is checked above, as the ip may be unbanned by
+            // the previous command.
+            peers_acl_is_disconnected(state, &address)
+        }
+        NetworkCommand::Ban => {
+            let ban = PeersGraylistAddressBanAction {
+                address,
+                peer_id: peer_id.clone(),
+            };
+            // trusted peers can't be banned.
+            if !ban.is_enabled(state) {
+                return Err(NetworkCommandError::NotAllowed);
+            }
+            true
+        }
+        NetworkCommand::Unban | NetworkCommand::Open => true,
+        NetworkCommand::Trust => PeersAclTrustedAddAction { address }.is_enabled(state),
+        NetworkCommand::Untrust => PeersAclTrustedRemoveAction { address }.is_enabled(state),
+    };
+    if enabled {
+        Ok(())
+    } else {
+        Err(NetworkCommandError::Unchanged)
+    }
+}
+
+fn network_command_apply<S: Service>(
+    store: &mut Store<S>,
+    address: SocketAddr,
+    peer_id: &Option<CryptoboxPublicKeyHash>,
+    command: NetworkCommand,
+) {
+    match command {
+        NetworkCommand::Connect => {
+            store.dispatch(PeersConnectPointAction { address });
+        }
+        NetworkCommand::Ban => {
+            store.dispatch(PeersGraylistAddressBanAction {
+                address,
+                peer_id: peer_id.clone(),
+            });
+        }
+        NetworkCommand::Unban => {
+            store.dispatch(PeersGraylistAddressUnbanAction { address });
+        }
+        NetworkCommand::Trust => {
+            store.dispatch(PeersAclTrustedAddAction { address });
+        }
+        NetworkCommand::Untrust => {
+            store.dispatch(PeersAclTrustedRemoveAction { address });
+        }
+        NetworkCommand::Open => {
+            store.dispatch(PeersAclTrustedRemoveAction { address });
+            store.dispatch(PeersGraylistAddressUnbanAction { address });
+        }
+    }
+}
+
+/// Applies the `commands` only if every one of them is allowed, so the
+/// request is never applied halfway.
+fn network_command<S: Service>(
+    store: &mut Store<S>,
+    target: NetworkTarget,
+    commands: &[NetworkCommand],
+) -> Result<(), NetworkCommandError> {
+    let peers = &store.state().peers;
+    let (address, peer_id) = match target {
+        NetworkTarget::Point(address) => {
+            let address = canonical_address(address);
+            let peer_id = peers
+                .get_handshaked(&address)
+                .map(|peer| peer.public_key_hash.clone());
+            (address, peer_id)
+        }
+        NetworkTarget::PeerId(peer_id) => {
+            let address = peers
+                .handshaked_iter()
+                .find(|(_, peer)| peer.public_key_hash == peer_id)
+                .map(|(address, _)| address)
+                .or_else(|| peers.banned_peer_ids.get(&peer_id).copied())
+                .ok_or(NetworkCommandError::UnknownPeer)?;
+            (address, Some(peer_id))
+        }
+    };
+
+    for (index, command) in commands.iter().enumerate() {
+        network_command_check(
+            store.state(),
+            address,
+            &peer_id,
+            *command,
+            &commands[..index],
+        )?;
+    }
+    for command in commands {
+        network_command_apply(store, address, &peer_id, *command);
+    }
+    Ok(())
+}
+
 pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
     match &action.action {
         Action::WakeupEvent(_) => {
@@ -104,6 +234,71 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
                             .rpc()
                             .respond(rpc_id, serde_json::Value::Null);
                     }
+                    RpcRequest::GetNetworkStats { channel } => {
+                        let state = store.state.get();
+                        let bandwidth = &state.peers.bandwidth;
+                        let _ = channel.send(NetworkStats {
+                            total_sent: bandwidth.total_sent,
+                            total_recv: bandwidth.total_recv,
+                            current_inflow: bandwidth.current_inflow,
+                            current_outflow: bandwidth.current_outflow,
+                            bandwidth_limit: state.config.bandwidth_limit.clone(),
+                        });
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::GetPeersAcl { channel } => {
+                        let _ = channel.send(store.state().peers.acl.clone());
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::UpdatePeersAcl { update, channel } => {
+                        if let Some(trusted) = update.trusted {
+                            let acl = &store.state().peers.acl;
+                            let removed = acl
+                                .trusted
+                                .iter()
+                                .filter(|address| !trusted.contains(address))
+                                .copied()
+                                .collect::<Vec<_>>();
+                            for address in removed {
+                                store.dispatch(PeersAclTrustedRemoveAction { address });
+                            }
+                            for address in trusted {
+                                store.dispatch(PeersAclTrustedAddAction { address });
+                            }
+                        }
+                        if update.incoming_allow.is_some() || update.incoming_deny.is_some() {
+                            let acl = &store.state().peers.acl;
+                            let allow = update
+                                .incoming_allow
+                                .unwrap_or_else(|| acl.incoming_allow.clone());
+                            let deny = update
+                                .incoming_deny
+                                .unwrap_or_else(|| acl.incoming_deny.clone());
+                            store.dispatch(PeersAclIncomingSetAction { allow, deny });
+                        }
+                        let _ = channel.send(store.state().peers.acl.clone());
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::NetworkCommand { target, commands } => {
+                        let result = network_command(store, target, &commands);
+                        store.service().rpc().respond(rpc_id, result);
+                    }
+                    RpcRequest::NetworkGreylistClear => {
+                        store.dispatch(PeersGraylistClearAction {});
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
                     RpcRequest::GetActionKindStats { channel } => {
                         let data = store
                             .service
@@ -219,6 +414,20 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
                         operation_hash,
                         injected,
                     } => {
+                        let state = store.state();
+                        let check = match state.config.disable_mempool {
+                            true => Ok(None),
+                            false => state
+                                .mempool
+                                .check_new_operation(&operation_hash, &operation),
+                        };
+                        if let Err(reason) = check {
+                            store
+                                .service
+                                .rpc()
+                                .respond(rpc_id, serde_json::Value::String(reason.to_string()));
+                            continue;
+                        }
                         let injected_timestamp = store.monotonic_to_time(injected);
                         store.dispatch(MempoolOperationInjectAction {
                             operation,
@@ -313,6 +522,55 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: &ActionWithMeta) {
                             .collect();
                         let _ = channel.send(stats);
                     }
+                    RpcRequest::GetMempoolFilter { channel } => {
+                        let _ = channel.send(store.state().mempool.filter.clone());
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::SetMempoolFilter { filter, channel } => {
+                        store.dispatch(MempoolFilterSetAction { filter });
+                        let _ = channel.send(());
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::MempoolOperationBan {
+                        operation_hash,
+                        channel,
+                    } => {
+                        store.dispatch(MempoolOperationBanAction {
+                            hash: operation_hash,
+                        });
+                        let _ = channel.send(());
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::MempoolOperationUnban {
+                        operation_hash,
+                        channel,
+                    } => {
+                        let unbanned = store.dispatch(MempoolOperationUnbanAction {
+                            hash: operation_hash,
+                        });
+                        let _ = channel.send(unbanned);
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
+                    RpcRequest::MempoolOperationUnbanAll { channel } => {
+                        store.dispatch(MempoolOperationUnbanAllAction {});
+                        let _ = channel.send(());
+                        store
+                            .service()
+                            .rpc()
+                            .respond(rpc_id, serde_json::Value::Null);
+                    }
                 }
             }
         }
@@ -1,10 +1,12 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-use std::net::{IpAddr, SocketAddr};
+use std::net::SocketAddr;
 
 pub use dns_lookup::LookupErrorKind as DnsLookupError;
 
+use crate::peers::canonical_address;
+
 pub trait DnsService {
     /// Try to resolve common peer name into Socket Address representation.
     fn resolve_dns_name_to_peer_address(
@@ -44,16 +46,8 @@ impl DnsService for DnsServiceDefault {
                     dns_lookup::AddrFamily::Inet.eq(&info.address)
                         || dns_lookup::AddrFamily::Inet6.eq(&info.address)
                 })
-                .map(|info: dns_lookup::AddrInfo| {
-                    // convert to uniform IPv6 format
-                    match &info.sockaddr {
-                        SocketAddr::V4(ipv4) => {
-                            // convert ipv4 to ipv6
-                            SocketAddr::new(IpAddr::V6(ipv4.ip().to_ipv6_mapped()), ipv4.port())
-                        }
-                        SocketAddr::V6(_) => info.sockaddr,
-                    }
-                })
+                // IPv4-mapped addresses are converted to IPv4, same as peers' addresses.
+                .map(|info: dns_lookup::AddrInfo| canonical_address(info.sockaddr))
                 .collect();
         Ok(addrs)
     }
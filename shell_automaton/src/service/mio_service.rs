@@ -1,23 +1,27 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-use mio::net::{TcpListener, TcpSocket, TcpStream};
+use mio::net::{TcpListener, TcpStream};
 use serde::{Deserialize, Serialize};
 use slab::Slab;
+use socket2::{Domain, Protocol, Socket, Type};
 use std::io::{self, Read, Write};
-use std::net::{IpAddr, SocketAddr};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::event::{Event, P2pPeerEvent, P2pPeerUnknownEvent, P2pServerEvent, WakeupEvent};
 use crate::io_error_kind::IOErrorKind;
 use crate::peer::PeerToken;
+use crate::peers::canonical_address;
 
 pub type MioInternalEvent = mio::event::Event;
 pub type MioInternalEventsContainer = mio::Events;
 
 /// We will receive listening socket server events with this token, when
 /// there are incoming connections that need to be accepted.
+///
+/// All listening sockets are registered with this token.
 pub const MIO_SERVER_TOKEN: mio::Token = mio::Token(usize::MAX);
 
 /// Event with this token will be issued, when `mio::Waker::wake` is called.
@@ -133,7 +137,8 @@ impl<'a, S: Write> MioPeerRefMut<'a, S> {
 }
 
 pub struct MioServiceDefault {
-    listen_addr: SocketAddr,
+    /// Addresses (IPv4 or IPv6) where we listen for incoming connections.
+    listen_addrs: Vec<SocketAddr>,
 
     /// Backlog size for incoming connections.
     ///
@@ -148,7 +153,8 @@ pub struct MioServiceDefault {
 
     poll: mio::Poll,
     waker: Arc<mio::Waker>,
-    server: Option<TcpListener>,
+    /// Listening sockets, empty if we aren't listening.
+    servers: Vec<TcpListener>,
 
     peers: Slab<MioPeer<TcpStream>>,
 }
@@ -156,19 +162,19 @@ pub struct MioServiceDefault {
 impl MioServiceDefault {
     const DEFAULT_BACKLOG_SIZE: u32 = 255;
 
-    pub fn new(listen_addr: SocketAddr, buffer_size: usize) -> Self {
+    pub fn new(listen_addrs: Vec<SocketAddr>, buffer_size: usize) -> Self {
         let poll = mio::Poll::new().expect("failed to create mio::Poll");
         let waker = Arc::new(
             mio::Waker::new(poll.registry(), MIO_WAKE_TOKEN).expect("failed to create mio::Waker"),
         );
 
         Self {
-            listen_addr,
+            listen_addrs,
             backlog_size: Self::DEFAULT_BACKLOG_SIZE,
             buffer: vec![0; buffer_size],
             poll,
             waker,
-            server: None,
+            servers: vec![],
             peers: Slab::new(),
         }
     }
@@ -225,34 +231,50 @@ impl MioService for MioServiceDefault {
     }
 
     fn peer_connection_incoming_listen_start(&mut self) -> io::Result<()> {
-        if self.server.is_none() {
-            let socket = match self.listen_addr.ip() {
-                IpAddr::V4(_) => TcpSocket::new_v4()?,
-                IpAddr::V6(_) => TcpSocket::new_v6()?,
-            };
+        if self.servers.is_empty() {
+            let mut servers = Vec::with_capacity(self.listen_addrs.len());
+
+            for listen_addr in self.listen_addrs.iter().copied() {
+                let socket = Socket::new(
+                    Domain::for_address(listen_addr),
+                    Type::STREAM,
+                    Some(Protocol::TCP),
+                )?;
+
+                // IPv6 listener accepts only IPv6 connections, otherwise
+                // it would conflict with IPv4 listener on the same port.
+                // To listen on both families `0.0.0.0` and `[::]` must be set.
+                if listen_addr.is_ipv6() {
+                    socket.set_only_v6(true)?;
+                }
 
-            // read more details about why not on windows in mio docs
-            // for [mio::TcpListener::bind].
-            #[cfg(not(windows))]
-            socket.set_reuseaddr(true)?;
+                // read more details about why not on windows in mio docs
+                // for [mio::TcpListener::bind].
+                #[cfg(not(windows))]
+                socket.set_reuse_address(true)?;
 
-            socket.bind(self.listen_addr)?;
+                socket.set_nonblocking(true)?;
+                socket.bind(&listen_addr.into())?;
+                socket.listen(self.backlog_size as i32)?;
 
-            let mut server = socket.listen(self.backlog_size)?;
+                let mut server = TcpListener::from_std(socket.into());
 
-            self.poll.registry().register(
-                &mut server,
-                MIO_SERVER_TOKEN,
-                mio::Interest::READABLE,
-            )?;
+                self.poll.registry().register(
+                    &mut server,
+                    MIO_SERVER_TOKEN,
+                    mio::Interest::READABLE,
+                )?;
+
+                servers.push(server);
+            }
 
-            self.server = Some(server);
+            self.servers = servers;
         }
         Ok(())
     }
 
     fn peer_connection_incoming_listen_stop(&mut self) {
-        drop(self.server.take());
+        self.servers.clear();
     }
 
     fn peer_connection_incoming_accept(
@@ -260,14 +282,30 @@ impl MioService for MioServiceDefault {
     ) -> Result<(PeerToken, MioPeerRefMut<Self::PeerStream>), PeerConnectionIncomingAcceptError>
     {
         let buffer = &mut self.buffer;
-        let server = &mut self.server;
         let poll = &mut self.poll;
         let peers = &mut self.peers;
 
-        if let Some(server) = server.as_mut() {
-            let (mut stream, address) = server
-                .accept()
-                .map_err(PeerConnectionIncomingAcceptError::accept_error)?;
+        if self.servers.is_empty() {
+            return Err(PeerConnectionIncomingAcceptError::ServerNotListening);
+        }
+
+        // Accept from the first listener with pending connection. Only
+        // when all of them would block, we are done with accepting.
+        let mut accepted = None;
+        for server in self.servers.iter() {
+            match server.accept() {
+                Ok(v) => {
+                    accepted = Some(v);
+                    break;
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(err) => return Err(PeerConnectionIncomingAcceptError::accept_error(err)),
+            }
+        }
+
+        if let Some((mut stream, address)) = accepted {
+            // same form as the addresses of the outgoing connections.
+            let address = canonical_address(address);
 
             let peer_entry = peers.vacant_entry();
             let token = mio::Token(peer_entry.key());
@@ -285,7 +323,7 @@ impl MioService for MioServiceDefault {
 
             Ok((PeerToken::new_unchecked(token.0), peer_ref_mut))
         } else {
-            Err(PeerConnectionIncomingAcceptError::ServerNotListening)
+            Err(PeerConnectionIncomingAcceptError::WouldBlock)
         }
     }
 
@@ -32,6 +32,12 @@ pub use actors_service::{ActorsService, ActorsServiceDefault};
 pub mod statistics_service;
 pub use statistics_service::{BlockApplyStats, BlockPeerStats, StatisticsService};
 
+pub mod p2p_recorder_service;
+pub use p2p_recorder_service::{P2pRecorderConfig, P2pRecorderService};
+
+pub mod signature_verifier_service;
+pub use signature_verifier_service::{SignatureVerifierService, SignatureVerifierServiceDefault};
+
 pub trait Service: TimeService {
     type Randomness: RandomnessService;
     type Dns: DnsService;
@@ -40,6 +46,7 @@ pub trait Service: TimeService {
     type ProtocolRunner: ProtocolRunnerService;
     type Rpc: RpcService;
     type Actors: ActorsService;
+    type SignatureVerifier: SignatureVerifierService;
 
     fn randomness(&mut self) -> &mut Self::Randomness;
 
@@ -55,11 +62,17 @@ pub trait Service: TimeService {
 
     fn actors(&mut self) -> &mut Self::Actors;
 
+    fn signature_verifier(&mut self) -> &mut Self::SignatureVerifier;
+
     fn prevalidator(&mut self) -> &mut Self::ProtocolRunner;
 
     fn statistics(&mut self) -> Option<&mut StatisticsService> {
         None
     }
+
+    fn p2p_recorder(&mut self) -> Option<&mut P2pRecorderService> {
+        None
+    }
 }
 
 pub struct ServiceDefault {
@@ -70,7 +83,9 @@ pub struct ServiceDefault {
     pub protocol_runner: ProtocolRunnerServiceDefault,
     pub rpc: RpcServiceDefault,
     pub actors: ActorsServiceDefault,
+    pub signature_verifier: SignatureVerifierServiceDefault,
     pub statistics: Option<StatisticsService>,
+    pub p2p_recorder: Option<P2pRecorderService>,
 }
 
 impl TimeService for ServiceDefault {}
@@ -83,6 +98,7 @@ impl Service for ServiceDefault {
     type ProtocolRunner = ProtocolRunnerServiceDefault;
     type Rpc = RpcServiceDefault;
     type Actors = ActorsServiceDefault;
+    type SignatureVerifier = SignatureVerifierServiceDefault;
 
     fn randomness(&mut self) -> &mut Self::Randomness {
         &mut self.randomness
@@ -112,6 +128,10 @@ impl Service for ServiceDefault {
         &mut self.actors
     }
 
+    fn signature_verifier(&mut self) -> &mut Self::SignatureVerifier {
+        &mut self.signature_verifier
+    }
+
     fn prevalidator(&mut self) -> &mut Self::ProtocolRunner {
         self.protocol_runner()
     }
@@ -119,4 +139,8 @@ impl Service for ServiceDefault {
     fn statistics(&mut self) -> Option<&mut StatisticsService> {
         self.statistics.as_mut()
     }
+
+    fn p2p_recorder(&mut self) -> Option<&mut P2pRecorderService> {
+        self.p2p_recorder.as_mut()
+    }
 }
@@ -0,0 +1,326 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Opt-in recorder of the decrypted p2p traffic.
+//!
+//! Every record is a single line of json in the current recording file. Once
+//! the file grows over the configured size, it is rotated: `p2p-recording.jsonl`
+//! is renamed to `p2p-recording.1.jsonl`, `p2p-recording.1.jsonl` to
+//! `p2p-recording.2.jsonl` and so on, the oldest file is removed.
+//!
+//! Records are written by a background thread, so the state machine isn't
+//! blocked by the disk. If the writer can't keep up, new records are dropped.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crypto::hash::CryptoboxPublicKeyHash;
+use tezos_messages::p2p::encoding::peer::PeerMessage;
+
+pub const P2P_RECORDING_FILE_NAME: &str = "p2p-recording";
+const P2P_RECORDING_FILE_EXTENSION: &str = "jsonl";
+
+/// How many records can wait for the writer thread.
+const P2P_RECORDER_CHANNEL_BOUND: usize = 4096;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum P2pRecordDirection {
+    /// Message received from the peer.
+    Incoming,
+    /// Message sent to the peer.
+    Outgoing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum P2pRecordEvent {
+    Handshaked {
+        public_key_hash: CryptoboxPublicKeyHash,
+        port: u16,
+        disable_mempool: bool,
+        private_node: bool,
+    },
+    Message {
+        direction: P2pRecordDirection,
+        /// Decrypted binary message, hex encoded.
+        #[serde(with = "hex_bytes")]
+        raw: Vec<u8>,
+        /// Decoded message, `None` if the message couldn't be decoded.
+        message: Option<PeerMessage>,
+    },
+    Disconnected,
+}
+
+/// Single recorded event of the connection with the peer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pRecord {
+    /// Time of the event in nanoseconds since unix epoch.
+    pub time: u64,
+    pub address: SocketAddr,
+    pub event: P2pRecordEvent,
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        hex::decode(hex).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct P2pRecorderConfig {
+    /// Directory where the recording files are stored.
+    pub dir: PathBuf,
+    /// Size in bytes after which the recording file is rotated.
+    pub max_file_size: u64,
+    /// How many files (including the current one) are kept.
+    pub max_files: usize,
+}
+
+impl P2pRecorderConfig {
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
+    pub const DEFAULT_MAX_FILES: usize = 10;
+}
+
+/// Path of the recording file, `index` 0 is the current one.
+pub fn p2p_recording_file_path(dir: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        dir.join(format!(
+            "{}.{}",
+            P2P_RECORDING_FILE_NAME, P2P_RECORDING_FILE_EXTENSION
+        ))
+    } else {
+        dir.join(format!(
+            "{}.{}.{}",
+            P2P_RECORDING_FILE_NAME, index, P2P_RECORDING_FILE_EXTENSION
+        ))
+    }
+}
+
+/// Writes the records into the recording files, rotating them.
+struct P2pRecordWriter {
+    config: P2pRecorderConfig,
+    file: LineWriter<File>,
+    file_size: u64,
+}
+
+impl P2pRecordWriter {
+    fn new(config: P2pRecorderConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let file = Self::open(&config.dir)?;
+        let file_size = file.metadata()?.len();
+        Ok(Self {
+            config,
+            file: LineWriter::new(file),
+            file_size,
+        })
+    }
+
+    fn open(dir: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(p2p_recording_file_path(dir, 0))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let dir = &self.config.dir;
+        let max_files = self.config.max_files.max(1);
+
+        let oldest = p2p_recording_file_path(dir, max_files - 1);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+        for index in (0..max_files - 1).rev() {
+            let path = p2p_recording_file_path(dir, index);
+            if path.exists() {
+                fs::rename(path, p2p_recording_file_path(dir, index + 1))?;
+            }
+        }
+
+        self.file = LineWriter::new(Self::open(dir)?);
+        self.file_size = 0;
+        Ok(())
+    }
+
+    fn write(&mut self, record: &P2pRecord) -> io::Result<()> {
+        if self.file_size >= self.config.max_file_size {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file_size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Sends the records to the thread writing them.
+///
+/// Records still in the queue are written when the service is dropped.
+pub struct P2pRecorderService {
+    sender: Option<mpsc::SyncSender<P2pRecord>>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl P2pRecorderService {
+    pub fn new(config: P2pRecorderConfig, log: slog::Logger) -> io::Result<Self> {
+        // open the recording file right away, so the errors are reported on startup.
+        let mut writer = P2pRecordWriter::new(config)?;
+        let (sender, receiver) = mpsc::sync_channel::<P2pRecord>(P2P_RECORDER_CHANNEL_BOUND);
+
+        let worker = thread::Builder::new()
+            .name("p2p-recorder-thread".to_owned())
+            .spawn(move || {
+                while let Ok(record) = receiver.recv() {
+                    if let Err(err) = writer.write(&record) {
+                        slog::warn!(&log, "Failed to record p2p traffic";
+                            "peer" => format!("{}", record.address),
+                            "error" => format!("{:?}", err));
+                    }
+                }
+                if let Err(err) = writer.file.flush() {
+                    slog::warn!(&log, "Failed to flush p2p recording";
+                        "error" => format!("{:?}", err));
+                }
+            })?;
+
+        Ok(Self {
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+
+    /// Queues the record for the writer thread.
+    ///
+    /// Fails without blocking if the queue is full or the writer thread has stopped.
+    pub fn record(&mut self, record: P2pRecord) -> io::Result<()> {
+        let sender = match self.sender.as_ref() {
+            Some(v) => v,
+            None => return Err(io::ErrorKind::BrokenPipe.into()),
+        };
+        sender.try_send(record).map_err(|err| match err {
+            mpsc::TrySendError::Full(_) => {
+                io::Error::new(io::ErrorKind::WouldBlock, "p2p recorder queue is full")
+            }
+            mpsc::TrySendError::Disconnected(_) => {
+                io::Error::new(io::ErrorKind::BrokenPipe, "p2p recorder thread has stopped")
+            }
+        })
+    }
+}
+
+impl Drop for P2pRecorderService {
+    fn drop(&mut self) {
+        // closing the channel stops the writer once the queue is written.
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Reads records from the recording file.
+pub fn p2p_recording_read<P: AsRef<Path>>(
+    path: P,
+) -> io::Result<impl Iterator<Item = io::Result<P2pRecord>>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: u64) -> P2pRecord {
+        P2pRecord {
+            time,
+            address: ([1, 2, 3, 4], 9732).into(),
+            event: P2pRecordEvent::Message {
+                direction: P2pRecordDirection::Incoming,
+                raw: vec![0, 0x10],
+                message: Some(PeerMessage::Bootstrap),
+            },
+        }
+    }
+
+    #[test]
+    fn test_p2p_recorder_rotation() -> io::Result<()> {
+        let dir = std::env::temp_dir().join("__test_p2p_recorder_rotation");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut writer = P2pRecordWriter::new(P2pRecorderConfig {
+            dir: dir.clone(),
+            max_file_size: 1,
+            max_files: 2,
+        })?;
+        for time in 0..3 {
+            writer.write(&record(time))?;
+        }
+
+        // every record is in its own file, the oldest one was removed.
+        assert!(!p2p_recording_file_path(&dir, 2).exists());
+        let times = |index| -> io::Result<Vec<u64>> {
+            p2p_recording_read(p2p_recording_file_path(&dir, index))?
+                .map(|record| record.map(|record| record.time))
+                .collect()
+        };
+        assert_eq!(times(0)?, vec![2]);
+        assert_eq!(times(1)?, vec![1]);
+
+        let recorded = p2p_recording_read(p2p_recording_file_path(&dir, 0))?
+            .next()
+            .unwrap()?;
+        match recorded.event {
+            P2pRecordEvent::Message { raw, message, .. } => {
+                assert_eq!(raw, vec![0, 0x10]);
+                assert!(matches!(message, Some(PeerMessage::Bootstrap)));
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+
+        fs::remove_dir_all(&dir)
+    }
+
+    #[test]
+    fn test_p2p_recorder_writer_thread() -> io::Result<()> {
+        let dir = std::env::temp_dir().join("__test_p2p_recorder_writer_thread");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut recorder = P2pRecorderService::new(
+            P2pRecorderConfig {
+                dir: dir.clone(),
+                max_file_size: P2pRecorderConfig::DEFAULT_MAX_FILE_SIZE,
+                max_files: P2pRecorderConfig::DEFAULT_MAX_FILES,
+            },
+            slog::Logger::root(slog::Discard, slog::o!()),
+        )?;
+        for time in 0..10 {
+            recorder.record(record(time))?;
+        }
+        // the queued records are written before the writer thread stops.
+        drop(recorder);
+
+        let times = p2p_recording_read(p2p_recording_file_path(&dir, 0))?
+            .map(|record| record.map(|record| record.time))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(times, (0..10).collect::<Vec<_>>());
+
+        fs::remove_dir_all(&dir)
+    }
+}
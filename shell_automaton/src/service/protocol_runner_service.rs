@@ -15,10 +15,13 @@ use tezos_api::ffi::{
     InitProtocolContextResult, PrevalidatorWrapper, TezosRuntimeConfiguration,
     ValidateOperationRequest, ValidateOperationResponse,
 };
-use tezos_context_api::{PatchContext, TezosContextStorageConfiguration};
+use tezos_context_api::{
+    ContextKeyOwned, ContextValue, PatchContext, TezosContextStorageConfiguration,
+};
 use tezos_protocol_ipc_client::{ProtocolRunnerApi, ProtocolRunnerError, ProtocolServiceError};
 use tezos_protocol_ipc_messages::{
-    GenesisResultDataParams, InitProtocolContextParams, ProtocolMessage,
+    ContextGetKeyValuesByPrefixRequest, GenesisResultDataParams, InitProtocolContextParams,
+    ProtocolMessage,
 };
 
 use crate::protocol_runner::ProtocolRunnerToken;
@@ -64,6 +67,13 @@ pub enum ProtocolRunnerResult {
         ),
     ),
 
+    ContextKeyValuesByPrefixGet(
+        (
+            ProtocolRunnerToken,
+            Result<Option<Vec<(ContextKeyOwned, ContextValue)>>, ProtocolServiceError>,
+        ),
+    ),
+
     ApplyBlock(
         (
             ProtocolRunnerToken,
@@ -97,6 +107,7 @@ impl ProtocolRunnerResult {
             Self::InitContextIpcServer((token, _)) => Some(*token),
             Self::GenesisCommitResultGet((token, _)) => Some(*token),
             Self::LatestContextHashesGet((token, _)) => Some(*token),
+            Self::ContextKeyValuesByPrefixGet((token, _)) => Some(*token),
             Self::ApplyBlock((token, _)) => Some(*token),
             Self::BeginConstruction((token, _)) => Some(*token),
             Self::ValidateOperation((token, _)) => Some(*token),
@@ -140,6 +151,13 @@ pub trait ProtocolRunnerService {
 
     fn get_latest_context_hashes(&mut self, count: i64) -> ProtocolRunnerToken;
 
+    /// Read all key-value pairs under the `prefix` from the context with the given hash.
+    fn get_context_key_values_by_prefix(
+        &mut self,
+        context_hash: ContextHash,
+        prefix: ContextKeyOwned,
+    ) -> ProtocolRunnerToken;
+
     fn apply_block(&mut self, req: ApplyBlockRequest);
 
     // Prevalidator
@@ -295,6 +313,23 @@ impl ProtocolRunnerService for ProtocolRunnerServiceDefault {
         token
     }
 
+    fn get_context_key_values_by_prefix(
+        &mut self,
+        context_hash: ContextHash,
+        prefix: ContextKeyOwned,
+    ) -> ProtocolRunnerToken {
+        let token = self.new_token();
+        let message =
+            ProtocolMessage::ContextGetKeyValuesByPrefix(ContextGetKeyValuesByPrefixRequest {
+                context_hash,
+                prefix,
+            });
+        self.channel
+            .blocking_send(ProtocolRunnerRequest::Message((token, message)))
+            .unwrap();
+        token
+    }
+
     fn apply_block(&mut self, req: ApplyBlockRequest) {
         let token = self.new_token();
         let message = ProtocolMessage::ApplyBlockCall(req);
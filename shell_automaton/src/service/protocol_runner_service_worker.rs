@@ -255,6 +255,16 @@ impl ProtocolRunnerServiceWorker {
                     .send(ProtocolRunnerResult::LatestContextHashesGet((token, res)))
                     .await;
             }
+            ProtocolMessage::ContextGetKeyValuesByPrefix(req) => {
+                let res = conn
+                    .get_context_key_values_by_prefix(&req.context_hash, req.prefix)
+                    .await;
+                let _ = channel
+                    .send(ProtocolRunnerResult::ContextKeyValuesByPrefixGet((
+                        token, res,
+                    )))
+                    .await;
+            }
             _other => {
                 // TODO: say which message
                 slog::warn!(
@@ -437,6 +447,12 @@ impl ProtocolRunnerServiceWorker {
                         | ProtocolMessage::BeginConstructionForMempoolCall(_) => {
                             ProtocolRunnerResult::BeginConstruction((token, Err(err.into())))
                         }
+                        ProtocolMessage::ContextGetKeyValuesByPrefix(_) => {
+                            ProtocolRunnerResult::ContextKeyValuesByPrefixGet((
+                                token,
+                                Err(err.into()),
+                            ))
+                        }
                         _ => panic!("Unexpected protocol runner message"),
                     })
                     .await
@@ -3,6 +3,7 @@
 
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
+    net::SocketAddr,
     sync::Arc,
     thread,
     time::Instant,
@@ -11,7 +12,7 @@ use std::{
 use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc, oneshot};
 
-use crypto::hash::{BlockHash, ChainId, OperationHash};
+use crypto::hash::{BlockHash, ChainId, CryptoboxPublicKeyHash, OperationHash};
 use storage::persistent::SchemaError;
 use storage::{
     shell_automaton_action_meta_storage::ShellAutomatonActionsStats, BlockHeaderWithHash,
@@ -22,10 +23,13 @@ use tezos_messages::p2p::encoding::{
 };
 
 use crate::{
-    mempool::mempool_actions::ConsensusOperationMatcher, request::RequestId, rpc::ValidBlocksQuery,
+    mempool::mempool_actions::ConsensusOperationMatcher,
+    peers::acl::{IpCidr, PeersAclState},
+    request::RequestId,
+    rpc::ValidBlocksQuery,
     storage::request::StorageRequestor,
 };
-use crate::{Action, State};
+use crate::{Action, BandwidthLimit, State};
 
 use super::{
     statistics_service::{ActionGraph, ActionKindStatsForBlock},
@@ -37,6 +41,12 @@ use super::{
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RpcId(u64);
 
+impl RpcId {
+    pub fn new(id: u64) -> Self {
+        Self(id)
+    }
+}
+
 pub type RpcRecvError = mpsc::error::TryRecvError;
 
 pub trait RpcService {
@@ -71,6 +81,57 @@ pub struct StorageRequests {
     pub finished: Vec<crate::service::statistics_service::StorageRequestFinished>,
 }
 
+/// Traffic with all peers together.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkStats {
+    pub total_sent: u64,
+    pub total_recv: u64,
+    /// Bytes received per second.
+    pub current_inflow: u64,
+    /// Bytes sent per second.
+    pub current_outflow: u64,
+    pub bandwidth_limit: BandwidthLimit,
+}
+
+/// Change of the peers connection policies, `None` leaves the policy unchanged.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PeersAclUpdate {
+    pub trusted: Option<Vec<SocketAddr>>,
+    pub incoming_allow: Option<Vec<IpCidr>>,
+    pub incoming_deny: Option<Vec<IpCidr>>,
+}
+
+/// Peer the [`NetworkCommand`] applies to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum NetworkTarget {
+    /// Address the peer listens on.
+    Point(SocketAddr),
+    /// Peer id of the connected peer, or of the peer banned by its peer id.
+    PeerId(CryptoboxPublicKeyHash),
+}
+
+/// Operator's command to manage the peer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkCommand {
+    Connect,
+    Ban,
+    Unban,
+    Trust,
+    Untrust,
+    /// Unban and untrust the peer.
+    Open,
+}
+
+#[derive(Serialize, Deserialize, thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum NetworkCommandError {
+    #[error("unknown peer")]
+    UnknownPeer,
+    #[error("command is not allowed for the peer")]
+    NotAllowed,
+    #[error("peer is already connected or trusted")]
+    Unchanged,
+}
+
 #[derive(Debug)]
 pub enum RpcRequest {
     GetCurrentGlobalState {
@@ -79,6 +140,23 @@ pub enum RpcRequest {
     GetStorageRequests {
         channel: oneshot::Sender<StorageRequests>,
     },
+    GetNetworkStats {
+        channel: oneshot::Sender<NetworkStats>,
+    },
+    GetPeersAcl {
+        channel: oneshot::Sender<PeersAclState>,
+    },
+    UpdatePeersAcl {
+        update: PeersAclUpdate,
+        channel: oneshot::Sender<PeersAclState>,
+    },
+    /// Applies all the commands or none of them, responds with
+    /// `Result<(), NetworkCommandError>` of the first rejected one.
+    NetworkCommand {
+        target: NetworkTarget,
+        commands: Vec<NetworkCommand>,
+    },
+    NetworkGreylistClear,
     GetActionKindStats {
         channel: oneshot::Sender<ShellAutomatonActionsStats>,
     },
@@ -97,6 +175,25 @@ pub enum RpcRequest {
     GetMempooEndrosementsStats {
         channel: oneshot::Sender<BTreeMap<OperationHash, crate::mempool::OperationStats>>,
     },
+    GetMempoolFilter {
+        channel: oneshot::Sender<crate::mempool::MempoolFilter>,
+    },
+    SetMempoolFilter {
+        filter: crate::mempool::MempoolFilter,
+        channel: oneshot::Sender<()>,
+    },
+    MempoolOperationBan {
+        operation_hash: OperationHash,
+        channel: oneshot::Sender<()>,
+    },
+    /// Responds `false` if the operation isn't banned.
+    MempoolOperationUnban {
+        operation_hash: OperationHash,
+        channel: oneshot::Sender<bool>,
+    },
+    MempoolOperationUnbanAll {
+        channel: oneshot::Sender<()>,
+    },
     GetBlockStats {
         channel: oneshot::Sender<Option<crate::service::statistics_service::BlocksApplyStats>>,
     },
@@ -149,6 +149,11 @@ impl<Req, Resp> ServiceWorkerResponder<Req, Resp> {
     pub fn recv(&mut self) -> Result<Req, RequestRecvError> {
         Ok(self.receiver.recv()?)
     }
+
+    /// Receive request if there is any queued, without blocking.
+    pub fn try_recv(&mut self) -> Option<Req> {
+        self.receiver.try_recv().ok()
+    }
 }
 
 pub fn worker_channel<Req, Resp>(
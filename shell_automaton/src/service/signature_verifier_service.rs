@@ -0,0 +1,265 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Verification of the operations' and block headers' signatures on a pool
+//! of worker threads.
+//!
+//! Every worker takes all the queued requests (up to the batch size limit)
+//! and verifies them, see [SignaturePublicKey::verify_each_signature], so that
+//! endorsements received together at the beginning of a level are verified
+//! in parallel, not one by one on the automaton thread.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crypto::hash::{BlockHash, OperationHash, Signature};
+use crypto::CryptoError;
+use tezos_messages::base::signature_public_key::{SignaturePublicKey, SignatureWatermark};
+
+use super::service_channel::{
+    worker_channel, RequestSendError, ResponseTryRecvError, ServiceWorkerRequester,
+    ServiceWorkerResponder, ServiceWorkerResponderSender,
+};
+
+pub trait SignatureVerifierService {
+    /// Queue the signature for the verification.
+    fn verify(
+        &mut self,
+        req: SignatureVerificationRequest,
+    ) -> Result<(), RequestSendError<SignatureVerificationRequest>>;
+
+    /// Try to receive/read queued verification result, if there is any.
+    fn try_recv(&mut self) -> Result<SignatureVerificationResponse, ResponseTryRecvError>;
+}
+
+type SignatureVerifierWorkerRequester =
+    ServiceWorkerRequester<SignatureVerificationRequest, SignatureVerificationResponse>;
+type SignatureVerifierWorkerResponder =
+    ServiceWorkerResponder<SignatureVerificationRequest, SignatureVerificationResponse>;
+
+/// Signature together with the signed data.
+#[derive(Debug, Clone)]
+pub struct SignedData {
+    pub public_key: SignaturePublicKey,
+    pub signature: Signature,
+    pub watermark: SignatureWatermark,
+    pub bytes: Vec<u8>,
+}
+
+impl SignedData {
+    /// Verifies the signature on the current thread.
+    pub fn verify(&self) -> Result<bool, SignatureVerificationError> {
+        Ok(self
+            .public_key
+            .verify_signature(&self.signature, &self.watermark, &self.bytes)?)
+    }
+}
+
+/// What the verified signature is of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureVerificationTarget {
+    Operation(OperationHash),
+    /// Block header, verified against the baker with the `priority`.
+    BlockHeader {
+        block_hash: BlockHash,
+        priority: u16,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct SignatureVerificationRequest {
+    pub target: SignatureVerificationTarget,
+    pub signed_data: SignedData,
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureVerificationError {
+    #[error("Public key is not supported")]
+    Unsupported,
+    #[error("Error verifying signature: {0}")]
+    Other(String),
+}
+
+impl From<CryptoError> for SignatureVerificationError {
+    fn from(error: CryptoError) -> Self {
+        match error {
+            CryptoError::Unsupported(_) => Self::Unsupported,
+            error => Self::Other(error.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SignatureVerificationResponse {
+    pub target: SignatureVerificationTarget,
+    pub result: Result<bool, SignatureVerificationError>,
+}
+
+#[derive(Debug)]
+pub struct SignatureVerifierServiceDefault {
+    worker_channel: SignatureVerifierWorkerRequester,
+}
+
+impl SignatureVerifierServiceDefault {
+    /// Maximal number of requests verified by a worker at once, so that
+    /// a burst of requests is split between the workers.
+    const MAX_BATCH_SIZE: usize = 64;
+
+    pub fn init(waker: Arc<mio::Waker>, threads: usize, channel_bound: usize) -> Self {
+        let (requester, responder) = worker_channel(waker, channel_bound);
+        let sender = responder.sender();
+        let responder = Arc::new(Mutex::new(responder));
+
+        for index in 0..threads.max(1) {
+            let responder = responder.clone();
+            let sender = sender.clone();
+            thread::Builder::new()
+                .name(format!("signature-verifier-{}", index))
+                .spawn(move || Self::run_worker(responder, sender))
+                .unwrap();
+        }
+
+        Self {
+            worker_channel: requester,
+        }
+    }
+
+    fn run_worker(
+        channel: Arc<Mutex<SignatureVerifierWorkerResponder>>,
+        mut sender: ServiceWorkerResponderSender<SignatureVerificationResponse>,
+    ) {
+        loop {
+            let requests = {
+                let mut channel = match channel.lock() {
+                    Ok(v) => v,
+                    Err(_) => return,
+                };
+                let mut requests = match channel.recv() {
+                    Ok(req) => vec![req],
+                    Err(_) => return,
+                };
+                while requests.len() < Self::MAX_BATCH_SIZE {
+                    match channel.try_recv() {
+                        Some(req) => requests.push(req),
+                        None => break,
+                    }
+                }
+                requests
+            };
+
+            let items = requests
+                .iter()
+                .map(|req| {
+                    let data = &req.signed_data;
+                    (
+                        &data.public_key,
+                        &data.signature,
+                        &data.watermark,
+                        &data.bytes,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let results = SignaturePublicKey::verify_each_signature(&items);
+
+            for (req, result) in requests.iter().zip(results) {
+                let resp = SignatureVerificationResponse {
+                    target: req.target.clone(),
+                    result: result.map_err(Into::into),
+                };
+                if sender.send(resp).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl SignatureVerifierService for SignatureVerifierServiceDefault {
+    #[inline(always)]
+    fn verify(
+        &mut self,
+        req: SignatureVerificationRequest,
+    ) -> Result<(), RequestSendError<SignatureVerificationRequest>> {
+        self.worker_channel.send(req)
+    }
+
+    #[inline(always)]
+    fn try_recv(&mut self) -> Result<SignatureVerificationResponse, ResponseTryRecvError> {
+        self.worker_channel.try_recv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crypto::hash::HashTrait;
+
+    use super::*;
+
+    fn signed_data(bytes: &[u8]) -> SignedData {
+        SignedData {
+            public_key: SignaturePublicKey::from_b58_hash(
+                "edpkuQ1C2vXZk7F2bQCjNTN99gRVsCdRKLQbfvxsRQ1pnh55R733Vk",
+            )
+            .unwrap(),
+            signature: Signature::from_base58_check("sigVyVvEX4WHAQrQwx2QvWuVUtgUQ3cGk4qRF7BbskrpaABqH9GNANesNFk55U4uLL6gmVrbLG6HBu8zbsTrkYLJGNcQupDa").unwrap(),
+            watermark: SignatureWatermark::GenericOperation,
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    fn target(i: u16) -> SignatureVerificationTarget {
+        SignatureVerificationTarget::BlockHeader {
+            block_hash: BlockHash::try_from_bytes(&[0; 32]).unwrap(),
+            priority: i,
+        }
+    }
+
+    #[test]
+    fn test_signature_verifier_service() {
+        let poll = mio::Poll::new().unwrap();
+        let waker = Arc::new(mio::Waker::new(poll.registry(), mio::Token(0)).unwrap());
+        let mut service = SignatureVerifierServiceDefault::init(waker, 2, 1024);
+
+        // every other signature is of other bytes.
+        let count = 200;
+        for i in 0..count {
+            let bytes: &[u8] = if i % 2 == 0 {
+                &[1, 2, 3, 4, 5]
+            } else {
+                &[1, 2, 3, 4]
+            };
+            service
+                .verify(SignatureVerificationRequest {
+                    target: target(i),
+                    signed_data: signed_data(bytes),
+                })
+                .unwrap();
+        }
+
+        let mut results = vec![None; count as usize];
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while results.iter().any(Option::is_none) {
+            assert!(Instant::now() < deadline, "verification timed out");
+            match service.try_recv() {
+                Ok(SignatureVerificationResponse {
+                    target: SignatureVerificationTarget::BlockHeader { priority, .. },
+                    result,
+                }) => {
+                    assert_eq!(results[priority as usize], None);
+                    results[priority as usize] = Some(result);
+                }
+                Ok(resp) => panic!("unexpected response: {:?}", resp),
+                Err(_) => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result, Some(Ok(i % 2 == 0)));
+        }
+    }
+}
@@ -2,29 +2,37 @@
 // SPDX-License-Identifier: MIT
 
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::net::SocketAddr;
+use std::sync::{mpsc, Arc};
 use std::{fmt, thread};
 
 use enum_kinds::EnumKind;
 use serde::{Deserialize, Serialize};
 
-use crypto::hash::{BlockHash, ChainId, ContextHash, ProtocolHash};
+use crypto::hash::{BlockHash, ChainId, ContextHash, OperationHash, ProtocolHash};
 use storage::block_meta_storage::Meta;
 use storage::cycle_eras_storage::CycleErasData;
 use storage::cycle_storage::CycleData;
+use storage::mempool_storage::MempoolOperationType;
+use storage::peer_address_book_storage::{PeerAddressBookEntry, PeerAddressBookUpdate};
 use storage::{
-    BlockAdditionalData, BlockHeaderWithHash, BlockMetaStorage, BlockMetaStorageReader,
-    BlockStorage, BlockStorageReader, ChainMetaStorage, ConstantsStorage, CycleErasStorage,
-    CycleMetaStorage, OperationKey, OperationsMetaStorage, OperationsStorage,
-    OperationsStorageReader, PersistentStorage, ShellAutomatonActionStorage,
-    ShellAutomatonStateStorage, StorageInitInfo,
+    AccountOperationsIndexer, BlockAdditionalData, BlockHeaderWithHash, BlockMetaStorage,
+    BlockMetaStorageReader, BlockStorage, BlockStorageReader, ChainMetaStorage, ConstantsStorage,
+    CycleErasStorage, CycleMetaStorage, HistoryMode, HistoryPruner, MempoolStorage, OperationKey,
+    OperationsMetaStorage, OperationsStorage, OperationsStorageReader, PeerAddressBookStorage,
+    PersistentStorage, ProtocolStorage, ShellAutomatonActionStorage, ShellAutomatonStateStorage,
+    StorageInitInfo, SystemStorage,
 };
 use tezos_api::ffi::{ApplyBlockRequest, ApplyBlockResponse, CommitGenesisResult};
 use tezos_messages::p2p::encoding::block_header::{BlockHeader, Level};
 use tezos_messages::p2p::encoding::fitness::Fitness;
 use tezos_messages::p2p::encoding::operation::Operation;
 use tezos_messages::p2p::encoding::operations_for_blocks::OperationsForBlocksMessage;
+use tezos_messages::p2p::encoding::protocol::Protocol;
 
+#[cfg(feature = "fuzzing")]
+use crate::fuzzing::net::SocketAddrMutator;
+use crate::mempool::MempoolFilter;
 use crate::request::RequestId;
 use crate::storage::kv_cycle_meta::CycleKey;
 use crate::{Action, ActionId, ActionWithMeta, State};
@@ -94,6 +102,19 @@ pub enum StorageRequestPayload {
     BlockOperationsPut(OperationsForBlocksMessage),
     BlockAdditionalDataPut((BlockHash, BlockAdditionalData)),
 
+    PeerAddressBookLoad,
+    PeerAddressBookEntryUpdate(SocketAddr, PeerAddressBookUpdate),
+
+    MempoolFilterPut(MempoolFilter),
+    MempoolOperationsLoad,
+    MempoolOperationsUpdate {
+        put: Vec<MempoolStorageItem>,
+        delete: Vec<OperationHash>,
+    },
+
+    ProtocolGet(ProtocolHash),
+    ProtocolPut(ProtocolHash, Protocol),
+
     PrepareApplyBlockData {
         chain_id: Arc<ChainId>,
         block_hash: Arc<BlockHash>,
@@ -140,6 +161,17 @@ pub enum StorageResponseSuccess {
     BlockOperationsPutSuccess(bool),
     BlockAdditionalDataPutSuccess(()),
 
+    /// Known peers, best-known first.
+    PeerAddressBookLoadSuccess(Vec<PeerAddressBookItem>),
+    PeerAddressBookEntryUpdateSuccess(()),
+
+    MempoolFilterPutSuccess(()),
+    MempoolOperationsLoadSuccess(Vec<MempoolStorageLoadedItem>),
+    MempoolOperationsUpdateSuccess(()),
+
+    ProtocolGetSuccess(ProtocolHash, Option<Protocol>),
+    ProtocolPutSuccess(()),
+
     PrepareApplyBlockDataSuccess {
         block: Arc<BlockHeaderWithHash>,
         block_meta: Arc<Meta>,
@@ -170,10 +202,46 @@ pub enum StorageResponseError {
     BlockOperationsPutError(StorageError),
     BlockAdditionalDataPutError(StorageError),
 
+    PeerAddressBookLoadError(StorageError),
+    PeerAddressBookEntryUpdateError(StorageError),
+
+    MempoolFilterPutError(StorageError),
+    MempoolOperationsLoadError(StorageError),
+    MempoolOperationsUpdateError(StorageError),
+
+    ProtocolGetError(ProtocolHash, StorageError),
+    ProtocolPutError(ProtocolHash, StorageError),
+
     PrepareApplyBlockDataError(StorageError),
     StoreApplyBlockResultError(StorageError),
 }
 
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerAddressBookItem {
+    #[cfg_attr(feature = "fuzzing", field_mutator(SocketAddrMutator))]
+    pub address: SocketAddr,
+    pub entry: PeerAddressBookEntry,
+}
+
+/// Operation persisted in the mempool storage.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageItem {
+    pub hash: OperationHash,
+    pub operation: Operation,
+    /// Operation was applied by the prevalidator, otherwise it's pending.
+    pub known_valid: bool,
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MempoolStorageLoadedItem {
+    pub item: MempoolStorageItem,
+    /// Level of the operation's branch, `None` if the block is not in the storage.
+    pub branch_level: Option<Level>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StorageRequest {
     /// Identifier for the Request.
@@ -282,10 +350,46 @@ fn find_block_with_context_hash(
     }
 }
 
+/// Head applied by the storage worker, sent to the threads pruning the history
+/// and indexing the operations by accounts.
+#[derive(Clone)]
+struct AppliedHead {
+    chain_id: ChainId,
+    block_hash: BlockHash,
+    level: Level,
+    block_additional_data: BlockAdditionalData,
+}
+
 impl StorageServiceDefault {
+    /// Handles the applied heads on its own thread, so that the storage worker doesn't wait for it.
+    ///
+    /// Heads applied while the `handler` is running are coalesced, only the latest one is handled.
+    fn spawn_applied_head_handler<F>(name: &str, mut handler: F) -> mpsc::Sender<AppliedHead>
+    where
+        F: FnMut(AppliedHead) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<AppliedHead>();
+
+        thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || {
+                while let Ok(mut head) = receiver.recv() {
+                    while let Ok(next_head) = receiver.try_recv() {
+                        head = next_head;
+                    }
+                    handler(head);
+                }
+            })
+            .unwrap();
+
+        sender
+    }
+
     fn run_worker(
         log: slog::Logger,
         storage: PersistentStorage,
+        history_mode: HistoryMode,
+        index_account_operations: bool,
         mut channel: StorageWorkerResponder,
     ) {
         use StorageRequestPayload::*;
@@ -303,6 +407,47 @@ impl StorageServiceDefault {
         let constants_storage = ConstantsStorage::new(&storage);
         let cycle_meta_storage = CycleMetaStorage::new(&storage);
         let cycle_eras_storage = CycleErasStorage::new(&storage);
+        let peer_address_book_storage = PeerAddressBookStorage::new(&storage);
+        let mut system_storage = SystemStorage::new(storage.main_db());
+        let mut mempool_storage = MempoolStorage::new(&storage);
+        let protocol_storage = ProtocolStorage::new(&storage);
+        let history_pruner = if history_mode.is_archive() {
+            None
+        } else {
+            let log = log.clone();
+            let history_pruner = HistoryPruner::new(&storage, history_mode, log.clone());
+            Some(Self::spawn_applied_head_handler(
+                "history-pruner-thread",
+                move |head| {
+                    if let Err(err) = history_pruner.block_applied(
+                        &head.chain_id,
+                        &head.block_hash,
+                        head.level,
+                        &head.block_additional_data,
+                    ) {
+                        slog::warn!(&log, "Failed to prune history"; "reason" => err);
+                    }
+                },
+            ))
+        };
+        let account_operations_indexer = if index_account_operations {
+            let log = log.clone();
+            let mut account_operations_indexer = AccountOperationsIndexer::new(&storage);
+            Some(Self::spawn_applied_head_handler(
+                "account-operations-indexer-thread",
+                move |head| {
+                    if let Err(err) = account_operations_indexer.block_applied(
+                        &head.chain_id,
+                        &head.block_hash,
+                        head.level,
+                    ) {
+                        slog::warn!(&log, "Failed to index operations by accounts"; "reason" => err);
+                    }
+                },
+            ))
+        } else {
+            None
+        };
 
         // let mut last_time_meta_saved = Instant::now();
 
@@ -437,6 +582,75 @@ impl StorageServiceDefault {
                     }
                 }
 
+                PeerAddressBookLoad => peer_address_book_storage
+                    .load()
+                    .map(|entries| {
+                        entries
+                            .into_iter()
+                            .map(|(address, entry)| PeerAddressBookItem { address, entry })
+                            .collect()
+                    })
+                    .map(PeerAddressBookLoadSuccess)
+                    .map_err(|err| PeerAddressBookLoadError(err.into())),
+                PeerAddressBookEntryUpdate(address, update) => peer_address_book_storage
+                    .update(&address, &update)
+                    .map(PeerAddressBookEntryUpdateSuccess)
+                    .map_err(|err| PeerAddressBookEntryUpdateError(err.into())),
+
+                MempoolFilterPut(filter) => serde_json::to_string(&filter)
+                    .map_err(|err| StorageError(err.to_string()))
+                    .and_then(|filter| Ok(system_storage.set_mempool_filter(&filter)?))
+                    .map(MempoolFilterPutSuccess)
+                    .map_err(MempoolFilterPutError),
+                MempoolOperationsLoad => mempool_storage
+                    .load()
+                    .and_then(|operations| {
+                        operations
+                            .into_iter()
+                            .map(|(operation_type, hash, operation)| {
+                                let operation = Operation::from(operation);
+                                let branch_level = block_storage
+                                    .get(operation.branch())?
+                                    .map(|block| block.header.level());
+                                let item = MempoolStorageItem {
+                                    hash,
+                                    operation,
+                                    known_valid: operation_type == MempoolOperationType::KnownValid,
+                                };
+                                Ok(MempoolStorageLoadedItem { item, branch_level })
+                            })
+                            .collect()
+                    })
+                    .map(MempoolOperationsLoadSuccess)
+                    .map_err(|err| MempoolOperationsLoadError(err.into())),
+                MempoolOperationsUpdate { put, delete } => delete
+                    .iter()
+                    .try_for_each(|hash| mempool_storage.delete(hash))
+                    .and_then(|_| {
+                        put.into_iter().try_for_each(|item| {
+                            // operation is stored only with its latest type.
+                            mempool_storage.delete(&item.hash)?;
+                            if item.known_valid {
+                                mempool_storage.put_known_valid(item.operation.into())
+                            } else {
+                                mempool_storage.put_pending(item.operation.into())
+                            }
+                        })
+                    })
+                    .map(MempoolOperationsUpdateSuccess)
+                    .map_err(|err| MempoolOperationsUpdateError(err.into())),
+
+                ProtocolGet(protocol_hash) => match protocol_storage.get(&protocol_hash) {
+                    Ok(protocol) => Ok(ProtocolGetSuccess(protocol_hash, protocol)),
+                    Err(err) => Err(ProtocolGetError(protocol_hash, err.into())),
+                },
+                ProtocolPut(protocol_hash, protocol) => {
+                    match protocol_storage.put(&protocol_hash, &protocol) {
+                        Ok(()) => Ok(ProtocolPutSuccess(())),
+                        Err(err) => Err(ProtocolPutError(protocol_hash, err.into())),
+                    }
+                }
+
                 PrepareApplyBlockData {
                     chain_id,
                     block_hash,
@@ -480,6 +694,21 @@ impl StorageServiceDefault {
                         &constants_storage,
                     );
 
+                    if let Ok(data) = &result {
+                        let head = AppliedHead {
+                            chain_id: block_meta.chain_id().clone(),
+                            block_hash: block_hash.clone(),
+                            level: block_meta.level(),
+                            block_additional_data: data.clone(),
+                        };
+                        if let Some(account_operations_indexer) = &account_operations_indexer {
+                            let _ = account_operations_indexer.send(head.clone());
+                        }
+                        if let Some(history_pruner) = &history_pruner {
+                            let _ = history_pruner.send(head);
+                        }
+                    }
+
                     match result {
                         Ok(data) => Ok(StoreApplyBlockResultSuccess(data.into())),
                         Err(err) => Err(StoreApplyBlockResultError(err.into())),
@@ -509,6 +738,8 @@ impl StorageServiceDefault {
         log: slog::Logger,
         waker: Arc<mio::Waker>,
         persistent_storage: PersistentStorage,
+        history_mode: HistoryMode,
+        index_account_operations: bool,
         channel_bound: usize,
     ) -> Self {
         let (requester, responder) = worker_channel(waker, channel_bound);
@@ -517,7 +748,15 @@ impl StorageServiceDefault {
 
         thread::Builder::new()
             .name("storage-thread".to_owned())
-            .spawn(move || Self::run_worker(log, storage, responder))
+            .spawn(move || {
+                Self::run_worker(
+                    log,
+                    storage,
+                    history_mode,
+                    index_account_operations,
+                    responder,
+                )
+            })
             .unwrap();
 
         Self {
@@ -19,6 +19,8 @@ use crate::current_head_precheck::CurrentHeads;
 use crate::mempool::MempoolState;
 use crate::paused_loops::PausedLoopsState;
 use crate::peer::connection::incoming::accept::PeerConnectionIncomingAcceptState;
+use crate::peers::acl::PeersAclState;
+use crate::peers::bandwidth::PEERS_BANDWIDTH_THROTTLED_WAIT;
 use crate::peers::PeersState;
 use crate::prechecker::PrecheckerState;
 use crate::protocol_runner::ProtocolRunnerState;
@@ -95,10 +97,12 @@ impl State {
 
     pub fn new(config: Config) -> Self {
         let block_applier = BlockApplierState::new(&config);
+        let mut peers = PeersState::new();
+        peers.acl = PeersAclState::new(&config);
         Self {
             log: Default::default(),
             config,
-            peers: PeersState::new(),
+            peers,
             peer_connection_incoming_accept: PeerConnectionIncomingAcceptState::Idle { time: 0 },
             storage: StorageState::new(),
             bootstrap: BootstrapState::new(),
@@ -173,6 +177,8 @@ impl State {
         // of blocking up until timeout or until there are some events.
         if !self.paused_loops.is_empty() {
             Some(Duration::ZERO)
+        } else if self.peers.is_bandwidth_throttled() {
+            Some(PEERS_BANDWIDTH_THROTTLED_WAIT.min(self.config.min_time_interval()))
         } else {
             Some(self.config.min_time_interval())
         }
@@ -0,0 +1,107 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Replays p2p traffic recorded by the node (`--record-p2p-traffic-dir`)
+//! into the [Cluster] with the node's state machine and a mocked peer.
+//!
+//! Usage: `p2p_replay <RECORDING_DIR|RECORDING_FILE> [PEER_ADDRESS] [CHAIN_ID]`
+//!
+//! Without the peer address, recorded connections are listed.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process;
+use std::time::SystemTime;
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::service::p2p_recorder_service::P2pRecordEvent;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::replay::{p2p_recording_files, p2p_recording_load, p2p_replay};
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("{}", error);
+    eprintln!("Usage: p2p_replay <RECORDING_DIR|RECORDING_FILE> [PEER_ADDRESS] [CHAIN_ID]");
+    process::exit(1)
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let path = match args.get(0) {
+        Some(v) => PathBuf::from(v),
+        None => exit_with_usage("Missing recording path"),
+    };
+    let address = args.get(1).map(|v| {
+        v.parse::<SocketAddr>()
+            .unwrap_or_else(|_| exit_with_usage("Invalid peer address, expected IP:PORT"))
+    });
+    let chain_id = args.get(2).map(|v| {
+        ChainId::try_from(v.as_str()).unwrap_or_else(|_| exit_with_usage("Invalid chain id"))
+    });
+
+    let files = if path.is_dir() {
+        p2p_recording_files(&path)
+    } else {
+        vec![path]
+    };
+    let records = p2p_recording_load(&files, address).unwrap_or_else(|err| {
+        eprintln!("Failed to load recording: {}", err);
+        process::exit(1)
+    });
+
+    let address = match address {
+        Some(v) => v,
+        None => {
+            let mut connections = BTreeMap::<SocketAddr, (usize, usize)>::new();
+            for record in &records {
+                let (handshakes, messages) = connections.entry(record.address).or_default();
+                match record.event {
+                    P2pRecordEvent::Handshaked { .. } => *handshakes += 1,
+                    P2pRecordEvent::Message { .. } => *messages += 1,
+                    P2pRecordEvent::Disconnected => {}
+                }
+            }
+            for (address, (handshakes, messages)) in connections {
+                println!(
+                    "{}\thandshakes: {}\tmessages: {}",
+                    address, handshakes, messages
+                );
+            }
+            return;
+        }
+    };
+
+    let initial_time = SystemTime::now();
+    let mut config = Config {
+        initial_time,
+        pow_target: 0.0,
+        ..default_test_config()
+    };
+    if let Some(chain_id) = chain_id {
+        config.chain_id = chain_id;
+    }
+    let state = State::new(config);
+    let mut cluster = Cluster::new(state, initial_time);
+
+    let (_, report) = p2p_replay(&mut cluster, &records).unwrap_or_else(|err| {
+        eprintln!("Handshake with the node failed: {:?}", err);
+        process::exit(1)
+    });
+
+    println!("Replayed connection with: {}", address);
+    println!(
+        "Sent messages: {}, skipped: {}",
+        report.sent, report.skipped
+    );
+    println!("Recorded responses:");
+    for message in &report.recorded_responses {
+        println!("  {:?}", message);
+    }
+    println!("Replayed responses:");
+    for message in &report.responses {
+        println!("  {:?}", message);
+    }
+}
@@ -11,6 +11,7 @@ use storage::BlockHeaderWithHash;
 use tezos_messages::p2p::encoding::block_header::{BlockHeaderBuilder, Level};
 
 pub mod one_real_node_cluster;
+pub mod replay;
 pub mod service;
 
 pub fn generate_chain(
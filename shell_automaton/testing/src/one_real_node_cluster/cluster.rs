@@ -22,7 +22,7 @@ use tezos_messages::p2p::encoding::metadata::MetadataMessage;
 use crate::service::{
     ActorsServiceDummy, ConnectedState, DnsServiceMocked, IOCondition, MioPeerMockedId,
     MioPeerStreamMocked, MioServiceMocked, ProtocolRunnerServiceDummy, RandomnessServiceMocked,
-    RpcServiceDummy, StorageServiceDummy,
+    RpcServiceDummy, SignatureVerifierServiceDummy, StorageServiceDummy,
 };
 use crate::service::{Service, TimeService};
 
@@ -43,6 +43,7 @@ pub struct ServiceMocked {
     pub storage: StorageServiceDummy,
     pub rpc: RpcServiceDummy,
     pub actors: ActorsServiceDummy,
+    pub signature_verifier: SignatureVerifierServiceDummy,
 }
 
 impl ServiceMocked {
@@ -56,6 +57,7 @@ impl ServiceMocked {
             storage: StorageServiceDummy::new(),
             rpc: RpcServiceDummy::new(),
             actors: ActorsServiceDummy::new(),
+            signature_verifier: SignatureVerifierServiceDummy::new(),
         }
     }
 
@@ -84,6 +86,7 @@ impl Service for ServiceMocked {
     type Storage = StorageServiceDummy;
     type Rpc = RpcServiceDummy;
     type Actors = ActorsServiceDummy;
+    type SignatureVerifier = SignatureVerifierServiceDummy;
 
     fn randomness(&mut self) -> &mut Self::Randomness {
         &mut self.randomness
@@ -113,6 +116,10 @@ impl Service for ServiceMocked {
         &mut self.actors
     }
 
+    fn signature_verifier(&mut self) -> &mut Self::SignatureVerifier {
+        &mut self.signature_verifier
+    }
+
     fn prevalidator(&mut self) -> &mut Self::ProtocolRunner {
         self.protocol_runner()
     }
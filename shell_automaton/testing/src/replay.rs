@@ -0,0 +1,132 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Replay of the p2p traffic recorded by [P2pRecorderService].
+//!
+//! Messages received from the recorded peer are sent to the node by the
+//! mocked peer, so the node's behavior can be reproduced and examined
+//! in the [Cluster].
+//!
+//! [P2pRecorderService]: shell_automaton::service::P2pRecorderService
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use shell_automaton::service::p2p_recorder_service::{
+    p2p_recording_file_path, p2p_recording_read, P2pRecord, P2pRecordDirection, P2pRecordEvent,
+};
+use tezos_messages::p2p::binary_message::BinaryRead;
+use tezos_messages::p2p::encoding::peer::{PeerMessage, PeerMessageResponse};
+
+use crate::one_real_node_cluster::{Cluster, HandshakeError};
+use crate::service::{IOCondition, MioPeerMockedId};
+
+/// Recording files in the directory, the oldest first.
+pub fn p2p_recording_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = (0..)
+        .map(|index| p2p_recording_file_path(dir, index))
+        .take_while(|path| path.exists())
+        .collect::<Vec<_>>();
+    files.reverse();
+    files
+}
+
+/// Loads records from the recording files, optionally only of the
+/// connection with the peer with the given `address`.
+pub fn p2p_recording_load(
+    files: &[PathBuf],
+    address: Option<SocketAddr>,
+) -> io::Result<Vec<P2pRecord>> {
+    let mut records = vec![];
+    for file in files {
+        for record in p2p_recording_read(file)? {
+            let record = record?;
+            if address.map_or(true, |address| address == record.address) {
+                records.push(record);
+            }
+        }
+    }
+    Ok(records)
+}
+
+#[derive(Debug, Default)]
+pub struct P2pReplayReport {
+    /// Messages sent to the node.
+    pub sent: usize,
+    /// Recorded messages which couldn't be decoded, so they weren't sent.
+    pub skipped: usize,
+    /// Messages the node sent to the peer in the original connection.
+    pub recorded_responses: Vec<PeerMessage>,
+    /// Messages the node sent to the peer during the replay.
+    pub responses: Vec<PeerMessage>,
+}
+
+/// Replays the first recorded connection in `records` into the `cluster`.
+///
+/// Records are expected to be of the single peer, see [p2p_recording_load].
+/// Mocked peer handshakes with the node and then sends it the messages
+/// received from the recorded peer, advancing the cluster's time by the
+/// recorded time between them. Replay stops when the recorded peer disconnected.
+pub fn p2p_replay(
+    cluster: &mut Cluster,
+    records: &[P2pRecord],
+) -> Result<(MioPeerMockedId, P2pReplayReport), HandshakeError> {
+    let mut report = P2pReplayReport::default();
+    let records = records
+        .iter()
+        .skip_while(|record| !matches!(record.event, P2pRecordEvent::Handshaked { .. }));
+
+    let peer_id = cluster.peer_init(cluster.state().config.pow_target);
+    cluster.connect_to_peer(peer_id);
+    cluster.set_peer_connected(peer_id);
+    cluster.do_handshake(peer_id)?;
+
+    let mut last_time = None;
+    for record in records {
+        if let Some(last_time) = last_time {
+            cluster.advance_time(Duration::from_nanos(record.time.saturating_sub(last_time)));
+            cluster.loop_next();
+        }
+        last_time = Some(record.time);
+
+        match &record.event {
+            P2pRecordEvent::Handshaked { .. } => continue,
+            P2pRecordEvent::Disconnected => break,
+            P2pRecordEvent::Message {
+                direction: P2pRecordDirection::Outgoing,
+                message,
+                ..
+            } => {
+                report.recorded_responses.extend(message.clone());
+            }
+            P2pRecordEvent::Message {
+                direction: P2pRecordDirection::Incoming,
+                raw,
+                ..
+            } => {
+                let message = match PeerMessageResponse::from_bytes(raw) {
+                    Ok(v) => v.message().clone(),
+                    Err(_) => {
+                        report.skipped += 1;
+                        continue;
+                    }
+                };
+                cluster
+                    .peer(peer_id)
+                    .set_read_cond(IOCondition::NoLimit)
+                    .set_write_cond(IOCondition::NoLimit)
+                    .send_peer_message(message);
+                cluster.dispatch_peer_ready_event(peer_id, true, true, false);
+                report.sent += 1;
+            }
+        }
+
+        while let Some(message) = cluster.peer(peer_id).read_peer_message() {
+            report.responses.push(message);
+        }
+    }
+
+    Ok((peer_id, report))
+}
@@ -3,12 +3,13 @@
 
 use std::path::PathBuf;
 
+use crypto::hash::ContextHash;
 use tezos_api::environment::TezosEnvironmentConfiguration;
 use tezos_api::ffi::{
     ApplyBlockRequest, BeginConstructionRequest, TezosRuntimeConfiguration,
     ValidateOperationRequest,
 };
-use tezos_context_api::{PatchContext, TezosContextStorageConfiguration};
+use tezos_context_api::{ContextKeyOwned, PatchContext, TezosContextStorageConfiguration};
 use tezos_protocol_ipc_client::ProtocolServiceError;
 use tezos_protocol_ipc_messages::GenesisResultDataParams;
 
@@ -117,4 +118,12 @@ impl ProtocolRunnerService for ProtocolRunnerServiceDummy {
     fn get_latest_context_hashes(&mut self, _: i64) -> ProtocolRunnerToken {
         self.new_token()
     }
+
+    fn get_context_key_values_by_prefix(
+        &mut self,
+        _: ContextHash,
+        _: ContextKeyOwned,
+    ) -> ProtocolRunnerToken {
+        self.new_token()
+    }
 }
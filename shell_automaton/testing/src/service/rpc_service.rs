@@ -1,18 +1,36 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
+use std::collections::VecDeque;
+
 use serde::Serialize;
 
 pub use shell_automaton::service::rpc_service::{
     RpcId, RpcRecvError, RpcRequest, RpcRequestStream, RpcService,
 };
 
-#[derive(Debug, Clone)]
-pub struct RpcServiceDummy {}
+#[derive(Debug)]
+pub struct RpcServiceDummy {
+    pub requests: VecDeque<(RpcRequest, RpcId)>,
+    pub responses: Vec<(RpcId, serde_json::Value)>,
+}
 
 impl RpcServiceDummy {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            requests: Default::default(),
+            responses: Default::default(),
+        }
+    }
+}
+
+/// [`RpcRequest`] isn't `Clone`, so pending requests aren't cloned.
+impl Clone for RpcServiceDummy {
+    fn clone(&self) -> Self {
+        Self {
+            requests: Default::default(),
+            responses: self.responses.clone(),
+        }
     }
 }
 
@@ -24,14 +42,15 @@ impl Default for RpcServiceDummy {
 
 impl RpcService for RpcServiceDummy {
     fn try_recv(&mut self) -> Result<(RpcRequest, RpcId), RpcRecvError> {
-        Err(RpcRecvError::Empty)
+        self.requests.pop_front().ok_or(RpcRecvError::Empty)
     }
 
     fn respond<J>(&mut self, call_id: RpcId, json: J)
     where
         J: Serialize,
     {
-        let _ = (call_id, json);
+        let json = serde_json::to_value(json).unwrap_or(serde_json::Value::Null);
+        self.responses.push((call_id, json));
     }
 
     fn try_recv_stream(&mut self) -> Result<(RpcRequestStream, RpcId), RpcRecvError> {
@@ -0,0 +1,50 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::collections::VecDeque;
+
+use shell_automaton::service::service_channel::{RequestSendError, ResponseTryRecvError};
+pub use shell_automaton::service::signature_verifier_service::{
+    SignatureVerificationRequest, SignatureVerificationResponse, SignatureVerifierService,
+};
+
+/// Mocked SignatureVerifierService.
+///
+/// Verifies signatures synchronously, responses are queued until received.
+#[derive(Debug, Clone)]
+pub struct SignatureVerifierServiceDummy {
+    responses: VecDeque<SignatureVerificationResponse>,
+}
+
+impl SignatureVerifierServiceDummy {
+    pub fn new() -> Self {
+        Self {
+            responses: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for SignatureVerifierServiceDummy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SignatureVerifierService for SignatureVerifierServiceDummy {
+    fn verify(
+        &mut self,
+        req: SignatureVerificationRequest,
+    ) -> Result<(), RequestSendError<SignatureVerificationRequest>> {
+        self.responses.push_back(SignatureVerificationResponse {
+            result: req.signed_data.verify(),
+            target: req.target,
+        });
+        Ok(())
+    }
+
+    fn try_recv(&mut self) -> Result<SignatureVerificationResponse, ResponseTryRecvError> {
+        self.responses
+            .pop_front()
+            .ok_or(ResponseTryRecvError::Empty)
+    }
+}
@@ -2,3 +2,8 @@ pub mod bootstrap;
 pub mod p2p_requests;
 
 pub mod test_handshaking_basic;
+pub mod test_network_rpc;
+pub mod test_p2p_replay;
+pub mod test_peers_acl;
+pub mod test_peers_score;
+pub mod test_precheck_manager_operation;
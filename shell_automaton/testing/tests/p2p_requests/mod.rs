@@ -1 +1,4 @@
+pub mod test_deactivate;
 pub mod test_get_current_branch;
+pub mod test_get_protocols;
+pub mod test_swap;
@@ -0,0 +1,135 @@
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime},
+};
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::service::{IOCondition, MioPeerMockedId};
+use tezos_identity::Identity;
+use tezos_messages::p2p::encoding::peer::PeerMessage;
+use tezos_messages::p2p::encoding::prelude::{
+    DeactivateMessage, GetCurrentBranchMessage, GetCurrentHeadMessage,
+};
+
+fn data() -> (Cluster, MioPeerMockedId) {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 2,
+        peers_connected_max: 2,
+        ..default_test_config()
+    });
+    let mut cluster = Cluster::new(state, initial_time);
+
+    let peer_id = cluster.peer_init(0.0);
+    cluster.connect_to_peer(peer_id);
+    cluster.set_peer_connected(peer_id);
+    cluster.do_handshake(peer_id).unwrap();
+
+    (cluster, peer_id)
+}
+
+fn other_chain_id() -> ChainId {
+    ChainId::try_from("NetXdQprcVkpaWU").unwrap() // mainnet
+}
+
+fn send(cluster: &mut Cluster, peer_id: MioPeerMockedId, message: PeerMessage) {
+    cluster
+        .peer(peer_id)
+        .set_read_cond(IOCondition::NoLimit)
+        .set_write_cond(IOCondition::NoLimit)
+        .send_peer_message(message);
+    cluster.dispatch_peer_ready_event(peer_id, true, true, false);
+}
+
+/// Chains deactivated by the node in its responses to the peer.
+fn deactivated(cluster: &mut Cluster, peer_id: MioPeerMockedId) -> Vec<ChainId> {
+    let mut deactivated = vec![];
+    loop {
+        cluster.dispatch_peer_ready_event(peer_id, true, true, false);
+        let msg = match cluster.peer(peer_id).read_peer_message() {
+            Some(msg) => msg,
+            None => return deactivated,
+        };
+        if let PeerMessage::Deactivate(msg) = msg {
+            deactivated.push(msg.deactivate().clone());
+        }
+    }
+}
+
+fn is_handshaked(cluster: &Cluster, peer_id: MioPeerMockedId) -> bool {
+    cluster
+        .state()
+        .peers
+        .get(&peer_id.to_ipv4())
+        .map_or(false, |peer| peer.is_handshaked())
+}
+
+/// Requests for a chain we don't run are answered with `Deactivate`.
+#[test]
+fn test_deactivate_unknown_chain_requests() {
+    let (mut cluster, peer_id) = data();
+
+    send(
+        &mut cluster,
+        peer_id,
+        PeerMessage::GetCurrentBranch(GetCurrentBranchMessage::new(other_chain_id())),
+    );
+    send(
+        &mut cluster,
+        peer_id,
+        PeerMessage::GetCurrentHead(GetCurrentHeadMessage::new(other_chain_id())),
+    );
+
+    assert_eq!(
+        deactivated(&mut cluster, peer_id),
+        vec![other_chain_id(), other_chain_id()]
+    );
+    assert!(is_handshaked(&cluster, peer_id));
+}
+
+/// Peer which stops following our chain is disconnected.
+#[test]
+fn test_deactivate_our_chain() {
+    let (mut cluster, peer_id) = data();
+    let chain_id = cluster.state().config.chain_id.clone();
+
+    send(
+        &mut cluster,
+        peer_id,
+        PeerMessage::Deactivate(DeactivateMessage::new(chain_id)),
+    );
+
+    assert!(!is_handshaked(&cluster, peer_id));
+}
+
+/// Deactivation of a chain we don't run (like a test chain) is ignored.
+#[test]
+fn test_deactivate_other_chain() {
+    let (mut cluster, peer_id) = data();
+
+    send(
+        &mut cluster,
+        peer_id,
+        PeerMessage::Deactivate(DeactivateMessage::new(other_chain_id())),
+    );
+
+    assert!(is_handshaked(&cluster, peer_id));
+    assert!(deactivated(&mut cluster, peer_id).is_empty());
+}
@@ -0,0 +1,139 @@
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime},
+};
+
+use crypto::hash::{ChainId, ProtocolHash};
+use shell_automaton::config::default_test_config;
+use shell_automaton::event::WakeupEvent;
+use shell_automaton::service::storage_service::{StorageRequestPayload, StorageResponseSuccess};
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::service::{IOCondition, MioPeerMockedId, StorageResponse};
+use tezos_identity::Identity;
+use tezos_messages::p2p::binary_message::MessageHash;
+use tezos_messages::p2p::encoding::peer::PeerMessage;
+use tezos_messages::p2p::encoding::protocol::{Component, GetProtocolsMessage, Protocol};
+
+fn data() -> (Cluster, MioPeerMockedId) {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 2,
+        peers_connected_max: 2,
+        ..default_test_config()
+    });
+    let mut cluster = Cluster::new(state, initial_time);
+
+    let peer_id = cluster.peer_init(0.0);
+    cluster.connect_to_peer(peer_id);
+    cluster.set_peer_connected(peer_id);
+    cluster.do_handshake(peer_id).unwrap();
+
+    (cluster, peer_id)
+}
+
+fn protocol(implementation: &str) -> (ProtocolHash, Protocol) {
+    let protocol = Protocol::new(
+        1,
+        vec![Component::new(
+            "Main".to_owned(),
+            None,
+            implementation.to_owned(),
+        )],
+    );
+    (protocol.message_typed_hash().unwrap(), protocol)
+}
+
+/// Sends `GetProtocols` to the node and answers its storage requests
+/// with `stored` protocols.
+///
+/// Returns the protocols sent back by the node.
+fn test(requested: Vec<ProtocolHash>, stored: Vec<(ProtocolHash, Protocol)>) -> Vec<Protocol> {
+    let (mut cluster, peer_id) = data();
+
+    let peer = cluster.peer(peer_id);
+    peer.send_peer_message(PeerMessage::GetProtocols(GetProtocolsMessage::new(
+        requested.clone(),
+    )));
+    peer.set_read_cond(IOCondition::NoLimit)
+        .set_write_cond(IOCondition::NoLimit);
+    cluster.dispatch_peer_ready_event(peer_id, true, true, false);
+
+    let mut storage_requested = vec![];
+    while let Some(req) = cluster.service().storage().requests.pop_front() {
+        let protocol_hash = match req.payload {
+            StorageRequestPayload::ProtocolGet(protocol_hash) => protocol_hash,
+            _ => continue,
+        };
+        let protocol = stored
+            .iter()
+            .find(|(hash, _)| hash == &protocol_hash)
+            .map(|(_, protocol)| protocol.clone());
+        storage_requested.push(protocol_hash.clone());
+
+        cluster
+            .service()
+            .storage()
+            .responses
+            .push_back(StorageResponse {
+                req_id: req.id,
+                result: Ok(StorageResponseSuccess::ProtocolGetSuccess(
+                    protocol_hash,
+                    protocol,
+                )),
+            });
+        cluster.dispatch(WakeupEvent);
+    }
+    assert_eq!(storage_requested, requested);
+
+    let mut sent = vec![];
+    loop {
+        cluster.dispatch_peer_ready_event(peer_id, true, true, false);
+        let msg = match cluster.peer(peer_id).read_peer_message() {
+            Some(msg) => msg,
+            None => return sent,
+        };
+        if let PeerMessage::Protocol(msg) = msg {
+            sent.push(msg.protocol().clone());
+        }
+    }
+}
+
+#[test]
+fn test_get_protocols_known() {
+    let (hash1, protocol1) = protocol("let x = 1");
+    let (hash2, protocol2) = protocol("let x = 2");
+
+    let sent = test(
+        vec![hash1.clone(), hash2.clone()],
+        vec![(hash1, protocol1.clone()), (hash2, protocol2.clone())],
+    );
+    assert_eq!(sent, vec![protocol1, protocol2]);
+}
+
+/// Like Octez, unknown protocols are not answered.
+#[test]
+fn test_get_protocols_unknown() {
+    let (hash1, protocol1) = protocol("let x = 1");
+    let (hash2, _) = protocol("let x = 2");
+
+    let sent = test(vec![hash1.clone(), hash2], vec![(hash1, protocol1.clone())]);
+    assert_eq!(sent, vec![protocol1]);
+
+    let (hash3, _) = protocol("let x = 3");
+    assert!(test(vec![hash3], vec![]).is_empty());
+}
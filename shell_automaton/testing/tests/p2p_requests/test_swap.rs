@@ -0,0 +1,152 @@
+use std::{
+    convert::TryFrom,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::service::{IOCondition, MioPeerMockedId};
+use tezos_identity::Identity;
+use tezos_messages::p2p::encoding::peer::PeerMessage;
+use tezos_messages::p2p::encoding::swap::SwapMessage;
+
+fn data(peers_swap_disable: bool) -> Cluster {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 4,
+        peers_connected_max: 4,
+        peers_swap_disable,
+        ..default_test_config()
+    });
+    Cluster::new(state, initial_time)
+}
+
+fn handshaked_peer(cluster: &mut Cluster) -> MioPeerMockedId {
+    let peer_id = cluster.peer_init(0.0);
+    cluster.connect_to_peer(peer_id);
+    cluster.set_peer_connected(peer_id);
+    cluster.do_handshake(peer_id).unwrap();
+    peer_id
+}
+
+fn is_handshaked(cluster: &Cluster, address: SocketAddr) -> bool {
+    cluster
+        .state()
+        .peers
+        .get(&address)
+        .map_or(false, |peer| peer.is_handshaked())
+}
+
+/// Sends the swap request proposing `proposed` from `peer_id`.
+///
+/// Returns the swap ack sent back by the node, if any.
+fn swap_request(
+    cluster: &mut Cluster,
+    peer_id: MioPeerMockedId,
+    proposed: MioPeerMockedId,
+) -> Option<SwapMessage> {
+    let proposed_peer_id = cluster.peer(proposed).identity().peer_id();
+    cluster
+        .peer(peer_id)
+        .set_read_cond(IOCondition::NoLimit)
+        .set_write_cond(IOCondition::NoLimit)
+        .send_peer_message(PeerMessage::SwapRequest(SwapMessage::new(
+            proposed.to_ipv4().to_string(),
+            proposed_peer_id,
+        )));
+
+    let mut ack = None;
+    loop {
+        cluster.dispatch_peer_ready_event(peer_id, true, true, false);
+        match cluster.peer(peer_id).read_peer_message() {
+            Some(PeerMessage::SwapAck(msg)) => ack = Some(msg),
+            Some(_) => continue,
+            None => return ack,
+        }
+    }
+}
+
+#[test]
+fn test_swap_request_accepted() {
+    let mut cluster = data(false);
+    let requester = handshaked_peer(&mut cluster);
+    let other = handshaked_peer(&mut cluster);
+    let proposed = cluster.peer_init(0.0);
+
+    let ack = swap_request(&mut cluster, requester, proposed).expect("Expected SwapAck");
+    // we propose our other peer in exchange.
+    assert_eq!(
+        ack.point().parse::<SocketAddr>().unwrap().ip(),
+        other.to_ipv4().ip()
+    );
+    assert_eq!(ack.peer_id(), &cluster.peer(other).identity().peer_id());
+
+    let pending = cluster.state().peers.swap.pending.clone().unwrap();
+    assert_eq!(pending.connect, proposed.to_ipv4());
+    assert_eq!(pending.disconnect, requester.to_ipv4());
+
+    // once connected to the proposed peer, we disconnect from the requester.
+    cluster.set_peer_connected(proposed);
+    cluster.do_handshake(proposed).unwrap();
+
+    assert!(is_handshaked(&cluster, proposed.to_ipv4()));
+    assert!(is_handshaked(&cluster, other.to_ipv4()));
+    assert!(!is_handshaked(&cluster, requester.to_ipv4()));
+    assert!(cluster.state().peers.swap.pending.is_none());
+    assert_ne!(cluster.state().peers.swap.last_successful, 0);
+}
+
+#[test]
+fn test_swap_request_pending() {
+    let mut cluster = data(false);
+    let requester = handshaked_peer(&mut cluster);
+    let other = handshaked_peer(&mut cluster);
+    let proposed = cluster.peer_init(0.0);
+
+    assert!(swap_request(&mut cluster, requester, proposed).is_some());
+
+    // swap is pending, other requests are ignored.
+    let proposed2 = cluster.peer_init(0.0);
+    assert!(swap_request(&mut cluster, other, proposed2).is_none());
+    assert!(cluster.state().peers.get(&proposed2.to_ipv4()).is_none());
+}
+
+#[test]
+fn test_swap_request_already_connected() {
+    let mut cluster = data(false);
+    let requester = handshaked_peer(&mut cluster);
+    let other = handshaked_peer(&mut cluster);
+
+    assert!(swap_request(&mut cluster, requester, other).is_none());
+    assert!(cluster.state().peers.swap.pending.is_none());
+    assert!(is_handshaked(&cluster, requester.to_ipv4()));
+}
+
+#[test]
+fn test_swap_request_disabled() {
+    let mut cluster = data(true);
+    let requester = handshaked_peer(&mut cluster);
+    handshaked_peer(&mut cluster);
+    let proposed = cluster.peer_init(0.0);
+
+    assert!(swap_request(&mut cluster, requester, proposed).is_none());
+    assert!(cluster.state().peers.swap.pending.is_none());
+    assert!(cluster.state().peers.get(&proposed.to_ipv4()).is_none());
+}
@@ -0,0 +1,256 @@
+use std::{
+    convert::TryFrom,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::event::WakeupEvent;
+use shell_automaton::service::rpc_service::{
+    NetworkCommand, NetworkCommandError, NetworkTarget, RpcId, RpcRequest,
+};
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::service::MioPeerMockedId;
+use tezos_identity::Identity;
+
+fn build_cluster() -> Cluster {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 2,
+        peers_connected_max: 2,
+        peers_graylist_disable: false,
+        ..default_test_config()
+    });
+
+    Cluster::new(state, initial_time)
+}
+
+/// Sends the rpc request and returns the single response to it.
+fn rpc(cluster: &mut Cluster, id: u64, request: RpcRequest) -> serde_json::Value {
+    let rpc_id = RpcId::new(id);
+    cluster.service().rpc.requests.push_back((request, rpc_id));
+    cluster.dispatch(WakeupEvent {});
+
+    let responses = std::mem::take(&mut cluster.service().rpc.responses);
+    assert_eq!(responses.len(), 1, "{:?}", responses);
+    let (response_id, response) = responses.into_iter().next().unwrap();
+    assert_eq!(response_id, rpc_id);
+    response
+}
+
+fn network_command(
+    cluster: &mut Cluster,
+    id: u64,
+    target: NetworkTarget,
+    command: NetworkCommand,
+) -> Result<(), NetworkCommandError> {
+    network_commands(cluster, id, target, vec![command])
+}
+
+fn network_commands(
+    cluster: &mut Cluster,
+    id: u64,
+    target: NetworkTarget,
+    commands: Vec<NetworkCommand>,
+) -> Result<(), NetworkCommandError> {
+    let response = rpc(cluster, id, RpcRequest::NetworkCommand { target, commands });
+    serde_json::from_value(response).unwrap()
+}
+
+fn point(index: usize) -> NetworkTarget {
+    NetworkTarget::Point(MioPeerMockedId::new_unchecked(index).to_ipv4())
+}
+
+fn is_blacklisted(cluster: &Cluster, address: SocketAddr) -> bool {
+    cluster.state().peers.is_blacklisted(&address.ip())
+}
+
+#[test]
+fn test_network_command_trust() {
+    let mut cluster = build_cluster();
+    let address = MioPeerMockedId::new_unchecked(0).to_ipv4();
+
+    assert_eq!(
+        network_command(&mut cluster, 1, point(0), NetworkCommand::Trust),
+        Ok(())
+    );
+    assert!(cluster.state().peers.acl.trusted.contains(&address));
+
+    // already trusted.
+    assert_eq!(
+        network_command(&mut cluster, 2, point(0), NetworkCommand::Trust),
+        Err(NetworkCommandError::Unchanged)
+    );
+
+    // trusted peers can't be banned.
+    assert_eq!(
+        network_command(&mut cluster, 3, point(0), NetworkCommand::Ban),
+        Err(NetworkCommandError::NotAllowed)
+    );
+    assert!(!is_blacklisted(&cluster, address));
+
+    assert_eq!(
+        network_command(&mut cluster, 4, point(0), NetworkCommand::Open),
+        Ok(())
+    );
+    assert!(!cluster.state().peers.acl.trusted.contains(&address));
+}
+
+#[test]
+fn test_network_command_ban() {
+    let mut cluster = build_cluster();
+    let address = MioPeerMockedId::new_unchecked(0).to_ipv4();
+
+    assert_eq!(
+        network_command(&mut cluster, 1, point(0), NetworkCommand::Ban),
+        Ok(())
+    );
+    assert!(is_blacklisted(&cluster, address));
+
+    // banned points can't be connected to.
+    assert_eq!(
+        network_command(&mut cluster, 2, point(0), NetworkCommand::Connect),
+        Err(NetworkCommandError::NotAllowed)
+    );
+
+    assert_eq!(
+        network_command(&mut cluster, 3, point(0), NetworkCommand::Open),
+        Ok(())
+    );
+    assert!(!is_blacklisted(&cluster, address));
+
+    assert_eq!(
+        network_command(&mut cluster, 4, point(0), NetworkCommand::Connect),
+        Ok(())
+    );
+    assert!(cluster.state().peers.get(&address).is_some());
+}
+
+#[test]
+fn test_network_commands_not_applied_halfway() {
+    let mut cluster = build_cluster();
+    let address = MioPeerMockedId::new_unchecked(0).to_ipv4();
+
+    assert_eq!(
+        network_command(&mut cluster, 1, point(0), NetworkCommand::Ban),
+        Ok(())
+    );
+
+    // connect is rejected, so the point isn't trusted either.
+    assert_eq!(
+        network_commands(
+            &mut cluster,
+            2,
+            point(0),
+            vec![NetworkCommand::Trust, NetworkCommand::Connect],
+        ),
+        Err(NetworkCommandError::NotAllowed)
+    );
+    assert!(!cluster.state().peers.acl.trusted.contains(&address));
+    assert!(is_blacklisted(&cluster, address));
+
+    assert_eq!(
+        network_commands(
+            &mut cluster,
+            3,
+            point(0),
+            vec![NetworkCommand::Open, NetworkCommand::Connect],
+        ),
+        Ok(())
+    );
+    assert!(!is_blacklisted(&cluster, address));
+    assert!(cluster.state().peers.get(&address).is_some());
+}
+
+#[test]
+fn test_network_command_untrust() {
+    let mut cluster = build_cluster();
+    let address = MioPeerMockedId::new_unchecked(0).to_ipv4();
+
+    assert_eq!(
+        network_command(&mut cluster, 1, point(0), NetworkCommand::Untrust),
+        Err(NetworkCommandError::Unchanged)
+    );
+    assert_eq!(
+        network_command(&mut cluster, 2, point(0), NetworkCommand::Trust),
+        Ok(())
+    );
+    assert_eq!(
+        network_command(&mut cluster, 3, point(0), NetworkCommand::Untrust),
+        Ok(())
+    );
+    assert!(!cluster.state().peers.acl.trusted.contains(&address));
+}
+
+#[test]
+fn test_network_command_peer_id() {
+    let mut cluster = build_cluster();
+    let peer = cluster.peer_init(0.0);
+    let peer_id = cluster
+        .peer(peer)
+        .identity()
+        .public_key
+        .public_key_hash()
+        .unwrap();
+
+    assert_eq!(
+        network_command(
+            &mut cluster,
+            1,
+            NetworkTarget::PeerId(peer_id.clone()),
+            NetworkCommand::Ban,
+        ),
+        Err(NetworkCommandError::UnknownPeer)
+    );
+
+    cluster.connect_to_peer(peer);
+    cluster.set_peer_connected(peer);
+    cluster.do_handshake(peer).unwrap();
+
+    assert_eq!(
+        network_command(
+            &mut cluster,
+            2,
+            NetworkTarget::PeerId(peer_id.clone()),
+            NetworkCommand::Ban,
+        ),
+        Ok(())
+    );
+    assert!(is_blacklisted(&cluster, peer.to_ipv4()));
+
+    // the banned peer is still known by its peer id.
+    assert_eq!(
+        network_command(
+            &mut cluster,
+            3,
+            NetworkTarget::PeerId(peer_id),
+            NetworkCommand::Open,
+        ),
+        Ok(())
+    );
+    assert!(!is_blacklisted(&cluster, peer.to_ipv4()));
+}
+
+#[test]
+fn test_network_greylist_clear() {
+    let mut cluster = build_cluster();
+
+    let response = rpc(&mut cluster, 1, RpcRequest::NetworkGreylistClear);
+    assert_eq!(response, serde_json::Value::Null);
+}
@@ -0,0 +1,101 @@
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime};
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::service::p2p_recorder_service::{
+    P2pRecord, P2pRecordDirection, P2pRecordEvent,
+};
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::replay::p2p_replay;
+use tezos_identity::Identity;
+use tezos_messages::p2p::binary_message::BinaryWrite;
+use tezos_messages::p2p::encoding::peer::{PeerMessage, PeerMessageResponse};
+
+fn build_cluster() -> Cluster {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 2,
+        peers_connected_max: 2,
+        ..default_test_config()
+    });
+
+    Cluster::new(state, initial_time)
+}
+
+fn record(time: u64, event: P2pRecordEvent) -> P2pRecord {
+    P2pRecord {
+        time,
+        address: ([1, 2, 3, 4], 9732).into(),
+        event,
+    }
+}
+
+fn message_record(time: u64, direction: P2pRecordDirection, message: PeerMessage) -> P2pRecord {
+    let raw = PeerMessageResponse::from(message.clone())
+        .as_bytes()
+        .unwrap();
+    record(
+        time,
+        P2pRecordEvent::Message {
+            direction,
+            raw,
+            message: Some(message),
+        },
+    )
+}
+
+#[test]
+fn test_p2p_replay_bootstrap() {
+    let mut cluster = build_cluster();
+    let peer_pkh = Identity::generate(0.0).unwrap().peer_id();
+
+    let records = vec![
+        message_record(0, P2pRecordDirection::Incoming, PeerMessage::Bootstrap),
+        record(
+            1_000,
+            P2pRecordEvent::Handshaked {
+                public_key_hash: peer_pkh,
+                port: 9732,
+                disable_mempool: false,
+                private_node: false,
+            },
+        ),
+        message_record(2_000, P2pRecordDirection::Incoming, PeerMessage::Bootstrap),
+        record(
+            3_000,
+            P2pRecordEvent::Message {
+                direction: P2pRecordDirection::Incoming,
+                raw: vec![0xff, 0xff],
+                message: None,
+            },
+        ),
+        record(4_000, P2pRecordEvent::Disconnected),
+        message_record(5_000, P2pRecordDirection::Incoming, PeerMessage::Bootstrap),
+    ];
+
+    let (_, report) = p2p_replay(&mut cluster, &records).unwrap();
+
+    // only messages of the first connection after the handshake are sent.
+    assert_eq!(report.sent, 1);
+    assert_eq!(report.skipped, 1);
+    assert!(report
+        .responses
+        .iter()
+        .any(|message| matches!(message, PeerMessage::Advertise(_))));
+}
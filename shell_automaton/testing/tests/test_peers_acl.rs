@@ -0,0 +1,81 @@
+use std::{
+    convert::TryFrom,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::peer::PeerStatus;
+use shell_automaton::peers::acl::PeersAclTrustedReconnectAction;
+use shell_automaton::peers::add::multi::PeersAddMultiAction;
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use shell_automaton_testing::service::MioPeerMockedId;
+use tezos_identity::Identity;
+
+fn build_cluster(peers_trusted: Vec<SocketAddr>) -> Cluster {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 2,
+        peers_connected_max: 1,
+        peers_trusted,
+        ..default_test_config()
+    });
+
+    Cluster::new(state, initial_time)
+}
+
+fn is_connecting(cluster: &Cluster, address: &SocketAddr) -> bool {
+    cluster.state().peers.get(address).map_or(false, |peer| {
+        matches!(peer.status, PeerStatus::Connecting(_))
+    })
+}
+
+/// Trusted peer is connected even if it's already known as a potential peer
+/// and we have enough connected peers.
+#[test]
+fn test_trusted_reconnect_potential_peer() {
+    let mut cluster = build_cluster(vec![MioPeerMockedId::new_unchecked(1).to_ipv4()]);
+    let other = cluster.peer_init(0.0);
+    let trusted = cluster.peer_init(0.0);
+    assert_eq!(trusted.index(), 1);
+
+    cluster.connect_to_peer(other);
+    cluster.set_peer_connected(other);
+    cluster.do_handshake(other).unwrap();
+
+    // we have enough peers, so the trusted peer stays potential.
+    cluster.dispatch(PeersAddMultiAction {
+        addresses: vec![trusted.to_ipv4()],
+    });
+    assert!(matches!(
+        cluster
+            .state()
+            .peers
+            .get(&trusted.to_ipv4())
+            .map(|peer| &peer.status),
+        Some(PeerStatus::Potential)
+    ));
+
+    assert!(cluster.dispatch(PeersAclTrustedReconnectAction {}));
+    assert!(is_connecting(&cluster, &trusted.to_ipv4()));
+
+    // nothing to reconnect while connecting.
+    cluster.advance_time(Duration::from_secs(60));
+    assert!(!cluster.dispatch(PeersAclTrustedReconnectAction {}));
+}
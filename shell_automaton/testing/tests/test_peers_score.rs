@@ -0,0 +1,150 @@
+use std::{
+    convert::TryFrom,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use crypto::hash::ChainId;
+use shell_automaton::config::default_test_config;
+use shell_automaton::peers::graylist::PeerGraylistReason;
+use shell_automaton::peers::score::{PeerScoreEvent, PeersScoreUpdateAction};
+use shell_automaton::peers::PeerBlacklistState;
+use shell_automaton::shell_compatibility_version::ShellCompatibilityVersion;
+use shell_automaton::{Config, PeersScoreThresholds, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use tezos_identity::Identity;
+
+fn build_cluster(peers_graylist_disable: bool, thresholds: PeersScoreThresholds) -> Cluster {
+    let initial_time = SystemTime::now();
+
+    let state = State::new(Config {
+        initial_time,
+        pow_target: 0.0,
+        identity: Identity::generate(0.0).unwrap(),
+        shell_compatibility_version: ShellCompatibilityVersion::new(
+            "TEZOS_LOCALNET".to_owned(),
+            vec![1],
+            vec![1],
+        ),
+        chain_id: ChainId::try_from("NetXz969SFaFn8k").unwrap(), // granada
+        check_timeouts_interval: Duration::from_millis(500),
+        peer_connecting_timeout: Duration::from_millis(2000),
+        peer_handshaking_timeout: Duration::from_secs(8),
+        peers_potential_max: 2,
+        peers_connected_max: 2,
+        peers_graylist_disable,
+        peers_score_thresholds: thresholds,
+        ..default_test_config()
+    });
+
+    Cluster::new(state, initial_time)
+}
+
+/// Returns the address of the handshaked peer.
+fn handshaked_peer(cluster: &mut Cluster) -> SocketAddr {
+    let peer_id = cluster.peer_init(0.0);
+
+    cluster.connect_to_peer(peer_id);
+    cluster.set_peer_connected(peer_id);
+    cluster.do_handshake(peer_id).unwrap();
+
+    peer_id.to_ipv4()
+}
+
+fn misbehaved(cluster: &mut Cluster, address: SocketAddr, reason: PeerGraylistReason) {
+    cluster.dispatch(PeersScoreUpdateAction {
+        address,
+        event: PeerScoreEvent::Misbehaved(reason),
+    });
+}
+
+fn is_handshaked(cluster: &Cluster, address: &SocketAddr) -> bool {
+    cluster
+        .state()
+        .peers
+        .get(address)
+        .map_or(false, |peer| peer.is_handshaked())
+}
+
+#[test]
+fn test_peer_score_above_thresholds() {
+    let mut cluster = build_cluster(false, PeersScoreThresholds::default());
+    let address = handshaked_peer(&mut cluster);
+
+    misbehaved(&mut cluster, address, PeerGraylistReason::ConnectionClosed);
+    cluster.dispatch(PeersScoreUpdateAction {
+        address,
+        event: PeerScoreEvent::Timeout,
+    });
+
+    assert!(is_handshaked(&cluster, &address));
+    assert!(!cluster.state().peers.is_blacklisted(&address.ip()));
+}
+
+#[test]
+fn test_peer_score_disconnect_threshold() {
+    let mut cluster = build_cluster(false, PeersScoreThresholds::default());
+    let address = handshaked_peer(&mut cluster);
+
+    // -25 twice reaches the disconnect threshold.
+    misbehaved(&mut cluster, address, PeerGraylistReason::ConnectionClosed);
+    assert!(is_handshaked(&cluster, &address));
+    misbehaved(&mut cluster, address, PeerGraylistReason::ConnectionClosed);
+
+    assert!(!is_handshaked(&cluster, &address));
+    assert!(!cluster.state().peers.is_blacklisted(&address.ip()));
+}
+
+#[test]
+fn test_peer_score_graylist_threshold() {
+    let mut cluster = build_cluster(false, PeersScoreThresholds::default());
+    let address = handshaked_peer(&mut cluster);
+
+    misbehaved(&mut cluster, address, PeerGraylistReason::MessageReadError);
+
+    assert!(!is_handshaked(&cluster, &address));
+    assert!(matches!(
+        cluster.state().peers.get_blacklisted_ip(&address.ip()),
+        Some(PeerBlacklistState::Graylisted { .. })
+    ));
+}
+
+#[test]
+fn test_peer_score_ban_threshold() {
+    let mut cluster = build_cluster(
+        false,
+        PeersScoreThresholds {
+            ban: -250,
+            ..PeersScoreThresholds::default()
+        },
+    );
+    let address = handshaked_peer(&mut cluster);
+
+    misbehaved(
+        &mut cluster,
+        address,
+        PeerGraylistReason::RequestedBlockHeaderLevelMismatch,
+    );
+
+    assert!(!is_handshaked(&cluster, &address));
+    assert!(matches!(
+        cluster.state().peers.get_blacklisted_ip(&address.ip()),
+        Some(PeerBlacklistState::Banned { .. })
+    ));
+}
+
+#[test]
+fn test_peer_score_graylist_disabled() {
+    let mut cluster = build_cluster(true, PeersScoreThresholds::default());
+    let address = handshaked_peer(&mut cluster);
+
+    misbehaved(
+        &mut cluster,
+        address,
+        PeerGraylistReason::RequestedBlockHeaderLevelMismatch,
+    );
+
+    // peer is only disconnected.
+    assert!(!is_handshaked(&cluster, &address));
+    assert!(!cluster.state().peers.is_blacklisted(&address.ip()));
+}
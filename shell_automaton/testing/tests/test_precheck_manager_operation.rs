@@ -0,0 +1,232 @@
+use std::{collections::HashMap, convert::TryInto, time::SystemTime};
+
+use crypto::hash::{BlockHash, HashTrait, OperationHash};
+use shell_automaton::config::default_test_config;
+use shell_automaton::event::WakeupEvent;
+use shell_automaton::mempool::{BroadcastState, HeadState, MempoolOperation, OperationState};
+use shell_automaton::prechecker::prechecker_actions::PrecheckerValidateManagerOperationAction;
+use shell_automaton::prechecker::{
+    Key, OperationDecodedContents, PrecheckerOperation, PrecheckerOperationState,
+};
+use shell_automaton::protocol_runner::{ProtocolRunnerReadyState, ProtocolRunnerResponseAction};
+use shell_automaton::service::protocol_runner_service::ProtocolRunnerResult;
+use shell_automaton::{ActionId, Config, State};
+use shell_automaton_testing::one_real_node_cluster::Cluster;
+use tezos_messages::base::contract::ContractId;
+use tezos_messages::base::signature_public_key::{SignaturePublicKey, SignaturePublicKeyHash};
+use tezos_messages::p2p::binary_message::{BinaryRead, BinaryWrite};
+use tezos_messages::p2p::encoding::block_header::BlockHeaderBuilder;
+use tezos_messages::p2p::encoding::operation::Operation;
+use tezos_messages::protocol::proto_012::operation::Operation as ProtocolOperation;
+
+const HEAD_LEVEL: i32 = 200;
+const HEAD_HASH: &str = "BKpbfCvh777DQHnXjU2sqHvVUNZ7dBAdqEfKkdw8EGSkD9LSYXb";
+const SOURCE: &str = "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx";
+/// Public key the [`transaction`] is not signed with.
+const PUBLIC_KEY: &str = "edpkuQ1C2vXZk7F2bQCjNTN99gRVsCdRKLQbfvxsRQ1pnh55R733Vk";
+
+fn head_hash() -> BlockHash {
+    BlockHash::from_base58_check(HEAD_HASH).unwrap()
+}
+
+fn op_hash() -> OperationHash {
+    OperationHash::try_from_bytes(&[1; 32]).unwrap()
+}
+
+fn key() -> Key {
+    (&op_hash()).into()
+}
+
+/// Transaction of the [`SOURCE`] with counter `732`.
+fn transaction(branch: &BlockHash) -> ProtocolOperation {
+    serde_json::from_value(serde_json::json!({
+        "branch": branch.to_base58_check(),
+        "contents": [{
+            "kind": "transaction",
+            "source": SOURCE,
+            "fee": "10000",
+            "counter": "732",
+            "gas_limit": "10000",
+            "storage_limit": "257",
+            "amount": "407",
+            "destination": SOURCE
+        }],
+        "signature": "sigbQ5ZNvkjvGssJgoAnUAfY4Wvvg3QZqawBYB1j1VDBNTMBAALnCzRHWzer34bnfmzgHg3EvwdzQKdxgSghB897cono6gbQ"
+    }))
+    .unwrap()
+}
+
+/// Cluster with the `operation` decoded by the prechecker and the mempool.
+fn build_cluster(operation: ProtocolOperation) -> Cluster {
+    let initial_time = SystemTime::now();
+
+    let mut state = State::new(Config {
+        initial_time,
+        ..default_test_config()
+    });
+    state.protocol_runner = ProtocolRunnerReadyState {
+        genesis_commit_hash: None,
+        latest_context_hashes: vec![],
+    }
+    .into();
+
+    let header = BlockHeaderBuilder::default()
+        .level(HEAD_LEVEL)
+        .proto(1)
+        .predecessor(BlockHash::try_from_bytes(&[2; 32]).unwrap())
+        .timestamp(0i64.into())
+        .validation_pass(4)
+        .operations_hash(
+            "LLoZS2LW3rEi7KYU4ouBQtorua37aWWCtpDmv1n2x3xoKi6sVXLWp"
+                .try_into()
+                .unwrap(),
+        )
+        .fitness(vec![HEAD_LEVEL.to_be_bytes().to_vec()].into())
+        .context(
+            "CoV8SQumiVU9saiu3FVNeDNewJaJH8yWdsGF3WLdsRr2P9S7MzCj"
+                .try_into()
+                .unwrap(),
+        )
+        .protocol_data(vec![0, 1, 2, 3, 4, 5, 6, 7, 8].into())
+        .build()
+        .unwrap();
+    state.mempool.local_head_state = Some(HeadState {
+        header,
+        hash: head_hash(),
+    });
+    state.mempool.operations_state.insert(
+        op_hash(),
+        MempoolOperation {
+            level: HEAD_LEVEL,
+            state: OperationState::Decoded,
+            broadcast: BroadcastState::Pending,
+            operation_decoded_contents: None,
+            times: HashMap::new(),
+        },
+    );
+
+    let operation_binary_encoding = operation.as_bytes().unwrap();
+    state.prechecker.operations.insert(
+        key(),
+        PrecheckerOperation {
+            start: ActionId::new_unchecked(0),
+            operation: Operation::from_bytes(&operation_binary_encoding).unwrap(),
+            operation_binary_encoding,
+            state: PrecheckerOperationState::DecodedContentReady {
+                operation_decoded_contents: OperationDecodedContents::Proto012(operation),
+            },
+        },
+    );
+
+    Cluster::new(state, initial_time)
+}
+
+fn operation_state(cluster: &Cluster) -> OperationState {
+    cluster.state().mempool.operations_state[&op_hash()].state
+}
+
+/// Validates the operation and responds to the context read with the manager's `counter`.
+fn precheck_with_counter(cluster: &mut Cluster, counter: &[u8]) {
+    precheck_with_context(cluster, vec![("counter", counter.to_vec())]);
+}
+
+/// Validates the operation and responds to the context read with the manager's `fields`.
+fn precheck_with_context(cluster: &mut Cluster, fields: Vec<(&str, Vec<u8>)>) {
+    assert!(cluster.dispatch(PrecheckerValidateManagerOperationAction { key: key() }));
+    let token = match &cluster.state().prechecker.operations[&key()].state {
+        PrecheckerOperationState::PendingManagerContext { token, .. } => *token,
+        state => panic!("unexpected prechecker state: {:?}", state),
+    };
+
+    let source = SignaturePublicKeyHash::from_b58_hash(SOURCE).unwrap();
+    let path = ContractId::Implicit(source).context_path().unwrap();
+    let key_values = fields
+        .into_iter()
+        .map(|(field, value)| {
+            let mut key = path.clone();
+            key.push(field.to_string());
+            (key, value)
+        })
+        .collect();
+    assert!(cluster.dispatch(ProtocolRunnerResponseAction {
+        result: ProtocolRunnerResult::ContextKeyValuesByPrefixGet((token, Ok(Some(key_values)))),
+    }));
+}
+
+#[test]
+fn test_precheck_manager_operation_counter_in_the_past() {
+    let mut cluster = build_cluster(transaction(&head_hash()));
+
+    // operation's counter `732` is already used.
+    precheck_with_counter(&mut cluster, &[0xdc, 0x05]);
+
+    assert!(!cluster.state().prechecker.operations.contains_key(&key()));
+    assert!(matches!(
+        operation_state(&cluster),
+        OperationState::PrecheckRefused
+    ));
+}
+
+#[test]
+fn test_precheck_manager_operation_unrevealed_key() {
+    let mut cluster = build_cluster(transaction(&head_hash()));
+
+    // counter `731`, but the key is not revealed, so the signature is left to the protocol.
+    precheck_with_counter(&mut cluster, &[0xdb, 0x05]);
+
+    assert!(!cluster.state().prechecker.operations.contains_key(&key()));
+    assert!(matches!(operation_state(&cluster), OperationState::Decoded));
+}
+
+#[test]
+fn test_precheck_manager_operation_signature_mismatch() {
+    let mut cluster = build_cluster(transaction(&head_hash()));
+
+    // revealed key the operation is not signed with.
+    let public_key = SignaturePublicKey::from_b58_hash(PUBLIC_KEY).unwrap();
+    let mut manager = vec![1];
+    manager.extend(public_key.as_bytes().unwrap());
+    precheck_with_context(
+        &mut cluster,
+        vec![("counter", vec![0xdb, 0x05]), ("manager", manager)],
+    );
+
+    // signature is being verified by the service.
+    assert!(matches!(
+        cluster.state().prechecker.operations[&key()].state,
+        PrecheckerOperationState::ManagerContextReady { .. }
+    ));
+    assert!(matches!(operation_state(&cluster), OperationState::Decoded));
+
+    assert!(cluster.dispatch(WakeupEvent {}));
+
+    assert!(!cluster.state().prechecker.operations.contains_key(&key()));
+    assert!(matches!(
+        operation_state(&cluster),
+        OperationState::PrecheckRefused
+    ));
+}
+
+#[test]
+fn test_precheck_manager_operation_unknown_branch() {
+    let branch = BlockHash::try_from_bytes(&[3; 32]).unwrap();
+    let mut cluster = build_cluster(transaction(&branch));
+
+    // refused without reading the context.
+    assert!(cluster.dispatch(PrecheckerValidateManagerOperationAction { key: key() }));
+
+    assert!(!cluster.state().prechecker.operations.contains_key(&key()));
+    assert!(matches!(
+        operation_state(&cluster),
+        OperationState::PrecheckRefused
+    ));
+}
+
+#[test]
+fn test_precheck_manager_operation_enabling_conditions() {
+    let mut cluster = build_cluster(transaction(&head_hash()));
+
+    assert!(cluster.dispatch(PrecheckerValidateManagerOperationAction { key: key() }));
+    // the operation is already waiting for the context.
+    assert!(!cluster.dispatch(PrecheckerValidateManagerOperationAction { key: key() }));
+}
@@ -15,6 +15,7 @@ use shell_automaton::block_applier;
 use shell_automaton::current_head_precheck;
 use shell_automaton::fuzzing::state_singleton::FUZZER_STATE;
 use shell_automaton::mempool::mempool_actions;
+use shell_automaton::mempool::storage as mempool_storage;
 use shell_automaton::mempool::validator as mempool_validator;
 use shell_automaton::peers::init::PeersInitAction;
 use shell_automaton::prechecker::prechecker_actions;
@@ -157,20 +158,36 @@ use shell_automaton::{
         PeerTryWriteLoopFinishAction, PeerTryWriteLoopStartAction,
     },
     peers::{
+        acl::{
+            PeersAclIncomingSetAction, PeersAclTrustedAddAction, PeersAclTrustedReconnectAction,
+            PeersAclTrustedRemoveAction,
+        },
         add::{multi::PeersAddMultiAction, PeersAddIncomingPeerAction},
+        address_book::{
+            PeersAddressBookLoadErrorAction, PeersAddressBookLoadInitAction,
+            PeersAddressBookLoadPendingAction, PeersAddressBookLoadSuccessAction,
+        },
         check::timeouts::{
             PeersCheckTimeoutsCleanupAction, PeersCheckTimeoutsInitAction,
             PeersCheckTimeoutsSuccessAction,
         },
+        connect::PeersConnectPointAction,
         dns_lookup::{
             PeersDnsLookupCleanupAction, PeersDnsLookupErrorAction, PeersDnsLookupInitAction,
             PeersDnsLookupSuccessAction,
         },
         graylist::{
-            PeersGraylistAddressAction, PeersGraylistIpAddAction, PeersGraylistIpAddedAction,
-            PeersGraylistIpRemoveAction, PeersGraylistIpRemovedAction,
+            PeersGraylistAddressAction, PeersGraylistAddressBanAction,
+            PeersGraylistAddressUnbanAction, PeersGraylistClearAction, PeersGraylistIpAddAction,
+            PeersGraylistIpAddedAction, PeersGraylistIpBanAction, PeersGraylistIpRemoveAction,
+            PeersGraylistIpRemovedAction,
         },
         remove::PeersRemoveAction,
+        score::PeersScoreUpdateAction,
+        swap::{
+            PeersSwapAckReceivedAction, PeersSwapErrorAction, PeersSwapInitAction,
+            PeersSwapRequestReceivedAction, PeersSwapRequestSendAction, PeersSwapSuccessAction,
+        },
     },
     storage,
 };
@@ -182,6 +199,9 @@ pub static FUZZER_ARGS: Lazy<RwLock<Option<fuzzcheck::Arguments>>> =
 enum AllActionsTest {
     TestControl(ControlActionTest),
     TestPeersDnsAction(PeersDnsLookupActionTest),
+    TestPeersAddressBookAction(PeersAddressBookActionTest),
+    TestPeersSwapAction(PeersSwapActionTest),
+    TestPeersAclAction(PeersAclActionTest),
     TestPeerActions(PeerActionTest),
     TestStorage(StorageActionTest),
     TestMempool(MempoolActionTest),
@@ -200,6 +220,9 @@ impl AllActionsTest {
         match self.clone() {
             Self::TestControl(a) => a.to_action(),
             Self::TestPeersDnsAction(a) => a.to_action(),
+            Self::TestPeersAddressBookAction(a) => a.to_action(),
+            Self::TestPeersSwapAction(a) => a.to_action(),
+            Self::TestPeersAclAction(a) => a.to_action(),
             Self::TestPeerActions(a) => a.to_action(),
             Self::TestStorage(a) => a.to_action(),
             Self::TestMempool(a) => a.to_action(),
@@ -424,6 +447,9 @@ impl StatsActionTest {
 enum CurrentHeadActionTest {
     TestCurrentHeadReceivedAction(current_head_precheck::CurrentHeadReceivedAction),
     TestCurrentHeadPrecheckAction(current_head_precheck::CurrentHeadPrecheckAction),
+    TestCurrentHeadPrecheckSignatureVerifiedAction(
+        current_head_precheck::CurrentHeadPrecheckSignatureVerifiedAction,
+    ),
     TestCurrentHeadPrecheckSuccessAction(current_head_precheck::CurrentHeadPrecheckSuccessAction),
     TestCurrentHeadPrecheckRejectedAction(current_head_precheck::CurrentHeadPrecheckRejectedAction),
     TestCurrentHeadErrorAction(current_head_precheck::CurrentHeadErrorAction),
@@ -437,6 +463,7 @@ impl CurrentHeadActionTest {
         match self.clone() {
             Self::TestCurrentHeadReceivedAction(a) => a.into(),
             Self::TestCurrentHeadPrecheckAction(a) => a.into(),
+            Self::TestCurrentHeadPrecheckSignatureVerifiedAction(a) => a.into(),
             Self::TestCurrentHeadPrecheckSuccessAction(a) => a.into(),
             Self::TestCurrentHeadPrecheckRejectedAction(a) => a.into(),
             Self::TestCurrentHeadErrorAction(a) => a.into(),
@@ -862,6 +889,10 @@ enum MempoolActionTest {
     ),
     TestMempoolBlockInjectAction(mempool_actions::BlockInjectAction),
     TestMempoolOperationValidateNext(mempool_actions::MempoolOperationValidateNextAction),
+    TestMempoolFilterSetAction(mempool_actions::MempoolFilterSetAction),
+    TestMempoolOperationBanAction(mempool_actions::MempoolOperationBanAction),
+    TestMempoolOperationUnbanAction(mempool_actions::MempoolOperationUnbanAction),
+    TestMempoolOperationUnbanAllAction(mempool_actions::MempoolOperationUnbanAllAction),
     TestMempoolValidatorInit(mempool_validator::MempoolValidatorInitAction),
     TestMempoolValidatorPending(mempool_validator::MempoolValidatorPendingAction),
     TestMempoolValidatorSuccess(mempool_validator::MempoolValidatorSuccessAction),
@@ -869,6 +900,11 @@ enum MempoolActionTest {
     TestMempoolValidatorValidateInit(mempool_validator::MempoolValidatorValidateInitAction),
     TestMempoolValidatorValidatePending(mempool_validator::MempoolValidatorValidatePendingAction),
     TestMempoolValidatorValidateSuccess(mempool_validator::MempoolValidatorValidateSuccessAction),
+    TestMempoolStorageLoadInit(mempool_storage::MempoolStorageLoadInitAction),
+    TestMempoolStorageLoadPending(mempool_storage::MempoolStorageLoadPendingAction),
+    TestMempoolStorageLoadError(mempool_storage::MempoolStorageLoadErrorAction),
+    TestMempoolStorageLoadSuccess(mempool_storage::MempoolStorageLoadSuccessAction),
+    TestMempoolStorageSync(mempool_storage::MempoolStorageSyncAction),
 }
 
 impl MempoolActionTest {
@@ -892,6 +928,10 @@ impl MempoolActionTest {
             Self::TestMempoolRpcEndorsementsStatusGetAction(a) => a.into(),
             Self::TestMempoolBlockInjectAction(a) => a.into(),
             Self::TestMempoolOperationValidateNext(a) => a.into(),
+            Self::TestMempoolFilterSetAction(a) => a.into(),
+            Self::TestMempoolOperationBanAction(a) => a.into(),
+            Self::TestMempoolOperationUnbanAction(a) => a.into(),
+            Self::TestMempoolOperationUnbanAllAction(a) => a.into(),
             Self::TestMempoolValidatorInit(a) => a.into(),
             Self::TestMempoolValidatorPending(a) => a.into(),
             Self::TestMempoolValidatorSuccess(a) => a.into(),
@@ -899,6 +939,11 @@ impl MempoolActionTest {
             Self::TestMempoolValidatorValidateInit(a) => a.into(),
             Self::TestMempoolValidatorValidatePending(a) => a.into(),
             Self::TestMempoolValidatorValidateSuccess(a) => a.into(),
+            Self::TestMempoolStorageLoadInit(a) => a.into(),
+            Self::TestMempoolStorageLoadPending(a) => a.into(),
+            Self::TestMempoolStorageLoadError(a) => a.into(),
+            Self::TestMempoolStorageLoadSuccess(a) => a.into(),
+            Self::TestMempoolStorageSync(a) => a.into(),
         }
     }
 }
@@ -939,6 +984,19 @@ enum PrecheckerActionTest {
     TestPrecheckerEndorsementValidationRefusedAction(
         prechecker_actions::PrecheckerEndorsementValidationRefusedAction,
     ),
+    TestPrecheckerValidateManagerOperationAction(
+        prechecker_actions::PrecheckerValidateManagerOperationAction,
+    ),
+    TestPrecheckerManagerContextPendingAction(
+        prechecker_actions::PrecheckerManagerContextPendingAction,
+    ),
+    TestPrecheckerManagerContextReadyAction(
+        prechecker_actions::PrecheckerManagerContextReadyAction,
+    ),
+    TestPrecheckerManagerOperationValidationRefusedAction(
+        prechecker_actions::PrecheckerManagerOperationValidationRefusedAction,
+    ),
+    TestPrecheckerSignatureVerifiedAction(prechecker_actions::PrecheckerSignatureVerifiedAction),
     TestPrecheckerProtocolNeededAction(prechecker_actions::PrecheckerProtocolNeededAction),
     TestPrecheckerErrorAction(prechecker_actions::PrecheckerErrorAction),
     TestPrecheckerPrecacheEndorsingRightsAction(
@@ -966,6 +1024,11 @@ impl PrecheckerActionTest {
             Self::TestPrecheckerValidateEndorsementAction(a) => a.into(),
             Self::TestPrecheckerEndorsementValidationAppliedAction(a) => a.into(),
             Self::TestPrecheckerEndorsementValidationRefusedAction(a) => a.into(),
+            Self::TestPrecheckerValidateManagerOperationAction(a) => a.into(),
+            Self::TestPrecheckerManagerContextPendingAction(a) => a.into(),
+            Self::TestPrecheckerManagerContextReadyAction(a) => a.into(),
+            Self::TestPrecheckerManagerOperationValidationRefusedAction(a) => a.into(),
+            Self::TestPrecheckerSignatureVerifiedAction(a) => a.into(),
             Self::TestPrecheckerProtocolNeededAction(a) => a.into(),
             Self::TestPrecheckerErrorAction(a) => a.into(),
             Self::TestPrecheckerPrecacheEndorsingRightsAction(a) => a.into(),
@@ -977,8 +1040,10 @@ impl PrecheckerActionTest {
 #[derive(fuzzcheck::DefaultMutator, Serialize, Deserialize, Debug, Clone)]
 enum PeerActionTest {
     TestPeersGraylistAction(PeersGraylistActionTest),
+    TestPeersScoreUpdateAction(PeersScoreUpdateAction),
     TestPeersAddMultiAction(PeersAddMultiAction),
     TestPeersRemoveAction(PeersRemoveAction),
+    TestPeersConnectPointAction(PeersConnectPointAction),
     TestPeerConnection(PeerConnectionActionTest),
     TestPeerChunking(PeerChunkActionTest),
     TestPeerMessages(PeerMessageActionTest),
@@ -990,8 +1055,10 @@ impl PeerActionTest {
     fn to_action(&self) -> Action {
         match self.clone() {
             Self::TestPeersGraylistAction(a) => a.to_action(),
+            Self::TestPeersScoreUpdateAction(a) => a.into(),
             Self::TestPeersAddMultiAction(a) => a.into(),
             Self::TestPeersRemoveAction(a) => a.into(),
+            Self::TestPeersConnectPointAction(a) => a.into(),
             Self::TestPeerConnection(a) => a.to_action(),
             Self::TestPeerChunking(a) => a.to_action(),
             Self::TestPeerMessages(a) => a.to_action(),
@@ -1090,13 +1157,78 @@ impl PeersDnsLookupActionTest {
     }
 }
 
+#[derive(fuzzcheck::DefaultMutator, Serialize, Deserialize, Debug, Clone)]
+enum PeersAddressBookActionTest {
+    TestPeersAddressBookLoadInitAction(PeersAddressBookLoadInitAction),
+    TestPeersAddressBookLoadPendingAction(PeersAddressBookLoadPendingAction),
+    TestPeersAddressBookLoadErrorAction(PeersAddressBookLoadErrorAction),
+    TestPeersAddressBookLoadSuccessAction(PeersAddressBookLoadSuccessAction),
+}
+
+impl PeersAddressBookActionTest {
+    fn to_action(&self) -> Action {
+        match self.clone() {
+            Self::TestPeersAddressBookLoadInitAction(a) => a.into(),
+            Self::TestPeersAddressBookLoadPendingAction(a) => a.into(),
+            Self::TestPeersAddressBookLoadErrorAction(a) => a.into(),
+            Self::TestPeersAddressBookLoadSuccessAction(a) => a.into(),
+        }
+    }
+}
+
+#[derive(fuzzcheck::DefaultMutator, Serialize, Deserialize, Debug, Clone)]
+enum PeersSwapActionTest {
+    TestPeersSwapRequestSendAction(PeersSwapRequestSendAction),
+    TestPeersSwapRequestReceivedAction(PeersSwapRequestReceivedAction),
+    TestPeersSwapAckReceivedAction(PeersSwapAckReceivedAction),
+    TestPeersSwapInitAction(PeersSwapInitAction),
+    TestPeersSwapSuccessAction(PeersSwapSuccessAction),
+    TestPeersSwapErrorAction(PeersSwapErrorAction),
+}
+
+impl PeersSwapActionTest {
+    fn to_action(&self) -> Action {
+        match self.clone() {
+            Self::TestPeersSwapRequestSendAction(a) => a.into(),
+            Self::TestPeersSwapRequestReceivedAction(a) => a.into(),
+            Self::TestPeersSwapAckReceivedAction(a) => a.into(),
+            Self::TestPeersSwapInitAction(a) => a.into(),
+            Self::TestPeersSwapSuccessAction(a) => a.into(),
+            Self::TestPeersSwapErrorAction(a) => a.into(),
+        }
+    }
+}
+
+#[derive(fuzzcheck::DefaultMutator, Serialize, Deserialize, Debug, Clone)]
+enum PeersAclActionTest {
+    TestPeersAclTrustedAddAction(PeersAclTrustedAddAction),
+    TestPeersAclTrustedRemoveAction(PeersAclTrustedRemoveAction),
+    TestPeersAclTrustedReconnectAction(PeersAclTrustedReconnectAction),
+    TestPeersAclIncomingSetAction(PeersAclIncomingSetAction),
+}
+
+impl PeersAclActionTest {
+    fn to_action(&self) -> Action {
+        match self.clone() {
+            Self::TestPeersAclTrustedAddAction(a) => a.into(),
+            Self::TestPeersAclTrustedRemoveAction(a) => a.into(),
+            Self::TestPeersAclTrustedReconnectAction(a) => a.into(),
+            Self::TestPeersAclIncomingSetAction(a) => a.into(),
+        }
+    }
+}
+
 #[derive(fuzzcheck::DefaultMutator, Serialize, Deserialize, Debug, Clone)]
 enum PeersGraylistActionTest {
     TestPeersGraylistAddressAction(PeersGraylistAddressAction),
     TestPeersGraylistIpAddAction(PeersGraylistIpAddAction),
     TestPeersGraylistIpAddedAction(PeersGraylistIpAddedAction),
+    TestPeersGraylistIpBanAction(PeersGraylistIpBanAction),
     TestPeersGraylistIpRemoveAction(PeersGraylistIpRemoveAction),
     TestPeersGraylistIpRemovedAction(PeersGraylistIpRemovedAction),
+    TestPeersGraylistAddressBanAction(PeersGraylistAddressBanAction),
+    TestPeersGraylistAddressUnbanAction(PeersGraylistAddressUnbanAction),
+    TestPeersGraylistClearAction(PeersGraylistClearAction),
 }
 
 impl PeersGraylistActionTest {
@@ -1105,8 +1237,12 @@ impl PeersGraylistActionTest {
             Self::TestPeersGraylistAddressAction(a) => a.into(),
             Self::TestPeersGraylistIpAddAction(a) => a.into(),
             Self::TestPeersGraylistIpAddedAction(a) => a.into(),
+            Self::TestPeersGraylistIpBanAction(a) => a.into(),
             Self::TestPeersGraylistIpRemoveAction(a) => a.into(),
             Self::TestPeersGraylistIpRemovedAction(a) => a.into(),
+            Self::TestPeersGraylistAddressBanAction(a) => a.into(),
+            Self::TestPeersGraylistAddressUnbanAction(a) => a.into(),
+            Self::TestPeersGraylistClearAction(a) => a.into(),
         }
     }
 }
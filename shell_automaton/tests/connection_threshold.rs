@@ -92,7 +92,7 @@ fn prepare_actions(count: usize) -> Vec<ActionWithMeta> {
             PeerConnectionClosedAction { address },
 
             PeerDisconnectAction { address },
-            PeerDisconnectedAction { address },
+            PeerDisconnectedAction { address, listen_port: None },
         });
     actions
         .into_iter()
@@ -0,0 +1,719 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Optional index of the applied operations by the accounts they touch.
+//!
+//! For every manager operation (reveal, transaction, origination and delegation)
+//! of an applied block, a record is stored for its source, the destination
+//! of the transaction, the delegate and the contract originated by the operation.
+//! Records of an account are ordered by their [`AccountOperationKey::id`],
+//! which grows with the level, so the history can be paged from the newest operation.
+//!
+//! Operations are indexed from their contents, not from their receipts, so failed
+//! and backtracked operations are indexed too, and contracts originated internally
+//! by a contract call are not.
+//!
+//! The index follows the chain of the head, see [`AccountOperationsIndexer`]: blocks applied
+//! before the index was enabled are indexed gradually, and records of the blocks
+//! reorganized away are removed.
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crypto::blake2b;
+use crypto::hash::{BlockHash, ChainId, ContractKt1Hash, HashType, OperationHash, ProtocolHash};
+use tezos_messages::base::signature_public_key::SignaturePublicKeyHash;
+use tezos_messages::p2p::binary_message::{BinaryRead, BinaryWrite, MessageHash};
+use tezos_messages::p2p::encoding::block_header::Level;
+use tezos_messages::p2p::encoding::prelude::OperationsForBlocksMessage;
+use tezos_messages::protocol::proto_001::operation::{
+    ContractId, DelegationOperation, OriginationOperation, RevealOperation, TransactionOperation,
+};
+use tezos_messages::protocol::SupportedProtocol;
+
+use crate::database::tezedge_database::{KVStoreKeyValueSchema, TezedgeDatabaseWithIterator};
+use crate::persistent::database::RocksDbKeyValueSchema;
+use crate::persistent::{BincodeEncoded, Decoder, Encoder, KeyValueSchema, SchemaError};
+use crate::{
+    num_from_slice, BlockMetaStorage, BlockMetaStorageReader, ChainMetaStorage,
+    ChainMetaStorageReader, Direction, IteratorMode, OperationsStorage, OperationsStorageReader,
+    PersistentStorage, StorageError, SystemStorage,
+};
+
+pub type AccountOperationsStorageKV =
+    dyn TezedgeDatabaseWithIterator<AccountOperationsStorage> + Sync + Send;
+pub type AccountOperationsByBlockIndexKV =
+    dyn TezedgeDatabaseWithIterator<AccountOperationsByBlockIndex> + Sync + Send;
+
+/// Maximal number of blocks indexed by a single [`AccountOperationsIndexer::block_applied`], so that
+/// the blocks applied before the index was enabled are indexed gradually by the following applied blocks.
+pub const MAX_INDEXED_BLOCKS: Level = 1024;
+
+/// Maximal number of records scanned by a single [`AccountOperationsStorage::get_operations`],
+/// so that filtering by a role the account rarely has doesn't scan its whole history.
+pub const MAX_SCANNED_OPERATIONS: usize = 10_000;
+
+/// How the account was involved in the operation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountRole {
+    Source,
+    Destination,
+    Delegate,
+    Originated,
+}
+
+impl AccountRole {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            AccountRole::Source => 0,
+            AccountRole::Destination => 1,
+            AccountRole::Delegate => 2,
+            AccountRole::Originated => 3,
+        }
+    }
+
+    pub fn from_u8(num: u8) -> Result<Self, SchemaError> {
+        match num {
+            0 => Ok(AccountRole::Source),
+            1 => Ok(AccountRole::Destination),
+            2 => Ok(AccountRole::Delegate),
+            3 => Ok(AccountRole::Originated),
+            _ => Err(SchemaError::DecodeError),
+        }
+    }
+}
+
+impl std::str::FromStr for AccountRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "source" => Ok(AccountRole::Source),
+            "destination" => Ok(AccountRole::Destination),
+            "delegate" => Ok(AccountRole::Delegate),
+            "originated" => Ok(AccountRole::Originated),
+            _ => Err(format!("Invalid account role: {}", s)),
+        }
+    }
+}
+
+/// Address of an implicit or originated account, in the binary encoding
+/// of the contract id (22 bytes).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AccountAddress(Vec<u8>);
+
+impl AccountAddress {
+    pub const SIZE: usize = 22;
+
+    pub fn from_contract_id(contract_id: &ContractId) -> Result<Self, SchemaError> {
+        let bytes = contract_id
+            .as_bytes()
+            .map_err(|_| SchemaError::EncodeError)?;
+        Self::from_bytes(bytes)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, SchemaError> {
+        if bytes.len() == Self::SIZE {
+            Ok(Self(bytes))
+        } else {
+            Err(SchemaError::EncodeError)
+        }
+    }
+
+    /// Parses `tz1...`, `tz2...`, `tz3...` or `KT1...` address.
+    pub fn from_b58_check(address: &str) -> Option<Self> {
+        let contract_id = if address.starts_with("KT1") {
+            ContractId::Originated(ContractKt1Hash::from_base58_check(address).ok()?.into())
+        } else {
+            ContractId::Implicit(SignaturePublicKeyHash::from_b58_hash(address).ok()?)
+        };
+        Self::from_contract_id(&contract_id).ok()
+    }
+
+    pub fn to_b58_check(&self) -> Result<String, SchemaError> {
+        match ContractId::from_bytes(&self.0).map_err(|_| SchemaError::DecodeError)? {
+            ContractId::Implicit(pkh) => Ok(pkh.to_string_representation()),
+            ContractId::Originated(originated) => Ok(originated.contract_hash.to_base58_check()),
+        }
+    }
+}
+
+impl AsRef<[u8]> for AccountAddress {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Operation which touched the account, as returned by [`AccountOperationsStorage::get_operations`].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountOperation {
+    pub id: u64,
+    pub level: Level,
+    pub role: AccountRole,
+    pub block_hash: BlockHash,
+    pub operation_hash: OperationHash,
+}
+
+#[derive(Clone)]
+pub struct AccountOperationsStorage {
+    kv: Arc<AccountOperationsStorageKV>,
+    by_block_index: AccountOperationsByBlockIndex,
+}
+
+impl AccountOperationsStorage {
+    pub fn new(persistent_storage: &PersistentStorage) -> Self {
+        Self {
+            kv: persistent_storage.main_db(),
+            by_block_index: AccountOperationsByBlockIndex::new(persistent_storage.main_db()),
+        }
+    }
+
+    /// Indexes the manager operations of the applied block.
+    ///
+    /// Blocks of the protocols older than 009 are not indexed.
+    pub fn index_operations(
+        &self,
+        block_hash: &BlockHash,
+        level: Level,
+        protocol_hash: &ProtocolHash,
+        operations: &[OperationsForBlocksMessage],
+    ) -> Result<(), StorageError> {
+        let protocol = match SupportedProtocol::try_from(protocol_hash) {
+            Ok(protocol) => protocol,
+            Err(_) => return Ok(()),
+        };
+
+        let mut batch = vec![];
+        for message in operations {
+            let validation_pass = message.operations_for_block().validation_pass() as u8;
+            for (operation_index, operation) in message.operations().iter().enumerate() {
+                let manager_operations =
+                    match decode_manager_operations(&protocol, operation.data()) {
+                        Some(manager_operations) if !manager_operations.is_empty() => {
+                            manager_operations
+                        }
+                        // not a manager operation, or not decodable by the protocol
+                        _ => continue,
+                    };
+                let operation_index =
+                    u16::try_from(operation_index).map_err(|_| SchemaError::EncodeError)?;
+                let operation_hash = OperationHash::try_from(operation.message_hash()?)?;
+                let value = AccountOperationValue {
+                    block_hash: block_hash.clone(),
+                    operation_hash: operation_hash.clone(),
+                };
+
+                for (address, role) in touched_accounts(&operation_hash, &manager_operations)? {
+                    let key = AccountOperationKey {
+                        address,
+                        level,
+                        validation_pass,
+                        operation_index,
+                        role,
+                    };
+                    batch.push((key, value.clone()));
+                }
+            }
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let keys = batch.iter().map(|(key, _)| key.clone()).collect();
+        self.kv.write_batch(batch)?;
+        self.by_block_index
+            .put(block_hash, &AccountOperationKeys(keys))
+    }
+
+    /// Removes the records of the block, returns the number of removed records.
+    pub fn remove_block(&self, block_hash: &BlockHash) -> Result<usize, StorageError> {
+        let keys = match self.by_block_index.get(block_hash)? {
+            Some(keys) => keys.0,
+            None => return Ok(0),
+        };
+        let mut removed = vec![];
+        for key in keys {
+            // the record could be overwritten by a block at the same level
+            if let Some(value) = self.kv.get(&key)? {
+                if &value.block_hash == block_hash {
+                    removed.push(key);
+                }
+            }
+        }
+        let count = removed.len();
+        self.kv.delete_batch(removed)?;
+        self.by_block_index.delete(block_hash)?;
+        Ok(count)
+    }
+
+    /// Returns up to `limit` operations of the account, from the newest one,
+    /// only those where the account has the `role`, if any, and the id to continue
+    /// from, if there are more records.
+    ///
+    /// `from_id` is the id of the first returned operation, used for paging.
+    /// At most [`MAX_SCANNED_OPERATIONS`] records are scanned, so fewer operations
+    /// than the `limit` can be returned even if there are more of them.
+    pub fn get_operations(
+        &self,
+        address: &AccountAddress,
+        role: Option<AccountRole>,
+        from_id: Option<u64>,
+        limit: usize,
+    ) -> Result<(Vec<AccountOperation>, Option<u64>), StorageError> {
+        let from = AccountOperationKey::from_id(address.clone(), from_id.unwrap_or(u64::MAX))?;
+
+        let mut operations = vec![];
+        let mut scanned = 0;
+        for result in self
+            .kv
+            .find(IteratorMode::From(Cow::Owned(from), Direction::Reverse))?
+        {
+            let (key, value) = result?;
+            let key = AccountOperationKey::decode(&key)?;
+            if &key.address != address {
+                break;
+            }
+            if operations.len() >= limit || scanned >= MAX_SCANNED_OPERATIONS {
+                return Ok((operations, Some(key.id())));
+            }
+            scanned += 1;
+            if role.map_or(false, |role| role != key.role) {
+                continue;
+            }
+            let value = AccountOperationValue::decode(&value)?;
+            operations.push(AccountOperation {
+                id: key.id(),
+                level: key.level,
+                role: key.role,
+                block_hash: value.block_hash,
+                operation_hash: value.operation_hash,
+            });
+        }
+        Ok((operations, None))
+    }
+}
+
+impl KeyValueSchema for AccountOperationsStorage {
+    type Key = AccountOperationKey;
+    type Value = AccountOperationValue;
+}
+
+impl RocksDbKeyValueSchema for AccountOperationsStorage {
+    #[inline]
+    fn name() -> &'static str {
+        "account_operations_storage"
+    }
+}
+
+impl KVStoreKeyValueSchema for AccountOperationsStorage {
+    fn column_name() -> &'static str {
+        Self::name()
+    }
+}
+
+/// Keeps the index in sync with the chain of the head.
+pub struct AccountOperationsIndexer {
+    storage: AccountOperationsStorage,
+    block_meta_storage: BlockMetaStorage,
+    operations_storage: OperationsStorage,
+    chain_meta_storage: ChainMetaStorage,
+    system_storage: SystemStorage,
+}
+
+impl AccountOperationsIndexer {
+    pub fn new(persistent_storage: &PersistentStorage) -> Self {
+        Self {
+            storage: AccountOperationsStorage::new(persistent_storage),
+            block_meta_storage: BlockMetaStorage::new(persistent_storage),
+            operations_storage: OperationsStorage::new(persistent_storage),
+            chain_meta_storage: ChainMetaStorage::new(persistent_storage),
+            system_storage: SystemStorage::new(persistent_storage.main_db()),
+        }
+    }
+
+    /// Called once the block `head` at `head_level` is applied (and is the new head).
+    ///
+    /// Removes the records of the indexed blocks which are not on the chain of the `head`
+    /// and indexes the blocks of the chain up to the `head`, by at most [`MAX_INDEXED_BLOCKS`],
+    /// starting from the caboose, if nothing was indexed yet.
+    /// The last indexed block is stored, so that indexing continues after a restart.
+    ///
+    /// Returns the level of the last indexed block.
+    pub fn block_applied(
+        &mut self,
+        chain_id: &ChainId,
+        head: &BlockHash,
+        head_level: Level,
+    ) -> Result<Option<Level>, StorageError> {
+        let mut indexed = self.indexed_block()?;
+        while let Some((block_hash, level)) = indexed.take() {
+            if self.canonical_block(head, head_level, level)?.as_ref() == Some(&block_hash) {
+                indexed = Some((block_hash, level));
+                break;
+            }
+            // reorganized away
+            self.storage.remove_block(&block_hash)?;
+            indexed = self
+                .predecessor(&block_hash)?
+                .map(|predecessor| (predecessor, level - 1));
+        }
+
+        let from_level = match &indexed {
+            Some((_, level)) => level + 1,
+            None => self
+                .chain_meta_storage
+                .get_caboose(chain_id)?
+                .map(|caboose| *caboose.level())
+                .unwrap_or(0),
+        };
+        let to_level = head_level.min(from_level.saturating_add(MAX_INDEXED_BLOCKS - 1));
+        for level in from_level..=to_level {
+            if let Some(block_hash) = self.canonical_block(head, head_level, level)? {
+                self.index_block(&block_hash, level)?;
+                indexed = Some((block_hash, level));
+            }
+        }
+
+        match indexed {
+            Some((block_hash, level)) => {
+                self.system_storage
+                    .set_account_operations_indexed(&block_hash)?;
+                Ok(Some(level))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Blocks without the metadata (e.g. below the savepoint) are not indexed,
+    /// because their protocol is not known.
+    fn index_block(&self, block_hash: &BlockHash, level: Level) -> Result<(), StorageError> {
+        let protocol_hash = match self.block_meta_storage.get_additional_data(block_hash)? {
+            Some(data) => data.protocol_hash().clone(),
+            None => return Ok(()),
+        };
+        let operations = self.operations_storage.get_operations(block_hash)?;
+        self.storage
+            .index_operations(block_hash, level, &protocol_hash, &operations)
+    }
+
+    fn indexed_block(&self) -> Result<Option<(BlockHash, Level)>, StorageError> {
+        let block_hash = match self.system_storage.get_account_operations_indexed()? {
+            Some(block_hash) => block_hash,
+            None => return Ok(None),
+        };
+        Ok(self
+            .block_meta_storage
+            .get(&block_hash)?
+            .map(|meta| (block_hash, meta.level())))
+    }
+
+    fn predecessor(&self, block_hash: &BlockHash) -> Result<Option<BlockHash>, StorageError> {
+        Ok(self
+            .block_meta_storage
+            .get(block_hash)?
+            .and_then(|meta| meta.take_predecessor()))
+    }
+
+    /// Block at `level` on the chain of the `head`.
+    fn canonical_block(
+        &self,
+        head: &BlockHash,
+        head_level: Level,
+        level: Level,
+    ) -> Result<Option<BlockHash>, StorageError> {
+        if level > head_level || level < 0 {
+            return Ok(None);
+        }
+        self.block_meta_storage
+            .find_block_at_distance(head.clone(), (head_level - level) as u32)
+    }
+}
+
+/// Index of the records by the block, so that they can be removed with the block.
+#[derive(Clone)]
+pub struct AccountOperationsByBlockIndex {
+    kv: Arc<AccountOperationsByBlockIndexKV>,
+}
+
+impl AccountOperationsByBlockIndex {
+    fn new(kv: Arc<AccountOperationsByBlockIndexKV>) -> Self {
+        Self { kv }
+    }
+
+    fn put(&self, block_hash: &BlockHash, keys: &AccountOperationKeys) -> Result<(), StorageError> {
+        self.kv.put(block_hash, keys).map_err(StorageError::from)
+    }
+
+    fn get(&self, block_hash: &BlockHash) -> Result<Option<AccountOperationKeys>, StorageError> {
+        self.kv.get(block_hash).map_err(StorageError::from)
+    }
+
+    fn delete(&self, block_hash: &BlockHash) -> Result<(), StorageError> {
+        self.kv.delete(block_hash).map_err(StorageError::from)
+    }
+}
+
+impl KeyValueSchema for AccountOperationsByBlockIndex {
+    type Key = BlockHash;
+    type Value = AccountOperationKeys;
+}
+
+impl RocksDbKeyValueSchema for AccountOperationsByBlockIndex {
+    #[inline]
+    fn name() -> &'static str {
+        "account_operations_by_block_storage"
+    }
+}
+
+impl KVStoreKeyValueSchema for AccountOperationsByBlockIndex {
+    fn column_name() -> &'static str {
+        Self::name()
+    }
+}
+
+/// Manager operations the accounts are extracted from, shared by all the indexed protocols.
+enum ManagerOperation {
+    Reveal(RevealOperation),
+    Transaction(TransactionOperation),
+    Origination(OriginationOperation),
+    Delegation(DelegationOperation),
+}
+
+macro_rules! decode_manager_operations {
+    ($proto:ident, $data:expr) => {{
+        use tezos_messages::protocol::$proto::operation::{Contents, OperationContents};
+        OperationContents::from_bytes($data).ok().map(|operation| {
+            operation
+                .contents
+                .into_iter()
+                .filter_map(|contents| match contents {
+                    Contents::Reveal(op) => Some(ManagerOperation::Reveal(op)),
+                    Contents::Transaction(op) => Some(ManagerOperation::Transaction(op)),
+                    Contents::Origination(op) => Some(ManagerOperation::Origination(op)),
+                    Contents::Delegation(op) => Some(ManagerOperation::Delegation(op)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+    }};
+}
+
+fn decode_manager_operations(
+    protocol: &SupportedProtocol,
+    data: &[u8],
+) -> Option<Vec<ManagerOperation>> {
+    match protocol {
+        SupportedProtocol::Proto009 => decode_manager_operations!(proto_009, data),
+        SupportedProtocol::Proto010 => decode_manager_operations!(proto_010, data),
+        SupportedProtocol::Proto011 => decode_manager_operations!(proto_011, data),
+        SupportedProtocol::Proto012 => decode_manager_operations!(proto_012, data),
+        _ => None,
+    }
+}
+
+/// Accounts touched by the operation, with their role.
+fn touched_accounts(
+    operation_hash: &OperationHash,
+    manager_operations: &[ManagerOperation],
+) -> Result<Vec<(AccountAddress, AccountRole)>, SchemaError> {
+    let implicit = |pkh: &SignaturePublicKeyHash| {
+        AccountAddress::from_contract_id(&ContractId::Implicit(pkh.clone()))
+    };
+
+    let mut accounts = vec![];
+    let mut origination_index = 0;
+    for manager_operation in manager_operations {
+        match manager_operation {
+            ManagerOperation::Reveal(op) => {
+                accounts.push((implicit(&op.source)?, AccountRole::Source));
+            }
+            ManagerOperation::Transaction(op) => {
+                accounts.push((implicit(&op.source)?, AccountRole::Source));
+                accounts.push((
+                    AccountAddress::from_contract_id(&op.destination)?,
+                    AccountRole::Destination,
+                ));
+            }
+            ManagerOperation::Origination(op) => {
+                accounts.push((implicit(&op.source)?, AccountRole::Source));
+                if let Some(delegate) = &op.delegate {
+                    accounts.push((implicit(delegate)?, AccountRole::Delegate));
+                }
+                let originated = originated_contract(operation_hash, origination_index)?;
+                accounts.push((
+                    AccountAddress::from_contract_id(&ContractId::Originated(originated.into()))?,
+                    AccountRole::Originated,
+                ));
+                origination_index += 1;
+            }
+            ManagerOperation::Delegation(op) => {
+                accounts.push((implicit(&op.source)?, AccountRole::Source));
+                if let Some(delegate) = &op.delegate {
+                    accounts.push((implicit(delegate)?, AccountRole::Delegate));
+                }
+            }
+        }
+    }
+    Ok(accounts)
+}
+
+/// Address of the contract originated by the operation, as computed by the protocol
+/// from the origination nonce: the operation hash and the index of the origination.
+///
+/// Contracts originated internally by a contract call are not known without
+/// the operation's receipt, so they are not counted in the index.
+fn originated_contract(
+    operation_hash: &OperationHash,
+    origination_index: i32,
+) -> Result<ContractKt1Hash, SchemaError> {
+    let mut nonce = Vec::with_capacity(HashType::OperationHash.size() + 4);
+    nonce.extend(operation_hash.as_ref());
+    nonce.extend(&origination_index.to_be_bytes());
+    let hash = blake2b::digest_160(&nonce).map_err(|_| SchemaError::EncodeError)?;
+    Ok(ContractKt1Hash::try_from(hash.as_slice())?)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountOperationKey {
+    pub address: AccountAddress,
+    pub level: Level,
+    pub validation_pass: u8,
+    pub operation_index: u16,
+    pub role: AccountRole,
+}
+
+impl AccountOperationKey {
+    const LEN_ADDRESS: usize = AccountAddress::SIZE;
+    const LEN_ID: usize = std::mem::size_of::<u64>();
+    const LEN_KEY: usize = Self::LEN_ADDRESS + Self::LEN_ID;
+
+    const IDX_ADDRESS: usize = 0;
+    const IDX_ID: usize = Self::IDX_ADDRESS + Self::LEN_ADDRESS;
+
+    /// Position of the record among the records of the account:
+    /// `[level(4)][validation_pass(1)][operation_index(2)][role(1)]`.
+    pub fn id(&self) -> u64 {
+        (self.level as u32 as u64) << 32
+            | (self.validation_pass as u64) << 24
+            | (self.operation_index as u64) << 8
+            | self.role.to_u8() as u64
+    }
+
+    fn from_id(address: AccountAddress, id: u64) -> Result<Self, SchemaError> {
+        Ok(Self {
+            address,
+            level: (id >> 32) as u32 as Level,
+            validation_pass: (id >> 24) as u8,
+            operation_index: (id >> 8) as u16,
+            // roles beyond the last one are used only as an upper bound
+            role: AccountRole::from_u8((id as u8).min(AccountRole::Originated.to_u8()))?,
+        })
+    }
+}
+
+/// Layout of the `AccountOperationKey` is:
+///
+/// * bytes layout: `[address(22)][id(8)]`, see [`AccountOperationKey::id`]
+impl Encoder for AccountOperationKey {
+    fn encode(&self) -> Result<Vec<u8>, SchemaError> {
+        if self.address.as_ref().len() == Self::LEN_ADDRESS {
+            let mut bytes = Vec::with_capacity(Self::LEN_KEY);
+            bytes.extend(self.address.as_ref());
+            bytes.extend(&self.id().to_be_bytes());
+            Ok(bytes)
+        } else {
+            Err(SchemaError::EncodeError)
+        }
+    }
+}
+
+impl Decoder for AccountOperationKey {
+    fn decode(bytes: &[u8]) -> Result<Self, SchemaError> {
+        if bytes.len() == Self::LEN_KEY {
+            let address = AccountAddress(
+                bytes[Self::IDX_ADDRESS..Self::IDX_ADDRESS + Self::LEN_ADDRESS].to_vec(),
+            );
+            let id = num_from_slice!(bytes, Self::IDX_ID, u64);
+            let key = Self::from_id(address, id)?;
+            if key.role.to_u8() == id as u8 {
+                Ok(key)
+            } else {
+                Err(SchemaError::DecodeError)
+            }
+        } else {
+            Err(SchemaError::DecodeError)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountOperationValue {
+    block_hash: BlockHash,
+    operation_hash: OperationHash,
+}
+
+impl BincodeEncoded for AccountOperationValue {}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountOperationKeys(Vec<AccountOperationKey>);
+
+impl BincodeEncoded for AccountOperationKeys {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+
+    use super::*;
+
+    #[test]
+    fn account_operation_key_encoded_equals_decoded() -> Result<(), Error> {
+        let expected = AccountOperationKey {
+            address: AccountAddress::from_b58_check("tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx")
+                .unwrap(),
+            level: 2_000_000,
+            validation_pass: 3,
+            operation_index: 300,
+            role: AccountRole::Destination,
+        };
+        let encoded_bytes = expected.encode()?;
+        let decoded = AccountOperationKey::decode(&encoded_bytes)?;
+        assert_eq!(expected, decoded);
+        Ok(())
+    }
+
+    #[test]
+    fn account_operation_key_decode_invalid() {
+        let result = AccountOperationKey::decode(&[0; AccountAddress::SIZE]);
+        assert!(matches!(result, Err(SchemaError::DecodeError)));
+        let result = AccountOperationKey::decode(&[0xff; AccountOperationKey::LEN_KEY]);
+        assert!(matches!(result, Err(SchemaError::DecodeError)));
+    }
+
+    #[test]
+    fn account_address_b58_check() -> Result<(), Error> {
+        for address in [
+            "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx",
+            "KT1BEqzn5Wx8uJrZNvuS9DVHmLvG9td3fDLi",
+        ] {
+            let account = AccountAddress::from_b58_check(address).unwrap();
+            assert_eq!(account.as_ref().len(), AccountAddress::SIZE);
+            assert_eq!(account.to_b58_check()?, address);
+        }
+        assert_eq!(AccountAddress::from_b58_check("tz1invalid"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn originated_contract_address() -> Result<(), Error> {
+        let operation_hash = OperationHash::try_from(&[1; 32][..])?;
+        let first = originated_contract(&operation_hash, 0)?;
+        let second = originated_contract(&operation_hash, 1)?;
+        assert_ne!(first, second);
+        assert!(first.to_base58_check().starts_with("KT1"));
+        Ok(())
+    }
+}
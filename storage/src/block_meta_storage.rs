@@ -215,6 +215,22 @@ impl BlockMetaStorage {
             .map_err(StorageError::from)
     }
 
+    /// Removes the application result of the block, used when pruning history
+    pub fn remove_block_additional_data(&self, block_hash: &BlockHash) -> Result<(), StorageError> {
+        self.additional_data_index
+            .delete(block_hash)
+            .map_err(StorageError::from)
+    }
+
+    /// Removes the metadata, the predecessors and the application result of the block,
+    /// used when pruning history
+    pub fn remove(&self, block_hash: &BlockHash) -> Result<(), StorageError> {
+        self.predecessors_index
+            .remove_predecessors(block_hash, Self::STORED_PREDECESSORS_SIZE)?;
+        self.remove_block_additional_data(block_hash)?;
+        self.kv.delete(block_hash).map_err(StorageError::from)
+    }
+
     #[inline]
     pub fn put(&self, block_hash: &BlockHash, meta: &Meta) -> Result<(), StorageError> {
         self.kv.merge(block_hash, meta).map_err(StorageError::from)
@@ -406,6 +422,7 @@ pub struct Meta {
     #[get = "pub"]
     predecessor: Option<BlockHash>,
     #[get = "pub"]
+    #[set = "pub"]
     successors: Vec<BlockHash>,
     #[get_copy = "pub"]
     #[set = "pub"]
@@ -195,6 +195,69 @@ impl BlockStorage {
             )
     }
 
+    /// Removes the block from the indexes, used when pruning history.
+    /// The data stays in the commit log until its segment is dropped.
+    ///
+    /// Returns true, if the level index pointed to the block and was removed too.
+    pub fn remove_block(&self, block_hash: &BlockHash, level: Level) -> Result<bool, StorageError> {
+        let mut level_removed = false;
+        if let Some(location) = self.by_level_index.get(level)? {
+            if let Some(stored) = self.primary_index.get(block_hash)? {
+                if stored.block_header.0 == location.block_header.0 {
+                    self.by_level_index.delete(level)?;
+                    level_removed = true;
+                }
+            }
+        }
+        self.primary_index.delete(block_hash)?;
+        Ok(level_removed)
+    }
+
+    /// Points the level index to the block, used to restore the index of the canonical chain
+    /// after a fork block was removed.
+    pub fn index_by_level(&self, block_hash: &BlockHash, level: Level) -> Result<(), StorageError> {
+        match self.primary_index.get(block_hash)? {
+            Some(location) => self.by_level_index.put(level, &location),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the reference to the json data (block and operations metadata) of the block,
+    /// the header stays available.
+    pub fn remove_block_json_data(
+        &self,
+        block_hash: &BlockHash,
+        level: Level,
+    ) -> Result<(), StorageError> {
+        let mut location = match self.primary_index.get(block_hash)? {
+            Some(location) => location,
+            None => return Ok(()),
+        };
+        if location.block_json_data.take().is_none() {
+            return Ok(());
+        }
+
+        if let Some(by_level_location) = self.by_level_index.get(level)? {
+            if by_level_location.block_header.0 == location.block_header.0 {
+                self.by_level_index.put(level, &location)?;
+            }
+        }
+        self.primary_index.put(block_hash, &location)
+    }
+
+    /// Returns the lowest commit log offset referenced by the blocks from `from_level`.
+    pub fn lowest_offset_from_level(&self, from_level: Level) -> Result<Option<u64>, StorageError> {
+        self.by_level_index.lowest_offset_from(from_level)
+    }
+
+    /// Drops the commit log segments whose records are all below `offset`,
+    /// returns the number of dropped segments.
+    pub fn drop_commit_log_segments_below(&self, offset: u64) -> Result<usize, StorageError> {
+        self.clog
+            .drop_segments_below(offset)
+            .map_err(StorageError::from)
+    }
+
     #[inline]
     fn get_block_header_by_location(
         &self,
@@ -471,6 +534,11 @@ impl BlockPrimaryIndex {
         self.kv.contains(block_hash).map_err(StorageError::from)
     }
 
+    #[inline]
+    fn delete(&self, block_hash: &BlockHash) -> Result<(), StorageError> {
+        self.kv.delete(block_hash).map_err(StorageError::from)
+    }
+
     #[inline]
     fn iterator(&self) -> Result<Vec<BlockHash>, StorageError> {
         use crate::persistent::codec::Decoder;
@@ -521,6 +589,31 @@ impl BlockByLevelIndex {
         self.kv.put(&level, location).map_err(StorageError::from)
     }
 
+    fn get(&self, level: BlockLevel) -> Result<Option<BlockStorageColumnsLocation>, StorageError> {
+        self.kv.get(&level).map_err(StorageError::from)
+    }
+
+    fn delete(&self, level: BlockLevel) -> Result<(), StorageError> {
+        self.kv.delete(&level).map_err(StorageError::from)
+    }
+
+    /// Iterates the locations from `from_level` without collecting them
+    fn lowest_offset_from(&self, from_level: BlockLevel) -> Result<Option<u64>, StorageError> {
+        self.kv
+            .find(IteratorMode::From(
+                Cow::Owned(from_level),
+                Direction::Forward,
+            ))?
+            .try_fold(None, |lowest: Option<u64>, result| {
+                let location = <Self as KeyValueSchema>::Value::decode(&result?.1)?;
+                let offset = location
+                    .block_json_data
+                    .map(|json| json.0.min(location.block_header.0))
+                    .unwrap_or(location.block_header.0);
+                Ok(Some(lowest.map_or(offset, |lowest| lowest.min(offset))))
+            })
+    }
+
     fn get_blocks(
         &self,
         from_level: BlockLevel,
@@ -29,6 +29,9 @@ pub trait ChainMetaStorageReader: Sync + Send {
     /// - caboose - so in particular it is the lowest block for which we have stored the context
     fn get_caboose(&self, chain_id: &ChainId) -> Result<Option<Head>, StorageError>;
 
+    /// Load save_point for chain_id from dedicated storage
+    fn get_savepoint(&self, chain_id: &ChainId) -> Result<Option<Head>, StorageError>;
+
     /// Load genesis for chain_id from dedicated storage
     fn get_genesis(&self, chain_id: &ChainId) -> Result<Option<Head>, StorageError>;
 }
@@ -82,6 +85,16 @@ impl ChainMetaStorage {
             .map_err(StorageError::from)
     }
 
+    #[inline]
+    pub fn set_savepoint(&self, chain_id: &ChainId, head: Head) -> Result<(), StorageError> {
+        self.kv
+            .put(
+                &MetaKey::key_savepoint(chain_id.clone()),
+                &MetadataValue::Head(head),
+            )
+            .map_err(StorageError::from)
+    }
+
     #[inline]
     pub fn set_genesis(&self, chain_id: &ChainId, head: Head) -> Result<(), StorageError> {
         self.kv
@@ -148,6 +161,17 @@ impl ChainMetaStorageReader for ChainMetaStorage {
             .map_err(StorageError::from)
     }
 
+    #[inline]
+    fn get_savepoint(&self, chain_id: &ChainId) -> Result<Option<Head>, StorageError> {
+        self.kv
+            .get(&MetaKey::key_savepoint(chain_id.clone()))
+            .map(|result| match result {
+                Some(MetadataValue::Head(value)) => Some(value),
+                _ => None,
+            })
+            .map_err(StorageError::from)
+    }
+
     #[inline]
     fn get_genesis(&self, chain_id: &ChainId) -> Result<Option<Head>, StorageError> {
         self.kv
@@ -196,6 +220,7 @@ impl MetaKey {
 
     const KEY_CURRENT_HEAD: &'static str = "ch";
     const KEY_CABOOSE: &'static str = "cbs";
+    const KEY_SAVEPOINT: &'static str = "svp";
     const KEY_GENESIS: &'static str = "gns";
     const KEY_TEST_CHAIN_ID: &'static str = "tcid";
 
@@ -213,6 +238,13 @@ impl MetaKey {
         }
     }
 
+    fn key_savepoint(chain_id: ChainId) -> MetaKey {
+        MetaKey {
+            chain_id,
+            key: Self::KEY_SAVEPOINT.to_string(),
+        }
+    }
+
     fn key_genesis(chain_id: ChainId) -> MetaKey {
         MetaKey {
             chain_id,
@@ -396,6 +428,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_savepoint() -> Result<(), Error> {
+        let tmp_storage = TmpStorage::create_to_out_dir("__test_savepoint")?;
+        let index = ChainMetaStorage::new(tmp_storage.storage());
+
+        let chain_id = "NetXgtSLGNJvNye".try_into()?;
+        let block_1 = Head::new(
+            "BLockGenesisGenesisGenesisGenesisGenesisb83baZgbyZe".try_into()?,
+            1,
+            Fitness::default(),
+        );
+        let block_2 = Head::new(
+            "BLockGenesisGenesisGenesisGenesisGenesisd6f5afWyME7".try_into()?,
+            2,
+            Fitness::default(),
+        );
+
+        assert!(index.get_savepoint(&chain_id)?.is_none());
+
+        index.set_caboose(&chain_id, block_1.clone())?;
+        index.set_savepoint(&chain_id, block_2.clone())?;
+        assert_eq!(
+            index.get_savepoint(&chain_id)?.unwrap().block_hash(),
+            block_2.block_hash()
+        );
+        assert_eq!(
+            index.get_caboose(&chain_id)?.unwrap().block_hash(),
+            block_1.block_hash()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_genesis() -> Result<(), Error> {
         let tmp_storage = TmpStorage::create_to_out_dir("__test_genesis")?;
@@ -4,6 +4,9 @@
 //! ## Commit Log
 //! append only - adds data in a file then returns the data size and location in  file
 //! uses zstd as a compression library
+//!
+//! Data is split in fixed size segments, every segment starts with a header
+//! and every record is prefixed with its length and crc32 checksum.
 
 mod compression;
 
@@ -19,7 +22,8 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 use crate::commit_log::compression::{zstd_compress, zstd_decompress};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 
@@ -27,11 +31,72 @@ use slog::Logger;
 
 pub type CommitLogRef = Arc<RwLock<CommitLog>>;
 
-const DATA_FILE_NAME: &str = "table.data";
+/// Name of the single data file used by commit logs before segmentation,
+/// records stored in it are still readable, but are not checksummed.
+const LEGACY_DATA_FILE_NAME: &str = "table.data";
+
+const SEGMENT_FILE_EXTENSION: &str = "segment";
+const SEGMENT_MAGIC: &[u8; 4] = b"TZCL";
+const SEGMENT_VERSION: u32 = 1;
+/// magic (4 bytes) + version (4 bytes) + base offset (8 bytes)
+const SEGMENT_HEADER_SIZE: u64 = 16;
+/// payload length (4 bytes) + payload crc32 (4 bytes)
+const RECORD_HEADER_SIZE: u64 = 8;
+
+/// Default maximum size of a segment, a new segment is started once a record does not fit
+pub const DEFAULT_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// One file of the commit log.
+///
+/// Records of a segment are addressed by a global offset, the first record of the segment
+/// is at `base_offset` and the next segment starts right after the last record.
+struct Segment {
+    base_offset: u64,
+    path: PathBuf,
+    /// Size of the records stored in the segment, header excluded
+    size: u64,
+}
+
+impl Segment {
+    fn end_offset(&self) -> u64 {
+        self.base_offset + self.size
+    }
+
+    fn contains(&self, offset: u64) -> bool {
+        offset >= self.base_offset && offset < self.end_offset()
+    }
+
+    fn file_position(&self, offset: u64) -> u64 {
+        SEGMENT_HEADER_SIZE + (offset - self.base_offset)
+    }
+}
+
+/// Result of [`CommitLog::repair`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RepairReport {
+    /// Number of valid records found in the last segment
+    pub records: usize,
+    /// Number of bytes removed from the tail of the last segment
+    pub truncated_bytes: u64,
+}
+
+/// Outcome of scanning the records of a segment file.
+struct SegmentScan {
+    records: usize,
+    /// Size of the valid records, header excluded
+    valid_size: u64,
+    /// First record which is incomplete or fails the checksum
+    invalid: Option<Location>,
+}
 
 pub struct CommitLog {
-    data_file: File,
-    data_file_path: PathBuf,
+    dir: PathBuf,
+    /// Data file written before segmentation, with its size
+    legacy_data_file: Option<(PathBuf, u64)>,
+    /// Segments ordered by base offset, the last one is the active segment
+    segments: BTreeMap<u64, Segment>,
+    active_file: File,
+    max_segment_size: u64,
     use_compression: bool,
 }
 
@@ -40,66 +105,437 @@ impl CommitLog {
     /// *use_compression* - when enabled compresses data when `append_msg()` is called
     /// > Using compression decrease read and write speed
     pub fn new<P: AsRef<Path>>(dir: P, use_compression: bool) -> Result<Self, CommitLogError> {
-        if !dir.as_ref().exists() {
-            std::fs::create_dir_all(dir.as_ref())?;
+        Self::with_segment_size(dir, use_compression, DEFAULT_SEGMENT_SIZE)
+    }
+
+    /// Same as [`CommitLog::new`], with *max_segment_size* - size in bytes after which
+    /// a new segment is started
+    pub fn with_segment_size<P: AsRef<Path>>(
+        dir: P,
+        use_compression: bool,
+        max_segment_size: u64,
+    ) -> Result<Self, CommitLogError> {
+        let dir = dir.as_ref().to_path_buf();
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        let legacy_path = dir.join(LEGACY_DATA_FILE_NAME);
+        let legacy_data_file = if legacy_path.exists() {
+            let size = std::fs::metadata(&legacy_path)?.len();
+            Some((legacy_path, size))
+        } else {
+            None
+        };
+
+        let mut base_offsets = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_FILE_EXTENSION) {
+                continue;
+            }
+            if let Some(base_offset) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            {
+                base_offsets.push(base_offset);
+            }
         }
-        let mut data_file_path = PathBuf::new();
-        data_file_path.push(dir.as_ref());
-        data_file_path.push(DATA_FILE_NAME);
-        let data_file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .open(data_file_path.clone())?;
+        base_offsets.sort_unstable();
+
+        let mut segments = BTreeMap::new();
+        for (index, base_offset) in base_offsets.iter().enumerate() {
+            let path = segment_path(&dir, *base_offset);
+            let file_size = std::fs::metadata(&path)?.len();
+            let is_last = index + 1 == base_offsets.len();
+
+            if file_size < SEGMENT_HEADER_SIZE && is_last {
+                // Crashed while the segment was being created
+                let mut file = OpenOptions::new().write(true).open(&path)?;
+                write_segment_header(&mut file, *base_offset)?;
+            } else {
+                let mut file = File::open(&path)?;
+                check_segment_header(&mut file, &path, *base_offset)?;
+            }
+
+            segments.insert(
+                *base_offset,
+                Segment {
+                    base_offset: *base_offset,
+                    path,
+                    size: file_size.saturating_sub(SEGMENT_HEADER_SIZE),
+                },
+            );
+        }
+
+        let active_file = match segments.values().next_back() {
+            Some(segment) => OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&segment.path)?,
+            None => {
+                let base_offset = legacy_data_file
+                    .as_ref()
+                    .map(|(_, size)| *size)
+                    .unwrap_or(0);
+                let (segment, file) = create_segment(&dir, base_offset)?;
+                segments.insert(base_offset, segment);
+                file
+            }
+        };
+
         Ok(Self {
-            data_file,
-            data_file_path,
+            dir,
+            legacy_data_file,
+            segments,
+            active_file,
+            max_segment_size,
             use_compression,
         })
     }
+
     /// appends bytes of data to file
     pub fn append_msg<B: AsRef<[u8]>>(
         &mut self,
         payload: B,
     ) -> Result<(u64, usize), CommitLogError> {
-        let mut writer = BufWriter::new(&mut self.data_file);
-        let offset = writer.seek(SeekFrom::End(0))?;
-        let buf_size = if self.use_compression {
-            let mut compressed_payload = Vec::new();
-            zstd_compress(payload, &mut compressed_payload)?;
-            writer.write_all(compressed_payload.as_slice())?;
-            compressed_payload.len()
+        let compressed_payload;
+        let payload = if self.use_compression {
+            let mut output = Vec::new();
+            zstd_compress(payload, &mut output)?;
+            compressed_payload = output;
+            compressed_payload.as_slice()
         } else {
-            writer.write_all(payload.as_ref())?;
-            payload.as_ref().len()
+            payload.as_ref()
         };
+        let buf_size = payload.len();
+        let payload_len = u32::try_from(buf_size)
+            .map_err(|_| CommitLogError::RecordTooLarge { size: buf_size })?;
+        let record_size = RECORD_HEADER_SIZE + buf_size as u64;
+
+        let active_size = self.active_segment()?.size;
+        if active_size > 0 && active_size + record_size > self.max_segment_size {
+            self.rotate()?;
+        }
+
+        let active = self.active_segment()?;
+        let offset = active.end_offset();
+        let position = active.file_position(offset);
+
+        let mut writer = BufWriter::new(&mut self.active_file);
+        writer.seek(SeekFrom::Start(position))?;
+        writer.write_all(&payload_len.to_le_bytes())?;
+        writer.write_all(&crc32fast::hash(payload).to_le_bytes())?;
+        writer.write_all(payload)?;
         writer.flush()?;
+        drop(writer);
+
+        self.active_segment_mut()?.size += record_size;
+
         Ok((offset, buf_size))
     }
+
     /// `offset` - location of data in log file
     /// `buf_size` - exact data size to be read
     pub fn read(&self, offset: u64, buf_size: usize) -> Result<Vec<u8>, CommitLogError> {
-        let mut buf = vec![0_u8; buf_size];
-        let mut reader = BufReader::new(File::open(self.data_file_path.as_path())?);
-        reader.seek(SeekFrom::Start(offset))?;
-        reader.read_exact(&mut buf)?;
+        let location = Location(offset, buf_size);
+
+        let buf = match &self.legacy_data_file {
+            Some((path, size)) if offset < *size => {
+                let mut buf = vec![0_u8; buf_size];
+                let mut reader = BufReader::new(File::open(path)?);
+                reader.seek(SeekFrom::Start(offset))?;
+                reader.read_exact(&mut buf)?;
+                buf
+            }
+            _ => {
+                let segment = match self.segments.range(..=offset).next_back() {
+                    Some((_, segment)) if segment.contains(offset) => segment,
+                    Some(_) => return Err(CommitLogError::ReadError { location }),
+                    None => return Err(CommitLogError::SegmentDropped { location }),
+                };
+                if offset + RECORD_HEADER_SIZE + buf_size as u64 > segment.end_offset() {
+                    return Err(CommitLogError::ReadError { location });
+                }
+
+                let mut reader = BufReader::new(File::open(&segment.path)?);
+                reader.seek(SeekFrom::Start(segment.file_position(offset)))?;
+                let (payload_len, crc) = read_record_header(&mut reader)?;
+                if payload_len as usize != buf_size {
+                    return Err(CommitLogError::ReadError { location });
+                }
+                let mut buf = vec![0_u8; buf_size];
+                reader.read_exact(&mut buf)?;
+                if crc32fast::hash(&buf) != crc {
+                    return Err(CommitLogError::ChecksumMismatch { location });
+                }
+                buf
+            }
+        };
 
-        return if self.use_compression {
+        if self.use_compression {
             let mut uncompressed_payload = Vec::new();
             zstd_decompress(buf.as_slice(), &mut uncompressed_payload)?;
             Ok(uncompressed_payload)
         } else {
             Ok(buf)
-        };
+        }
     }
 
-    /// Flushes data to disc
+    /// Flushes data to the file, without syncing it to disc
+    ///
+    /// Records are synced when their segment is sealed, and by [`CommitLog::sync`].
     pub fn flush(&mut self) -> Result<(), CommitLogError> {
-        self.data_file.flush()?;
+        self.active_file.flush()?;
+        Ok(())
+    }
+
+    /// Flushes data and syncs it to disc
+    pub fn sync(&mut self) -> Result<(), CommitLogError> {
+        self.flush()?;
+        self.active_file.sync_data()?;
+        Ok(())
+    }
+
+    /// Checks the records of the last segment and truncates the incomplete or corrupted
+    /// records at its tail.
+    ///
+    /// New records are then appended to a new segment starting after the truncated bytes:
+    /// the offsets of the truncated records are never reused, so their locations which may
+    /// still be stored in an index fail with [`CommitLogError::ReadError`] instead of
+    /// reading newer records.
+    ///
+    /// Only the last segment can be torn by a crash, other segments are left untouched,
+    /// use [`CommitLog::verify`] to check them.
+    pub fn repair(&mut self) -> Result<RepairReport, CommitLogError> {
+        let (base_offset, file_size) = {
+            let active = self.active_segment()?;
+            (active.base_offset, active.size)
+        };
+        let scan = scan_segment(&mut self.active_file, base_offset)?;
+
+        let truncated_bytes = file_size - scan.valid_size;
+        if truncated_bytes > 0 {
+            self.active_file
+                .set_len(SEGMENT_HEADER_SIZE + scan.valid_size)?;
+            self.active_file.sync_all()?;
+            self.active_segment_mut()?.size = scan.valid_size;
+
+            let (segment, file) = create_segment(&self.dir, base_offset + file_size)?;
+            self.segments.insert(segment.base_offset, segment);
+            self.active_file = file;
+        }
+
+        Ok(RepairReport {
+            records: scan.records,
+            truncated_bytes,
+        })
+    }
+
+    /// Checks the checksum of every record of every segment, returns the number of records,
+    /// or the location of the first invalid record.
+    pub fn verify(&self) -> Result<usize, CommitLogError> {
+        let mut records = 0;
+        for segment in self.segments.values() {
+            let mut file = File::open(&segment.path)?;
+            let scan = scan_segment(&mut file, segment.base_offset)?;
+            if let Some(location) = scan.invalid {
+                return Err(CommitLogError::ChecksumMismatch { location });
+            }
+            records += scan.records;
+        }
+        Ok(records)
+    }
+
+    /// Removes the segments (and the legacy data file) whose records are all below `offset`,
+    /// the active segment is never removed. Returns the number of removed files.
+    ///
+    /// Reading a record from a removed segment fails with [`CommitLogError::SegmentDropped`].
+    pub fn drop_segments_below(&mut self, offset: u64) -> Result<usize, CommitLogError> {
+        let mut dropped = 0;
+
+        if let Some((path, size)) = &self.legacy_data_file {
+            if *size <= offset {
+                std::fs::remove_file(path)?;
+                self.legacy_data_file = None;
+                dropped += 1;
+            }
+        }
+
+        let active_base_offset = self.active_segment()?.base_offset;
+        let to_drop: Vec<u64> = self
+            .segments
+            .values()
+            .filter(|segment| {
+                segment.base_offset != active_base_offset && segment.end_offset() <= offset
+            })
+            .map(|segment| segment.base_offset)
+            .collect();
+
+        for base_offset in to_drop {
+            if let Some(segment) = self.segments.remove(&base_offset) {
+                std::fs::remove_file(&segment.path)?;
+                dropped += 1;
+            }
+        }
+
+        Ok(dropped)
+    }
+
+    /// Number of segment files of the commit log
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Lowest offset still readable from the commit log
+    pub fn first_offset(&self) -> u64 {
+        if self.legacy_data_file.is_some() {
+            return 0;
+        }
+        self.segments
+            .values()
+            .next()
+            .map(|segment| segment.base_offset)
+            .unwrap_or(0)
+    }
+
+    fn active_segment(&self) -> Result<&Segment, CommitLogError> {
+        self.segments
+            .values()
+            .next_back()
+            .ok_or(CommitLogError::MissingSegment)
+    }
+
+    fn active_segment_mut(&mut self) -> Result<&mut Segment, CommitLogError> {
+        self.segments
+            .values_mut()
+            .next_back()
+            .ok_or(CommitLogError::MissingSegment)
+    }
+
+    /// Seals the active segment and starts a new one right after it
+    fn rotate(&mut self) -> Result<(), CommitLogError> {
+        self.active_file.sync_data()?;
+        let base_offset = self.active_segment()?.end_offset();
+        let (segment, file) = create_segment(&self.dir, base_offset)?;
+        self.segments.insert(base_offset, segment);
+        self.active_file = file;
         Ok(())
     }
 }
 
+fn segment_path(dir: &Path, base_offset: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", base_offset, SEGMENT_FILE_EXTENSION))
+}
+
+fn create_segment(dir: &Path, base_offset: u64) -> Result<(Segment, File), CommitLogError> {
+    let path = segment_path(dir, base_offset);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .read(true)
+        .write(true)
+        .open(&path)?;
+    write_segment_header(&mut file, base_offset)?;
+    Ok((
+        Segment {
+            base_offset,
+            path,
+            size: 0,
+        },
+        file,
+    ))
+}
+
+fn write_segment_header(file: &mut File, base_offset: u64) -> Result<(), CommitLogError> {
+    let mut header = Vec::with_capacity(SEGMENT_HEADER_SIZE as usize);
+    header.extend_from_slice(SEGMENT_MAGIC);
+    header.extend_from_slice(&SEGMENT_VERSION.to_le_bytes());
+    header.extend_from_slice(&base_offset.to_le_bytes());
+
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn check_segment_header(
+    file: &mut File,
+    path: &Path,
+    base_offset: u64,
+) -> Result<(), CommitLogError> {
+    let mut header = [0_u8; SEGMENT_HEADER_SIZE as usize];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)?;
+
+    let mut version = [0_u8; 4];
+    version.copy_from_slice(&header[4..8]);
+    let mut offset = [0_u8; 8];
+    offset.copy_from_slice(&header[8..16]);
+
+    if &header[0..4] != SEGMENT_MAGIC
+        || u32::from_le_bytes(version) != SEGMENT_VERSION
+        || u64::from_le_bytes(offset) != base_offset
+    {
+        return Err(CommitLogError::InvalidSegment {
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+fn read_record_header<R: Read>(reader: &mut R) -> Result<(u32, u32), CommitLogError> {
+    let mut header = [0_u8; RECORD_HEADER_SIZE as usize];
+    reader.read_exact(&mut header)?;
+    let mut payload_len = [0_u8; 4];
+    payload_len.copy_from_slice(&header[0..4]);
+    let mut crc = [0_u8; 4];
+    crc.copy_from_slice(&header[4..8]);
+    Ok((u32::from_le_bytes(payload_len), u32::from_le_bytes(crc)))
+}
+
+/// Reads the records of a segment until the end of the file or until the first
+/// incomplete or corrupted record
+fn scan_segment(file: &mut File, base_offset: u64) -> Result<SegmentScan, CommitLogError> {
+    let size = file.metadata()?.len().saturating_sub(SEGMENT_HEADER_SIZE);
+    let mut reader = BufReader::new(file);
+    reader.seek(SeekFrom::Start(SEGMENT_HEADER_SIZE))?;
+
+    let mut scan = SegmentScan {
+        records: 0,
+        valid_size: 0,
+        invalid: None,
+    };
+    let mut payload = Vec::new();
+
+    while scan.valid_size < size {
+        let offset = base_offset + scan.valid_size;
+        if scan.valid_size + RECORD_HEADER_SIZE > size {
+            scan.invalid = Some(Location(offset, 0));
+            break;
+        }
+        let (payload_len, crc) = read_record_header(&mut reader)?;
+        let location = Location(offset, payload_len as usize);
+        let record_size = RECORD_HEADER_SIZE + payload_len as u64;
+        if scan.valid_size + record_size > size {
+            scan.invalid = Some(location);
+            break;
+        }
+        payload.resize(payload_len as usize, 0);
+        reader.read_exact(&mut payload)?;
+        if crc32fast::hash(&payload) != crc {
+            scan.invalid = Some(location);
+            break;
+        }
+        scan.records += 1;
+        scan.valid_size += record_size;
+    }
+
+    Ok(scan)
+}
+
 /// Possible errors for commit log
 #[derive(Debug, Error)]
 pub enum CommitLogError {
@@ -113,6 +549,16 @@ pub enum CommitLogError {
     ReadError { location: Location },
     #[error("Failed to read record data corrupted")]
     CorruptData,
+    #[error("Checksum mismatch for record at {location}")]
+    ChecksumMismatch { location: Location },
+    #[error("Record at {location} belongs to a dropped segment")]
+    SegmentDropped { location: Location },
+    #[error("Invalid commit log segment header {path:?}")]
+    InvalidSegment { path: PathBuf },
+    #[error("Commit log has no active segment")]
+    MissingSegment,
+    #[error("Record of {size} bytes is too large for commit log")]
+    RecordTooLarge { size: usize },
     #[error("RwLock Poison Error {error}")]
     RwLockPoisonError { error: String },
 }
@@ -173,6 +619,9 @@ pub trait CommitLogWithSchema<S: CommitLogSchema> {
 
     /// Retrieve a stored record.
     fn get(&self, location: &Location) -> Result<S::Value, CommitLogError>;
+
+    /// Remove the segments whose records are all below `offset`.
+    fn drop_segments_below(&self, offset: u64) -> Result<usize, CommitLogError>;
 }
 
 impl<S: CommitLogSchema> CommitLogWithSchema<S> for CommitLogs {
@@ -200,6 +649,10 @@ impl<S: CommitLogSchema> CommitLogWithSchema<S> for CommitLogs {
         let value = S::Value::decode(&bytes)?;
         Ok(value)
     }
+
+    fn drop_segments_below(&self, offset: u64) -> Result<usize, CommitLogError> {
+        CommitLogs::drop_segments_below::<S>(self, offset)
+    }
 }
 
 pub fn fold_consecutive_locations(locations: &[Location]) -> Vec<Range> {
@@ -258,7 +711,12 @@ impl CommitLogs {
         if !Path::new(&path).exists() {
             std::fs::create_dir_all(&path)?;
         }
-        let log = CommitLog::new(path, false)?;
+        let mut log = CommitLog::new(path, false)?;
+
+        let report = log.repair()?;
+        if report.truncated_bytes > 0 {
+            slog::warn!(&self.log, "Repaired commit log, incomplete records were truncated"; "commit_log_name" => name, "valid_records" => report.records, "truncated_bytes" => report.truncated_bytes);
+        }
 
         let mut commit_log_map =
             self.commit_log_map
@@ -283,7 +741,22 @@ impl CommitLogs {
         Ok(commit_log_map.get(name).cloned())
     }
 
-    /// Flush all registered commit logs.
+    /// Remove the segments of the commit log of schema `S` whose records are all below `offset`,
+    /// returns the number of removed segments.
+    pub fn drop_segments_below<S: CommitLogSchema>(
+        &self,
+        offset: u64,
+    ) -> Result<usize, CommitLogError> {
+        let cl = self
+            .cl_handle(S::name())?
+            .ok_or(CommitLogError::MissingCommitLog { name: S::name() })?;
+        let mut cl = cl.write().map_err(|e| CommitLogError::RwLockPoisonError {
+            error: e.to_string(),
+        })?;
+        cl.drop_segments_below(offset)
+    }
+
+    /// Flush and sync all registered commit logs.
     fn flush(&self) -> Result<(), CommitLogError> {
         let commit_log_map =
             self.commit_log_map
@@ -298,12 +771,12 @@ impl CommitLogs {
                     .map_err(|e| CommitLogError::RwLockPoisonError {
                         error: e.to_string(),
                     })?;
-            match commit_log.flush() {
+            match commit_log.sync() {
                 Ok(_) => {
-                    slog::debug!(&self.log, "Successfully flushed commit log"; "commit_log_num" => (commit_log_idx + 1), "commit_log_name" => commit_log_name, "data_file_path" => format!("{:?}", commit_log.data_file_path))
+                    slog::debug!(&self.log, "Successfully flushed commit log"; "commit_log_num" => (commit_log_idx + 1), "commit_log_name" => commit_log_name, "commit_log_dir" => format!("{:?}", commit_log.dir))
                 }
                 Err(e) => {
-                    slog::error!(&self.log, "Failed to flush commit log"; "commit_log_name" => commit_log_name, "data_file_path" => format!("{:?}", commit_log.data_file_path), "reason" =>  e)
+                    slog::error!(&self.log, "Failed to flush commit log"; "commit_log_name" => commit_log_name, "commit_log_dir" => format!("{:?}", commit_log.dir), "reason" =>  e)
                 }
             }
         }
@@ -383,6 +856,186 @@ mod tests {
         );
     }
 
+    fn clean_dir(dir: &str) {
+        if Path::new(dir).exists() {
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_segment_rotation() {
+        let dir = "./testdir/commit_log/segment_rotation";
+        clean_dir(dir);
+
+        let messages = generate_random_data(100, 100, 200);
+        let mut commit_log = CommitLog::with_segment_size(dir, false, 1024).unwrap();
+        let locations: Vec<_> = messages
+            .iter()
+            .map(|msg| commit_log.append_msg(msg).unwrap())
+            .collect();
+        assert!(commit_log.segment_count() > 1);
+
+        for (msg, (offset, size)) in messages.iter().zip(&locations) {
+            assert_eq!(msg, &commit_log.read(*offset, *size).unwrap());
+        }
+        drop(commit_log);
+
+        // offsets are still valid after reopening
+        let commit_log = CommitLog::with_segment_size(dir, false, 1024).unwrap();
+        assert_eq!(messages.len(), commit_log.verify().unwrap());
+        for (msg, (offset, size)) in messages.iter().zip(&locations) {
+            assert_eq!(msg, &commit_log.read(*offset, *size).unwrap());
+        }
+
+        clean_dir(dir);
+    }
+
+    #[test]
+    fn test_repair_truncates_torn_tail() {
+        let dir = "./testdir/commit_log/repair";
+        clean_dir(dir);
+
+        let messages = generate_random_data(10, 100, 200);
+        let mut commit_log = CommitLog::new(dir, false).unwrap();
+        let locations: Vec<_> = messages
+            .iter()
+            .map(|msg| commit_log.append_msg(msg).unwrap())
+            .collect();
+        drop(commit_log);
+
+        // simulate a record partially written at shutdown
+        let segment_path = segment_path(Path::new(dir), 0);
+        let mut file = OpenOptions::new().append(true).open(&segment_path).unwrap();
+        file.write_all(&150_u32.to_le_bytes()).unwrap();
+        file.write_all(&[1, 2, 3, 4, 5, 6]).unwrap();
+        drop(file);
+
+        let mut commit_log = CommitLog::new(dir, false).unwrap();
+        assert!(commit_log.verify().is_err());
+        let report = commit_log.repair().unwrap();
+        assert_eq!(
+            RepairReport {
+                records: messages.len(),
+                truncated_bytes: 10,
+            },
+            report
+        );
+        assert_eq!(messages.len(), commit_log.verify().unwrap());
+
+        // the offset of the torn record is not reused
+        let (last_offset, last_size) = locations.last().unwrap();
+        let torn_offset = last_offset + RECORD_HEADER_SIZE + *last_size as u64;
+        let (offset, size) = commit_log.append_msg(&messages[0]).unwrap();
+        assert_eq!(torn_offset + 10, offset);
+        assert_eq!(messages[0], commit_log.read(offset, size).unwrap());
+        assert!(matches!(
+            commit_log.read(torn_offset, 150),
+            Err(CommitLogError::ReadError { .. })
+        ));
+        for (msg, (offset, size)) in messages.iter().zip(&locations) {
+            assert_eq!(msg, &commit_log.read(*offset, *size).unwrap());
+        }
+
+        clean_dir(dir);
+    }
+
+    #[test]
+    fn test_read_detects_corruption() {
+        let dir = "./testdir/commit_log/corruption";
+        clean_dir(dir);
+
+        let mut commit_log = CommitLog::new(dir, false).unwrap();
+        let (offset, size) = commit_log.append_msg(vec![7_u8; 64]).unwrap();
+        let (next_offset, next_size) = commit_log.append_msg(vec![8_u8; 64]).unwrap();
+
+        // flip a byte of the first record payload
+        let segment_path = segment_path(Path::new(dir), 0);
+        let mut file = OpenOptions::new().write(true).open(&segment_path).unwrap();
+        file.seek(SeekFrom::Start(
+            SEGMENT_HEADER_SIZE + offset + RECORD_HEADER_SIZE + 10,
+        ))
+        .unwrap();
+        file.write_all(&[0]).unwrap();
+        drop(file);
+
+        assert!(matches!(
+            commit_log.read(offset, size),
+            Err(CommitLogError::ChecksumMismatch { .. })
+        ));
+        assert_eq!(
+            vec![8_u8; 64],
+            commit_log.read(next_offset, next_size).unwrap()
+        );
+        assert!(commit_log.read(offset, size + 1).is_err());
+
+        clean_dir(dir);
+    }
+
+    #[test]
+    fn test_drop_segments_below() {
+        let dir = "./testdir/commit_log/drop_segments";
+        clean_dir(dir);
+
+        let messages = generate_random_data(50, 100, 200);
+        let mut commit_log = CommitLog::with_segment_size(dir, true, 1024).unwrap();
+        let locations: Vec<_> = messages
+            .iter()
+            .map(|msg| commit_log.append_msg(msg).unwrap())
+            .collect();
+        let segment_count = commit_log.segment_count();
+
+        let (prune_offset, _) = locations[25];
+        let dropped = commit_log.drop_segments_below(prune_offset).unwrap();
+        assert!(dropped > 0);
+        assert_eq!(segment_count - dropped, commit_log.segment_count());
+        assert!(commit_log.first_offset() <= prune_offset);
+
+        let (offset, size) = locations[0];
+        assert!(matches!(
+            commit_log.read(offset, size),
+            Err(CommitLogError::SegmentDropped { .. })
+        ));
+        for (msg, (offset, size)) in messages.iter().zip(&locations).skip(25) {
+            assert_eq!(msg, &commit_log.read(*offset, *size).unwrap());
+        }
+
+        // the active segment is never dropped
+        commit_log.drop_segments_below(u64::MAX).unwrap();
+        assert_eq!(1, commit_log.segment_count());
+        let (offset, size) = commit_log.append_msg(&messages[0]).unwrap();
+        assert_eq!(messages[0], commit_log.read(offset, size).unwrap());
+
+        clean_dir(dir);
+    }
+
+    #[test]
+    fn test_legacy_data_file() {
+        let dir = "./testdir/commit_log/legacy";
+        clean_dir(dir);
+        std::fs::create_dir_all(dir).unwrap();
+
+        let legacy_data = [vec![1_u8; 32], vec![2_u8; 48]];
+        std::fs::write(
+            Path::new(dir).join(LEGACY_DATA_FILE_NAME),
+            legacy_data.concat(),
+        )
+        .unwrap();
+
+        let mut commit_log = CommitLog::new(dir, false).unwrap();
+        assert_eq!(legacy_data[0], commit_log.read(0, 32).unwrap());
+        assert_eq!(legacy_data[1], commit_log.read(32, 48).unwrap());
+
+        let (offset, size) = commit_log.append_msg(vec![3_u8; 16]).unwrap();
+        assert_eq!(80, offset);
+        assert_eq!(vec![3_u8; 16], commit_log.read(offset, size).unwrap());
+
+        assert_eq!(1, commit_log.drop_segments_below(80).unwrap());
+        assert!(!Path::new(dir).join(LEGACY_DATA_FILE_NAME).exists());
+        assert_eq!(80, commit_log.first_offset());
+
+        clean_dir(dir);
+    }
+
     fn generate_random_data(
         data_size: usize,
         min_message_size: usize,
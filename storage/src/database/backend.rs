@@ -28,6 +28,17 @@ pub struct DBStats {
     pub total_updates: u64,
     #[serde(serialize_with = "to_u128")]
     pub total_update_duration: Duration,
+    /// Bytes on disk still referenced, and bytes reclaimable by a compaction (EdgeKV only)
+    pub live_bytes: u64,
+    pub dead_bytes: u64,
+    pub files: Vec<DBFileStats>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct DBFileStats {
+    pub file_id: String,
+    pub live_bytes: u64,
+    pub dead_bytes: u64,
 }
 
 fn to_u128<S>(x: &Duration, s: S) -> Result<S::Ok, S::Error>
@@ -49,6 +60,7 @@ pub trait TezedgeDatabaseBackendStore {
         column: &'static str,
         batch: Vec<(Vec<u8>, Vec<u8>)>,
     ) -> Result<(), Error>;
+    fn delete_batch(&self, column: &'static str, keys: Vec<Vec<u8>>) -> Result<(), Error>;
     fn flush(&self) -> Result<usize, Error>;
     fn size(&self) -> HashMap<&'static str, usize>;
     fn sync(&self) -> Result<(), Error>;
@@ -2,14 +2,16 @@
 // SPDX-License-Identifier: MIT
 
 use crate::database::backend::{
-    BackendIterator, BackendIteratorMode, DBStats, TezedgeDatabaseBackendStore,
+    BackendIterator, BackendIteratorMode, DBFileStats, DBStats, TezedgeDatabaseBackendStore,
 };
 use crate::database::error::Error;
 use crate::database::tezedge_database::{KVStoreKeyValueSchema, TezdegeDatabaseBackendKV};
 use crate::{
     block_meta_storage, operations_meta_storage, BlockMetaStorage, Direction, OperationsMetaStorage,
 };
-use edgekv::edgekv::EdgeKV;
+use edgekv::batch::WriteBatch;
+use edgekv::edgekv::{EdgeKV, EdgeKVConfiguration, SnapshotIterator};
+use slog::{o, Logger};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
@@ -21,14 +23,23 @@ pub struct EdgeKVBackend {
 }
 
 impl EdgeKVBackend {
-    pub fn new<P: AsRef<Path>>(path: P, columns: Vec<&'static str>) -> Result<Self, Error> {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        columns: Vec<&'static str>,
+        log: Logger,
+    ) -> Result<Self, Error> {
         let mut db = HashMap::with_capacity(columns.len());
         let mut p = PathBuf::new();
         p.push(path);
         for col in columns {
             let mut path = p.clone();
             path.push(col);
-            let col_db = EdgeKV::open(path.as_path()).map_err(|error| Error::EdgeKVError {
+            let col_db = EdgeKV::open_with_configuration(
+                path.as_path(),
+                EdgeKVConfiguration::default(),
+                log.new(o!("column" => col)),
+            )
+            .map_err(|error| Error::EdgeKVError {
                 error: format!("{:?}", error),
             })?;
             db.insert(col, col_db);
@@ -51,33 +62,26 @@ pub enum EdgeKVIteratorMode {
 
 pub struct EdgeKVIterator {
     mode: EdgeKVIteratorMode,
-    iter: edgekv::edgekv::DBIterator,
+    iter: SnapshotIterator,
 }
 
 impl EdgeKVIterator {
-    fn new(mode: EdgeKVIteratorMode, db: &EdgeKV) -> Self {
-        match mode.clone() {
-            EdgeKVIteratorMode::Start => Self {
-                mode,
-                iter: db.iter(),
-            },
-            EdgeKVIteratorMode::End => Self {
-                mode,
-                iter: db.iter(),
-            },
-            EdgeKVIteratorMode::From(key, direction) => {
-                let iter = match direction {
-                    Direction::Forward => db.range(key..),
-                    Direction::Reverse => db.range(..=key),
-                };
-
-                Self { mode, iter }
-            }
-            EdgeKVIteratorMode::Prefix(key) => Self {
-                mode,
-                iter: db.prefix(&key),
+    /// Iterates over a snapshot of the column, so concurrent writes and compactions
+    /// don't affect the iteration
+    fn new(mode: EdgeKVIteratorMode, db: &EdgeKV) -> Result<Self, Error> {
+        let snapshot = db.snapshot().map_err(|error| Error::EdgeKVError {
+            error: format!("{:?}", error),
+        })?;
+        let iter = match mode.clone() {
+            EdgeKVIteratorMode::Start => snapshot.iter(),
+            EdgeKVIteratorMode::End => snapshot.iter(),
+            EdgeKVIteratorMode::From(key, direction) => match direction {
+                Direction::Forward => snapshot.range(key..),
+                Direction::Reverse => snapshot.range(..=key),
             },
-        }
+            EdgeKVIteratorMode::Prefix(key) => snapshot.prefix(&key),
+        };
+        Ok(Self { mode, iter })
     }
 }
 impl TezdegeDatabaseBackendKV for EdgeKVBackend {}
@@ -231,12 +235,28 @@ impl TezedgeDatabaseBackendStore for EdgeKVBackend {
         let db = self.db.get(column).ok_or(Error::EdgeKVError {
             error: format!("Column Missing: {}", column),
         })?;
+        let mut write_batch = WriteBatch::new();
         for (key, value) in batch {
-            db.put(key, value).map_err(|error| Error::EdgeKVError {
+            write_batch.put(key, value);
+        }
+        db.write_batch(write_batch)
+            .map_err(|error| Error::EdgeKVError {
                 error: format!("{:?}", error),
-            })?;
+            })
+    }
+
+    fn delete_batch(&self, column: &'static str, keys: Vec<Vec<u8>>) -> Result<(), Error> {
+        let db = self.db.get(column).ok_or(Error::EdgeKVError {
+            error: format!("Column Missing: {}", column),
+        })?;
+        let mut write_batch = WriteBatch::new();
+        for key in keys {
+            write_batch.delete(key);
         }
-        Ok(())
+        db.write_batch(write_batch)
+            .map_err(|error| Error::EdgeKVError {
+                error: format!("{:?}", error),
+            })
     }
 
     fn flush(&self) -> Result<usize, Error> {
@@ -274,10 +294,10 @@ impl TezedgeDatabaseBackendStore for EdgeKVBackend {
         })?;
 
         let iter = match mode {
-            BackendIteratorMode::Start => EdgeKVIterator::new(EdgeKVIteratorMode::Start, db),
-            BackendIteratorMode::End => EdgeKVIterator::new(EdgeKVIteratorMode::End, db),
+            BackendIteratorMode::Start => EdgeKVIterator::new(EdgeKVIteratorMode::Start, db)?,
+            BackendIteratorMode::End => EdgeKVIterator::new(EdgeKVIteratorMode::End, db)?,
             BackendIteratorMode::From(key, direction) => {
-                EdgeKVIterator::new(EdgeKVIteratorMode::From(key, direction), db)
+                EdgeKVIterator::new(EdgeKVIteratorMode::From(key, direction), db)?
             }
         };
 
@@ -297,7 +317,7 @@ impl TezedgeDatabaseBackendStore for EdgeKVBackend {
         })?;
 
         let prefix_key = key[..max_key_len].to_vec();
-        let iter = EdgeKVIterator::new(EdgeKVIteratorMode::Prefix(prefix_key), db);
+        let iter = EdgeKVIterator::new(EdgeKVIteratorMode::Prefix(prefix_key), db)?;
 
         Ok(Box::new(iter.map(|result| {
             result.map(|(k, v)| (k.into_boxed_slice(), v.into_boxed_slice()))
@@ -311,6 +331,26 @@ impl TezedgeDatabaseBackendStore for EdgeKVBackend {
             Ok(stats) => stats,
             Err(_) => return Default::default(),
         };
-        stats.clone()
+        let mut stats = stats.clone();
+
+        for (column, db) in self.db.iter() {
+            let files = match db.file_stats() {
+                Ok(files) => files,
+                Err(_) => continue,
+            };
+            let stat = stats.entry(*column).or_insert_with(Default::default);
+            stat.live_bytes = files.iter().map(|file| file.live_bytes).sum();
+            stat.dead_bytes = files.iter().map(|file| file.dead_bytes()).sum();
+            stat.files = files
+                .into_iter()
+                .map(|file| DBFileStats {
+                    dead_bytes: file.dead_bytes(),
+                    live_bytes: file.live_bytes,
+                    file_id: file.file_id,
+                })
+                .collect();
+        }
+
+        stats
     }
 }
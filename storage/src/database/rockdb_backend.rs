@@ -162,6 +162,19 @@ impl TezedgeDatabaseBackendStore for RocksDBBackend {
         Ok(())
     }
 
+    fn delete_batch(&self, column: &'static str, keys: Vec<Vec<u8>>) -> Result<(), Error> {
+        let cf = self
+            .db
+            .cf_handle(column)
+            .ok_or(Error::MissingColumnFamily { name: column })?;
+        let mut rocksb_batch = WriteBatch::default();
+        for key in keys.iter() {
+            rocksb_batch.delete_cf(cf, key);
+        }
+        self.db.write_opt(rocksb_batch, &default_write_options())?;
+        Ok(())
+    }
+
     fn flush(&self) -> Result<usize, Error> {
         self.db.flush()?;
         Ok(0)
@@ -131,6 +131,15 @@ impl TezedgeDatabaseBackendStore for SledDBBackend {
         tree.apply_batch(sled_batch).map_err(Error::from)
     }
 
+    fn delete_batch(&self, column: &'static str, keys: Vec<Vec<u8>>) -> Result<(), Error> {
+        let mut sled_batch = sled::Batch::default();
+        let tree = self.get_tree(column)?;
+        for k in keys {
+            sled_batch.remove(k)
+        }
+        tree.apply_batch(sled_batch).map_err(Error::from)
+    }
+
     fn flush(&self) -> Result<usize, Error> {
         self.db.flush().map_err(Error::from)
     }
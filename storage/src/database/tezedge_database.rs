@@ -61,6 +61,12 @@ pub trait KVStore<S: KeyValueSchema> {
     /// # Arguments
     /// * `batch` - WriteBatch containing all batched writes to be written to DB
     fn write_batch(&self, batch: Vec<(S::Key, S::Value)>) -> Result<(), Error>;
+
+    /// Delete all the keys from the DB atomically
+    ///
+    /// # Arguments
+    /// * `keys` - Values of keys specified by schema
+    fn delete_batch(&self, keys: Vec<S::Key>) -> Result<(), Error>;
 }
 
 pub trait KVStoreWithSchemaIterator<S: KeyValueSchema> {
@@ -236,6 +242,14 @@ impl<S: KVStoreKeyValueSchema> KVStore<S> for TezedgeDatabase {
         self.backend.write_batch(S::column_name(), generic_batch)?;
         Ok(())
     }
+
+    fn delete_batch(&self, keys: Vec<S::Key>) -> Result<(), Error> {
+        let keys = keys
+            .iter()
+            .map(|key| key.encode())
+            .collect::<Result<Vec<_>, _>>()?;
+        self.backend.delete_batch(S::column_name(), keys)
+    }
 }
 
 impl TezedgeDatabase {
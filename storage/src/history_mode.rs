@@ -0,0 +1,898 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! History modes of the main database, following the Octez ones:
+//!
+//! - `archive` - everything is kept since genesis
+//! - `full` - block headers and operations are kept since genesis, but the block application
+//!   results (metadata) are only kept above the savepoint
+//! - `rolling` - like `full`, but blocks below the caboose are removed too
+//!
+//! The savepoint and the caboose are moved every time a cycle completes, so that
+//! `preserved_cycles` (from the protocol constants) + `additional_cycles` are kept.
+//! Contexts below the savepoint are removed by the garbage collector of the context
+//! (see [`HistoryMode::retained_cycles`]).
+
+use std::fmt;
+use std::str::FromStr;
+
+use slog::{info, Logger};
+
+use crypto::hash::{BlockHash, ChainId, ProtocolHash};
+use tezos_messages::p2p::encoding::block_header::Level;
+use tezos_messages::Head;
+
+use crate::cycle_eras_storage::CycleEra;
+use crate::{
+    AccountOperationsStorage, BlockAdditionalData, BlockHeaderWithHash, BlockMetaStorage,
+    BlockMetaStorageReader, BlockStorage, BlockStorageReader, ChainMetaStorage,
+    ChainMetaStorageReader, ConstantsStorage, CycleErasStorage, OperationsMetaStorage,
+    OperationsStorage, PersistentStorage, StorageError, SystemStorage,
+};
+
+/// Number of cycles kept in addition to `preserved_cycles`, same default as Octez
+pub const DEFAULT_ADDITIONAL_CYCLES: u8 = 5;
+
+/// Used when the constants of the protocol are not known
+pub const DEFAULT_PRESERVED_CYCLES: u8 = 5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistoryMode {
+    Archive,
+    Full { additional_cycles: u8 },
+    Rolling { additional_cycles: u8 },
+}
+
+impl HistoryMode {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec!["archive", "full", "rolling"]
+    }
+
+    pub fn is_archive(&self) -> bool {
+        matches!(self, HistoryMode::Archive)
+    }
+
+    /// Number of cycles to keep below the current head, `None` in `archive` mode
+    pub fn retained_cycles(&self, preserved_cycles: u8) -> Option<u32> {
+        match self {
+            HistoryMode::Archive => None,
+            HistoryMode::Full { additional_cycles }
+            | HistoryMode::Rolling { additional_cycles } => {
+                Some(preserved_cycles as u32 + *additional_cycles as u32)
+            }
+        }
+    }
+
+    /// Mode stored in a database can only be switched to a mode keeping less history
+    fn rank(&self) -> u8 {
+        match self {
+            HistoryMode::Archive => 0,
+            HistoryMode::Full { .. } => 1,
+            HistoryMode::Rolling { .. } => 2,
+        }
+    }
+}
+
+impl Default for HistoryMode {
+    fn default() -> Self {
+        HistoryMode::Archive
+    }
+}
+
+impl fmt::Display for HistoryMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryMode::Archive => write!(f, "archive"),
+            HistoryMode::Full { additional_cycles } => write!(f, "full:{}", additional_cycles),
+            HistoryMode::Rolling { additional_cycles } => {
+                write!(f, "rolling:{}", additional_cycles)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHistoryModeError(String);
+
+impl fmt::Display for ParseHistoryModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid history mode '{}', expected one of: archive, full[:<additional_cycles>], rolling[:<additional_cycles>]",
+            self.0
+        )
+    }
+}
+
+impl FromStr for HistoryMode {
+    type Err = ParseHistoryModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, additional_cycles) = match s.split_once(':') {
+            Some((mode, cycles)) => (
+                mode,
+                cycles
+                    .parse::<u8>()
+                    .map_err(|_| ParseHistoryModeError(s.to_string()))?,
+            ),
+            None => (s, DEFAULT_ADDITIONAL_CYCLES),
+        };
+
+        match mode.to_ascii_lowercase().as_str() {
+            "archive" if !s.contains(':') => Ok(HistoryMode::Archive),
+            "full" => Ok(HistoryMode::Full { additional_cycles }),
+            "rolling" => Ok(HistoryMode::Rolling { additional_cycles }),
+            _ => Err(ParseHistoryModeError(s.to_string())),
+        }
+    }
+}
+
+/// Stores the history mode of a new database, or checks that the history mode of an existing
+/// database can be switched to `history_mode`.
+///
+/// A database without a stored history mode was created before history modes, so it is an archive.
+pub fn check_history_mode(
+    system_storage: &mut SystemStorage,
+    history_mode: HistoryMode,
+) -> Result<(), StorageError> {
+    let stored =
+        match system_storage.get_history_mode()? {
+            Some(stored) => stored.parse::<HistoryMode>().map_err(|_| {
+                StorageError::IncompatibleHistoryMode {
+                    stored: stored.clone(),
+                    requested: history_mode.to_string(),
+                }
+            })?,
+            None => HistoryMode::Archive,
+        };
+
+    if history_mode.rank() < stored.rank() {
+        return Err(StorageError::IncompatibleHistoryMode {
+            stored: stored.to_string(),
+            requested: history_mode.to_string(),
+        });
+    }
+
+    system_storage.set_history_mode(&history_mode.to_string())
+}
+
+/// Maximal number of levels handled by a single [`HistoryPruner::prune`], so that a long history
+/// (e.g. of an archive switched to `full`) is pruned gradually by the following applied blocks.
+pub const MAX_PRUNED_LEVELS: Level = 4096;
+
+/// Result of a [`HistoryPruner::prune`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PruneReport {
+    pub savepoint_level: Level,
+    pub caboose_level: Level,
+    /// Number of blocks whose metadata was removed
+    pub pruned_metadata: usize,
+    /// Number of blocks removed (fork blocks below the savepoint and blocks below the caboose)
+    pub pruned_blocks: usize,
+    /// Number of commit log segments removed
+    pub dropped_segments: usize,
+    /// False, if the savepoint (or the caboose) did not reach its target level yet
+    pub complete: bool,
+}
+
+/// Enforces the history mode, moves the savepoint (and the caboose in `rolling` mode)
+/// when a cycle completes and removes the data below them.
+pub struct HistoryPruner {
+    history_mode: HistoryMode,
+    block_storage: BlockStorage,
+    block_meta_storage: BlockMetaStorage,
+    operations_storage: OperationsStorage,
+    operations_meta_storage: OperationsMetaStorage,
+    account_operations_storage: AccountOperationsStorage,
+    chain_meta_storage: ChainMetaStorage,
+    constants_storage: ConstantsStorage,
+    cycle_eras_storage: CycleErasStorage,
+    log: Logger,
+}
+
+impl HistoryPruner {
+    pub fn new(
+        persistent_storage: &PersistentStorage,
+        history_mode: HistoryMode,
+        log: Logger,
+    ) -> Self {
+        Self {
+            history_mode,
+            block_storage: BlockStorage::new(persistent_storage),
+            block_meta_storage: BlockMetaStorage::new(persistent_storage),
+            operations_storage: OperationsStorage::new(persistent_storage),
+            operations_meta_storage: OperationsMetaStorage::new(persistent_storage),
+            account_operations_storage: AccountOperationsStorage::new(persistent_storage),
+            chain_meta_storage: ChainMetaStorage::new(persistent_storage),
+            constants_storage: ConstantsStorage::new(persistent_storage),
+            cycle_eras_storage: CycleErasStorage::new(persistent_storage),
+            log,
+        }
+    }
+
+    pub fn history_mode(&self) -> HistoryMode {
+        self.history_mode
+    }
+
+    /// Called once the block `block_hash` at `level` is applied (and is the new head),
+    /// prunes the history below the cycles retained since the last completed cycle.
+    pub fn block_applied(
+        &self,
+        chain_id: &ChainId,
+        block_hash: &BlockHash,
+        level: Level,
+        block_additional_data: &BlockAdditionalData,
+    ) -> Result<Option<PruneReport>, StorageError> {
+        if self.history_mode.is_archive() {
+            return Ok(None);
+        }
+
+        let protocol_hash = block_additional_data.protocol_hash();
+        let era = match self.cycle_era(protocol_hash, level)? {
+            Some(era) => era,
+            None => return Ok(None),
+        };
+        let blocks_per_cycle = *era.blocks_per_cycle();
+        if blocks_per_cycle <= 0 {
+            return Ok(None);
+        }
+        let retained_cycles = match self
+            .history_mode
+            .retained_cycles(preserved_cycles(&self.constants_storage, protocol_hash)?)
+        {
+            Some(retained_cycles) => retained_cycles as i32,
+            None => return Ok(None),
+        };
+
+        // last block of the last completed cycle
+        let cycle_end_level = level - (level - *era.first_level() + 1).rem_euclid(blocks_per_cycle);
+        let savepoint_level =
+            cycle_end_level + 1 - retained_cycles.saturating_mul(blocks_per_cycle);
+        if savepoint_level <= 0 {
+            return Ok(None);
+        }
+
+        self.prune(chain_id, block_hash, level, savepoint_level)
+    }
+
+    /// Moves the savepoint towards `savepoint_level` (and the caboose below it in `rolling` mode)
+    /// on the chain of the `head`, by at most [`MAX_PRUNED_LEVELS`].
+    ///
+    /// Returns `None`, if there is nothing to prune.
+    pub fn prune(
+        &self,
+        chain_id: &ChainId,
+        head: &BlockHash,
+        head_level: Level,
+        savepoint_level: Level,
+    ) -> Result<Option<PruneReport>, StorageError> {
+        if self.history_mode.is_archive() || savepoint_level > head_level {
+            return Ok(None);
+        }
+
+        let savepoint = self.chain_meta_storage.get_savepoint(chain_id)?;
+        let caboose = self.chain_meta_storage.get_caboose(chain_id)?;
+        let mut report = PruneReport {
+            savepoint_level: savepoint.as_ref().map(|head| *head.level()).unwrap_or(0),
+            caboose_level: caboose.as_ref().map(|head| *head.level()).unwrap_or(0),
+            complete: true,
+            ..PruneReport::default()
+        };
+        let mut savepoint_hash = savepoint.map(|head| head.block_hash().clone());
+        let mut pruned = false;
+
+        if savepoint_level > report.savepoint_level {
+            let batch_level = savepoint_level.min(report.savepoint_level + MAX_PRUNED_LEVELS);
+            let block = match self.canonical_block(head, head_level, batch_level)? {
+                Some(block) => block,
+                None => return Ok(None),
+            };
+
+            info!(self.log, "Pruning history";
+                            "history_mode" => self.history_mode.to_string(),
+                            "head_level" => head_level,
+                            "savepoint_level" => batch_level,
+                            "target_savepoint_level" => savepoint_level);
+
+            let mut cleared_levels = Vec::new();
+            self.prune_metadata(
+                &block.hash,
+                batch_level,
+                report.savepoint_level,
+                &mut report,
+                &mut cleared_levels,
+            )?;
+            // the level index is last-write-wins, so it could point to a removed fork block
+            for level in cleared_levels {
+                if let Some(canonical) = self.canonical_block(head, head_level, level)? {
+                    self.block_storage.index_by_level(&canonical.hash, level)?;
+                }
+            }
+
+            self.chain_meta_storage.set_savepoint(
+                chain_id,
+                Head::new(
+                    block.hash.clone(),
+                    batch_level,
+                    block.header.fitness().clone(),
+                ),
+            )?;
+            report.savepoint_level = batch_level;
+            report.complete = batch_level == savepoint_level;
+            savepoint_hash = Some(block.hash);
+            pruned = true;
+        }
+
+        if let (HistoryMode::Rolling { .. }, true, Some(savepoint_hash)) =
+            (self.history_mode, report.complete, savepoint_hash)
+        {
+            // keep the blocks needed to validate the operations of the savepoint successors
+            let max_operations_ttl = self
+                .block_meta_storage
+                .get_additional_data(&savepoint_hash)?
+                .map(|data| data.max_operations_ttl() as i32)
+                .unwrap_or(0);
+            let caboose_level = report.savepoint_level - max_operations_ttl;
+
+            if caboose_level > report.caboose_level {
+                let batch_level = caboose_level.min(report.caboose_level + MAX_PRUNED_LEVELS);
+                if let Some(block) = self.canonical_block(head, head_level, batch_level)? {
+                    self.remove_blocks(
+                        &block.hash,
+                        batch_level,
+                        report.caboose_level,
+                        &mut report,
+                    )?;
+                    self.chain_meta_storage.set_caboose(
+                        chain_id,
+                        Head::new(block.hash, batch_level, block.header.fitness().clone()),
+                    )?;
+                    report.caboose_level = batch_level;
+                    report.complete = batch_level == caboose_level;
+                    pruned = true;
+
+                    if report.complete {
+                        if let Some(offset) =
+                            self.block_storage.lowest_offset_from_level(batch_level)?
+                        {
+                            report.dropped_segments =
+                                self.block_storage.drop_commit_log_segments_below(offset)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        if !pruned {
+            return Ok(None);
+        }
+
+        info!(self.log, "History pruned";
+                        "savepoint_level" => report.savepoint_level,
+                        "caboose_level" => report.caboose_level,
+                        "pruned_metadata" => report.pruned_metadata,
+                        "pruned_blocks" => report.pruned_blocks,
+                        "dropped_segments" => report.dropped_segments,
+                        "complete" => report.complete);
+
+        Ok(Some(report))
+    }
+
+    /// Removes the metadata of the canonical predecessors of the block at `level` down to `from_level`
+    /// and the forks branching from them.
+    fn prune_metadata(
+        &self,
+        block_hash: &BlockHash,
+        mut level: Level,
+        from_level: Level,
+        report: &mut PruneReport,
+        cleared_levels: &mut Vec<Level>,
+    ) -> Result<(), StorageError> {
+        let mut successor = block_hash.clone();
+        let mut predecessor = self.predecessor(block_hash)?;
+        while level > from_level {
+            let block_hash = match predecessor {
+                Some(block_hash) => block_hash,
+                None => break,
+            };
+            level -= 1;
+            predecessor = self.predecessor(&block_hash)?;
+
+            report.pruned_blocks += self.remove_forks(&block_hash, &successor, cleared_levels)?;
+            // the genesis block is always kept
+            if level > 0 {
+                self.block_storage
+                    .remove_block_json_data(&block_hash, level)?;
+                self.block_meta_storage
+                    .remove_block_additional_data(&block_hash)?;
+                report.pruned_metadata += 1;
+            }
+            successor = block_hash;
+        }
+        Ok(())
+    }
+
+    /// Removes the canonical predecessors of the block at `level` down to `from_level`,
+    /// the forks were already removed when the savepoint was moved above them.
+    fn remove_blocks(
+        &self,
+        block_hash: &BlockHash,
+        mut level: Level,
+        from_level: Level,
+        report: &mut PruneReport,
+    ) -> Result<(), StorageError> {
+        let mut predecessor = self.predecessor(block_hash)?;
+        while level > from_level {
+            let block_hash = match predecessor {
+                Some(block_hash) => block_hash,
+                None => break,
+            };
+            level -= 1;
+            predecessor = self.predecessor(&block_hash)?;
+
+            // the genesis block is always kept
+            if level > 0 {
+                self.remove_block(&block_hash, level)?;
+                report.pruned_blocks += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes all blocks branching from `block_hash`, except its canonical `successor`.
+    /// Returns the number of removed blocks.
+    fn remove_forks(
+        &self,
+        block_hash: &BlockHash,
+        successor: &BlockHash,
+        cleared_levels: &mut Vec<Level>,
+    ) -> Result<usize, StorageError> {
+        let mut meta = match self.block_meta_storage.get(block_hash)? {
+            Some(meta) => meta,
+            None => return Ok(0),
+        };
+        let mut forks = meta
+            .successors()
+            .iter()
+            .filter(|fork| *fork != successor)
+            .cloned()
+            .collect::<Vec<_>>();
+        if forks.is_empty() {
+            return Ok(0);
+        }
+        meta.set_successors(vec![successor.clone()]);
+        self.block_meta_storage.put(block_hash, &meta)?;
+
+        let mut removed = 0;
+        while let Some(fork) = forks.pop() {
+            if let Some(fork_meta) = self.block_meta_storage.get(&fork)? {
+                let level = fork_meta.level();
+                forks.extend(fork_meta.take_successors());
+                if self.remove_block(&fork, level)? {
+                    cleared_levels.push(level);
+                }
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Returns true, if the level index pointed to the removed block
+    fn remove_block(&self, block_hash: &BlockHash, level: Level) -> Result<bool, StorageError> {
+        self.account_operations_storage.remove_block(block_hash)?;
+        self.operations_storage.remove_operations(block_hash)?;
+        self.operations_meta_storage.remove(block_hash)?;
+        self.block_meta_storage.remove(block_hash)?;
+        self.block_storage.remove_block(block_hash, level)
+    }
+
+    fn predecessor(&self, block_hash: &BlockHash) -> Result<Option<BlockHash>, StorageError> {
+        Ok(self
+            .block_meta_storage
+            .get(block_hash)?
+            .and_then(|meta| meta.take_predecessor()))
+    }
+
+    /// Block at `level` on the chain of the `head`, the level index cannot be used,
+    /// because it can point to a fork block.
+    fn canonical_block(
+        &self,
+        head: &BlockHash,
+        head_level: Level,
+        level: Level,
+    ) -> Result<Option<BlockHeaderWithHash>, StorageError> {
+        if level > head_level || level < 0 {
+            return Ok(None);
+        }
+        match self
+            .block_meta_storage
+            .find_block_at_distance(head.clone(), (head_level - level) as u32)?
+        {
+            Some(block_hash) => self.block_storage.get(&block_hash),
+            None => Ok(None),
+        }
+    }
+
+    fn cycle_era(
+        &self,
+        protocol_hash: &ProtocolHash,
+        level: Level,
+    ) -> Result<Option<CycleEra>, StorageError> {
+        Ok(self
+            .cycle_eras_storage
+            .get(protocol_hash)?
+            .and_then(|eras| {
+                eras.into_iter()
+                    .filter(|era| *era.first_level() <= level)
+                    .max_by_key(|era| *era.first_level())
+            }))
+    }
+}
+
+/// Returns the `preserved_cycles` constant of the protocol, or [`DEFAULT_PRESERVED_CYCLES`]
+/// when its constants are not known.
+fn preserved_cycles(
+    constants_storage: &ConstantsStorage,
+    protocol_hash: &ProtocolHash,
+) -> Result<u8, StorageError> {
+    let constants = match constants_storage.get(protocol_hash)? {
+        Some(constants) => constants,
+        None => return Ok(DEFAULT_PRESERVED_CYCLES),
+    };
+    let constants: serde_json::Value = serde_json::from_str(&constants)?;
+    Ok(constants
+        .get("preserved_cycles")
+        .and_then(|value| value.as_u64())
+        .map(|value| value.min(u8::MAX as u64) as u8)
+        .unwrap_or(DEFAULT_PRESERVED_CYCLES))
+}
+
+/// Returns the `preserved_cycles` constant of the protocol of the current head of `chain_id`,
+/// or [`DEFAULT_PRESERVED_CYCLES`] when it's not known yet (e.g. on a new database).
+pub fn head_preserved_cycles(
+    persistent_storage: &PersistentStorage,
+    chain_id: &ChainId,
+) -> Result<u8, StorageError> {
+    let head = match ChainMetaStorage::new(persistent_storage).get_current_head(chain_id)? {
+        Some(head) => head,
+        None => return Ok(DEFAULT_PRESERVED_CYCLES),
+    };
+    match BlockMetaStorage::new(persistent_storage).get_additional_data(head.block_hash())? {
+        Some(block_additional_data) => preserved_cycles(
+            &ConstantsStorage::new(persistent_storage),
+            block_additional_data.protocol_hash(),
+        ),
+        None => Ok(DEFAULT_PRESERVED_CYCLES),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use anyhow::Error;
+
+    use tezos_messages::p2p::encoding::fitness::Fitness;
+    use tezos_messages::p2p::encoding::prelude::BlockHeaderBuilder;
+
+    use crate::tests_common::TmpStorage;
+    use crate::BlockJsonData;
+
+    use super::*;
+
+    const MAX_OPERATIONS_TTL: u16 = 3;
+
+    /// Canonical chain of 30 blocks, with a fork of three blocks branching from the level 5
+    /// (stored after the canonical blocks, so the level index points to them)
+    /// and a fork of one block branching from the level 20
+    struct TestChain {
+        storage: TmpStorage,
+        chain_id: ChainId,
+        canonical: Vec<BlockHash>,
+        old_fork: Vec<BlockHash>,
+        recent_fork: BlockHash,
+    }
+
+    impl TestChain {
+        fn new(name: &str) -> Result<Self, Error> {
+            let storage = TmpStorage::create_to_out_dir(name)?;
+            let chain_id: ChainId = "NetXgtSLGNJvNye".try_into()?;
+            let genesis: BlockHash =
+                "BLockGenesisGenesisGenesisGenesisGenesisb83baZgbyZe".try_into()?;
+            BlockMetaStorage::new(storage.storage()).put(
+                &genesis,
+                &crate::block_meta_storage::Meta::genesis_meta(&genesis, &chain_id, true),
+            )?;
+
+            let mut chain = Self {
+                storage,
+                chain_id,
+                canonical: vec![genesis],
+                old_fork: vec![],
+                recent_fork: "BLockGenesisGenesisGenesisGenesisGenesisd6f5afWyME7".try_into()?,
+            };
+            for level in 1..=30 {
+                let block = chain.store(&chain.canonical[level as usize - 1].clone(), level, 0)?;
+                chain.canonical.push(block);
+            }
+            let mut predecessor = chain.canonical[5].clone();
+            for level in 6..=8 {
+                predecessor = chain.store(&predecessor, level, 1)?;
+                chain.old_fork.push(predecessor.clone());
+            }
+            chain.recent_fork = chain.store(&chain.canonical[20].clone(), 21, 2)?;
+
+            Ok(chain)
+        }
+
+        fn store(
+            &self,
+            predecessor: &BlockHash,
+            level: Level,
+            branch: u8,
+        ) -> Result<BlockHash, Error> {
+            let block = BlockHeaderWithHash::new(
+                BlockHeaderBuilder::default()
+                    .level(level)
+                    .proto(1)
+                    .predecessor(predecessor.clone())
+                    .timestamp((level as i64).into())
+                    .validation_pass(4)
+                    .operations_hash(
+                        "LLoaGLRPRx3Zf8kB4ACtgku8F4feeBiskeb41J1ciwfcXB3KzHKXc".try_into()?,
+                    )
+                    .fitness(Fitness::from(vec![vec![0, branch]]))
+                    .context("CoVmAcMV64uAQo8XvfLr9VDuz7HVZLT4cgK1w1qYmTjQNbGwQwDd".try_into()?)
+                    .protocol_data(vec![branch].into())
+                    .build()
+                    .unwrap(),
+            )?;
+            let block_storage = BlockStorage::new(self.storage.storage());
+            let block_meta_storage = BlockMetaStorage::new(self.storage.storage());
+            let log = Logger::root(slog::Discard, slog::o!());
+
+            block_storage.put_block_header(&block)?;
+            block_storage.put_block_json_data(
+                &block.hash,
+                BlockJsonData::new(String::new(), vec![], vec![]),
+            )?;
+            let meta = block_meta_storage.put_block_header(&block, &self.chain_id, &log)?;
+            block_meta_storage.store_predecessors(&block.hash, &meta)?;
+            block_meta_storage.put_block_additional_data(
+                &block.hash,
+                &BlockAdditionalData::new(
+                    MAX_OPERATIONS_TTL,
+                    0,
+                    "PtHangz2aRngywmSRGGvrcTyMbbdpWdpFKuS4uMWxg2RaH9i1qx".try_into()?,
+                    "PtHangz2aRngywmSRGGvrcTyMbbdpWdpFKuS4uMWxg2RaH9i1qx".try_into()?,
+                    None,
+                    None,
+                    None,
+                ),
+            )?;
+            Ok(block.hash)
+        }
+
+        fn pruner(&self, history_mode: HistoryMode) -> HistoryPruner {
+            HistoryPruner::new(
+                self.storage.storage(),
+                history_mode,
+                Logger::root(slog::Discard, slog::o!()),
+            )
+        }
+
+        fn prune(
+            &self,
+            history_mode: HistoryMode,
+            savepoint_level: Level,
+        ) -> Result<Option<PruneReport>, StorageError> {
+            self.pruner(history_mode).prune(
+                &self.chain_id,
+                &self.canonical[30],
+                30,
+                savepoint_level,
+            )
+        }
+
+        fn has_block(&self, block_hash: &BlockHash) -> Result<bool, Error> {
+            Ok(BlockStorage::new(self.storage.storage())
+                .get(block_hash)?
+                .is_some()
+                && BlockMetaStorage::new(self.storage.storage())
+                    .get(block_hash)?
+                    .is_some())
+        }
+
+        fn has_metadata(&self, block_hash: &BlockHash) -> Result<bool, Error> {
+            Ok(BlockStorage::new(self.storage.storage())
+                .get_json_data(block_hash)?
+                .is_some()
+                && BlockMetaStorage::new(self.storage.storage())
+                    .get_additional_data(block_hash)?
+                    .is_some())
+        }
+
+        fn hash_by_level(&self, level: Level) -> Result<Option<BlockHash>, Error> {
+            Ok(BlockStorage::new(self.storage.storage())
+                .get_by_level(level)?
+                .filter(|block| block.header.level() == level)
+                .map(|block| block.hash))
+        }
+    }
+
+    #[test]
+    fn test_prune_full() -> Result<(), Error> {
+        let chain = TestChain::new("__history_mode_prune_full")?;
+        let history_mode = HistoryMode::Full {
+            additional_cycles: 0,
+        };
+        // the level index points to the fork
+        assert_eq!(Some(chain.old_fork[0].clone()), chain.hash_by_level(6)?);
+
+        let report = chain.prune(history_mode, 12)?.expect("pruned");
+        assert_eq!(
+            PruneReport {
+                savepoint_level: 12,
+                caboose_level: 0,
+                pruned_metadata: 11,
+                pruned_blocks: 3,
+                dropped_segments: 0,
+                complete: true,
+            },
+            report
+        );
+
+        // the canonical blocks are kept, with metadata from the savepoint
+        for (level, block_hash) in chain.canonical.iter().enumerate().skip(1) {
+            assert!(chain.has_block(block_hash)?);
+            assert_eq!(level >= 12, chain.has_metadata(block_hash)?);
+        }
+        // the level index is restored to the canonical blocks
+        for level in 1..=20 {
+            assert_eq!(
+                Some(chain.canonical[level as usize].clone()),
+                chain.hash_by_level(level)?
+            );
+        }
+        // forks below the savepoint are removed, not orphaned
+        for block_hash in &chain.old_fork {
+            assert!(!chain.has_block(block_hash)?);
+        }
+        assert_eq!(
+            &vec![chain.canonical[6].clone()],
+            BlockMetaStorage::new(chain.storage.storage())
+                .get(&chain.canonical[5])?
+                .unwrap()
+                .successors()
+        );
+        assert!(chain.has_block(&chain.recent_fork)?);
+        assert!(chain.has_metadata(&chain.recent_fork)?);
+
+        assert_eq!(
+            Some(chain.canonical[12].clone()),
+            ChainMetaStorage::new(chain.storage.storage())
+                .get_savepoint(&chain.chain_id)?
+                .map(|savepoint| savepoint.block_hash().clone())
+        );
+
+        // nothing more to prune for the same savepoint
+        assert_eq!(None, chain.prune(history_mode, 12)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_rolling() -> Result<(), Error> {
+        let chain = TestChain::new("__history_mode_prune_rolling")?;
+        let history_mode = HistoryMode::Rolling {
+            additional_cycles: 0,
+        };
+
+        let report = chain.prune(history_mode, 12)?.expect("pruned");
+        let caboose_level = 12 - MAX_OPERATIONS_TTL as Level;
+        assert_eq!(
+            PruneReport {
+                savepoint_level: 12,
+                caboose_level,
+                pruned_metadata: 11,
+                pruned_blocks: 3 + (caboose_level as usize - 1),
+                dropped_segments: 0,
+                complete: true,
+            },
+            report
+        );
+
+        // the canonical blocks are kept from the caboose
+        for (level, block_hash) in chain.canonical.iter().enumerate().skip(1) {
+            assert_eq!(
+                level as Level >= caboose_level,
+                chain.has_block(block_hash)?
+            );
+        }
+        for level in 1..=20 {
+            assert_eq!(
+                (level >= caboose_level).then(|| chain.canonical[level as usize].clone()),
+                chain.hash_by_level(level)?
+            );
+        }
+        for block_hash in &chain.old_fork {
+            assert!(!chain.has_block(block_hash)?);
+        }
+        assert!(chain.has_block(&chain.recent_fork)?);
+
+        assert_eq!(
+            Some(chain.canonical[caboose_level as usize].clone()),
+            ChainMetaStorage::new(chain.storage.storage())
+                .get_caboose(&chain.chain_id)?
+                .map(|caboose| caboose.block_hash().clone())
+        );
+
+        // the next savepoint moves the caboose from the current one
+        let report = chain.prune(history_mode, 20)?.expect("pruned");
+        assert_eq!(20, report.savepoint_level);
+        assert_eq!(20 - MAX_OPERATIONS_TTL as Level, report.caboose_level);
+        assert_eq!(8, report.pruned_metadata);
+        assert_eq!(8, report.pruned_blocks);
+        assert!(!chain.has_block(&chain.canonical[16])?);
+        assert!(chain.has_block(&chain.canonical[17])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_archive() -> Result<(), Error> {
+        let chain = TestChain::new("__history_mode_prune_archive")?;
+
+        assert_eq!(None, chain.prune(HistoryMode::Archive, 12)?);
+        for block_hash in chain.canonical.iter().skip(1).chain(chain.old_fork.iter()) {
+            assert!(chain.has_block(block_hash)?);
+            assert!(chain.has_metadata(block_hash)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_history_mode() {
+        assert_eq!(Ok(HistoryMode::Archive), "archive".parse());
+        assert_eq!(
+            Ok(HistoryMode::Full {
+                additional_cycles: DEFAULT_ADDITIONAL_CYCLES
+            }),
+            "full".parse()
+        );
+        assert_eq!(
+            Ok(HistoryMode::Rolling {
+                additional_cycles: 2
+            }),
+            "rolling:2".parse()
+        );
+        assert!("archive:2".parse::<HistoryMode>().is_err());
+        assert!("rolling:x".parse::<HistoryMode>().is_err());
+        assert!("experimental".parse::<HistoryMode>().is_err());
+
+        for mode in &[
+            HistoryMode::Archive,
+            HistoryMode::Full {
+                additional_cycles: 3,
+            },
+            HistoryMode::Rolling {
+                additional_cycles: 0,
+            },
+        ] {
+            assert_eq!(Ok(*mode), mode.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn test_retained_cycles() {
+        assert_eq!(None, HistoryMode::Archive.retained_cycles(5));
+        assert_eq!(
+            Some(8),
+            HistoryMode::Rolling {
+                additional_cycles: 3
+            }
+            .retained_cycles(5)
+        );
+    }
+}
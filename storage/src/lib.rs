@@ -31,6 +31,9 @@ use tezos_messages::p2p::binary_message::{BinaryRead, BinaryWrite, MessageHash,
 use tezos_messages::p2p::encoding::prelude::BlockHeader;
 use tezos_messages::Head;
 
+pub use crate::account_operations_storage::{
+    AccountOperationsIndexer, AccountOperationsStorage, AccountOperationsStorageKV,
+};
 pub use crate::block_meta_storage::{
     BlockAdditionalData, BlockMetaStorage, BlockMetaStorageKV, BlockMetaStorageReader,
 };
@@ -41,18 +44,22 @@ pub use crate::constants_storage::ConstantsStorage;
 pub use crate::cycle_eras_storage::CycleErasStorage;
 pub use crate::cycle_storage::CycleMetaStorage;
 use crate::database::tezedge_database::TezedgeDatabase;
+pub use crate::history_mode::{HistoryMode, HistoryPruner};
 pub use crate::mempool_storage::{MempoolStorage, MempoolStorageKV};
 pub use crate::operations_meta_storage::{OperationsMetaStorage, OperationsMetaStorageKV};
 pub use crate::operations_storage::{
     OperationKey, OperationsStorage, OperationsStorageKV, OperationsStorageReader,
 };
+pub use crate::peer_address_book_storage::PeerAddressBookStorage;
 pub use crate::persistent::database::{Direction, IteratorMode};
 use crate::persistent::sequence::{SequenceError, Sequences};
 use crate::persistent::{DBError, Decoder, Encoder, SchemaError};
 pub use crate::predecessor_storage::PredecessorStorage;
+pub use crate::protocol_storage::ProtocolStorage;
 pub use crate::shell_automaton::*;
 pub use crate::system_storage::SystemStorage;
 
+pub mod account_operations_storage;
 pub mod block_meta_storage;
 pub mod block_storage;
 pub mod chain_meta_storage;
@@ -61,11 +68,15 @@ pub mod constants_storage;
 pub mod cycle_eras_storage;
 pub mod cycle_storage;
 pub mod database;
+pub mod history_mode;
 pub mod mempool_storage;
+pub mod migration;
 pub mod operations_meta_storage;
 pub mod operations_storage;
+pub mod peer_address_book_storage;
 pub mod persistent;
 pub mod predecessor_storage;
+pub mod protocol_storage;
 mod shell_automaton;
 pub mod system_storage;
 
@@ -158,6 +169,12 @@ pub enum StorageError {
     MainDBError { error: database::error::Error },
     #[error("Deserialization: {error}")]
     SerdeJsonError { error: serde_json::Error },
+    #[error("Database history mode '{stored}' cannot be switched to '{requested}'")]
+    IncompatibleHistoryMode { stored: String, requested: String },
+    #[error("There is no database migration from version {from} to version {to}")]
+    MissingMigration { from: i64, to: i64 },
+    #[error("Database version {found} is newer than the supported version {supported}")]
+    DatabaseVersionTooNew { found: i64, supported: i64 },
 }
 
 impl From<DBError> for StorageError {
@@ -243,6 +260,15 @@ pub struct Replay {
 pub struct StorageSnapshot {
     pub block: Option<BlockReference>,
     pub target_path: PathBuf,
+    pub format: SnapshotFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// Trimmed copy of the storage
+    Tezedge,
+    /// Snapshot compatible with Octez (`tezos-node snapshot import`)
+    Octez,
 }
 
 #[derive(Debug, Clone)]
@@ -544,6 +570,7 @@ pub fn store_commit_genesis_result(
             // init chain data
             chain_meta_storage.set_genesis(chain_id, head.clone())?;
             chain_meta_storage.set_caboose(chain_id, head.clone())?;
+            chain_meta_storage.set_savepoint(chain_id, head.clone())?;
             chain_meta_storage.set_current_head(chain_id, head)?;
 
             Ok(())
@@ -634,13 +661,13 @@ pub mod initializer {
     use std::sync::Arc;
 
     use rocksdb::{Cache, ColumnFamilyDescriptor, DB};
-    use slog::{error, Logger};
+    use slog::{error, info, Logger};
 
     use crate::database::error::Error as DatabaseError;
     use crate::database::tezedge_database::{TezedgeDatabase, TezedgeDatabaseBackendConfiguration};
     use crate::persistent::database::{open_kv, RocksDbKeyValueSchema};
     use crate::persistent::{open_main_db, DBError, DbConfiguration};
-    use crate::{StorageError, SystemStorage};
+    use crate::{migration, StorageError, SystemStorage};
     use crypto::hash::ChainId;
 
     // IMPORTANT: Cache object must live at least as long as DB (returned by open_kv)
@@ -678,9 +705,13 @@ pub mod initializer {
                 crate::CycleMetaStorage::descriptor(cache),
                 crate::CycleErasStorage::descriptor(cache),
                 crate::ConstantsStorage::descriptor(cache),
+                crate::PeerAddressBookStorage::descriptor(cache),
+                crate::ProtocolStorage::descriptor(cache),
                 crate::ShellAutomatonStateStorage::descriptor(cache),
                 crate::ShellAutomatonActionStorage::descriptor(cache),
                 crate::ShellAutomatonActionMetaStorage::descriptor(cache),
+                crate::AccountOperationsStorage::descriptor(cache),
+                crate::account_operations_storage::AccountOperationsByBlockIndex::descriptor(cache),
             ]
         }
     }
@@ -757,23 +788,33 @@ pub mod initializer {
         log: &Logger,
     ) -> Result<bool, StorageError> {
         let mut system_info = SystemStorage::new(db);
-        let (db_version_ok, found_database_version) = match system_info.get_db_version()? {
+        let db_version_ok = match system_info.get_db_version()? {
+            Some(db_version) if db_version == expected_database_version => true,
             Some(db_version) => {
-                // TODO: TE-608 - refactor this 19/20 fix
-                match expected_database_version {
-                    19 => (db_version == 19 || db_version == 20, db_version),
-                    20 => (db_version == 19 || db_version == 20, db_version),
-                    _ => (db_version == expected_database_version, db_version),
+                // older database is migrated, when the storage is opened (see migration::migrate_database)
+                match migration::migration_plan(
+                    migration::migrations(),
+                    db_version,
+                    expected_database_version,
+                ) {
+                    Ok(plan) => {
+                        info!(log, "Database needs to be migrated";
+                                   "found_version" => db_version,
+                                   "expected_version" => expected_database_version,
+                                   "steps" => plan.len());
+                        true
+                    }
+                    Err(e) => {
+                        error!(log, "Incompatible database version found (expected {}, found {}), reason: {}. Please re-sync your node to empty storage - see configuration!", expected_database_version, db_version, e);
+                        false
+                    }
                 }
             }
             None => {
                 system_info.set_db_version(expected_database_version)?;
-                (true, expected_database_version)
+                true
             }
         };
-        if !db_version_ok {
-            error!(log, "Incompatible database version found (expected {}, found {}). Please re-sync your node to empty storage - see configuration!", expected_database_version, found_database_version);
-        }
 
         let tezos_env_main_chain_id = &expected_main_chain.chain_id;
         let tezos_env_main_chain_name = &expected_main_chain.chain_name;
@@ -931,9 +972,15 @@ pub mod tests_common {
                         CycleErasStorage::descriptor(&db_cache),
                         CycleMetaStorage::descriptor(&db_cache),
                         ConstantsStorage::descriptor(&db_cache),
+                        PeerAddressBookStorage::descriptor(&db_cache),
+                        ProtocolStorage::descriptor(&db_cache),
                         ShellAutomatonStateStorage::descriptor(&db_cache),
                         ShellAutomatonActionStorage::descriptor(&db_cache),
                         ShellAutomatonActionMetaStorage::descriptor(&db_cache),
+                        AccountOperationsStorage::descriptor(&db_cache),
+                        account_operations_storage::AccountOperationsByBlockIndex::descriptor(
+                            &db_cache,
+                        ),
                     ],
                     &cfg,
                 )?);
@@ -963,10 +1010,15 @@ pub mod tests_common {
                         CycleErasStorage::name(),
                         CycleMetaStorage::name(),
                         ConstantsStorage::name(),
+                        PeerAddressBookStorage::name(),
+                        ProtocolStorage::name(),
                         ShellAutomatonStateStorage::name(),
                         ShellAutomatonActionStorage::name(),
                         ShellAutomatonActionMetaStorage::name(),
+                        AccountOperationsStorage::name(),
+                        account_operations_storage::AccountOperationsByBlockIndex::name(),
                     ],
+                    log.clone(),
                 )?)
             } else {
                 let kv = Arc::new(open_kv(
@@ -987,9 +1039,15 @@ pub mod tests_common {
                         CycleErasStorage::descriptor(&db_cache),
                         CycleMetaStorage::descriptor(&db_cache),
                         ConstantsStorage::descriptor(&db_cache),
+                        PeerAddressBookStorage::descriptor(&db_cache),
+                        ProtocolStorage::descriptor(&db_cache),
                         ShellAutomatonStateStorage::descriptor(&db_cache),
                         ShellAutomatonActionStorage::descriptor(&db_cache),
                         ShellAutomatonActionMetaStorage::descriptor(&db_cache),
+                        AccountOperationsStorage::descriptor(&db_cache),
+                        account_operations_storage::AccountOperationsByBlockIndex::descriptor(
+                            &db_cache,
+                        ),
                     ],
                     &cfg,
                 )?);
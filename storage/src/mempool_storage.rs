@@ -1,8 +1,6 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-// TODO: TE-238/ this storage is not used, please remove it
-
 use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Formatter;
@@ -22,9 +20,8 @@ use crate::{num_from_slice, IteratorMode, PersistentStorage, StorageError};
 /// Convenience type for operation meta storage database
 pub type MempoolStorageKV = dyn TezedgeDatabaseWithIterator<MempoolStorage> + Sync + Send;
 
-/// TODO: do we need this?
 /// Distinct
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MempoolOperationType {
     Pending,
     KnownValid,
@@ -151,6 +148,17 @@ impl MempoolStorage {
 
     #[inline]
     pub fn iter(&self) -> Result<Vec<(OperationHash, OperationMessage)>, StorageError> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .map(|(_, operation_hash, operation)| (operation_hash, operation))
+            .collect())
+    }
+
+    /// Returns all stored operations together with their type.
+    pub fn load(
+        &self,
+    ) -> Result<Vec<(MempoolOperationType, OperationHash, OperationMessage)>, StorageError> {
         let mut operations = vec![];
 
         for result in self.kv.find(IteratorMode::Start)? {
@@ -158,7 +166,7 @@ impl MempoolStorage {
 
             let key: MempoolKey = <Self as KeyValueSchema>::Key::decode(&k)?;
             let value: MempoolValue = BincodeEncoded::decode(&v)?;
-            operations.push((key.operation_hash, value.operation));
+            operations.push((key.operation_type, key.operation_hash, value.operation));
         }
 
         Ok(operations)
@@ -0,0 +1,424 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Schema migrations of the main database and the commit logs.
+//!
+//! A migration is an ordered list of steps, every step upgrades the database
+//! from one version to the next one. The version stored in [`SystemStorage`] is updated
+//! after every finished step, so an interrupted migration continues with the interrupted step.
+//! Long running steps can store a checkpoint (e.g. the last migrated key) and continue
+//! from it, which means that every step has to be idempotent since its last checkpoint.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use slog::{info, Logger};
+
+use crate::commit_log::CommitLogs;
+use crate::database::tezedge_database::{
+    KVStore, KVStoreKeyValueSchema, KVStoreWithSchemaIterator, TezedgeDatabase,
+};
+use crate::persistent::{Decoder, Encoder};
+use crate::system_storage::DbVersion;
+use crate::{
+    Direction, IteratorMode, PersistentStorage, ShellAutomatonActionMetaStorage,
+    ShellAutomatonActionStorage, ShellAutomatonStateStorage, StorageError, SystemStorage,
+};
+
+/// How many records are migrated between two checkpoints by [`migrate_column`]
+const CHECKPOINT_INTERVAL: usize = 10_000;
+
+pub type MigrationFn = fn(&mut MigrationContext) -> Result<(), StorageError>;
+
+/// Single step of the migration, from `from_version` to `to_version`
+pub struct Migration {
+    pub from_version: DbVersion,
+    pub to_version: DbVersion,
+    pub description: &'static str,
+    pub run: MigrationFn,
+}
+
+/// All known migrations steps, ordered by version
+pub fn migrations() -> &'static [Migration] {
+    &[
+        Migration {
+            from_version: 19,
+            to_version: 20,
+            description: "no schema changes",
+            run: no_schema_changes,
+        },
+        Migration {
+            from_version: 20,
+            to_version: 21,
+            description: "remove shell automaton state snapshots and actions",
+            run: remove_shell_automaton_records,
+        },
+    ]
+}
+
+fn no_schema_changes(_: &mut MigrationContext) -> Result<(), StorageError> {
+    Ok(())
+}
+
+/// Encoding of the shell automaton state and actions depends on the node version,
+/// so the records of an older node cannot be decoded (e.g. by the dev rpc).
+fn remove_shell_automaton_records(ctx: &mut MigrationContext) -> Result<(), StorageError> {
+    migrate_column::<ShellAutomatonStateStorage, _>(ctx, |_, _| Ok(None))?;
+    migrate_column::<ShellAutomatonActionStorage, _>(ctx, |_, _| Ok(None))?;
+    migrate_column::<ShellAutomatonActionMetaStorage, _>(ctx, |_, _| Ok(None))?;
+    Ok(())
+}
+
+/// Returns steps needed to migrate the database from `from_version` to `to_version`
+pub fn migration_plan(
+    migrations: &[Migration],
+    from_version: DbVersion,
+    to_version: DbVersion,
+) -> Result<Vec<&Migration>, StorageError> {
+    if from_version > to_version {
+        return Err(StorageError::DatabaseVersionTooNew {
+            found: from_version,
+            supported: to_version,
+        });
+    }
+
+    let mut plan = Vec::new();
+    let mut version = from_version;
+    while version < to_version {
+        match migrations
+            .iter()
+            .find(|migration| migration.from_version == version)
+        {
+            Some(migration) if migration.to_version > version => {
+                version = migration.to_version;
+                plan.push(migration);
+            }
+            _ => {
+                return Err(StorageError::MissingMigration {
+                    from: version,
+                    to: to_version,
+                })
+            }
+        }
+    }
+
+    if version == to_version {
+        Ok(plan)
+    } else {
+        Err(StorageError::MissingMigration {
+            from: from_version,
+            to: to_version,
+        })
+    }
+}
+
+/// Result of [`migrate_database`]
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// Versions of the finished (or planned, in dry run) steps
+    pub steps: Vec<(DbVersion, DbVersion)>,
+    /// Number of migrated records, in dry run the number of records which would be migrated
+    pub records: usize,
+}
+
+/// State of the running migration step
+pub struct MigrationContext<'a> {
+    storage: &'a PersistentStorage,
+    system_storage: SystemStorage,
+    to_version: DbVersion,
+    dry_run: bool,
+    records: usize,
+    log: &'a Logger,
+}
+
+impl<'a> MigrationContext<'a> {
+    pub fn main_db(&self) -> Arc<TezedgeDatabase> {
+        self.storage.main_db()
+    }
+
+    pub fn commit_logs(&self) -> Arc<CommitLogs> {
+        self.storage.clog()
+    }
+
+    pub fn log(&self) -> &Logger {
+        self.log
+    }
+
+    /// In dry run, steps must not write anything, just count the records they would migrate
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Checkpoint stored by the interrupted run of this step
+    pub fn checkpoint(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        self.system_storage
+            .get_migration_checkpoint(self.to_version)
+    }
+
+    /// Stores checkpoint of this step, ignored in dry run
+    pub fn save_checkpoint(&mut self, checkpoint: &[u8]) -> Result<(), StorageError> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.system_storage
+            .set_migration_checkpoint(self.to_version, checkpoint)
+    }
+
+    /// Counts migrated records for the [`MigrationReport`]
+    pub fn record_migrated(&mut self, count: usize) {
+        self.records += count;
+    }
+}
+
+/// Rewrites every record of the column `S`, `migrate` returns the new value
+/// or `None` if the record should be deleted.
+///
+/// The last migrated key is stored as a checkpoint, so the interrupted migration
+/// continues after it. A checkpoint of another column is ignored, so a step migrating
+/// more columns restarts the previous columns, after it was interrupted.
+pub fn migrate_column<S, F>(
+    ctx: &mut MigrationContext,
+    mut migrate: F,
+) -> Result<usize, StorageError>
+where
+    S: KVStoreKeyValueSchema,
+    F: FnMut(&S::Key, S::Value) -> Result<Option<S::Value>, StorageError>,
+{
+    let db = ctx.main_db();
+    let column_prefix = checkpoint_prefix::<S>();
+    let checkpoint = ctx.checkpoint()?.and_then(|checkpoint| {
+        checkpoint
+            .strip_prefix(column_prefix.as_slice())
+            .map(Vec::from)
+    });
+    let start_key = match &checkpoint {
+        Some(checkpoint) => Some(S::Key::decode(checkpoint)?),
+        None => None,
+    };
+    let mode = match &start_key {
+        Some(key) => IteratorMode::From(Cow::Borrowed(key), Direction::Forward),
+        None => IteratorMode::Start,
+    };
+
+    let mut migrated = 0;
+    for result in KVStoreWithSchemaIterator::<S>::find(db.as_ref(), mode)? {
+        let (key, value) = result?;
+        // the record of the checkpoint was already migrated
+        if checkpoint.as_deref() == Some(key.as_ref()) {
+            continue;
+        }
+        let key = S::Key::decode(&key)?;
+        let value = S::Value::decode(&value)?;
+
+        let new_value = migrate(&key, value)?;
+        if !ctx.is_dry_run() {
+            match new_value {
+                Some(value) => KVStore::<S>::put(db.as_ref(), &key, &value)?,
+                None => KVStore::<S>::delete(db.as_ref(), &key)?,
+            }
+        }
+
+        migrated += 1;
+        if migrated % CHECKPOINT_INTERVAL == 0 {
+            let mut checkpoint = column_prefix.clone();
+            checkpoint.extend(key.encode()?);
+            ctx.save_checkpoint(&checkpoint)?;
+        }
+    }
+
+    ctx.record_migrated(migrated);
+    Ok(migrated)
+}
+
+/// Checkpoints of [`migrate_column`] are prefixed by the column name
+fn checkpoint_prefix<S: KVStoreKeyValueSchema>() -> Vec<u8> {
+    let mut prefix = S::column_name().as_bytes().to_vec();
+    prefix.push(0);
+    prefix
+}
+
+/// Applies all steps needed to migrate the database from its stored version to `to_version`.
+///
+/// In dry run, the steps are executed without writing anything, so the report shows
+/// what the migration would do.
+pub fn migrate_database(
+    storage: &PersistentStorage,
+    migrations: &[Migration],
+    to_version: DbVersion,
+    dry_run: bool,
+    log: &Logger,
+) -> Result<MigrationReport, StorageError> {
+    let mut system_storage = SystemStorage::new(storage.main_db());
+    let from_version = match system_storage.get_db_version()? {
+        Some(version) => version,
+        // new database does not need any migration
+        None => return Ok(MigrationReport::default()),
+    };
+
+    let plan = migration_plan(migrations, from_version, to_version)?;
+    let mut report = MigrationReport::default();
+
+    for migration in plan {
+        info!(log, "Migrating database";
+                   "from_version" => migration.from_version,
+                   "to_version" => migration.to_version,
+                   "description" => migration.description,
+                   "dry_run" => dry_run);
+
+        let mut ctx = MigrationContext {
+            storage,
+            system_storage: system_storage.clone(),
+            to_version: migration.to_version,
+            dry_run,
+            records: 0,
+            log,
+        };
+        (migration.run)(&mut ctx)?;
+        report.records += ctx.records;
+        report
+            .steps
+            .push((migration.from_version, migration.to_version));
+
+        if !dry_run {
+            // make sure everything written by the step is persisted before the version is updated
+            storage.main_db().flush_checked();
+            storage.clog().flush_checked();
+            system_storage.set_db_version(migration.to_version)?;
+            system_storage.clear_migration_checkpoint(migration.to_version)?;
+        }
+    }
+
+    if !report.steps.is_empty() {
+        info!(log, "Database migration finished";
+                   "from_version" => from_version,
+                   "to_version" => to_version,
+                   "records" => report.records,
+                   "dry_run" => dry_run);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use slog::Level;
+
+    use crate::tests_common::{create_logger, TmpStorage};
+
+    use super::*;
+
+    fn step(from_version: DbVersion, to_version: DbVersion) -> Migration {
+        Migration {
+            from_version,
+            to_version,
+            description: "test",
+            run: no_schema_changes,
+        }
+    }
+
+    fn versions(plan: Vec<&Migration>) -> Vec<(DbVersion, DbVersion)> {
+        plan.iter()
+            .map(|migration| (migration.from_version, migration.to_version))
+            .collect()
+    }
+
+    #[test]
+    fn test_migration_plan() -> Result<(), anyhow::Error> {
+        let migrations = vec![step(19, 20), step(20, 22), step(22, 23)];
+
+        assert!(migration_plan(&migrations, 20, 20)?.is_empty());
+        assert_eq!(
+            vec![(19, 20), (20, 22), (22, 23)],
+            versions(migration_plan(&migrations, 19, 23)?)
+        );
+        assert_eq!(
+            vec![(20, 22)],
+            versions(migration_plan(&migrations, 20, 22)?)
+        );
+
+        assert!(matches!(
+            migration_plan(&migrations, 18, 20),
+            Err(StorageError::MissingMigration { from: 18, to: 20 })
+        ));
+        assert!(matches!(
+            migration_plan(&migrations, 20, 21),
+            Err(StorageError::MissingMigration { .. })
+        ));
+        assert!(matches!(
+            migration_plan(&migrations, 23, 22),
+            Err(StorageError::DatabaseVersionTooNew {
+                found: 23,
+                supported: 22
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_database() -> Result<(), anyhow::Error> {
+        let tmp_storage = TmpStorage::create_to_out_dir("__test_migrate_database")?;
+        let log = create_logger(Level::Debug);
+        let migrations = vec![step(19, 20), step(20, 21)];
+
+        let mut system_storage = SystemStorage::new(tmp_storage.storage().main_db());
+        system_storage.set_db_version(19)?;
+
+        // dry run does not change the version
+        let report = migrate_database(tmp_storage.storage(), &migrations, 21, true, &log)?;
+        assert_eq!(vec![(19, 20), (20, 21)], report.steps);
+        assert_eq!(Some(19), system_storage.get_db_version()?);
+
+        let report = migrate_database(tmp_storage.storage(), &migrations, 21, false, &log)?;
+        assert_eq!(vec![(19, 20), (20, 21)], report.steps);
+        assert_eq!(Some(21), system_storage.get_db_version()?);
+
+        // nothing to do anymore
+        let report = migrate_database(tmp_storage.storage(), &migrations, 21, false, &log)?;
+        assert!(report.steps.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_database_from_20() -> Result<(), anyhow::Error> {
+        let tmp_storage = TmpStorage::create_to_out_dir("__test_migrate_database_from_20")?;
+        let log = create_logger(Level::Debug);
+
+        // records of the version 20 node
+        let mut system_storage = SystemStorage::new(tmp_storage.storage().main_db());
+        system_storage.set_db_version(20)?;
+        let state_storage = ShellAutomatonStateStorage::new(tmp_storage.storage());
+        let action_storage = ShellAutomatonActionStorage::new(tmp_storage.storage());
+        for action_id in 0..5_u64 {
+            state_storage.put(&action_id, &vec![1, 2, 3])?;
+            action_storage.put(&action_id, &vec![4, 5, 6])?;
+        }
+        // checkpoint of an interrupted migration of the actions
+        system_storage.set_migration_checkpoint(21, &{
+            let mut checkpoint = checkpoint_prefix::<ShellAutomatonActionStorage>();
+            checkpoint.extend(1_u64.encode()?);
+            checkpoint
+        })?;
+
+        let report = migrate_database(tmp_storage.storage(), migrations(), 21, true, &log)?;
+        assert_eq!(vec![(20, 21)], report.steps);
+        assert_eq!(5 + 3, report.records);
+        assert_eq!(Some(20), system_storage.get_db_version()?);
+        assert!(state_storage.get::<Vec<u8>>(&0)?.is_some());
+
+        let report = migrate_database(tmp_storage.storage(), migrations(), 21, false, &log)?;
+        assert_eq!(vec![(20, 21)], report.steps);
+        assert_eq!(Some(21), system_storage.get_db_version()?);
+        assert_eq!(None, system_storage.get_migration_checkpoint(21)?);
+        for action_id in 0..5_u64 {
+            assert!(state_storage.get::<Vec<u8>>(&action_id)?.is_none());
+        }
+        // the actions up to the checkpoint were already migrated
+        for action_id in 0..=1_u64 {
+            assert!(action_storage.get::<Vec<u8>>(&action_id)?.is_some());
+        }
+        for action_id in 2..5_u64 {
+            assert!(action_storage.get::<Vec<u8>>(&action_id)?.is_none());
+        }
+        Ok(())
+    }
+}
@@ -47,6 +47,24 @@ impl OperationsStorage {
         self.put(&key, message)
     }
 
+    /// Removes the operations of all validation passes of the block
+    pub fn remove_operations(&self, block_hash: &BlockHash) -> Result<(), StorageError> {
+        let key = OperationKey {
+            block_hash: block_hash.clone(),
+            validation_pass: 0,
+        };
+
+        let mut keys = vec![];
+        for result in self.kv.find_by_prefix(&key, HashType::BlockHash.size())? {
+            let (key, _) = result?;
+            keys.push(OperationKey::decode(key.as_ref())?);
+        }
+        for key in keys {
+            self.kv.delete(&key)?;
+        }
+        Ok(())
+    }
+
     #[inline]
     fn put(
         &self,
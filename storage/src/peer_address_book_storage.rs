@@ -0,0 +1,189 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::cmp::Reverse;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use rocksdb::{Cache, ColumnFamilyDescriptor};
+use serde::{Deserialize, Serialize};
+
+use crate::database::tezedge_database::{KVStoreKeyValueSchema, TezedgeDatabaseWithIterator};
+use crate::persistent::database::{default_table_options, RocksDbKeyValueSchema};
+use crate::persistent::{BincodeEncoded, Decoder, KeyValueSchema};
+use crate::{IteratorMode, PersistentStorage, StorageError};
+
+pub type PeerAddressBookStorageKV =
+    dyn TezedgeDatabaseWithIterator<PeerAddressBookStorage> + Sync + Send;
+
+/// Ban expiry of the permanently banned peer.
+pub const PEER_BAN_PERMANENT: u64 = u64::MAX;
+
+/// Maximal number of entries of the address book, see [`PeerAddressBookStorage::evict`].
+pub const PEER_ADDRESS_BOOK_MAX_ENTRIES: usize = 1000;
+
+/// What we know about the peer, kept across restarts of the node.
+///
+/// All times are in nanoseconds since unix epoch, same as the time of the shell automaton.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct PeerAddressBookEntry {
+    /// Last time we handshaked with or disconnected from the peer.
+    pub last_seen: u64,
+    pub successful_handshakes: u32,
+    /// Score of the peer at `last_seen`.
+    pub score: i32,
+    /// Time until which the peer is banned, [`PEER_BAN_PERMANENT`] if the ban never expires.
+    pub ban_expiry: Option<u64>,
+}
+
+impl PeerAddressBookEntry {
+    #[inline]
+    pub fn is_banned(&self, time: u64) -> bool {
+        self.ban_expiry.map_or(false, |expiry| expiry > time)
+    }
+}
+
+impl BincodeEncoded for PeerAddressBookEntry {}
+
+impl BincodeEncoded for SocketAddr {}
+
+/// Change of the peer's entry, see [`PeerAddressBookStorage::update`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerAddressBookUpdate {
+    pub time: u64,
+    pub score: i32,
+    pub ban_expiry: Option<u64>,
+    /// Whether we have just successfully handshaked with the peer.
+    pub handshaked: bool,
+}
+
+/// Persisted address book of the peers, keyed by the peer's address.
+#[derive(Clone)]
+pub struct PeerAddressBookStorage {
+    kv: Arc<PeerAddressBookStorageKV>,
+}
+
+impl PeerAddressBookStorage {
+    pub fn new(persistent_storage: &PersistentStorage) -> Self {
+        Self {
+            kv: persistent_storage.main_db(),
+        }
+    }
+
+    #[inline]
+    pub fn put(
+        &self,
+        address: &SocketAddr,
+        entry: &PeerAddressBookEntry,
+    ) -> Result<(), StorageError> {
+        self.kv.put(address, entry).map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn get(&self, address: &SocketAddr) -> Result<Option<PeerAddressBookEntry>, StorageError> {
+        self.kv.get(address).map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn delete(&self, address: &SocketAddr) -> Result<(), StorageError> {
+        self.kv.delete(address).map_err(StorageError::from)
+    }
+
+    /// Applies the update to the peer's entry.
+    ///
+    /// New entry is created only for the handshaked or banned peer, so peers
+    /// we never managed to talk to don't fill up the address book. When it's full,
+    /// the worst-known peers are evicted.
+    pub fn update(
+        &self,
+        address: &SocketAddr,
+        update: &PeerAddressBookUpdate,
+    ) -> Result<(), StorageError> {
+        let mut entry = match self.get(address)? {
+            Some(entry) => entry,
+            None if update.handshaked || update.ban_expiry.is_some() => {
+                self.evict(PEER_ADDRESS_BOOK_MAX_ENTRIES - 1, update.time)?;
+                PeerAddressBookEntry::default()
+            }
+            None => return Ok(()),
+        };
+
+        entry.last_seen = entry.last_seen.max(update.time);
+        entry.score = update.score;
+        entry.ban_expiry = update.ban_expiry;
+        if update.handshaked {
+            entry.successful_handshakes = entry.successful_handshakes.saturating_add(1);
+        }
+        self.put(address, &entry)
+    }
+
+    /// Removes the worst-known peers until at most `max_entries` are left, returns the number
+    /// of removed peers.
+    ///
+    /// Peers banned at `time` are kept, so that the bans survive restarts of the node.
+    pub fn evict(&self, max_entries: usize, time: u64) -> Result<usize, StorageError> {
+        let entries = self.load()?;
+        let excess = entries.len().saturating_sub(max_entries);
+        if excess == 0 {
+            return Ok(0);
+        }
+
+        let evicted = entries
+            .iter()
+            .rev()
+            .filter(|(_, entry)| !entry.is_banned(time))
+            .take(excess)
+            .map(|(address, _)| address)
+            .collect::<Vec<_>>();
+        for address in &evicted {
+            self.delete(address)?;
+        }
+        Ok(evicted.len())
+    }
+
+    /// Returns all known peers, best-known first.
+    ///
+    /// Peers are ordered by their score, then by the number of successful
+    /// handshakes and then by the time we have seen them last.
+    pub fn load(&self) -> Result<Vec<(SocketAddr, PeerAddressBookEntry)>, StorageError> {
+        let mut entries = self
+            .kv
+            .find(IteratorMode::Start)?
+            .map(|result| {
+                let (key, value) = result?;
+                let address = <SocketAddr as Decoder>::decode(&key)?;
+                let entry = <PeerAddressBookEntry as Decoder>::decode(&value)?;
+                Ok((address, entry))
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+
+        entries.sort_by_key(|(_, entry)| {
+            Reverse((entry.score, entry.successful_handshakes, entry.last_seen))
+        });
+        Ok(entries)
+    }
+}
+
+impl KeyValueSchema for PeerAddressBookStorage {
+    type Key = SocketAddr;
+    type Value = PeerAddressBookEntry;
+}
+
+impl RocksDbKeyValueSchema for PeerAddressBookStorage {
+    fn descriptor(cache: &Cache) -> ColumnFamilyDescriptor {
+        let cf_opts = default_table_options(cache);
+        ColumnFamilyDescriptor::new(Self::name(), cf_opts)
+    }
+
+    #[inline]
+    fn name() -> &'static str {
+        "peer_address_book_storage"
+    }
+}
+
+impl KVStoreKeyValueSchema for PeerAddressBookStorage {
+    fn column_name() -> &'static str {
+        Self::name()
+    }
+}
@@ -98,9 +98,13 @@ fn edgekv_db_cols() -> Vec<&'static str> {
         crate::CycleMetaStorage::column_name(),
         crate::CycleErasStorage::column_name(),
         crate::ConstantsStorage::column_name(),
+        crate::PeerAddressBookStorage::column_name(),
+        crate::ProtocolStorage::column_name(),
         crate::ShellAutomatonStateStorage::column_name(),
         crate::ShellAutomatonActionStorage::column_name(),
         crate::ShellAutomatonActionMetaStorage::column_name(),
+        crate::AccountOperationsStorage::column_name(),
+        crate::account_operations_storage::AccountOperationsByBlockIndex::column_name(),
     ]
 }
 
@@ -124,7 +128,7 @@ pub fn open_main_db<C: RocksDbColumnFactory>(
             }
         }
         TezedgeDatabaseBackendConfiguration::EdgeKV => TezedgeDatabaseBackendOptions::EdgeKV(
-            EdgeKVBackend::new(config.db_path.as_path(), edgekv_db_cols())?,
+            EdgeKVBackend::new(config.db_path.as_path(), edgekv_db_cols(), log.clone())?,
         ),
     };
     Ok(TezedgeDatabase::new(backend, log))
@@ -81,6 +81,18 @@ impl PredecessorStorage {
         Ok(())
     }
 
+    pub fn remove_predecessors(
+        &self,
+        block_hash: &BlockHash,
+        stored_predecessors_size: u32,
+    ) -> Result<(), StorageError> {
+        for exponent_slot in 0..stored_predecessors_size {
+            self.kv
+                .delete(&PredecessorKey::new(block_hash.clone(), exponent_slot))?;
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn put(
         &self,
@@ -0,0 +1,217 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rocksdb::{Cache, ColumnFamilyDescriptor};
+use serde::Deserialize;
+use slog::{warn, Logger};
+use thiserror::Error;
+
+use crypto::hash::ProtocolHash;
+use tezos_messages::p2p::binary_message::MessageHash;
+use tezos_messages::p2p::encoding::protocol::{Component, Protocol};
+
+use crate::database::tezedge_database::{KVStoreKeyValueSchema, TezedgeDatabaseWithIterator};
+use crate::persistent::database::{default_table_options, RocksDbKeyValueSchema};
+use crate::persistent::{BincodeEncoded, KeyValueSchema};
+use crate::{PersistentStorage, StorageError};
+
+pub type ProtocolStorageKV = dyn TezedgeDatabaseWithIterator<ProtocolStorage> + Sync + Send;
+
+/// Sources of the protocols, so we can serve them to peers which don't know them yet.
+#[derive(Clone)]
+pub struct ProtocolStorage {
+    kv: Arc<ProtocolStorageKV>,
+}
+
+impl ProtocolStorage {
+    pub fn new(persistent_storage: &PersistentStorage) -> Self {
+        Self {
+            kv: persistent_storage.main_db(),
+        }
+    }
+
+    #[inline]
+    pub fn put(
+        &self,
+        protocol_hash: &ProtocolHash,
+        protocol: &Protocol,
+    ) -> Result<(), StorageError> {
+        self.kv
+            .put(protocol_hash, protocol)
+            .map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn get(&self, protocol_hash: &ProtocolHash) -> Result<Option<Protocol>, StorageError> {
+        self.kv.get(protocol_hash).map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn contains(&self, protocol_hash: &ProtocolHash) -> Result<bool, StorageError> {
+        self.kv.contains(protocol_hash).map_err(StorageError::from)
+    }
+
+    /// Stores the protocols whose sources are in the subdirectories of `sources_dir`,
+    /// so they can be served to peers before any peer sent them to us.
+    ///
+    /// A subdirectory holds a protocol if it (or its `lib_protocol` directory, like the
+    /// `src/proto_*` directories of Octez) contains a [`PROTOCOL_MANIFEST_FILE`].
+    /// Protocols which cannot be read, or whose hash doesn't match their manifest
+    /// (like `proto_alpha`), are skipped.
+    ///
+    /// Returns the hashes of the stored protocols.
+    pub fn seed_from_sources(
+        &self,
+        sources_dir: &Path,
+        log: &Logger,
+    ) -> Result<Vec<ProtocolHash>, ProtocolSourcesError> {
+        let mut protocol_dirs = Vec::new();
+        for entry in fs::read_dir(sources_dir)? {
+            let path = entry?.path();
+            let lib_protocol = path.join("lib_protocol");
+            if path.join(PROTOCOL_MANIFEST_FILE).is_file() {
+                protocol_dirs.push(path);
+            } else if lib_protocol.join(PROTOCOL_MANIFEST_FILE).is_file() {
+                protocol_dirs.push(lib_protocol);
+            }
+        }
+        protocol_dirs.sort();
+
+        let mut stored = Vec::with_capacity(protocol_dirs.len());
+        for protocol_dir in protocol_dirs {
+            match read_protocol_sources(&protocol_dir) {
+                Ok((protocol_hash, protocol)) => {
+                    if !self.contains(&protocol_hash)? {
+                        self.put(&protocol_hash, &protocol)?;
+                    }
+                    stored.push(protocol_hash);
+                }
+                Err(e) => {
+                    warn!(log, "Skipping protocol sources";
+                                "path" => protocol_dir.display().to_string(),
+                                "reason" => format!("{}", e));
+                }
+            }
+        }
+        Ok(stored)
+    }
+}
+
+/// Manifest of the sources of a protocol, in the Octez layout
+pub const PROTOCOL_MANIFEST_FILE: &str = "TEZOS_PROTOCOL";
+
+#[derive(Debug, Error)]
+pub enum ProtocolSourcesError {
+    #[error("I/O error: {error}")]
+    IOError { error: std::io::Error },
+    #[error("Invalid protocol manifest {path:?}: {reason}")]
+    InvalidManifest { path: PathBuf, reason: String },
+    #[error("Protocol hash {computed} doesn't match the manifest hash {expected}")]
+    HashMismatch { expected: String, computed: String },
+    #[error("Storage error: {error}")]
+    StorageError { error: StorageError },
+}
+
+impl From<std::io::Error> for ProtocolSourcesError {
+    fn from(error: std::io::Error) -> Self {
+        ProtocolSourcesError::IOError { error }
+    }
+}
+
+impl From<StorageError> for ProtocolSourcesError {
+    fn from(error: StorageError) -> Self {
+        ProtocolSourcesError::StorageError { error }
+    }
+}
+
+#[derive(Deserialize)]
+struct ProtocolManifest {
+    hash: String,
+    #[serde(default)]
+    expected_env_version: i16,
+    modules: Vec<String>,
+}
+
+/// Reads the sources of the protocol from `protocol_dir`, which contains
+/// a [`PROTOCOL_MANIFEST_FILE`] and the `.ml`/`.mli` files of its modules.
+///
+/// Like Octez, the file of the module `Foo` is `foo.ml` and its interface,
+/// if any, is `foo.mli`.
+pub fn read_protocol_sources(
+    protocol_dir: &Path,
+) -> Result<(ProtocolHash, Protocol), ProtocolSourcesError> {
+    let manifest_path = protocol_dir.join(PROTOCOL_MANIFEST_FILE);
+    let manifest: ProtocolManifest =
+        serde_json::from_slice(&fs::read(&manifest_path)?).map_err(|e| {
+            ProtocolSourcesError::InvalidManifest {
+                path: manifest_path.clone(),
+                reason: format!("{}", e),
+            }
+        })?;
+
+    let mut components = Vec::with_capacity(manifest.modules.len());
+    for module in manifest.modules {
+        let mut chars = module.chars();
+        let file_name = match chars.next() {
+            Some(first) => format!("{}{}", first.to_ascii_lowercase(), chars.as_str()),
+            None => {
+                return Err(ProtocolSourcesError::InvalidManifest {
+                    path: manifest_path,
+                    reason: "empty module name".to_string(),
+                })
+            }
+        };
+
+        let interface_path = protocol_dir.join(format!("{}.mli", file_name));
+        let interface = if interface_path.is_file() {
+            Some(fs::read_to_string(interface_path)?)
+        } else {
+            None
+        };
+        let implementation = fs::read_to_string(protocol_dir.join(format!("{}.ml", file_name)))?;
+
+        components.push(Component::new(module, interface, implementation));
+    }
+
+    let protocol = Protocol::new(manifest.expected_env_version, components);
+    let protocol_hash = protocol
+        .message_typed_hash::<ProtocolHash>()
+        .map_err(StorageError::from)?;
+    if protocol_hash.to_base58_check() != manifest.hash {
+        return Err(ProtocolSourcesError::HashMismatch {
+            expected: manifest.hash,
+            computed: protocol_hash.to_base58_check(),
+        });
+    }
+
+    Ok((protocol_hash, protocol))
+}
+
+impl BincodeEncoded for Protocol {}
+
+impl KeyValueSchema for ProtocolStorage {
+    type Key = ProtocolHash;
+    type Value = Protocol;
+}
+
+impl RocksDbKeyValueSchema for ProtocolStorage {
+    fn descriptor(cache: &Cache) -> ColumnFamilyDescriptor {
+        let cf_opts = default_table_options(cache);
+        ColumnFamilyDescriptor::new(Self::name(), cf_opts)
+    }
+
+    #[inline]
+    fn name() -> &'static str {
+        "protocol_storage"
+    }
+}
+
+impl KVStoreKeyValueSchema for ProtocolStorage {
+    fn column_name() -> &'static str {
+        Self::name()
+    }
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use rocksdb::{Cache, ColumnFamilyDescriptor};
 use serde::{Deserialize, Serialize};
 
-use crypto::hash::ChainId;
+use crypto::hash::{BlockHash, ChainId};
 
 use crate::database::tezedge_database::{KVStoreKeyValueSchema, TezedgeDatabaseWithIterator};
 use crate::persistent::database::{default_table_options, RocksDbKeyValueSchema};
@@ -29,6 +29,10 @@ impl SystemStorage {
     const CHAIN_ID: &'static str = "chain_id";
     const DB_VERSION: &'static str = "db_version";
     const CHAIN_NAME: &'static str = "chain_name";
+    const HISTORY_MODE: &'static str = "history_mode";
+    const MIGRATION_CHECKPOINT_PREFIX: &'static str = "migration_checkpoint";
+    const MEMPOOL_FILTER: &'static str = "mempool_filter";
+    const ACCOUNT_OPERATIONS_INDEXED: &'static str = "account_operations_indexed";
 
     pub fn new(kv: Arc<SystemStorageKv>) -> Self {
         SystemStorage { kv }
@@ -105,6 +109,115 @@ impl SystemStorage {
             )
             .map_err(StorageError::from)
     }
+
+    #[inline]
+    pub fn get_history_mode(&self) -> Result<Option<String>, StorageError> {
+        self.kv
+            .get(&Self::HISTORY_MODE.to_string())
+            .map(|result| match result {
+                Some(SystemValue::String(value)) => Some(value),
+                _ => None,
+            })
+            .map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn set_history_mode(&mut self, history_mode: &str) -> Result<(), StorageError> {
+        self.kv
+            .put(
+                &Self::HISTORY_MODE.to_string(),
+                &SystemValue::String(history_mode.to_string()),
+            )
+            .map_err(StorageError::from)
+    }
+
+    /// Returns the json encoded mempool filter set by the rpc.
+    #[inline]
+    pub fn get_mempool_filter(&self) -> Result<Option<String>, StorageError> {
+        self.kv
+            .get(&Self::MEMPOOL_FILTER.to_string())
+            .map(|result| match result {
+                Some(SystemValue::String(value)) => Some(value),
+                _ => None,
+            })
+            .map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn set_mempool_filter(&mut self, mempool_filter: &str) -> Result<(), StorageError> {
+        self.kv
+            .put(
+                &Self::MEMPOOL_FILTER.to_string(),
+                &SystemValue::String(mempool_filter.to_string()),
+            )
+            .map_err(StorageError::from)
+    }
+
+    /// Returns the last block indexed by the [`crate::account_operations_storage::AccountOperationsIndexer`].
+    #[inline]
+    pub fn get_account_operations_indexed(&self) -> Result<Option<BlockHash>, StorageError> {
+        match self.kv.get(&Self::ACCOUNT_OPERATIONS_INDEXED.to_string())? {
+            Some(SystemValue::Hash(value)) => Ok(Some(BlockHash::try_from(value)?)),
+            _ => Ok(None),
+        }
+    }
+
+    #[inline]
+    pub fn set_account_operations_indexed(
+        &mut self,
+        block_hash: &BlockHash,
+    ) -> Result<(), StorageError> {
+        self.kv
+            .put(
+                &Self::ACCOUNT_OPERATIONS_INDEXED.to_string(),
+                &SystemValue::Hash(block_hash.as_ref().to_vec()),
+            )
+            .map_err(StorageError::from)
+    }
+
+    #[inline]
+    fn migration_checkpoint_key(to_version: DbVersion) -> String {
+        format!("{}_{}", Self::MIGRATION_CHECKPOINT_PREFIX, to_version)
+    }
+
+    /// Returns checkpoint of the unfinished migration step to `to_version`
+    #[inline]
+    pub fn get_migration_checkpoint(
+        &self,
+        to_version: DbVersion,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        self.kv
+            .get(&Self::migration_checkpoint_key(to_version))
+            .map(|result| match result {
+                Some(SystemValue::Hash(value)) => Some(value),
+                _ => None,
+            })
+            .map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn set_migration_checkpoint(
+        &mut self,
+        to_version: DbVersion,
+        checkpoint: &[u8],
+    ) -> Result<(), StorageError> {
+        self.kv
+            .put(
+                &Self::migration_checkpoint_key(to_version),
+                &SystemValue::Hash(checkpoint.to_vec()),
+            )
+            .map_err(StorageError::from)
+    }
+
+    #[inline]
+    pub fn clear_migration_checkpoint(
+        &mut self,
+        to_version: DbVersion,
+    ) -> Result<(), StorageError> {
+        self.kv
+            .delete(&Self::migration_checkpoint_key(to_version))
+            .map_err(StorageError::from)
+    }
 }
 
 impl KeyValueSchema for SystemStorage {
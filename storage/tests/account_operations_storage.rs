@@ -0,0 +1,274 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::Error;
+use crypto::hash::{BlockHash, ChainId, OperationHash, ProtocolHash};
+use slog::Logger;
+
+use storage::account_operations_storage::{AccountAddress, AccountRole};
+use storage::block_meta_storage::Meta;
+use storage::tests_common::TmpStorage;
+use storage::{
+    AccountOperationsIndexer, AccountOperationsStorage, BlockAdditionalData, BlockHeaderWithHash,
+    BlockMetaStorage, OperationsStorage, PersistentStorage,
+};
+use tezos_messages::p2p::binary_message::{BinaryRead, MessageHash};
+use tezos_messages::p2p::encoding::fitness::Fitness;
+use tezos_messages::p2p::encoding::prelude::*;
+use tezos_messages::protocol::{proto_001, proto_011};
+
+const TRANSACTION: &str = "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a86c0002298c03ed7d454a101eb7022bc95f7e5f41ac7821dc05edecc004adcacdb7d4019703000002298c03ed7d454a101eb7022bc95f7e5f41ac780066804fe735e06e97e26da8236b6341b91c625d5e82b3524ec0a88cc982365e70f8a5b9bc65df2ea6d21ee244cc3a96fb33031c394c78b1179ff1b8a44237740c";
+const ORIGINATION: &str = "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a86d0002298c03ed7d454a101eb7022bc95f7e5f41ac7821dc05edecc004adcacdb7d401af9105ff0002298c03ed7d454a101eb7022bc95f7e5f41ac7800000020020000001b050003680501056303680502020000000a03160346053d036d03420000000e020000000901000000047465737466804fe735e06e97e26da8236b6341b91c625d5e82b3524ec0a88cc982365e70f8a5b9bc65df2ea6d21ee244cc3a96fb33031c394c78b1179ff1b8a44237740c";
+const DELEGATION: &str = "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a86e0002298c03ed7d454a101eb7022bc95f7e5f41ac7821dc05edecc004adcacdb7d401ff0002298c03ed7d454a101eb7022bc95f7e5f41ac7866804fe735e06e97e26da8236b6341b91c625d5e82b3524ec0a88cc982365e70f8a5b9bc65df2ea6d21ee244cc3a96fb33031c394c78b1179ff1b8a44237740c";
+
+/// Source, destination and delegate of all the test operations.
+const ACCOUNT: &str = "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx";
+
+#[test]
+fn account_operations_storage_index_and_page() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__account_operations_storage_index_and_page")?;
+    let storage = AccountOperationsStorage::new(tmp_storage.storage());
+    let protocol_hash = ProtocolHash::from_base58_check(proto_011::PROTOCOL_HASH)?;
+
+    let block_hash_1 = BlockHash::try_from("BKyQ9EofHrgaZKENioHyP4FZNsTmiSEcVmcghgzCC9cGhE7oCET")?;
+    let block_hash_2 = BlockHash::try_from("BLaf78njreWdt2WigJjM9e3ecEdVKm5ehahUfYBKvcWvZ8vfTcJ")?;
+    storage.index_operations(
+        &block_hash_1,
+        10,
+        &protocol_hash,
+        &[make_operations(&block_hash_1, &[TRANSACTION, ORIGINATION])?],
+    )?;
+    storage.index_operations(
+        &block_hash_2,
+        11,
+        &protocol_hash,
+        &[make_operations(&block_hash_2, &[DELEGATION])?],
+    )?;
+
+    let account = AccountAddress::from_b58_check(ACCOUNT).unwrap();
+    let (operations, next_id) = storage.get_operations(&account, None, None, 100)?;
+    assert_eq!(next_id, None);
+    let summary = operations
+        .iter()
+        .map(|operation| (operation.level, operation.role))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (11, AccountRole::Delegate),
+            (11, AccountRole::Source),
+            (10, AccountRole::Delegate),
+            (10, AccountRole::Source),
+            (10, AccountRole::Destination),
+            (10, AccountRole::Source),
+        ]
+    );
+    assert_eq!(operations[0].block_hash, block_hash_2);
+    assert_eq!(operations[0].operation_hash, operation_hash(DELEGATION)?);
+    assert_eq!(operations[5].block_hash, block_hash_1);
+    assert_eq!(operations[5].operation_hash, operation_hash(TRANSACTION)?);
+
+    // paging
+    let first_page = storage.get_operations(&account, None, None, 4)?;
+    assert_eq!(
+        first_page,
+        (operations[..4].to_vec(), Some(operations[4].id))
+    );
+    let second_page = storage.get_operations(&account, None, Some(operations[4].id), 4)?;
+    assert_eq!(second_page, (operations[4..].to_vec(), None));
+
+    // contract originated by the origination
+    let (originated, _) =
+        storage.get_operations(&originated_contract(ORIGINATION)?, None, None, 100)?;
+    assert_eq!(originated.len(), 1);
+    assert_eq!(originated[0].role, AccountRole::Originated);
+    assert_eq!(originated[0].operation_hash, operation_hash(ORIGINATION)?);
+
+    // filtered by the role
+    let (delegated, next_id) =
+        storage.get_operations(&account, Some(AccountRole::Delegate), None, 1)?;
+    assert_eq!(delegated, operations[..1]);
+    assert_eq!(next_id, Some(operations[1].id));
+    let (delegated, _) = storage.get_operations(
+        &account,
+        Some(AccountRole::Delegate),
+        Some(delegated[0].id - 1),
+        1,
+    )?;
+    assert_eq!(delegated, operations[2..3]);
+
+    // removed with the block
+    assert_eq!(storage.remove_block(&block_hash_2)?, 2);
+    assert_eq!(
+        storage.get_operations(&account, None, None, 100)?.0,
+        operations[2..]
+    );
+    assert_eq!(storage.remove_block(&block_hash_2)?, 0);
+
+    // unknown account
+    let unknown = AccountAddress::from_b58_check("tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb").unwrap();
+    assert!(storage
+        .get_operations(&unknown, None, None, 100)?
+        .0
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn account_operations_storage_skips_unsupported_protocol() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__account_operations_storage_skips_unsupported")?;
+    let storage = AccountOperationsStorage::new(tmp_storage.storage());
+    let protocol_hash = ProtocolHash::from_base58_check(proto_001::PROTOCOL_HASH)?;
+
+    let block_hash = BlockHash::try_from("BKyQ9EofHrgaZKENioHyP4FZNsTmiSEcVmcghgzCC9cGhE7oCET")?;
+    storage.index_operations(
+        &block_hash,
+        10,
+        &protocol_hash,
+        &[make_operations(&block_hash, &[TRANSACTION])?],
+    )?;
+
+    let account = AccountAddress::from_b58_check(ACCOUNT).unwrap();
+    assert!(storage
+        .get_operations(&account, None, None, 100)?
+        .0
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn account_operations_indexer_backfill_and_reorg() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__account_operations_indexer_backfill_and_reorg")?;
+    let storage = tmp_storage.storage();
+    let chain_id: ChainId = "NetXgtSLGNJvNye".try_into()?;
+    let genesis: BlockHash = "BLockGenesisGenesisGenesisGenesisGenesisb83baZgbyZe".try_into()?;
+    BlockMetaStorage::new(storage).put(&genesis, &Meta::genesis_meta(&genesis, &chain_id, true))?;
+
+    let block_1 = store_block(storage, &chain_id, &genesis, 1, 0, &[TRANSACTION])?;
+    let block_2 = store_block(storage, &chain_id, &block_1, 2, 0, &[ORIGINATION])?;
+    let block_3 = store_block(storage, &chain_id, &block_2, 3, 0, &[])?;
+    let fork_2 = store_block(storage, &chain_id, &block_1, 2, 1, &[DELEGATION])?;
+
+    let account = AccountAddress::from_b58_check(ACCOUNT).unwrap();
+    let account_operations = |storage: &AccountOperationsStorage| -> Result<_, Error> {
+        Ok(storage
+            .get_operations(&account, None, None, 100)?
+            .0
+            .into_iter()
+            .map(|operation| (operation.level, operation.block_hash, operation.role))
+            .collect::<Vec<_>>())
+    };
+    let account_operations_storage = AccountOperationsStorage::new(storage);
+
+    // the blocks applied before are indexed too
+    let mut indexer = AccountOperationsIndexer::new(storage);
+    assert_eq!(indexer.block_applied(&chain_id, &fork_2, 2)?, Some(2));
+    assert_eq!(
+        account_operations(&account_operations_storage)?,
+        vec![
+            (2, fork_2.clone(), AccountRole::Delegate),
+            (2, fork_2.clone(), AccountRole::Source),
+            (1, block_1.clone(), AccountRole::Destination),
+            (1, block_1.clone(), AccountRole::Source),
+        ]
+    );
+
+    // the fork is reorganized away
+    let mut indexer = AccountOperationsIndexer::new(storage);
+    assert_eq!(indexer.block_applied(&chain_id, &block_3, 3)?, Some(3));
+    let expected = vec![
+        (2, block_2.clone(), AccountRole::Delegate),
+        (2, block_2.clone(), AccountRole::Source),
+        (1, block_1.clone(), AccountRole::Destination),
+        (1, block_1.clone(), AccountRole::Source),
+    ];
+    assert_eq!(account_operations(&account_operations_storage)?, expected);
+    assert!(account_operations_storage
+        .get_operations(&account, None, None, 100)?
+        .0
+        .iter()
+        .all(|operation| operation.operation_hash != operation_hash(DELEGATION).unwrap()));
+
+    // the indexed block is kept
+    let mut indexer = AccountOperationsIndexer::new(storage);
+    assert_eq!(indexer.block_applied(&chain_id, &block_3, 3)?, Some(3));
+    assert_eq!(account_operations(&account_operations_storage)?, expected);
+
+    Ok(())
+}
+
+/// Stores the applied block with the `operations` of the protocol 011.
+fn store_block(
+    storage: &PersistentStorage,
+    chain_id: &ChainId,
+    predecessor: &BlockHash,
+    level: i32,
+    branch: u8,
+    operations: &[&str],
+) -> Result<BlockHash, Error> {
+    let block = BlockHeaderWithHash::new(
+        BlockHeaderBuilder::default()
+            .level(level)
+            .proto(1)
+            .predecessor(predecessor.clone())
+            .timestamp((level as i64).into())
+            .validation_pass(4)
+            .operations_hash("LLoaGLRPRx3Zf8kB4ACtgku8F4feeBiskeb41J1ciwfcXB3KzHKXc".try_into()?)
+            .fitness(Fitness::from(vec![vec![0, branch]]))
+            .context("CoVmAcMV64uAQo8XvfLr9VDuz7HVZLT4cgK1w1qYmTjQNbGwQwDd".try_into()?)
+            .protocol_data(vec![branch].into())
+            .build()
+            .unwrap(),
+    )?;
+    let block_meta_storage = BlockMetaStorage::new(storage);
+    let log = Logger::root(slog::Discard, slog::o!());
+    let meta = block_meta_storage.put_block_header(&block, chain_id, &log)?;
+    block_meta_storage.store_predecessors(&block.hash, &meta)?;
+    let protocol_hash = ProtocolHash::from_base58_check(proto_011::PROTOCOL_HASH)?;
+    block_meta_storage.put_block_additional_data(
+        &block.hash,
+        &BlockAdditionalData::new(
+            120,
+            0,
+            protocol_hash.clone(),
+            protocol_hash,
+            None,
+            None,
+            None,
+        ),
+    )?;
+    OperationsStorage::new(storage).put_operations(&make_operations(&block.hash, operations)?)?;
+    Ok(block.hash)
+}
+
+fn make_operations(
+    block_hash: &BlockHash,
+    operations: &[&str],
+) -> Result<OperationsForBlocksMessage, Error> {
+    let operations = operations
+        .iter()
+        .map(|operation| Ok(Operation::from_bytes(hex::decode(operation)?)?))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(OperationsForBlocksMessage::new(
+        OperationsForBlock::new(block_hash.clone(), 3),
+        Path::op(),
+        operations,
+    ))
+}
+
+fn operation_hash(operation: &str) -> Result<OperationHash, Error> {
+    Ok(Operation::from_bytes(hex::decode(operation)?)?.message_typed_hash()?)
+}
+
+fn originated_contract(operation: &str) -> Result<AccountAddress, Error> {
+    let mut nonce = operation_hash(operation)?.as_ref().to_vec();
+    nonce.extend(&0_i32.to_be_bytes());
+    let mut address = vec![1];
+    address.extend(crypto::blake2b::digest_160(&nonce)?);
+    address.push(0);
+    Ok(AccountAddress::from_bytes(address)?)
+}
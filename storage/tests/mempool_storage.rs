@@ -26,8 +26,15 @@ fn mempool_storage_read_write() -> Result<(), Error> {
     assert_eq!(block_header_res, operation);
 
     assert!(storage.find(&operation_hash)?.is_some());
+    let loaded = storage.load()?;
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].0, MempoolOperationType::KnownValid);
+    assert_eq!(loaded[0].1, operation_hash);
+    assert_eq!(loaded[0].2, operation);
+
     storage.delete(&operation_hash)?;
     assert!(storage.find(&operation_hash)?.is_none());
+    assert!(storage.load()?.is_empty());
 
     Ok(())
 }
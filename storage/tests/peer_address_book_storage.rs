@@ -0,0 +1,139 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::net::SocketAddr;
+
+use anyhow::Error;
+
+use storage::peer_address_book_storage::{PeerAddressBookEntry, PeerAddressBookUpdate};
+use storage::tests_common::TmpStorage;
+use storage::PeerAddressBookStorage;
+
+fn update(
+    time: u64,
+    score: i32,
+    ban_expiry: Option<u64>,
+    handshaked: bool,
+) -> PeerAddressBookUpdate {
+    PeerAddressBookUpdate {
+        time,
+        score,
+        ban_expiry,
+        handshaked,
+    }
+}
+
+#[test]
+fn peer_address_book_storage_update() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__peer_address_book_storage_update")?;
+    let storage = PeerAddressBookStorage::new(tmp_storage.storage());
+    let address: SocketAddr = "1.2.3.4:9732".parse()?;
+
+    // unknown peer without handshake is not stored
+    storage.update(&address, &update(1, -10, None, false))?;
+    assert!(storage.get(&address)?.is_none());
+
+    storage.update(&address, &update(2, 5, None, true))?;
+    storage.update(&address, &update(3, 7, None, true))?;
+    storage.update(&address, &update(4, 6, None, false))?;
+    assert_eq!(
+        storage.get(&address)?,
+        Some(PeerAddressBookEntry {
+            last_seen: 4,
+            successful_handshakes: 2,
+            score: 6,
+            ban_expiry: None,
+        })
+    );
+
+    // banned peer is stored even without handshake
+    let banned: SocketAddr = "5.6.7.8:9732".parse()?;
+    storage.update(&banned, &update(5, -500, Some(100), false))?;
+    let entry = storage.get(&banned)?.unwrap();
+    assert!(entry.is_banned(99));
+    assert!(!entry.is_banned(100));
+
+    storage.delete(&banned)?;
+    assert!(storage.get(&banned)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn peer_address_book_storage_load_best_first() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__peer_address_book_storage_load_best_first")?;
+    let storage = PeerAddressBookStorage::new(tmp_storage.storage());
+
+    let entry = |score, successful_handshakes, last_seen| PeerAddressBookEntry {
+        last_seen,
+        successful_handshakes,
+        score,
+        ban_expiry: None,
+    };
+    let peers: Vec<(SocketAddr, PeerAddressBookEntry)> = vec![
+        ("1.1.1.1:9732".parse()?, entry(-20, 10, 10)),
+        ("2.2.2.2:9732".parse()?, entry(10, 1, 5)),
+        ("3.3.3.3:9732".parse()?, entry(10, 3, 1)),
+        ("4.4.4.4:9732".parse()?, entry(10, 3, 2)),
+        ("[::1]:9732".parse()?, entry(0, 0, 0)),
+    ];
+    for (address, entry) in &peers {
+        storage.put(address, entry)?;
+    }
+
+    let loaded = storage
+        .load()?
+        .into_iter()
+        .map(|(address, _)| address.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        loaded,
+        vec![
+            "4.4.4.4:9732",
+            "3.3.3.3:9732",
+            "2.2.2.2:9732",
+            "[::1]:9732",
+            "1.1.1.1:9732"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn peer_address_book_storage_evict_worst() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__peer_address_book_storage_evict_worst")?;
+    let storage = PeerAddressBookStorage::new(tmp_storage.storage());
+
+    let entry = |score, ban_expiry| PeerAddressBookEntry {
+        last_seen: 1,
+        successful_handshakes: 1,
+        score,
+        ban_expiry,
+    };
+    let peers: Vec<(SocketAddr, PeerAddressBookEntry)> = vec![
+        ("1.1.1.1:9732".parse()?, entry(-500, Some(100))),
+        ("2.2.2.2:9732".parse()?, entry(-10, None)),
+        ("3.3.3.3:9732".parse()?, entry(5, None)),
+        ("4.4.4.4:9732".parse()?, entry(10, None)),
+    ];
+    for (address, entry) in &peers {
+        storage.put(address, entry)?;
+    }
+
+    assert_eq!(0, storage.evict(4, 50)?);
+
+    // the banned peer is kept while its ban lasts
+    assert_eq!(2, storage.evict(2, 50)?);
+    let loaded = storage
+        .load()?
+        .into_iter()
+        .map(|(address, _)| address.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(loaded, vec!["4.4.4.4:9732", "1.1.1.1:9732"]);
+
+    assert_eq!(1, storage.evict(1, 100)?);
+    assert!(storage.get(&"1.1.1.1:9732".parse()?)?.is_none());
+
+    Ok(())
+}
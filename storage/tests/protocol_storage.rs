@@ -0,0 +1,100 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Error;
+use slog::Level;
+
+use crypto::hash::{HashTrait, HashType, ProtocolHash};
+use storage::protocol_storage::PROTOCOL_MANIFEST_FILE;
+use storage::tests_common::{create_logger, TmpStorage};
+use storage::ProtocolStorage;
+use tezos_messages::p2p::binary_message::MessageHash;
+use tezos_messages::p2p::encoding::protocol::{Component, Protocol};
+
+#[test]
+fn protocol_storage_put_get() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__protocol_storage_put_get")?;
+    let storage = ProtocolStorage::new(tmp_storage.storage());
+
+    let protocol = Protocol::new(
+        1,
+        vec![Component::new(
+            "Main".to_owned(),
+            Some("module type S = sig end".to_owned()),
+            "let x = 1".to_owned(),
+        )],
+    );
+    let protocol_hash = protocol.message_typed_hash::<ProtocolHash>()?;
+
+    assert!(!storage.contains(&protocol_hash)?);
+    assert!(storage.get(&protocol_hash)?.is_none());
+
+    storage.put(&protocol_hash, &protocol)?;
+    assert!(storage.contains(&protocol_hash)?);
+    assert_eq!(storage.get(&protocol_hash)?, Some(protocol));
+
+    Ok(())
+}
+
+fn write_protocol_sources(dir: &Path, hash: &str) -> Result<(), Error> {
+    fs::create_dir_all(dir)?;
+    fs::write(
+        dir.join(PROTOCOL_MANIFEST_FILE),
+        format!(
+            r#"{{ "expected_env_version": 3, "hash": "{}", "modules": ["Misc", "Main"] }}"#,
+            hash
+        ),
+    )?;
+    fs::write(dir.join("misc.ml"), "let x = 1")?;
+    fs::write(dir.join("main.mli"), "val y : int")?;
+    fs::write(dir.join("main.ml"), "let y = Misc.x")?;
+    Ok(())
+}
+
+#[test]
+fn protocol_storage_seed_from_sources() -> Result<(), Error> {
+    let tmp_storage = TmpStorage::create("__protocol_storage_seed_from_sources")?;
+    let storage = ProtocolStorage::new(tmp_storage.storage());
+    let log = create_logger(Level::Debug);
+
+    let expected = Protocol::new(
+        3,
+        vec![
+            Component::new("Misc".to_owned(), None, "let x = 1".to_owned()),
+            Component::new(
+                "Main".to_owned(),
+                Some("val y : int".to_owned()),
+                "let y = Misc.x".to_owned(),
+            ),
+        ],
+    );
+    let expected_hash = expected.message_typed_hash::<ProtocolHash>()?;
+
+    let sources_dir = tmp_storage.path().join("protocols");
+    // Octez layout
+    write_protocol_sources(
+        &sources_dir.join("proto_001").join("lib_protocol"),
+        &expected_hash.to_base58_check(),
+    )?;
+    // hash of the manifest doesn't match the sources
+    let alpha_hash = ProtocolHash::try_from_bytes(&[1; HashType::ProtocolHash.size()])?;
+    write_protocol_sources(&sources_dir.join("alpha"), &alpha_hash.to_base58_check())?;
+    // not a protocol
+    fs::create_dir_all(sources_dir.join("lib_base"))?;
+
+    let stored = storage.seed_from_sources(&sources_dir, &log)?;
+    assert_eq!(stored, vec![expected_hash.clone()]);
+    assert_eq!(storage.get(&expected_hash)?, Some(expected));
+    assert!(!storage.contains(&alpha_hash)?);
+
+    // seeding again keeps the stored protocols
+    assert_eq!(
+        storage.seed_from_sources(&sources_dir, &log)?,
+        vec![expected_hash]
+    );
+
+    Ok(())
+}
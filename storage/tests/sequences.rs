@@ -47,6 +47,7 @@ fn generator_test_multiple_gen() -> Result<(), Error> {
             TezedgeDatabaseBackendOptions::EdgeKV(database::edgekv_backend::EdgeKVBackend::new(
                 path.join("db"),
                 vec![Sequences::name()],
+                log.clone(),
             )?)
         } else {
             let db = open_kv(
@@ -106,6 +107,7 @@ fn generator_test_cloned_gen() -> Result<(), Error> {
             TezedgeDatabaseBackendOptions::EdgeKV(database::edgekv_backend::EdgeKVBackend::new(
                 path.join("db"),
                 vec![Sequences::name()],
+                log.clone(),
             )?)
         } else {
             let db = open_kv(
@@ -166,6 +168,7 @@ fn generator_test_batch() -> Result<(), Error> {
             TezedgeDatabaseBackendOptions::EdgeKV(database::edgekv_backend::EdgeKVBackend::new(
                 path.join("db"),
                 vec![Sequences::name()],
+                log.clone(),
             )?)
         } else {
             let db = open_kv(
@@ -221,6 +224,7 @@ fn generator_test_continuation_after_persist() -> Result<(), Error> {
             TezedgeDatabaseBackendOptions::EdgeKV(database::edgekv_backend::EdgeKVBackend::new(
                 path.join("db"),
                 vec![Sequences::name()],
+                log.clone(),
             )?)
         } else {
             let db = open_kv(
@@ -1055,6 +1055,8 @@ pub enum ProtocolError {
     ContextGetKeyFromHistoryError { reason: String },
     #[error("Failed to get values by prefix: {reason}")]
     ContextGetKeyValuesByPrefixError { reason: String },
+    #[error("Failed to get merkle proof: {reason}")]
+    ContextGetMerkleProofError { reason: String },
 
     #[error("Failed when dumping the context: {reason}")]
     DumpContextError { reason: DumpContextError },
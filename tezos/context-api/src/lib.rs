@@ -10,6 +10,8 @@ use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use tezos_messages::base::rpc_support::{RpcJsonMap, UniversalValue};
 
+pub mod merkle_proof;
+
 pub const INMEM: &str = "inmem";
 pub const ONDISK: &str = "ondisk";
 
@@ -0,0 +1,171 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Inclusion proofs of keys in the context.
+//!
+//! A proof contains, for every directory (and inode) from the root of a commit to a key,
+//! the hashes of the siblings of the path. Hashes are computed the same way as Irmin
+//! computes them, so a proof can be verified against the `ContextHash` of a block without
+//! access to the context.
+//!
+//! The proof is built and verified by `tezos_context::merkle_proof`.
+//!
+//! The proof is sent with bincode from the protocol runner to the node, so the enums
+//! must stay externally tagged.
+
+use std::fmt;
+
+use crypto::hash::ContextHash;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ContextKeyOwned;
+
+pub const PROOF_HASH_LEN: usize = 32;
+
+/// Hash of an object of the context (directory, inode, blob or commit)
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProofHash(pub [u8; PROOF_HASH_LEN]);
+
+impl fmt::Debug for ProofHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.0))
+    }
+}
+
+impl Serialize for ProofHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for ProofHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let bytes = hex::decode(&string).map_err(de::Error::custom)?;
+
+        if bytes.len() != PROOF_HASH_LEN {
+            return Err(de::Error::invalid_length(bytes.len(), &"32 bytes"));
+        }
+
+        let mut hash = [0; PROOF_HASH_LEN];
+        hash.copy_from_slice(&bytes);
+        Ok(Self(hash))
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofEntryKind {
+    Directory,
+    Value,
+}
+
+/// Entry of a directory.
+///
+/// `hash` is `None` for the entry on the path to the key, its hash is
+/// computed from the next step of the proof.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEntry {
+    pub name: String,
+    pub kind: ProofEntryKind,
+    pub hash: Option<ProofHash>,
+}
+
+/// Pointer of an inode to one of its children.
+///
+/// `hash` is `None` for the pointer on the path to the key.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofPointer {
+    pub index: u8,
+    pub hash: Option<ProofHash>,
+}
+
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofStep {
+    /// Small directory, all its entries are hashed together
+    Directory { entries: Vec<ProofEntry> },
+    /// Leaf of the inodes of a large directory
+    InodeValues { entries: Vec<ProofEntry> },
+    /// Inode of a large directory, pointing to other inodes
+    InodeTree {
+        depth: u32,
+        nchildren: u32,
+        pointers: Vec<ProofPointer>,
+    },
+}
+
+/// Object found at the key
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofTarget {
+    Value {
+        #[serde(with = "hex_bytes")]
+        value: Vec<u8>,
+    },
+    /// The key is a directory, only its hash is included
+    Directory { hash: ProofHash },
+}
+
+impl fmt::Debug for ProofTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value { value } => f
+                .debug_struct("Value")
+                .field("value", &hex::encode(value))
+                .finish(),
+            Self::Directory { hash } => f.debug_struct("Directory").field("hash", hash).finish(),
+        }
+    }
+}
+
+/// Fields of the commit, required to compute its hash
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofCommit {
+    pub parent_hash: Option<ProofHash>,
+    pub time: u64,
+    pub author: String,
+    pub message: String,
+}
+
+/// Proof that `target` is at a key of the commit.
+///
+/// `steps` are ordered from the root directory of the commit to the directory
+/// containing the key.
+#[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub commit: ProofCommit,
+    pub steps: Vec<ProofStep>,
+    pub target: ProofTarget,
+}
+
+/// Request sent by the node to the merkle proof server of the protocol runner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleProofRequest {
+    pub context_hash: ContextHash,
+    pub key: ContextKeyOwned,
+}
+
+/// Response of the merkle proof server, `None` when the key is not in the context.
+pub type MerkleProofResponse = Result<Option<MerkleProof>, String>;
+
+mod hex_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        hex::decode(&string).map_err(de::Error::custom)
+    }
+}
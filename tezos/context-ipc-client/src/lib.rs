@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use async_ipc::IpcError;
 use crypto::hash::ContextHash;
+use tezos_context_api::merkle_proof::MerkleProof;
 use tezos_context_api::{ContextKeyOwned, ContextValue, StringTreeObject};
 use tezos_protocol_ipc_client::{ProtocolRunnerApi, ProtocolServiceError};
 use thiserror::Error;
@@ -72,4 +73,15 @@ impl TezedgeContextClient {
             .get_context_tree_by_prefix(context_hash, prefix, depth)
             .await?)
     }
+
+    pub async fn get_merkle_proof(
+        &self,
+        context_hash: &ContextHash,
+        key: ContextKeyOwned,
+    ) -> Result<Option<MerkleProof>, TezedgeContextClientError> {
+        Ok(self
+            .tezos_protocol_api
+            .get_context_merkle_proof(context_hash, key)
+            .await?)
+    }
 }
@@ -10,7 +10,7 @@ use parking_lot::RwLock;
 use tezos_context::{
     kv_store::persistent::{FileSizes, PersistentConfiguration},
     persistent::file::{File, TAG_SIZES},
-    snapshot,
+    snapshot::{self, ContextDiff, DiffKind},
     working_tree::string_interner::StringId,
     IndexApi, Persistent, TezedgeIndex,
 };
@@ -59,6 +59,27 @@ enum Commands {
         #[clap(short, long)]
         hash: Option<String>,
     },
+    /// Display the keys added, removed or modified between two commits
+    Diff {
+        /// Path of the persistent context
+        #[clap(short, long)]
+        context_path: String,
+        /// Context hash of the old commit
+        #[clap(long)]
+        from: String,
+        /// Context hash of the new commit, default to last commit
+        #[clap(long)]
+        to: Option<String>,
+        /// Path of the persistent context containing the new commit, default to `context_path`
+        #[clap(long)]
+        to_context_path: Option<String>,
+        /// Include the values of the modified keys
+        #[clap(long)]
+        values: bool,
+        /// Print the differences in JSON
+        #[clap(long)]
+        json: bool,
+    },
     /// Create a snapshot from a commit.
     /// This will create a new context with all unused objects removed
     MakeSnapshot {
@@ -109,6 +130,57 @@ fn create_snapshot_path(base_path: Option<String>) -> String {
     }
 }
 
+fn print_diffs(diffs: &[ContextDiff], json: bool) {
+    if json {
+        let diffs: Vec<_> = diffs
+            .iter()
+            .map(|diff| {
+                let kind = match diff.kind {
+                    DiffKind::Added => "added",
+                    DiffKind::Removed => "removed",
+                    DiffKind::Modified => "modified",
+                };
+                let mut value = serde_json::json!({
+                    "key": diff.key,
+                    "kind": kind,
+                    "is_directory": diff.is_directory,
+                });
+                if let Some(old_value) = diff.old_value.as_ref() {
+                    value["old_value"] = hex::encode(old_value).into();
+                }
+                if let Some(new_value) = diff.new_value.as_ref() {
+                    value["new_value"] = hex::encode(new_value).into();
+                }
+                value
+            })
+            .collect();
+
+        println!("{}", serde_json::to_string_pretty(&diffs).unwrap());
+        return;
+    }
+
+    for diff in diffs {
+        let prefix = match diff.kind {
+            DiffKind::Added => "+",
+            DiffKind::Removed => "-",
+            DiffKind::Modified => "~",
+        };
+        let suffix = if diff.is_directory { "/" } else { "" };
+
+        match (diff.old_value.as_ref(), diff.new_value.as_ref()) {
+            (None, None) => println!("{} {}{}", prefix, diff.key, suffix),
+            (old, new) => println!(
+                "{} {}{} {} -> {}",
+                prefix,
+                diff.key,
+                suffix,
+                old.map(hex::encode).unwrap_or_else(|| "_".to_string()),
+                new.map(hex::encode).unwrap_or_else(|| "_".to_string()),
+            ),
+        }
+    }
+}
+
 fn main() {
     let args = Args::parse();
 
@@ -199,6 +271,55 @@ fn main() {
             );
             log!("Total Time {:?}", now.elapsed());
         }
+        Commands::Diff {
+            context_path,
+            from,
+            to,
+            to_context_path,
+            values,
+            json,
+        } => {
+            let old_ctx = reload_context_readonly(context_path);
+            let new_ctx = to_context_path.map(reload_context_readonly);
+
+            let old_context_hash = ContextHash::from_b58check(&from).unwrap();
+            let new_context_hash = if let Some(context_hash) = to.as_ref() {
+                ContextHash::from_b58check(context_hash).unwrap()
+            } else {
+                new_ctx
+                    .as_ref()
+                    .unwrap_or(&old_ctx)
+                    .get_last_context_hash()
+                    .unwrap()
+            };
+
+            let old_index = TezedgeIndex::new(Arc::new(RwLock::new(old_ctx)), None);
+            let new_index = match new_ctx {
+                Some(new_ctx) => TezedgeIndex::new(Arc::new(RwLock::new(new_ctx)), None),
+                None => old_index.clone(),
+            };
+
+            log!(
+                "Computing diff from {:?} to {:?}",
+                old_context_hash.to_base58_check(),
+                new_context_hash.to_base58_check()
+            );
+
+            let now = std::time::Instant::now();
+
+            let diffs = snapshot::diff_commits(
+                &old_index,
+                &old_context_hash,
+                &new_index,
+                &new_context_hash,
+                values,
+            )
+            .unwrap();
+
+            print_diffs(&diffs, json);
+
+            log!("{} differences found in {:?}", diffs.len(), now.elapsed());
+        }
         Commands::MakeSnapshot {
             context_path,
             hash: context_hash,
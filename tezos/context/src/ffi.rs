@@ -289,8 +289,8 @@ ocaml_export! {
     ) -> OCaml<Result<(), String>> {
         let ocaml_index = rt.get(index);
         let index: &TezedgeIndexFFI = ocaml_index.borrow();
-        let index = index.0.borrow().clone();
-        let (block_level, _cycle_position): (i32, Option<i64>) = extra_data.to_rust(rt);
+        let mut index = index.0.borrow().clone();
+        let (block_level, cycle_position): (i32, Option<i64>) = extra_data.to_rust(rt);
         let context_hash: Option<ContextHash> = context_hash.to_rust(rt);
 
         // We call `IndexApi::block_applied` only when `context_hash` is `Some(_)`:
@@ -301,8 +301,17 @@ ocaml_export! {
         let result = match context_hash {
             Some(ref context_hash) => {
                 let block_level = block_level as u32;
-                index.block_applied(block_level, context_hash)
-                     .map_err(|err| format!("BlockApplied: {:?}", err))
+                // The first block of a cycle is at position 0
+                let cycle_started = if cycle_position == Some(0) {
+                    index.cycle_started()
+                         .map_err(|err| format!("CycleStarted: {:?}", err))
+                } else {
+                    Ok(())
+                };
+                cycle_started.and_then(|_| {
+                    index.block_applied(block_level, context_hash)
+                         .map_err(|err| format!("BlockApplied: {:?}", err))
+                })
             },
             _ => Ok(())
         };
@@ -1,7 +1,7 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-//! Implementation of the garbage collector for the in-memory repository.
+//! Implementation of the garbage collectors for the in-memory and persistent repositories.
 
 use std::array::TryFromSliceError;
 use std::sync::PoisonError;
@@ -31,6 +31,7 @@ macro_rules! elog {
 }
 
 pub mod jemalloc;
+pub mod persistent;
 mod sorted_map;
 mod stats;
 pub(crate) mod worker;
@@ -0,0 +1,992 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Garbage collector for the persistent repository.
+//!
+//! Objects in `data.db` are referenced by their absolute offsets, and hashes in
+//! `hashes.db` by their `HashId` (their index in the file), so those files
+//! cannot be rewritten without rewriting every object referencing them.
+//!
+//! Instead, when enough cycles have been applied, we find the commit (the root)
+//! preceding the oldest cycle to preserve, and we deallocate (punch holes into)
+//! every range of the files below that cycle which is not reachable from the root.
+//! The offsets of the remaining objects are unchanged.
+//!
+//! This is correct because a commit only refers to objects of its parent tree or to
+//! objects written after its parent: every commit descending from the root only
+//! refers to objects reachable from the root, or written after it.
+//!
+//! Commits above the boundary on a side branch, forked below the boundary, descend
+//! from another commit than the root: the trees of those commits are kept too.
+//! They are not indexed anymore, but their descendants above the boundary stay readable.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    convert::TryInto,
+    io,
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Instant,
+};
+
+use crossbeam_channel::{Receiver, TryRecvError};
+use crypto::hash::{ContextHash, FromBytesError, HashTrait};
+use parking_lot::RwLock;
+use thiserror::Error;
+
+use crate::{
+    gc::stats::PersistentCollectorStatistics,
+    hash::OBJECT_HASH_LEN,
+    initializer::IndexInitializationError,
+    kv_store::persistent::PersistentConfiguration,
+    persistent::{
+        file::{File, TAG_DATA, TAG_GARBAGE_COLLECTION, TAG_HASHES},
+        DBError,
+    },
+    serialize::{
+        persistent::{self as serialize_persistent, AbsoluteOffset},
+        DeserializationError, ObjectHeader, ObjectTag,
+    },
+    working_tree::{
+        storage::{Storage, StorageError},
+        string_interner::StringInterner,
+        working_tree::MerkleError,
+        Object, ObjectReference,
+    },
+    ContextKeyValueStore, Persistent, TezedgeIndex,
+};
+
+/// Environment variable containing the number of cycles to preserve.
+///
+/// It's only read when the number of cycles is not configured with `set_preserve_cycles`.
+/// The garbage collector of the persistent repository is disabled when none of them is set.
+pub const PRESERVE_CYCLES_ENV: &str = "TEZEDGE_PERSISTENT_GC_PRESERVE_CYCLES";
+
+/// Number of cycles to preserve configured with `set_preserve_cycles`, 0 when not configured
+static PRESERVE_CYCLES: AtomicUsize = AtomicUsize::new(0);
+
+/// Minimum number of consecutive unreachable bytes to deallocate.
+///
+/// Smaller ranges are not worth a syscall, the filesystem would not free any block.
+const MIN_HOLE_LENGTH: u64 = 4096;
+
+/// Number of objects processed between 2 checks of the cancellation flag
+const CANCEL_CHECK_INTERVAL: usize = 100_000;
+
+/// [data_boundary (8 bytes) | hashes_boundary (8 bytes) | root_offset (8 bytes) | completed (1 byte)]
+const RECORD_LENGTH: usize = 25;
+
+#[derive(Debug, Error)]
+pub enum PersistentGCError {
+    #[error("IO error {error}")]
+    IOError {
+        #[from]
+        error: io::Error,
+    },
+    #[error("DBError {error}")]
+    DBError {
+        #[from]
+        error: DBError,
+    },
+    #[error("MerkleError {error}")]
+    MerkleError {
+        #[from]
+        error: MerkleError,
+    },
+    #[error("StorageError {error}")]
+    StorageError {
+        #[from]
+        error: StorageError,
+    },
+    #[error("Fail while initializing repository {error}")]
+    IndexInitializationError {
+        #[from]
+        error: IndexInitializationError,
+    },
+    #[error("Failed to convert hash {error}")]
+    HashConversionError {
+        #[from]
+        error: FromBytesError,
+    },
+    #[error("Failed to deserialize object {error}")]
+    DeserializationError {
+        #[from]
+        error: DeserializationError,
+    },
+    #[error("Head commit not found: {context_hash}")]
+    HeadNotFound { context_hash: ContextHash },
+    #[error("No commit found below the boundary")]
+    RootNotFound,
+    #[error("The garbage collection was cancelled")]
+    Cancelled,
+    #[error("The garbage collection thread died")]
+    ThreadDied,
+}
+
+/// Record written to `gc.db` when a garbage collection starts, and when it's completed.
+///
+/// On reload, the commits below `data_boundary` (except the root) are not indexed.
+/// When the last record is not completed, the checksums of `data.db` and `hashes.db`
+/// in `sizes.db` cannot be trusted and are recomputed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GarbageCollectionRecord {
+    /// Offset in `data.db` where the oldest preserved cycle starts
+    pub data_boundary: u64,
+    /// Offset in `hashes.db` where the oldest preserved cycle starts
+    pub hashes_boundary: u64,
+    /// Offset of the commit from which objects below `data_boundary` are kept.
+    /// This is 0 when the root is not yet known.
+    pub root_offset: u64,
+    pub completed: bool,
+}
+
+impl GarbageCollectionRecord {
+    /// Returns `false` when the commit at `commit_ref` has been (or is being) collected
+    pub fn is_commit_alive(&self, commit_ref: &ObjectReference) -> bool {
+        match commit_ref.offset_opt() {
+            Some(offset) => {
+                let offset = offset.as_u64();
+                offset >= self.data_boundary || offset == self.root_offset
+            }
+            None => true,
+        }
+    }
+
+    fn serialize(&self) -> [u8; RECORD_LENGTH] {
+        let mut bytes = [0; RECORD_LENGTH];
+
+        bytes[0..8].copy_from_slice(&self.data_boundary.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.hashes_boundary.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.root_offset.to_le_bytes());
+        bytes[24] = self.completed as u8;
+
+        bytes
+    }
+
+    fn deserialize(bytes: &[u8; RECORD_LENGTH]) -> Self {
+        // Never fail, the slices have the correct length
+        Self {
+            data_boundary: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            hashes_boundary: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            root_offset: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            completed: bytes[24] != 0,
+        }
+    }
+}
+
+/// Returns the last record of `gc.db`, or `None` when the repository
+/// has never been garbage collected
+pub fn read_last_record(base_path: &str) -> Option<GarbageCollectionRecord> {
+    // The file doesn't exist on databases never opened in write mode with this version
+    let file = File::<{ TAG_GARBAGE_COLLECTION }>::try_new(base_path, true).ok()?;
+
+    let mut offset = file.start();
+    let end = file.offset().as_u64();
+    let mut bytes = [0; RECORD_LENGTH];
+    let mut last = None;
+
+    // A torn record at the end of the file is ignored
+    while offset + RECORD_LENGTH as u64 <= end {
+        if file.read_exact_at(&mut bytes, offset.into()).is_err() {
+            break;
+        }
+        last = Some(GarbageCollectionRecord::deserialize(&bytes));
+        offset += RECORD_LENGTH as u64;
+    }
+
+    last
+}
+
+/// Returns the length of `gc.db`, it changes each time a record is appended.
+///
+/// This is used as a generation by the repositories opened in read mode, to
+/// read the last record only after a new garbage collection.
+pub fn records_generation(base_path: &str) -> u64 {
+    std::fs::metadata(std::path::Path::new(base_path).join("gc.db"))
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+pub fn append_record(base_path: &str, record: &GarbageCollectionRecord) -> io::Result<()> {
+    let mut file = File::<{ TAG_GARBAGE_COLLECTION }>::try_new(base_path, false)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    file.append(record.serialize())?;
+    file.sync()
+}
+
+/// Configure the number of cycles preserved by the repositories opened in write mode
+/// in this process, `None` disables the garbage collector.
+///
+/// This must be called before the repository is opened.
+pub fn set_preserve_cycles(preserve_cycles: Option<NonZeroU64>) {
+    let preserve_cycles = preserve_cycles.map_or(0, |cycles| cycles.get() as usize);
+    PRESERVE_CYCLES.store(preserve_cycles, Ordering::Release);
+}
+
+/// Returns the number of cycles to preserve, configured with `set_preserve_cycles`
+/// or with `PRESERVE_CYCLES_ENV`
+pub fn configured_preserve_cycles() -> Option<usize> {
+    match PRESERVE_CYCLES.load(Ordering::Acquire) {
+        0 => preserve_cycles_from_env(),
+        preserve_cycles => Some(preserve_cycles),
+    }
+}
+
+/// Read the number of cycles to preserve from `PRESERVE_CYCLES_ENV`
+fn preserve_cycles_from_env() -> Option<usize> {
+    let preserve_cycles = std::env::var(PRESERVE_CYCLES_ENV).ok()?;
+
+    match preserve_cycles.parse::<usize>() {
+        Ok(preserve_cycles) if preserve_cycles > 0 => Some(preserve_cycles),
+        _ => {
+            elog!(
+                "Invalid value for {}: {:?}, the garbage collector is disabled",
+                PRESERVE_CYCLES_ENV,
+                preserve_cycles
+            );
+            None
+        }
+    }
+}
+
+/// Result of a garbage collection, applied by the main thread
+pub struct CollectionOutcome {
+    pub record: GarbageCollectionRecord,
+    pub root_hash: ContextHash,
+    pub root_ref: ObjectReference,
+    /// Checksum of `data.db`, until the offset where the collection started
+    pub data_checksum: crc32fast::Hasher,
+    /// Checksum of `hashes.db`, until the offset where the collection started
+    pub hashes_checksum: crc32fast::Hasher,
+    pub stats: PersistentCollectorStatistics,
+}
+
+pub struct CollectionJob {
+    pub base_path: String,
+    /// Last commit applied, the root is one of its ancestors
+    pub head: ContextHash,
+    pub data_boundary: AbsoluteOffset,
+    pub hashes_boundary: AbsoluteOffset,
+    /// Clones of the files of the repository, used to deallocate ranges.
+    pub data_file: File<{ TAG_DATA }>,
+    pub hashes_file: File<{ TAG_HASHES }>,
+}
+
+struct RunningCollection {
+    thread_handle: Option<JoinHandle<()>>,
+    recv: Receiver<Result<CollectionOutcome, PersistentGCError>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl RunningCollection {
+    fn join(&mut self) {
+        if let Some(thread_handle) = self.thread_handle.take() {
+            if let Err(e) = thread_handle.join() {
+                elog!("Failed to join persistent GC thread: {:?}", e);
+            }
+        }
+    }
+}
+
+/// State of the garbage collector, owned by `Persistent`
+pub struct PersistentGarbageCollector {
+    preserve_cycles: usize,
+    /// Offsets of `data.db` and `hashes.db` when each preserved cycle started
+    cycles_boundaries: VecDeque<(AbsoluteOffset, AbsoluteOffset)>,
+    /// Last commit applied
+    head: Option<ContextHash>,
+    running: Option<RunningCollection>,
+}
+
+impl PersistentGarbageCollector {
+    pub fn new(preserve_cycles: usize) -> Self {
+        log!(
+            "Persistent context garbage collector enabled, preserving {} cycles",
+            preserve_cycles
+        );
+
+        Self {
+            preserve_cycles,
+            cycles_boundaries: VecDeque::with_capacity(preserve_cycles + 1),
+            head: None,
+            running: None,
+        }
+    }
+
+    pub fn set_head(&mut self, context_hash: ContextHash) {
+        self.head = Some(context_hash);
+    }
+
+    pub fn head(&self) -> Option<&ContextHash> {
+        self.head.as_ref()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Record the offsets where the new cycle starts.
+    ///
+    /// Returns the offsets where the oldest preserved cycle starts, when
+    /// the objects below them can be collected.
+    pub fn cycle_started(
+        &mut self,
+        data_offset: AbsoluteOffset,
+        hashes_offset: AbsoluteOffset,
+    ) -> Option<(AbsoluteOffset, AbsoluteOffset)> {
+        self.cycles_boundaries
+            .push_back((data_offset, hashes_offset));
+
+        if self.cycles_boundaries.len() <= self.preserve_cycles {
+            return None;
+        }
+
+        self.cycles_boundaries.pop_front();
+        self.cycles_boundaries.front().copied()
+    }
+
+    pub fn spawn(&mut self, job: CollectionJob) -> io::Result<()> {
+        let (sender, recv) = crossbeam_channel::bounded(1);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+
+        let thread_handle = std::thread::Builder::new()
+            .name("ctx-persistent-gc-thread".to_string())
+            .spawn(move || {
+                let result = collect(job, &thread_cancel);
+                if let Err(e) = sender.send(result) {
+                    elog!("Failed to send result of persistent GC: {:?}", e);
+                }
+            })?;
+
+        self.running = Some(RunningCollection {
+            thread_handle: Some(thread_handle),
+            recv,
+            cancel,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the result of the running collection, if it's done
+    pub fn try_take_result(&mut self) -> Option<Result<CollectionOutcome, PersistentGCError>> {
+        let running = self.running.as_mut()?;
+
+        let result = match running.recv.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(PersistentGCError::ThreadDied),
+        };
+
+        running.join();
+        self.running = None;
+
+        Some(result)
+    }
+}
+
+impl Drop for PersistentGarbageCollector {
+    fn drop(&mut self) {
+        if let Some(mut running) = self.running.take() {
+            running.cancel.store(true, Ordering::Release);
+            running.join();
+        }
+    }
+}
+
+fn check_cancelled(cancel: &AtomicBool) -> Result<(), PersistentGCError> {
+    if cancel.load(Ordering::Acquire) {
+        Err(PersistentGCError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+/// Walk the ancestors of `head` and returns the first commit below `data_boundary`
+fn find_root(
+    index: &TezedgeIndex,
+    head: &ContextHash,
+    data_boundary: AbsoluteOffset,
+    cancel: &AtomicBool,
+) -> Result<ObjectReference, PersistentGCError> {
+    let mut commit_ref =
+        index
+            .fetch_context_hash_id(head)?
+            .ok_or_else(|| PersistentGCError::HeadNotFound {
+                context_hash: head.clone(),
+            })?;
+
+    // Commits don't contain any string
+    let mut strings = StringInterner::default();
+    let mut storage = Storage::new();
+
+    let mut nvisited: usize = 0;
+
+    while commit_ref.offset() >= data_boundary {
+        if nvisited % CANCEL_CHECK_INTERVAL == 0 {
+            check_cancelled(cancel)?;
+        }
+        nvisited += 1;
+
+        let commit = index.get_commit(commit_ref, &mut storage, &mut strings)?;
+
+        commit_ref = commit
+            .parent_commit_ref
+            .ok_or(PersistentGCError::RootNotFound)?;
+    }
+
+    Ok(commit_ref)
+}
+
+/// Deallocate the ranges of `[start; end[` not covered by `alives`.
+///
+/// `alives` must be sorted.
+/// Returns the number of bytes deallocated.
+fn punch_holes<const T: u64>(
+    file: &File<T>,
+    alives: &[(u64, u64)],
+    end: u64,
+) -> Result<u64, PersistentGCError> {
+    let mut cursor = file.start();
+    let mut reclaimed = 0;
+
+    let alives = alives.iter().copied().chain(std::iter::once((end, end)));
+
+    for (alive_start, alive_end) in alives {
+        let hole_end = alive_start.min(end);
+
+        if hole_end > cursor && hole_end - cursor >= MIN_HOLE_LENGTH {
+            let length = hole_end - cursor;
+
+            file.punch_hole(cursor.into(), length)?;
+            reclaimed += length;
+        }
+
+        cursor = cursor.max(alive_end);
+        if cursor >= end {
+            break;
+        }
+    }
+
+    Ok(reclaimed)
+}
+
+/// Returns the first ancestors below `data_boundary`, other than `root_ref`, of the
+/// commits above `data_boundary`: those commits are on side branches.
+fn find_side_roots(
+    index: &TezedgeIndex,
+    commits: &[ObjectReference],
+    root_ref: ObjectReference,
+    data_boundary: AbsoluteOffset,
+    cancel: &AtomicBool,
+) -> Result<Vec<ObjectReference>, PersistentGCError> {
+    let mut strings = StringInterner::default();
+    let mut storage = Storage::new();
+
+    // Offset of a commit above the boundary => its first ancestor below the boundary
+    let mut resolved: HashMap<u64, Option<ObjectReference>> = HashMap::default();
+    let mut path = Vec::with_capacity(1024);
+    let mut nvisited: usize = 0;
+
+    for commit_ref in commits {
+        let mut commit_ref = *commit_ref;
+
+        let ancestor = loop {
+            if commit_ref.offset() < data_boundary {
+                break Some(commit_ref);
+            }
+            if let Some(ancestor) = resolved.get(&commit_ref.offset().as_u64()) {
+                break *ancestor;
+            }
+
+            if nvisited % CANCEL_CHECK_INTERVAL == 0 {
+                check_cancelled(cancel)?;
+            }
+            nvisited += 1;
+
+            path.push(commit_ref.offset().as_u64());
+
+            storage.clear();
+            let commit = index.get_commit(commit_ref, &mut storage, &mut strings)?;
+
+            match commit.parent_commit_ref {
+                Some(parent_ref) => commit_ref = parent_ref,
+                None => break None,
+            }
+        };
+
+        for offset in path.drain(..) {
+            resolved.insert(offset, ancestor);
+        }
+    }
+
+    let mut side_roots: Vec<ObjectReference> = resolved
+        .into_values()
+        .flatten()
+        .filter(|ancestor| ancestor.offset() != root_ref.offset())
+        .collect();
+    side_roots.sort_unstable_by_key(|ancestor| ancestor.offset());
+    side_roots.dedup_by_key(|ancestor| ancestor.offset());
+
+    Ok(side_roots)
+}
+
+/// Objects and hashes which must not be deallocated
+#[derive(Default)]
+struct AliveObjects {
+    /// Ranges `[start; end[` of the objects in `data.db`
+    objects: Vec<(u64, u64)>,
+    /// Offsets of the hashes in `hashes.db`
+    hashes: Vec<u64>,
+    /// Offsets of the objects already visited
+    visited: HashSet<u64>,
+}
+
+impl AliveObjects {
+    /// Mark the object and its hash alive, and returns its bytes.
+    ///
+    /// Returns `None` when the object was already visited, or when it's inlined.
+    fn mark<'a>(
+        &mut self,
+        repository: &ContextKeyValueStore,
+        object_ref: ObjectReference,
+        hashes_start: u64,
+        buffer: &'a mut Vec<u8>,
+    ) -> Result<Option<&'a [u8]>, PersistentGCError> {
+        let bytes = match object_ref.offset_opt() {
+            Some(offset) => {
+                let offset = offset.as_u64();
+                if !self.visited.insert(offset) {
+                    return Ok(None);
+                }
+
+                let bytes = repository.get_object_bytes(object_ref, buffer)?;
+                self.objects.push((offset, offset + bytes.len() as u64));
+                Some(bytes)
+            }
+            // Inlined object
+            None if object_ref.hash_id_opt().is_none() => return Ok(None),
+            None => None,
+        };
+
+        let hash_id = repository.get_hash_id(object_ref)?;
+        let hash_index: usize = hash_id.try_into().map_err(DBError::from)?;
+        self.hashes
+            .push(hashes_start + (hash_index * OBJECT_HASH_LEN) as u64);
+
+        Ok(bytes)
+    }
+}
+
+/// Mark alive the commits `roots`, their parent commit, and every object reachable from
+/// their trees.
+///
+/// The trees are traversed one object at a time, only the offsets of the visited objects
+/// are kept in memory.
+fn mark_alive_objects(
+    repository: &ContextKeyValueStore,
+    roots: &[ObjectReference],
+    hashes_start: u64,
+    cancel: &AtomicBool,
+) -> Result<AliveObjects, PersistentGCError> {
+    let mut alive = AliveObjects::default();
+    let mut storage = Storage::new();
+    let mut strings = StringInterner::default();
+    let mut buffer = Vec::with_capacity(16 * 1024);
+    let mut stack = Vec::with_capacity(1024);
+
+    for root_ref in roots {
+        let bytes = match alive.mark(repository, *root_ref, hashes_start, &mut buffer)? {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+
+        storage.clear();
+        let object = serialize_persistent::deserialize_object(
+            bytes,
+            root_ref.offset(),
+            &mut storage,
+            &mut strings,
+            repository,
+        )?;
+
+        let commit = match object {
+            Object::Commit(commit) => commit,
+            _ => return Err(PersistentGCError::RootNotFound),
+        };
+
+        // The parent is needed to compute the hash of the commit, not its tree
+        if let Some(parent_ref) = commit.parent_commit_ref {
+            alive.mark(repository, parent_ref, hashes_start, &mut buffer)?;
+        }
+        stack.push(commit.root_ref);
+    }
+
+    let mut nvisited: usize = 0;
+
+    while let Some(object_ref) = stack.pop() {
+        if nvisited % CANCEL_CHECK_INTERVAL == 0 {
+            check_cancelled(cancel)?;
+        }
+        nvisited += 1;
+
+        let bytes = match alive.mark(repository, object_ref, hashes_start, &mut buffer)? {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+
+        let header = bytes
+            .get(0)
+            .copied()
+            .ok_or(DeserializationError::UnexpectedEOF)?;
+        let header = ObjectHeader::from_bytes([header]);
+        if let Ok(ObjectTag::Blob) = header.tag_or_err() {
+            continue;
+        }
+
+        // Deserialize only this object, to get the references to its children
+        storage.clear();
+        serialize_persistent::deserialize_object(
+            bytes,
+            object_ref.offset(),
+            &mut storage,
+            &mut strings,
+            repository,
+        )?;
+        storage.for_each_reference(|child_ref| stack.push(child_ref))?;
+    }
+
+    Ok(alive)
+}
+
+fn collect(
+    job: CollectionJob,
+    cancel: &AtomicBool,
+) -> Result<CollectionOutcome, PersistentGCError> {
+    let now = Instant::now();
+
+    let CollectionJob {
+        base_path,
+        head,
+        data_boundary,
+        hashes_boundary,
+        mut data_file,
+        mut hashes_file,
+    } = job;
+
+    // Offsets where the main thread started to compute the partial checksums
+    let data_end = data_file.offset().as_u64();
+    let hashes_end = hashes_file.offset().as_u64();
+
+    log!(
+        "Collecting persistent context below data_offset={:?} hashes_offset={:?}",
+        data_boundary,
+        hashes_boundary
+    );
+
+    let mut repository = Persistent::try_new(PersistentConfiguration {
+        db_path: Some(base_path.clone()),
+        startup_check: false,
+        read_mode: true,
+    })?;
+    repository.reload_database()?;
+
+    let commits_above_boundary: Vec<ObjectReference> = repository
+        .context_hashes
+        .values()
+        .filter(|commit_ref| commit_ref.offset() >= data_boundary)
+        .copied()
+        .collect();
+
+    let repository: Arc<RwLock<ContextKeyValueStore>> = Arc::new(RwLock::new(repository));
+
+    let (root_ref, root_hash, side_roots) = {
+        let index = TezedgeIndex::new(Arc::clone(&repository), None);
+        let root_ref = find_root(&index, &head, data_boundary, cancel)?;
+        let root_hash = ContextHash::try_from_bytes(&index.fetch_hash(root_ref)?)?;
+        let side_roots = find_side_roots(
+            &index,
+            &commits_above_boundary,
+            root_ref,
+            data_boundary,
+            cancel,
+        )?;
+        (root_ref, root_hash, side_roots)
+    };
+    std::mem::drop(commits_above_boundary);
+
+    if !side_roots.is_empty() {
+        log!(
+            "Keeping the trees of {} commits with descendants on side branches",
+            side_roots.len()
+        );
+    }
+
+    check_cancelled(cancel)?;
+
+    let mut roots = Vec::with_capacity(side_roots.len() + 1);
+    roots.push(root_ref);
+    roots.extend(side_roots);
+
+    let AliveObjects {
+        objects: mut alive_objects,
+        hashes: mut alive_hashes,
+        visited,
+    } = mark_alive_objects(&*repository.read(), &roots, hashes_file.start(), cancel)?;
+
+    let nobjects_alive = visited.len();
+    std::mem::drop(visited);
+
+    alive_objects.sort_unstable();
+    alive_objects.dedup();
+
+    alive_hashes.sort_unstable();
+    alive_hashes.dedup();
+    let alive_hashes: Vec<(u64, u64)> = alive_hashes
+        .into_iter()
+        .map(|offset| (offset, offset + OBJECT_HASH_LEN as u64))
+        .collect();
+
+    check_cancelled(cancel)?;
+
+    let record = GarbageCollectionRecord {
+        data_boundary: data_boundary.as_u64(),
+        hashes_boundary: hashes_boundary.as_u64(),
+        root_offset: root_ref.offset().as_u64(),
+        completed: false,
+    };
+
+    // The record must be on disk before deallocating anything, so that the
+    // repository knows, on reload, that the checksums must be recomputed.
+    append_record(&base_path, &record)?;
+
+    let data_bytes_reclaimed = punch_holes(&data_file, &alive_objects, data_boundary.as_u64())?;
+    let hashes_bytes_reclaimed =
+        punch_holes(&hashes_file, &alive_hashes, hashes_boundary.as_u64())?;
+
+    data_file.sync()?;
+    hashes_file.sync()?;
+
+    // Compute the checksums of the modified files, the main thread computes
+    // the checksums of what was appended since the collection started.
+    data_file.reset_checksum();
+    data_file.update_checksum_until(data_end)?;
+    hashes_file.reset_checksum();
+    hashes_file.update_checksum_until(hashes_end)?;
+
+    Ok(CollectionOutcome {
+        record,
+        root_hash,
+        root_ref,
+        data_checksum: data_file.checksum_hasher(),
+        hashes_checksum: hashes_file.checksum_hasher(),
+        stats: PersistentCollectorStatistics {
+            nobjects_alive,
+            data_bytes_reclaimed,
+            hashes_bytes_reclaimed,
+            gc_duration: now.elapsed(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        context_key, persistent::file::get_persistent_base_path, IndexApi, ProtocolContextApi,
+        ShellContextApi, TezedgeContext,
+    };
+
+    use super::*;
+
+    const NKEYS: usize = 16;
+
+    fn blob(index: usize, value: u8) -> Vec<u8> {
+        let mut blob = vec![value; 1024];
+        blob[0] = index as u8;
+        blob
+    }
+
+    /// Overwrite all the keys of `context` with blobs of `value`, and commit
+    fn commit_values(context: &TezedgeContext, value: u8, message: &str) -> ContextHash {
+        let mut context = context.clone();
+        for index in 0..NKEYS {
+            let key = format!("data/key{}", index);
+            context = context
+                .add(&context_key!(key), &blob(index, value))
+                .unwrap();
+        }
+        context
+            .commit("Tezedge".to_string(), message.to_string(), 0)
+            .unwrap()
+    }
+
+    fn assert_values(context: &TezedgeContext, value: u8) {
+        for index in 0..NKEYS {
+            let key = format!("data/key{}", index);
+            assert_eq!(
+                context.find(&context_key!(key)).unwrap(),
+                Some(blob(index, value))
+            );
+        }
+    }
+
+    fn checkout(index: &TezedgeIndex, context_hash: &ContextHash) -> TezedgeContext {
+        index.checkout(context_hash).unwrap().unwrap()
+    }
+
+    fn open_index(base_path: &str, read_mode: bool) -> TezedgeIndex {
+        let mut repository = Persistent::try_new(PersistentConfiguration {
+            db_path: Some(base_path.to_string()),
+            startup_check: false,
+            read_mode,
+        })
+        .unwrap();
+        repository.reload_database().unwrap();
+
+        let repository: Arc<RwLock<ContextKeyValueStore>> = Arc::new(RwLock::new(repository));
+        TezedgeIndex::new(repository, None)
+    }
+
+    #[test]
+    fn test_collect_and_reload() {
+        let base_path = get_persistent_base_path(None);
+
+        let (old, side_root, root, head, side, data_boundary, hashes_boundary) = {
+            let mut repository = Persistent::try_new(PersistentConfiguration {
+                db_path: Some(base_path.clone()),
+                startup_check: false,
+                read_mode: false,
+            })
+            .unwrap();
+            repository.reload_database().unwrap();
+
+            let repository = Arc::new(RwLock::new(repository));
+            let index_repository: Arc<RwLock<ContextKeyValueStore>> = repository.clone();
+            let index = TezedgeIndex::new(index_repository, None);
+
+            // Each commit overwrites all the objects of its parent
+            let old = commit_values(&TezedgeContext::new(index.clone(), None, None), 0, "old");
+            let side_root = commit_values(&checkout(&index, &old), 1, "side root");
+            let root = commit_values(&checkout(&index, &side_root), 2, "root");
+
+            let data_boundary = repository.read().data_file_offset();
+            let hashes_boundary = repository.read().hashes_file_offset();
+
+            let head = commit_values(&checkout(&index, &root), 3, "head");
+            // Side branch forked below the boundary
+            let side = checkout(&index, &side_root)
+                .add(&context_key!("data/side"), &[4; 1024])
+                .unwrap()
+                .commit("Tezedge".to_string(), "side".to_string(), 0)
+                .unwrap();
+
+            (
+                old,
+                side_root,
+                root,
+                head,
+                side,
+                data_boundary,
+                hashes_boundary,
+            )
+        };
+
+        // Repository opened in read mode before the collection
+        let reader = open_index(&base_path, true);
+        assert_values(&checkout(&reader, &old), 0);
+
+        let job = CollectionJob {
+            base_path: base_path.clone(),
+            head: head.clone(),
+            data_boundary,
+            hashes_boundary,
+            data_file: File::try_new(&base_path, false).unwrap(),
+            hashes_file: File::try_new(&base_path, false).unwrap(),
+        };
+        let outcome = collect(job, &AtomicBool::new(false)).unwrap();
+
+        assert_eq!(outcome.root_hash, root);
+        assert_eq!(outcome.record.data_boundary, data_boundary.as_u64());
+        assert!(outcome.stats.data_bytes_reclaimed > 0);
+
+        // The reader doesn't return collected commits
+        assert!(reader.checkout(&old).unwrap().is_none());
+        assert_values(&checkout(&reader, &root), 2);
+        std::mem::drop(reader);
+
+        // The record is not completed: the checksums are recomputed on reload
+        let index = open_index(&base_path, false);
+
+        assert!(index.checkout(&old).unwrap().is_none());
+        assert!(index.checkout(&side_root).unwrap().is_none());
+        assert_values(&checkout(&index, &root), 2);
+        assert_values(&checkout(&index, &head), 3);
+
+        let side = checkout(&index, &side);
+        assert_values(&side, 1);
+        assert_eq!(
+            side.find(&context_key!("data/side")).unwrap(),
+            Some(vec![4; 1024])
+        );
+
+        assert_eq!(
+            read_last_record(&base_path),
+            Some(GarbageCollectionRecord {
+                completed: true,
+                ..outcome.record
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_serialization() {
+        let record = GarbageCollectionRecord {
+            data_boundary: 1_000_000,
+            hashes_boundary: 32 * 100,
+            root_offset: 999_000,
+            completed: true,
+        };
+
+        let bytes = record.serialize();
+        assert_eq!(GarbageCollectionRecord::deserialize(&bytes), record);
+    }
+
+    #[test]
+    fn test_record_commit_alive() {
+        let record = GarbageCollectionRecord {
+            data_boundary: 1000,
+            hashes_boundary: 1000,
+            root_offset: 500,
+            completed: false,
+        };
+
+        let commit_ref = |offset: u64| ObjectReference::new(None, Some(offset.into()));
+
+        assert!(record.is_commit_alive(&commit_ref(1000)));
+        assert!(record.is_commit_alive(&commit_ref(5000)));
+        assert!(record.is_commit_alive(&commit_ref(500)));
+        assert!(!record.is_commit_alive(&commit_ref(499)));
+        assert!(!record.is_commit_alive(&commit_ref(999)));
+    }
+
+    #[test]
+    fn test_cycles_boundaries() {
+        let mut gc = PersistentGarbageCollector::new(2);
+
+        assert!(gc.cycle_started(100.into(), 10.into()).is_none());
+        assert!(gc.cycle_started(200.into(), 20.into()).is_none());
+        assert_eq!(
+            gc.cycle_started(300.into(), 30.into()),
+            Some((200.into(), 20.into()))
+        );
+        assert_eq!(
+            gc.cycle_started(400.into(), 40.into()),
+            Some((300.into(), 30.into()))
+        );
+    }
+}
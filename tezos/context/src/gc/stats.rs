@@ -53,6 +53,14 @@ pub struct CollectorStatistics {
     pub delay_since_last_gc: Option<std::time::Duration>,
 }
 
+#[derive(Debug)]
+pub struct PersistentCollectorStatistics {
+    pub nobjects_alive: usize,
+    pub data_bytes_reclaimed: u64,
+    pub hashes_bytes_reclaimed: u64,
+    pub gc_duration: std::time::Duration,
+}
+
 #[derive(Debug)]
 pub struct CommitStatistics {
     pub new_hash_id: usize,
@@ -115,6 +115,105 @@ pub(crate) fn index(depth: u32, name: &str) -> u32 {
     ocaml_hash_string(depth, name.as_bytes()) % 32
 }
 
+pub(crate) fn finalize_hash(hasher: VarBlake2b) -> ObjectHash {
+    let mut object_hash: ObjectHash = Default::default();
+    hasher.finalize_variable(|r| object_hash.copy_from_slice(r));
+    object_hash
+}
+
+// Inode value:
+//
+// |   1   |   1  |     n_1      |  ...  |      n_k      |
+// +-------+------+--------------+-------+---------------+
+// | \000  |  \n  | prehash(e_1) |  ...  | prehash(e_k)  |
+//
+// where n_i = len(prehash(e_i))
+pub(crate) fn update_inode_value_header(hasher: &mut VarBlake2b, nentries: usize) {
+    hasher.update(&[0u8]); // type tag
+    hasher.update(&[nentries as u8]);
+}
+
+// Inode value object:
+//
+// |   (LEB128)  |  len(name)   |   1    |   32   |
+// +-------------+--------------+--------+--------+
+// | \len(name)  |     name     |  kind  |  hash  |
+pub(crate) fn update_inode_value_entry(
+    hasher: &mut VarBlake2b,
+    name: &str,
+    kind: &DirEntryKind,
+    hash: &[u8],
+) -> Result<(), HashingError> {
+    leb128::write::unsigned(hasher, name.len() as u64)?;
+    hasher.update(name.as_bytes());
+
+    // \000 for nodes, and \001 for contents.
+    match kind {
+        DirEntryKind::Blob => hasher.update(&[1u8]),
+        DirEntryKind::Directory => hasher.update(&[0u8]),
+    };
+
+    hasher.update(hash);
+    Ok(())
+}
+
+// Inode directory:
+//
+// |   1    | (LEB128) |   (LEB128)    |    1   |  33  | ... |  33  |
+// +--------+----------+---------------+--------+------+-----+------+
+// |  \001  |  depth   | len(children) |   \k   | s_1  | ... | s_k  |
+pub(crate) fn update_inode_tree_header(
+    hasher: &mut VarBlake2b,
+    depth: u32,
+    nchildren: u32,
+    npointers: usize,
+) -> Result<(), HashingError> {
+    hasher.update(&[1u8]); // type tag
+    leb128::write::unsigned(hasher, depth as u64)?;
+    leb128::write::unsigned(hasher, nchildren as u64)?;
+    hasher.update(&[npointers as u8]);
+    Ok(())
+}
+
+// Inode pointer:
+//
+// |    1    |   32   |
+// +---------+--------+
+// |  index  |  hash  |
+pub(crate) fn update_inode_pointer(hasher: &mut VarBlake2b, index: u8, hash: &[u8]) {
+    hasher.update(&[index]);
+    hasher.update(hash);
+}
+
+// DirEntry list:
+//
+// |    8   |     n_1      | ... |      n_k     |
+// +--------+--------------+-----+--------------+
+// |   \k   | prehash(e_1) | ... | prehash(e_k) |
+pub(crate) fn update_short_inode_header(hasher: &mut VarBlake2b, nentries: usize) {
+    hasher.update(&(nentries as u64).to_be_bytes());
+}
+
+// DirEntry object:
+//
+// |   8   |   (LEB128)   |  len(name)  |   8   |   32   |
+// +-------+--------------+-------------+-------+--------+
+// | kind  |  \len(name)  |    name     |  \32  |  hash  |
+pub(crate) fn update_short_inode_entry(
+    hasher: &mut VarBlake2b,
+    name: &str,
+    kind: &DirEntryKind,
+    hash: &[u8],
+) -> Result<(), HashingError> {
+    hasher.update(encode_irmin_dir_entry_kind(kind));
+    // Key length is written in LEB128 encoding
+    leb128::write::unsigned(hasher, name.len() as u64)?;
+    hasher.update(name.as_bytes());
+    hasher.update(&(OBJECT_HASH_LEN as u64).to_be_bytes());
+    hasher.update(hash);
+    Ok(())
+}
+
 fn hash_long_inode(
     ptr_id: DirectoryOrInodeId,
     store: &mut ContextKeyValueStore,
@@ -125,43 +224,20 @@ fn hash_long_inode(
 
     match ptr_id {
         DirectoryOrInodeId::Directory(dir_id) => {
-            // Inode value:
-            //
-            // |   1   |   1  |     n_1      |  ...  |      n_k      |
-            // +-------+------+--------------+-------+---------------+
-            // | \000  |  \n  | prehash(e_1) |  ...  | prehash(e_k)  |
-            //
-            // where n_i = len(prehash(e_i))
-
             let dir = storage.get_small_dir(dir_id)?;
 
-            hasher.update(&[0u8]); // type tag
-            hasher.update(&[dir.len() as u8]);
-
-            // Inode value object:
-            //
-            // |   (LEB128)  |  len(name)   |   1    |   32   |
-            // +-------------+--------------+--------+--------+
-            // | \len(name)  |     name     |  kind  |  hash  |
+            update_inode_value_header(&mut hasher, dir.len());
 
             for (name, dir_entry_id) in dir.as_ref() {
                 let name = strings.get_str(*name)?;
-
-                leb128::write::unsigned(&mut hasher, name.len() as u64)?;
-                hasher.update(name.as_bytes());
-
-                // \000 for nodes, and \001 for contents.
                 let dir_entry = storage.get_dir_entry(*dir_entry_id)?;
-                match dir_entry.dir_entry_kind() {
-                    DirEntryKind::Blob => hasher.update(&[1u8]),
-                    DirEntryKind::Directory => hasher.update(&[0u8]),
+
+                let hash = match dir_entry.get_inlined_blob(storage) {
+                    Some(blob) => hash_inlined_blob(blob)?,
+                    None => dir_entry.object_hash(store, storage, strings)?.into_owned(),
                 };
 
-                if let Some(blob) = dir_entry.get_inlined_blob(storage) {
-                    hasher.update(&hash_inlined_blob(blob)?);
-                } else {
-                    hasher.update(dir_entry.object_hash(store, storage, strings)?.as_ref());
-                }
+                update_inode_value_entry(&mut hasher, &name, &dir_entry.dir_entry_kind(), &hash)?;
             }
         }
         DirectoryOrInodeId::Inode(inode_id) => {
@@ -172,33 +248,11 @@ fn hash_long_inode(
                 ..
             } = storage.get_inode(inode_id)?;
 
-            let npointers = pointers.npointers();
-
-            // Inode directory:
-            //
-            // |   1    | (LEB128) |   (LEB128)    |    1   |  33  | ... |  33  |
-            // +--------+----------+---------------+--------+------+-----+------+
-            // |  \001  |  depth   | len(children) |   \k   | s_1  | ... | s_k  |
-
-            hasher.update(&[1u8]); // type tag
-            leb128::write::unsigned(&mut hasher, *depth as u64)?;
-            leb128::write::unsigned(&mut hasher, *nchildren as u64)?;
-            hasher.update(&[npointers as u8]);
-
-            // Inode pointer:
-
-            //
-            // |    1    |   32   |
-            // +---------+--------+
-            // |  index  |  hash  |
+            update_inode_tree_header(&mut hasher, *depth as u32, *nchildren, pointers.npointers())?;
 
             for (ptr_index, thin_pointer_id) in pointers.iter() {
                 let pointer = storage.pointer_copy(thin_pointer_id)?;
 
-                let ptr_index: u8 = ptr_index as u8;
-
-                hasher.update(&[ptr_index]);
-
                 let hash_id = match storage.pointer_retrieve_hashid(&pointer, store)? {
                     Some(hash_id) => hash_id,
                     None => {
@@ -213,7 +267,7 @@ fn hash_long_inode(
 
                 let hash = store.get_hash(ObjectReference::new(Some(hash_id), None))?;
 
-                hasher.update(hash.as_ref());
+                update_inode_pointer(&mut hasher, ptr_index as u8, hash.as_ref());
             }
         }
     }
@@ -238,36 +292,19 @@ fn hash_short_inode(
 ) -> Result<HashId, HashingError> {
     let mut hasher = VarBlake2b::new(OBJECT_HASH_LEN)?;
 
-    // DirEntry list:
-    //
-    // |    8   |     n_1      | ... |      n_k     |
-    // +--------+--------------+-----+--------------+
-    // |   \k   | prehash(e_1) | ... | prehash(e_k) |
-
     let dir = storage.get_small_dir(dir_id)?;
-    hasher.update(&(dir.len() as u64).to_be_bytes());
-
-    // DirEntry object:
-    //
-    // |   8   |   (LEB128)   |  len(name)  |   8   |   32   |
-    // +-------+--------------+-------------+-------+--------+
-    // | kind  |  \len(name)  |    name     |  \32  |  hash  |
+    update_short_inode_header(&mut hasher, dir.len());
 
     for (k, v) in dir.as_ref() {
         let v = storage.get_dir_entry(*v)?;
-        hasher.update(encode_irmin_dir_entry_kind(&v.dir_entry_kind()));
-        // Key length is written in LEB128 encoding
-
         let k = strings.get_str(*k)?;
-        leb128::write::unsigned(&mut hasher, k.len() as u64)?;
-        hasher.update(k.as_bytes());
-        hasher.update(&(OBJECT_HASH_LEN as u64).to_be_bytes());
-
-        if let Some(blob) = v.get_inlined_blob(storage) {
-            hasher.update(&hash_inlined_blob(blob)?);
-        } else {
-            hasher.update(v.object_hash(store, storage, strings)?.as_ref());
-        }
+
+        let hash = match v.get_inlined_blob(storage) {
+            Some(blob) => hash_inlined_blob(blob)?,
+            None => v.object_hash(store, storage, strings)?.into_owned(),
+        };
+
+        update_short_inode_entry(&mut hasher, &k, &v.dir_entry_kind(), &hash)?;
     }
 
     let hash_id = store
@@ -321,15 +358,17 @@ pub(crate) fn hash_blob(
 // uses BLAKE2 binary 256 length hash function
 // hash is calculated as <length of data (8 bytes)><data>
 pub(crate) fn hash_inlined_blob(blob: Blob) -> Result<ObjectHash, HashingError> {
+    hash_blob_bytes(&blob)
+}
+
+// See `hash_inlined_blob`
+pub(crate) fn hash_blob_bytes(blob: &[u8]) -> Result<ObjectHash, HashingError> {
     let mut hasher = VarBlake2b::new(OBJECT_HASH_LEN)?;
 
     hasher.update(&(blob.len() as u64).to_be_bytes());
     hasher.update(blob);
 
-    let mut object_hash: ObjectHash = Default::default();
-    hasher.finalize_variable(|r| object_hash.copy_from_slice(r));
-
-    Ok(object_hash)
+    Ok(finalize_hash(hasher))
 }
 
 // Calculates hash of commit
@@ -344,32 +383,56 @@ pub(crate) fn hash_commit(
     commit: &Commit,
     store: &mut ContextKeyValueStore,
 ) -> Result<HashId, HashingError> {
+    let commit_hash = {
+        let root_hash = store.get_hash(commit.root_ref)?;
+        let parent_commit_hash = match commit.parent_commit_ref {
+            Some(parent) => Some(store.get_hash(parent)?),
+            None => None,
+        };
+
+        hash_commit_fields(
+            root_hash.as_ref(),
+            parent_commit_hash.as_ref().map(|hash| hash.as_ref()),
+            commit.time,
+            &commit.author,
+            &commit.message,
+        )?
+    };
+
+    let hash_id = store
+        .get_vacant_object_hash()?
+        .write_with(|object| object.copy_from_slice(&commit_hash))?;
+
+    Ok(hash_id)
+}
+
+// See `hash_commit`
+pub(crate) fn hash_commit_fields(
+    root_hash: &[u8],
+    parent_commit_hash: Option<&[u8]>,
+    time: u64,
+    author: &str,
+    message: &str,
+) -> Result<ObjectHash, HashingError> {
     let mut hasher = VarBlake2b::new(OBJECT_HASH_LEN)?;
     hasher.update(&(OBJECT_HASH_LEN as u64).to_be_bytes());
+    hasher.update(root_hash);
 
-    let root_hash = store.get_hash(commit.root_ref)?;
-    hasher.update(root_hash.as_ref());
-
-    if let Some(parent) = commit.parent_commit_ref {
-        let parent_commit_hash = store.get_hash(parent)?;
+    if let Some(parent_commit_hash) = parent_commit_hash {
         hasher.update(&(1_u64).to_be_bytes()); // # of parents; we support only 1
         hasher.update(&(parent_commit_hash.len() as u64).to_be_bytes());
-        hasher.update(&parent_commit_hash.as_ref());
+        hasher.update(parent_commit_hash);
     } else {
         hasher.update(&(0_u64).to_be_bytes());
     }
 
-    hasher.update(&(commit.time as u64).to_be_bytes());
-    hasher.update(&(commit.author.len() as u64).to_be_bytes());
-    hasher.update(&commit.author.clone().into_bytes());
-    hasher.update(&(commit.message.len() as u64).to_be_bytes());
-    hasher.update(&commit.message.clone().into_bytes());
+    hasher.update(&time.to_be_bytes());
+    hasher.update(&(author.len() as u64).to_be_bytes());
+    hasher.update(author.as_bytes());
+    hasher.update(&(message.len() as u64).to_be_bytes());
+    hasher.update(message.as_bytes());
 
-    let hash_id = store
-        .get_vacant_object_hash()?
-        .write_with(|object| hasher.finalize_variable(|r| object.copy_from_slice(r)))?;
-
-    Ok(hash_id)
+    Ok(finalize_hash(hasher))
 }
 
 pub(crate) fn hash_object(
@@ -23,7 +23,13 @@ use tezos_timing::{RepositoryMemoryUsage, SerializeStats};
 
 use crate::{
     chunks::ChunkedVec,
-    gc::NotGarbageCollected,
+    gc::{
+        persistent::{
+            self as garbage_collection, CollectionJob, GarbageCollectionRecord,
+            PersistentGarbageCollector,
+        },
+        GarbageCollectionError, GarbageCollector,
+    },
     hash::OBJECT_HASH_LEN,
     initializer::IndexInitializationError,
     persistent::{
@@ -134,6 +140,15 @@ pub struct Persistent {
     startup_check: bool,
     lastest_commits_on_startup: VecDeque<ObjectReference>,
     read_statistics: Option<Mutex<ReadStatistics>>,
+    base_path: String,
+    /// `None` when the garbage collector is disabled, or in read mode
+    ///
+    /// See `gc::persistent`
+    garbage_collector: Option<PersistentGarbageCollector>,
+    /// Last record of `gc.db` read in read mode, with the generation it was read at
+    ///
+    /// See `garbage_collection::records_generation`
+    last_gc_record: RwLock<(u64, Option<GarbageCollectionRecord>)>,
 }
 
 impl Drop for Persistent {
@@ -142,7 +157,37 @@ impl Drop for Persistent {
     }
 }
 
-impl NotGarbageCollected for Persistent {}
+impl GarbageCollector for Persistent {
+    fn new_cycle_started(&mut self) -> Result<(), GarbageCollectionError> {
+        let data_offset = self.data_file.offset();
+        let hashes_offset = self.hashes.hashes_file.offset();
+
+        let boundaries = match self.garbage_collector.as_mut() {
+            Some(gc) => gc.cycle_started(data_offset, hashes_offset),
+            None => return Ok(()),
+        };
+
+        match boundaries {
+            Some((data_boundary, hashes_boundary)) => {
+                self.start_garbage_collection(data_boundary, hashes_boundary)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn block_applied(
+        &mut self,
+        _block_level: u32,
+        context_hash: &ContextHash,
+    ) -> Result<(), GarbageCollectionError> {
+        match self.garbage_collector.as_mut() {
+            Some(gc) => gc.set_head(context_hash.clone()),
+            None => return Ok(()),
+        };
+
+        self.poll_garbage_collection()
+    }
+}
 
 impl Flushable for Persistent {
     fn flush(&self) -> Result<(), anyhow::Error> {
@@ -262,6 +307,12 @@ impl Persistent {
             None
         };
 
+        let garbage_collector = if !read_mode {
+            garbage_collection::configured_preserve_cycles().map(PersistentGarbageCollector::new)
+        } else {
+            None
+        };
+
         let sizes_file = File::<{ TAG_SIZES }>::try_new(&base_path, read_mode)?;
         let data_file = File::<{ TAG_DATA }>::try_new(&base_path, read_mode)?;
         let shape_file = File::<{ TAG_SHAPE }>::try_new(&base_path, read_mode)?;
@@ -294,6 +345,9 @@ impl Persistent {
             } else {
                 None
             },
+            base_path,
+            garbage_collector,
+            last_gc_record: RwLock::new((0, None)),
         })
     }
 
@@ -338,6 +392,7 @@ impl Persistent {
         big_strings_file: &mut File<{ TAG_BIG_STRINGS }>,
         hashes_file: &mut File<{ TAG_HASHES }>,
         startup_check: bool,
+        gc_interrupted: bool,
     ) -> Result<u64, IndexInitializationError> {
         let list_sizes = match list_sizes {
             Some(list) if !list.is_empty() => list,
@@ -467,6 +522,13 @@ hashes_file={:?}, in sizes.db={:?}",
                 }
                 log!("shape crc computed in {:?}", now.elapsed());
 
+                if gc_interrupted {
+                    // The garbage collector modified `hashes.db` and `data.db` but did not
+                    // update their checksums, they are recomputed after truncation
+                    last_valid = Some(sizes.clone());
+                    continue;
+                }
+
                 let now = std::time::Instant::now();
                 if hashes_file.update_checksum_until(sizes.hashes_size)? != sizes.hashes_checksum {
                     elog!(
@@ -621,6 +683,10 @@ hashes_file={:?}, in sizes.db={:?}",
         self.data_file.offset()
     }
 
+    pub fn hashes_file_offset(&self) -> AbsoluteOffset {
+        self.hashes.hashes_file.offset()
+    }
+
     fn update_sizes_to_disk(
         &mut self,
         output: Option<&mut File<{ TAG_SIZES }>>,
@@ -648,6 +714,9 @@ hashes_file={:?}, in sizes.db={:?}",
     pub fn reload_database(&mut self) -> Result<(), IndexInitializationError> {
         let list_sizes = FileSizes::make_list_from_file(&self.sizes_file);
 
+        let gc_record = garbage_collection::read_last_record(&self.base_path);
+        let gc_interrupted = gc_record.map(|r| !r.completed).unwrap_or(false);
+
         let commit_counter = Self::truncate_files_with_correct_sizes(
             list_sizes.as_ref().map(AsRef::as_ref),
             &mut self.data_file,
@@ -658,6 +727,7 @@ hashes_file={:?}, in sizes.db={:?}",
             &mut self.big_strings_file,
             &mut self.hashes.hashes_file,
             self.startup_check,
+            gc_interrupted,
         )?;
 
         // Clone the `File` to deserialize them in other threads
@@ -705,12 +775,185 @@ hashes_file={:?}, in sizes.db={:?}",
                     reason: format!("{:?}", e),
                 })??;
 
+        let DeserializedCommitIndex {
+            index: mut context_hashes,
+            last_commits: mut lastest_commits_on_startup,
+        } = context_hashes;
+
+        if let Some(gc_record) = gc_record.as_ref() {
+            // Commits below the garbage collector boundary are not readable anymore
+            context_hashes.retain(|_, commit_ref| gc_record.is_commit_alive(commit_ref));
+            lastest_commits_on_startup.retain(|commit_ref| gc_record.is_commit_alive(commit_ref));
+        }
+
         self.shapes = shapes;
         self.string_interner = string_interner;
-        self.context_hashes = context_hashes.index;
-        self.lastest_commits_on_startup = context_hashes.last_commits;
+        self.context_hashes = context_hashes;
+        self.lastest_commits_on_startup = lastest_commits_on_startup;
         self.commit_counter = commit_counter;
 
+        // We don't use a lock file when the repository is opened in read mode
+        let read_mode = self.lock_file.is_none();
+
+        if let (Some(gc_record), true) = (gc_record, gc_interrupted && !read_mode) {
+            log!("The last garbage collection was interrupted, recomputing checksums");
+
+            self.data_file.reset_checksum();
+            self.data_file
+                .update_checksum_until(self.data_file.offset().as_u64())?;
+            self.hashes.hashes_file.reset_checksum();
+            self.hashes
+                .hashes_file
+                .update_checksum_until(self.hashes.hashes_file.offset().as_u64())?;
+
+            self.rewrite_all_sizes()?;
+            garbage_collection::append_record(
+                &self.base_path,
+                &GarbageCollectionRecord {
+                    completed: true,
+                    ..gc_record
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the current sizes and checksums on every line of `sizes.db`
+    ///
+    /// This is used after `data.db` and `hashes.db` were modified by the garbage
+    /// collector: the previous lines have checksums which will never match.
+    fn rewrite_all_sizes(&mut self) -> Result<(), std::io::Error> {
+        for _ in 0..SIZES_NUMBER_OF_LINES {
+            self.update_sizes_to_disk(None)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the last record of `gc.db`, read again only when a record has been
+    /// appended since the previous call
+    fn last_gc_record(&self) -> Option<GarbageCollectionRecord> {
+        let generation = garbage_collection::records_generation(&self.base_path);
+
+        {
+            let last_gc_record = self.last_gc_record.read();
+            if last_gc_record.0 == generation {
+                return last_gc_record.1;
+            }
+        }
+
+        let gc_record = garbage_collection::read_last_record(&self.base_path);
+        *self.last_gc_record.write() = (generation, gc_record);
+        gc_record
+    }
+
+    fn start_garbage_collection(
+        &mut self,
+        data_boundary: AbsoluteOffset,
+        hashes_boundary: AbsoluteOffset,
+    ) -> Result<(), GarbageCollectionError> {
+        let head = match self.garbage_collector.as_ref() {
+            Some(gc) if gc.is_running() => {
+                log!("Previous garbage collection still running, skipping this cycle");
+                return Ok(());
+            }
+            Some(gc) => match gc.head() {
+                Some(head) => head.clone(),
+                None => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        let job = CollectionJob {
+            base_path: self.base_path.clone(),
+            head,
+            data_boundary,
+            hashes_boundary,
+            data_file: self.data_file.try_clone().map_err(DBError::from)?,
+            hashes_file: self
+                .hashes
+                .hashes_file
+                .try_clone()
+                .map_err(DBError::from)?,
+        };
+
+        // Commits below the boundary are about to be collected, they must not be
+        // checked out anymore. The root will be indexed again once it's known.
+        let gc_record = GarbageCollectionRecord {
+            data_boundary: data_boundary.as_u64(),
+            hashes_boundary: hashes_boundary.as_u64(),
+            root_offset: 0,
+            completed: false,
+        };
+        self.context_hashes
+            .retain(|_, commit_ref| gc_record.is_commit_alive(commit_ref));
+        self.lastest_commits_on_startup
+            .retain(|commit_ref| gc_record.is_commit_alive(commit_ref));
+
+        self.data_file.start_partial_checksum();
+        self.hashes.hashes_file.start_partial_checksum();
+
+        let spawned = match self.garbage_collector.as_mut() {
+            Some(gc) => gc.spawn(job),
+            None => return Ok(()),
+        };
+
+        if let Err(e) = spawned {
+            elog!("Failed to spawn the garbage collector thread: {:?}", e);
+            self.data_file.cancel_partial_checksum();
+            self.hashes.hashes_file.cancel_partial_checksum();
+        }
+
+        Ok(())
+    }
+
+    /// Apply the result of the garbage collection, when it's done
+    fn poll_garbage_collection(&mut self) -> Result<(), GarbageCollectionError> {
+        let result = match self
+            .garbage_collector
+            .as_mut()
+            .and_then(|gc| gc.try_take_result())
+        {
+            Some(result) => result,
+            None => return Ok(()),
+        };
+
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                // If the files were already modified, the checksums will be
+                // recomputed on reload (the record in `gc.db` is not completed)
+                elog!("Persistent garbage collection failed: {:?}", e);
+                self.data_file.cancel_partial_checksum();
+                self.hashes.hashes_file.cancel_partial_checksum();
+                return Ok(());
+            }
+        };
+
+        self.data_file
+            .replace_checksum_prefix(outcome.data_checksum);
+        self.hashes
+            .hashes_file
+            .replace_checksum_prefix(outcome.hashes_checksum);
+
+        // The root is still alive
+        let mut hasher = DefaultHasher::new();
+        hasher.write(outcome.root_hash.as_ref());
+        let hashed = hasher.finish();
+        self.context_hashes.insert(hashed, outcome.root_ref);
+
+        self.rewrite_all_sizes().map_err(DBError::from)?;
+        garbage_collection::append_record(
+            &self.base_path,
+            &GarbageCollectionRecord {
+                completed: true,
+                ..outcome.record
+            },
+        )
+        .map_err(DBError::from)?;
+
+        log!("{:?}", outcome.stats);
+
         Ok(())
     }
 
@@ -1002,7 +1245,22 @@ impl KeyValueStoreBackend for Persistent {
         hasher.write(context_hash.as_ref());
         let hashed = hasher.finish();
 
-        Ok(self.context_hashes.get(&hashed).cloned())
+        let commit_ref = match self.context_hashes.get(&hashed) {
+            Some(commit_ref) => *commit_ref,
+            None => return Ok(None),
+        };
+
+        // In read mode, the repository is not notified of the garbage collections
+        // made by the writer: read its last record to not return a collected commit.
+        if self.lock_file.is_none() {
+            if let Some(gc_record) = self.last_gc_record() {
+                if !gc_record.is_commit_alive(&commit_ref) {
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some(commit_ref))
     }
 
     fn get_hash(&self, object_ref: ObjectReference) -> Result<Cow<ObjectHash>, DBError> {
@@ -132,6 +132,7 @@ macro_rules! elog {
 pub mod chunks;
 pub mod gc;
 pub mod hash;
+pub mod merkle_proof;
 pub mod serialize;
 pub mod working_tree;
 
@@ -154,6 +155,7 @@ use gc::GarbageCollectionError;
 pub use kv_store::persistent::Persistent;
 
 use persistent::{DBError, KeyValueStoreBackend};
+use tezos_context_api::merkle_proof::MerkleProof;
 use tezos_context_api::{ContextKey, ContextKeyOwned, ContextValue, StringTreeObject};
 use thiserror::Error;
 
@@ -249,6 +251,12 @@ pub trait IndexApi<T: ShellContextApi + ProtocolContextApi> {
         prefix: &ContextKey,
         depth: Option<usize>,
     ) -> Result<StringTreeObject, ContextError>;
+    // build a proof that the key is in the commit, returns None when the key doesn't exist
+    fn get_merkle_proof(
+        &self,
+        context_hash: &ContextHash,
+        key: &ContextKey,
+    ) -> Result<Option<MerkleProof>, ContextError>;
 }
 
 /// Context API used by the Shell
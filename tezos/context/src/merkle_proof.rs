@@ -0,0 +1,700 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Build and verify inclusion proofs of keys in the context.
+//!
+//! See `tezos_context_api::merkle_proof` for the format of a proof.
+//!
+//! The hashes are computed with the encodings of `crate::hash`:
+//! - Small directories (`ProofStep::Directory`) are hashed like in `hash_short_inode`
+//! - Inodes (`ProofStep::InodeTree` and `ProofStep::InodeValues`) are hashed like in `hash_long_inode`
+
+use std::path::Path;
+
+use blake2::VarBlake2b;
+use crypto::hash::ContextHash;
+use ipc::{IpcError, IpcServer};
+use slog::{error, warn, Logger};
+use tezos_context_api::merkle_proof::{
+    MerkleProof, MerkleProofRequest, MerkleProofResponse, ProofCommit, ProofEntry, ProofEntryKind,
+    ProofHash, ProofPointer, ProofStep, ProofTarget,
+};
+use tezos_context_api::ContextKey;
+use thiserror::Error;
+
+use crate::{
+    ffi::TezedgeIndexError,
+    hash::{
+        finalize_hash, hash_blob_bytes, hash_commit_fields, hash_inlined_blob, index,
+        update_inode_pointer, update_inode_tree_header, update_inode_value_entry,
+        update_inode_value_header, update_short_inode_entry, update_short_inode_header,
+        HashingError, ObjectHash, OBJECT_HASH_LEN,
+    },
+    working_tree::{
+        storage::{DirectoryId, DirectoryOrInodeId, Inode, InodeId, Storage},
+        string_interner::StringInterner,
+        working_tree::MerkleError,
+        DirEntry, DirEntryKind, Object, ObjectReference,
+    },
+    ContextKeyValueStore, IndexApi, TezedgeIndex,
+};
+
+#[derive(Debug, Error)]
+pub enum MerkleProofError {
+    #[error("The proof doesn't match the key: {reason}")]
+    KeyMismatch { reason: String },
+    #[error("Invalid proof: {reason}")]
+    InvalidProof { reason: String },
+    #[error("Hash mismatch, expected={expected} computed={computed}")]
+    HashMismatch { expected: String, computed: String },
+    #[error("Hashing error: {error}")]
+    HashingError {
+        #[from]
+        error: HashingError,
+    },
+}
+
+fn invalid_proof(reason: &str) -> MerkleProofError {
+    MerkleProofError::InvalidProof {
+        reason: reason.to_string(),
+    }
+}
+
+fn proof_entry_kind(kind: DirEntryKind) -> ProofEntryKind {
+    match kind {
+        DirEntryKind::Directory => ProofEntryKind::Directory,
+        DirEntryKind::Blob => ProofEntryKind::Value,
+    }
+}
+
+fn dir_entry_kind(kind: ProofEntryKind) -> DirEntryKind {
+    match kind {
+        ProofEntryKind::Directory => DirEntryKind::Directory,
+        ProofEntryKind::Value => DirEntryKind::Blob,
+    }
+}
+
+fn fetch_hash(
+    object_ref: ObjectReference,
+    repository: &ContextKeyValueStore,
+) -> Result<ProofHash, MerkleError> {
+    Ok(ProofHash(repository.get_hash(object_ref)?.into_owned()))
+}
+
+fn dir_entry_hash(
+    dir_entry: &DirEntry,
+    storage: &Storage,
+    repository: &ContextKeyValueStore,
+) -> Result<ProofHash, MerkleError> {
+    match dir_entry.get_inlined_blob(storage) {
+        Some(blob) => Ok(ProofHash(hash_inlined_blob(blob)?)),
+        None => fetch_hash(dir_entry.get_reference(), repository),
+    }
+}
+
+/// Returns the entries of the small directory `dir_id`, without the hash of `name`.
+///
+/// Returns `None` when `name` is not in the directory.
+fn directory_entries(
+    dir_id: DirectoryId,
+    name: &str,
+    storage: &Storage,
+    strings: &StringInterner,
+    repository: &ContextKeyValueStore,
+) -> Result<Option<Vec<ProofEntry>>, MerkleError> {
+    let dir = storage.get_small_dir(dir_id)?;
+
+    let mut entries = Vec::with_capacity(dir.len());
+    let mut found = false;
+
+    for (key_id, dir_entry_id) in dir.as_ref() {
+        let key = strings.get_str(*key_id)?;
+        let dir_entry = storage.get_dir_entry(*dir_entry_id)?;
+
+        let hash = if key.as_ref() == name {
+            found = true;
+            None
+        } else {
+            Some(dir_entry_hash(dir_entry, storage, repository)?)
+        };
+
+        entries.push(ProofEntry {
+            name: key.to_string(),
+            kind: proof_entry_kind(dir_entry.dir_entry_kind()),
+            hash,
+        });
+    }
+
+    Ok(if found { Some(entries) } else { None })
+}
+
+fn inode_step(
+    inode_id: InodeId,
+    name: &str,
+    storage: &Storage,
+    repository: &ContextKeyValueStore,
+) -> Result<ProofStep, MerkleError> {
+    let Inode {
+        depth,
+        nchildren,
+        pointers,
+    } = storage.get_inode(inode_id)?;
+
+    let hole_index = index(*depth as u32, name) as usize;
+    let mut proof_pointers = Vec::with_capacity(pointers.npointers());
+
+    for (ptr_index, thin_pointer_id) in pointers.iter() {
+        let hash = if ptr_index == hole_index {
+            None
+        } else {
+            let pointer = storage.pointer_copy(thin_pointer_id)?;
+            let hash_id = storage
+                .pointer_retrieve_hashid(&pointer, repository)?
+                .ok_or(HashingError::MissingPointer)?;
+
+            Some(fetch_hash(
+                ObjectReference::new(Some(hash_id), None),
+                repository,
+            )?)
+        };
+
+        proof_pointers.push(ProofPointer {
+            index: ptr_index as u8,
+            hash,
+        });
+    }
+
+    Ok(ProofStep::InodeTree {
+        depth: *depth as u32,
+        nchildren: *nchildren,
+        pointers: proof_pointers,
+    })
+}
+
+/// Build the proof that `key` is in the commit `commit_ref`.
+///
+/// Returns `None` when `key` doesn't exist.
+/// Use `TezedgeIndex::get_merkle_proof` to build a proof from a `ContextHash`.
+pub fn build_merkle_proof(
+    index: &TezedgeIndex,
+    commit_ref: ObjectReference,
+    key: &ContextKey,
+) -> Result<Option<MerkleProof>, MerkleError> {
+    let mut storage = index.storage.borrow_mut();
+    let mut strings = index.get_string_interner()?;
+
+    let commit = index.get_commit(commit_ref, &mut storage, &mut strings)?;
+    let mut dir_id = index.get_directory(commit.root_ref, &mut storage, &mut strings)?;
+
+    let (commit, mut target) = {
+        let repository = index.repository.read();
+
+        let parent_hash = match commit.parent_commit_ref {
+            Some(parent_ref) => Some(fetch_hash(parent_ref, &*repository)?),
+            None => None,
+        };
+
+        let root_hash = fetch_hash(commit.root_ref, &*repository)?;
+
+        let commit = ProofCommit {
+            parent_hash,
+            time: commit.time,
+            author: commit.author,
+            message: commit.message,
+        };
+
+        (commit, ProofTarget::Directory { hash: root_hash })
+    };
+
+    let mut steps = Vec::with_capacity(key.len() * 2);
+
+    for (key_index, name) in key.iter().enumerate() {
+        let dir_entry_id = {
+            let repository = index.repository.read();
+
+            let inodes = storage.dir_inodes_path(dir_id, name, &mut strings, &*repository)?;
+
+            for ptr_id in inodes {
+                let step = match ptr_id {
+                    DirectoryOrInodeId::Inode(inode_id) => {
+                        inode_step(inode_id, name, &storage, &*repository)?
+                    }
+                    DirectoryOrInodeId::Directory(small_dir_id) => {
+                        let entries = match directory_entries(
+                            small_dir_id,
+                            name,
+                            &storage,
+                            &strings,
+                            &*repository,
+                        )? {
+                            Some(entries) => entries,
+                            None => return Ok(None),
+                        };
+
+                        if dir_id.is_inode() {
+                            ProofStep::InodeValues { entries }
+                        } else {
+                            ProofStep::Directory { entries }
+                        }
+                    }
+                };
+                steps.push(step);
+            }
+
+            match storage.dir_find_dir_entry(dir_id, name, &mut strings, &*repository)? {
+                Some(dir_entry_id) => dir_entry_id,
+                None => return Ok(None),
+            }
+        };
+
+        let object = index.dir_entry_object(dir_entry_id, &mut storage, &mut strings)?;
+        let is_last = key_index == key.len() - 1;
+
+        match object {
+            Object::Directory(child_dir_id) if !is_last => {
+                dir_id = child_dir_id;
+            }
+            Object::Blob(_) if !is_last => return Ok(None),
+            Object::Directory(_) => {
+                let repository = index.repository.read();
+                let dir_entry = storage.get_dir_entry(dir_entry_id)?;
+
+                target = ProofTarget::Directory {
+                    hash: dir_entry_hash(dir_entry, &storage, &*repository)?,
+                };
+            }
+            Object::Blob(blob_id) => {
+                target = ProofTarget::Value {
+                    value: storage.get_blob(blob_id)?.to_vec(),
+                };
+            }
+            Object::Commit(_) => {
+                return Err(MerkleError::FoundUnexpectedStructure {
+                    sought: "Directory/Blob".to_string(),
+                    found: "Commit".to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(Some(MerkleProof {
+        commit,
+        steps,
+        target,
+    }))
+}
+
+/// Returns the hashes of `entries`, with `hole_hash` as the hash of the entry `name`.
+fn entries_with_hole<'a>(
+    entries: &'a [ProofEntry],
+    name: &str,
+    hole_kind: ProofEntryKind,
+    hole_hash: &'a ObjectHash,
+) -> Result<Vec<(&'a ProofEntry, &'a ObjectHash)>, MerkleProofError> {
+    let mut result = Vec::with_capacity(entries.len());
+    let mut nholes = 0;
+
+    for entry in entries {
+        match entry.hash.as_ref() {
+            Some(hash) => result.push((entry, &hash.0)),
+            None => {
+                if entry.name != name {
+                    return Err(MerkleProofError::KeyMismatch {
+                        reason: format!("expected {:?}, found {:?}", name, entry.name),
+                    });
+                }
+                if entry.kind != hole_kind {
+                    return Err(invalid_proof("Kind of the entry doesn't match"));
+                }
+                nholes += 1;
+                result.push((entry, hole_hash));
+            }
+        }
+    }
+
+    if nholes != 1 {
+        return Err(invalid_proof(
+            "A directory must have exactly 1 entry without hash",
+        ));
+    }
+
+    Ok(result)
+}
+
+fn hash_directory_step(
+    entries: &[ProofEntry],
+    name: &str,
+    hole_kind: ProofEntryKind,
+    hole_hash: &ObjectHash,
+) -> Result<ObjectHash, MerkleProofError> {
+    let entries = entries_with_hole(entries, name, hole_kind, hole_hash)?;
+    let mut hasher = VarBlake2b::new(OBJECT_HASH_LEN).map_err(HashingError::from)?;
+
+    update_short_inode_header(&mut hasher, entries.len());
+
+    for (entry, hash) in entries {
+        update_short_inode_entry(&mut hasher, &entry.name, &dir_entry_kind(entry.kind), hash)?;
+    }
+
+    Ok(finalize_hash(hasher))
+}
+
+fn hash_inode_values_step(
+    entries: &[ProofEntry],
+    name: &str,
+    hole_kind: ProofEntryKind,
+    hole_hash: &ObjectHash,
+) -> Result<ObjectHash, MerkleProofError> {
+    let entries = entries_with_hole(entries, name, hole_kind, hole_hash)?;
+    let mut hasher = VarBlake2b::new(OBJECT_HASH_LEN).map_err(HashingError::from)?;
+
+    update_inode_value_header(&mut hasher, entries.len());
+
+    for (entry, hash) in entries {
+        update_inode_value_entry(&mut hasher, &entry.name, &dir_entry_kind(entry.kind), hash)?;
+    }
+
+    Ok(finalize_hash(hasher))
+}
+
+fn hash_inode_tree_step(
+    depth: u32,
+    nchildren: u32,
+    pointers: &[ProofPointer],
+    name: &str,
+    hole_hash: &ObjectHash,
+) -> Result<ObjectHash, MerkleProofError> {
+    let hole_index = index(depth, name);
+    let mut hasher = VarBlake2b::new(OBJECT_HASH_LEN).map_err(HashingError::from)?;
+    let mut nholes = 0;
+
+    update_inode_tree_header(&mut hasher, depth, nchildren, pointers.len())?;
+
+    for pointer in pointers {
+        match pointer.hash.as_ref() {
+            Some(hash) => update_inode_pointer(&mut hasher, pointer.index, &hash.0),
+            None if pointer.index as u32 == hole_index => {
+                nholes += 1;
+                update_inode_pointer(&mut hasher, pointer.index, hole_hash);
+            }
+            None => {
+                return Err(MerkleProofError::KeyMismatch {
+                    reason: format!(
+                        "expected pointer {} for {:?}, found {}",
+                        hole_index, name, pointer.index
+                    ),
+                })
+            }
+        }
+    }
+
+    if nholes != 1 {
+        return Err(invalid_proof(
+            "An inode must have exactly 1 pointer without hash",
+        ));
+    }
+
+    Ok(finalize_hash(hasher))
+}
+
+/// Verify that `proof` proves that its target is at `key` in the commit `context_hash`.
+///
+/// This doesn't require access to the context.
+pub fn verify_merkle_proof(
+    proof: &MerkleProof,
+    context_hash: &ContextHash,
+    key: &ContextKey,
+) -> Result<(), MerkleProofError> {
+    // Find the key segment looked up by each step.
+    // A directory is either a `ProofStep::Directory`, or one or more
+    // `ProofStep::InodeTree` followed by a `ProofStep::InodeValues`
+    let mut names = Vec::with_capacity(proof.steps.len());
+    let mut key_index = 0;
+    let mut in_inodes = false;
+
+    for step in &proof.steps {
+        let name = key
+            .get(key_index)
+            .ok_or_else(|| MerkleProofError::KeyMismatch {
+                reason: "The proof is longer than the key".to_string(),
+            })?;
+
+        match step {
+            ProofStep::Directory { .. } if !in_inodes => key_index += 1,
+            ProofStep::InodeValues { .. } if in_inodes => {
+                key_index += 1;
+                in_inodes = false;
+            }
+            ProofStep::InodeTree { .. } => in_inodes = true,
+            _ => return Err(invalid_proof("Unexpected step")),
+        }
+
+        names.push(*name);
+    }
+
+    if in_inodes {
+        return Err(invalid_proof("The proof ends in an inode"));
+    }
+
+    if key_index != key.len() {
+        return Err(MerkleProofError::KeyMismatch {
+            reason: "The proof is shorter than the key".to_string(),
+        });
+    }
+
+    let (mut hash, mut kind) = match &proof.target {
+        ProofTarget::Value { value } => (hash_blob_bytes(value)?, ProofEntryKind::Value),
+        ProofTarget::Directory { hash } => (hash.0, ProofEntryKind::Directory),
+    };
+
+    for (step, name) in proof.steps.iter().zip(names).rev() {
+        hash = match step {
+            ProofStep::Directory { entries } => {
+                let hash = hash_directory_step(entries, name, kind, &hash)?;
+                kind = ProofEntryKind::Directory;
+                hash
+            }
+            ProofStep::InodeValues { entries } => {
+                let hash = hash_inode_values_step(entries, name, kind, &hash)?;
+                kind = ProofEntryKind::Directory;
+                hash
+            }
+            ProofStep::InodeTree {
+                depth,
+                nchildren,
+                pointers,
+            } => hash_inode_tree_step(*depth, *nchildren, pointers, name, &hash)?,
+        };
+    }
+
+    if kind != ProofEntryKind::Directory {
+        return Err(invalid_proof("The root of the commit must be a directory"));
+    }
+
+    let commit_hash = hash_commit_fields(
+        &hash,
+        proof
+            .commit
+            .parent_hash
+            .as_ref()
+            .map(|hash| hash.0.as_ref()),
+        proof.commit.time,
+        &proof.commit.author,
+        &proof.commit.message,
+    )?;
+
+    if context_hash.as_ref().as_slice() != commit_hash.as_ref() {
+        return Err(MerkleProofError::HashMismatch {
+            expected: context_hash.to_base58_check(),
+            computed: hex::encode(commit_hash),
+        });
+    }
+
+    Ok(())
+}
+
+// IPC
+
+/// Serves the merkle proofs requested by the node on `socket_path`.
+///
+/// The proofs are built by the protocol runner, the OCaml IPC loop doesn't know about them.
+/// `get_index` gives access to the context, each connection is served by its own thread.
+pub fn spawn_merkle_proof_server<P: AsRef<Path>>(
+    socket_path: P,
+    get_index: fn() -> Result<Option<TezedgeIndex>, TezedgeIndexError>,
+    log: Logger,
+) -> Result<(), IpcError> {
+    // Remove file first, otherwise bind will fail.
+    std::fs::remove_file(&socket_path).ok();
+
+    let mut server: IpcServer<MerkleProofRequest, MerkleProofResponse> =
+        IpcServer::bind_path(socket_path)?;
+
+    std::thread::Builder::new()
+        .name("merkle-proof-server".to_string())
+        .spawn(move || loop {
+            let (mut rx, mut tx) = match server.accept() {
+                Ok(connection) => connection,
+                Err(err) => {
+                    error!(log, "Error accepting merkle proof connection"; "reason" => format!("{:?}", err));
+                    continue;
+                }
+            };
+
+            let log = log.clone();
+            let spawned = std::thread::Builder::new()
+                .name("merkle-proof-connection".to_string())
+                .spawn(move || {
+                    // Ends when the node closes the connection
+                    while let Ok(request) = rx.receive() {
+                        let response = get_merkle_proof(get_index, request);
+
+                        if let Err(err) = tx.send(&response) {
+                            warn!(log, "Failed to send merkle proof"; "reason" => format!("{:?}", err));
+                            break;
+                        }
+                    }
+                });
+
+            if let Err(err) = spawned {
+                error!(log, "Failed to spawn merkle proof connection thread"; "reason" => format!("{:?}", err));
+            }
+        })
+        .map_err(|reason| IpcError::ThreadError { reason })?;
+
+    Ok(())
+}
+
+fn get_merkle_proof(
+    get_index: fn() -> Result<Option<TezedgeIndex>, TezedgeIndexError>,
+    request: MerkleProofRequest,
+) -> MerkleProofResponse {
+    let index = get_index()
+        .map_err(|err| format!("{:?}", err))?
+        .ok_or_else(|| "Context index unavailable".to_string())?;
+    let key: Vec<&str> = request.key.iter().map(String::as_str).collect();
+
+    index
+        .get_merkle_proof(&request.context_hash, &key)
+        .map_err(|err| format!("{}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use tezos_context_api::{
+        ContextKvStoreConfiguration, TezosContextTezEdgeStorageConfiguration,
+        TezosContextTezedgeOnDiskBackendOptions,
+    };
+
+    use super::*;
+    use crate::initializer::initialize_tezedge_context;
+    use crate::{IndexApi, ProtocolContextApi, ShellContextApi};
+
+    #[test]
+    fn test_merkle_proof() {
+        let context = initialize_tezedge_context(&TezosContextTezEdgeStorageConfiguration {
+            backend: ContextKvStoreConfiguration::InMem(TezosContextTezedgeOnDiskBackendOptions {
+                base_path: "".to_string(),
+                startup_check: false,
+            }),
+            ipc_socket_path: None,
+        })
+        .unwrap();
+
+        let mut context = context.add(&["a", "b", "c"], &[1, 2, 3]).unwrap();
+        context = context.add(&["a", "d"], &[4; 100]).unwrap();
+
+        // Large directory, stored as inodes
+        for i in 0..1000 {
+            let name = format!("{}", i);
+            context = context.add(&["big", &name], &[i as u8]).unwrap();
+        }
+
+        let context_hash = context
+            .commit("author".to_string(), "message".to_string(), 0)
+            .unwrap();
+        let index = &context.index;
+
+        let proof = index
+            .get_merkle_proof(&context_hash, &["a", "b", "c"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            proof.target,
+            ProofTarget::Value {
+                value: vec![1, 2, 3]
+            }
+        );
+        verify_merkle_proof(&proof, &context_hash, &["a", "b", "c"]).unwrap();
+        assert!(verify_merkle_proof(&proof, &context_hash, &["a", "b", "d"]).is_err());
+
+        let proof = index
+            .get_merkle_proof(&context_hash, &["big", "542"])
+            .unwrap()
+            .unwrap();
+        assert!(proof
+            .steps
+            .iter()
+            .any(|step| matches!(step, ProofStep::InodeTree { .. })));
+        verify_merkle_proof(&proof, &context_hash, &["big", "542"]).unwrap();
+
+        // Tampered value
+        let mut tampered = proof.clone();
+        tampered.target = ProofTarget::Value { value: vec![0] };
+        assert!(verify_merkle_proof(&tampered, &context_hash, &["big", "542"]).is_err());
+
+        // Proof of a directory
+        let proof = index
+            .get_merkle_proof(&context_hash, &["a"])
+            .unwrap()
+            .unwrap();
+        assert!(matches!(proof.target, ProofTarget::Directory { .. }));
+        verify_merkle_proof(&proof, &context_hash, &["a"]).unwrap();
+
+        // Proof of the root
+        let proof = index.get_merkle_proof(&context_hash, &[]).unwrap().unwrap();
+        assert!(proof.steps.is_empty());
+        verify_merkle_proof(&proof, &context_hash, &[]).unwrap();
+
+        // Missing keys
+        assert!(index
+            .get_merkle_proof(&context_hash, &["a", "z"])
+            .unwrap()
+            .is_none());
+        assert!(index
+            .get_merkle_proof(&context_hash, &["a", "b", "c", "d"])
+            .unwrap()
+            .is_none());
+    }
+
+    fn committed_context() -> (TezedgeIndex, ContextHash) {
+        let context = initialize_tezedge_context(&TezosContextTezEdgeStorageConfiguration {
+            backend: ContextKvStoreConfiguration::InMem(TezosContextTezedgeOnDiskBackendOptions {
+                base_path: "".to_string(),
+                startup_check: false,
+            }),
+            ipc_socket_path: None,
+        })
+        .unwrap();
+
+        let context = context.add(&["a", "b"], &[1, 2]).unwrap();
+        let context_hash = context
+            .commit("author".to_string(), "message".to_string(), 0)
+            .unwrap();
+
+        (context.index, context_hash)
+    }
+
+    #[test]
+    fn test_merkle_proof_server() {
+        let socket_path = ipc::temp_sock();
+        spawn_merkle_proof_server(
+            &socket_path,
+            || Ok(Some(committed_context().0)),
+            slog::Logger::root(slog::Discard, slog::o!()),
+        )
+        .unwrap();
+
+        let (_, context_hash) = committed_context();
+        let client: ipc::IpcClient<MerkleProofResponse, MerkleProofRequest> =
+            ipc::IpcClient::new(&socket_path);
+        let (mut rx, mut tx) = client.connect().unwrap();
+
+        tx.send(&MerkleProofRequest {
+            context_hash: context_hash.clone(),
+            key: vec!["a".to_string(), "b".to_string()],
+        })
+        .unwrap();
+        let proof = rx.receive().unwrap().unwrap().unwrap();
+        assert_eq!(proof.target, ProofTarget::Value { value: vec![1, 2] });
+        verify_merkle_proof(&proof, &context_hash, &["a", "b"]).unwrap();
+
+        // Missing key, on the same connection
+        tx.send(&MerkleProofRequest {
+            context_hash,
+            key: vec!["z".to_string()],
+        })
+        .unwrap();
+        assert!(rx.receive().unwrap().unwrap().is_none());
+    }
+}
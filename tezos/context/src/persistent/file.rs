@@ -28,6 +28,7 @@ pub enum FileType {
     BigStrings,
     Hashes,
     Sizes,
+    GarbageCollection,
 }
 
 type TaggedFile = u64;
@@ -40,6 +41,7 @@ pub const TAG_STRINGS: u64 = 4;
 pub const TAG_BIG_STRINGS: u64 = 5;
 pub const TAG_HASHES: u64 = 6;
 pub const TAG_SIZES: u64 = 7;
+pub const TAG_GARBAGE_COLLECTION: u64 = 8;
 
 impl From<FileType> for u64 {
     fn from(file_type: FileType) -> Self {
@@ -52,6 +54,7 @@ impl From<FileType> for u64 {
             FileType::BigStrings => TAG_BIG_STRINGS,
             FileType::Hashes => TAG_HASHES,
             FileType::Sizes => TAG_SIZES,
+            FileType::GarbageCollection => TAG_GARBAGE_COLLECTION,
         }
     }
 }
@@ -67,6 +70,7 @@ impl From<u64> for FileType {
             TAG_BIG_STRINGS => FileType::BigStrings,
             TAG_HASHES => FileType::Hashes,
             TAG_SIZES => FileType::Sizes,
+            TAG_GARBAGE_COLLECTION => FileType::GarbageCollection,
             _ => unreachable!(), // error at compile time
         }
     }
@@ -101,6 +105,7 @@ impl FileType {
             FileType::Hashes => Path::new("hashes.db"),
             FileType::BigStrings => Path::new("big_strings.db"),
             FileType::Sizes => Path::new("sizes.db"),
+            FileType::GarbageCollection => Path::new("gc.db"),
         }
     }
 }
@@ -113,6 +118,11 @@ pub struct File<const T: TaggedFile> {
     /// where the checksum was computed
     checksum_computed_until: u64,
     crc32: crc32fast::Hasher,
+    /// Checksum of the bytes appended since `Self::start_partial_checksum` was called.
+    ///
+    /// Used by the garbage collector of the persistent context, which modifies the
+    /// beginning of the file while we keep appending to it.
+    partial_crc32: Option<crc32fast::Hasher>,
     read_only: bool,
 }
 
@@ -194,6 +204,7 @@ impl<const T: TaggedFile> File<T> {
             file,
             offset,
             crc32,
+            partial_crc32: None,
             checksum_computed_until: 0,
             read_only,
         };
@@ -231,6 +242,7 @@ impl<const T: TaggedFile> File<T> {
             offset: self.offset,
             checksum_computed_until: self.checksum_computed_until,
             crc32: self.crc32.clone(),
+            partial_crc32: self.partial_crc32.clone(),
             read_only: self.read_only,
         })
     }
@@ -370,6 +382,9 @@ impl<const T: TaggedFile> File<T> {
         let bytes = bytes.as_ref();
 
         self.crc32.update(bytes);
+        if let Some(partial_crc32) = self.partial_crc32.as_mut() {
+            partial_crc32.update(bytes);
+        }
         self.offset += bytes.len() as u64;
         self.checksum_computed_until = self.offset;
         self.file.write_all(bytes)
@@ -379,6 +394,76 @@ impl<const T: TaggedFile> File<T> {
         self.crc32.clone().finalize()
     }
 
+    /// Returns the state of the checksum, to be combined with another one
+    pub fn checksum_hasher(&self) -> crc32fast::Hasher {
+        self.crc32.clone()
+    }
+
+    /// Forget the checksum, it will be recomputed from the beginning of the file
+    /// on the next call to `Self::update_checksum_until`
+    pub fn reset_checksum(&mut self) {
+        self.crc32 = crc32fast::Hasher::new();
+        self.checksum_computed_until = 0;
+    }
+
+    /// Start computing, in parallel of the main checksum, the checksum of
+    /// the bytes appended from now on
+    pub fn start_partial_checksum(&mut self) {
+        self.partial_crc32 = Some(crc32fast::Hasher::new());
+    }
+
+    pub fn cancel_partial_checksum(&mut self) {
+        self.partial_crc32 = None;
+    }
+
+    /// Replace the checksum of the bytes written before `Self::start_partial_checksum`
+    /// was called by `prefix`
+    ///
+    /// This is used when the beginning of the file has been modified (see `Self::punch_hole`)
+    pub fn replace_checksum_prefix(&mut self, mut prefix: crc32fast::Hasher) {
+        if let Some(partial_crc32) = self.partial_crc32.take() {
+            prefix.combine(&partial_crc32);
+        }
+        self.crc32 = prefix;
+    }
+
+    /// Deallocate the bytes in `[offset; offset + length[`, they will be read as zeros.
+    ///
+    /// The file length and the offsets of the other bytes remain the same.
+    #[cfg(target_os = "linux")]
+    pub fn punch_hole(&self, offset: AbsoluteOffset, length: u64) -> Result<(), io::Error> {
+        use std::os::unix::io::AsRawFd;
+
+        assert!(!self.read_only);
+
+        let result = unsafe {
+            libc::fallocate(
+                self.file.as_raw_fd(),
+                libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+                offset.as_u64() as libc::off_t,
+                length as libc::off_t,
+            )
+        };
+
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Deallocating a range of the file is supported on Linux only, we
+    /// just zero the bytes here.
+    #[cfg(not(target_os = "linux"))]
+    pub fn punch_hole(&self, offset: AbsoluteOffset, length: u64) -> Result<(), io::Error> {
+        use std::os::unix::prelude::FileExt;
+
+        assert!(!self.read_only);
+
+        let zeros = vec![0; length as usize];
+        self.file.write_all_at(&zeros, offset.as_u64())
+    }
+
     pub fn write_all_at(
         &mut self,
         bytes: impl AsRef<[u8]>,
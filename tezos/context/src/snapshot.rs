@@ -1,18 +1,21 @@
 // Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
 // SPDX-License-Identifier: MIT
 
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use std::{cell::RefCell, cmp::Ordering, rc::Rc, sync::Arc};
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use crypto::hash::ContextHash;
 use parking_lot::RwLock;
 
 use crate::{
+    hash::hash_inlined_blob,
     kv_store::persistent::{FileSizes, PersistentConfiguration},
     persistent::file::{File, TAG_SIZES},
     working_tree::{
-        storage::Storage, string_interner::StringInterner, working_tree::WorkingTree, Commit,
-        ObjectReference,
+        storage::{DirEntryId, DirectoryId, Storage},
+        string_interner::StringInterner,
+        working_tree::WorkingTree,
+        Commit, DirEntryKind, Object, ObjectReference,
     },
     ContextKeyValueStore, IndexApi, ObjectHash, Persistent, ShellContextApi, TezedgeContext,
     TezedgeIndex,
@@ -49,6 +52,23 @@ pub fn read_commit_tree(
     Error,
 > {
     let read_repo: Arc<RwLock<ContextKeyValueStore>> = Arc::new(RwLock::new(ctx));
+    read_commit_tree_from_repository(read_repo, checkout_context_hash)
+}
+
+/// Same as `read_commit_tree`, with a repository which is already shared
+pub fn read_commit_tree_from_repository(
+    read_repo: Arc<RwLock<ContextKeyValueStore>>,
+    checkout_context_hash: &ContextHash,
+) -> Result<
+    (
+        WorkingTree,
+        Storage,
+        StringInterner,
+        Option<ObjectHash>,
+        Commit,
+    ),
+    Error,
+> {
     let index = TezedgeIndex::new(Arc::clone(&read_repo), None);
     let context = index.checkout(checkout_context_hash)?.unwrap();
 
@@ -193,3 +213,298 @@ pub fn recompute_hashes(
 
     Ok(())
 }
+
+/// Kind of difference of a key between two commits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A key which differs between two commits.
+///
+/// When a whole directory is added or removed, only the directory is
+/// reported, not its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextDiff {
+    pub key: String,
+    pub kind: DiffKind,
+    pub is_directory: bool,
+    /// Value in the old commit, only set when the values are requested
+    pub old_value: Option<Vec<u8>>,
+    /// Value in the new commit, only set when the values are requested
+    pub new_value: Option<Vec<u8>>,
+}
+
+struct DiffEntry {
+    name: String,
+    dir_entry_id: DirEntryId,
+    kind: DirEntryKind,
+    hash: ObjectHash,
+}
+
+/// Returns the keys added, removed or modified between 2 commits.
+///
+/// The commits can be in different repositories.
+/// Both trees are walked in parallel, and subtrees with the same hash are skipped.
+pub fn diff_commits(
+    old_index: &TezedgeIndex,
+    old_context_hash: &ContextHash,
+    new_index: &TezedgeIndex,
+    new_context_hash: &ContextHash,
+    with_values: bool,
+) -> Result<Vec<ContextDiff>, Error> {
+    let (old_dir_id, old_root_hash) = commit_root(old_index, old_context_hash)?;
+    let (new_dir_id, new_root_hash) = commit_root(new_index, new_context_hash)?;
+
+    let mut diffs = Vec::new();
+
+    if old_root_hash != new_root_hash {
+        diff_directories(
+            (old_index, old_dir_id),
+            (new_index, new_dir_id),
+            &mut Vec::new(),
+            with_values,
+            &mut diffs,
+        )?;
+    }
+
+    Ok(diffs)
+}
+
+fn commit_root(
+    index: &TezedgeIndex,
+    context_hash: &ContextHash,
+) -> Result<(DirectoryId, ObjectHash), Error> {
+    let commit = index
+        .fetch_commit_from_context_hash(context_hash)?
+        .ok_or_else(|| anyhow!("Commit {} not found", context_hash.to_base58_check()))?;
+
+    let root_hash = index
+        .repository
+        .read()
+        .get_hash(commit.root_ref)?
+        .into_owned();
+
+    let mut storage = index.storage.borrow_mut();
+    let mut strings = index.get_string_interner()?;
+    let dir_id = index.get_directory(commit.root_ref, &mut storage, &mut strings)?;
+
+    Ok((dir_id, root_hash))
+}
+
+/// Returns the entries of the directory, sorted by name
+fn read_dir_entries(index: &TezedgeIndex, dir_id: DirectoryId) -> Result<Vec<DiffEntry>, Error> {
+    let mut storage = index.storage.borrow_mut();
+    let mut strings = index.get_string_interner()?;
+    let repository = index.repository.read();
+
+    let dir = storage.dir_to_vec_unsorted(dir_id, &mut strings, &*repository)?;
+    let mut entries = Vec::with_capacity(dir.len());
+
+    for (key_id, dir_entry_id) in dir {
+        let dir_entry = storage.get_dir_entry(dir_entry_id)?;
+
+        let hash = match dir_entry.get_inlined_blob(&storage) {
+            Some(blob) => hash_inlined_blob(blob)?,
+            None => repository.get_hash(dir_entry.get_reference())?.into_owned(),
+        };
+
+        entries.push(DiffEntry {
+            name: strings.get_str(key_id)?.to_string(),
+            dir_entry_id,
+            kind: dir_entry.dir_entry_kind(),
+            hash,
+        });
+    }
+
+    entries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(entries)
+}
+
+fn entry_object(index: &TezedgeIndex, entry: &DiffEntry) -> Result<Object, Error> {
+    let mut storage = index.storage.borrow_mut();
+    let mut strings = index.get_string_interner()?;
+
+    Ok(index.dir_entry_object(entry.dir_entry_id, &mut storage, &mut strings)?)
+}
+
+fn entry_value(index: &TezedgeIndex, entry: &DiffEntry) -> Result<Option<Vec<u8>>, Error> {
+    match entry_object(index, entry)? {
+        Object::Blob(blob_id) => Ok(Some(index.storage.borrow().get_blob(blob_id)?.to_vec())),
+        _ => Ok(None),
+    }
+}
+
+fn entry_directory(index: &TezedgeIndex, entry: &DiffEntry) -> Result<DirectoryId, Error> {
+    match entry_object(index, entry)? {
+        Object::Directory(dir_id) => Ok(dir_id),
+        _ => Err(anyhow!("{:?} is not a directory", entry.name)),
+    }
+}
+
+fn make_diff(
+    kind: DiffKind,
+    path: &[String],
+    old: Option<(&TezedgeIndex, &DiffEntry)>,
+    new: Option<(&TezedgeIndex, &DiffEntry)>,
+    with_values: bool,
+) -> Result<ContextDiff, Error> {
+    let entry = match new.or(old) {
+        Some((_, entry)) => entry,
+        None => return Err(anyhow!("A diff requires at least one entry")),
+    };
+
+    let value = |side: Option<(&TezedgeIndex, &DiffEntry)>| match side {
+        Some((index, entry)) if with_values => entry_value(index, entry),
+        _ => Ok(None),
+    };
+
+    let mut key = path.join("/");
+    if !key.is_empty() {
+        key.push('/');
+    }
+    key.push_str(&entry.name);
+
+    Ok(ContextDiff {
+        key,
+        kind,
+        is_directory: entry.kind == DirEntryKind::Directory,
+        old_value: value(old)?,
+        new_value: value(new)?,
+    })
+}
+
+fn diff_directories(
+    (old_index, old_dir_id): (&TezedgeIndex, DirectoryId),
+    (new_index, new_dir_id): (&TezedgeIndex, DirectoryId),
+    path: &mut Vec<String>,
+    with_values: bool,
+    diffs: &mut Vec<ContextDiff>,
+) -> Result<(), Error> {
+    let mut old_entries = read_dir_entries(old_index, old_dir_id)?
+        .into_iter()
+        .peekable();
+    let mut new_entries = read_dir_entries(new_index, new_dir_id)?
+        .into_iter()
+        .peekable();
+
+    loop {
+        let ordering = match (old_entries.peek(), new_entries.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(old), Some(new)) => old.name.cmp(&new.name),
+        };
+
+        match ordering {
+            Ordering::Less => {
+                let old = old_entries.next().unwrap();
+                let old = Some((old_index, &old));
+                diffs.push(make_diff(DiffKind::Removed, path, old, None, with_values)?);
+            }
+            Ordering::Greater => {
+                let new = new_entries.next().unwrap();
+                let new = Some((new_index, &new));
+                diffs.push(make_diff(DiffKind::Added, path, None, new, with_values)?);
+            }
+            Ordering::Equal => {
+                let old = old_entries.next().unwrap();
+                let new = new_entries.next().unwrap();
+
+                if old.hash == new.hash {
+                    continue;
+                }
+
+                match (old.kind, new.kind) {
+                    (DirEntryKind::Directory, DirEntryKind::Directory) => {
+                        let old_dir_id = entry_directory(old_index, &old)?;
+                        let new_dir_id = entry_directory(new_index, &new)?;
+
+                        path.push(old.name);
+                        diff_directories(
+                            (old_index, old_dir_id),
+                            (new_index, new_dir_id),
+                            path,
+                            with_values,
+                            diffs,
+                        )?;
+                        path.pop();
+                    }
+                    (DirEntryKind::Blob, DirEntryKind::Blob) => {
+                        let (old, new) = (Some((old_index, &old)), Some((new_index, &new)));
+                        diffs.push(make_diff(DiffKind::Modified, path, old, new, with_values)?);
+                    }
+                    _ => {
+                        // The key changed from a directory to a value, or the opposite
+                        let (old, new) = (Some((old_index, &old)), Some((new_index, &new)));
+                        diffs.push(make_diff(DiffKind::Removed, path, old, None, with_values)?);
+                        diffs.push(make_diff(DiffKind::Added, path, None, new, with_values)?);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tezos_context_api::{
+        ContextKvStoreConfiguration, TezosContextTezEdgeStorageConfiguration,
+        TezosContextTezedgeOnDiskBackendOptions,
+    };
+
+    use super::*;
+    use crate::{initializer::initialize_tezedge_context, ProtocolContextApi};
+
+    #[test]
+    fn test_diff_commits() {
+        let context = initialize_tezedge_context(&TezosContextTezEdgeStorageConfiguration {
+            backend: ContextKvStoreConfiguration::InMem(TezosContextTezedgeOnDiskBackendOptions {
+                base_path: "".to_string(),
+                startup_check: false,
+            }),
+            ipc_socket_path: None,
+        })
+        .unwrap();
+
+        let context = context.add(&["a", "b", "c"], &[1]).unwrap();
+        let context = context.add(&["a", "d"], &[2]).unwrap();
+        let context = context.add(&["e", "f"], &[3]).unwrap();
+        let context = context.add(&["g", "h"], &[4]).unwrap();
+        let old_hash = context.commit("".to_string(), "".to_string(), 0).unwrap();
+
+        let context = context.add(&["a", "b", "c"], &[10]).unwrap();
+        let context = context.delete(&["e"]).unwrap();
+        let context = context.add(&["i"], &[5]).unwrap();
+        let context = context.delete(&["g", "h"]).unwrap();
+        let context = context.add(&["g"], &[6]).unwrap();
+        let new_hash = context.commit("".to_string(), "".to_string(), 1).unwrap();
+
+        let index = &context.index;
+
+        let diffs = diff_commits(index, &old_hash, index, &old_hash, true).unwrap();
+        assert!(diffs.is_empty());
+
+        let diffs = diff_commits(index, &old_hash, index, &new_hash, true).unwrap();
+        let diffs: Vec<_> = diffs
+            .iter()
+            .map(|d| (d.key.as_str(), d.kind, d.old_value.clone(), d.new_value.clone()))
+            .collect();
+
+        assert_eq!(
+            diffs,
+            vec![
+                ("a/b/c", DiffKind::Modified, Some(vec![1]), Some(vec![10])),
+                ("e", DiffKind::Removed, None, None),
+                ("g", DiffKind::Removed, None, None),
+                ("g", DiffKind::Added, None, Some(vec![6])),
+                ("i", DiffKind::Added, None, Some(vec![5])),
+            ]
+        );
+    }
+}
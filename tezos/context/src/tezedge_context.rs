@@ -9,6 +9,7 @@ use std::{cell::RefCell, convert::TryInto, sync::Arc};
 use crypto::hash::ContextHash;
 use ocaml_interop::BoxRoot;
 use parking_lot::RwLock;
+use tezos_context_api::merkle_proof::MerkleProof;
 use tezos_context_api::StringDirectoryMap;
 use tezos_timing::{BlockMemoryUsage, ContextMemoryUsage};
 
@@ -16,6 +17,7 @@ use crate::working_tree::working_tree::FoldOrder;
 use crate::{
     hash::ObjectHash,
     kv_store::HashId,
+    merkle_proof::build_merkle_proof,
     persistent::{get_commit_hash, DBError},
     timings::send_statistics,
     working_tree::{
@@ -801,6 +803,26 @@ impl TezedgeIndex {
         )
         .map_err(ContextError::from)
     }
+
+    fn get_merkle_proof_impl(
+        &self,
+        context_hash: &ContextHash,
+        key: &ContextKey,
+    ) -> Result<Option<MerkleProof>, ContextError> {
+        let object_ref = {
+            let repository = self.repository.read();
+            match repository.get_context_hash(context_hash)? {
+                Some(hash_id) => hash_id,
+                None => {
+                    return Err(ContextError::UnknownContextHashError {
+                        context_hash: context_hash.to_base58_check(),
+                    })
+                }
+            }
+        };
+
+        build_merkle_proof(self, object_ref, key).map_err(ContextError::from)
+    }
 }
 
 impl IndexApi<TezedgeContext> for TezedgeIndex {
@@ -912,6 +934,15 @@ impl IndexApi<TezedgeContext> for TezedgeIndex {
         let index = self.with_deallocation();
         index.get_context_tree_by_prefix_impl(context_hash, prefix, depth)
     }
+
+    fn get_merkle_proof(
+        &self,
+        context_hash: &ContextHash,
+        key: &ContextKey,
+    ) -> Result<Option<MerkleProof>, ContextError> {
+        let index = self.with_deallocation();
+        index.get_merkle_proof_impl(context_hash, key)
+    }
 }
 
 /// Handle that represents a specific context (obtained from a checkout).
@@ -1549,6 +1549,51 @@ impl Storage {
         }
     }
 
+    /// Returns the inodes traversed to find `key` in the directory.
+    ///
+    /// When `dir_id` is a small directory, this returns only `dir_id`.
+    /// Otherwise, the last element is the small directory at the bottom of the
+    /// inodes which contains `key`, unless `key` doesn't exist.
+    pub fn dir_inodes_path(
+        &mut self,
+        dir_id: DirectoryId,
+        key: &str,
+        strings: &mut StringInterner,
+        repository: &ContextKeyValueStore,
+    ) -> Result<Vec<DirectoryOrInodeId>, StorageError> {
+        let mut ptr_id = match dir_id.get_inode_id() {
+            Some(inode_id) => DirectoryOrInodeId::Inode(inode_id),
+            None => return Ok(vec![DirectoryOrInodeId::Directory(dir_id)]),
+        };
+
+        let mut path = Vec::with_capacity(8);
+
+        while let DirectoryOrInodeId::Inode(inode_id) = ptr_id {
+            path.push(ptr_id);
+
+            let Inode {
+                depth, pointers, ..
+            } = self.get_inode(inode_id)?;
+
+            let index_at_depth = index_of_key(*depth as u32, key) as usize;
+
+            let thin_pointer_id = match pointers.get_thin_pointer_for_ptr(index_at_depth) {
+                Some(index) => index,
+                None => return Ok(path),
+            };
+
+            ptr_id = if let Some(ptr_id) = self.pointer_get_id(thin_pointer_id)? {
+                ptr_id
+            } else {
+                self.pointer_fetch(thin_pointer_id, repository, strings)?
+            };
+        }
+
+        path.push(ptr_id);
+
+        Ok(path)
+    }
+
     /// Move `new_dir` into `Self::directories` and return the `DirectoryId`.
     pub fn append_to_directories(
         &mut self,
@@ -2625,6 +2670,45 @@ mod tool {
             Ok(())
         }
 
+        /// Call `fun` with the `ObjectReference` of every directory entry and inode
+        /// pointer in `Self`
+        ///
+        /// Method used by the garbage collector of the persistent context, to find
+        /// the objects reachable from a commit.
+        pub fn for_each_reference<F>(&self, mut fun: F) -> Result<(), StorageError>
+        where
+            F: FnMut(ObjectReference),
+        {
+            for (_, dir_entry_id) in self.directories.iter() {
+                let dir_entry = self.get_dir_entry(*dir_entry_id)?;
+                fun(dir_entry.get_reference());
+            }
+
+            let pointers_data = self.pointers_data.borrow();
+
+            let mut with_pointer = |pointer: &FatPointer| -> Result<(), StorageError> {
+                if let Some(object_ref) = pointer.get_data()? {
+                    fun(object_ref);
+                } else if let Some(ptr_id) = pointer.ptr_id() {
+                    if let Some(object_ref) = pointers_data.get(&ptr_id.as_u64()) {
+                        fun(*object_ref);
+                    }
+                }
+                Ok(())
+            };
+
+            for index in 0..self.thin_pointers.len() {
+                let pointer = self.pointer_copy(ThinPointerId(index))?;
+                with_pointer(&pointer)?;
+            }
+
+            for pointer in self.fat_pointers.iter_values() {
+                with_pointer(pointer)?;
+            }
+
+            Ok(())
+        }
+
         /// Remove all `HashId` and `AbsoluteOffset` in `Self`
         /// This is used in order to recompute them
         ///
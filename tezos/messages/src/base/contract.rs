@@ -0,0 +1,178 @@
+// Copyright (c) SimpleStaking, Viable Systems and Tezedge Contributors
+// SPDX-License-Identifier: MIT
+
+//! Contracts as stored in the context.
+//!
+//! Contract's data is stored under the flat path `data/contracts/index/<hex of the contract id>`,
+//! one key per field, in all the supported protocols.
+
+use crypto::hash::ContractKt1Hash;
+use tezos_encoding::{
+    binary_reader::BinaryReaderError,
+    enc::BinWriter,
+    encoding::HasEncoding,
+    nom::NomReader,
+    types::{Mutez, Zarith},
+};
+
+use crate::p2p::binary_message::{BinaryRead, BinaryWrite};
+
+use super::signature_public_key::{SignaturePublicKey, SignaturePublicKeyHash};
+
+/// Contract id, as encoded in the context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractId {
+    Implicit(SignaturePublicKeyHash),
+    Originated(ContractKt1Hash),
+}
+
+impl ContractId {
+    /// Parses `tz1...`, `tz2...`, `tz3...` or `KT1...` contract address.
+    pub fn from_b58_check(contract_id: &str) -> Option<Self> {
+        if contract_id.starts_with("KT1") {
+            ContractKt1Hash::from_base58_check(contract_id)
+                .ok()
+                .map(Self::Originated)
+        } else {
+            SignaturePublicKeyHash::from_b58_hash(contract_id)
+                .ok()
+                .map(Self::Implicit)
+        }
+    }
+
+    /// Hex of the binary encoding of the contract id, as used in the context path:
+    /// implicit contracts are tagged with `0`, originated with `1` and padded.
+    pub fn to_index_hex(&self) -> Option<String> {
+        let mut bytes = vec![];
+        match self {
+            Self::Implicit(pkh) => {
+                bytes.push(0);
+                bytes.extend(pkh.as_bytes().ok()?);
+            }
+            Self::Originated(kt1) => {
+                bytes.push(1);
+                bytes.extend(kt1.as_ref());
+                bytes.push(0);
+            }
+        }
+        Some(hex::encode(bytes))
+    }
+
+    /// Context path the contract's fields are stored under.
+    pub fn context_path(&self) -> Option<Vec<String>> {
+        Some(vec![
+            "data".to_string(),
+            "contracts".to_string(),
+            "index".to_string(),
+            self.to_index_hex()?,
+        ])
+    }
+}
+
+/// Contract's field, stored under the [`ContractId::context_path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractField {
+    Balance,
+    Counter,
+    Manager,
+    Delegate,
+}
+
+impl ContractField {
+    /// Name of the context key the field is stored under.
+    pub fn context_key(&self) -> &'static str {
+        match self {
+            Self::Balance => "balance",
+            Self::Counter => "counter",
+            Self::Manager => "manager",
+            Self::Delegate => "delegate",
+        }
+    }
+
+    /// Fields which are stored only for the implicit contracts.
+    pub fn implicit_only(&self) -> bool {
+        matches!(self, Self::Counter | Self::Manager)
+    }
+}
+
+/// Manager of the implicit contract, the public key once it is revealed.
+#[derive(Debug, Clone, PartialEq, Eq, HasEncoding, NomReader, BinWriter)]
+pub enum ContractManager {
+    PublicKeyHash(SignaturePublicKeyHash),
+    PublicKey(SignaturePublicKey),
+}
+
+/// Decoded value of the [`ContractField`].
+#[derive(Debug, Clone)]
+pub enum ContractFieldValue {
+    Balance(Mutez),
+    Counter(Zarith),
+    Manager(ContractManager),
+    Delegate(SignaturePublicKeyHash),
+}
+
+impl ContractFieldValue {
+    /// Decodes the context value of the `field`.
+    pub fn from_bytes(field: ContractField, value: &[u8]) -> Result<Self, BinaryReaderError> {
+        Ok(match field {
+            ContractField::Balance => Self::Balance(Mutez::from_bytes(value)?),
+            ContractField::Counter => Self::Counter(Zarith::from_bytes(value)?),
+            ContractField::Manager => Self::Manager(ContractManager::from_bytes(value)?),
+            ContractField::Delegate => Self::Delegate(SignaturePublicKeyHash::from_bytes(value)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLIC_KEY: &str = "edpkuBknW28nW72KG6RoHtYW7p12T6GKc7nAbwYX5m8Wd9sDVC9yav";
+    const PUBLIC_KEY_HASH: &str = "tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx";
+
+    #[test]
+    fn test_contract_id_context_path() {
+        let implicit = ContractId::from_b58_check(PUBLIC_KEY_HASH).unwrap();
+        let path = implicit.context_path().unwrap();
+        assert_eq!(&path[..3], &["data", "contracts", "index"]);
+        // contract tag, curve tag and 20 bytes of the public key hash.
+        assert!(path[3].starts_with("0000"));
+        assert_eq!(path[3].len(), 2 * 22);
+
+        let originated =
+            ContractId::from_b58_check("KT1BEqzn5Wx8uJrZNvuS9DVHmLvG9td3fDLi").unwrap();
+        let index = originated.to_index_hex().unwrap();
+        // contract tag, 20 bytes of the contract hash and padding.
+        assert!(index.starts_with("01"));
+        assert!(index.ends_with("00"));
+        assert_eq!(index.len(), 2 * 22);
+
+        assert_eq!(ContractId::from_b58_check("tz1invalid"), None);
+    }
+
+    #[test]
+    fn test_contract_field_value_from_bytes() {
+        assert!(matches!(
+            ContractFieldValue::from_bytes(ContractField::Counter, &[0x2a]),
+            Ok(ContractFieldValue::Counter(counter)) if counter.0 == 42.into()
+        ));
+
+        let public_key = SignaturePublicKey::from_b58_hash(PUBLIC_KEY).unwrap();
+        let mut manager = vec![1];
+        manager.extend(public_key.as_bytes().unwrap());
+        assert!(matches!(
+            ContractFieldValue::from_bytes(ContractField::Manager, &manager),
+            Ok(ContractFieldValue::Manager(ContractManager::PublicKey(key))) if key == public_key
+        ));
+
+        let pkh = SignaturePublicKeyHash::from_b58_hash(PUBLIC_KEY_HASH).unwrap();
+        let mut manager = vec![0];
+        manager.extend(pkh.as_bytes().unwrap());
+        assert!(matches!(
+            ContractFieldValue::from_bytes(ContractField::Manager, &manager),
+            Ok(ContractFieldValue::Manager(ContractManager::PublicKeyHash(hash))) if hash == pkh
+        ));
+
+        assert!(ContractFieldValue::from_bytes(ContractField::Manager, &[2]).is_err());
+    }
+}
@@ -10,6 +10,7 @@ use thiserror::Error;
 use crypto::base58::FromBase58CheckError;
 use crypto::hash::{FromBytesError, TryFromPKError};
 
+pub mod contract;
 pub mod fitness_comparator;
 pub mod rpc_support;
 pub mod signature_public_key;
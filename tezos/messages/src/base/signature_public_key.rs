@@ -162,46 +162,67 @@ impl SignaturePublicKey {
     where
         B: AsRef<[u8]>,
     {
-        // TODO directly use `sodiumoxide::generichash` to avoid constructing single slice to be hashed
-        let bytes_ref = bytes.as_ref();
-        let bytes = match watermark {
-            SignatureWatermark::BlockHeader(chain_id) => {
-                let mut bytes = Vec::with_capacity(1 + ChainId::hash_size() + bytes_ref.len());
-                bytes.push(0x01);
-                bytes.extend_from_slice(chain_id.as_ref());
-                bytes.extend_from_slice(bytes_ref);
-                bytes
-            }
-            SignatureWatermark::Endorsement(chain_id) => {
-                let mut bytes = Vec::with_capacity(1 + ChainId::hash_size() + bytes_ref.len());
-                bytes.push(0x02);
-                bytes.extend_from_slice(chain_id.as_ref());
-                bytes.extend_from_slice(bytes_ref);
-                bytes
-            }
-            SignatureWatermark::GenericOperation => {
-                let mut bytes = Vec::with_capacity(1 + bytes_ref.len());
-                bytes.push(0x03);
-                bytes.extend_from_slice(bytes_ref);
-                bytes
-            }
-            SignatureWatermark::Custom(prefix) => {
-                let mut bytes = Vec::with_capacity(prefix.len() + bytes_ref.len());
-                bytes.extend_from_slice(prefix);
-                bytes.extend_from_slice(bytes_ref);
-                bytes
-            }
-            SignatureWatermark::None => bytes_ref.to_vec(),
-        };
-        let hash = blake2b::digest(&bytes, 32)
-            .map_err(|_| ())
-            .map_err(|_| CryptoError::InvalidMessage)?;
+        let hash = watermarked_hash(watermark, bytes.as_ref())?;
         match self {
             SignaturePublicKey::Ed25519(pk) => pk.verify_signature(signature, &hash),
             SignaturePublicKey::Secp256k1(pk) => pk.verify_signature(signature, &hash),
             SignaturePublicKey::P256(pk) => pk.verify_signature(signature, &hash),
         }
     }
+
+    /// Verifies signatures of the `(public key, signature, watermark, bytes)`
+    /// items one by one, results are in the same order as the `items`.
+    pub fn verify_each_signature<B>(
+        items: &[(&SignaturePublicKey, &Signature, &SignatureWatermark, B)],
+    ) -> Vec<Result<bool, CryptoError>>
+    where
+        B: AsRef<[u8]>,
+    {
+        items
+            .iter()
+            .map(|(public_key, signature, watermark, bytes)| {
+                public_key.verify_signature(signature, watermark, bytes)
+            })
+            .collect()
+    }
+}
+
+/// Hash of the `bytes` prefixed with the `watermark`, this is what is actually signed.
+fn watermarked_hash(
+    watermark: &SignatureWatermark,
+    bytes_ref: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    // TODO directly use `sodiumoxide::generichash` to avoid constructing single slice to be hashed
+    let bytes = match watermark {
+        SignatureWatermark::BlockHeader(chain_id) => {
+            let mut bytes = Vec::with_capacity(1 + ChainId::hash_size() + bytes_ref.len());
+            bytes.push(0x01);
+            bytes.extend_from_slice(chain_id.as_ref());
+            bytes.extend_from_slice(bytes_ref);
+            bytes
+        }
+        SignatureWatermark::Endorsement(chain_id) => {
+            let mut bytes = Vec::with_capacity(1 + ChainId::hash_size() + bytes_ref.len());
+            bytes.push(0x02);
+            bytes.extend_from_slice(chain_id.as_ref());
+            bytes.extend_from_slice(bytes_ref);
+            bytes
+        }
+        SignatureWatermark::GenericOperation => {
+            let mut bytes = Vec::with_capacity(1 + bytes_ref.len());
+            bytes.push(0x03);
+            bytes.extend_from_slice(bytes_ref);
+            bytes
+        }
+        SignatureWatermark::Custom(prefix) => {
+            let mut bytes = Vec::with_capacity(prefix.len() + bytes_ref.len());
+            bytes.extend_from_slice(prefix);
+            bytes.extend_from_slice(bytes_ref);
+            bytes
+        }
+        SignatureWatermark::None => bytes_ref.to_vec(),
+    };
+    blake2b::digest(&bytes, 32).map_err(|_| CryptoError::InvalidMessage)
 }
 
 impl serde::Serialize for SignaturePublicKey {
@@ -644,6 +665,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_verify_each_signature() {
+        use crypto::hash::Signature;
+
+        use super::SignatureWatermark;
+
+        let ed25519 = SignaturePublicKey::from_b58_hash(
+            "edpkuQ1C2vXZk7F2bQCjNTN99gRVsCdRKLQbfvxsRQ1pnh55R733Vk",
+        )
+        .unwrap();
+        let p256 = SignaturePublicKey::from_b58_hash(
+            "p2pk66G3vbHoscNYJdgQU72xSkrCWzoXNnFwroADcRTUtrHDvwnUNyW",
+        )
+        .unwrap();
+        let signature = Signature::from_base58_check("sigVyVvEX4WHAQrQwx2QvWuVUtgUQ3cGk4qRF7BbskrpaABqH9GNANesNFk55U4uLL6gmVrbLG6HBu8zbsTrkYLJGNcQupDa").unwrap();
+        let generic = SignatureWatermark::GenericOperation;
+        let bytes: [u8; 5] = [1, 2, 3, 4, 5];
+
+        let items = [
+            (&ed25519, &signature, &generic, &bytes[..]),
+            (&p256, &signature, &generic, &bytes[..]),
+            (&ed25519, &signature, &SignatureWatermark::None, &bytes[..]),
+            (&ed25519, &signature, &generic, &bytes[..4]),
+            (&ed25519, &signature, &generic, &bytes[..]),
+        ];
+        let results = SignaturePublicKey::verify_each_signature(&items)
+            .into_iter()
+            .map(|result| result.unwrap_or(false))
+            .collect::<Vec<_>>();
+        assert_eq!(results, vec![true, false, false, false, true]);
+
+        for ((public_key, signature, watermark, bytes), result) in items.iter().zip(results) {
+            assert_eq!(
+                public_key
+                    .verify_signature(signature, watermark, bytes)
+                    .unwrap_or(false),
+                result
+            );
+        }
+    }
+
     #[test]
     fn sig_as_json_is_base58check() {
         let pkss = [
@@ -304,6 +304,7 @@ into_peer_message!(GetCurrentHeadMessage, GetCurrentHead);
 into_peer_message!(CurrentHeadMessage, CurrentHead);
 into_peer_message!(GetOperationsForBlocksMessage, GetOperationsForBlocks);
 into_peer_message!(OperationsForBlocksMessage, OperationsForBlocks);
+into_peer_message!(DeactivateMessage, Deactivate);
 into_peer_message!(GetProtocolsMessage, GetProtocols);
 into_peer_message!(ProtocolMessage, Protocol);
 into_peer_message!(GetOperationsMessage, GetOperations);
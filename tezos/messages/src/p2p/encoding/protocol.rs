@@ -21,6 +21,16 @@ pub struct ProtocolMessage {
     protocol: Protocol,
 }
 
+impl ProtocolMessage {
+    pub fn new(protocol: Protocol) -> Self {
+        Self { protocol }
+    }
+
+    pub fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+}
+
 // -----------------------------------------------------------------------------------------------
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(
@@ -35,6 +45,28 @@ pub struct Component {
     implementation: String,
 }
 
+impl Component {
+    pub fn new(name: String, interface: Option<String>, implementation: String) -> Self {
+        Self {
+            name,
+            interface,
+            implementation,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    pub fn implementation(&self) -> &str {
+        &self.implementation
+    }
+}
+
 // -----------------------------------------------------------------------------------------------
 #[cfg_attr(feature = "fuzzing", derive(fuzzcheck::DefaultMutator))]
 #[derive(
@@ -47,6 +79,13 @@ pub struct Protocol {
 }
 
 impl Protocol {
+    pub fn new(expected_env_version: i16, components: Vec<Component>) -> Self {
+        Self {
+            expected_env_version,
+            components,
+        }
+    }
+
     pub fn expected_env_version(&self) -> i16 {
         self.expected_env_version
     }
@@ -65,3 +104,13 @@ pub struct GetProtocolsMessage {
     #[encoding(dynamic, list = "GET_PROTOCOLS_MAX_LENGTH")]
     get_protocols: Vec<ProtocolHash>,
 }
+
+impl GetProtocolsMessage {
+    pub fn new(get_protocols: Vec<ProtocolHash>) -> Self {
+        Self { get_protocols }
+    }
+
+    pub fn get_protocols(&self) -> &Vec<ProtocolHash> {
+        &self.get_protocols
+    }
+}
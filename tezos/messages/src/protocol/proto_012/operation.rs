@@ -271,6 +271,34 @@ pub enum Contents {
     SetDepositsLimit(SetDepositsLimitOperation),
 }
 
+impl Contents {
+    /// Fee and gas limit of the manager operation, `None` for other operations.
+    pub fn manager_fee_and_gas_limit(&self) -> Option<(&Mutez, &Mutez)> {
+        match self {
+            Contents::Reveal(op) => Some((&op.fee, &op.gas_limit)),
+            Contents::Transaction(op) => Some((&op.fee, &op.gas_limit)),
+            Contents::Origination(op) => Some((&op.fee, &op.gas_limit)),
+            Contents::Delegation(op) => Some((&op.fee, &op.gas_limit)),
+            Contents::RegisterGlobalConstant(op) => Some((&op.fee, &op.gas_limit)),
+            Contents::SetDepositsLimit(op) => Some((&op.fee, &op.gas_limit)),
+            _ => None,
+        }
+    }
+
+    /// Source and counter of the manager operation, `None` for other operations.
+    pub fn manager_source_and_counter(&self) -> Option<(&SignaturePublicKeyHash, &Mutez)> {
+        match self {
+            Contents::Reveal(op) => Some((&op.source, &op.counter)),
+            Contents::Transaction(op) => Some((&op.source, &op.counter)),
+            Contents::Origination(op) => Some((&op.source, &op.counter)),
+            Contents::Delegation(op) => Some((&op.source, &op.counter)),
+            Contents::RegisterGlobalConstant(op) => Some((&op.source, &op.counter)),
+            Contents::SetDepositsLimit(op) => Some((&op.source, &op.counter)),
+            _ => None,
+        }
+    }
+}
+
 /**
 Double_endorsement_evidence (tag 2)
 ===================================
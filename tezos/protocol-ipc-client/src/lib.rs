@@ -7,6 +7,7 @@
 pub mod slog_level_serde;
 
 use std::{
+    num::NonZeroU64,
     path::{Path, PathBuf},
     process::Stdio,
     sync::Arc,
@@ -28,7 +29,9 @@ use tokio::{
 
 use tezos_api::{environment::TezosEnvironmentConfiguration, ffi::*};
 use tezos_context_api::{
-    ContextKeyOwned, ContextValue, PatchContext, StringTreeObject, TezosContextStorageConfiguration,
+    merkle_proof::{MerkleProof, MerkleProofRequest, MerkleProofResponse},
+    ContextKeyOwned, ContextValue, PatchContext, StringTreeObject,
+    TezosContextStorageConfiguration,
 };
 
 /// Errors generated by `protocol_runner`.
@@ -69,6 +72,9 @@ pub struct ProtocolRunnerConfiguration {
     pub environment: TezosEnvironmentConfiguration,
     pub enable_testchain: bool,
     pub storage: TezosContextStorageConfiguration,
+    /// Number of cycles preserved by the garbage collector of the persistent context,
+    /// `None` disables it
+    pub context_gc_preserve_cycles: Option<NonZeroU64>,
     pub executable_path: PathBuf,
     #[serde(with = "slog_level_serde")]
     pub log_level: Level,
@@ -80,6 +86,7 @@ impl ProtocolRunnerConfiguration {
         environment: TezosEnvironmentConfiguration,
         enable_testchain: bool,
         storage: TezosContextStorageConfiguration,
+        context_gc_preserve_cycles: Option<NonZeroU64>,
         executable_path: PathBuf,
         log_level: Level,
     ) -> Self {
@@ -88,6 +95,7 @@ impl ProtocolRunnerConfiguration {
             environment,
             enable_testchain,
             storage,
+            context_gc_preserve_cycles,
             executable_path,
             log_level,
         }
@@ -127,6 +135,7 @@ pub struct ProtocolRunnerApi {
     status_watcher: Arc<tokio::sync::Mutex<tokio::sync::watch::Receiver<bool>>>,
     log: Logger,
     socket_path: PathBuf,
+    merkle_proof_socket_path: PathBuf,
     endpoint_name: String,
     configuration: ProtocolRunnerConfiguration,
 }
@@ -143,6 +152,7 @@ impl ProtocolRunnerApi {
             status_watcher: Arc::new(status_watcher.into()),
             log,
             socket_path: async_ipc::temp_sock(),
+            merkle_proof_socket_path: async_ipc::temp_sock(),
             endpoint_name: "writable-protocol-runner".to_owned(),
             configuration,
         }
@@ -166,13 +176,16 @@ impl ProtocolRunnerApi {
         let ProtocolRunnerConfiguration {
             executable_path,
             log_level,
+            context_gc_preserve_cycles,
             ..
         } = &self.configuration;
         let child = Self::spawn_process(
             executable_path,
             &self.socket_path,
+            &self.merkle_proof_socket_path,
             &self.endpoint_name,
             log_level,
+            *context_gc_preserve_cycles,
             self.log.clone(),
             &self.tokio_runtime,
         )?;
@@ -203,22 +216,32 @@ impl ProtocolRunnerApi {
     fn spawn_process(
         executable_path: &Path,
         socket_path: &Path,
+        merkle_proof_socket_path: &Path,
         endpoint_name: &str,
         log_level: &Level,
+        context_gc_preserve_cycles: Option<NonZeroU64>,
         log: Logger,
         tokio_runtime: &tokio::runtime::Handle,
     ) -> Result<tokio::process::Child, ProtocolRunnerError> {
         let _guard = tokio_runtime.enter();
-        let mut process = Command::new(executable_path)
+        let mut command = Command::new(executable_path);
+        command
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .arg("--socket-path")
             .arg(socket_path)
+            .arg("--merkle-proof-socket-path")
+            .arg(merkle_proof_socket_path)
             .arg("--endpoint")
             .arg(endpoint_name)
             .arg("--log-level")
-            .arg(log_level.as_str().to_lowercase())
-            .spawn()?;
+            .arg(log_level.as_str().to_lowercase());
+        if let Some(preserve_cycles) = context_gc_preserve_cycles {
+            command
+                .arg("--context-gc-preserve-cycles")
+                .arg(preserve_cycles.to_string());
+        }
+        let mut process = command.spawn()?;
 
         Self::log_subprocess_output(tokio_runtime, &mut process, log.clone());
 
@@ -306,6 +329,44 @@ impl ProtocolRunnerApi {
     pub fn readable_connection_sync(&self) -> Result<ProtocolRunnerConnection, IpcError> {
         tokio::task::block_in_place(|| self.tokio_runtime.block_on(self.readable_connection()))
     }
+
+    /// Builds the proof that `key` is in the context `context_hash`.
+    ///
+    /// Proofs are served by the protocol runner on their own socket, they don't go
+    /// through the OCaml IPC loop.
+    pub async fn get_context_merkle_proof(
+        &self,
+        context_hash: &ContextHash,
+        key: ContextKeyOwned,
+    ) -> Result<Option<MerkleProof>, ProtocolServiceError> {
+        self.wait_for_context_init()
+            .await
+            .map_err(|err| ProtocolError::ContextGetMerkleProofError {
+                reason: format!("Context was not initialized: {}", err),
+            })?;
+
+        let request = MerkleProofRequest {
+            context_hash: context_hash.clone(),
+            key,
+        };
+        let socket_path = self.merkle_proof_socket_path.clone();
+
+        let response = tokio::task::spawn_blocking(move || -> MerkleProofResponse {
+            let client: ipc::IpcClient<MerkleProofResponse, MerkleProofRequest> =
+                ipc::IpcClient::new(socket_path);
+            let (mut rx, mut tx) = client.connect().map_err(|err| err.to_string())?;
+            rx.set_read_timeout(Some(ProtocolRunnerConnection::DEFAULT_TIMEOUT_VERY_LONG))
+                .map_err(|err| err.to_string())?;
+
+            tx.send(&request).map_err(|err| err.to_string())?;
+            rx.receive().map_err(|err| err.to_string())?
+        })
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|response| response);
+
+        response.map_err(|reason| ProtocolError::ContextGetMerkleProofError { reason }.into())
+    }
 }
 
 pub struct ProtocolRunnerConnection {